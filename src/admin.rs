@@ -0,0 +1,266 @@
+//! Admin endpoints
+//!
+//! An [`Admin`] serves operational endpoints on their own address, separate
+//! from the application's routes:
+//!
+//! - `GET /routes`: the routes registered with [`Admin::route`]
+//! - `GET /config`: the configuration registered with [`Admin::config`]
+//! - `GET /connections`: the number of open connections, and the most that
+//!   have been open at once, as reported to [`Admin::connection_gauge`]
+//! - `GET /log-level`: the current log level
+//! - `PUT /log-level`: changes the log level to the one in the request body,
+//!   such as `debug`
+//!
+//! *This module requires the `"admin"` feature.*
+
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::{Method, StatusCode};
+use log::LevelFilter;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::filter::Filter;
+use crate::reject::Rejection;
+use crate::reply::{self, Reply};
+
+/// A set of admin endpoints.
+///
+/// # Example
+///
+/// ```
+/// use warp::admin::Admin;
+/// use warp::http::Method;
+/// use warp::Filter;
+///
+/// # async fn run() {
+/// let admin = Admin::new()
+///     .route(Method::GET, "/hello/{String}")
+///     .config("max_connections", 1024);
+///
+/// let hello = warp::path!("hello" / String).map(|name| format!("Hello, {}!", name));
+///
+/// let server = warp::serve(hello)
+///     .max_connections(1024)
+///     .connection_gauge(admin.connection_gauge());
+///
+/// // Only reachable from the local machine.
+/// tokio::spawn(admin.run(([127, 0, 0, 1], 9090)));
+/// server.run(([0, 0, 0, 0], 8080)).await;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Admin {
+    routes: Vec<Value>,
+    config: Map<String, Value>,
+    connections: Arc<Connections>,
+    log_level: Arc<Mutex<LevelFilter>>,
+    set_log_level: Arc<dyn Fn(LevelFilter) + Send + Sync>,
+}
+
+#[derive(Debug, Default)]
+struct Connections {
+    open: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Admin {
+    /// Creates admin endpoints, with no routes or configuration registered.
+    ///
+    /// Changing the log level sets the maximum level of the [`log`] crate,
+    /// unless [`Admin::on_log_level`] is used.
+    ///
+    /// [`log`]: https://docs.rs/log
+    pub fn new() -> Self {
+        Admin {
+            routes: Vec::new(),
+            config: Map::new(),
+            connections: Arc::default(),
+            log_level: Arc::new(Mutex::new(log::max_level())),
+            set_log_level: Arc::new(log::set_max_level),
+        }
+    }
+
+    /// Lists a route of the application at `GET /routes`.
+    pub fn route(mut self, method: Method, path: impl Into<String>) -> Self {
+        self.routes.push(json!({
+            "method": method.as_str(),
+            "path": path.into(),
+        }));
+        self
+    }
+
+    /// Lists the routes of a [`Router`](crate::Router) at `GET /routes`.
+    pub fn router(mut self, router: &crate::Router) -> Self {
+        for endpoint in router.routes() {
+            self.routes.push(json!({
+                "method": endpoint.method().as_str(),
+                "path": endpoint.template(),
+                "name": endpoint.name(),
+                "tags": endpoint.tags(),
+            }));
+        }
+        self
+    }
+
+    /// Shows a configuration value at `GET /config`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` can't be serialized to JSON.
+    pub fn config(mut self, name: impl Into<String>, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).expect("admin config value serializes to JSON");
+        self.config.insert(name.into(), value);
+        self
+    }
+
+    /// Calls `func` to change the log level, instead of setting the maximum
+    /// level of the `log` crate.
+    ///
+    /// This can be used to reload a `tracing` subscriber's filter.
+    pub fn on_log_level<F>(mut self, func: F) -> Self
+    where
+        F: Fn(LevelFilter) + Send + Sync + 'static,
+    {
+        self.set_log_level = Arc::new(func);
+        self
+    }
+
+    /// Returns a function to pass to [`Server::connection_gauge`], so that
+    /// the server's connections are counted at `GET /connections`.
+    ///
+    /// [`Server::connection_gauge`]: crate::Server::connection_gauge
+    pub fn connection_gauge(&self) -> impl Fn(usize) + Send + Sync + 'static {
+        let connections = self.connections.clone();
+        move |open| {
+            connections.open.store(open, Ordering::SeqCst);
+            connections.peak.fetch_max(open, Ordering::SeqCst);
+        }
+    }
+
+    /// Create a filter for the admin endpoints.
+    ///
+    /// This is what [`Admin::run`] serves, for serving the endpoints some
+    /// other way.
+    pub fn filter(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        let routes = Value::Array(self.routes.clone());
+        let list_routes = crate::path("routes")
+            .and(crate::path::end())
+            .and(crate::get())
+            .map(move || reply::json(&routes));
+
+        let config = Value::Object(self.config.clone());
+        let show_config = crate::path("config")
+            .and(crate::path::end())
+            .and(crate::get())
+            .map(move || reply::json(&config));
+
+        let connections = self.connections.clone();
+        let count_connections = crate::path("connections")
+            .and(crate::path::end())
+            .and(crate::get())
+            .map(move || {
+                reply::json(&json!({
+                    "open": connections.open.load(Ordering::SeqCst),
+                    "peak": connections.peak.load(Ordering::SeqCst),
+                }))
+            });
+
+        let level = self.log_level.clone();
+        let get_log_level = crate::get().map(move || {
+            let level = *level.lock().unwrap();
+            reply::with_status(log_level_json(level), StatusCode::OK)
+        });
+
+        let level = self.log_level.clone();
+        let set_level = self.set_log_level.clone();
+        let set_log_level = crate::put()
+            .and(crate::body::content_length_limit(64))
+            .and(crate::body::bytes())
+            .map(move |body: Bytes| {
+                let parsed = std::str::from_utf8(&body)
+                    .ok()
+                    .and_then(|s| LevelFilter::from_str(s.trim()).ok());
+                match parsed {
+                    Some(new_level) => {
+                        set_level(new_level);
+                        *level.lock().unwrap() = new_level;
+                        tracing::info!("log level changed to {}", new_level);
+                        reply::with_status(log_level_json(new_level), StatusCode::OK)
+                    }
+                    None => reply::with_status(
+                        reply::json(&json!({
+                            "error": "expected one of off, error, warn, info, debug, trace",
+                        })),
+                        StatusCode::BAD_REQUEST,
+                    ),
+                }
+            });
+
+        let log_level = crate::path("log-level")
+            .and(crate::path::end())
+            .and(get_log_level.or(set_log_level).unify());
+
+        list_routes
+            .or(show_config)
+            .unify()
+            .or(count_connections)
+            .unify()
+            .map(|json| reply::with_status(json, StatusCode::OK))
+            .or(log_level)
+            .unify()
+    }
+
+    /// Bind the admin endpoints to `addr`, returning the bound address and a
+    /// future that serves them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if unable to bind to the provided address.
+    pub fn bind_ephemeral(
+        &self,
+        addr: impl Into<SocketAddr>,
+    ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
+        crate::serve(self.filter()).bind_ephemeral(addr)
+    }
+
+    /// Serve the admin endpoints on `addr`, forever.
+    ///
+    /// # Panics
+    ///
+    /// Panics if unable to bind to the provided address.
+    pub fn run(&self, addr: impl Into<SocketAddr>) -> impl Future<Output = ()> + 'static {
+        let (addr, server) = self.bind_ephemeral(addr);
+        tracing::info!("admin listening on http://{}", addr);
+        server
+    }
+}
+
+fn log_level_json(level: LevelFilter) -> reply::Json {
+    reply::json(&json!({
+        "level": level.to_string().to_lowercase(),
+    }))
+}
+
+impl Default for Admin {
+    fn default() -> Self {
+        Admin::new()
+    }
+}
+
+impl fmt::Debug for Admin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Admin")
+            .field("routes", &self.routes)
+            .field("config", &self.config)
+            .field("connections", &self.connections)
+            .field("log_level", &self.log_level)
+            .finish()
+    }
+}
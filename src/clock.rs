@@ -0,0 +1,48 @@
+use std::cell::Cell;
+use std::time::{Duration, SystemTime};
+
+use tokio::time::Instant;
+
+thread_local! {
+    // The time on this thread, while paused by `warp::test::pause_time`.
+    static PAUSED: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// The current time, for filters whose behavior depends on elapsed time,
+/// such as expiring entries or refilling quotas.
+///
+/// Tests can pause and advance it, with `warp::test::pause_time` and
+/// `warp::test::advance`.
+pub(crate) fn now() -> Instant {
+    PAUSED.with(Cell::get).unwrap_or_else(Instant::now)
+}
+
+/// The current wall-clock time, moving with [`now`], for expiry times that
+/// outlive the process, such as in files.
+pub(crate) fn system_now() -> SystemTime {
+    let (now, real) = (now(), Instant::now());
+    if now >= real {
+        SystemTime::now() + (now - real)
+    } else {
+        SystemTime::now() - (real - now)
+    }
+}
+
+pub(crate) fn pause() {
+    PAUSED.with(|paused| {
+        if paused.get().is_none() {
+            paused.set(Some(Instant::now()));
+        }
+    });
+}
+
+pub(crate) fn resume() {
+    PAUSED.with(|paused| paused.set(None));
+}
+
+pub(crate) fn advance(duration: Duration) {
+    PAUSED.with(|paused| {
+        let now = paused.get().expect("time must be paused to advance it");
+        paused.set(Some(now + duration));
+    });
+}
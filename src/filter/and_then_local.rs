@@ -0,0 +1,101 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{ready, TryFuture};
+use pin_project::pin_project;
+use tokio::task::JoinHandle;
+
+use super::{Filter, FilterBase, Func, Internal};
+use crate::reject::CombineRejection;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AndThenLocal<T, F> {
+    pub(super) filter: T,
+    pub(super) callback: F,
+}
+
+impl<T, F> FilterBase for AndThenLocal<T, F>
+where
+    T: Filter,
+    F: Func<T::Extract> + Clone + Send,
+    F::Output: TryFuture + 'static,
+    <F::Output as TryFuture>::Ok: Send + 'static,
+    <F::Output as TryFuture>::Error: CombineRejection<T::Error> + Send + 'static,
+{
+    type Extract = (<F::Output as TryFuture>::Ok,);
+    type Error = <<F::Output as TryFuture>::Error as CombineRejection<T::Error>>::One;
+    type Future = AndThenLocalFuture<T, F>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        AndThenLocalFuture {
+            state: State::First(self.filter.filter(Internal), self.callback.clone()),
+        }
+    }
+}
+
+type Output<F> = Result<<F as TryFuture>::Ok, <F as TryFuture>::Error>;
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct AndThenLocalFuture<T, F>
+where
+    T: Filter,
+    F: Func<T::Extract>,
+    F::Output: TryFuture,
+{
+    #[pin]
+    state: State<T::Future, F, JoinHandle<Output<F::Output>>>,
+}
+
+#[pin_project(project = StateProj)]
+enum State<T, F, H> {
+    First(#[pin] T, F),
+    Second(#[pin] H),
+    Done,
+}
+
+impl<T, F> Future for AndThenLocalFuture<T, F>
+where
+    T: Filter,
+    F: Func<T::Extract>,
+    F::Output: TryFuture + 'static,
+    <F::Output as TryFuture>::Ok: Send + 'static,
+    <F::Output as TryFuture>::Error: CombineRejection<T::Error> + Send + 'static,
+{
+    type Output = Result<
+        (<F::Output as TryFuture>::Ok,),
+        <<F::Output as TryFuture>::Error as CombineRejection<T::Error>>::One,
+    >;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.project().state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::First(first, second) => {
+                    let ex1 = ready!(first.try_poll(cx))?;
+                    let fut2 = second.call(ex1);
+                    // The handler's future may not be `Send`, so it is
+                    // driven by the `LocalSet` of the current thread, and
+                    // only its output crosses back.
+                    let handle = tokio::task::spawn_local(async move {
+                        futures::pin_mut!(fut2);
+                        futures::future::poll_fn(|cx| fut2.as_mut().try_poll(cx)).await
+                    });
+                    state.set(State::Second(handle));
+                }
+                StateProj::Second(second) => {
+                    let ex3 = match ready!(second.poll(cx)) {
+                        Ok(Ok(item)) => Ok((item,)),
+                        Ok(Err(err)) => Err(From::from(err)),
+                        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                        Err(_) => panic!("local handler was cancelled"),
+                    };
+                    state.set(State::Done);
+                    return Poll::Ready(ex3);
+                }
+                StateProj::Done => panic!("polled after complete"),
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::{Filter, FilterBase, Internal};
+use crate::clock;
+
+pub struct Cached<T: FilterBase> {
+    pub(super) filter: Arc<T>,
+    pub(super) ttl: Option<Duration>,
+    pub(super) value: Arc<Slot<T::Extract>>,
+}
+
+// The last extracted value, and when it was extracted. Locked while the
+// filter runs, so concurrent requests wait for it instead of running it too.
+type Slot<E> = Mutex<Option<(Instant, E)>>;
+
+impl<T: FilterBase> Cached<T> {
+    pub(super) fn new(filter: T, ttl: Option<Duration>) -> Cached<T> {
+        Cached {
+            filter: Arc::new(filter),
+            ttl,
+            value: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl<T: FilterBase> Clone for Cached<T> {
+    fn clone(&self) -> Self {
+        Cached {
+            filter: self.filter.clone(),
+            ttl: self.ttl,
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: FilterBase + fmt::Debug> fmt::Debug for Cached<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cached")
+            .field("filter", &self.filter)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+type CachedFuture<T> = Pin<
+    Box<dyn Future<Output = Result<<T as FilterBase>::Extract, <T as FilterBase>::Error>> + Send>,
+>;
+
+impl<T> FilterBase for Cached<T>
+where
+    T: Filter + Send + Sync + 'static,
+    T::Extract: Clone + Send,
+{
+    type Extract = T::Extract;
+    type Error = T::Error;
+    type Future = CachedFuture<T>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let filter = self.filter.clone();
+        let ttl = self.ttl;
+        let value = self.value.clone();
+        Box::pin(async move {
+            let mut value = value.lock().await;
+            if let Some((at, ref ex)) = *value {
+                if ttl.map_or(true, |ttl| clock::now() - at < ttl) {
+                    return Ok(ex.clone());
+                }
+            }
+            let ex = filter.filter(Internal).await?;
+            *value = Some((clock::now(), ex.clone()));
+            Ok(ex)
+        })
+    }
+}
@@ -6,7 +6,7 @@ use futures::TryFuture;
 use pin_project::pin_project;
 
 use super::{Filter, FilterBase, Internal};
-use crate::reject::IsReject;
+use crate::reject::Rejection;
 
 #[derive(Clone, Copy, Debug)]
 pub struct MapErr<T, F> {
@@ -18,10 +18,10 @@ impl<T, F, E> FilterBase for MapErr<T, F>
 where
     T: Filter,
     F: Fn(T::Error) -> E + Clone + Send,
-    E: IsReject,
+    E: Into<Rejection>,
 {
     type Extract = T::Extract;
-    type Error = E;
+    type Error = Rejection;
     type Future = MapErrFuture<T, F>;
     #[inline]
     fn filter(&self, _: Internal) -> Self::Future {
@@ -44,8 +44,9 @@ impl<T, F, E> Future for MapErrFuture<T, F>
 where
     T: Filter,
     F: Fn(T::Error) -> E,
+    E: Into<Rejection>,
 {
-    type Output = Result<T::Extract, E>;
+    type Output = Result<T::Extract, Rejection>;
 
     #[inline]
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
@@ -53,6 +54,6 @@ where
             .project()
             .extract
             .try_poll(cx)
-            .map_err(|err| (self.callback)(err))
+            .map_err(|err| (self.callback)(err).into())
     }
 }
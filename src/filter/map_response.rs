@@ -0,0 +1,62 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::reply::{Reply, Response};
+
+#[derive(Clone, Copy, Debug)]
+pub struct MapResponse<T, F> {
+    pub(super) filter: T,
+    pub(super) callback: F,
+}
+
+impl<T, F> FilterBase for MapResponse<T, F>
+where
+    T: Filter,
+    T::Extract: Reply,
+    F: Fn(Response) -> Response + Clone + Send,
+{
+    type Extract = (Response,);
+    type Error = T::Error;
+    type Future = MapResponseFuture<T, F>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        MapResponseFuture {
+            extract: self.filter.filter(Internal),
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct MapResponseFuture<T: Filter, F> {
+    #[pin]
+    extract: T::Future,
+    callback: F,
+}
+
+impl<T, F> Future for MapResponseFuture<T, F>
+where
+    T: Filter,
+    T::Extract: Reply,
+    F: Fn(Response) -> Response,
+{
+    type Output = Result<(Response,), T::Error>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let pin = self.project();
+        match ready!(pin.extract.try_poll(cx)) {
+            Ok(ex) => {
+                let res = (pin.callback)(ex.into_response());
+                Poll::Ready(Ok((res,)))
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
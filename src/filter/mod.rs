@@ -1,13 +1,19 @@
 mod and;
 mod and_then;
+mod and_then_local;
 mod boxed;
+mod cached;
 mod map;
 mod map_err;
+mod map_response;
 mod or;
 mod or_else;
 mod recover;
+mod recover_with;
 pub(crate) mod service;
+mod then;
 mod unify;
+mod untuple;
 mod untuple_one;
 mod wrap;
 
@@ -16,22 +22,28 @@ use std::pin::Pin;
 
 use futures::{future, TryFuture, TryFutureExt};
 
-pub(crate) use crate::generic::{one, Combine, Either, Func, One, Tuple};
+pub(crate) use crate::generic::{one, Combine, Either, Flatten, Func, One, Tuple};
 use crate::reject::{CombineRejection, IsReject, Rejection};
+use crate::reply::{Reply, Response};
 use crate::route::{self, Route};
 
 pub(crate) use self::and::And;
 use self::and_then::AndThen;
+use self::and_then_local::AndThenLocal;
 pub use self::boxed::BoxedFilter;
+use self::cached::Cached;
 pub(crate) use self::map::Map;
-pub(crate) use self::map_err::MapErr;
+use self::map_err::MapErr;
+pub(crate) use self::map_response::MapResponse;
 pub(crate) use self::or::Or;
 use self::or_else::OrElse;
 use self::recover::Recover;
+use self::recover_with::RecoverWith;
+use self::then::Then;
 use self::unify::Unify;
+use self::untuple::Untuple;
 use self::untuple_one::UntupleOne;
-pub use self::wrap::wrap_fn;
-pub(crate) use self::wrap::{Wrap, WrapSealed};
+pub use self::wrap::{wrap_fn, Wrap, WrapFn};
 
 // A crate-private base trait, allowing the actual `filter` method to change
 // signatures without it being a breaking change.
@@ -41,18 +53,6 @@ pub trait FilterBase {
     type Future: Future<Output = Result<Self::Extract, Self::Error>> + Send;
 
     fn filter(&self, internal: Internal) -> Self::Future;
-
-    fn map_err<F, E>(self, _internal: Internal, fun: F) -> MapErr<Self, F>
-    where
-        Self: Sized,
-        F: Fn(Self::Error) -> E + Clone,
-        E: ::std::fmt::Debug + Send,
-    {
-        MapErr {
-            filter: self,
-            callback: fun,
-        }
-    }
 }
 
 // A crate-private argument to prevent users from calling methods on
@@ -198,6 +198,39 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Composes this `Filter` with a function receiving the reply it
+    /// extracts, once converted into a `Response`.
+    ///
+    /// This allows post-processing the replies of many routes at once, such
+    /// as to add headers, rewrite the status, or transform the body. The
+    /// [`reply::with::map_response`](crate::reply::with::map_response)
+    /// wrapper does the same, with [`Filter::with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::http::StatusCode;
+    /// use warp::Filter;
+    ///
+    /// let route = warp::path("teapot")
+    ///     .map(|| "short and stout")
+    ///     .map_response(|mut res: warp::reply::Response| {
+    ///         *res.status_mut() = StatusCode::IM_A_TEAPOT;
+    ///         res
+    ///     });
+    /// ```
+    fn map_response<F>(self, fun: F) -> MapResponse<Self, F>
+    where
+        Self: Sized,
+        Self::Extract: Reply,
+        F: Fn(Response) -> Response + Clone,
+    {
+        MapResponse {
+            filter: self,
+            callback: fun,
+        }
+    }
+
     /// Composes this `Filter` with a function receiving the extracted value.
     ///
     /// The function should return some `TryFuture` type.
@@ -232,6 +265,106 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Composes this `Filter` with an async function receiving the extracted
+    /// value, which can't reject.
+    ///
+    /// This is like [`Filter::and_then`], for functions that always
+    /// succeed: the output of the returned `Future` is extracted as is,
+    /// instead of being a `Result`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// // Greet after `/:id`
+    /// warp::path::param().then(|id: u64| async move {
+    ///     format!("Hello #{}", id)
+    /// });
+    /// ```
+    fn then<F>(self, fun: F) -> Then<Self, F>
+    where
+        Self: Sized,
+        F: Func<Self::Extract> + Clone,
+        F::Output: Future + Send,
+    {
+        Then {
+            filter: self,
+            callback: fun,
+        }
+    }
+
+    /// Composes this `Filter` with a function whose future isn't `Send`.
+    ///
+    /// This is like [`Filter::and_then`], but the returned future is run on
+    /// the current thread's [`LocalSet`](tokio::task::LocalSet), so it may
+    /// hold things like `Rc` or thread-bound handles across `.await`s. Only
+    /// the result has to be `Send`.
+    ///
+    /// # Panics
+    ///
+    /// The filter panics if it isn't running within a `LocalSet`, such as
+    /// when served by [`Server::run_local`](crate::Server::run_local).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use warp::Filter;
+    ///
+    /// warp::path::param().and_then_local(|id: u64| async move {
+    ///     let shared = Rc::new(id);
+    ///     tokio::task::yield_now().await;
+    ///     Ok::<_, warp::Rejection>(format!("Hello #{}", shared))
+    /// });
+    /// ```
+    fn and_then_local<F>(self, fun: F) -> AndThenLocal<Self, F>
+    where
+        Self: Sized,
+        F: Func<Self::Extract> + Clone,
+        F::Output: TryFuture + 'static,
+        <F::Output as TryFuture>::Ok: Send + 'static,
+        <F::Output as TryFuture>::Error: CombineRejection<Self::Error> + Send + 'static,
+    {
+        AndThenLocal {
+            filter: self,
+            callback: fun,
+        }
+    }
+
+    /// Composes this `Filter` with a function receiving its error, and
+    /// returning another error to reject with.
+    ///
+    /// This lets a part of the filters translate their rejections into an
+    /// error of the domain, where there's enough context to do so. The new
+    /// error can be a [`Rejection`], or any type implementing
+    /// [`Reject`](crate::reject::Reject), found again later with
+    /// [`Rejection::find`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// #[derive(Debug)]
+    /// struct MissingApiKey;
+    ///
+    /// impl warp::reject::Reject for MissingApiKey {}
+    ///
+    /// let api_key = warp::header::<String>("x-api-key").map_err(|_| MissingApiKey);
+    /// ```
+    fn map_err<F, E>(self, fun: F) -> MapErr<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Error) -> E + Clone,
+        E: Into<Rejection>,
+    {
+        MapErr {
+            filter: self,
+            callback: fun,
+        }
+    }
+
     /// Compose this `Filter` with a function receiving an error.
     ///
     /// The function should return some `TryFuture` type yielding the
@@ -269,6 +402,99 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Composes this `Filter` with a function replying to rejections caused
+    /// by an `E`.
+    ///
+    /// This is like [`Filter::recover`], only for the rejections that
+    /// [contain](Rejection::find) an `E`, such as a custom error of the
+    /// domain. Other rejections are left as they are, so that other filters
+    /// can be tried.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::http::StatusCode;
+    /// use warp::Filter;
+    ///
+    /// #[derive(Debug)]
+    /// struct NotAdmin;
+    ///
+    /// impl warp::reject::Reject for NotAdmin {}
+    ///
+    /// let admin = warp::path("admin")
+    ///     .and_then(|| async { Err::<&str, _>(warp::reject::custom(NotAdmin)) })
+    ///     .recover_with(|_: &NotAdmin| {
+    ///         warp::reply::with_status("admins only", StatusCode::FORBIDDEN)
+    ///     });
+    /// ```
+    fn recover_with<E, F, R>(self, fun: F) -> RecoverWith<Self, F, E>
+    where
+        Self: Filter<Error = Rejection> + Sized,
+        F: Fn(&E) -> R + Clone,
+        E: 'static,
+        R: Send,
+    {
+        RecoverWith {
+            filter: self,
+            callback: fun,
+            _cause: std::marker::PhantomData,
+        }
+    }
+
+    /// Runs this filter once, and extracts clones of its value for every
+    /// request after.
+    ///
+    /// This is for expensive filters that don't depend on the request, like
+    /// loading a configuration. Concurrent requests wait for the first run
+    /// to finish, instead of running the filter too. If it rejects, the next
+    /// request runs it again.
+    ///
+    /// Clones of the returned filter share the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use warp::Filter;
+    ///
+    /// async fn load_config() -> Result<String, Infallible> {
+    ///     Ok("from disk".to_owned())
+    /// }
+    ///
+    /// let config = warp::any().and_then(load_config).shared();
+    ///
+    /// let route = warp::path("config").and(config).map(|config: String| config);
+    /// ```
+    fn shared(self) -> Cached<Self>
+    where
+        Self: Sized,
+        Self::Extract: Clone,
+    {
+        Cached::new(self, None)
+    }
+
+    /// Like [`Filter::shared`], but runs this filter again for the first
+    /// request after `ttl` has passed since its value was extracted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use warp::Filter;
+    ///
+    /// // Refresh the signing keys every 10 minutes.
+    /// let keys = warp::any()
+    ///     .map(|| vec!["key-1".to_owned()])
+    ///     .cached(Duration::from_secs(600));
+    /// ```
+    fn cached(self, ttl: std::time::Duration) -> Cached<Self>
+    where
+        Self: Sized,
+        Self::Extract: Clone,
+    {
+        Cached::new(self, Some(ttl))
+    }
+
     /// Unifies the extracted value of `Filter`s composed with `or`.
     ///
     /// When a `Filter` extracts some `Either<T, T>`, where both sides
@@ -342,6 +568,33 @@ pub trait Filter: FilterBase {
         UntupleOne { filter: self }
     }
 
+    /// Flatten the tuples extracted by this filter into one tuple.
+    ///
+    /// Where [`untuple_one`](Filter::untuple_one) only unwraps a `(T,)`,
+    /// this joins up to 4 tuples, such as the `((A,), (B, C))` a filter
+    /// extracting tuples can end up with, into `(A, B, C)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let route = warp::any()
+    ///     .map(|| ((1u32,), ("warp", true)))
+    ///     .untuple_one()
+    ///     .untuple()
+    ///     .map(|id: u32, name: &str, enabled: bool| {
+    ///         format!("{} {} {}", id, name, enabled)
+    ///     });
+    /// ```
+    fn untuple(self) -> Untuple<Self>
+    where
+        Self: Sized,
+        Self::Extract: Flatten,
+    {
+        Untuple { filter: self }
+    }
+
     /// Wraps the current filter with some wrapper.
     ///
     /// The wrapper may do some preparation work before starting this filter,
@@ -30,10 +30,11 @@ where
     type Future = EitherFuture<T, U>;
 
     fn filter(&self, _: Internal) -> Self::Future {
-        let idx = route::with(|route| route.matched_path_index());
+        let (idx, path_matched) =
+            route::with(|route| (route.matched_path_index(), route.path_matched()));
         EitherFuture {
             state: State::First(self.first.filter(Internal), self.second.clone()),
-            original_path_index: PathIndex(idx),
+            original_path_index: PathIndex(idx, path_matched),
         }
     }
 }
@@ -54,11 +55,14 @@ enum State<T: Filter, U: Filter> {
 }
 
 #[derive(Copy, Clone)]
-struct PathIndex(usize);
+struct PathIndex(usize, bool);
 
 impl PathIndex {
     fn reset_path(&self) {
-        route::with(|route| route.reset_matched_path_index(self.0));
+        route::with(|route| {
+            route.reset_matched_path_index(self.0);
+            route.set_path_matched(self.1);
+        });
     }
 }
 
@@ -28,10 +28,11 @@ where
     type Future = RecoverFuture<T, F>;
     #[inline]
     fn filter(&self, _: Internal) -> Self::Future {
-        let idx = route::with(|route| route.matched_path_index());
+        let (idx, path_matched) =
+            route::with(|route| (route.matched_path_index(), route.path_matched()));
         RecoverFuture {
             state: State::First(self.filter.filter(Internal), self.callback.clone()),
-            original_path_index: PathIndex(idx),
+            original_path_index: PathIndex(idx, path_matched),
         }
     }
 }
@@ -64,11 +65,14 @@ where
 }
 
 #[derive(Copy, Clone)]
-struct PathIndex(usize);
+struct PathIndex(usize, bool);
 
 impl PathIndex {
     fn reset_path(&self) {
-        route::with(|route| route.reset_matched_path_index(self.0));
+        route::with(|route| {
+            route.reset_matched_path_index(self.0);
+            route.set_path_matched(self.1);
+        });
     }
 }
 
@@ -0,0 +1,95 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::generic::Either;
+use crate::reject::Rejection;
+use crate::route;
+
+#[derive(Debug)]
+pub struct RecoverWith<T, F, E> {
+    pub(super) filter: T,
+    pub(super) callback: F,
+    // `fn` so that the filter is `Send` and `Sync` whatever `E` is.
+    pub(super) _cause: PhantomData<fn(&E)>,
+}
+
+impl<T: Clone, F: Clone, E> Clone for RecoverWith<T, F, E> {
+    fn clone(&self) -> Self {
+        RecoverWith {
+            filter: self.filter.clone(),
+            callback: self.callback.clone(),
+            _cause: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, F: Copy, E> Copy for RecoverWith<T, F, E> {}
+
+impl<T, F, E, R> FilterBase for RecoverWith<T, F, E>
+where
+    T: Filter<Error = Rejection>,
+    F: Fn(&E) -> R + Clone + Send,
+    E: 'static,
+    R: Send,
+{
+    type Extract = (Either<T::Extract, (R,)>,);
+    type Error = Rejection;
+    type Future = RecoverWithFuture<T, F, E>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        let (idx, path_matched) =
+            route::with(|route| (route.matched_path_index(), route.path_matched()));
+        RecoverWithFuture {
+            future: self.filter.filter(Internal),
+            callback: self.callback.clone(),
+            original_path_index: idx,
+            original_path_matched: path_matched,
+            _cause: PhantomData,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct RecoverWithFuture<T: Filter, F, E> {
+    #[pin]
+    future: T::Future,
+    callback: F,
+    original_path_index: usize,
+    original_path_matched: bool,
+    _cause: PhantomData<fn(&E)>,
+}
+
+impl<T, F, E, R> Future for RecoverWithFuture<T, F, E>
+where
+    T: Filter<Error = Rejection>,
+    F: Fn(&E) -> R,
+    E: 'static,
+{
+    type Output = Result<(Either<T::Extract, (R,)>,), Rejection>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let pin = self.project();
+        let err = match ready!(pin.future.try_poll(cx)) {
+            Ok(ex) => return Poll::Ready(Ok((Either::A(ex),))),
+            Err(err) => err,
+        };
+        match err.find::<E>() {
+            Some(cause) => {
+                let (idx, path_matched) = (*pin.original_path_index, *pin.original_path_matched);
+                route::with(|route| {
+                    route.reset_matched_path_index(idx);
+                    route.set_path_matched(path_matched);
+                });
+                Poll::Ready(Ok((Either::B(((pin.callback)(cause),)),)))
+            }
+            None => Poll::Ready(Err(err)),
+        }
+    }
+}
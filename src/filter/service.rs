@@ -5,12 +5,16 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures::future::TryFuture;
+use http::StatusCode;
 use hyper::service::Service;
 use pin_project::pin_project;
 
-use crate::reject::IsReject;
+use crate::filters::catch_panic::{catching, Panic};
+use crate::filters::wrap::{BoxError, ServiceFilter};
+use crate::reject::{IsReject, Rejection};
 use crate::reply::{Reply, Response};
 use crate::route::{self, Route};
+use crate::server::{ErrorHook, UnhandledError};
 use crate::{Filter, Request};
 
 /// Convert a `Filter` into a `Service`.
@@ -58,6 +62,49 @@ where
     FilteredService { filter }
 }
 
+/// Convert a [`Service`][Service] into a `Filter`, extracting its response.
+///
+/// The service is handed the whole request, so existing `tower` or `hyper`
+/// services can be mounted within a tree of filters, such as while moving
+/// them over to warp. It sees the full path of the request, including any
+/// part already matched by path filters before it.
+///
+/// The extensions and body of the request are moved into the service, so
+/// filters tried after it, with `or`, don't see them. Errors of the service
+/// reject the request with a [`ServiceError`](crate::wrap::ServiceError).
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use warp::hyper::{service::service_fn, Body, Request, Response};
+/// use warp::Filter;
+///
+/// let legacy = service_fn(|req: Request<Body>| async move {
+///     Ok::<_, Infallible>(Response::new(Body::from(format!("legacy {}", req.uri()))))
+/// });
+///
+/// // GET /legacy/... is served by the old service
+/// let route = warp::path("legacy")
+///     .and(warp::service::from_service(legacy))
+///     .or(warp::path("new").map(|| "new"));
+/// ```
+///
+/// [Service]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+pub fn from_service<S>(
+    service: S,
+) -> impl Filter<Extract = (S::Response,), Error = Rejection> + Clone
+where
+    S: Service<Request> + Clone + Send,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    ServiceFilter::new(service)
+}
+
+/// A `Filter` as a [`Service`][Service], returned by [`service`].
+///
+/// [Service]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
 #[derive(Copy, Clone, Debug)]
 pub struct FilteredService<F> {
     filter: F,
@@ -69,6 +116,37 @@ where
     <F::Future as TryFuture>::Ok: Reply,
     <F::Future as TryFuture>::Error: IsReject,
 {
+    /// Handle a single request, replying to it without a server.
+    ///
+    /// Rejections are turned into replies, as when serving the filter. This
+    /// allows running the same filters where requests come from elsewhere
+    /// than a TCP listener, such as serverless functions. For AWS Lambda,
+    /// the `lambda` feature adds the `warp::lambda` module, which does the
+    /// conversion from and to Lambda events.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn handle() {
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::path("hello").map(|| "Hello, World!");
+    /// let service = warp::service(routes);
+    ///
+    /// let req = warp::http::Request::get("/hello")
+    ///     .body(warp::hyper::Body::empty())
+    ///     .unwrap();
+    /// let res = service.call_once(req).await;
+    /// assert_eq!(res.status(), 200);
+    /// # }
+    /// ```
+    pub async fn call_once(&self, req: http::Request<hyper::Body>) -> Response {
+        match self.call_with_addr(req, None).await {
+            Ok(res) => res,
+            Err(never) => match never {},
+        }
+    }
+
     #[inline]
     pub(crate) fn call_with_addr(
         &self,
@@ -81,6 +159,37 @@ where
         let fut = route::set(&route, || self.filter.filter(super::Internal));
         FilteredFuture { future: fut, route }
     }
+
+    /// Like `call_with_addr`, but passes rejections and panics that reach
+    /// the service to `on_error`, if there is one.
+    pub(crate) fn call_reporting(
+        &self,
+        req: Request,
+        remote_addr: Option<SocketAddr>,
+        on_error: Option<ErrorHook>,
+    ) -> ReportedFuture<F::Future> {
+        debug_assert!(!route::is_set(), "nested route::set calls");
+
+        let route = Route::new(req, remote_addr);
+        let (future, panic) = match on_error {
+            Some(_) => {
+                match route::set(&route, || catching(|| self.filter.filter(super::Internal))) {
+                    Ok(fut) => (Some(fut), None),
+                    Err(payload) => (None, Some(Panic::new(payload))),
+                }
+            }
+            None => (
+                Some(route::set(&route, || self.filter.filter(super::Internal))),
+                None,
+            ),
+        };
+        ReportedFuture {
+            future,
+            panic,
+            route,
+            on_error,
+        }
+    }
 }
 
 impl<F> Service<Request> for FilteredService<F>
@@ -135,3 +244,68 @@ where
         }
     }
 }
+
+#[pin_project]
+pub(crate) struct ReportedFuture<F> {
+    #[pin]
+    future: Option<F>,
+    panic: Option<Panic>,
+    route: ::std::cell::RefCell<Route>,
+    on_error: Option<ErrorHook>,
+}
+
+impl<F> Future for ReportedFuture<F>
+where
+    F: TryFuture,
+    F::Ok: Reply,
+    F::Error: IsReject,
+{
+    type Output = Result<Response, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        debug_assert!(!route::is_set(), "nested route::set calls");
+
+        let mut pin = self.project();
+        let panic = match pin.future.as_mut().as_pin_mut() {
+            Some(fut) => {
+                let polled = if pin.on_error.is_some() {
+                    route::set(pin.route, || catching(|| fut.try_poll(cx)))
+                } else {
+                    Ok(route::set(pin.route, || fut.try_poll(cx)))
+                };
+                match polled {
+                    Ok(Poll::Ready(Ok(ok))) => return Poll::Ready(Ok(ok.into_response())),
+                    Ok(Poll::Pending) => return Poll::Pending,
+                    Ok(Poll::Ready(Err(err))) => {
+                        tracing::debug!("rejected: {:?}", err);
+                        let res = err.into_response();
+                        report(pin.on_error, pin.route, &res, err.as_rejection(), None);
+                        return Poll::Ready(Ok(res));
+                    }
+                    Err(payload) => Panic::new(payload),
+                }
+            }
+            None => pin.panic.take().expect("polled after complete"),
+        };
+        pin.future.set(None);
+
+        tracing::error!("request handler panicked: {:?}", panic.message());
+        let mut res = Response::default();
+        *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        report(pin.on_error, pin.route, &res, None, Some(&panic));
+        Poll::Ready(Ok(res))
+    }
+}
+
+fn report(
+    on_error: &Option<ErrorHook>,
+    route: &::std::cell::RefCell<Route>,
+    res: &Response,
+    rejection: Option<&crate::Rejection>,
+    panic: Option<&Panic>,
+) {
+    if let Some(ref on_error) = on_error {
+        let route = route.borrow();
+        on_error(&UnhandledError::new(&route, res.status(), rejection, panic));
+    }
+}
@@ -0,0 +1,51 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::generic::Flatten;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Untuple<F> {
+    pub(super) filter: F,
+}
+
+impl<F> FilterBase for Untuple<F>
+where
+    F: Filter,
+    F::Extract: Flatten,
+{
+    type Extract = <F::Extract as Flatten>::Output;
+    type Error = F::Error;
+    type Future = UntupleFuture<F>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        UntupleFuture {
+            extract: self.filter.filter(Internal),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct UntupleFuture<F: Filter> {
+    #[pin]
+    extract: F::Future,
+}
+
+impl<F> Future for UntupleFuture<F>
+where
+    F: Filter,
+    F::Extract: Flatten,
+{
+    type Output = Result<<F::Extract as Flatten>::Output, F::Error>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let ex = ready!(self.project().extract.try_poll(cx))?;
+        Poll::Ready(Ok(ex.flatten_tuples()))
+    }
+}
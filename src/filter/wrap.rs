@@ -1,14 +1,57 @@
 use super::Filter;
 
-pub trait WrapSealed<F: Filter> {
+/// A wrapper that can be applied to a `Filter` with [`Filter::with`].
+///
+/// Wrappers can do some work before the wrapped filter runs, and process
+/// what it returns afterwards, such as logging, collecting metrics, or
+/// adding headers to replies.
+///
+/// Since the combinators return types that can't be named, a wrapper
+/// usually boxes the filter it builds, or uses [`wrap_fn`] with a plain
+/// function.
+///
+/// # Example
+///
+/// ```
+/// use warp::filters::BoxedFilter;
+/// use warp::{Filter, Rejection, Reply, Wrap};
+///
+/// /// Adds a `server` header to every reply.
+/// struct ServerHeader(&'static str);
+///
+/// impl<F, R> Wrap<F> for ServerHeader
+/// where
+///     F: Filter<Extract = (R,)> + Clone + Send + Sync + 'static,
+///     F::Error: Into<Rejection>,
+///     R: Reply,
+/// {
+///     type Wrapped = BoxedFilter<(warp::reply::Response,)>;
+///
+///     fn wrap(&self, filter: F) -> Self::Wrapped {
+///         let name = self.0;
+///         filter
+///             .map(move |reply: R| {
+///                 warp::reply::with_header(reply, "server", name).into_response()
+///             })
+///             .boxed()
+///     }
+/// }
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(ServerHeader("warp"));
+/// ```
+pub trait Wrap<F: Filter> {
+    /// The `Filter` produced by wrapping `F`.
     type Wrapped: Filter;
 
+    /// Wrap the provided `Filter`.
     fn wrap(&self, filter: F) -> Self::Wrapped;
 }
 
-impl<'a, T, F> WrapSealed<F> for &'a T
+impl<'a, T, F> Wrap<F> for &'a T
 where
-    T: WrapSealed<F>,
+    T: Wrap<F>,
     F: Filter,
 {
     type Wrapped = T::Wrapped;
@@ -17,15 +60,6 @@ where
     }
 }
 
-pub trait Wrap<F: Filter>: WrapSealed<F> {}
-
-impl<T, F> Wrap<F> for T
-where
-    T: WrapSealed<F>,
-    F: Filter,
-{
-}
-
 /// Function that receives a filter to be combined with pre and after filters
 pub fn wrap_fn<F, T, U>(func: F) -> WrapFn<F>
 where
@@ -36,12 +70,13 @@ where
     WrapFn { func }
 }
 
+/// A `Wrap` built from a function, returned by [`wrap_fn`].
 #[derive(Debug)]
 pub struct WrapFn<F> {
     func: F,
 }
 
-impl<F, T, U> WrapSealed<T> for WrapFn<F>
+impl<F, T, U> Wrap<T> for WrapFn<F>
 where
     F: Fn(T) -> U,
     T: Filter,
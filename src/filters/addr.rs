@@ -1,9 +1,19 @@
 //! Socket Address filters.
 
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::error::Error as StdError;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
 
-use crate::filter::{filter_fn_one, Filter};
+use headers::HeaderMapExt;
+use http::header::HeaderMap;
+pub use ipnet::IpNet;
+
+use crate::filter::{filter_fn, filter_fn_one, Filter};
+use crate::header::Forwarded;
+use crate::reject::{self, Rejection};
+use crate::route::Route;
 
 /// Creates a `Filter` to get the remote address of the connection.
 ///
@@ -24,3 +34,298 @@ use crate::filter::{filter_fn_one, Filter};
 pub fn remote() -> impl Filter<Extract = (Option<SocketAddr>,), Error = Infallible> + Copy {
     filter_fn_one(|route| futures::future::ok(route.remote_addr()))
 }
+
+/// Creates a `Filter` to get the address of the client that made the request.
+///
+/// When the connection comes from a trusted proxy, the client address is
+/// taken from `header`, the one the proxies set. Other headers are ignored,
+/// since proxies pass on the ones they don't set as the client sent them.
+/// The forwarded chain is walked from the nearest hop outwards, skipping any
+/// addresses that are themselves in `trusted`. If the header is invalid,
+/// or a hop on the way is `unknown` or obfuscated, this yields `None`, since
+/// the client can't be told apart from the proxies.
+///
+/// When the peer isn't trusted, the headers are ignored and the address of
+/// the socket is used, since an untrusted client could claim to be anyone.
+///
+/// If the underlying transport doesn't use socket addresses, this will yield
+/// `None`.
+///
+/// # Example
+///
+/// ```
+/// use std::net::IpAddr;
+/// use warp::addr::ForwardedHeader;
+/// use warp::Filter;
+///
+/// let trusted = vec!["10.0.0.0/8".parse().unwrap()];
+///
+/// let route = warp::addr::client_ip(trusted, ForwardedHeader::XForwardedFor)
+///     .map(|ip: Option<IpAddr>| {
+///         println!("client address = {:?}", ip);
+///     });
+/// ```
+pub fn client_ip<I>(
+    trusted: I,
+    header: ForwardedHeader,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Infallible> + Clone
+where
+    I: IntoIterator<Item = IpNet>,
+{
+    let trusted: Arc<[IpNet]> = trusted.into_iter().collect();
+    filter_fn(move |route| {
+        let ip = route
+            .remote_addr()
+            .and_then(|addr| resolve(&trusted, header, addr.ip(), route.headers()));
+        futures::future::ok((ip,))
+    })
+}
+
+/// The header trusted proxies record the client in, for [`client_ip`],
+/// [`IpList::trust_proxies`] and [`scheme`](crate::scheme()).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// The `Forwarded` header of RFC 7239, with the client in its `for`
+    /// parameters, and the scheme in its `proto` parameters.
+    Forwarded,
+    /// The `X-Forwarded-For` header, with the scheme in `X-Forwarded-Proto`.
+    XForwardedFor,
+    /// The `X-Real-IP` header, with the scheme in `X-Forwarded-Proto`.
+    XRealIp,
+}
+
+/// Creates a `Filter` that only lets through clients with an address in
+/// `list`.
+///
+/// Other clients, requests without a socket address, and requests from a
+/// trusted proxy without a client address it can be read from, are rejected
+/// with an [`AddressForbidden`] rejection, which replies with
+/// `403 Forbidden`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::addr::{ForwardedHeader, IpList};
+///
+/// let office = IpList::new(vec!["192.168.0.0/16".parse().unwrap()])
+///     .trust_proxies(vec!["10.0.0.0/8".parse().unwrap()], ForwardedHeader::Forwarded);
+///
+/// let admin = warp::path("admin")
+///     .and(warp::addr::allow(office.clone()))
+///     .map(|| "welcome");
+///
+/// // Later, without restarting:
+/// office.set(vec!["192.168.1.0/24".parse().unwrap()]);
+/// ```
+pub fn allow(list: impl Into<IpList>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let list = list.into();
+    filter_fn(move |route| {
+        let ip = list.client_ip(route);
+        let result = match ip {
+            Some(ip) if list.contains(&ip) => Ok(()),
+            _ => Err(reject::known(AddressForbidden { ip })),
+        };
+        futures::future::ready(result)
+    })
+}
+
+/// Creates a `Filter` that rejects clients with an address in `list`.
+///
+/// They are rejected with an [`AddressForbidden`] rejection, which replies
+/// with `403 Forbidden`, as are requests from a trusted proxy without a
+/// client address it can be read from. Requests without a socket address
+/// are let through.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let blocked = vec!["203.0.113.0/24".parse().unwrap()];
+///
+/// let route = warp::addr::deny(blocked)
+///     .map(|| "hello");
+/// ```
+pub fn deny(list: impl Into<IpList>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let list = list.into();
+    filter_fn(move |route| {
+        let ip = list.client_ip(route);
+        let result = match ip {
+            Some(ip) if list.contains(&ip) => Err(reject::known(AddressForbidden { ip: Some(ip) })),
+            // Any client could be hiding there, including denied ones.
+            None if route.remote_addr().is_some() => {
+                Err(reject::known(AddressForbidden { ip: None }))
+            }
+            _ => Ok(()),
+        };
+        futures::future::ready(result)
+    })
+}
+
+/// A list of IPv4 and IPv6 networks, for [`allow`] and [`deny`].
+///
+/// The networks can be replaced with [`set`](IpList::set) while the server is
+/// running; clones of a list share the same networks.
+#[derive(Clone, Debug)]
+pub struct IpList {
+    nets: Arc<RwLock<Arc<[IpNet]>>>,
+    trusted: Arc<[IpNet]>,
+    header: ForwardedHeader,
+}
+
+impl IpList {
+    /// Creates a list of the given networks.
+    pub fn new<I>(nets: I) -> Self
+    where
+        I: IntoIterator<Item = IpNet>,
+    {
+        IpList {
+            nets: Arc::new(RwLock::new(nets.into_iter().collect())),
+            trusted: Arc::new([]),
+            header: ForwardedHeader::Forwarded,
+        }
+    }
+
+    /// Checks the address of the client in `header` instead of the peer
+    /// when the connection comes from one of these proxies, as with
+    /// [`client_ip`].
+    pub fn trust_proxies<I>(mut self, trusted: I, header: ForwardedHeader) -> Self
+    where
+        I: IntoIterator<Item = IpNet>,
+    {
+        self.trusted = trusted.into_iter().collect();
+        self.header = header;
+        self
+    }
+
+    /// Replaces the networks in the list.
+    pub fn set<I>(&self, nets: I)
+    where
+        I: IntoIterator<Item = IpNet>,
+    {
+        *self.nets.write().unwrap() = nets.into_iter().collect();
+    }
+
+    /// Whether an address is in one of the networks of the list.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let nets = self.nets.read().unwrap().clone();
+        nets.iter().any(|net| net.contains(ip))
+    }
+
+    fn client_ip(&self, route: &Route) -> Option<IpAddr> {
+        route
+            .remote_addr()
+            .and_then(|addr| resolve(&self.trusted, self.header, addr.ip(), route.headers()))
+    }
+}
+
+impl From<Vec<IpNet>> for IpList {
+    fn from(nets: Vec<IpNet>) -> Self {
+        IpList::new(nets)
+    }
+}
+
+impl From<&[IpNet]> for IpList {
+    fn from(nets: &[IpNet]) -> Self {
+        IpList::new(nets.iter().copied())
+    }
+}
+
+/// An error used to reject clients by [`allow`] and [`deny`].
+#[derive(Debug)]
+pub struct AddressForbidden {
+    ip: Option<IpAddr>,
+}
+
+impl AddressForbidden {
+    /// The address of the client, if it had one.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+}
+
+impl fmt::Display for AddressForbidden {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.ip {
+            Some(ip) => write!(f, "Access from {} is forbidden", ip),
+            None => f.write_str("Access without a client address is forbidden"),
+        }
+    }
+}
+
+impl StdError for AddressForbidden {}
+
+fn resolve(
+    trusted: &[IpNet],
+    header: ForwardedHeader,
+    peer: IpAddr,
+    headers: &HeaderMap,
+) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+
+    if !is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    let hops = forwarded_hops(header, headers)?;
+
+    // Each hop was appended by the proxy after it, so walk from the nearest
+    // one outwards, stopping at the first address not vouched for. A hop
+    // without an address hides where the request came from.
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        client = (*hop)?;
+        if !is_trusted(&client) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+// The hops in `header`, or `None` if it's invalid.
+fn forwarded_hops(header: ForwardedHeader, headers: &HeaderMap) -> Option<Vec<Option<IpAddr>>> {
+    let hops = match header {
+        ForwardedHeader::Forwarded => match headers.typed_try_get::<Forwarded>() {
+            Ok(Some(forwarded)) => forwarded
+                .elements()
+                .iter()
+                .filter_map(|element| element.for_node())
+                .map(|node| node.ip())
+                .collect(),
+            Ok(None) => Vec::new(),
+            // The nearest proxy appended its hop to a header it couldn't
+            // parse, so none of the hops can be told apart.
+            Err(_) => return None,
+        },
+        ForwardedHeader::XForwardedFor => headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|node| parse_node(node.trim()))
+            .collect(),
+        ForwardedHeader::XRealIp => headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .map(|node| parse_node(node.trim()))
+            .into_iter()
+            .collect(),
+    };
+    Some(hops)
+}
+
+/// Parse a node from `X-Forwarded-For`, which may be quoted, have a port, and
+/// wrap IPv6 addresses in brackets.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim_matches('"');
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    let v6 = node.strip_prefix('[')?;
+    let end = v6.find(']')?;
+    v6[..end].parse().ok()
+}
@@ -0,0 +1,668 @@
+//! Response caching Filters
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::header::{
+    HeaderMap, HeaderName, HeaderValue, AGE, AUTHORIZATION, CACHE_CONTROL, COOKIE, SET_COOKIE, VARY,
+};
+use http::StatusCode;
+use hyper::body::HttpBody;
+use hyper::Body;
+use tokio::time::Instant;
+
+use crate::clock;
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::{Reply, Response};
+use crate::route::Route;
+
+use self::internal::WithCache;
+
+/// Create a wrapping filter that caches successful `GET` replies in memory.
+///
+/// Replies are cached by the host, path and query of the request, and, when
+/// the reply has a `Vary` header or [`Cache::vary`] is used, by the values of
+/// those request headers. A reply is only cached when it is a `200 OK`, its
+/// `Cache-Control` doesn't say `no-store` or `private`, it doesn't set a
+/// cookie, and its body is already fully in memory; streaming bodies pass
+/// through untouched.
+///
+/// Requests with an `Authorization` or `Cookie` header bypass the cache,
+/// since their replies are likely meant for that client only, unless the
+/// header is one the cache [varies](Cache::vary) on.
+///
+/// Cached replies are served for the [`ttl`](Cache::ttl). After that, they
+/// may still be served during the
+/// [`stale_while_revalidate`](Cache::stale_while_revalidate) period, while
+/// the wrapped filter runs again in the background to refresh them.
+///
+/// Use a [`CacheHandle`] to invalidate entries when the data behind them
+/// changes.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let cache = warp::cache()
+///     .ttl(Duration::from_secs(30))
+///     .stale_while_revalidate(Duration::from_secs(60))
+///     .max_entries(10_000);
+/// let handle = cache.handle();
+///
+/// let articles = warp::path("articles")
+///     .map(|| "all the articles")
+///     .with(cache);
+///
+/// // Later, when an article is published:
+/// handle.invalidate_prefix("/articles");
+/// ```
+pub fn cache() -> Cache {
+    Cache {
+        store: Arc::new(Mutex::new(Store {
+            entries: HashMap::new(),
+            bytes: 0,
+            tick: 0,
+        })),
+        config: Arc::new(Config {
+            ttl: Duration::from_secs(60),
+            stale_while_revalidate: Duration::from_secs(0),
+            max_entries: 1024,
+            max_bytes: 64 * 1024 * 1024,
+            vary: Vec::new(),
+        }),
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to cache its replies.
+///
+/// Filters wrapped with the same `Cache`, or a clone of it, share entries.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    store: Arc<Mutex<Store>>,
+    config: Arc<Config>,
+}
+
+#[derive(Clone, Debug)]
+struct Config {
+    ttl: Duration,
+    stale_while_revalidate: Duration,
+    max_entries: usize,
+    max_bytes: usize,
+    vary: Vec<HeaderName>,
+}
+
+/// Invalidates entries of a [`cache`].
+#[derive(Clone, Debug)]
+pub struct CacheHandle {
+    store: Arc<Mutex<Store>>,
+}
+
+#[derive(Debug)]
+struct Store {
+    // All the variants cached for a path and query, for any host.
+    entries: HashMap<String, Vec<Entry>>,
+    bytes: usize,
+    // Counts lookups, to find the least recently used entries.
+    tick: u64,
+}
+
+// Where a reply is cached: virtual hosts serving the same paths get
+// separate variants.
+#[derive(Clone, Debug)]
+struct Key {
+    path: String,
+    authority: Option<String>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    authority: Option<String>,
+    vary: Vec<(HeaderName, Option<HeaderValue>)>,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored: Instant,
+    last_used: u64,
+    revalidating: bool,
+}
+
+enum Lookup {
+    Fresh(Response),
+    // The stale reply, and whether this request should revalidate it.
+    Stale(Response, bool),
+    Miss,
+}
+
+impl Cache {
+    /// Sets how long replies are served from the cache.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.config).ttl = ttl;
+        self
+    }
+
+    /// Sets how long after the `ttl` stale replies may still be served while
+    /// they are refreshed in the background.
+    ///
+    /// Defaults to 0, so entries are refreshed before replying.
+    pub fn stale_while_revalidate(mut self, duration: Duration) -> Self {
+        Arc::make_mut(&mut self.config).stale_while_revalidate = duration;
+        self
+    }
+
+    /// Sets how many replies may be cached, evicting the least recently
+    /// used ones beyond that.
+    ///
+    /// Defaults to 1024.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_entries = max;
+        self
+    }
+
+    /// Sets how many bytes of bodies may be cached, evicting the least
+    /// recently used ones beyond that.
+    ///
+    /// Defaults to 64 MiB.
+    pub fn max_bytes(mut self, max: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_bytes = max;
+        self
+    }
+
+    /// Caches separate replies for each value of a request header, as if
+    /// every reply had it in its `Vary` header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    pub fn vary<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        let name =
+            HeaderName::try_from(name).unwrap_or_else(|_| panic!("cache: invalid header name"));
+        Arc::make_mut(&mut self.config).vary.push(name);
+        self
+    }
+
+    /// Get a handle to invalidate entries of this cache.
+    pub fn handle(&self) -> CacheHandle {
+        CacheHandle {
+            store: self.store.clone(),
+        }
+    }
+
+    fn lookup(&self, key: &Key, req_headers: &HeaderMap) -> Lookup {
+        let mut store = self.store.lock().unwrap();
+        store.tick += 1;
+        let tick = store.tick;
+        let entry = match store
+            .entries
+            .get_mut(&key.path)
+            .and_then(|variants| variants.iter_mut().find(|e| e.matches(key, req_headers)))
+        {
+            Some(entry) => entry,
+            None => return Lookup::Miss,
+        };
+
+        let age = clock::now().saturating_duration_since(entry.stored);
+        if age < self.config.ttl {
+            entry.last_used = tick;
+            Lookup::Fresh(entry.response(age))
+        } else if age < self.config.ttl + self.config.stale_while_revalidate {
+            entry.last_used = tick;
+            let revalidate = !entry.revalidating;
+            entry.revalidating = true;
+            Lookup::Stale(entry.response(age), revalidate)
+        } else {
+            Lookup::Miss
+        }
+    }
+
+    // Whether the request carries credentials the cache doesn't vary on,
+    // so its reply mustn't be shared with, nor taken from, other clients.
+    fn is_private(&self, req_headers: &HeaderMap) -> bool {
+        [AUTHORIZATION, COOKIE]
+            .iter()
+            .any(|name| req_headers.contains_key(name) && !self.config.vary.contains(name))
+    }
+
+    fn is_cacheable(&self, res: &Response) -> bool {
+        if res.status() != StatusCode::OK || res.headers().contains_key(SET_COOKIE) {
+            return false;
+        }
+        let no_store = res
+            .headers()
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|directive| {
+                let directive = directive.trim();
+                directive.eq_ignore_ascii_case("no-store")
+                    || directive.eq_ignore_ascii_case("private")
+            });
+        if no_store || vary_names(res.headers()).is_none() {
+            return false;
+        }
+        // Only bodies already in memory, since buffering a stream could
+        // wait forever.
+        matches!(res.body().size_hint().exact(), Some(len) if len as usize <= self.config.max_bytes)
+    }
+
+    // Buffers the body of a cacheable reply to store it.
+    fn store(
+        &self,
+        key: Key,
+        req_headers: HeaderMap,
+        res: Response,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let cache = self.clone();
+        Box::pin(async move {
+            let (parts, body) = res.into_parts();
+            let body = match hyper::body::to_bytes(body).await {
+                Ok(body) => body,
+                Err(err) => {
+                    tracing::debug!("cache: error buffering body: {}", err);
+                    cache.revalidated(&key, &req_headers);
+                    return Response::from_parts(parts, Body::empty());
+                }
+            };
+
+            let mut vary = Vec::new();
+            let names = cache
+                .config
+                .vary
+                .iter()
+                .cloned()
+                .chain(vary_names(&parts.headers).unwrap_or_default());
+            for name in names {
+                if !vary.iter().any(|(n, _)| *n == name) {
+                    let value = req_headers.get(&name).cloned();
+                    vary.push((name, value));
+                }
+            }
+
+            cache.insert(
+                key.path,
+                Entry {
+                    authority: key.authority,
+                    vary,
+                    status: parts.status,
+                    headers: parts.headers.clone(),
+                    body: body.clone(),
+                    stored: clock::now(),
+                    last_used: 0,
+                    revalidating: false,
+                },
+            );
+            Response::from_parts(parts, Body::from(body))
+        })
+    }
+
+    fn insert(&self, key: String, mut entry: Entry) {
+        let mut store = self.store.lock().unwrap();
+        store.tick += 1;
+        entry.last_used = store.tick;
+        store.bytes += entry.body.len();
+
+        let variants = store.entries.entry(key).or_default();
+        let replaced = variants
+            .iter()
+            .position(|e| e.authority == entry.authority && e.vary == entry.vary)
+            .map(|i| variants.swap_remove(i));
+        variants.push(entry);
+        if let Some(replaced) = replaced {
+            store.bytes -= replaced.body.len();
+        }
+
+        store.evict(self.config.max_entries, self.config.max_bytes);
+    }
+
+    // A failed revalidation lets a later request try again.
+    fn revalidated(&self, key: &Key, req_headers: &HeaderMap) {
+        let mut store = self.store.lock().unwrap();
+        if let Some(variants) = store.entries.get_mut(&key.path) {
+            for entry in variants.iter_mut().filter(|e| e.matches(key, req_headers)) {
+                entry.revalidating = false;
+            }
+        }
+    }
+}
+
+impl CacheHandle {
+    /// Removes all variants cached for a path and query, such as
+    /// `/articles?page=2`, for every host.
+    ///
+    /// Returns whether there were any.
+    pub fn invalidate(&self, key: &str) -> bool {
+        let mut store = self.store.lock().unwrap();
+        match store.entries.remove(key) {
+            Some(variants) => {
+                store.bytes -= variants.iter().map(|e| e.body.len()).sum::<usize>();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all replies cached for paths starting with `prefix`, for
+    /// every host.
+    ///
+    /// Returns how many paths were removed.
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize {
+        let mut store = self.store.lock().unwrap();
+        let keys = store
+            .entries
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &keys {
+            if let Some(variants) = store.entries.remove(key) {
+                store.bytes -= variants.iter().map(|e| e.body.len()).sum::<usize>();
+            }
+        }
+        keys.len()
+    }
+
+    /// Removes everything from the cache.
+    pub fn clear(&self) {
+        let mut store = self.store.lock().unwrap();
+        store.entries.clear();
+        store.bytes = 0;
+    }
+
+    /// The number of replies in the cache.
+    pub fn len(&self) -> usize {
+        let store = self.store.lock().unwrap();
+        store.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Store {
+    fn evict(&mut self, max_entries: usize, max_bytes: usize) {
+        let mut len = self.entries.values().map(Vec::len).sum::<usize>();
+        while len > max_entries || self.bytes > max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .flat_map(|(key, variants)| {
+                    variants
+                        .iter()
+                        .enumerate()
+                        .map(move |(i, e)| (e.last_used, key, i))
+                })
+                .min()
+                .map(|(_, key, i)| (key.clone(), i));
+            let (key, i) = match oldest {
+                Some(oldest) => oldest,
+                None => return,
+            };
+
+            let variants = self.entries.get_mut(&key).expect("oldest key exists");
+            let entry = variants.swap_remove(i);
+            if variants.is_empty() {
+                self.entries.remove(&key);
+            }
+            self.bytes -= entry.body.len();
+            len -= 1;
+        }
+    }
+}
+
+impl Entry {
+    fn matches(&self, key: &Key, req_headers: &HeaderMap) -> bool {
+        self.authority == key.authority
+            && self
+                .vary
+                .iter()
+                .all(|(name, value)| req_headers.get(name) == value.as_ref())
+    }
+
+    fn response(&self, age: Duration) -> Response {
+        let mut res = Response::new(Body::from(self.body.clone()));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(AGE, HeaderValue::from(age.as_secs()));
+        res
+    }
+}
+
+// The request headers named by `Vary`, or `None` for `Vary: *`.
+fn vary_names(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    let values = headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim);
+    for value in values {
+        if value == "*" {
+            return None;
+        }
+        if let Ok(name) = HeaderName::try_from(value) {
+            names.push(name);
+        }
+    }
+    Some(names)
+}
+
+fn key(route: &Route) -> Key {
+    let path = match route.query() {
+        Some(query) => format!("{}?{}", route.full_path(), query),
+        None => route.full_path().to_owned(),
+    };
+    Key {
+        path,
+        authority: route.authority(),
+    }
+}
+
+impl<F> Wrap<F> for Cache
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithCache<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithCache {
+            filter,
+            cache: self.clone(),
+        }
+    }
+}
+
+mod internal {
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use http::header::HeaderMap;
+    use http::Method;
+    use pin_project::pin_project;
+
+    use super::{key, Cache, Key, Lookup};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{IsReject, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Cached(Response);
+
+    impl Reply for Cached {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithCache<F> {
+        pub(super) filter: F,
+        pub(super) cache: Cache,
+    }
+
+    impl<F> WithCache<F>
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        // Runs the filter again for a forked route, to refresh a stale entry
+        // without holding up the request that found it.
+        fn revalidate(&self, route: RefCell<route::Route>, key: Key) {
+            let filter = self.filter.clone();
+            let cache = self.cache.clone();
+            tokio::spawn(async move {
+                let req_headers = route.borrow().headers().clone();
+                let future = route::set(&route, || filter.filter(Internal));
+                match (Forked { future, route }).await {
+                    Ok(reply) => {
+                        let res = reply.into_response();
+                        if cache.is_cacheable(&res) {
+                            cache.store(key, req_headers, res).await;
+                            return;
+                        }
+                    }
+                    Err(err) => {
+                        let rejection: Rejection = err.into();
+                        tracing::debug!("cache: revalidation rejected: {:?}", rejection.status());
+                    }
+                }
+                cache.revalidated(&key, &req_headers);
+            });
+        }
+    }
+
+    #[pin_project]
+    struct Forked<F> {
+        #[pin]
+        future: F,
+        route: RefCell<route::Route>,
+    }
+
+    impl<F: TryFuture> Future for Forked<F> {
+        type Output = Result<F::Ok, F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let future = pin.future;
+            route::set(pin.route, || future.try_poll(cx))
+        }
+    }
+
+    impl<F> FilterBase for WithCache<F>
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Cached,);
+        type Error = Rejection;
+        type Future = WithCacheFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let lookup = route::with(|route| {
+                if route.method() != Method::GET || self.cache.is_private(route.headers()) {
+                    return None;
+                }
+                let key = key(route);
+                match self.cache.lookup(&key, route.headers()) {
+                    Lookup::Fresh(res) => Some(Ok(res)),
+                    Lookup::Stale(res, revalidate) => {
+                        if revalidate {
+                            tracing::debug!("cache: revalidating {}", key.path);
+                            self.revalidate(route.fork(), key);
+                        }
+                        Some(Ok(res))
+                    }
+                    Lookup::Miss => Some(Err((key, route.headers().clone()))),
+                }
+            });
+
+            let state = match lookup {
+                Some(Ok(res)) => State::Hit(Some(res)),
+                Some(Err(miss)) => State::Filter(self.filter.filter(Internal), Some(miss)),
+                None => State::Filter(self.filter.filter(Internal), None),
+            };
+            WithCacheFuture {
+                state,
+                cache: self.cache.clone(),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithCacheFuture<F: Filter> {
+        #[pin]
+        state: State<F>,
+        cache: Cache,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Hit(Option<Response>),
+        Filter(#[pin] F::Future, Option<(Key, HeaderMap)>),
+        Store(Pin<Box<dyn Future<Output = Response> + Send>>),
+    }
+
+    impl<F> Future for WithCacheFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Cached,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Hit(res) => {
+                        let res = res.take().expect("polled after complete");
+                        return Poll::Ready(Ok((Cached(res),)));
+                    }
+                    StateProj::Filter(fut, miss) => {
+                        let reply = ready!(fut.try_poll(cx)).map_err(Into::into)?;
+                        let res = reply.into_response();
+                        match miss.take() {
+                            Some((key, req_headers)) if pin.cache.is_cacheable(&res) => {
+                                let store = pin.cache.store(key, req_headers, res);
+                                state.set(State::Store(store));
+                            }
+                            _ => return Poll::Ready(Ok((Cached(res),))),
+                        }
+                    }
+                    StateProj::Store(store) => {
+                        let res = ready!(store.as_mut().poll(cx));
+                        return Poll::Ready(Ok((Cached(res),)));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Body capture Filters
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use bytes::{Bytes, BytesMut};
+use futures::stream;
+use http::header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, Method, StatusCode, Uri};
+use hyper::body::HttpBody;
+use hyper::Body;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::IsReject;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithCapture;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Create a wrapping filter that captures the bodies of requests and
+/// replies, and passes them to `func` as a [`Record`], for debugging.
+///
+/// Bodies are captured as they are read and sent, up to a
+/// [limit](Capture::max_body), so streaming bodies aren't held up. `func` is
+/// called once the reply body has been sent, with what was read of the
+/// request body by then.
+///
+/// The values of the `Authorization`, `Proxy-Authorization`, `Cookie`, and
+/// `Set-Cookie` headers are redacted by default. More headers, and values in
+/// JSON bodies, can be redacted with [`redact_header`](Capture::redact_header)
+/// and [`redact_json`](Capture::redact_json).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let capture = warp::capture(|record| {
+///     eprintln!(
+///         "{} {} -> {}: {:?}",
+///         record.method(),
+///         record.uri(),
+///         record.status(),
+///         record.request_body(),
+///     );
+/// })
+/// .redact_header("x-api-key")
+/// .redact_json("/password");
+///
+/// let route = warp::path("login")
+///     .and(warp::body::json())
+///     .map(|body: serde_json::Value| warp::reply::json(&body))
+///     .with(capture);
+/// ```
+pub fn capture<F>(func: F) -> Capture<F>
+where
+    F: Fn(Record) + Clone + Send + Sync + 'static,
+{
+    Capture {
+        func,
+        config: Arc::new(Config {
+            max_body: 16 * 1024,
+            headers: vec![
+                http::header::AUTHORIZATION,
+                http::header::PROXY_AUTHORIZATION,
+                http::header::COOKIE,
+                http::header::SET_COOKIE,
+            ],
+            json: Vec::new(),
+        }),
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to capture request and reply
+/// bodies.
+#[derive(Clone)]
+pub struct Capture<F> {
+    func: F,
+    config: Arc<Config>,
+}
+
+#[derive(Clone, Debug)]
+struct Config {
+    max_body: usize,
+    headers: Vec<HeaderName>,
+    json: Vec<String>,
+}
+
+/// A captured request and reply, with sensitive values redacted.
+#[derive(Debug)]
+pub struct Record {
+    method: Method,
+    uri: Uri,
+    status: StatusCode,
+    request_headers: HeaderMap,
+    request_body: Option<Bytes>,
+    request_truncated: bool,
+    response_headers: HeaderMap,
+    response_body: Option<Bytes>,
+    response_truncated: bool,
+}
+
+impl<F> Capture<F> {
+    /// Sets how many bytes of each body are captured.
+    ///
+    /// Defaults to 16KiB.
+    pub fn max_body(mut self, bytes: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_body = bytes;
+        self
+    }
+
+    /// Redacts the values of the header `name`, in requests and replies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    pub fn redact_header<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        let name =
+            HeaderName::try_from(name).unwrap_or_else(|_| panic!("capture: invalid header name"));
+        Arc::make_mut(&mut self.config).headers.push(name);
+        self
+    }
+
+    /// Redacts the value at the JSON `pointer`, such as `/user/password`, in
+    /// JSON bodies of requests and replies.
+    ///
+    /// JSON bodies that can't be parsed, such as those that were truncated,
+    /// are left out of the record once a pointer is set.
+    pub fn redact_json(mut self, pointer: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).json.push(pointer.into());
+        self
+    }
+}
+
+impl<F> fmt::Debug for Capture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Capture")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<FN, F> Wrap<F> for Capture<FN>
+where
+    FN: Fn(Record) + Clone + Send + Sync + 'static,
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithCapture<FN, F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithCapture {
+            filter,
+            capture: self.clone(),
+        }
+    }
+}
+
+impl Record {
+    /// View the `http::Method` of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// View the `http::Uri` of the request.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// View the `http::StatusCode` of the reply.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// View the headers of the request.
+    pub fn request_headers(&self) -> &HeaderMap {
+        &self.request_headers
+    }
+
+    /// View what was read of the request body.
+    ///
+    /// This is `None` if it was left out, as it couldn't be redacted.
+    pub fn request_body(&self) -> Option<&Bytes> {
+        self.request_body.as_ref()
+    }
+
+    /// Whether more of the request body was read than was captured.
+    pub fn request_truncated(&self) -> bool {
+        self.request_truncated
+    }
+
+    /// View the headers of the reply.
+    ///
+    /// These are empty if the request was rejected.
+    pub fn response_headers(&self) -> &HeaderMap {
+        &self.response_headers
+    }
+
+    /// View what was sent of the reply body.
+    ///
+    /// This is `None` if the request was rejected, or it was left out, as it
+    /// couldn't be redacted.
+    pub fn response_body(&self) -> Option<&Bytes> {
+        self.response_body.as_ref()
+    }
+
+    /// Whether more of the reply body was sent than was captured.
+    pub fn response_truncated(&self) -> bool {
+        self.response_truncated
+    }
+}
+
+// A body captured as it is streamed.
+#[derive(Debug, Default)]
+struct Captured {
+    bytes: BytesMut,
+    truncated: bool,
+}
+
+type Shared = Arc<Mutex<Captured>>;
+
+impl Config {
+    fn redact_headers(&self, headers: &HeaderMap) -> HeaderMap {
+        let mut headers = headers.clone();
+        for name in &self.headers {
+            if let http::header::Entry::Occupied(mut entry) = headers.entry(name) {
+                for value in entry.iter_mut() {
+                    *value = HeaderValue::from_static(REDACTED);
+                }
+            }
+        }
+        headers
+    }
+
+    fn redact_body(&self, headers: &HeaderMap, captured: &Shared) -> (Option<Bytes>, bool) {
+        let captured = captured.lock().unwrap();
+        let body = captured.bytes.clone().freeze();
+        if self.json.is_empty() || body.is_empty() || !is_json(headers) {
+            return (Some(body), captured.truncated);
+        }
+        let mut value = match serde_json::from_slice::<serde_json::Value>(&body) {
+            Ok(value) if !captured.truncated => value,
+            _ => return (None, captured.truncated),
+        };
+        for pointer in &self.json {
+            if let Some(found) = value.pointer_mut(pointer) {
+                *found = serde_json::Value::String(REDACTED.to_owned());
+            }
+        }
+        let body = serde_json::to_vec(&value).map(Bytes::from).ok();
+        (body, false)
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let mime = match content_type.and_then(|ct| ct.parse::<mime::Mime>().ok()) {
+        Some(mime) => mime,
+        None => return false,
+    };
+    mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON)
+}
+
+// Wraps `body` to capture up to `max` bytes of it into `captured`, calling
+// `done` once it has been streamed or dropped.
+fn tee(body: Body, captured: Shared, max: usize, done: Option<Done>) -> Body {
+    let state = Teed {
+        body,
+        captured,
+        max,
+        done,
+    };
+    let stream = stream::try_unfold(state, |mut state| async move {
+        match state.body.data().await {
+            Some(chunk) => {
+                let chunk = chunk?;
+                state.capture(&chunk);
+                Ok::<_, hyper::Error>(Some((chunk, state)))
+            }
+            None => Ok(None),
+        }
+    });
+    Body::wrap_stream(stream)
+}
+
+type Done = Box<dyn FnOnce() + Send>;
+
+struct Teed {
+    body: Body,
+    captured: Shared,
+    max: usize,
+    done: Option<Done>,
+}
+
+impl Teed {
+    fn capture(&self, chunk: &[u8]) {
+        let mut captured = self.captured.lock().unwrap();
+        let room = self.max.saturating_sub(captured.bytes.len());
+        if chunk.len() > room {
+            captured.truncated = true;
+        }
+        captured
+            .bytes
+            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+    }
+}
+
+impl Drop for Teed {
+    fn drop(&mut self) {
+        if let Some(done) = self.done.take() {
+            done();
+        }
+    }
+}
+
+fn tee_response(res: Response, captured: Shared, max: usize, done: Done) -> Response {
+    let (mut parts, body) = res.into_parts();
+    match body.size_hint().exact() {
+        Some(0) => {
+            done();
+            return Response::from_parts(parts, body);
+        }
+        Some(len) => {
+            // The captured body is a stream, so keep the length known.
+            parts
+                .headers
+                .entry(CONTENT_LENGTH)
+                .or_insert_with(|| HeaderValue::from(len));
+        }
+        None => {}
+    }
+    Response::from_parts(parts, tee(body, captured, max, Some(done)))
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use http::{HeaderMap, Method, Uri};
+    use pin_project::pin_project;
+
+    use super::{tee, tee_response, Capture, Config, Record, Shared};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::IsReject;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Recorded(Response);
+
+    impl Reply for Recorded {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithCapture<FN, F> {
+        pub(super) filter: F,
+        pub(super) capture: Capture<FN>,
+    }
+
+    impl<FN, F> FilterBase for WithCapture<FN, F>
+    where
+        FN: Fn(Record) + Clone + Send + Sync + 'static,
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Recorded,);
+        type Error = F::Error;
+        type Future = WithCaptureFuture<FN, F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let config = self.capture.config.clone();
+            let request_body = Shared::default();
+            let request = route::with(|route| {
+                if let Some(body) = route.take_body() {
+                    route.restore_body(tee(body, request_body.clone(), config.max_body, None));
+                }
+                Request {
+                    method: route.method().clone(),
+                    uri: route.uri().clone(),
+                    headers: config.redact_headers(route.headers()),
+                    body: request_body,
+                }
+            });
+            WithCaptureFuture {
+                future: self.filter.filter(Internal),
+                func: self.capture.func.clone(),
+                config,
+                request: Some(request),
+            }
+        }
+    }
+
+    pub struct Request {
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Shared,
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithCaptureFuture<FN, F> {
+        #[pin]
+        future: F,
+        func: FN,
+        config: Arc<Config>,
+        request: Option<Request>,
+    }
+
+    impl<FN, F> Future for WithCaptureFuture<FN, F>
+    where
+        FN: Fn(Record) + Clone + Send + Sync + 'static,
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Recorded,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.future.try_poll(cx));
+            let request = pin.request.take().expect("polled after complete");
+            let config = pin.config.clone();
+            let func = pin.func.clone();
+
+            match result {
+                Ok(reply) => {
+                    let res = reply.into_response();
+                    let status = res.status();
+                    let headers = res.headers().clone();
+                    let response_body = Shared::default();
+                    let captured = response_body.clone();
+                    let done = Box::new(move || {
+                        let (body, truncated) = config.redact_body(&headers, &response_body);
+                        let record = record(&config, request, status, &headers, body, truncated);
+                        func(record);
+                    });
+                    let res = tee_response(res, captured, pin.config.max_body, done);
+                    Poll::Ready(Ok((Recorded(res),)))
+                }
+                Err(reject) => {
+                    let headers = HeaderMap::new();
+                    let record = record(&config, request, reject.status(), &headers, None, false);
+                    func(record);
+                    Poll::Ready(Err(reject))
+                }
+            }
+        }
+    }
+
+    fn record(
+        config: &Config,
+        request: Request,
+        status: http::StatusCode,
+        response_headers: &HeaderMap,
+        response_body: Option<bytes::Bytes>,
+        response_truncated: bool,
+    ) -> Record {
+        let (request_body, request_truncated) = config.redact_body(&request.headers, &request.body);
+        Record {
+            method: request.method,
+            uri: request.uri,
+            status,
+            request_headers: request.headers,
+            request_body,
+            request_truncated,
+            response_headers: config.redact_headers(response_headers),
+            response_body,
+            response_truncated,
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Panic catching Filters
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Arc, Once};
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+
+use self::internal::WithCatchPanic;
+
+/// Create a wrapping filter that turns panics into rejections.
+///
+/// Without it, a panic in a handler tears down the connection that the
+/// request came in on. With it, the panic is caught, and the request is
+/// rejected with a [`Panicked`] rejection, which replies with a
+/// `500 Internal Server Error`.
+///
+/// Use [`CatchPanic::on_panic`] to report the panic. Its backtrace is only
+/// captured if enabled with the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+/// environment variables.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(|| -> &'static str { panic!("oh no") })
+///     .with(warp::catch_panic().on_panic(|panic| {
+///         eprintln!("handler panicked: {:?}", panic.message());
+///     }));
+/// ```
+pub fn catch_panic() -> CatchPanic {
+    CatchPanic { on_panic: None }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to catch panics.
+#[derive(Clone)]
+pub struct CatchPanic {
+    on_panic: Option<OnPanic>,
+}
+
+type OnPanic = Arc<dyn Fn(&Panic) + Send + Sync>;
+
+impl CatchPanic {
+    /// Calls `func` with every panic caught, for example to report it.
+    pub fn on_panic<F>(mut self, func: F) -> Self
+    where
+        F: Fn(&Panic) + Send + Sync + 'static,
+    {
+        self.on_panic = Some(Arc::new(func));
+        self
+    }
+}
+
+impl fmt::Debug for CatchPanic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CatchPanic").finish()
+    }
+}
+
+impl<F> Wrap<F> for CatchPanic
+where
+    F: Filter + Clone + Send,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithCatchPanic<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        install_hook();
+        WithCatchPanic {
+            filter,
+            catch: self.clone(),
+        }
+    }
+}
+
+/// A panic caught by [`catch_panic`].
+pub struct Panic {
+    payload: Box<dyn Any + Send>,
+    backtrace: Option<Backtrace>,
+}
+
+impl Panic {
+    /// The message the panic was started with, if it was a string.
+    pub fn message(&self) -> Option<&str> {
+        payload_str(&*self.payload)
+    }
+
+    /// The value the panic was started with.
+    pub fn payload(&self) -> &(dyn Any + Send) {
+        &*self.payload
+    }
+
+    /// Where the panic happened, if backtraces are enabled.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+}
+
+impl fmt::Debug for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Panic")
+            .field("message", &self.message())
+            .field("backtrace", &self.backtrace)
+            .finish()
+    }
+}
+
+impl Panic {
+    pub(crate) fn new(payload: Box<dyn Any + Send>) -> Panic {
+        Panic {
+            payload,
+            backtrace: take_backtrace(),
+        }
+    }
+}
+
+fn payload_str(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&'static str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+/// An error used to reject requests whose filter panicked.
+#[derive(Debug)]
+pub struct Panicked {
+    message: Option<String>,
+}
+
+impl Panicked {
+    /// The message the panic was started with, if it was a string.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+// The message isn't shown, since rejections are rendered to the client,
+// and panic messages may contain anything.
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Request handler panicked")
+    }
+}
+
+impl StdError for Panicked {}
+
+thread_local! {
+    // Whether a `catch_panic` filter is being polled on this thread.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+// The backtrace has to be captured while unwinding hasn't started yet, so
+// chain a panic hook that stashes it for the filter to pick up.
+pub(crate) fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let prev = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) {
+                let backtrace = Backtrace::capture();
+                BACKTRACE.with(|bt| *bt.borrow_mut() = Some(backtrace));
+            }
+            prev(info);
+        }));
+    });
+}
+
+pub(crate) fn catching<T>(f: impl FnOnce() -> T) -> std::thread::Result<T> {
+    let was = CATCHING.with(|c| c.replace(true));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    CATCHING.with(|c| c.set(was));
+    result
+}
+
+fn take_backtrace() -> Option<Backtrace> {
+    BACKTRACE
+        .with(|bt| bt.borrow_mut().take())
+        .filter(|bt| bt.status() == std::backtrace::BacktraceStatus::Captured)
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::TryFuture;
+    use pin_project::pin_project;
+
+    use super::{catching, payload_str, CatchPanic, Panic, Panicked};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithCatchPanic<F> {
+        pub(super) filter: F,
+        pub(super) catch: CatchPanic,
+    }
+
+    impl<F> FilterBase for WithCatchPanic<F>
+    where
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = WithCatchPanicFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            // Filters may panic while building their future, too.
+            match catching(|| self.filter.filter(Internal)) {
+                Ok(future) => WithCatchPanicFuture {
+                    future: Some(future),
+                    panicked: None,
+                    catch: self.catch.clone(),
+                },
+                Err(payload) => WithCatchPanicFuture {
+                    future: None,
+                    panicked: Some(report(&self.catch, payload)),
+                    catch: self.catch.clone(),
+                },
+            }
+        }
+    }
+
+    fn report(catch: &CatchPanic, payload: Box<dyn std::any::Any + Send>) -> Rejection {
+        let panic = Panic::new(payload);
+        tracing::error!("request handler panicked: {:?}", panic.message());
+        if let Some(ref on_panic) = catch.on_panic {
+            on_panic(&panic);
+        }
+        reject::known(Panicked {
+            message: payload_str(&*panic.payload).map(ToOwned::to_owned),
+        })
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithCatchPanicFuture<F> {
+        #[pin]
+        future: Option<F>,
+        panicked: Option<Rejection>,
+        catch: CatchPanic,
+    }
+
+    impl<F> Future for WithCatchPanicFuture<F>
+    where
+        F: TryFuture,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<F::Ok, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut pin = self.project();
+            let future = match pin.future.as_mut().as_pin_mut() {
+                Some(future) => future,
+                None => {
+                    let rejection = pin.panicked.take().expect("polled after complete");
+                    return Poll::Ready(Err(rejection));
+                }
+            };
+            match catching(|| future.try_poll(cx)) {
+                Ok(Poll::Ready(result)) => Poll::Ready(result.map_err(Into::into)),
+                Ok(Poll::Pending) => Poll::Pending,
+                Err(payload) => {
+                    // A panicked future can't be polled again.
+                    pin.future.set(None);
+                    Poll::Ready(Err(report(pin.catch, payload)))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Circuit breaker Filters
+
+use std::collections::VecDeque;
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use tokio::time::Instant;
+
+use crate::clock;
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::Reply;
+
+use self::internal::WithCircuitBreaker;
+
+/// Create a wrapping filter that stops calling a failing filter for a while.
+///
+/// The outcomes of the last [`window`](CircuitBreaker::window) requests are
+/// tracked, where replies with a `5xx` status and rejections that would
+/// reply with one count as failures. Once the share of failures reaches the
+/// [`failure_rate`](CircuitBreaker::failure_rate), the circuit *opens*, and
+/// requests are rejected with a [`CircuitOpen`] rejection without calling
+/// the wrapped filter. It replies with a `503 Service Unavailable`.
+///
+/// After [`open_for`](CircuitBreaker::open_for) has passed, the circuit is
+/// *half-open*: a single request is let through to probe. If it succeeds,
+/// the circuit closes again, otherwise it stays open for another period.
+///
+/// The circuit is shared by every filter wrapped with the same
+/// `CircuitBreaker`, or a clone of it.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let breaker = warp::circuit_breaker()
+///     .failure_rate(0.5)
+///     .open_for(Duration::from_secs(10));
+///
+/// let route = warp::path("inventory")
+///     .map(warp::reply)
+///     .with(breaker);
+/// ```
+pub fn circuit_breaker() -> CircuitBreaker {
+    CircuitBreaker {
+        shared: Arc::new(Mutex::new(Circuit {
+            outcomes: VecDeque::new(),
+            failures: 0,
+            state: State::Closed,
+        })),
+        config: Config {
+            window: 20,
+            min_requests: 10,
+            failure_rate: 0.5,
+            open_for: Duration::from_secs(30),
+        },
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) with a circuit breaker.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    shared: Arc<Mutex<Circuit>>,
+    config: Config,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Config {
+    window: usize,
+    min_requests: usize,
+    failure_rate: f64,
+    open_for: Duration,
+}
+
+#[derive(Debug)]
+struct Circuit {
+    // `true` for each failure among the most recent outcomes.
+    outcomes: VecDeque<bool>,
+    failures: usize,
+    state: State,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Closed,
+    Open { until: Instant },
+    HalfOpen { probing: bool },
+}
+
+/// The state of a [`circuit_breaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are passed to the wrapped filter.
+    Closed,
+    /// Requests are rejected without calling the wrapped filter.
+    Open,
+    /// A request may be let through to probe whether the filter recovered.
+    HalfOpen,
+}
+
+impl CircuitBreaker {
+    /// Sets how many of the most recent requests the failure rate is
+    /// computed from.
+    ///
+    /// Defaults to `20`.
+    pub fn window(mut self, requests: usize) -> Self {
+        assert!(
+            requests > 0,
+            "circuit_breaker window must be greater than 0"
+        );
+        self.config.window = requests;
+        self
+    }
+
+    /// Sets how many requests must be in the window before the circuit may
+    /// open.
+    ///
+    /// Defaults to `10`.
+    pub fn min_requests(mut self, requests: usize) -> Self {
+        self.config.min_requests = requests;
+        self
+    }
+
+    /// Sets the share of failed requests, between `0.0` and `1.0`, at which
+    /// the circuit opens.
+    ///
+    /// Defaults to `0.5`.
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        assert!(
+            rate > 0.0 && rate <= 1.0,
+            "circuit_breaker failure_rate must be in (0.0, 1.0]"
+        );
+        self.config.failure_rate = rate;
+        self
+    }
+
+    /// Sets how long the circuit stays open before probing again.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn open_for(mut self, duration: Duration) -> Self {
+        self.config.open_for = duration;
+        self
+    }
+
+    /// Get the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        match self.shared.lock().unwrap().state {
+            State::Closed => CircuitState::Closed,
+            State::Open { until } if clock::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    // Returns `Err` with the time left open if the request shouldn't be let
+    // through, or whether it is a probe.
+    fn admit(&self) -> Result<bool, Duration> {
+        let mut circuit = self.shared.lock().unwrap();
+        match circuit.state {
+            State::Closed => Ok(false),
+            State::Open { until } => {
+                let now = clock::now();
+                if now < until {
+                    Err(until - now)
+                } else {
+                    circuit.state = State::HalfOpen { probing: true };
+                    Ok(true)
+                }
+            }
+            State::HalfOpen { probing: true } => Err(Duration::from_secs(0)),
+            State::HalfOpen { probing: false } => {
+                circuit.state = State::HalfOpen { probing: true };
+                Ok(true)
+            }
+        }
+    }
+
+    fn record(&self, probe: bool, failed: bool) {
+        let mut circuit = self.shared.lock().unwrap();
+        if probe {
+            if failed {
+                self.open(&mut circuit);
+            } else {
+                tracing::debug!("circuit closed");
+                circuit.outcomes.clear();
+                circuit.failures = 0;
+                circuit.state = State::Closed;
+            }
+            return;
+        }
+
+        // Requests started before the circuit opened may still finish.
+        if !matches!(circuit.state, State::Closed) {
+            return;
+        }
+
+        circuit.outcomes.push_back(failed);
+        circuit.failures += usize::from(failed);
+        if circuit.outcomes.len() > self.config.window {
+            let oldest = circuit.outcomes.pop_front().unwrap_or(false);
+            circuit.failures -= usize::from(oldest);
+        }
+
+        let total = circuit.outcomes.len();
+        if total >= self.config.min_requests
+            && circuit.failures as f64 >= self.config.failure_rate * total as f64
+        {
+            self.open(&mut circuit);
+        }
+    }
+
+    // A probe that never finished shouldn't keep the circuit half-open.
+    fn cancel_probe(&self) {
+        let mut circuit = self.shared.lock().unwrap();
+        if let State::HalfOpen { probing: true } = circuit.state {
+            circuit.state = State::HalfOpen { probing: false };
+        }
+    }
+
+    fn open(&self, circuit: &mut Circuit) {
+        tracing::debug!("circuit opened for {:?}", self.config.open_for);
+        circuit.outcomes.clear();
+        circuit.failures = 0;
+        circuit.state = State::Open {
+            until: clock::now() + self.config.open_for,
+        };
+    }
+}
+
+impl<F> Wrap<F> for CircuitBreaker
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithCircuitBreaker<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithCircuitBreaker {
+            filter,
+            breaker: self.clone(),
+        }
+    }
+}
+
+/// An error used to reject requests while a [`circuit_breaker`] is open.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    retry_after: Duration,
+}
+
+impl CircuitOpen {
+    /// How long until the circuit will let a request through again.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+
+    pub(crate) fn headers(&self, headers: &mut HeaderMap) {
+        let secs = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+        headers.insert(RETRY_AFTER, HeaderValue::from(secs));
+    }
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Circuit breaker is open")
+    }
+}
+
+impl StdError for CircuitOpen {}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::{pin_project, pinned_drop};
+
+    use super::{CircuitBreaker, CircuitOpen};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, IsReject, Rejection};
+    use crate::reply::{Reply, Response};
+
+    #[allow(missing_debug_implementations)]
+    pub struct Guarded(Response);
+
+    impl Reply for Guarded {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithCircuitBreaker<F> {
+        pub(super) filter: F,
+        pub(super) breaker: CircuitBreaker,
+    }
+
+    impl<F> FilterBase for WithCircuitBreaker<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Guarded,);
+        type Error = Rejection;
+        type Future = WithCircuitBreakerFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            match self.breaker.admit() {
+                Ok(probe) => WithCircuitBreakerFuture {
+                    future: Some(self.filter.filter(Internal)),
+                    breaker: self.breaker.clone(),
+                    probe,
+                    result: Err(std::time::Duration::from_secs(0)),
+                },
+                Err(retry_after) => WithCircuitBreakerFuture {
+                    future: None,
+                    breaker: self.breaker.clone(),
+                    probe: false,
+                    result: Err(retry_after),
+                },
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project(PinnedDrop)]
+    pub struct WithCircuitBreakerFuture<F> {
+        #[pin]
+        future: Option<F>,
+        breaker: CircuitBreaker,
+        probe: bool,
+        // `Ok` once the outcome has been recorded, or the time left open.
+        result: Result<(), std::time::Duration>,
+    }
+
+    impl<F> Future for WithCircuitBreakerFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Guarded,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut pin = self.project();
+            let future = match pin.future.as_mut().as_pin_mut() {
+                Some(future) => future,
+                None => {
+                    let retry_after = pin.result.err().unwrap_or_default();
+                    return Poll::Ready(Err(reject::known(CircuitOpen { retry_after })));
+                }
+            };
+            let (result, failed) = match ready!(future.try_poll(cx)) {
+                Ok(reply) => {
+                    let res = reply.into_response();
+                    let failed = res.status().is_server_error();
+                    (Ok((Guarded(res),)), failed)
+                }
+                Err(err) => {
+                    let rejection = err.into();
+                    let failed = rejection.status().is_server_error();
+                    (Err(rejection), failed)
+                }
+            };
+            pin.breaker.record(*pin.probe, failed);
+            *pin.result = Ok(());
+            pin.future.set(None);
+            Poll::Ready(result)
+        }
+    }
+
+    #[pinned_drop]
+    impl<F> PinnedDrop for WithCircuitBreakerFuture<F> {
+        fn drop(self: Pin<&mut Self>) {
+            if self.probe && self.result.is_err() {
+                self.breaker.cancel_probe();
+            }
+        }
+    }
+}
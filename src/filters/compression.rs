@@ -10,7 +10,7 @@ use hyper::{
 };
 use tokio_util::io::{ReaderStream, StreamReader};
 
-use crate::filter::{Filter, WrapSealed};
+use crate::filter::{Filter, Wrap};
 use crate::reject::IsReject;
 use crate::reply::{Reply, Response};
 
@@ -126,7 +126,7 @@ pub fn brotli() -> Compression<impl Fn(CompressionProps) -> Response + Copy> {
     Compression { func }
 }
 
-impl<FN, F> WrapSealed<F> for Compression<FN>
+impl<FN, F> Wrap<F> for Compression<FN>
 where
     FN: Fn(CompressionProps) -> Response + Clone + Send,
     F: Filter + Clone + Send,
@@ -0,0 +1,245 @@
+//! Concurrency limiting Filters
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+
+use self::internal::WithConcurrencyLimit;
+
+/// Create a wrapping filter that limits how many requests it runs at once.
+///
+/// At most `max` requests are inside the wrapped filter at any time. Once
+/// that many are in flight, further requests are rejected with a
+/// [`ConcurrencyLimited`] rejection, which replies with a
+/// `503 Service Unavailable`. Use [`ConcurrencyLimit::queue`] to let some of
+/// them wait for a slot instead.
+///
+/// The limit is shared by every filter wrapped with the same
+/// `ConcurrencyLimit`, or a clone of it.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Generate at most 4 reports at once, with up to 16 more waiting.
+/// let limit = warp::concurrency_limit(4).queue(16);
+///
+/// let route = warp::path("report")
+///     .map(warp::reply)
+///     .with(limit);
+/// ```
+pub fn concurrency_limit(max: usize) -> ConcurrencyLimit {
+    assert!(max > 0, "concurrency_limit max must be greater than 0");
+    ConcurrencyLimit {
+        shared: Arc::new(Shared {
+            semaphore: Arc::new(Semaphore::new(max)),
+            max,
+            queued: AtomicUsize::new(0),
+        }),
+        queue: 0,
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to limit concurrent requests.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyLimit {
+    shared: Arc<Shared>,
+    queue: usize,
+}
+
+#[derive(Debug)]
+struct Shared {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    queued: AtomicUsize,
+}
+
+impl ConcurrencyLimit {
+    /// Sets how many requests may wait for a slot once the limit is reached.
+    ///
+    /// Requests arriving while the queue is full are rejected. Defaults to
+    /// `0`, rejecting as soon as the limit is reached.
+    pub fn queue(mut self, depth: usize) -> Self {
+        self.queue = depth;
+        self
+    }
+
+    /// The number of requests currently running in the wrapped filter.
+    pub fn in_flight(&self) -> usize {
+        self.shared.max - self.shared.semaphore.available_permits()
+    }
+
+    /// The number of requests currently waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.shared.queued.load(Ordering::Acquire)
+    }
+}
+
+impl<F> Wrap<F> for ConcurrencyLimit
+where
+    F: Filter + Clone + Send,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithConcurrencyLimit<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithConcurrencyLimit {
+            filter,
+            limit: self.clone(),
+        }
+    }
+}
+
+/// An error used to reject requests over a [`concurrency_limit`].
+#[derive(Debug)]
+pub struct ConcurrencyLimited {
+    max: usize,
+}
+
+impl ConcurrencyLimited {
+    /// The number of requests allowed to run at once.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl fmt::Display for ConcurrencyLimited {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Too many concurrent requests (limit {})", self.max)
+    }
+}
+
+impl StdError for ConcurrencyLimited {}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+    use tokio::sync::{AcquireError, OwnedSemaphorePermit};
+
+    use super::{ConcurrencyLimit, ConcurrencyLimited, Shared};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithConcurrencyLimit<F> {
+        pub(super) filter: F,
+        pub(super) limit: ConcurrencyLimit,
+    }
+
+    impl<F> FilterBase for WithConcurrencyLimit<F>
+    where
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = WithConcurrencyLimitFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let shared = &self.limit.shared;
+            let state = match shared.semaphore.clone().try_acquire_owned() {
+                Ok(permit) => State::Filter(self.filter.filter(Internal), permit),
+                Err(_) => match Queued::enter(shared, self.limit.queue) {
+                    Some(queued) => State::Acquire(
+                        Box::pin(shared.semaphore.clone().acquire_owned()),
+                        self.filter.clone(),
+                        queued,
+                    ),
+                    None => State::Rejected(shared.max),
+                },
+            };
+            WithConcurrencyLimitFuture { state }
+        }
+    }
+
+    /// Holds a place in the queue, giving it up when dropped.
+    struct Queued(Arc<Shared>);
+
+    impl Queued {
+        fn enter(shared: &Arc<Shared>, depth: usize) -> Option<Queued> {
+            shared
+                .queued
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                    if queued < depth {
+                        Some(queued + 1)
+                    } else {
+                        None
+                    }
+                })
+                .ok()
+                .map(|_| Queued(shared.clone()))
+        }
+    }
+
+    impl Drop for Queued {
+        fn drop(&mut self) {
+            self.0.queued.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    type Acquire = Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>;
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithConcurrencyLimitFuture<F: Filter> {
+        #[pin]
+        state: State<F>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Acquire(Acquire, F, Queued),
+        Filter(#[pin] F::Future, OwnedSemaphorePermit),
+        Rejected(usize),
+        Done,
+    }
+
+    impl<F> Future for WithConcurrencyLimitFuture<F>
+    where
+        F: Filter,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<F::Extract, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut state = self.project().state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Acquire(acquire, filter, _) => {
+                        let permit = ready!(acquire.as_mut().poll(cx))
+                            .expect("concurrency_limit semaphore is never closed");
+                        let fut = filter.filter(Internal);
+                        state.set(State::Filter(fut, permit));
+                    }
+                    StateProj::Filter(fut, _) => {
+                        let result = ready!(fut.try_poll(cx)).map_err(Into::into);
+                        // Dropping the state releases the permit.
+                        state.set(State::Done);
+                        return Poll::Ready(result);
+                    }
+                    StateProj::Rejected(max) => {
+                        let max = *max;
+                        tracing::debug!("concurrency limit of {} reached", max);
+                        state.set(State::Done);
+                        return Poll::Ready(Err(reject::known(ConcurrencyLimited { max })));
+                    }
+                    StateProj::Done => panic!("polled after complete"),
+                }
+            }
+        }
+    }
+}
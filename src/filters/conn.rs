@@ -0,0 +1,95 @@
+//! Connection info filters.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use crate::filter::{filter_fn_one, Filter};
+
+/// Details about the connection a request arrived on.
+#[derive(Clone, Debug, Default)]
+pub struct Info {
+    pub(crate) local_addr: Option<SocketAddr>,
+    pub(crate) remote_addr: Option<SocketAddr>,
+    pub(crate) tls: Option<TlsInfo>,
+}
+
+/// Details about a negotiated TLS session.
+#[derive(Clone, Debug, Default)]
+pub struct TlsInfo {
+    pub(crate) alpn_protocol: Option<Vec<u8>>,
+    pub(crate) version: Option<String>,
+    pub(crate) cipher_suite: Option<String>,
+}
+
+impl Info {
+    /// The local address the connection was accepted on, if the transport
+    /// uses socket addresses.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// The address of the peer, if the transport uses socket addresses.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Whether the connection is secured with TLS.
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    /// Details about the TLS session, if the connection used TLS.
+    pub fn tls(&self) -> Option<&TlsInfo> {
+        self.tls.as_ref()
+    }
+}
+
+impl TlsInfo {
+    /// The protocol negotiated with ALPN, such as `b"h2"`.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// The negotiated TLS version, such as `"TLSv1_3"`.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The negotiated cipher suite, such as `"TLS13_AES_256_GCM_SHA384"`.
+    pub fn cipher_suite(&self) -> Option<&str> {
+        self.cipher_suite.as_deref()
+    }
+}
+
+/// Creates a `Filter` to get details about the connection of a request.
+///
+/// Transports that don't know some of the details, such as a custom incoming
+/// stream without socket addresses, will leave them empty.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::conn::info()
+///     .map(|info: warp::conn::Info| {
+///         if info.is_tls() {
+///             "secure"
+///         } else {
+///             "plain"
+///         }
+///     });
+/// ```
+pub fn info() -> impl Filter<Extract = (Info,), Error = Infallible> + Copy {
+    filter_fn_one(|route| {
+        let info = route
+            .extensions()
+            .get::<Info>()
+            .cloned()
+            .unwrap_or_else(|| Info {
+                remote_addr: route.remote_addr(),
+                ..Info::default()
+            });
+        futures::future::ok(info)
+    })
+}
@@ -1,13 +1,22 @@
 //! Cookie Filters
 
 use futures::future;
-use headers::Cookie;
+use headers::{Cookie as CookieHeader, Expires, Header};
+use http::HeaderValue;
+#[cfg(feature = "cookie-signed")]
+use ring::{aead, hkdf, hmac};
+#[cfg(feature = "cookie-signed")]
+use serde::de::DeserializeOwned;
 
 use super::header;
 use crate::filter::{Filter, One};
 use crate::reject::Rejection;
 use std::convert::Infallible;
+use std::fmt;
 use std::str::FromStr;
+#[cfg(feature = "cookie-signed")]
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 /// Creates a `Filter` that requires a cookie by name.
 ///
@@ -16,7 +25,7 @@ pub fn cookie<T>(name: &'static str) -> impl Filter<Extract = One<T>, Error = Re
 where
     T: FromStr + Send + 'static,
 {
-    header::header2().and_then(move |cookie: Cookie| {
+    header::header2().and_then(move |cookie: CookieHeader| {
         let cookie = cookie
             .get(name)
             .ok_or_else(|| crate::reject::missing_cookie(name))
@@ -35,7 +44,7 @@ pub fn optional<T>(
 where
     T: FromStr + Send + 'static,
 {
-    header::optional2().map(move |opt: Option<Cookie>| {
+    header::optional2().map(move |opt: Option<CookieHeader>| {
         let cookie = opt.and_then(|cookie| cookie.get(name).map(|x| T::from_str(x)));
         match cookie {
             Some(Ok(t)) => Some(t),
@@ -44,3 +53,433 @@ where
         }
     })
 }
+
+/// Creates a `Filter` that requires a cookie by name, signed with `key`.
+///
+/// The cookie must have been set with
+/// [`reply::with_signed_cookie`](crate::reply::with_signed_cookie), using
+/// `key` or one of its previous keys. If it's missing or its value can't be
+/// parsed, rejects like [`cookie`]. If its signature doesn't match, because
+/// the client changed it, rejects with an
+/// [`InvalidCookie`](crate::reject::InvalidCookie).
+///
+/// *This function requires the `"cookie-signed"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::cookie::Key;
+///
+/// let key = Key::generate();
+///
+/// let login = warp::path("login").map({
+///     let key = key.clone();
+///     move || warp::reply::with_signed_cookie(warp::reply(), "user", "sean", &key)
+/// });
+///
+/// let whoami = warp::path("whoami")
+///     .and(warp::cookie::signed::<String>("user", key))
+///     .map(|user| format!("hello, {}", user));
+/// ```
+#[cfg(feature = "cookie-signed")]
+pub fn signed<T>(
+    name: &'static str,
+    key: Key,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Clone
+where
+    T: FromStr + Send + 'static,
+{
+    header::header2().and_then(move |cookie: CookieHeader| {
+        let cookie = cookie
+            .get(name)
+            .ok_or_else(|| crate::reject::missing_cookie(name))
+            .and_then(|s| {
+                key.verify(name, s)
+                    .ok_or_else(|| crate::reject::invalid_cookie(name))
+            })
+            .and_then(|s| T::from_str(&s).map_err(|_| crate::reject::missing_cookie(name)));
+        future::ready(cookie)
+    })
+}
+
+/// Creates a `Filter` that requires a cookie by name, encrypted with `key`,
+/// and deserializes its value.
+///
+/// The cookie must have been set with
+/// [`reply::with_private_cookie`](crate::reply::with_private_cookie), using
+/// `key` or one of its previous keys, so its value is kept confidential
+/// from the client, and can't be changed. If it's missing, rejects like
+/// [`cookie`]. If it can't be decrypted or deserialized, rejects with an
+/// [`InvalidCookie`](crate::reject::InvalidCookie).
+///
+/// *This function requires the `"cookie-signed"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::{Deserialize, Serialize};
+/// use warp::Filter;
+/// use warp::cookie::Key;
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Cart {
+///     items: Vec<u32>,
+/// }
+///
+/// let key = Key::generate();
+///
+/// let cart = warp::path("cart")
+///     .and(warp::cookie::private::<Cart>("cart", key.clone()))
+///     .map(move |mut cart: Cart| {
+///         cart.items.push(42);
+///         warp::reply::with_private_cookie(warp::reply(), "cart", &cart, &key)
+///     });
+/// ```
+#[cfg(feature = "cookie-signed")]
+pub fn private<T>(
+    name: &'static str,
+    key: Key,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Clone
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    header::header2().and_then(move |cookie: CookieHeader| {
+        let cookie = cookie
+            .get(name)
+            .ok_or_else(|| crate::reject::missing_cookie(name))
+            .and_then(|s| {
+                key.decrypt(name, s)
+                    .and_then(|value| serde_json::from_slice(&value).ok())
+                    .ok_or_else(|| crate::reject::invalid_cookie(name))
+            });
+        future::ready(cookie)
+    })
+}
+
+/// A secret key to sign and encrypt cookies.
+///
+/// Keys can be rotated, with [`Key::with_previous`]: cookies are signed
+/// and encrypted with the new key, but those from previous keys are still
+/// accepted, until they expire and the previous keys can be dropped.
+///
+/// *This type requires the `"cookie-signed"` feature.*
+#[cfg(feature = "cookie-signed")]
+#[derive(Clone)]
+pub struct Key {
+    // The current secret first, then the previous ones.
+    secrets: Arc<Vec<Secret>>,
+}
+
+#[cfg(feature = "cookie-signed")]
+#[derive(Clone)]
+struct Secret {
+    signing: hmac::Key,
+    encryption: aead::LessSafeKey,
+}
+
+#[cfg(feature = "cookie-signed")]
+impl Key {
+    /// Derives a key from `secret`, which should come from a secure random
+    /// source, such as a secrets manager, so that every instance of the
+    /// server shares it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `secret` is shorter than 32 bytes.
+    pub fn from_secret(secret: &[u8]) -> Key {
+        assert!(
+            secret.len() >= 32,
+            "cookie key secrets must be at least 32 bytes"
+        );
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"warp cookie key").extract(secret);
+        let signing = prk
+            .expand(&[b"signed"], hmac::HMAC_SHA256)
+            .expect("hmac key length")
+            .into();
+        let encryption = aead::UnboundKey::from(
+            prk.expand(&[b"private"], &aead::AES_256_GCM)
+                .expect("aead key length"),
+        );
+        Key {
+            secrets: Arc::new(vec![Secret {
+                signing,
+                encryption: aead::LessSafeKey::new(encryption),
+            }]),
+        }
+    }
+
+    /// Generates a random key.
+    ///
+    /// Cookies signed with it are only accepted by this process, until it
+    /// exits.
+    pub fn generate() -> Key {
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).expect("cookie key needs a random number source");
+        Key::from_secret(&secret)
+    }
+
+    /// Also accepts cookies signed with `previous`, and its own previous
+    /// keys.
+    pub fn with_previous(self, previous: &Key) -> Key {
+        let secrets = self
+            .secrets
+            .iter()
+            .chain(previous.secrets.iter())
+            .cloned()
+            .collect();
+        Key {
+            secrets: Arc::new(secrets),
+        }
+    }
+
+    // The name is signed too, so a value can't be moved to another cookie.
+    pub(crate) fn sign(&self, name: &str, value: &str) -> String {
+        let value = base64::encode_config(value, base64::URL_SAFE_NO_PAD);
+        let tag = hmac::sign(
+            &self.secrets[0].signing,
+            signed_data(name, &value).as_bytes(),
+        );
+        format!(
+            "{}.{}",
+            value,
+            base64::encode_config(tag, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    fn verify(&self, name: &str, signed: &str) -> Option<String> {
+        let (value, tag) = signed.rsplit_once('.')?;
+        let tag = base64::decode_config(tag, base64::URL_SAFE_NO_PAD).ok()?;
+        let data = signed_data(name, value);
+        self.secrets
+            .iter()
+            .find(|secret| hmac::verify(&secret.signing, data.as_bytes(), &tag).is_ok())?;
+        let value = base64::decode_config(value, base64::URL_SAFE_NO_PAD).ok()?;
+        String::from_utf8(value).ok()
+    }
+
+    // The name is authenticated too, so a value can't be moved to another
+    // cookie.
+    pub(crate) fn encrypt(&self, name: &str, value: &[u8]) -> String {
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("cookie nonce needs a random number source");
+        let mut sealed = value.to_vec();
+        self.secrets[0]
+            .encryption
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(name.as_bytes()),
+                &mut sealed,
+            )
+            .expect("cookie value too large to encrypt");
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&sealed);
+        base64::encode_config(out, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn decrypt(&self, name: &str, encrypted: &str) -> Option<Vec<u8>> {
+        let encrypted = base64::decode_config(encrypted, base64::URL_SAFE_NO_PAD).ok()?;
+        if encrypted.len() < aead::NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = encrypted.split_at(aead::NONCE_LEN);
+        self.secrets.iter().find_map(|secret| {
+            let nonce = aead::Nonce::try_assume_unique_for_key(nonce).ok()?;
+            let mut sealed = sealed.to_vec();
+            let len = secret
+                .encryption
+                .open_in_place(nonce, aead::Aad::from(name.as_bytes()), &mut sealed)
+                .ok()?
+                .len();
+            sealed.truncate(len);
+            Some(sealed)
+        })
+    }
+}
+
+#[cfg(feature = "cookie-signed")]
+fn signed_data(name: &str, value: &str) -> String {
+    format!("{}={}", name, value)
+}
+
+#[cfg(feature = "cookie-signed")]
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("previous", &(self.secrets.len() - 1))
+            .finish()
+    }
+}
+
+/// A cookie to set on a reply, with
+/// [`reply::with_cookie`](crate::reply::with_cookie).
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::cookie::{Cookie, SameSite};
+///
+/// let cookie = Cookie::new("theme", "dark")
+///     .path("/")
+///     .max_age(Duration::from_secs(30 * 24 * 60 * 60))
+///     .secure(true)
+///     .http_only(true)
+///     .same_site(SameSite::Lax);
+///
+/// assert_eq!(
+///     cookie.to_string(),
+///     "theme=dark; Max-Age=2592000; Path=/; Secure; HttpOnly; SameSite=Lax",
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    expires: Option<SystemTime>,
+    max_age: Option<Duration>,
+    domain: Option<String>,
+    path: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+/// The `SameSite` attribute of a [`Cookie`], restricting which cross-site
+/// requests it's sent with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    /// Only sent with same-site requests.
+    Strict,
+    /// Also sent when navigating to the site from another one.
+    Lax,
+    /// Sent with all requests, which browsers only allow for `Secure`
+    /// cookies.
+    None,
+}
+
+impl Cookie {
+    /// Creates a cookie named `name`, with the value `value`, lasting until
+    /// the browser is closed.
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Cookie {
+        Cookie {
+            name: name.into(),
+            value: value.into(),
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Sets when the cookie expires.
+    pub fn expires(mut self, expires: SystemTime) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Sets how long the cookie lasts, which takes precedence over
+    /// [`expires`](Cookie::expires).
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the domain the cookie is sent to, including its subdomains.
+    ///
+    /// By default, it's only sent to the host that set it.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the path the cookie is sent to, including its subpaths.
+    ///
+    /// By default, it's the directory of the request path that set it.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets whether the cookie is only sent over HTTPS.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets whether the cookie is hidden from scripts.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the cookie.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// The name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The value of the cookie.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    // Names are tokens, and values can't have spaces, quotes, commas,
+    // semicolons or backslashes, which would let them add attributes.
+    pub(crate) fn header_value(&self) -> Option<HeaderValue> {
+        let token = |b: u8| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b);
+        let octet = |b: u8| b.is_ascii_graphic() && !b"\",;\\".contains(&b);
+        let attr = |s: &str| s.bytes().all(|b| b.is_ascii_graphic() && b != b';');
+        let valid = !self.name.is_empty()
+            && self.name.bytes().all(token)
+            && self.value.bytes().all(octet)
+            && self.domain.as_deref().map_or(true, attr)
+            && self.path.as_deref().map_or(true, attr);
+        if !valid {
+            tracing::error!("invalid cookie: {:?}", self);
+            return None;
+        }
+        HeaderValue::from_str(&self.to_string()).ok()
+    }
+}
+
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(expires) = self.expires {
+            let mut values = Vec::new();
+            Expires::from(expires).encode(&mut values);
+            let date = values[0].to_str().map_err(|_| fmt::Error)?;
+            write!(f, "; Expires={}", date)?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(ref domain) = self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(ref path) = self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        match self.same_site {
+            Some(SameSite::Strict) => f.write_str("; SameSite=Strict"),
+            Some(SameSite::Lax) => f.write_str("; SameSite=Lax"),
+            Some(SameSite::None) => f.write_str("; SameSite=None"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Content-Security-Policy Filters
+//!
+//! A policy is applied to replies by wrapping a filter with [`csp()`]. When
+//! the policy asks for nonces, every request gets a fresh one, which the
+//! handler extracts with [`nonce()`] to embed in its `<script>` or `<style>`
+//! tags.
+//!
+//! ```
+//! use warp::Filter;
+//!
+//! let policy = warp::csp()
+//!     .default_src(["'self'"])
+//!     .img_src(["'self'", "https://images.example.com"])
+//!     .script_nonce()
+//!     .report_uri("/csp-reports");
+//!
+//! let route = warp::path("page")
+//!     .and(warp::csp::nonce())
+//!     .map(|nonce: warp::csp::Nonce| {
+//!         warp::reply::html(format!(
+//!             "<script nonce=\"{}\">console.log('hi')</script>",
+//!             nonce,
+//!         ))
+//!     })
+//!     .with(policy);
+//! ```
+
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+
+use http::header::{
+    HeaderName, HeaderValue, CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY_REPORT_ONLY,
+};
+
+use crate::filter::{filter_fn_one, Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::Reply;
+use crate::route::Route;
+
+use self::internal::WithCsp;
+
+/// Create a wrapping filter that sets a `Content-Security-Policy` on replies.
+///
+/// The policy starts out empty; add directives with the builder methods.
+/// If the wrapped filter already set a policy, it is kept as is.
+///
+/// See the [module docs](mod@crate::csp) for an example.
+pub fn csp() -> ContentSecurityPolicy {
+    ContentSecurityPolicy {
+        directives: Vec::new(),
+        script_nonce: false,
+        style_nonce: false,
+        report_only: false,
+    }
+}
+
+/// Creates a `Filter` that extracts the CSP nonce of the request.
+///
+/// The nonce is generated the first time it is asked for, and the same one
+/// is added to the policy by an enclosing [`csp()`] wrapper that has
+/// [`script_nonce`](ContentSecurityPolicy::script_nonce) or
+/// [`style_nonce`](ContentSecurityPolicy::style_nonce) set.
+pub fn nonce() -> impl Filter<Extract = (Nonce,), Error = Infallible> + Copy {
+    filter_fn_one(|route| futures::future::ok(Nonce::of(route)))
+}
+
+/// A random value allowing inline scripts or styles for a single request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Nonce(Arc<str>);
+
+impl Nonce {
+    /// The nonce, as put in the `nonce` attribute of a tag.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn generate() -> Nonce {
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).expect("csp nonce needs a random number source");
+        Nonce(base64::encode(bytes).into())
+    }
+
+    fn of(route: &mut Route) -> Nonce {
+        if let Some(nonce) = route.extensions().get::<Nonce>() {
+            return nonce.clone();
+        }
+        let nonce = Nonce::generate();
+        route.extensions_mut().insert(nonce.clone());
+        nonce
+    }
+}
+
+impl fmt::Display for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to set a `Content-Security-Policy`.
+#[derive(Clone, Debug)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+    script_nonce: bool,
+    style_nonce: bool,
+    report_only: bool,
+}
+
+macro_rules! directives {
+    ($($(#[$attr:meta])* $method:ident => $name:expr,)+) => {
+        $(
+        $(#[$attr])*
+        pub fn $method<I>(self, sources: I) -> Self
+        where
+            I: IntoIterator,
+            I::Item: Into<String>,
+        {
+            self.directive($name, sources)
+        }
+        )+
+    };
+}
+
+impl ContentSecurityPolicy {
+    directives! {
+        /// Adds sources to the `default-src` directive.
+        default_src => "default-src",
+        /// Adds sources to the `script-src` directive.
+        script_src => "script-src",
+        /// Adds sources to the `style-src` directive.
+        style_src => "style-src",
+        /// Adds sources to the `img-src` directive.
+        img_src => "img-src",
+        /// Adds sources to the `connect-src` directive.
+        connect_src => "connect-src",
+        /// Adds sources to the `font-src` directive.
+        font_src => "font-src",
+        /// Adds sources to the `object-src` directive.
+        object_src => "object-src",
+        /// Adds sources to the `media-src` directive.
+        media_src => "media-src",
+        /// Adds sources to the `frame-src` directive.
+        frame_src => "frame-src",
+        /// Adds sources to the `frame-ancestors` directive.
+        frame_ancestors => "frame-ancestors",
+        /// Adds sources to the `base-uri` directive.
+        base_uri => "base-uri",
+        /// Adds sources to the `form-action` directive.
+        form_action => "form-action",
+    }
+
+    /// Adds values to any directive, such as `worker-src`.
+    ///
+    /// Adding to the same directive again appends to its values.
+    pub fn directive<I>(mut self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let values = values.into_iter().map(Into::into);
+        match self.directives.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => existing.extend(values),
+            None => self.directives.push((name.to_owned(), values.collect())),
+        }
+        self
+    }
+
+    /// Sets the `report-uri` directive.
+    pub fn report_uri(self, uri: impl Into<String>) -> Self {
+        self.directive("report-uri", Some(uri))
+    }
+
+    /// Adds the `upgrade-insecure-requests` directive.
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive("upgrade-insecure-requests", None::<String>)
+    }
+
+    /// Allows scripts carrying the request's [`Nonce`] in `script-src`.
+    pub fn script_nonce(mut self) -> Self {
+        self.script_nonce = true;
+        self
+    }
+
+    /// Allows styles carrying the request's [`Nonce`] in `style-src`.
+    pub fn style_nonce(mut self) -> Self {
+        self.style_nonce = true;
+        self
+    }
+
+    /// Sends the policy as `Content-Security-Policy-Report-Only`, so that
+    /// violations are only reported instead of blocked.
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    fn header_name(&self) -> HeaderName {
+        if self.report_only {
+            CONTENT_SECURITY_POLICY_REPORT_ONLY
+        } else {
+            CONTENT_SECURITY_POLICY
+        }
+    }
+
+    fn uses_nonce(&self) -> bool {
+        self.script_nonce || self.style_nonce
+    }
+
+    fn render(&self, nonce: Option<&Nonce>) -> Option<HeaderValue> {
+        let nonce = nonce.map(|nonce| format!("'nonce-{}'", nonce));
+        let mut directives = self.directives.clone();
+        if let Some(ref nonce) = nonce {
+            let with_nonce = [
+                ("script-src", self.script_nonce),
+                ("style-src", self.style_nonce),
+            ];
+            for &(name, _) in with_nonce.iter().filter(|(_, set)| *set) {
+                match directives.iter_mut().find(|(n, _)| n == name) {
+                    Some((_, values)) => values.push(nonce.clone()),
+                    None => directives.push((name.to_owned(), vec![nonce.clone()])),
+                }
+            }
+        }
+
+        let policy = directives
+            .iter()
+            .map(|(name, values)| {
+                let mut directive = name.clone();
+                for value in values {
+                    directive.push(' ');
+                    directive.push_str(value);
+                }
+                directive
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&policy)
+            .map_err(|_| tracing::error!("csp: invalid policy {:?}", policy))
+            .ok()
+    }
+}
+
+impl<F> Wrap<F> for ContentSecurityPolicy
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithCsp<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithCsp {
+            filter,
+            policy: Arc::new(self.clone()),
+        }
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{ContentSecurityPolicy, Nonce};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct WithPolicy(Response);
+
+    impl Reply for WithPolicy {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithCsp<F> {
+        pub(super) filter: F,
+        pub(super) policy: Arc<ContentSecurityPolicy>,
+    }
+
+    impl<F> FilterBase for WithCsp<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (WithPolicy,);
+        type Error = Rejection;
+        type Future = WithCspFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            // The nonce is made up front, so the wrapped filter extracts the
+            // same one that goes into the header.
+            let nonce = if self.policy.uses_nonce() {
+                Some(route::with(Nonce::of))
+            } else {
+                None
+            };
+            WithCspFuture {
+                future: self.filter.filter(Internal),
+                policy: self.policy.clone(),
+                nonce,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithCspFuture<F> {
+        #[pin]
+        future: F,
+        policy: Arc<ContentSecurityPolicy>,
+        nonce: Option<Nonce>,
+    }
+
+    impl<F> Future for WithCspFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(WithPolicy,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let reply = ready!(pin.future.try_poll(cx)).map_err(Into::into)?;
+            let mut res = reply.into_response();
+            let name = pin.policy.header_name();
+            if !res.headers().contains_key(&name) {
+                if let Some(value) = pin.policy.render(pin.nonce.as_ref()) {
+                    res.headers_mut().insert(name, value);
+                }
+            }
+            Poll::Ready(Ok((WithPolicy(res),)))
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! ETag Filters
+
+use bytes::Bytes;
+use http::header::{
+    HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_LOCATION, DATE, ETAG, EXPIRES, IF_NONE_MATCH,
+    VARY,
+};
+use http::StatusCode;
+use hyper::Body;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithEtag;
+
+/// Create a wrapping filter that tags replies with an `ETag`, and answers
+/// with `304 Not Modified` when the client already has the same reply.
+///
+/// The tag is a hash of the body, so handlers don't need to know anything
+/// about it. Replies that already have an `ETag` keep it, but are still
+/// checked against the request's `If-None-Match`.
+///
+/// Only successful replies to `GET` and `HEAD` requests with bodies already
+/// in memory are tagged; streaming bodies pass through untouched.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path("report")
+///     .map(|| warp::reply::json(&vec!["expensive", "but", "stable"]))
+///     .with(warp::etag());
+/// ```
+pub fn etag() -> Etag {
+    Etag { _p: () }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to tag replies with an `ETag`.
+#[derive(Clone, Copy, Debug)]
+pub struct Etag {
+    _p: (),
+}
+
+impl<F> Wrap<F> for Etag
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithEtag<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithEtag { filter }
+    }
+}
+
+// `DefaultHasher::new` always uses the same keys, so every instance of the
+// server gives a body the same tag.
+fn tag_for(body: &[u8]) -> HeaderValue {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(body);
+    let tag = format!("\"{:016x}\"", hasher.finish());
+    HeaderValue::from_str(&tag).expect("hex is a valid header value")
+}
+
+// `If-None-Match` uses the weak comparison, so `W/` prefixes are ignored.
+fn matches(if_none_match: &HeaderMap, etag: &HeaderValue) -> bool {
+    let etag = match etag.to_str() {
+        Ok(etag) => etag.trim_start_matches("W/"),
+        Err(_) => return false,
+    };
+    if_none_match
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+fn not_modified(res: &Response, etag: HeaderValue) -> Response {
+    let mut not_modified = Response::new(Body::empty());
+    *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+    let headers = not_modified.headers_mut();
+    for name in &[CACHE_CONTROL, CONTENT_LOCATION, DATE, EXPIRES, VARY] {
+        for value in res.headers().get_all(name) {
+            headers.append(name, value.clone());
+        }
+    }
+    headers.insert(ETAG, etag);
+    not_modified
+}
+
+fn finish(mut res: Response, body: Option<Bytes>, req_headers: &HeaderMap) -> Response {
+    let etag = match (res.headers().get(ETAG), body) {
+        (Some(etag), _) => etag.clone(),
+        (None, Some(ref body)) => tag_for(body),
+        (None, None) => return res,
+    };
+    if matches(req_headers, &etag) {
+        return not_modified(&res, etag);
+    }
+    res.headers_mut().insert(ETAG, etag);
+    res
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use bytes::Bytes;
+    use futures::{ready, TryFuture};
+    use http::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+    use http::Method;
+    use hyper::body::HttpBody;
+    use hyper::Body;
+    use pin_project::pin_project;
+
+    use super::finish;
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Tagged(Response);
+
+    impl Reply for Tagged {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithEtag<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithEtag<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Tagged,);
+        type Error = Rejection;
+        type Future = WithEtagFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            // Only the conditional headers are kept, not the whole map.
+            let req_headers = route::with(|route| {
+                if route.method() != Method::GET && route.method() != Method::HEAD {
+                    return None;
+                }
+                let mut headers = HeaderMap::new();
+                for value in route.headers().get_all(IF_NONE_MATCH) {
+                    headers.append(IF_NONE_MATCH, value.clone());
+                }
+                Some(headers)
+            });
+            WithEtagFuture {
+                state: State::Filter(self.filter.filter(Internal)),
+                req_headers,
+            }
+        }
+    }
+
+    type Buffer = Pin<Box<dyn Future<Output = Result<Bytes, hyper::Error>> + Send>>;
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithEtagFuture<F> {
+        #[pin]
+        state: State<F>,
+        req_headers: Option<HeaderMap>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F> {
+        Filter(#[pin] F),
+        Buffer(Buffer, Option<http::response::Parts>),
+    }
+
+    impl<F> Future for WithEtagFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Tagged,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Filter(fut) => {
+                        let res = ready!(fut.try_poll(cx))
+                            .map_err(Into::into)?
+                            .into_response();
+                        let req_headers = match pin.req_headers {
+                            Some(ref req_headers) if res.status().is_success() => req_headers,
+                            _ => return Poll::Ready(Ok((Tagged(res),))),
+                        };
+                        let in_memory = res.body().size_hint().exact().is_some();
+                        if res.headers().contains_key(ETAG) || !in_memory {
+                            let res = finish(res, None, req_headers);
+                            return Poll::Ready(Ok((Tagged(res),)));
+                        }
+                        let (parts, body) = res.into_parts();
+                        state.set(State::Buffer(
+                            Box::pin(hyper::body::to_bytes(body)),
+                            Some(parts),
+                        ));
+                    }
+                    StateProj::Buffer(buffer, parts) => {
+                        let result = ready!(buffer.as_mut().poll(cx));
+                        let parts = parts.take().expect("polled after complete");
+                        let res = match result {
+                            Ok(body) => {
+                                let res = Response::from_parts(parts, Body::from(body.clone()));
+                                let req_headers = pin.req_headers.as_ref().expect("checked");
+                                finish(res, Some(body), req_headers)
+                            }
+                            Err(err) => {
+                                tracing::debug!("etag: error buffering body: {}", err);
+                                Response::from_parts(parts, Body::empty())
+                            }
+                        };
+                        return Poll::Ready(Ok((Tagged(res),)));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,15 @@
 //! Request Extensions
+//!
+//! Extensions carry values along with a request, from the filters that
+//! [`set`] or [`store`] them to the ones that [`get`] them.
 
 use std::convert::Infallible;
 
 use futures::future;
 
-use crate::filter::{filter_fn_one, Filter};
+use crate::filter::{filter_fn, filter_fn_one, Filter};
 use crate::reject::{self, Rejection};
+use crate::route;
 
 /// Get a previously set extension of the current route.
 ///
@@ -30,6 +34,69 @@ pub fn optional<T: Clone + Send + Sync + 'static>(
     filter_fn_one(|route| future::ok(route.extensions().get::<T>().cloned()))
 }
 
+/// Create a `Filter` that sets a clone of `value` as an extension of the
+/// request, for later filters to [`get`].
+///
+/// It replaces any extension of the same type.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// #[derive(Clone)]
+/// struct Region(&'static str);
+///
+/// let route = warp::path("eu")
+///     .and(warp::ext::set(Region("eu-west")))
+///     .and(warp::ext::get::<Region>())
+///     .map(|region: Region| region.0);
+/// ```
+pub fn set<T>(value: T) -> impl Filter<Extract = (), Error = Infallible> + Clone
+where
+    T: Clone + Send + Sync + 'static,
+{
+    filter_fn(move |route| {
+        route.extensions_mut().insert(value.clone());
+        future::ok(())
+    })
+}
+
+/// Create a `Filter` that sets the value extracted by `filter` as an
+/// extension of the request, instead of extracting it.
+///
+/// This lets a filter, such as one authenticating the request, leave a
+/// value for later filters that are otherwise unrelated to it, and that
+/// [`get`] it.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// #[derive(Clone)]
+/// struct User(String);
+///
+/// let auth = warp::header::<String>("x-user").map(User);
+///
+/// let whoami = warp::ext::get::<User>().map(|user: User| user.0);
+///
+/// let route = warp::ext::store(auth).and(whoami);
+/// ```
+pub fn store<F, T>(filter: F) -> impl Filter<Extract = (), Error = F::Error> + Clone
+where
+    F: Filter<Extract = (T,)> + Clone,
+    T: Send + Sync + 'static,
+{
+    filter
+        .map(|value: T| {
+            route::with(|route| {
+                route.extensions_mut().insert(value);
+            })
+        })
+        .untuple_one()
+}
+
 unit_error! {
     /// An error used to reject if `get` cannot find the extension.
     pub MissingExtension: "Missing request extension"
@@ -0,0 +1,284 @@
+//! Fault injection
+//!
+//! Filters that make a service misbehave on purpose, to check that its
+//! clients handle slow responses, server errors, and broken connections,
+//! such as by retrying or timing out. Meant for tests and staging, not for
+//! production.
+
+use std::io;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{future, stream, StreamExt};
+use http::header::{HeaderValue, CONTENT_LENGTH};
+use http::StatusCode;
+use hyper::body::HttpBody;
+use hyper::Body;
+
+use crate::filter::{Filter, Wrap};
+use crate::filters::sample::random;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithFault;
+
+/// Create a wrapping filter that injects the faults of `config` into
+/// replies.
+///
+/// Each fault happens independently, with its own probability, so that a
+/// request can be both slow and fail.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::fault::Config;
+/// use warp::http::StatusCode;
+/// use warp::Filter;
+///
+/// let faults = Config::new()
+///     .latency(0.2, Duration::from_millis(500))
+///     .error(0.05, StatusCode::SERVICE_UNAVAILABLE)
+///     .abort(0.01);
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::fault::inject(faults));
+/// ```
+pub fn inject(config: Config) -> Fault {
+    Fault { config }
+}
+
+/// Which faults to inject, and how often.
+///
+/// Probabilities are between `0.0`, never, and `1.0`, every request. The
+/// default is to inject no faults.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Config {
+    latency: Option<(f64, Duration)>,
+    error: Option<(f64, StatusCode)>,
+    abort: f64,
+    truncate: f64,
+}
+
+impl Config {
+    /// Creates a config injecting no faults.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Delay requests by `delay` with a probability of `probability`,
+    /// before they are handled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` isn't between `0.0` and `1.0`.
+    pub fn latency(mut self, probability: f64, delay: Duration) -> Self {
+        self.latency = Some((check(probability), delay));
+        self
+    }
+
+    /// Reply to requests with an empty `status` response with a probability
+    /// of `probability`, without handling them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` isn't between `0.0` and `1.0`.
+    pub fn error(mut self, probability: f64, status: StatusCode) -> Self {
+        self.error = Some((check(probability), status));
+        self
+    }
+
+    /// Abort the connection of requests with a probability of `probability`,
+    /// closing it without sending a response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` isn't between `0.0` and `1.0`.
+    pub fn abort(mut self, probability: f64) -> Self {
+        self.abort = check(probability);
+        self
+    }
+
+    /// Truncate the body of responses with a probability of `probability`,
+    /// closing the connection after half of the first chunk of the body was
+    /// sent.
+    ///
+    /// The `content-length` of the full body is kept, when it's known, so
+    /// clients can tell the body is incomplete.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` isn't between `0.0` and `1.0`.
+    pub fn truncate(mut self, probability: f64) -> Self {
+        self.truncate = check(probability);
+        self
+    }
+
+    fn roll(&self) -> Faults {
+        Faults {
+            delay: self
+                .latency
+                .filter(|&(p, _)| random() < p)
+                .map(|(_, delay)| delay),
+            error: self.error.filter(|&(p, _)| random() < p).map(|(_, s)| s),
+            abort: random() < self.abort,
+            truncate: random() < self.truncate,
+        }
+    }
+}
+
+fn check(probability: f64) -> f64 {
+    assert!(
+        (0.0..=1.0).contains(&probability),
+        "fault probability must be between 0 and 1"
+    );
+    probability
+}
+
+/// Decorates a [`Filter`](crate::Filter) to inject faults into its replies.
+#[derive(Clone, Copy, Debug)]
+pub struct Fault {
+    config: Config,
+}
+
+impl<F> Wrap<F> for Fault
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+{
+    type Wrapped = WithFault<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithFault {
+            filter,
+            config: self.config,
+        }
+    }
+}
+
+// The faults picked for a request.
+#[derive(Debug)]
+struct Faults {
+    delay: Option<Duration>,
+    error: Option<StatusCode>,
+    abort: bool,
+    truncate: bool,
+}
+
+impl Faults {
+    fn apply(self, res: Response) -> Response {
+        if self.abort {
+            tracing::debug!("fault injected: connection aborted");
+            let (head, _) = res.into_parts();
+            let body = stream::once(future::ready(Err::<Bytes, _>(io::Error::other(
+                "fault injected: connection aborted",
+            ))));
+            return Response::from_parts(head, Body::wrap_stream(body));
+        }
+        if self.truncate {
+            tracing::debug!("fault injected: body truncated");
+            let (mut head, mut body) = res.into_parts();
+            if let Some(len) = body.size_hint().exact() {
+                head.headers
+                    .entry(CONTENT_LENGTH)
+                    .or_insert_with(|| HeaderValue::from(len));
+            }
+            let half = stream::once(async move {
+                match body.data().await {
+                    Some(Ok(chunk)) => Ok(chunk.slice(..chunk.len() / 2)),
+                    Some(Err(err)) => Err(io::Error::other(err)),
+                    None => Ok(Bytes::new()),
+                }
+            });
+            // Yield first, so the server flushes what it was given before
+            // the connection is closed.
+            let cut = stream::once(async {
+                tokio::task::yield_now().await;
+                Err(io::Error::other("fault injected: body truncated"))
+            });
+            return Response::from_parts(head, Body::wrap_stream(half.chain(cut)));
+        }
+        res
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+    use tokio::time::Sleep;
+
+    use super::{Config, Faults};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reply::{Reply, Response};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithFault<F> {
+        pub(super) filter: F,
+        pub(super) config: Config,
+    }
+
+    impl<F> FilterBase for WithFault<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+    {
+        type Extract = (Response,);
+        type Error = F::Error;
+        type Future = WithFaultFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let faults = self.config.roll();
+            WithFaultFuture {
+                delay: faults
+                    .delay
+                    .map(|delay| Box::pin(tokio::time::sleep(delay))),
+                future: self.filter.filter(Internal),
+                faults: Some(faults),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithFaultFuture<F> {
+        delay: Option<Pin<Box<Sleep>>>,
+        #[pin]
+        future: F,
+        faults: Option<Faults>,
+    }
+
+    impl<F> Future for WithFaultFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+    {
+        type Output = Result<(Response,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let faults = pin.faults.as_ref().expect("polled after complete");
+            if let Some(delay) = pin.delay {
+                ready!(delay.as_mut().poll(cx));
+                tracing::debug!("fault injected: delayed by {:?}", faults.delay);
+                *pin.delay = None;
+            }
+
+            if let Some(status) = faults.error {
+                tracing::debug!("fault injected: {} error", status);
+                pin.faults.take();
+                let mut res = Response::default();
+                *res.status_mut() = status;
+                return Poll::Ready(Ok((res,)));
+            }
+
+            let reply = ready!(pin.future.try_poll(cx))?;
+            let faults = pin.faults.take().expect("polled after complete");
+            Poll::Ready(Ok((faults.apply(reply.into_response()),)))
+        }
+    }
+}
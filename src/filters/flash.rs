@@ -0,0 +1,77 @@
+//! Flash message Filters
+//!
+//! Flash messages are set while handling a request, and shown on the next
+//! one, such as a "Saved!" message after a form posts and redirects. They
+//! are kept in the [session](mod@crate::session), so routes using them must be
+//! wrapped with a [`session`](crate::session()) filter.
+
+use crate::filter::Filter;
+use crate::filters::session::{self, Session};
+use crate::reject::Rejection;
+
+// The session key holding the message for the next request.
+const KEY: &str = "warp.flash";
+
+/// Creates a `Filter` that extracts the [`Flash`] of the request.
+///
+/// The message set by the previous request is taken out of the session
+/// when extracted, so it's only shown once.
+///
+/// Rejects with a `500 Internal Server Error` if the filter isn't wrapped
+/// with a [`session`](crate::session()) filter.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::flash::Flash;
+/// use warp::http::Uri;
+///
+/// let save = warp::post()
+///     .and(warp::path("settings"))
+///     .and(warp::flash())
+///     .map(|flash: Flash| {
+///         flash.set("Saved!");
+///         warp::redirect::see_other(Uri::from_static("/settings"))
+///     });
+///
+/// let show = warp::get()
+///     .and(warp::path("settings"))
+///     .and(warp::flash())
+///     .map(|flash: Flash| {
+///         let message = flash.message().unwrap_or_default();
+///         warp::reply::html(format!("<p>{}</p><form method=post>...</form>", message))
+///     });
+///
+/// let routes = save
+///     .or(show)
+///     .with(warp::session(warp::session::MemoryStore::default()));
+/// ```
+pub fn flash() -> impl Filter<Extract = (Flash,), Error = Rejection> + Copy {
+    session::current().map(|session: Session| {
+        let message = session.remove(KEY);
+        Flash { session, message }
+    })
+}
+
+/// The flash message of a request, extracted with [`flash`].
+#[derive(Clone, Debug)]
+pub struct Flash {
+    session: Session,
+    message: Option<String>,
+}
+
+impl Flash {
+    /// The message set by the previous request, if any.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Sets the message to show on the next request, replacing any set
+    /// before.
+    pub fn set(&self, message: impl Into<String>) {
+        self.session
+            .insert(KEY, message.into())
+            .expect("strings serialize as JSON");
+    }
+}
@@ -312,8 +312,7 @@ fn file_conditional(
                     .map(|(start, end)| {
                         let sub_len = end - start;
                         let buf_size = optimal_buf_size(&meta);
-                        let stream = file_stream(file, buf_size, (start, end));
-                        let body = Body::wrap_stream(stream);
+                        let body = file_body(file, buf_size, (start, end));
 
                         let mut resp = Response::new(body);
 
@@ -391,6 +390,22 @@ fn bytes_range(range: Option<Range>, max_len: u64) -> Result<(u64, u64), BadRang
     ret
 }
 
+fn file_body(file: TkFile, buf_size: usize, range: (u64, u64)) -> Body {
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    let file = if crate::uring::is_active() {
+        match file.try_into_std() {
+            Ok(file) => {
+                return Body::wrap_stream(crate::uring::file_stream(file, buf_size, range));
+            }
+            Err(file) => file,
+        }
+    } else {
+        file
+    };
+
+    Body::wrap_stream(file_stream(file, buf_size, range))
+}
+
 fn file_stream(
     mut file: TkFile,
     buf_size: usize,
@@ -3,18 +3,30 @@
 //! These filters are used to interact with the Request HTTP headers. Some
 //! of them, like `exact` and `exact_ignore_case`, are just predicates,
 //! they don't extract any values. The `header` filter allows parsing
-//! a type from any header.
+//! a type from any header, and the `typed` filter decoding one of the
+//! typed headers of the `headers` crate.
+use std::borrow::Cow;
 use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::fmt;
 use std::str::FromStr;
 
 use futures::future;
 use headers::{Header, HeaderMapExt};
-use http::header::HeaderValue;
+use http::header::{HeaderValue, ACCEPT_LANGUAGE};
 use http::HeaderMap;
 
 use crate::filter::{filter_fn, filter_fn_one, Filter, One};
 use crate::reject::{self, Rejection};
 
+pub use self::forwarded::{Forwarded, ForwardedElement, Node};
+#[cfg(feature = "user-agent")]
+pub use self::user_agent::{user_agent, DeviceClass, UserAgent};
+
+mod forwarded;
+#[cfg(feature = "user-agent")]
+mod user_agent;
+
 /// Create a `Filter` that tries to parse the specified header.
 ///
 /// This `Filter` will look for a header with supplied name, and try to
@@ -104,6 +116,67 @@ where
     filter_fn_one(move |route| future::ready(Ok(route.headers().typed_get())))
 }
 
+/// Create a `Filter` that extracts a typed header, from the
+/// [`headers`](https://docs.rs/headers) crate.
+///
+/// Rejects if the header is missing, or can't be decoded as an `H`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::headers::{CacheControl, IfMatch, Range};
+///
+/// let range = warp::header::typed::<Range>();
+///
+/// let cacheable = warp::header::typed::<CacheControl>()
+///     .map(|cache_control: CacheControl| !cache_control.no_store());
+///
+/// let update = warp::put()
+///     .and(warp::header::typed::<IfMatch>())
+///     .map(|_if_match: IfMatch| "updated");
+/// ```
+pub fn typed<H: Header + Send + 'static>() -> impl Filter<Extract = One<H>, Error = Rejection> + Copy
+{
+    filter_fn_one(move |route| {
+        tracing::trace!("typed({:?})", H::name());
+        let name = H::name().as_str();
+        let route = route
+            .headers()
+            .typed_try_get()
+            .map_err(|_| reject::invalid_header(name))
+            .and_then(|header| header.ok_or_else(|| reject::missing_header(name)));
+        future::ready(route)
+    })
+}
+
+/// Create a `Filter` that extracts a typed header, from the
+/// [`headers`](https://docs.rs/headers) crate, if it exists.
+///
+/// If the header does not exist, it yields `None`. If it can't be decoded
+/// as an `H`, the request is rejected.
+///
+/// # Example
+///
+/// ```
+/// use warp::headers::IfNoneMatch;
+///
+/// let if_none_match = warp::header::typed_optional::<IfNoneMatch>();
+/// ```
+pub fn typed_optional<H>() -> impl Filter<Extract = One<Option<H>>, Error = Rejection> + Copy
+where
+    H: Header + Send + 'static,
+{
+    filter_fn_one(move |route| {
+        tracing::trace!("typed_optional({:?})", H::name());
+        let route = route
+            .headers()
+            .typed_try_get()
+            .map_err(|_| reject::invalid_header(H::name().as_str()));
+        future::ready(route)
+    })
+}
+
 /* TODO
 pub fn exact2<T>(header: T) -> impl FilterClone<Extract=(), Error=Rejection>
 where
@@ -228,3 +301,199 @@ pub fn value(
 pub fn headers_cloned() -> impl Filter<Extract = One<HeaderMap>, Error = Infallible> + Copy {
     filter_fn_one(|route| future::ok(route.headers().clone()))
 }
+
+/// Create a `Filter` that negotiates the language of the reply, extracting
+/// the one of `supported` the client prefers.
+///
+/// The ranges of the `Accept-Language` header are tried by their q-value,
+/// highest first. A range matches a tag equal to it, or starting with it,
+/// so `en` matches `en-US`; if none does, it's shortened, so `en-GB`
+/// matches `en`. Tags the client marks with `q=0` aren't picked.
+///
+/// If the header is missing, or nothing in it is supported, the first of
+/// `supported` is extracted, as the default.
+///
+/// # Panics
+///
+/// Panics if `supported` is empty.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::header::LanguageTag;
+///
+/// let supported = [LanguageTag::from_static("en"), LanguageTag::from_static("fr")];
+///
+/// let route = warp::header::accept_language(&supported)
+///     .map(|lang: LanguageTag| {
+///         let greeting = if lang.as_str() == "fr" { "Bonjour" } else { "Hello" };
+///         warp::reply::with_content_language(greeting, &lang)
+///     });
+/// ```
+pub fn accept_language(
+    supported: &[LanguageTag],
+) -> impl Filter<Extract = One<LanguageTag>, Error = Infallible> + Clone {
+    assert!(
+        !supported.is_empty(),
+        "accept_language needs at least one supported language"
+    );
+    let supported = supported.to_vec();
+    filter_fn_one(|route| future::ok(route.headers().get(ACCEPT_LANGUAGE).cloned())).map(
+        move |header: Option<HeaderValue>| {
+            let header = header.as_ref().and_then(|value| value.to_str().ok());
+            tracing::trace!("accept_language({:?})", header);
+            header
+                .and_then(|header| negotiate_language(header, &supported))
+                .unwrap_or(&supported[0])
+                .clone()
+        },
+    )
+}
+
+fn negotiate_language<'a>(header: &str, supported: &'a [LanguageTag]) -> Option<&'a LanguageTag> {
+    let mut ranges = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let range = parts
+                .next()
+                .filter(|range| *range == "*" || range.parse::<LanguageTag>().is_ok())?;
+            let mut q = 1.0;
+            for param in parts {
+                if let Some(value) = param
+                    .strip_prefix("q=")
+                    .or_else(|| param.strip_prefix("Q="))
+                {
+                    q = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|q| (0.0..=1.0).contains(q))?;
+                }
+            }
+            Some((range, q))
+        })
+        .collect::<Vec<_>>();
+    // Stable, so ranges with the same q-value keep the client's order.
+    ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).expect("q-values are finite"));
+
+    let refused = |tag: &LanguageTag| {
+        ranges
+            .iter()
+            .any(|&(range, q)| q == 0.0 && range != "*" && tag.matches(range))
+    };
+    let mut candidates = supported.iter().filter(|tag| !refused(tag));
+
+    for &(range, q) in &ranges {
+        if q == 0.0 {
+            break;
+        }
+        if range == "*" {
+            return candidates.next();
+        }
+        if let Some(tag) = candidates.clone().find(|tag| tag.matches(range)) {
+            return Some(tag);
+        }
+        let mut prefix = range;
+        while let Some(end) = prefix.rfind('-') {
+            prefix = &prefix[..end];
+            if let Some(tag) = candidates
+                .clone()
+                .find(|tag| tag.as_str().eq_ignore_ascii_case(prefix))
+            {
+                return Some(tag);
+            }
+        }
+    }
+    None
+}
+
+/// A language tag, such as `en` or `pt-BR`, negotiated by
+/// [`accept_language`].
+///
+/// Tags compare ignoring ASCII case, as `Accept-Language` ranges do.
+#[derive(Clone, Debug)]
+pub struct LanguageTag(Cow<'static, str>);
+
+impl LanguageTag {
+    /// Creates a `LanguageTag` from a static string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` isn't a valid language tag.
+    pub fn from_static(tag: &'static str) -> LanguageTag {
+        if !is_language_tag(tag) {
+            panic!("invalid language tag: {:?}", tag);
+        }
+        LanguageTag(Cow::Borrowed(tag))
+    }
+
+    /// The tag as a string, as it was created.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The primary language subtag, such as `pt` for `pt-BR`.
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+
+    // Whether the `Accept-Language` range matches this tag, by being equal
+    // to it, or to a prefix of it ending at a `-`.
+    fn matches(&self, range: &str) -> bool {
+        let tag = self.as_str();
+        tag.len() >= range.len()
+            && tag.is_char_boundary(range.len())
+            && tag[..range.len()].eq_ignore_ascii_case(range)
+            && (tag.len() == range.len() || tag.as_bytes()[range.len()] == b'-')
+    }
+}
+
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    let valid = |subtag: &str, f: fn(&u8) -> bool| {
+        (1..=8).contains(&subtag.len()) && subtag.as_bytes().iter().all(f)
+    };
+    valid(primary, u8::is_ascii_alphabetic) && subtags.all(|s| valid(s, u8::is_ascii_alphanumeric))
+}
+
+impl FromStr for LanguageTag {
+    type Err = InvalidLanguageTag;
+
+    fn from_str(s: &str) -> Result<LanguageTag, InvalidLanguageTag> {
+        if is_language_tag(s) {
+            Ok(LanguageTag(Cow::Owned(s.to_owned())))
+        } else {
+            Err(InvalidLanguageTag { _p: () })
+        }
+    }
+}
+
+impl PartialEq for LanguageTag {
+    fn eq(&self, other: &LanguageTag) -> bool {
+        self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+}
+
+impl Eq for LanguageTag {}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An error parsing a [`LanguageTag`].
+#[derive(Debug)]
+pub struct InvalidLanguageTag {
+    _p: (),
+}
+
+impl fmt::Display for InvalidLanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid language tag")
+    }
+}
+
+impl StdError for InvalidLanguageTag {}
@@ -0,0 +1,362 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use headers::{Error, Header};
+use http::header::{HeaderName, HeaderValue, FORWARDED};
+
+/// The `Forwarded` header of [RFC 7239](https://tools.ietf.org/html/rfc7239),
+/// where each proxy on the way records a hop.
+///
+/// It's a typed header, so it's extracted with
+/// [`header::typed`](crate::header::typed). A header that doesn't follow
+/// the RFC is rejected as a whole, since the hops after the first mistake
+/// can't be told apart from ones a client made up.
+///
+/// To find the address of the client behind trusted proxies, use
+/// [`addr::client_ip`](crate::addr::client_ip), which can read this header.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::header::Forwarded;
+///
+/// let route = warp::header::typed::<Forwarded>()
+///     .map(|forwarded: Forwarded| {
+///         let hosts = forwarded
+///             .elements()
+///             .iter()
+///             .filter_map(|element| element.host())
+///             .collect::<Vec<_>>();
+///         format!("forwarded for hosts {:?}", hosts)
+///     });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forwarded {
+    elements: Vec<ForwardedElement>,
+}
+
+/// One hop of a [`Forwarded`] header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForwardedElement {
+    by: Option<Node>,
+    for_: Option<Node>,
+    host: Option<String>,
+    proto: Option<String>,
+}
+
+/// The client or proxy in a `for` or `by` parameter of a
+/// [`ForwardedElement`].
+///
+/// It's an IP address, `unknown`, or an obfuscated identifier starting with
+/// `_`, optionally followed by a port, which may be obfuscated too.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    raw: String,
+    ip: Option<IpAddr>,
+    port: Option<u16>,
+}
+
+impl Forwarded {
+    /// The elements of the header, in the order the proxies added them: the
+    /// first one was added by the proxy the client connected to.
+    pub fn elements(&self) -> &[ForwardedElement] {
+        &self.elements
+    }
+}
+
+impl ForwardedElement {
+    /// The interface the request came in on at the proxy, from `by`.
+    pub fn by_node(&self) -> Option<&Node> {
+        self.by.as_ref()
+    }
+
+    /// The client making the request to the proxy, from `for`.
+    pub fn for_node(&self) -> Option<&Node> {
+        self.for_.as_ref()
+    }
+
+    /// The `Host` header of the request received by the proxy, from `host`.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// The scheme of the request received by the proxy, such as `https`,
+    /// from `proto`.
+    pub fn proto(&self) -> Option<&str> {
+        self.proto.as_deref()
+    }
+}
+
+impl Node {
+    /// The node as it was sent, such as `"[2001:db8::1]:4711"` or `_hidden`.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// The IP address of the node, unless it's unknown or obfuscated.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+
+    /// The port of the node, unless it's missing or obfuscated.
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Whether the node is `unknown`.
+    pub fn is_unknown(&self) -> bool {
+        self.name().eq_ignore_ascii_case("unknown")
+    }
+
+    /// Whether the node is an obfuscated identifier.
+    pub fn is_obfuscated(&self) -> bool {
+        self.name().starts_with('_')
+    }
+
+    fn name(&self) -> &str {
+        match self.raw.rfind(']') {
+            Some(end) => &self.raw[..=end],
+            None => self.raw.split(':').next().unwrap_or_default(),
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Node> {
+        let (name, port) = if let Some(v6) = raw.strip_prefix('[') {
+            let end = v6.find(']')?;
+            (&raw[..end + 2], v6[end + 1..].strip_prefix(':'))
+        } else {
+            match raw.split_once(':') {
+                Some((name, port)) => (name, Some(port)),
+                None => (raw, None),
+            }
+        };
+        if raw.len() > name.len() && port.is_none() {
+            return None;
+        }
+
+        let ip = if let Some(v6) = name.strip_prefix('[') {
+            Some(IpAddr::V6(v6.strip_suffix(']')?.parse::<Ipv6Addr>().ok()?))
+        } else if name.eq_ignore_ascii_case("unknown") || is_obfuscated(name) {
+            None
+        } else {
+            Some(IpAddr::V4(name.parse::<Ipv4Addr>().ok()?))
+        };
+        let port = match port {
+            Some(port) if is_obfuscated(port) => None,
+            Some(port) if (1..=5).contains(&port.len()) && is_digits(port) => {
+                Some(port.parse().ok()?)
+            }
+            Some(_) => return None,
+            None => None,
+        };
+        Some(Node {
+            raw: raw.to_owned(),
+            ip,
+            port,
+        })
+    }
+}
+
+// `obfnode` and `obfport`: `_` followed by letters, digits, `.`, `_` or `-`.
+fn is_obfuscated(s: &str) -> bool {
+    s.len() > 1
+        && s.starts_with('_')
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'_' || b == b'-')
+}
+
+fn is_digits(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+fn is_scheme(s: &str) -> bool {
+    s.bytes().next().is_some_and(|b| b.is_ascii_alphabetic())
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.')
+}
+
+fn is_host(s: &str) -> bool {
+    !s.is_empty() && !s.contains('@') && s.parse::<http::uri::Authority>().is_ok()
+}
+
+// A cursor over one header value, following the grammar of RFC 7239:
+//
+//     Forwarded         = 1#forwarded-element
+//     forwarded-element = [ forwarded-pair ] *( ";" [ forwarded-pair ] )
+//     forwarded-pair    = token "=" value
+//     value             = token / quoted-string
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn skip_ows(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn token(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        while self.peek().is_some_and(is_tchar) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return None;
+        }
+        std::str::from_utf8(&self.s[start..self.pos]).ok()
+    }
+
+    fn quoted_string(&mut self) -> Option<String> {
+        self.pos += 1;
+        let mut value = Vec::new();
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => {
+                    self.pos += 1;
+                    value.push(self.peek()?);
+                }
+                b => value.push(b),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        String::from_utf8(value).ok()
+    }
+
+    fn value(&mut self) -> Option<String> {
+        if self.peek() == Some(b'"') {
+            self.quoted_string()
+        } else {
+            self.token().map(str::to_owned)
+        }
+    }
+
+    // Parses the pairs of an element, until a `,` or the end, and keeps it
+    // unless it was empty.
+    fn element(&mut self, elements: &mut Vec<ForwardedElement>) -> Option<()> {
+        let mut element = ForwardedElement::default();
+        let mut empty = true;
+        loop {
+            self.skip_ows();
+            if !matches!(self.peek(), Some(b';') | Some(b',') | None) {
+                let key = self.token()?;
+                if self.peek() != Some(b'=') {
+                    return None;
+                }
+                self.pos += 1;
+                let value = self.value()?;
+                let duplicate = match key.to_ascii_lowercase().as_str() {
+                    "by" => element.by.replace(Node::parse(&value)?).is_some(),
+                    "for" => element.for_.replace(Node::parse(&value)?).is_some(),
+                    "host" if is_host(&value) => element.host.replace(value).is_some(),
+                    "proto" if is_scheme(&value) => element.proto.replace(value).is_some(),
+                    "host" | "proto" => return None,
+                    // Extensions are allowed, but not known.
+                    _ => false,
+                };
+                if duplicate {
+                    return None;
+                }
+                empty = false;
+                self.skip_ows();
+            }
+            match self.peek() {
+                Some(b';') => self.pos += 1,
+                Some(b',') | None => break,
+                Some(_) => return None,
+            }
+        }
+        if !empty {
+            elements.push(element);
+        }
+        Some(())
+    }
+
+    fn elements(&mut self, elements: &mut Vec<ForwardedElement>) -> Option<()> {
+        loop {
+            self.element(elements)?;
+            match self.peek() {
+                Some(_) => self.pos += 1,
+                None => return Some(()),
+            }
+        }
+    }
+}
+
+impl Header for Forwarded {
+    fn name() -> &'static HeaderName {
+        &FORWARDED
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let mut elements = Vec::new();
+        for value in values {
+            let mut parser = Parser {
+                s: value.as_bytes(),
+                pos: 0,
+            };
+            parser.elements(&mut elements).ok_or_else(Error::invalid)?;
+        }
+        if elements.is_empty() {
+            return Err(Error::invalid());
+        }
+        Ok(Forwarded { elements })
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = HeaderValue::from_str(&self.to_string())
+            .expect("Forwarded is encoded as a valid header value");
+        values.extend(std::iter::once(value));
+    }
+}
+
+impl fmt::Display for Forwarded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, element) in self.elements.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(element, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ForwardedElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs = [
+            ("by", self.by.as_ref().map(Node::as_str)),
+            ("for", self.for_.as_ref().map(Node::as_str)),
+            ("host", self.host()),
+            ("proto", self.proto()),
+        ];
+        let mut first = true;
+        for (key, value) in pairs.iter().filter_map(|&(k, v)| Some((k, v?))) {
+            if !first {
+                f.write_str(";")?;
+            }
+            first = false;
+            if value.bytes().all(is_tchar) {
+                write!(f, "{}={}", key, value)?;
+            } else {
+                write!(f, "{}=\"{}\"", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::filter::{Filter, One};
+use crate::reject::Rejection;
+
+/// Create a `Filter` that parses the `User-Agent` header into a
+/// [`UserAgent`].
+///
+/// Rejects if the header is missing. Use
+/// `warp::header::optional::<UserAgent>("user-agent")` for clients that
+/// may not send one.
+///
+/// Requires the `user-agent` feature.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::header::{DeviceClass, UserAgent};
+///
+/// let route = warp::header::user_agent()
+///     .map(|agent: UserAgent| match agent.device_class() {
+///         DeviceClass::Mobile => "mobile layout",
+///         _ => "desktop layout",
+///     });
+/// ```
+pub fn user_agent() -> impl Filter<Extract = One<UserAgent>, Error = Rejection> + Copy {
+    super::header("user-agent")
+}
+
+/// A `User-Agent` header, parsed into the browser or client that sent it,
+/// the operating system it runs on, and the kind of device.
+///
+/// Parsing is a best effort: the parts that aren't recognized are `None`,
+/// so parsing never fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserAgent {
+    raw: String,
+    product: Option<String>,
+    version: Option<String>,
+    os: Option<String>,
+    device_class: DeviceClass,
+}
+
+/// The kind of device a [`UserAgent`] runs on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceClass {
+    /// A desktop or laptop computer.
+    Desktop,
+    /// A phone.
+    Mobile,
+    /// A tablet.
+    Tablet,
+    /// A crawler, or another automated client.
+    Bot,
+    /// The device couldn't be recognized.
+    Unknown,
+}
+
+// Browsers and the tokens naming them, before the ones they're built on,
+// since most also claim to be Chrome or Safari.
+const PRODUCTS: &[(&str, &str)] = &[
+    ("Edg", "Edge"),
+    ("EdgA", "Edge"),
+    ("EdgiOS", "Edge"),
+    ("Edge", "Edge"),
+    ("OPR", "Opera"),
+    ("SamsungBrowser", "Samsung Internet"),
+    ("Firefox", "Firefox"),
+    ("FxiOS", "Firefox"),
+    ("CriOS", "Chrome"),
+    ("Chromium", "Chromium"),
+    ("Chrome", "Chrome"),
+];
+
+const BOTS: &[&str] = &["bot", "crawl", "spider", "slurp", "facebookexternalhit"];
+
+impl UserAgent {
+    /// The header as it was sent.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// The browser or client, such as `Firefox` or `curl`.
+    pub fn product(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
+    /// The version of the [`product`](UserAgent::product), such as `91.0`.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The operating system, such as `Windows`, `macOS` or `Android`.
+    pub fn os(&self) -> Option<&str> {
+        self.os.as_deref()
+    }
+
+    /// The kind of device.
+    pub fn device_class(&self) -> DeviceClass {
+        self.device_class
+    }
+
+    fn parse(raw: &str) -> UserAgent {
+        let lower = raw.to_ascii_lowercase();
+        let bot = BOTS.iter().any(|bot| lower.contains(bot));
+
+        let (product, version) =
+            first_product(raw, |name| bot && name.to_ascii_lowercase().contains("bot"))
+                .or_else(|| {
+                    PRODUCTS.iter().find_map(|&(token, name)| {
+                        let version = token_version(raw, token)?;
+                        Some((name, version))
+                    })
+                })
+                .or_else(|| {
+                    let version = token_version(raw, "Version")?;
+                    token_version(raw, "Safari").map(|_| ("Safari", version))
+                })
+                .or_else(|| {
+                    let msie = lower.find("msie ")?;
+                    let version = raw[msie + 5..].split(&[';', ')'][..]).next()?;
+                    Some(("Internet Explorer", version))
+                })
+                .or_else(|| {
+                    let version = token_version(raw, "Trident").and(lower.find("rv:"))?;
+                    let version = raw[version + 3..].split(&[';', ')'][..]).next()?;
+                    Some(("Internet Explorer", version))
+                })
+                .or_else(|| first_product(raw, |name| name != "Mozilla"))
+                .map_or((None, None), |(product, version)| {
+                    (Some(product.to_owned()), Some(version.trim().to_owned()))
+                });
+
+        let os = [
+            ("windows phone", "Windows Phone"),
+            ("windows", "Windows"),
+            ("android", "Android"),
+            ("iphone", "iOS"),
+            ("ipad", "iOS"),
+            ("ipod", "iOS"),
+            ("cros", "Chrome OS"),
+            ("mac os x", "macOS"),
+            ("macintosh", "macOS"),
+            ("linux", "Linux"),
+        ]
+        .iter()
+        .find(|(needle, _)| lower.contains(needle))
+        .map(|&(_, os)| os.to_owned());
+
+        let device_class = if bot {
+            DeviceClass::Bot
+        } else if lower.contains("ipad")
+            || lower.contains("tablet")
+            || (lower.contains("android") && !lower.contains("mobi"))
+        {
+            DeviceClass::Tablet
+        } else if lower.contains("mobi") || lower.contains("iphone") || lower.contains("ipod") {
+            DeviceClass::Mobile
+        } else {
+            match os.as_deref() {
+                Some("Windows") | Some("macOS") | Some("Linux") | Some("Chrome OS") => {
+                    DeviceClass::Desktop
+                }
+                _ => DeviceClass::Unknown,
+            }
+        };
+
+        UserAgent {
+            raw: raw.to_owned(),
+            product,
+            version,
+            os,
+            device_class,
+        }
+    }
+}
+
+// The version of the `name/version` product token called `name`.
+fn token_version<'a>(raw: &'a str, name: &str) -> Option<&'a str> {
+    products(raw).find(|&(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn first_product(raw: &str, pred: impl Fn(&str) -> bool) -> Option<(&str, &str)> {
+    products(raw).find(|&(name, _)| pred(name))
+}
+
+// The `name/version` tokens of the header, including those in comments,
+// such as `Googlebot/2.1` in `(compatible; Googlebot/2.1; ...)`.
+fn products(raw: &str) -> impl Iterator<Item = (&str, &str)> {
+    raw.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ';' || c == ',')
+        .filter_map(|token| {
+            let mut parts = token.splitn(2, '/');
+            let name = parts.next().filter(|name| !name.is_empty())?;
+            let version = parts.next().filter(|version| !version.is_empty())?;
+            Some((name, version))
+        })
+}
+
+impl FromStr for UserAgent {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<UserAgent, Infallible> {
+        Ok(UserAgent::parse(s))
+    }
+}
+
+impl fmt::Display for UserAgent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
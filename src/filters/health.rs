@@ -0,0 +1,261 @@
+//! Health check Filters
+//!
+//! [`builder()`] registers named checks, and creates the `/healthz`,
+//! `/readyz`, and `/livez` endpoints that run them.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::{self, BoxFuture, FutureExt};
+use http::StatusCode;
+use serde_json::{json, Map, Value};
+
+use crate::filter::Filter;
+use crate::reject::Rejection;
+use crate::reply::{self, Reply};
+
+/// Create a [`Builder`] to register health checks with.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// async fn ping_db() -> Result<(), String> {
+///     // ...
+///     Ok(())
+/// }
+///
+/// let health = warp::health::builder()
+///     .readiness("db", Duration::from_secs(1), ping_db)
+///     .liveness("event_loop", Duration::from_millis(100), || async {
+///         Ok::<_, String>(())
+///     })
+///     .build();
+///
+/// let api = warp::path("hello").map(|| "Hello, World!");
+///
+/// let routes = health.routes().or(api);
+/// ```
+pub fn builder() -> Builder {
+    Builder { checks: Vec::new() }
+}
+
+/// A builder of [`Health`] endpoints.
+///
+/// Constructed by calling [`builder()`].
+#[derive(Clone, Debug)]
+pub struct Builder {
+    checks: Vec<Check>,
+}
+
+/// The health check endpoints, created by [`Builder::build`].
+///
+/// Each endpoint runs its checks concurrently, and replies with a JSON body
+/// like this one, with a `200 OK` if every check passed, or a
+/// `503 Service Unavailable` if any failed or timed out:
+///
+/// ```json
+/// {
+///   "status": "fail",
+///   "checks": {
+///     "db": { "status": "pass", "duration_ms": 1.2 },
+///     "queue": { "status": "fail", "duration_ms": 1000.1, "error": "timed out after 1s" }
+///   }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Health {
+    checks: Arc<Vec<Check>>,
+}
+
+type CheckFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+#[derive(Clone)]
+struct Check {
+    name: &'static str,
+    kind: Kind,
+    timeout: Duration,
+    func: CheckFn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Readiness,
+    Liveness,
+}
+
+struct Outcome {
+    name: &'static str,
+    duration: Duration,
+    result: Result<(), String>,
+}
+
+impl Builder {
+    /// Registers a check of whether the server is ready to handle requests,
+    /// such as whether its database can be reached.
+    ///
+    /// Readiness checks are run by `/readyz` and `/healthz`. The check fails
+    /// if it returns an error, or doesn't finish within `timeout`.
+    pub fn readiness<F, Fut, E>(self, name: &'static str, timeout: Duration, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        self.check(name, Kind::Readiness, timeout, check)
+    }
+
+    /// Registers a check of whether the server is working at all, and should
+    /// be restarted if not.
+    ///
+    /// Liveness checks are run by `/livez` and `/healthz`. The check fails if
+    /// it returns an error, or doesn't finish within `timeout`.
+    pub fn liveness<F, Fut, E>(self, name: &'static str, timeout: Duration, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        self.check(name, Kind::Liveness, timeout, check)
+    }
+
+    fn check<F, Fut, E>(
+        mut self,
+        name: &'static str,
+        kind: Kind,
+        timeout: Duration,
+        check: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        self.checks.push(Check {
+            name,
+            kind,
+            timeout,
+            func: Arc::new(move || check().map(|r| r.map_err(|e| e.to_string())).boxed()),
+        });
+        self
+    }
+
+    /// Builds the [`Health`] endpoints.
+    pub fn build(self) -> Health {
+        Health {
+            checks: Arc::new(self.checks),
+        }
+    }
+}
+
+impl Health {
+    /// Create a filter for `GET /healthz`, which runs every check.
+    pub fn healthz(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        self.endpoint("healthz", None)
+    }
+
+    /// Create a filter for `GET /readyz`, which runs the readiness checks.
+    pub fn readyz(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        self.endpoint("readyz", Some(Kind::Readiness))
+    }
+
+    /// Create a filter for `GET /livez`, which runs the liveness checks.
+    pub fn livez(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        self.endpoint("livez", Some(Kind::Liveness))
+    }
+
+    /// Create a filter for all of `/healthz`, `/readyz`, and `/livez`.
+    pub fn routes(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        self.endpoint("healthz", None)
+            .or(self.endpoint("readyz", Some(Kind::Readiness)))
+            .unify()
+            .or(self.endpoint("livez", Some(Kind::Liveness)))
+            .unify()
+    }
+
+    fn endpoint(
+        &self,
+        path: &'static str,
+        kind: Option<Kind>,
+    ) -> impl Filter<Extract = (reply::WithStatus<reply::Json>,), Error = Rejection> + Clone {
+        let checks = self.checks.clone();
+        crate::path(path)
+            .and(crate::path::end())
+            .and(crate::get().or(crate::head()).unify())
+            .and_then(move || {
+                let checks = checks.clone();
+                async move { Ok::<_, Rejection>(run(&checks, kind).await) }
+            })
+    }
+}
+
+async fn run(checks: &[Check], kind: Option<Kind>) -> reply::WithStatus<reply::Json> {
+    let outcomes = future::join_all(
+        checks
+            .iter()
+            .filter(|check| kind.map_or(true, |kind| check.kind == kind))
+            .map(Check::run),
+    )
+    .await;
+
+    let passed = outcomes.iter().all(|outcome| outcome.result.is_ok());
+    let mut details = Map::new();
+    for outcome in outcomes {
+        let mut detail = json!({
+            "status": status(outcome.result.is_ok()),
+            "duration_ms": outcome.duration.as_secs_f64() * 1000.0,
+        });
+        if let Err(error) = outcome.result {
+            detail["error"] = Value::String(error);
+        }
+        details.insert(outcome.name.to_owned(), detail);
+    }
+
+    let body = json!({
+        "status": status(passed),
+        "checks": details,
+    });
+    let code = if passed {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    reply::with_status(reply::json(&body), code)
+}
+
+fn status(passed: bool) -> &'static str {
+    if passed {
+        "pass"
+    } else {
+        "fail"
+    }
+}
+
+impl Check {
+    async fn run(&self) -> Outcome {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, (self.func)()).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {:?}", self.timeout)),
+        };
+        Outcome {
+            name: self.name,
+            duration: started.elapsed(),
+            result,
+        }
+    }
+}
+
+impl fmt::Debug for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Check")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
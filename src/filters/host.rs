@@ -30,6 +30,101 @@ pub fn exact(expected: &str) -> impl Filter<Extract = (), Error = Rejection> + C
         .untuple_one()
 }
 
+/// Creates a `Filter` that requires the request's host to match `pattern`.
+///
+/// The pattern is a host name, such as `api.example.com`, or a wildcard
+/// matching any subdomain, such as `*.example.com`. Hosts are compared
+/// ignoring case. The port is only compared if the pattern has one.
+///
+/// Authority is specified either in the `Host` header or in the target URI,
+/// including the `:authority` of HTTP/2 requests.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let tenants = warp::host::matches("*.example.com").map(|| "a tenant");
+/// ```
+pub fn matches(pattern: &str) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let pattern = HostPattern::new(pattern);
+    optional()
+        .and_then(move |option: Option<Authority>| match option {
+            Some(ref authority) if pattern.subdomain(authority).is_some() => future::ok(()),
+            _ => future::err(reject::not_found()),
+        })
+        .untuple_one()
+}
+
+/// Creates a `Filter` that requires the request's host to be a subdomain of
+/// `domain`, and extracts the subdomain.
+///
+/// Hosts are compared ignoring case, and the subdomain is extracted in
+/// lowercase.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Host: acme.example.com
+/// let tenant = warp::host::subdomain("example.com")
+///     .map(|tenant: String| format!("welcome, {}", tenant));
+/// ```
+pub fn subdomain(domain: &str) -> impl Filter<Extract = One<String>, Error = Rejection> + Clone {
+    let pattern = HostPattern::new(&format!("*.{}", domain));
+    optional().and_then(move |option: Option<Authority>| {
+        let subdomain = option
+            .as_ref()
+            .and_then(|authority| pattern.subdomain(authority))
+            .ok_or_else(reject::not_found);
+        future::ready(subdomain)
+    })
+}
+
+#[derive(Clone, Debug)]
+struct HostPattern {
+    wildcard: bool,
+    host: String,
+    port: Option<u16>,
+}
+
+impl HostPattern {
+    fn new(pattern: &str) -> HostPattern {
+        let (wildcard, rest) = match pattern.strip_prefix("*.") {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let authority = Authority::from_str(rest).expect("invalid host pattern");
+        HostPattern {
+            wildcard,
+            host: authority.host().to_ascii_lowercase(),
+            port: authority.port_u16(),
+        }
+    }
+
+    // The subdomain matched by the pattern, empty if it isn't a wildcard.
+    fn subdomain(&self, authority: &Authority) -> Option<String> {
+        if self.port.is_some() && self.port != authority.port_u16() {
+            return None;
+        }
+        let host = authority.host().to_ascii_lowercase();
+        if !self.wildcard {
+            return if host == self.host {
+                Some(String::new())
+            } else {
+                None
+            };
+        }
+        let subdomain = host.strip_suffix(&self.host)?.strip_suffix('.')?;
+        if subdomain.is_empty() {
+            None
+        } else {
+            Some(subdomain.to_owned())
+        }
+    }
+}
+
 /// Creates a `Filter` that looks for an authority (target server's host
 /// and port) in the request.
 ///
@@ -94,3 +189,63 @@ pub fn optional() -> impl Filter<Extract = One<Option<Authority>>, Error = Rejec
         })
     })
 }
+
+/// Dispatches whole filter trees by the request's host.
+///
+/// Each arm is a host pattern, as accepted by [`host::matches`](crate::host::matches),
+/// and the filter serving it. Arms are tried in order, and a final `_` arm
+/// serves requests for any other host.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let api = warp::path("users").map(|| "users");
+/// let tenants = warp::any().map(|| "tenant site");
+/// let www = warp::any().map(|| "home page");
+///
+/// let routes = warp::vhost! {
+///     "api.example.com" => api,
+///     "*.example.com" => tenants,
+///     _ => www,
+/// };
+/// ```
+#[macro_export]
+macro_rules! vhost {
+    ($($arms:tt)+) => ({
+        $crate::__internal_vhost!(@start $($arms)+)
+    });
+}
+
+#[doc(hidden)]
+#[macro_export]
+// not public API
+macro_rules! __internal_vhost {
+    (@start _ => $($rest:tt)*) => (
+        compile_error!("vhost! needs a host before the '_' arm")
+    );
+    (@start $host:literal => $routes:expr $(,)?) => (
+        $crate::__internal_vhost!(@arm $host => $routes)
+    );
+    (@start $host:literal => $routes:expr, $($rest:tt)+) => (
+        $crate::__internal_vhost!(@munch $crate::__internal_vhost!(@arm $host => $routes); $($rest)+)
+    );
+
+    (@munch $acc:expr; _ => $fallback:expr $(,)?) => (
+        $crate::Filter::or($acc, $fallback)
+    );
+    (@munch $acc:expr; $host:literal => $routes:expr $(,)?) => (
+        $crate::Filter::or($acc, $crate::__internal_vhost!(@arm $host => $routes))
+    );
+    (@munch $acc:expr; $host:literal => $routes:expr, $($rest:tt)+) => (
+        $crate::__internal_vhost!(
+            @munch $crate::Filter::or($acc, $crate::__internal_vhost!(@arm $host => $routes));
+            $($rest)+
+        )
+    );
+
+    (@arm $host:literal => $routes:expr) => (
+        $crate::Filter::and($crate::host::matches($host), $routes)
+    );
+}
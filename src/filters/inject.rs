@@ -0,0 +1,70 @@
+//! Shared state Filters
+//!
+//! Handlers often need some state shared by every request, like a database
+//! pool or a configuration. [`inject`] extracts a clone of such state for
+//! each request, instead of writing `warp::any().map(move || state.clone())`.
+
+use std::convert::Infallible;
+
+use futures::future;
+
+use crate::filter::{filter_fn, Filter};
+
+/// Create a `Filter` that extracts a clone of `state` for every request.
+///
+/// Wrap the state in an `Arc` if it's costly to clone.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use warp::Filter;
+///
+/// struct Db {
+///     name: String,
+/// }
+///
+/// let db = Arc::new(Db { name: "users".into() });
+///
+/// let route = warp::path("db")
+///     .and(warp::inject(db))
+///     .map(|db: Arc<Db>| db.name.clone());
+/// ```
+pub fn inject<T>(state: T) -> impl Filter<Extract = (T,), Error = Infallible> + Clone
+where
+    T: Clone + Send + Sync + 'static,
+{
+    filter_fn(move |_| future::ok((state.clone(),)))
+}
+
+/// Create a `Filter` that extracts the request extension of type `T` if
+/// there is one, or else a clone of `state`.
+///
+/// This lets filters that run before, such as one setting the extension
+/// with [`ext::set`](crate::ext::set), override the state for some
+/// requests, like to use another tenant's database.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// #[derive(Clone)]
+/// struct Tenant(&'static str);
+///
+/// let route = warp::inject::overridable(Tenant("default"))
+///     .map(|tenant: Tenant| tenant.0);
+/// ```
+pub fn overridable<T>(state: T) -> impl Filter<Extract = (T,), Error = Infallible> + Clone
+where
+    T: Clone + Send + Sync + 'static,
+{
+    filter_fn(move |route| {
+        let state = route
+            .extensions()
+            .get::<T>()
+            .cloned()
+            .unwrap_or_else(|| state.clone());
+        future::ok((state,))
+    })
+}
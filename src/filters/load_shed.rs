@@ -0,0 +1,375 @@
+//! Load shedding Filters
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use tokio::time::Instant;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+
+use self::internal::WithLoadShed;
+
+/// Create a wrapping filter that sheds load when the server is overwhelmed.
+///
+/// The wrapper keeps track of how many requests are pending in the wrapped
+/// filter, and of a moving average of how long they take. Once either goes
+/// over its threshold, new requests are rejected with an [`Overloaded`]
+/// rejection, which replies with a `503 Service Unavailable` and a
+/// `Retry-After` header.
+///
+/// While shedding because of latency, one request per
+/// [`retry_after`](LoadShed::retry_after) period is still let through, so
+/// the average can recover once the handler speeds up again.
+///
+/// No thresholds are set by default, so configure at least one of
+/// [`max_pending`](LoadShed::max_pending) and
+/// [`max_latency`](LoadShed::max_latency).
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let shed = warp::load_shed()
+///     .max_pending(100)
+///     .max_latency(Duration::from_millis(500))
+///     .on_update(|load| {
+///         println!("pending = {}, shedding = {}", load.pending(), load.is_shedding());
+///     });
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(shed);
+/// ```
+pub fn load_shed() -> LoadShed {
+    LoadShed {
+        shared: Arc::new(Shared {
+            pending: AtomicUsize::new(0),
+            stats: Mutex::new(Stats {
+                latency: None,
+                last_probe: None,
+            }),
+        }),
+        config: Config {
+            max_pending: None,
+            max_latency: None,
+            retry_after: Duration::from_secs(1),
+            on_update: None,
+        },
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to shed load when overwhelmed.
+///
+/// Filters wrapped with the same `LoadShed`, or a clone of it, share their
+/// pending count and latency average.
+#[derive(Clone)]
+pub struct LoadShed {
+    shared: Arc<Shared>,
+    config: Config,
+}
+
+#[derive(Clone)]
+struct Config {
+    max_pending: Option<usize>,
+    max_latency: Option<Duration>,
+    retry_after: Duration,
+    on_update: Option<Arc<dyn Fn(Load) + Send + Sync>>,
+}
+
+struct Shared {
+    pending: AtomicUsize,
+    stats: Mutex<Stats>,
+}
+
+struct Stats {
+    latency: Option<Duration>,
+    last_probe: Option<Instant>,
+}
+
+/// A snapshot of the load seen by a [`load_shed`] filter.
+#[derive(Clone, Copy, Debug)]
+pub struct Load {
+    pending: usize,
+    latency: Option<Duration>,
+    shedding: bool,
+}
+
+impl Load {
+    /// The number of requests currently in the wrapped filter.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// The moving average of how long requests take, if any have finished.
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// Whether new requests are currently being rejected.
+    pub fn is_shedding(&self) -> bool {
+        self.shedding
+    }
+}
+
+// Weight given to each new latency sample in the moving average.
+const LATENCY_WEIGHT: f64 = 0.3;
+
+impl LoadShed {
+    /// Sheds load once this many requests are pending in the wrapped filter.
+    pub fn max_pending(mut self, max: usize) -> Self {
+        self.config.max_pending = Some(max);
+        self
+    }
+
+    /// Sheds load once the average time taken by requests goes over `max`.
+    pub fn max_latency(mut self, max: Duration) -> Self {
+        self.config.max_latency = Some(max);
+        self
+    }
+
+    /// Sets the `Retry-After` sent with rejections, which is also how often a
+    /// request is let through to probe the latency while shedding.
+    ///
+    /// Defaults to 1 second.
+    pub fn retry_after(mut self, after: Duration) -> Self {
+        self.config.retry_after = after;
+        self
+    }
+
+    /// Calls `func` with the current [`Load`] whenever a request finishes or
+    /// is shed, for example to export it as metrics.
+    pub fn on_update<F>(mut self, func: F) -> Self
+    where
+        F: Fn(Load) + Send + Sync + 'static,
+    {
+        self.config.on_update = Some(Arc::new(func));
+        self
+    }
+
+    /// Get a snapshot of the current load.
+    pub fn load(&self) -> Load {
+        let stats = self.shared.stats.lock().unwrap();
+        let pending = self.shared.pending.load(Ordering::Acquire);
+        Load {
+            pending,
+            latency: stats.latency,
+            shedding: self.is_over(pending, stats.latency),
+        }
+    }
+
+    fn is_over(&self, pending: usize, latency: Option<Duration>) -> bool {
+        let over_pending = matches!(self.config.max_pending, Some(max) if pending >= max);
+        let over_latency = match (self.config.max_latency, latency) {
+            (Some(max), Some(latency)) => latency > max,
+            _ => false,
+        };
+        over_pending || over_latency
+    }
+
+    // Decide whether to admit a new request, counting it as pending if so.
+    fn admit(&self) -> bool {
+        // The check and the increment are one step, so that concurrent
+        // requests can't all take the last slot.
+        let max_pending = self.config.max_pending;
+        let reserved =
+            self.shared
+                .pending
+                .fetch_update(
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    |pending| match max_pending {
+                        Some(max) if pending >= max => None,
+                        _ => Some(pending + 1),
+                    },
+                );
+        if reserved.is_err() {
+            return false;
+        }
+
+        if let Some(max) = self.config.max_latency {
+            let mut stats = self.shared.stats.lock().unwrap();
+            if matches!(stats.latency, Some(latency) if latency > max) {
+                let now = Instant::now();
+                if matches!(stats.last_probe, Some(last) if now < last + self.config.retry_after) {
+                    self.shared.pending.fetch_sub(1, Ordering::AcqRel);
+                    return false;
+                }
+                stats.last_probe = Some(now);
+            }
+        }
+
+        true
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let mut stats = self.shared.stats.lock().unwrap();
+        stats.latency = Some(match stats.latency {
+            Some(avg) => avg.mul_f64(1.0 - LATENCY_WEIGHT) + elapsed.mul_f64(LATENCY_WEIGHT),
+            None => elapsed,
+        });
+    }
+
+    fn updated(&self) {
+        if let Some(ref on_update) = self.config.on_update {
+            on_update(self.load());
+        }
+    }
+}
+
+impl fmt::Debug for LoadShed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LoadShed")
+            .field("max_pending", &self.config.max_pending)
+            .field("max_latency", &self.config.max_latency)
+            .field("retry_after", &self.config.retry_after)
+            .field("load", &self.load())
+            .finish()
+    }
+}
+
+impl<F> Wrap<F> for LoadShed
+where
+    F: Filter + Clone + Send,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithLoadShed<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithLoadShed {
+            filter,
+            shed: self.clone(),
+        }
+    }
+}
+
+/// An error used to reject requests shed by a [`load_shed`] filter.
+#[derive(Debug)]
+pub struct Overloaded {
+    retry_after: Duration,
+}
+
+impl Overloaded {
+    /// How long the client should wait before trying again.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+
+    pub(crate) fn headers(&self, headers: &mut HeaderMap) {
+        let secs = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+        headers.insert(RETRY_AFTER, HeaderValue::from(secs));
+    }
+}
+
+impl fmt::Display for Overloaded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Server is overloaded")
+    }
+}
+
+impl StdError for Overloaded {}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::Ordering;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+    use tokio::time::Instant;
+
+    use super::{LoadShed, Overloaded};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithLoadShed<F> {
+        pub(super) filter: F,
+        pub(super) shed: LoadShed,
+    }
+
+    impl<F> FilterBase for WithLoadShed<F>
+    where
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = WithLoadShedFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            if !self.shed.admit() {
+                tracing::debug!("shedding load: {:?}", self.shed.load());
+                self.shed.updated();
+                return WithLoadShedFuture {
+                    future: None,
+                    pending: None,
+                    retry_after: self.shed.config.retry_after,
+                };
+            }
+            WithLoadShedFuture {
+                future: Some(self.filter.filter(Internal)),
+                pending: Some(Pending {
+                    shed: self.shed.clone(),
+                    started: Instant::now(),
+                }),
+                retry_after: self.shed.config.retry_after,
+            }
+        }
+    }
+
+    /// Counts a request as pending until dropped.
+    struct Pending {
+        shed: LoadShed,
+        started: Instant,
+    }
+
+    impl Drop for Pending {
+        fn drop(&mut self) {
+            self.shed.shared.pending.fetch_sub(1, Ordering::AcqRel);
+            self.shed.updated();
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithLoadShedFuture<F> {
+        #[pin]
+        future: Option<F>,
+        pending: Option<Pending>,
+        retry_after: std::time::Duration,
+    }
+
+    impl<F> Future for WithLoadShedFuture<F>
+    where
+        F: TryFuture,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<F::Ok, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let future = match pin.future.as_pin_mut() {
+                Some(future) => future,
+                None => {
+                    return Poll::Ready(Err(reject::known(Overloaded {
+                        retry_after: *pin.retry_after,
+                    })))
+                }
+            };
+            let result = ready!(future.try_poll(cx));
+            if let Some(pending) = pin.pending.take() {
+                pending.shed.record(pending.started.elapsed());
+            }
+            Poll::Ready(result.map_err(Into::into))
+        }
+    }
+}
@@ -1,17 +1,30 @@
 //! Logger Filters
 
+use std::ffi::OsString;
 use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufWriter, Write};
 use std::net::SocketAddr;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use futures::stream;
 use http::{self, header, StatusCode};
+use hyper::body::HttpBody;
+use hyper::Body;
+use serde_json::json;
 
-use crate::filter::{Filter, WrapSealed};
+use crate::filter::{Filter, Wrap};
 use crate::reject::IsReject;
-use crate::reply::Reply;
+use crate::reply::{Reply, Response};
+use crate::request_id::RequestId;
 use crate::route::Route;
+use crate::sample::Sample;
 
-use self::internal::WithLog;
+use self::internal::{WithAsyncLog, WithLog};
 
 /// Create a wrapping filter with the specified `name` as the `target`.
 ///
@@ -36,7 +49,7 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info) + Copy> {
         // - response content length?
         log::info!(
             target: name,
-            "{} \"{} {} {:?}\" {} \"{}\" \"{}\" {:?}",
+            "{} \"{} {} {:?}\" {} \"{}\" \"{}\" {:?}{}",
             OptFmt(info.route.remote_addr()),
             info.method(),
             info.path(),
@@ -45,13 +58,178 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info) + Copy> {
             OptFmt(info.referer()),
             OptFmt(info.user_agent()),
             info.elapsed(),
+            IdFmt(info.request_id()),
         );
     };
-    Log { func }
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
+}
+
+/// Create a wrapping filter that logs a JSON object per request, with the
+/// specified `name` as the `target`.
+///
+/// The object has these fields, with `null` for those that aren't known:
+///
+/// - `timestamp`: when the request started, in RFC 3339 format, in UTC
+/// - `method`, `path`, and `status`
+/// - `latency_ms`: the time taken to reply, in milliseconds
+/// - `bytes`: the length of the reply body
+/// - `remote_addr`, `request_id`, and `user_agent`
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::log::json("example::access"));
+/// ```
+pub fn json(name: &'static str) -> Log<impl Fn(Info) + Copy> {
+    let func = move |info: Info| {
+        log::info!(target: name, "{}", info.json());
+    };
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
+}
+
+/// Create a wrapping filter that writes a JSON object per request, as a
+/// line to `writer`.
+///
+/// The objects are the same as those of [`json()`]. Errors writing them are
+/// ignored.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::log::json_writer(std::io::stdout()));
+/// ```
+pub fn json_writer<W>(writer: W) -> Log<impl Fn(Info) + Clone>
+where
+    W: Write + Send + 'static,
+{
+    write_lines(writer, |info| info.json())
+}
+
+/// Create a wrapping filter that logs in the Common Log Format, with the
+/// specified `name` as the `target`.
+///
+/// Lines look like the ones of Apache's `common` format, with times in UTC:
+///
+/// ```text
+/// 1.2.3.4 - - [03/Feb/2021:04:05:06 +0000] "GET /hello?name=warp HTTP/1.1" 200 13
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::log::clf("example::access"));
+/// ```
+pub fn clf(name: &'static str) -> Log<impl Fn(Info) + Copy> {
+    let func = move |info: Info| {
+        log::info!(target: name, "{}", info.clf(false));
+    };
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
+}
+
+/// Create a wrapping filter that logs in the Combined Log Format, with the
+/// specified `name` as the `target`.
+///
+/// This is the [Common Log Format](clf), followed by the quoted referer and
+/// user agent of the request.
+pub fn combined(name: &'static str) -> Log<impl Fn(Info) + Copy> {
+    let func = move |info: Info| {
+        log::info!(target: name, "{}", info.clf(true));
+    };
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
+}
+
+/// Create a wrapping filter that writes lines in the Common Log Format to
+/// `writer`, such as a [`RotatingFile`].
+///
+/// Errors writing them are ignored.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use warp::Filter;
+/// use warp::log::RotatingFile;
+///
+/// let file = RotatingFile::new("/var/log/app/access.log")
+///     .max_size(100 * 1024 * 1024)
+///     .rotate_every(Duration::from_secs(24 * 60 * 60))
+///     .open()
+///     .expect("open access log");
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::log::clf_writer(file));
+/// ```
+pub fn clf_writer<W>(writer: W) -> Log<impl Fn(Info) + Clone>
+where
+    W: Write + Send + 'static,
+{
+    write_lines(writer, |info| info.clf(false))
+}
+
+/// Create a wrapping filter that writes lines in the Combined Log Format to
+/// `writer`, such as a [`RotatingFile`].
+///
+/// Errors writing them are ignored.
+pub fn combined_writer<W>(writer: W) -> Log<impl Fn(Info) + Clone>
+where
+    W: Write + Send + 'static,
+{
+    write_lines(writer, |info| info.clf(true))
+}
+
+fn write_lines<W, L>(writer: W, line: fn(&Info) -> L) -> Log<impl Fn(Info) + Clone>
+where
+    W: Write + Send + 'static,
+    L: fmt::Display,
+{
+    let writer = Arc::new(Mutex::new(writer));
+    let func = move |info: Info| {
+        let line = line(&info);
+        let mut writer = writer.lock().unwrap();
+        let _ = writeln!(writer, "{}", line);
+    };
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
 }
 
 /// Create a wrapping filter that receives `warp::log::Info`.
 ///
+/// The closure is called once the reply body has been sent, or the
+/// connection was closed before, so the number of bytes sent is known.
+/// Rejections are logged right away.
+///
 /// # Example
 ///
 /// ```
@@ -74,26 +252,85 @@ pub fn custom<F>(func: F) -> Log<F>
 where
     F: Fn(Info),
 {
-    Log { func }
+    Log {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
+}
+
+/// Create a wrapping filter that passes an [`OwnedInfo`] to an async
+/// closure, such as one sending log records to a channel or a database.
+///
+/// The closure is called once the reply body has been sent, or the
+/// connection was closed before, so the number of bytes sent is known. The
+/// future it returns is spawned onto the runtime, and doesn't hold up the
+/// request.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+///
+/// let log = warp::log::custom_async(move |info: warp::log::OwnedInfo| {
+///     let tx = tx.clone();
+///     async move {
+///         let _ = tx.send(format!("{} {} {:?}", info.method(), info.path(), info.bytes_sent()));
+///     }
+/// });
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(log);
+/// ```
+pub fn custom_async<F, Fut>(func: F) -> AsyncLog<F>
+where
+    F: Fn(OwnedInfo) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    AsyncLog {
+        func,
+        extensions: Arc::new(Vec::new()),
+        sample: Sample::all(),
+    }
 }
 
 /// Decorates a [`Filter`](crate::Filter) to log requests and responses.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Log<F> {
     func: F,
+    extensions: Arc<Vec<CopyExtension>>,
+    sample: Sample,
 }
 
+/// Decorates a [`Filter`](crate::Filter) to log requests and responses with
+/// an async closure.
+///
+/// Constructed with [`custom_async`].
+#[derive(Clone)]
+#[allow(missing_debug_implementations)]
+pub struct AsyncLog<F> {
+    func: F,
+    extensions: Arc<Vec<CopyExtension>>,
+    sample: Sample,
+}
+
+type CopyExtension = fn(&http::Extensions, &mut http::Extensions);
+
 /// Information about the request/response that can be used to prepare log lines.
 #[allow(missing_debug_implementations)]
 pub struct Info<'a> {
     route: &'a Route,
     start: Instant,
     status: StatusCode,
+    content_length: Option<u64>,
+    bytes_sent: Option<u64>,
 }
 
-impl<FN, F> WrapSealed<F> for Log<FN>
+impl<FN, F> Wrap<F> for Log<FN>
 where
-    FN: Fn(Info) + Clone + Send,
+    FN: Fn(Info) + Clone + Send + 'static,
     F: Filter + Clone + Send,
     F::Extract: Reply,
     F::Error: IsReject,
@@ -108,6 +345,68 @@ where
     }
 }
 
+impl<F> Log<F> {
+    /// Only logs the requests picked by `sample`.
+    ///
+    /// Defaults to logging every request.
+    pub fn sample(mut self, sample: Sample) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    /// Copies the request extension of type `T`, if there is one, to be
+    /// read with [`Info::extension`] once the reply body has been sent.
+    pub fn extension<T: Clone + Send + Sync + 'static>(mut self) -> Self {
+        Arc::make_mut(&mut self.extensions).push(copy_extension::<T>);
+        self
+    }
+}
+
+impl<F> AsyncLog<F> {
+    /// Only logs the requests picked by `sample`.
+    ///
+    /// Defaults to logging every request. Whether a request is slow is
+    /// decided once the reply is ready, not once its body has been sent.
+    pub fn sample(mut self, sample: Sample) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    /// Copies the request extension of type `T`, if there is one, into the
+    /// [`OwnedInfo`], to be read with [`OwnedInfo::extension`].
+    pub fn extension<T: Clone + Send + Sync + 'static>(mut self) -> Self {
+        Arc::make_mut(&mut self.extensions).push(copy_extension::<T>);
+        self
+    }
+}
+
+fn copy_extension<T: Clone + Send + Sync + 'static>(
+    from: &http::Extensions,
+    to: &mut http::Extensions,
+) {
+    if let Some(value) = from.get::<T>() {
+        to.insert(value.clone());
+    }
+}
+
+impl<FN, Fut, F> Wrap<F> for AsyncLog<FN>
+where
+    FN: Fn(OwnedInfo) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithAsyncLog<FN, F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithAsyncLog {
+            filter,
+            log: self.clone(),
+        }
+    }
+}
+
 impl<'a> Info<'a> {
     /// View the remote `SocketAddr` of the request.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
@@ -167,6 +466,289 @@ impl<'a> Info<'a> {
     pub fn request_headers(&self) -> &http::HeaderMap {
         self.route.headers()
     }
+
+    /// View the ID given to the request by [`warp::request_id`](crate::request_id()).
+    pub fn request_id(&self) -> Option<&str> {
+        self.route
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+    }
+
+    /// View the length of the response body, if it is known up front.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// View the number of body bytes sent.
+    ///
+    /// This is less than the length of the body if the connection was
+    /// closed early, and `None` if the request was rejected.
+    pub fn bytes_sent(&self) -> Option<u64> {
+        self.bytes_sent
+    }
+
+    /// View the template of the path the request matched, such as
+    /// `/users/{u32}/posts`.
+    ///
+    /// Literal segments come from [`path`](crate::path()), parameters from
+    /// [`param`](crate::path::param) as their type name in braces, and a
+    /// [`tail`](crate::path::tail) is `*`. This is `None` if no path filters
+    /// matched.
+    pub fn route_template(&self) -> Option<String> {
+        self.route.template()
+    }
+
+    /// View a value of type `T` in the request's extensions, such as one
+    /// inserted by an earlier filter.
+    ///
+    /// Once the reply body has been sent, only the extensions copied with
+    /// [`Log::extension`] are left.
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.route.extensions().get::<T>()
+    }
+
+    fn clf(&self, combined: bool) -> String {
+        let started = SystemTime::now() - self.elapsed();
+        let uri = self.route.uri();
+        let target = uri.path_and_query().map_or(uri.path(), |pq| pq.as_str());
+        let mut line = format!(
+            "{} - - [{}] \"{} {} {:?}\" {} {}",
+            OptFmt(self.remote_addr().map(|addr| addr.ip())),
+            clf_date(started),
+            self.method(),
+            target,
+            self.version(),
+            self.status().as_u16(),
+            OptFmt(self.bytes_sent().or(self.content_length())),
+        );
+        if combined {
+            line.push_str(&format!(
+                " \"{}\" \"{}\"",
+                OptFmt(self.referer()),
+                OptFmt(self.user_agent()),
+            ));
+        }
+        line
+    }
+
+    fn json(&self) -> serde_json::Value {
+        let elapsed = self.elapsed();
+        let started = SystemTime::now() - elapsed;
+        json!({
+            "timestamp": rfc3339(started),
+            "method": self.method().as_str(),
+            "path": self.path(),
+            "status": self.status().as_u16(),
+            "latency_ms": elapsed.as_secs_f64() * 1000.0,
+            "bytes": self.bytes_sent().or(self.content_length()),
+            "remote_addr": self.remote_addr().map(|addr| addr.to_string()),
+            "request_id": self.request_id(),
+            "user_agent": self.user_agent(),
+        })
+    }
+}
+
+/// An owned copy of [`Info`], passed to [`custom_async`] closures.
+#[derive(Debug)]
+pub struct OwnedInfo {
+    remote_addr: Option<SocketAddr>,
+    method: http::Method,
+    uri: http::Uri,
+    version: http::Version,
+    status: StatusCode,
+    headers: http::HeaderMap,
+    elapsed: Duration,
+    request_id: Option<String>,
+    content_length: Option<u64>,
+    bytes_sent: Option<u64>,
+    route_template: Option<String>,
+    extensions: http::Extensions,
+}
+
+impl OwnedInfo {
+    fn new(info: &Info<'_>, extensions: &[CopyExtension]) -> OwnedInfo {
+        let mut copied = http::Extensions::new();
+        for copy in extensions {
+            copy(info.route.extensions(), &mut copied);
+        }
+        OwnedInfo {
+            remote_addr: info.remote_addr(),
+            method: info.method().clone(),
+            uri: info.route.uri().clone(),
+            version: info.version(),
+            status: info.status(),
+            headers: info.request_headers().clone(),
+            elapsed: info.elapsed(),
+            request_id: info.request_id().map(ToOwned::to_owned),
+            content_length: info.content_length(),
+            bytes_sent: info.bytes_sent(),
+            route_template: info.route_template(),
+            extensions: copied,
+        }
+    }
+
+    /// View the remote `SocketAddr` of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// View the `http::Method` of the request.
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    /// View the URI path of the request.
+    pub fn path(&self) -> &str {
+        self.uri.path()
+    }
+
+    /// View the `http::Version` of the request.
+    pub fn version(&self) -> http::Version {
+        self.version
+    }
+
+    /// View the `http::StatusCode` of the response.
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+
+    /// View the referer of the request.
+    pub fn referer(&self) -> Option<&str> {
+        self.headers
+            .get(header::REFERER)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    /// View the user agent of the request.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    /// View the `Duration` taken to send the whole response.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// View the host of the request
+    pub fn host(&self) -> Option<&str> {
+        self.headers.get(header::HOST).and_then(|v| v.to_str().ok())
+    }
+
+    /// Access the full headers of the request
+    pub fn request_headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    /// View the ID given to the request by [`warp::request_id`](crate::request_id()).
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// View the length of the response body, if it was known up front.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// View the number of body bytes sent.
+    ///
+    /// This is less than the length of the body if the connection was
+    /// closed early, and `None` if the request was rejected.
+    pub fn bytes_sent(&self) -> Option<u64> {
+        self.bytes_sent
+    }
+
+    /// View the template of the path the request matched.
+    ///
+    /// See [`Info::route_template`].
+    pub fn route_template(&self) -> Option<&str> {
+        self.route_template.as_deref()
+    }
+
+    /// View a request extension copied with [`AsyncLog::extension`].
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<T>()
+    }
+}
+
+// Formats as `2021-02-03T04:05:06.789Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let t = Civil::from(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second, t.millis,
+    )
+}
+
+// Formats as `03/Feb/2021:04:05:06 +0000`, as in the Common Log Format.
+fn clf_date(time: SystemTime) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let t = Civil::from(time);
+    format!(
+        "{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000",
+        t.day,
+        MONTHS[t.month as usize - 1],
+        t.year,
+        t.hour,
+        t.minute,
+        t.second,
+    )
+}
+
+// A UTC date and time.
+struct Civil {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: u64,
+    minute: u64,
+    second: u64,
+    millis: u32,
+}
+
+impl From<SystemTime> for Civil {
+    fn from(time: SystemTime) -> Civil {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs();
+        let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+        // Civil date from days since the epoch, from Howard Hinnant's algorithms.
+        let z = days as i64 + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Civil {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+            millis: since_epoch.subsec_millis(),
+        }
+    }
+}
+
+// Request IDs are only appended to the access log when there is one.
+struct IdFmt<'a>(Option<&'a str>);
+
+impl fmt::Display for IdFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(id) => write!(f, " {}", id),
+            None => Ok(()),
+        }
+    }
 }
 
 struct OptFmt<T>(Option<T>);
@@ -181,6 +763,250 @@ impl<T: fmt::Display> fmt::Display for OptFmt<T> {
     }
 }
 
+/// An access log file, rotated when it gets too big or too old.
+///
+/// When rotated, `access.log` is renamed to `access.log.1`, the previous
+/// `access.log.1` to `access.log.2`, and so on, keeping up to
+/// [`keep`](RotatingFile::keep) old files. Rotation only happens between
+/// lines.
+///
+/// See [`clf_writer`] for an example.
+#[derive(Clone, Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: Option<u64>,
+    rotate_every: Option<Duration>,
+    keep: usize,
+}
+
+/// A [`RotatingFile`] open for writing.
+///
+/// Writes are handed to a background thread, so requests don't wait for the
+/// disk. Clones write to the same file.
+#[derive(Clone, Debug)]
+pub struct LogFile {
+    tx: mpsc::Sender<Vec<u8>>,
+}
+
+impl RotatingFile {
+    /// Creates a log file at `path`, appending to it if it exists.
+    ///
+    /// By default, it is never rotated.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        RotatingFile {
+            path: path.as_ref().to_owned(),
+            max_size: None,
+            rotate_every: None,
+            keep: 5,
+        }
+    }
+
+    /// Rotates the file before it grows over `bytes`.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Rotates the file once it has been written to for `period`.
+    pub fn rotate_every(mut self, period: Duration) -> Self {
+        self.rotate_every = Some(period);
+        self
+    }
+
+    /// Sets how many rotated files to keep.
+    ///
+    /// Defaults to 5.
+    pub fn keep(mut self, files: usize) -> Self {
+        self.keep = files;
+        self
+    }
+
+    /// Opens the file, and starts the thread writing to it.
+    pub fn open(self) -> io::Result<LogFile> {
+        let (file, size) = open_append(&self.path)?;
+        let (tx, rx) = mpsc::channel();
+        let mut writer = Rotator {
+            config: self,
+            file,
+            size,
+            opened: Instant::now(),
+            at_line_start: true,
+        };
+        thread::Builder::new()
+            .name("warp-log-file".into())
+            .spawn(move || writer.run(rx))?;
+        Ok(LogFile { tx })
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", n));
+        path.into()
+    }
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "log file writer stopped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<(BufWriter<File>, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((BufWriter::new(file), size))
+}
+
+struct Rotator {
+    config: RotatingFile,
+    file: BufWriter<File>,
+    size: u64,
+    opened: Instant,
+    at_line_start: bool,
+}
+
+impl Rotator {
+    fn run(&mut self, rx: mpsc::Receiver<Vec<u8>>) {
+        // Ends once every `LogFile` is dropped.
+        while let Ok(buf) = rx.recv() {
+            self.write(&buf);
+            while let Ok(buf) = rx.try_recv() {
+                self.write(&buf);
+            }
+            if let Err(err) = self.file.flush() {
+                tracing::error!("log file write error: {}", err);
+            }
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) {
+        if self.at_line_start && self.is_due(buf.len() as u64) {
+            if let Err(err) = self.rotate() {
+                tracing::error!("log file rotation error: {}", err);
+            }
+        }
+        if let Err(err) = self.file.write_all(buf) {
+            tracing::error!("log file write error: {}", err);
+        }
+        self.size += buf.len() as u64;
+        self.at_line_start = buf.ends_with(b"\n");
+    }
+
+    fn is_due(&self, len: u64) -> bool {
+        let too_big =
+            matches!(self.config.max_size, Some(max) if self.size > 0 && self.size + len > max);
+        let too_old =
+            matches!(self.config.rotate_every, Some(period) if self.opened.elapsed() >= period);
+        too_big || too_old
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let config = &self.config;
+        if config.keep == 0 {
+            fs::remove_file(&config.path)?;
+        } else {
+            for n in (1..config.keep).rev() {
+                let from = config.rotated_path(n);
+                if from.exists() {
+                    fs::rename(from, config.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&config.path, config.rotated_path(1))?;
+        }
+        let (file, size) = open_append(&config.path)?;
+        self.file = file;
+        self.size = size;
+        self.opened = Instant::now();
+        Ok(())
+    }
+}
+
+// The length of a body counted by an inner log filter is only in the header.
+fn content_length(res: &Response) -> Option<u64> {
+    res.body().size_hint().exact().or_else(|| {
+        res.headers()
+            .get(header::CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    })
+}
+
+// Wraps the body to call `done` with the number of bytes sent, once it has
+// been sent or dropped.
+fn count_sent<D>(res: Response, done: D) -> Response
+where
+    D: FnOnce(u64) + Send + 'static,
+{
+    let (mut parts, body) = res.into_parts();
+    match body.size_hint().exact() {
+        Some(0) => {
+            done(0);
+            return Response::from_parts(parts, body);
+        }
+        Some(len) => {
+            // The counted body is a stream, so keep the length known.
+            parts
+                .headers
+                .entry(header::CONTENT_LENGTH)
+                .or_insert_with(|| header::HeaderValue::from(len));
+        }
+        None => {}
+    }
+
+    let counted = Counted {
+        body,
+        sent: 0,
+        done: Some(done),
+    };
+    let stream = stream::try_unfold(counted, |mut counted| async move {
+        match counted.body.data().await {
+            Some(chunk) => {
+                let chunk = chunk?;
+                counted.sent += chunk.len() as u64;
+                Ok::<_, hyper::Error>(Some((chunk, counted)))
+            }
+            None => Ok(None),
+        }
+    });
+    Response::from_parts(parts, Body::wrap_stream(stream))
+}
+
+struct Counted<D: FnOnce(u64)> {
+    body: Body,
+    sent: u64,
+    done: Option<D>,
+}
+
+impl<D: FnOnce(u64)> Drop for Counted<D> {
+    fn drop(&mut self) {
+        if let Some(done) = self.done.take() {
+            done(self.sent);
+        }
+    }
+}
+
+fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(future);
+        }
+        Err(_) => tracing::warn!("log: no runtime to run the log future on"),
+    }
+}
+
 mod internal {
     use std::future::Future;
     use std::pin::Pin;
@@ -190,10 +1016,13 @@ mod internal {
     use futures::{ready, TryFuture};
     use pin_project::pin_project;
 
-    use super::{Info, Log};
+    use super::{
+        content_length, copy_extension, count_sent, spawn, AsyncLog, Info, Log, OwnedInfo,
+    };
     use crate::filter::{Filter, FilterBase, Internal};
     use crate::reject::IsReject;
     use crate::reply::{Reply, Response};
+    use crate::request_id::RequestId;
     use crate::route;
 
     #[allow(missing_debug_implementations)]
@@ -207,7 +1036,7 @@ mod internal {
     }
 
     #[allow(missing_debug_implementations)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone)]
     pub struct WithLog<FN, F> {
         pub(super) filter: F,
         pub(super) log: Log<FN>,
@@ -215,7 +1044,7 @@ mod internal {
 
     impl<FN, F> FilterBase for WithLog<FN, F>
     where
-        FN: Fn(Info) + Clone + Send,
+        FN: Fn(Info) + Clone + Send + 'static,
         F: Filter + Clone + Send,
         F::Extract: Reply,
         F::Error: IsReject,
@@ -227,6 +1056,7 @@ mod internal {
         fn filter(&self, _: Internal) -> Self::Future {
             let started = tokio::time::Instant::now().into_std();
             WithLogFuture {
+                sampled: self.log.sample.head(),
                 log: self.log.clone(),
                 future: self.filter.filter(Internal),
                 started,
@@ -241,11 +1071,12 @@ mod internal {
         #[pin]
         future: F,
         started: Instant,
+        sampled: bool,
     }
 
     impl<FN, F> Future for WithLogFuture<FN, F>
     where
-        FN: Fn(Info),
+        FN: Fn(Info) + Clone + Send + 'static,
         F: TryFuture,
         F::Ok: Reply,
         F::Error: IsReject,
@@ -254,27 +1085,164 @@ mod internal {
 
         fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
             let pin = self.as_mut().project();
-            let (result, status) = match ready!(pin.future.try_poll(cx)) {
+            let (result, status, content_length) = match ready!(pin.future.try_poll(cx)) {
+                Ok(reply) => {
+                    let resp = reply.into_response();
+                    let status = resp.status();
+                    let content_length = content_length(&resp);
+                    (Poll::Ready(Ok((Logged(resp),))), status, content_length)
+                }
+                Err(reject) => {
+                    let status = reject.status();
+                    (Poll::Ready(Err(reject)), status, None)
+                }
+            };
+
+            let started = self.started;
+            let elapsed = tokio::time::Instant::now().into_std() - started;
+            if !self.sampled && !self.log.sample.tail(status, elapsed) {
+                return result;
+            }
+
+            match result {
+                Poll::Ready(Ok((Logged(resp),))) => {
+                    // The route is gone once the body is sent, so keep a
+                    // copy of the request with the extensions that are
+                    // needed.
+                    let route = route::with(|route| {
+                        let mut copy = route.fork().into_inner();
+                        for copy_extension in self.log.extensions.iter() {
+                            copy_extension(route.extensions(), copy.extensions_mut());
+                        }
+                        copy_extension::<RequestId>(route.extensions(), copy.extensions_mut());
+                        copy
+                    });
+                    let func = self.log.func.clone();
+                    let resp = count_sent(resp, move |sent| {
+                        func(Info {
+                            route: &route,
+                            start: started,
+                            status,
+                            content_length,
+                            bytes_sent: Some(sent),
+                        });
+                    });
+                    Poll::Ready(Ok((Logged(resp),)))
+                }
+                result => {
+                    route::with(|route| {
+                        (self.log.func)(Info {
+                            route,
+                            start: started,
+                            status,
+                            content_length,
+                            bytes_sent: None,
+                        });
+                    });
+                    result
+                }
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithAsyncLog<FN, F> {
+        pub(super) filter: F,
+        pub(super) log: AsyncLog<FN>,
+    }
+
+    impl<FN, Fut, F> FilterBase for WithAsyncLog<FN, F>
+    where
+        FN: Fn(OwnedInfo) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Logged,);
+        type Error = F::Error;
+        type Future = WithAsyncLogFuture<FN, F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let started = tokio::time::Instant::now().into_std();
+            WithAsyncLogFuture {
+                sampled: self.log.sample.head(),
+                log: self.log.clone(),
+                future: self.filter.filter(Internal),
+                started,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithAsyncLogFuture<FN, F> {
+        log: AsyncLog<FN>,
+        #[pin]
+        future: F,
+        started: Instant,
+        sampled: bool,
+    }
+
+    impl<FN, Fut, F> Future for WithAsyncLogFuture<FN, F>
+    where
+        FN: Fn(OwnedInfo) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Logged,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+            let pin = self.project();
+            let (result, status, content_length) = match ready!(pin.future.try_poll(cx)) {
                 Ok(reply) => {
                     let resp = reply.into_response();
                     let status = resp.status();
-                    (Poll::Ready(Ok((Logged(resp),))), status)
+                    let content_length = content_length(&resp);
+                    (Ok(resp), status, content_length)
                 }
                 Err(reject) => {
                     let status = reject.status();
-                    (Poll::Ready(Err(reject)), status)
+                    (Err(reject), status, None)
                 }
             };
 
-            route::with(|route| {
-                (self.log.func)(Info {
+            let started = *pin.started;
+            let elapsed = tokio::time::Instant::now().into_std() - started;
+            if !*pin.sampled && !pin.log.sample.tail(status, elapsed) {
+                return Poll::Ready(result.map(|resp| (Logged(resp),)));
+            }
+
+            let extensions = &pin.log.extensions;
+            let mut info = route::with(|route| {
+                let info = Info {
                     route,
-                    start: self.started,
+                    start: started,
                     status,
-                });
+                    content_length,
+                    bytes_sent: None,
+                };
+                OwnedInfo::new(&info, extensions)
             });
+            let func = pin.log.func.clone();
 
-            result
+            match result {
+                Ok(resp) => {
+                    let resp = count_sent(resp, move |sent| {
+                        info.bytes_sent = Some(sent);
+                        info.elapsed = tokio::time::Instant::now().into_std() - started;
+                        spawn(func(info));
+                    });
+                    Poll::Ready(Ok((Logged(resp),)))
+                }
+                Err(reject) => {
+                    spawn(func(info));
+                    Poll::Ready(Err(reject))
+                }
+            }
         }
     }
 }
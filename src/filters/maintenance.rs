@@ -0,0 +1,257 @@
+//! Maintenance mode Filters
+
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use http::StatusCode;
+use hyper::Body;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithMaintenance;
+
+/// Create a wrapping filter that replies with `503 Service Unavailable`
+/// while the [`MaintenanceHandle`] is enabled.
+///
+/// While maintenance mode is on, the wrapped filter isn't run at all, and
+/// requests get a `Retry-After` header and the configured body. Turning it
+/// off lets requests through again, without restarting the server.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+/// use warp::maintenance::MaintenanceHandle;
+///
+/// let handle = MaintenanceHandle::new();
+///
+/// let api = warp::path("api")
+///     .map(|| "hello")
+///     .with(
+///         warp::maintenance(&handle)
+///             .retry_after(Duration::from_secs(300))
+///             .body("back in a few minutes"),
+///     );
+///
+/// // Before deploying:
+/// handle.enable();
+/// ```
+pub fn maintenance(handle: &MaintenanceHandle) -> Maintenance {
+    Maintenance {
+        enabled: handle.enabled.clone(),
+        config: Arc::new(Config {
+            retry_after: Duration::from_secs(60),
+            body: Bytes::from_static(b"Service Unavailable"),
+            content_type: HeaderValue::from_static("text/plain; charset=utf-8"),
+        }),
+    }
+}
+
+/// Turns maintenance mode on and off for [`maintenance`] filters.
+///
+/// Clones of a handle control the same filters.
+#[derive(Clone, Debug, Default)]
+pub struct MaintenanceHandle {
+    enabled: Arc<AtomicBool>,
+}
+
+/// Decorates a [`Filter`](crate::Filter) to reply with `503` during
+/// maintenance.
+#[derive(Clone, Debug)]
+pub struct Maintenance {
+    enabled: Arc<AtomicBool>,
+    config: Arc<Config>,
+}
+
+#[derive(Clone, Debug)]
+struct Config {
+    retry_after: Duration,
+    body: Bytes,
+    content_type: HeaderValue,
+}
+
+impl MaintenanceHandle {
+    /// Creates a handle with maintenance mode off.
+    pub fn new() -> Self {
+        MaintenanceHandle::default()
+    }
+
+    /// Turns maintenance mode on.
+    pub fn enable(&self) {
+        self.set(true);
+    }
+
+    /// Turns maintenance mode off.
+    pub fn disable(&self) {
+        self.set(false);
+    }
+
+    /// Turns maintenance mode on or off.
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Whether maintenance mode is on.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+}
+
+impl Maintenance {
+    /// Sets the `Retry-After` sent during maintenance.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        Arc::make_mut(&mut self.config).retry_after = retry_after;
+        self
+    }
+
+    /// Sets the body sent during maintenance.
+    ///
+    /// Defaults to `Service Unavailable`.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        Arc::make_mut(&mut self.config).body = body.into();
+        self
+    }
+
+    /// Sets the `Content-Type` of the body.
+    ///
+    /// Defaults to `text/plain; charset=utf-8`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn content_type<V>(mut self, content_type: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        let content_type = HeaderValue::try_from(content_type)
+            .unwrap_or_else(|_| panic!("maintenance: invalid content type"));
+        Arc::make_mut(&mut self.config).content_type = content_type;
+        self
+    }
+
+    fn response(&self) -> Response {
+        let config = &self.config;
+        let mut res = Response::new(Body::from(config.body.clone()));
+        *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_TYPE, config.content_type.clone());
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from(config.retry_after.as_secs().max(1)),
+        );
+        res
+    }
+}
+
+impl<F> Wrap<F> for Maintenance
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithMaintenance<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithMaintenance {
+            filter,
+            maintenance: self.clone(),
+        }
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::Ordering;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::Maintenance;
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+
+    #[allow(missing_debug_implementations)]
+    pub struct Gated(Response);
+
+    impl Reply for Gated {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithMaintenance<F> {
+        pub(super) filter: F,
+        pub(super) maintenance: Maintenance,
+    }
+
+    impl<F> FilterBase for WithMaintenance<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Gated,);
+        type Error = Rejection;
+        type Future = WithMaintenanceFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let state = if self.maintenance.enabled.load(Ordering::SeqCst) {
+                tracing::debug!("maintenance: mode is on, replying 503");
+                State::Maintenance(Some(self.maintenance.response()))
+            } else {
+                State::Filter(self.filter.filter(Internal))
+            };
+            WithMaintenanceFuture { state }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithMaintenanceFuture<F> {
+        #[pin]
+        state: State<F>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F> {
+        Filter(#[pin] F),
+        Maintenance(Option<Response>),
+    }
+
+    impl<F> Future for WithMaintenanceFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Gated,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.project().state.project() {
+                StateProj::Filter(fut) => {
+                    let reply = ready!(fut.try_poll(cx)).map_err(Into::into)?;
+                    Poll::Ready(Ok((Gated(reply.into_response()),)))
+                }
+                StateProj::Maintenance(res) => {
+                    let res = res.take().expect("polled after complete");
+                    Poll::Ready(Ok((Gated(res),)))
+                }
+            }
+        }
+    }
+}
@@ -6,12 +6,17 @@
 //!
 //! There is also [`warp::method()`](method), which never rejects
 //! a request, and just extracts the method to be used in your filter chains.
+use std::convert::{Infallible, TryFrom};
+use std::sync::Arc;
+
 use futures::future;
-use http::Method;
+use http::header::{HeaderName, CONTENT_TYPE};
+use http::{HeaderMap, Method};
 
-use crate::filter::{filter_fn, filter_fn_one, Filter, One};
+use self::internal::{WithAutoHead, WithAutoOptions, WithMethodOverride};
+use crate::filter::{filter_fn, filter_fn_one, Filter, One, Wrap};
 use crate::reject::Rejection;
-use std::convert::Infallible;
+use crate::reply::{Reply, Response};
 
 /// Create a `Filter` that requires the request method to be `GET`.
 ///
@@ -122,6 +127,278 @@ pub fn method() -> impl Filter<Extract = One<Method>, Error = Infallible> + Copy
     filter_fn_one(|route| future::ok::<_, Infallible>(route.method().clone()))
 }
 
+/// Create a wrapping filter that lets `POST` requests ask to be routed as
+/// another method.
+///
+/// The method is taken from the `X-HTTP-Method-Override` header, and, when
+/// [`form_field`](MethodOverride::form_field) is set, from that field of a
+/// `application/x-www-form-urlencoded` body. This allows clients that can
+/// only send `GET` and `POST`, such as HTML forms, to reach `PUT`, `PATCH`
+/// and `DELETE` routes.
+///
+/// Only methods in the allowed set are used, which defaults to `PUT`,
+/// `PATCH` and `DELETE`. Other requests are left untouched.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let delete = warp::delete()
+///     .and(warp::path!("posts" / u32))
+///     .map(|id| format!("deleted post {}", id));
+///
+/// let route = delete.with(warp::method::override_header().form_field("_method"));
+/// ```
+pub fn override_header() -> MethodOverride {
+    MethodOverride {
+        header: HeaderName::from_static("x-http-method-override"),
+        form_field: None,
+        allowed: Arc::new(vec![Method::PUT, Method::PATCH, Method::DELETE]),
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to override the method of `POST`
+/// requests.
+#[derive(Clone, Debug)]
+pub struct MethodOverride {
+    header: HeaderName,
+    form_field: Option<Arc<str>>,
+    allowed: Arc<Vec<Method>>,
+}
+
+// Form bodies larger than this aren't buffered to look for the field.
+const MAX_FORM_LEN: u64 = 64 * 1024;
+
+impl MethodOverride {
+    /// Sets the header the method is read from.
+    ///
+    /// Defaults to `X-HTTP-Method-Override`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    pub fn header<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        self.header = HeaderName::try_from(name)
+            .unwrap_or_else(|_| panic!("method override: invalid header name"));
+        self
+    }
+
+    /// Also reads the method from a field of url-encoded form bodies, such
+    /// as `_method`.
+    ///
+    /// The body is buffered to find the field, and is still available to the
+    /// wrapped filter afterwards. Bodies over 64 KiB are not checked.
+    pub fn form_field(mut self, name: &str) -> Self {
+        self.form_field = Some(name.into());
+        self
+    }
+
+    /// Sets the methods a request may be overridden to.
+    ///
+    /// Defaults to `PUT`, `PATCH` and `DELETE`.
+    pub fn allow<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.allowed = Arc::new(methods.into_iter().collect());
+        self
+    }
+
+    fn parse(&self, value: &[u8]) -> Option<Method> {
+        let method = Method::from_bytes(&value.to_ascii_uppercase()).ok()?;
+        if self.allowed.contains(&method) {
+            Some(method)
+        } else {
+            tracing::debug!("method override to {} is not allowed", method);
+            None
+        }
+    }
+
+    fn header_method(&self, headers: &HeaderMap) -> Option<Method> {
+        headers
+            .get(&self.header)
+            .and_then(|value| self.parse(value.as_bytes()))
+    }
+
+    fn form_method(&self, body: &[u8]) -> Option<Method> {
+        let name = self.form_field.as_ref()?;
+        let fields = serde_urlencoded::from_bytes::<Vec<(String, String)>>(body).ok()?;
+        fields
+            .iter()
+            .find(|(key, _)| **key == **name)
+            .and_then(|(_, value)| self.parse(value.as_bytes()))
+    }
+
+    fn wants_form(&self, headers: &HeaderMap) -> bool {
+        let mime = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok());
+        self.form_field.is_some()
+            && matches!(mime, Some(mime) if mime.essence_str() == "application/x-www-form-urlencoded")
+    }
+}
+
+impl<F> Wrap<F> for MethodOverride
+where
+    F: Filter + Clone + Send,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithMethodOverride<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithMethodOverride {
+            filter,
+            method_override: self.clone(),
+        }
+    }
+}
+
+/// Create a wrapping filter that answers `HEAD` requests with the matching
+/// `GET` route.
+///
+/// A `HEAD` request is first given to the wrapped filter as is, so explicit
+/// [`head()`] routes still work. If it is rejected, it is tried again as a
+/// `GET`, and the reply is sent without its body. The headers are kept, and
+/// `Content-Length` is set to the length of the body that was left out, when
+/// it is known.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let hello = warp::get()
+///     .and(warp::path("hello"))
+///     .map(|| "Hello, World!");
+///
+/// let route = hello.with(warp::method::auto_head());
+/// ```
+pub fn auto_head() -> AutoHead {
+    AutoHead { _p: () }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to answer `HEAD` requests with its
+/// `GET` routes.
+#[derive(Clone, Copy, Debug)]
+pub struct AutoHead {
+    _p: (),
+}
+
+impl<F> Wrap<F> for AutoHead
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithAutoHead<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithAutoHead { filter }
+    }
+}
+
+/// Create a wrapping filter that answers `OPTIONS` requests for known paths
+/// with the methods they allow.
+///
+/// An `OPTIONS` request is first given to the wrapped filter as is, so
+/// explicit [`options()`] routes, and [`warp::cors`](crate::cors()) preflight
+/// handling, still work. If it is rejected because of the method filters
+/// along the matching paths, the reply is a `204 No Content` with an `Allow`
+/// header listing their methods, and `HEAD` and `OPTIONS`. Otherwise, the
+/// rejection is kept.
+///
+/// The methods are found from the rejections of filters like [`get()`] and
+/// [`post()`], without running any handlers. For them to only count for
+/// their own paths, the method filters should come after the path filters.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let get = warp::path("posts").and(warp::get()).map(|| "all posts");
+/// let post = warp::path("posts").and(warp::post()).map(|| "created");
+///
+/// // `OPTIONS /posts` replies with `Allow: GET, HEAD, POST, OPTIONS`.
+/// let route = get.or(post).with(warp::method::auto_options());
+/// ```
+pub fn auto_options() -> AutoOptions {
+    AutoOptions { _p: () }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to answer `OPTIONS` requests with
+/// an `Allow` header.
+#[derive(Clone, Copy, Debug)]
+pub struct AutoOptions {
+    _p: (),
+}
+
+impl<F> Wrap<F> for AutoOptions
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithAutoOptions<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithAutoOptions { filter }
+    }
+}
+
+fn allow_response(mut methods: Vec<Method>) -> Response {
+    if methods.contains(&Method::GET) && !methods.contains(&Method::HEAD) {
+        methods.push(Method::HEAD);
+    }
+    if !methods.contains(&Method::OPTIONS) {
+        methods.push(Method::OPTIONS);
+    }
+    // The usual order, with extension methods last.
+    const ORDER: [Method; 7] = [
+        Method::GET,
+        Method::HEAD,
+        Method::POST,
+        Method::PUT,
+        Method::PATCH,
+        Method::DELETE,
+        Method::OPTIONS,
+    ];
+    methods.sort_by_key(|method| {
+        ORDER
+            .iter()
+            .position(|m| m == method)
+            .unwrap_or(ORDER.len())
+    });
+    let allow = methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut res = Response::new(hyper::Body::empty());
+    *res.status_mut() = http::StatusCode::NO_CONTENT;
+    if let Ok(allow) = http::HeaderValue::from_str(&allow) {
+        res.headers_mut().insert(http::header::ALLOW, allow);
+    }
+    res
+}
+
+fn strip_body(res: Response) -> Response {
+    let (mut parts, body) = res.into_parts();
+    if let Some(len) = hyper::body::HttpBody::size_hint(&body).exact() {
+        parts
+            .headers
+            .entry(http::header::CONTENT_LENGTH)
+            .or_insert_with(|| len.into());
+    }
+    Response::from_parts(parts, hyper::Body::empty())
+}
+
 // NOTE: This takes a static function instead of `&'static Method` directly
 // so that the `impl Filter` can be zero-sized. Moving it around should be
 // cheaper than holding a single static pointer (which would make it 1 word).
@@ -135,11 +412,314 @@ where
         if route.method() == method {
             future::ok(())
         } else {
-            future::err(crate::reject::method_not_allowed())
+            // Only a filter that comes after the path filters of its route
+            // knows that the path exists under another method.
+            future::err(crate::reject::method_not_allowed(
+                method.clone(),
+                route.path_matched(),
+            ))
         }
     })
 }
 
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use bytes::Bytes;
+    use futures::{ready, TryFuture};
+    use http::Method;
+    use hyper::body::HttpBody;
+    use hyper::Body;
+    use pin_project::pin_project;
+
+    use super::{allow_response, strip_body, MethodOverride, MAX_FORM_LEN};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, CombineRejection, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithMethodOverride<F> {
+        pub(super) filter: F,
+        pub(super) method_override: MethodOverride,
+    }
+
+    impl<F> FilterBase for WithMethodOverride<F>
+    where
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = WithMethodOverrideFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let over = &self.method_override;
+            let body = route::with(|route| {
+                if route.method() != Method::POST {
+                    return None;
+                }
+                if let Some(method) = over.header_method(route.headers()) {
+                    tracing::debug!("method overridden to {}", method);
+                    route.set_method(method);
+                    return None;
+                }
+                if !over.wants_form(route.headers()) {
+                    return None;
+                }
+                match route.take_body() {
+                    Some(body) if body.size_hint().upper().unwrap_or(u64::MAX) <= MAX_FORM_LEN => {
+                        Some(body)
+                    }
+                    Some(body) => {
+                        route.restore_body(body);
+                        None
+                    }
+                    None => None,
+                }
+            });
+
+            let state = match body {
+                Some(body) => State::Buffer(Box::pin(hyper::body::to_bytes(body))),
+                None => State::Filter(self.filter.filter(Internal)),
+            };
+            WithMethodOverrideFuture {
+                state,
+                filter: self.filter.clone(),
+                method_override: self.method_override.clone(),
+            }
+        }
+    }
+
+    type Buffer = Pin<Box<dyn Future<Output = Result<Bytes, hyper::Error>> + Send>>;
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithMethodOverrideFuture<F: Filter> {
+        #[pin]
+        state: State<F>,
+        filter: F,
+        method_override: MethodOverride,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Buffer(Buffer),
+        Filter(#[pin] F::Future),
+    }
+
+    impl<F> Future for WithMethodOverrideFuture<F>
+    where
+        F: Filter,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<F::Extract, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Buffer(buffer) => {
+                        let body = ready!(buffer.as_mut().poll(cx)).map_err(|err| {
+                            tracing::debug!("method override: error reading body: {}", err);
+                            reject::known(crate::body::BodyReadError(err))
+                        })?;
+                        let method = pin.method_override.form_method(&body);
+                        route::with(|route| {
+                            if let Some(method) = method {
+                                tracing::debug!("method overridden to {}", method);
+                                route.set_method(method);
+                            }
+                            route.restore_body(Body::from(body));
+                        });
+                        state.set(State::Filter(pin.filter.filter(Internal)));
+                    }
+                    StateProj::Filter(fut) => {
+                        return fut.try_poll(cx).map_err(Into::into);
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct Head(Response);
+
+    impl Reply for Head {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithAutoHead<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithAutoHead<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Head,);
+        type Error = Rejection;
+        type Future = WithAutoHeadFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let (is_head, path_index, path_matched) = route::with(|route| {
+                (
+                    route.method() == Method::HEAD,
+                    route.matched_path_index(),
+                    route.path_matched(),
+                )
+            });
+            let state = if is_head {
+                AutoHeadState::Head(self.filter.filter(Internal))
+            } else {
+                AutoHeadState::Other(self.filter.filter(Internal))
+            };
+            WithAutoHeadFuture {
+                state,
+                filter: self.filter.clone(),
+                path_index,
+                path_matched,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithAutoHeadFuture<F: Filter> {
+        #[pin]
+        state: AutoHeadState<F>,
+        filter: F,
+        path_index: usize,
+        path_matched: bool,
+    }
+
+    #[pin_project(project = AutoHeadStateProj)]
+    enum AutoHeadState<F: Filter> {
+        Other(#[pin] F::Future),
+        Head(#[pin] F::Future),
+        Get(#[pin] F::Future, Option<Rejection>),
+    }
+
+    impl<F> Future for WithAutoHeadFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Head,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            loop {
+                match state.as_mut().project() {
+                    AutoHeadStateProj::Other(fut) => {
+                        let reply = ready!(fut.try_poll(cx)).map_err(Into::into)?;
+                        return Poll::Ready(Ok((Head(reply.into_response()),)));
+                    }
+                    AutoHeadStateProj::Head(fut) => match ready!(fut.try_poll(cx)) {
+                        Ok(reply) => {
+                            let res = strip_body(reply.into_response());
+                            return Poll::Ready(Ok((Head(res),)));
+                        }
+                        Err(err) => {
+                            tracing::trace!("auto_head: HEAD rejected, trying GET");
+                            let (path_index, path_matched) = (*pin.path_index, *pin.path_matched);
+                            route::with(|route| {
+                                route.reset_matched_path_index(path_index);
+                                route.set_path_matched(path_matched);
+                                route.set_method(Method::GET);
+                            });
+                            let fut = pin.filter.filter(Internal);
+                            state.set(AutoHeadState::Get(fut, Some(err.into())));
+                        }
+                    },
+                    AutoHeadStateProj::Get(fut, head_err) => {
+                        let result = ready!(fut.try_poll(cx));
+                        route::with(|route| route.set_method(Method::HEAD));
+                        let head_err = head_err.take().expect("polled after complete");
+                        return match result {
+                            Ok(reply) => {
+                                let res = strip_body(reply.into_response());
+                                Poll::Ready(Ok((Head(res),)))
+                            }
+                            Err(err) => Poll::Ready(Err(head_err.combine(err.into()))),
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithAutoOptions<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithAutoOptions<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Head,);
+        type Error = Rejection;
+        type Future = WithAutoOptionsFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let is_options = route::with(|route| route.method() == Method::OPTIONS);
+            WithAutoOptionsFuture {
+                future: self.filter.filter(Internal),
+                is_options,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithAutoOptionsFuture<F> {
+        #[pin]
+        future: F,
+        is_options: bool,
+    }
+
+    impl<F> Future for WithAutoOptionsFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Head,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            match ready!(pin.future.try_poll(cx)) {
+                Ok(reply) => Poll::Ready(Ok((Head(reply.into_response()),))),
+                Err(err) => {
+                    let err = err.into();
+                    let methods = err.allowed_methods();
+                    if !*pin.is_options || methods.is_empty() {
+                        return Poll::Ready(Err(err));
+                    }
+                    Poll::Ready(Ok((Head(allow_response(methods)),)))
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
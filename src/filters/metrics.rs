@@ -0,0 +1,416 @@
+//! Prometheus metrics Filters
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use http::header::{HeaderValue, CONTENT_TYPE};
+use http::Method;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::IsReject;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithMetrics;
+
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const SIZE_BUCKETS: &[f64] = &[
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+];
+
+/// Create a wrapping filter that records Prometheus metrics about requests
+/// in the global [`Registry`].
+///
+/// These metrics are recorded:
+///
+/// - `http_requests_total`: a counter of requests
+/// - `http_request_duration_seconds`: a histogram of the time taken to reply
+/// - `http_response_size_bytes`: a histogram of reply body lengths, for
+///   bodies with a length known up front
+/// - `http_requests_in_flight`: a gauge of requests being handled
+///
+/// All but the gauge are labeled with the `method`, `route`, and `status`
+/// of the request. The route is the template of the path filters that
+/// matched, such as `/users/{u32}`, rather than the path itself, so that the
+/// number of series stays bounded. Requests that matched no path filters
+/// have the route `unmatched`. For the same reason, methods other than the
+/// standard ones have the method `other`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let api = warp::path!("users" / u32)
+///     .map(|id| format!("user #{}", id))
+///     .with(warp::metrics());
+///
+/// let metrics = warp::path("metrics").and(warp::metrics::endpoint());
+///
+/// let routes = api.or(metrics);
+/// ```
+pub fn metrics() -> Metrics {
+    Metrics {
+        registry: Registry::global().clone(),
+    }
+}
+
+/// Create a filter that replies with the metrics of the global [`Registry`],
+/// in the Prometheus text exposition format.
+///
+/// The filter always matches, so it usually follows a path filter.
+pub fn endpoint() -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    Registry::global().endpoint()
+}
+
+/// Decorates a [`Filter`](crate::Filter) to record metrics about requests.
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    registry: Registry,
+}
+
+/// A set of request metrics.
+///
+/// Clones share the same metrics.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    in_flight: AtomicI64,
+    series: Mutex<BTreeMap<Labels, Series>>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Labels {
+    method: &'static str,
+    route: String,
+    status: u16,
+}
+
+#[derive(Debug)]
+struct Series {
+    requests: u64,
+    duration: Histogram,
+    size: Histogram,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Metrics {
+    /// Records the metrics in `registry`, instead of the global one.
+    pub fn registry(mut self, registry: &Registry) -> Self {
+        self.registry = registry.clone();
+        self
+    }
+}
+
+impl<F> Wrap<F> for Metrics
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithMetrics<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithMetrics {
+            filter,
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// The registry used by [`metrics()`] and [`endpoint()`].
+    pub fn global() -> &'static Registry {
+        static GLOBAL: OnceLock<Registry> = OnceLock::new();
+        GLOBAL.get_or_init(Registry::new)
+    }
+
+    /// Create a filter that replies with the metrics of this registry, in
+    /// the Prometheus text exposition format.
+    pub fn endpoint(&self) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+        let registry = self.clone();
+        crate::any().map(move || {
+            let mut res = Response::new(registry.render().into());
+            res.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+            );
+            res
+        })
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let series = self.inner.series.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Total number of HTTP requests.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for (labels, series) in series.iter() {
+            let _ = writeln!(out, "http_requests_total{{{}}} {}", labels, series.requests);
+        }
+
+        out.push_str(
+            "# HELP http_request_duration_seconds Time taken to reply to HTTP requests.\n",
+        );
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for (labels, series) in series.iter() {
+            series
+                .duration
+                .render(&mut out, "http_request_duration_seconds", labels);
+        }
+
+        out.push_str("# HELP http_response_size_bytes Size of HTTP response bodies.\n");
+        out.push_str("# TYPE http_response_size_bytes histogram\n");
+        for (labels, series) in series.iter() {
+            if series.size.count > 0 {
+                series
+                    .size
+                    .render(&mut out, "http_response_size_bytes", labels);
+            }
+        }
+
+        out.push_str("# HELP http_requests_in_flight Number of HTTP requests being handled.\n");
+        out.push_str("# TYPE http_requests_in_flight gauge\n");
+        let _ = writeln!(
+            out,
+            "http_requests_in_flight {}",
+            self.inner.in_flight.load(Ordering::SeqCst)
+        );
+
+        out
+    }
+
+    fn record(&self, labels: Labels, elapsed: Duration, size: Option<u64>) {
+        let mut series = self.inner.series.lock().unwrap();
+        let series = series.entry(labels).or_insert_with(|| Series {
+            requests: 0,
+            duration: Histogram::new(DURATION_BUCKETS),
+            size: Histogram::new(SIZE_BUCKETS),
+        });
+        series.requests += 1;
+        series.duration.observe(elapsed.as_secs_f64());
+        if let Some(size) = size {
+            series.size.observe(size as f64);
+        }
+    }
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Histogram {
+        Histogram {
+            bounds,
+            counts: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(&mut self.counts) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &Labels) {
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                name, labels, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            name, labels, self.count
+        );
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, self.sum);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, self.count);
+    }
+}
+
+// Clients can send any method, so only the standard ones are labeled as
+// they are.
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "other",
+    }
+}
+
+impl fmt::Display for Labels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "method=\"{}\",route=\"{}\",status=\"{}\"",
+            Escaped(self.method),
+            Escaped(&self.route),
+            self.status
+        )
+    }
+}
+
+// Escapes a label value.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::Ordering;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    use futures::{ready, TryFuture};
+    use hyper::body::HttpBody;
+    use pin_project::pin_project;
+
+    use super::{Labels, Registry};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::IsReject;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Measured(Response);
+
+    impl Reply for Measured {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithMetrics<F> {
+        pub(super) filter: F,
+        pub(super) registry: Registry,
+    }
+
+    impl<F> FilterBase for WithMetrics<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Measured,);
+        type Error = F::Error;
+        type Future = WithMetricsFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            self.registry.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+            WithMetricsFuture {
+                future: self.filter.filter(Internal),
+                in_flight: InFlight(self.registry.clone()),
+                started: tokio::time::Instant::now().into_std(),
+            }
+        }
+    }
+
+    // Counts the request as in flight until it is finished or dropped.
+    struct InFlight(Registry);
+
+    impl Drop for InFlight {
+        fn drop(&mut self) {
+            self.0.inner.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithMetricsFuture<F> {
+        #[pin]
+        future: F,
+        in_flight: InFlight,
+        started: Instant,
+    }
+
+    impl<F> Future for WithMetricsFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Measured,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let (result, status, size) = match ready!(pin.future.try_poll(cx)) {
+                Ok(reply) => {
+                    let res = reply.into_response();
+                    let status = res.status();
+                    let size = res.body().size_hint().exact();
+                    (Ok((Measured(res),)), status, size)
+                }
+                Err(reject) => {
+                    let status = reject.status();
+                    (Err(reject), status, None)
+                }
+            };
+
+            let elapsed = tokio::time::Instant::now().into_std() - *pin.started;
+            let labels = route::with(|route| Labels {
+                method: super::method_label(route.method()),
+                route: route.template().unwrap_or_else(|| "unmatched".to_owned()),
+                status: status.as_u16(),
+            });
+            pin.in_flight.0.record(labels, elapsed, size);
+
+            Poll::Ready(result)
+        }
+    }
+}
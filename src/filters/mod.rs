@@ -6,23 +6,53 @@
 pub mod addr;
 pub mod any;
 pub mod body;
+pub mod cache;
+pub mod capture;
+pub mod catch_panic;
+pub mod circuit_breaker;
 #[cfg(feature = "compression")]
 pub mod compression;
+pub mod concurrency_limit;
+pub mod conn;
 pub mod cookie;
 pub mod cors;
+pub mod csp;
+pub mod etag;
 pub mod ext;
+pub mod fault;
+pub mod flash;
 pub mod fs;
 pub mod header;
+pub mod health;
 pub mod host;
+pub mod inject;
+pub mod load_shed;
 pub mod log;
+pub mod maintenance;
 pub mod method;
+pub mod metrics;
 #[cfg(feature = "multipart")]
 pub mod multipart;
 pub mod path;
+pub mod precondition;
 pub mod query;
+pub mod rate_limit;
 pub mod reply;
+pub mod request;
+pub mod request_id;
+pub mod sample;
+pub mod scheme;
+pub mod security_headers;
+pub mod server_timing;
+pub mod session;
+pub mod singleflight;
 pub mod sse;
+pub mod tap;
+pub mod throttle;
+pub mod timeout;
 pub mod trace;
+pub mod version;
+pub mod wrap;
 #[cfg(feature = "websocket")]
 pub mod ws;
 
@@ -4,8 +4,18 @@
 //!
 //! - [`path`](./fn.path.html) matches a specific segment, like `/foo`.
 //! - [`param`](./fn.param.html) tries to parse a segment into a type, like `/:u16`.
+//! - [`param_decoded`](./fn.param_decoded.html) does the same after percent-decoding the segment.
+//! - [`param_with`](./fn.param_with.html) and [`regex`](./fn.regex.html) only match segments
+//!   passing a check, letting others fall through to other routes.
 //! - [`end`](./fn.end.html) matches when the path end is found.
+//! - [`glob`](./fn.glob.html) matches the rest of the path against a pattern, like `assets/**/*.js`.
+//! - [`normalize_slashes`](./fn.normalize_slashes.html) wraps routes so `/foo/` and `//foo` match `/foo`.
+//! - [`ignore_case`](./fn.ignore_case.html) wraps routes so `/Foo` matches `/foo`.
+//! - [`limits`](./fn.limits.html) wraps routes to reject paths with too many or too long segments.
+//! - [`mount`](./fn.mount.html) moves a tree of routes under a prefix, like `/api/v2`.
 //! - [`path!`](../../macro.path.html) eases combining multiple `path` and `param` filters.
+//! - [`pattern`](./fn.pattern.html) matches a template with named parameters, like `/users/{id}`,
+//!   extracted with [`params`](./fn.params.html).
 //!
 //! # Routing
 //!
@@ -125,17 +135,20 @@
 //! with an invalid body for route `/right-path-wrong-body` may try matching against `/wrong-path`
 //! and return the error from `/wrong-path` instead of the correct body-related error.
 
+use std::borrow::Cow;
 use std::convert::Infallible;
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use futures::future;
 use http::uri::PathAndQuery;
 
 use self::internal::Opaque;
-use crate::filter::{filter_fn, one, Filter, FilterBase, Internal, One, Tuple};
+use crate::filter::{filter_fn, one, BoxedFilter, Filter, FilterBase, Internal, One, Tuple};
 use crate::reject::{self, Rejection};
-use crate::route::{self, Route};
+use crate::route::{self, Route, Segment};
+use crate::router;
 
 /// Create an exact match path segment `Filter`.
 ///
@@ -206,21 +219,25 @@ where
 
     #[inline]
     fn filter(&self, _: Internal) -> Self::Future {
-        route::with(|route| {
-            let p = self.0.as_ref();
-            future::ready(with_segment(route, |seg| {
-                tracing::trace!("{:?}?: {:?}", p, seg);
-
-                if seg == p {
-                    Ok(())
-                } else {
-                    Err(reject::not_found())
-                }
-            }))
-        })
+        route::with(|route| future::ready(match_literal(route, self.0.as_ref())))
     }
 }
 
+fn match_literal(route: &mut Route, p: &str) -> Result<(), Rejection> {
+    let start = route.matched_path_index();
+    let literal = Segment::Literal(start, start + p.len());
+    let ignore_case = route.ignore_case();
+    with_segment(route, literal, |seg| {
+        tracing::trace!("{:?}?: {:?}", p, seg);
+
+        if seg == p || (ignore_case && seg.eq_ignore_ascii_case(p)) {
+            Ok(())
+        } else {
+            Err(reject::not_found())
+        }
+    })
+}
+
 /// Matches the end of a route.
 ///
 /// Note that _not_ including `end()` may result in shorter paths like
@@ -238,6 +255,7 @@ where
 pub fn end() -> impl Filter<Extract = (), Error = Rejection> + Copy {
     filter_fn(move |route| {
         if route.path().is_empty() {
+            route.set_path_matched(true);
             future::ok(())
         } else {
             future::err(reject::not_found())
@@ -265,7 +283,7 @@ pub fn end() -> impl Filter<Extract = (), Error = Rejection> + Copy {
 /// ```
 pub fn param<T: FromStr + Send + 'static>(
 ) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
-    filter_segment(|seg| {
+    filter_segment(Segment::Param(type_name::<T>()), |seg| {
         tracing::trace!("param?: {:?}", seg);
         if seg.is_empty() {
             return Err(reject::not_found());
@@ -274,6 +292,279 @@ pub fn param<T: FromStr + Send + 'static>(
     })
 }
 
+/// Match the rest of the path against a template with named parameters,
+/// like `/users/{id}/posts/{post}`.
+///
+/// The template is written like the ones of [`Router`](crate::Router)
+/// routes: a `{name}` segment matches any one segment, and a final `*`
+/// matches the rest of the path, leaving it for [`tail()`]. The values of
+/// the parameters are extracted by name with [`params()`], instead of
+/// positionally like with [`param()`]. Like routes, patterns can be
+/// [named](Pattern::name), to build paths to them with
+/// [`uri_for`](crate::uri_for).
+///
+/// # Panics
+///
+/// Panics if the template doesn't start with `/`, has an unclosed `{`, or
+/// has a `*` segment that isn't the last.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /users/7/posts/42
+/// let post = warp::path::pattern("/users/{id}/posts/{post}")
+///     .and(warp::path::params())
+///     .map(|params: warp::path::Params| {
+///         let id: u64 = params.parse("id").unwrap();
+///         format!("post {} of user {}", params.get("post").unwrap(), id)
+///     });
+/// ```
+pub fn pattern(template: &'static str) -> Pattern {
+    Pattern {
+        template,
+        parts: router::parse(template).into(),
+    }
+}
+
+/// A `Filter` matching the rest of the path against a template.
+///
+/// Constructed from [`pattern()`].
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    template: &'static str,
+    parts: Arc<[router::Part]>,
+}
+
+impl Pattern {
+    /// Names the pattern, to build paths to it with
+    /// [`uri_for`](crate::uri_for).
+    ///
+    /// Only patterns can be named this way: other path filters, such as
+    /// those of [`path!`](crate::path!), don't know the template they
+    /// match until they run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` was already given to a different template.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let user = warp::path::pattern("/users/{id}")
+    ///     .name("user_detail")
+    ///     .and(warp::router::param::<u32>("id"))
+    ///     .map(|id| format!("user #{}", id));
+    ///
+    /// assert_eq!(
+    ///     warp::uri_for("user_detail", &[("id", 42)]).as_deref(),
+    ///     Some("/users/42"),
+    /// );
+    /// ```
+    pub fn name(self, name: &'static str) -> Pattern {
+        router::name_template(name, self.template);
+        self
+    }
+
+    /// The template the pattern matches.
+    pub fn template(&self) -> &'static str {
+        self.template
+    }
+}
+
+impl FilterBase for Pattern {
+    type Extract = ();
+    type Error = Rejection;
+    type Future = future::Ready<Result<Self::Extract, Self::Error>>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        route::with(|route| {
+            tracing::trace!("pattern {:?}?: {:?}", self.template, route.path());
+            if router::match_template(route, &self.parts) {
+                future::ok(())
+            } else {
+                future::err(reject::not_found())
+            }
+        })
+    }
+}
+
+pub use crate::router::{params, Params};
+
+/// Extract a segment like [`param()`], percent-decoding it with `decode`
+/// before parsing it.
+///
+/// The path is split into segments before decoding, so a `%2F` in the
+/// segment is decoded to a `/` in the parameter, instead of separating
+/// segments. Segments that can't be decoded with [`Decode::Strict`] are
+/// rejected with a `404 Not Found`, like those that can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use warp::path::Decode;
+/// use warp::Filter;
+///
+/// // GET /files/a%20b.txt extracts "a b.txt"
+/// let file = warp::path("files")
+///     .and(warp::path::param_decoded::<String>(Decode::Strict))
+///     .map(|name: String| name);
+/// ```
+pub fn param_decoded<T: FromStr + Send + 'static>(
+    decode: Decode,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        let result = with_segment(route, Segment::Param(type_name::<T>()), |seg| {
+            tracing::trace!("param_decoded?: {:?}", seg);
+            if seg.is_empty() {
+                return Err(reject::not_found());
+            }
+            decode
+                .decode(seg)
+                .and_then(|seg| T::from_str(&seg).ok())
+                .map(one)
+                .ok_or_else(reject::not_found)
+        });
+        future::ready(result)
+    })
+}
+
+/// How to percent-decode paths, for [`param_decoded`], [`Tail::decoded`],
+/// and [`FullPath::decoded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decode {
+    /// Keeps the path as it was sent, still percent-encoded.
+    Raw,
+    /// Decodes the path, replacing bytes that aren't valid UTF-8 with `�`.
+    Lossy,
+    /// Decodes the path, failing if it isn't valid UTF-8.
+    Strict,
+}
+
+impl Decode {
+    fn decode(self, path: &str) -> Option<Cow<'_, str>> {
+        let decoded = percent_encoding::percent_decode_str(path);
+        match self {
+            Decode::Raw => Some(Cow::Borrowed(path)),
+            Decode::Lossy => Some(decoded.decode_utf8_lossy()),
+            Decode::Strict => decoded.decode_utf8().ok(),
+        }
+    }
+}
+
+/// Extract an optional segment, parsing it like [`param()`].
+///
+/// If the path has no more segments, `None` is extracted and nothing is
+/// matched, so `/reports/7` and `/reports/7/summary` can share a route. A
+/// segment that can't be parsed is rejected with a `404 Not Found`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /reports/7 and GET /reports/7/summary
+/// let report = warp::path!("reports" / u32 / ..)
+///     .and(warp::path::param_opt::<String>())
+///     .and(warp::path::end())
+///     .map(|id: u32, view: Option<String>| {
+///         format!("report {} as {}", id, view.as_deref().unwrap_or("full"))
+///     });
+/// ```
+pub fn param_opt<T: FromStr + Send + 'static>(
+) -> impl Filter<Extract = One<Option<T>>, Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        if route.path().is_empty() {
+            return future::ok(one(None));
+        }
+        let result = with_segment(route, Segment::Param(type_name::<T>()), |seg| {
+            tracing::trace!("param_opt?: {:?}", seg);
+            if seg.is_empty() {
+                return Err(reject::not_found());
+            }
+            T::from_str(seg)
+                .map(|param| one(Some(param)))
+                .map_err(|_| reject::not_found())
+        });
+        future::ready(result)
+    })
+}
+
+/// Extract a segment like [`param()`], if it also passes `validate`.
+///
+/// Segments that can't be parsed, or that `validate` returns `false` for,
+/// are rejected with a `404 Not Found`, so the request can fall through to
+/// other routes.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /page/3, but not /page/0
+/// let page = warp::path("page")
+///     .and(warp::path::param_with(|page: &u32| *page > 0))
+///     .map(|page: u32| format!("page {}", page));
+/// ```
+pub fn param_with<T, F>(validate: F) -> impl Filter<Extract = One<T>, Error = Rejection> + Clone
+where
+    T: FromStr + Send + 'static,
+    F: Fn(&T) -> bool + Clone + Send + Sync + 'static,
+{
+    filter_fn(move |route| {
+        let result = with_segment(route, Segment::Param(type_name::<T>()), |seg| {
+            tracing::trace!("param_with?: {:?}", seg);
+            match T::from_str(seg) {
+                Ok(param) if !seg.is_empty() && validate(&param) => Ok(one(param)),
+                _ => Err(reject::not_found()),
+            }
+        });
+        future::ready(result)
+    })
+}
+
+/// Extract a segment as a `String`, if it matches the regular expression
+/// `pattern`.
+///
+/// Add `^` and `$` to the pattern to match the whole segment. Segments that
+/// don't match are rejected with a `404 Not Found`, so the request can fall
+/// through to other routes.
+///
+/// *This function requires the `"regex"` feature.*
+///
+/// # Panics
+///
+/// Panics if `pattern` isn't a valid regular expression.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /blobs/0123456789abcdef0123456789abcdef
+/// let blob = warp::path("blobs")
+///     .and(warp::path::regex("^[a-f0-9]{32}$"))
+///     .map(|id: String| format!("blob {}", id));
+/// ```
+#[cfg(feature = "regex")]
+pub fn regex(pattern: &str) -> impl Filter<Extract = One<String>, Error = Rejection> + Clone {
+    let regex = regex::Regex::new(pattern).expect("path::regex pattern is valid");
+    filter_fn(move |route| {
+        let result = with_segment(route, Segment::Param("String"), |seg| {
+            tracing::trace!("regex {:?}?: {:?}", regex.as_str(), seg);
+            if !seg.is_empty() && regex.is_match(seg) {
+                Ok(one(seg.to_owned()))
+            } else {
+                Err(reject::not_found())
+            }
+        });
+        future::ready(result)
+    })
+}
+
 /// Extract the unmatched tail of the path.
 ///
 /// This will return a `Tail`, which allows access to the rest of the path
@@ -300,7 +591,58 @@ pub fn tail() -> impl Filter<Extract = One<Tail>, Error = Infallible> + Copy {
         // has been matched now.
         let end = path.path().len() - idx;
         route.set_unmatched_path(end);
+        route.push_template(idx, Segment::Tail);
+
+        future::ok(one(Tail {
+            path,
+            start_index: idx,
+        }))
+    })
+}
+
+/// Match the rest of the path against a glob pattern, extracting it if it
+/// matches.
+///
+/// Pattern segments are separated by `/`. In a segment, `*` matches any
+/// characters, and `?` any one character. A `**` segment matches any number
+/// of segments, including none. Like [`tail()`], the whole rest of the path
+/// is matched.
+///
+/// Paths that don't match are rejected with a `404 Not Found`.
+///
+/// # Panics
+///
+/// Panics if `pattern` is empty.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /assets/app.js, /assets/vendor/react/index.js, ...
+/// let scripts = warp::path::glob("assets/**/*.js")
+///     .map(|path: warp::path::Tail| format!("script at {}", path.as_str()));
+/// ```
+pub fn glob(pattern: &str) -> impl Filter<Extract = One<Tail>, Error = Rejection> + Clone {
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    assert!(
+        !pattern.is_empty(),
+        "path::glob pattern should not be empty"
+    );
+    let segments: Arc<[String]> = pattern.split('/').map(str::to_owned).collect();
+
+    filter_fn(move |route| {
+        let path = path_and_query(&route);
+        let idx = route.matched_path_index();
+        let rest = &path.path()[idx..];
+        tracing::trace!("glob {:?}?: {:?}", segments, rest);
 
+        let matched = glob_segments(&segments, &rest.split('/').collect::<Vec<_>>());
+        if !matched {
+            return future::err(reject::not_found());
+        }
+        route.set_unmatched_path(rest.len());
+        route.push_template(idx, Segment::Tail);
         future::ok(one(Tail {
             path,
             start_index: idx,
@@ -308,6 +650,57 @@ pub fn tail() -> impl Filter<Extract = One<Tail>, Error = Infallible> + Copy {
     })
 }
 
+fn glob_segments(pattern: &[String], path: &[&str]) -> bool {
+    wildcard(
+        pattern,
+        path,
+        |segment| segment == "**",
+        |pattern, segment| glob_segment(pattern.as_bytes(), segment.as_bytes()),
+    )
+}
+
+fn glob_segment(pattern: &[u8], segment: &[u8]) -> bool {
+    wildcard(
+        pattern,
+        segment,
+        |&c| c == b'*',
+        |&c, &s| c == b'?' || c == s,
+    )
+}
+
+// Matches `text` against a pattern with stars matching any run of items.
+//
+// Only the last star is backtracked to, since matching more with an earlier
+// one can't help once a later one matched: this takes at most
+// `pattern.len() * text.len()` steps, however many stars there are.
+fn wildcard<P, T>(
+    pattern: &[P],
+    text: &[T],
+    is_star: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The pattern index after the last star, and the text index it's
+    // currently matched up to.
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && is_star(&pattern[p]) {
+            p += 1;
+            star = Some((p, t));
+        } else if p < pattern.len() && matches(&pattern[p], &text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((after, matched)) = star {
+            p = after;
+            t = matched + 1;
+            star = Some((after, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(is_star)
+}
+
 /// Represents that tail part of a request path, returned by the `tail()` filter.
 pub struct Tail {
     path: PathAndQuery,
@@ -319,6 +712,14 @@ impl Tail {
     pub fn as_str(&self) -> &str {
         &self.path.path()[self.start_index..]
     }
+
+    /// Get the remaining path, percent-decoded with `decode`.
+    ///
+    /// Returns `None` if it isn't valid UTF-8 with [`Decode::Strict`]. Note
+    /// that a decoded `%2F` can't be told apart from a `/`.
+    pub fn decoded(&self, decode: Decode) -> Option<Cow<'_, str>> {
+        decode.decode(self.as_str())
+    }
 }
 
 impl fmt::Debug for Tail {
@@ -424,6 +825,14 @@ impl FullPath {
     pub fn as_str(&self) -> &str {
         &self.0.path()
     }
+
+    /// Get the request path, percent-decoded with `decode`.
+    ///
+    /// Returns `None` if it isn't valid UTF-8 with [`Decode::Strict`]. Note
+    /// that a decoded `%2F` can't be told apart from a `/`.
+    pub fn decoded(&self, decode: Decode) -> Option<Cow<'_, str>> {
+        decode.decode(self.as_str())
+    }
 }
 
 impl fmt::Debug for FullPath {
@@ -432,15 +841,447 @@ impl fmt::Debug for FullPath {
     }
 }
 
-fn filter_segment<F, U>(func: F) -> impl Filter<Extract = U, Error = Rejection> + Copy
+/// Extract the template of the path matched so far.
+///
+/// The template has the literal segments matched by [`path()`], the type of
+/// those matched by [`param()`] in braces, and `*` for a [`tail()`]. Unlike
+/// the request path, it is the same for every request matching a route, so
+/// it is a good key for metrics and logs.
+///
+/// # Example
+///
+/// ```
+/// use warp::{Filter, path::Template};
+///
+/// // GET /users/7/posts would return "/users/{u32}/posts".
+/// let route = warp::path!("users" / u32 / "posts")
+///     .and(warp::path::template())
+///     .map(|_id, template: Template| template.to_string());
+/// ```
+pub fn template() -> impl Filter<Extract = One<Template>, Error = Infallible> + Copy {
+    filter_fn(move |route| {
+        let template = route.template().unwrap_or_else(|| "/".to_owned());
+        future::ok(one(Template(template)))
+    })
+}
+
+/// Represents the template of a matched path, returned by the `template()`
+/// filter.
+///
+/// If no path segments were matched, the template is `/`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Template(String);
+
+impl Template {
+    /// Get the `&str` representation of the template.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+/// A type extracted from a request path, by [`typed()`].
+///
+/// With the `"derive"` feature, it can be derived for a struct with named
+/// fields, from a path in the `uri_path` attribute. The path's literal
+/// segments must match, and each `:name` segment is parsed into the field
+/// `name`, like [`param()`] would.
+pub trait UriPath: Sized {
+    /// Create a filter matching the whole unmatched path, and extracting
+    /// it as `Self`.
+    fn filter() -> BoxedFilter<(Self,)>;
+}
+
+/// Derives [`UriPath`] for a struct.
+///
+/// *This macro requires the `"derive"` feature.*
+#[cfg(feature = "derive")]
+pub use warp_derive::UriPath;
+
+/// Extract the rest of the path as a `T`, rejecting if it doesn't match.
+///
+/// Like [`path!`](crate::path!), the path must end after it.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use warp::path::UriPath;
+/// use warp::Filter;
+///
+/// #[derive(UriPath)]
+/// #[uri_path("/users/:user_id/posts/:post_id")]
+/// struct UserPost {
+///     user_id: u64,
+///     post_id: u64,
+/// }
+///
+/// // GET /users/7/posts/42
+/// let route = warp::path::typed().map(|post: UserPost| {
+///     format!("post {} of user {}", post.post_id, post.user_id)
+/// });
+/// # }
+/// ```
+pub fn typed<T: UriPath>() -> BoxedFilter<(T,)> {
+    T::filter()
+}
+
+/// Mount a tree of filters under a path `prefix`, like `/api/v2`.
+///
+/// The segments of `prefix` are matched like [`path()`] literals, and the
+/// wrapped filters only see the rest of the path, so routes written
+/// without the prefix can be moved under one without changing them. The
+/// prefix is still part of [`full()`] paths, and of the [`template()`]
+/// shown by logs and metrics.
+///
+/// # Panics
+///
+/// Panics if `prefix` doesn't start with `/`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let users = warp::path!("users" / u32).map(|id| format!("user {}", id));
+///
+/// // GET /api/v2/users/7
+/// let routes = warp::mount("/api/v2", users);
+/// ```
+pub fn mount<F>(prefix: &'static str, filter: F) -> Mount<F>
+where
+    F: Filter + Clone,
+    F::Error: Into<Rejection>,
+{
+    assert!(
+        prefix.starts_with('/'),
+        "mount prefix should start with a slash: {:?}",
+        prefix
+    );
+    let segments = prefix
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    Mount {
+        segments: segments.into(),
+        filter,
+    }
+}
+
+/// A `Filter` matching a path prefix before the filters it wraps.
+///
+/// Constructed from [`mount()`].
+#[allow(missing_debug_implementations)]
+#[derive(Clone)]
+pub struct Mount<F> {
+    segments: Arc<[&'static str]>,
+    filter: F,
+}
+
+impl<F> FilterBase for Mount<F>
+where
+    F: Filter + Clone,
+    F::Error: Into<Rejection>,
+{
+    type Extract = F::Extract;
+    type Error = Rejection;
+    type Future = internal::MaybeRejected<F::Future>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let matched = route::with(|route| {
+            let start = route.matched_path_index();
+            let matched = self
+                .segments
+                .iter()
+                .try_for_each(|segment| match_literal(route, segment));
+            if matched.is_err() {
+                route.reset_matched_path_index(start);
+            }
+            matched
+        });
+        match matched {
+            Ok(()) => internal::MaybeRejected::Filtering(self.filter.filter(Internal)),
+            Err(rejection) => internal::MaybeRejected::Rejected(Some(rejection)),
+        }
+    }
+}
+
+/// Create a wrapping filter that normalizes the slashes of the unmatched
+/// path, so `/foo/`, `//foo`, and `/foo` don't need separate routes.
+///
+/// Runs of slashes are collapsed into one, and a trailing slash is removed,
+/// unless [`NormalizeSlashes::append_trailing_slash`] is used. What happens
+/// to requests whose path changes is decided by `policy`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::path::SlashPolicy;
+///
+/// let routes = warp::path!("users" / u32)
+///     .map(|id| format!("user {}", id))
+///     // GET /users/7/ is redirected to /users/7
+///     .with(warp::path::normalize_slashes(SlashPolicy::Redirect));
+/// ```
+pub fn normalize_slashes(policy: SlashPolicy) -> NormalizeSlashes {
+    NormalizeSlashes {
+        policy,
+        trailing_slash: false,
+    }
+}
+
+/// What [`normalize_slashes`] does with requests whose path isn't normal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlashPolicy {
+    /// Changes the path before the wrapped filters see it.
+    Rewrite,
+    /// Replies with a `301 Moved Permanently` redirect to the normal path.
+    ///
+    /// Clients may change the method of the request to `GET` when following
+    /// it.
+    Redirect,
+    /// Replies with a `308 Permanent Redirect` to the normal path, which
+    /// keeps the method and body of the request.
+    PermanentRedirect,
+}
+
+/// Decorates a [`Filter`] to normalize the slashes of the request path.
+///
+/// Constructed with [`normalize_slashes`].
+#[derive(Clone, Copy, Debug)]
+pub struct NormalizeSlashes {
+    policy: SlashPolicy,
+    trailing_slash: bool,
+}
+
+impl NormalizeSlashes {
+    /// Adds a trailing slash to paths without one, instead of removing it.
+    pub fn append_trailing_slash(mut self) -> Self {
+        self.trailing_slash = true;
+        self
+    }
+
+    // The normal form of the unmatched path, if it isn't already normal.
+    fn normalize(&self, rest: &str) -> Option<String> {
+        let mut normal = String::with_capacity(rest.len() + 1);
+        for segment in rest.split('/').filter(|segment| !segment.is_empty()) {
+            normal.push_str(segment);
+            normal.push('/');
+        }
+        if !self.trailing_slash || normal.is_empty() {
+            normal.pop();
+        }
+        if normal == rest {
+            None
+        } else {
+            Some(normal)
+        }
+    }
+}
+
+impl<F> crate::filter::Wrap<F> for NormalizeSlashes
+where
+    F: Filter + Clone + Send,
+    F::Extract: crate::reply::Reply,
+    F::Error: crate::reject::IsReject,
+{
+    type Wrapped = internal::WithNormalizeSlashes<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        internal::WithNormalizeSlashes {
+            filter,
+            normalize: *self,
+        }
+    }
+}
+
+/// Create a wrapping filter that matches the path literals of the wrapped
+/// filters ignoring ASCII case, so `/Api/Users` matches `path!("api" /
+/// "users")`.
+///
+/// This applies to [`path()`], [`path!`](crate::path!),
+/// [`pattern()`], and [`Router`](crate::Router) routes. Wrapping all routes
+/// makes every path case-insensitive for the server. Parameters are still
+/// extracted as they appear in the request.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let users = warp::path!("api" / "users").map(|| "users");
+///
+/// // GET /api/users, /API/Users, ...
+/// let routes = users.with(warp::path::ignore_case());
+/// ```
+pub fn ignore_case() -> IgnoreCase {
+    IgnoreCase { _p: () }
+}
+
+/// Decorates a [`Filter`] to match path literals ignoring case.
+///
+/// Constructed with [`ignore_case`].
+#[derive(Clone, Copy, Debug)]
+pub struct IgnoreCase {
+    _p: (),
+}
+
+impl<F> crate::filter::Wrap<F> for IgnoreCase
+where
+    F: Filter + Clone,
+{
+    type Wrapped = internal::WithIgnoreCase<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        internal::WithIgnoreCase { filter }
+    }
+}
+
+/// Create a wrapping filter that rejects requests whose unmatched path has
+/// more than `max_segments` segments, or a segment longer than
+/// `max_segment_len` bytes, before the wrapped filters try to match it.
+///
+/// This bounds the work of matching abusive paths, such as ones with
+/// thousands of segments. Too many segments are rejected with a `414 URI
+/// Too Long`, and too long segments with a `400 Bad Request`. Lengths are
+/// counted before percent-decoding.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let routes = warp::path!("users" / String)
+///     .map(|name| format!("user {}", name))
+///     .with(warp::path::limits(16, 256));
+/// ```
+pub fn limits(max_segments: usize, max_segment_len: usize) -> Limits {
+    Limits {
+        max_segments,
+        max_segment_len,
+    }
+}
+
+/// Decorates a [`Filter`] to reject paths over some limits.
+///
+/// Constructed with [`limits`].
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    max_segments: usize,
+    max_segment_len: usize,
+}
+
+impl Limits {
+    fn check(&self, path: &str) -> Result<(), Rejection> {
+        if path.is_empty() {
+            return Ok(());
+        }
+        let mut segments = 0;
+        for segment in path.split('/') {
+            segments += 1;
+            if segments > self.max_segments {
+                return Err(reject::known(TooManySegments {
+                    max: self.max_segments,
+                }));
+            }
+            if segment.len() > self.max_segment_len {
+                return Err(reject::known(SegmentTooLong {
+                    max: self.max_segment_len,
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F> crate::filter::Wrap<F> for Limits
+where
+    F: Filter + Clone,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = internal::WithLimits<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        internal::WithLimits {
+            filter,
+            limits: *self,
+        }
+    }
+}
+
+/// An error used to reject requests whose path has too many segments, by
+/// [`limits`].
+#[derive(Debug)]
+pub struct TooManySegments {
+    max: usize,
+}
+
+impl TooManySegments {
+    /// The maximum number of segments.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl fmt::Display for TooManySegments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request path has more than {} segments", self.max)
+    }
+}
+
+impl std::error::Error for TooManySegments {}
+
+/// An error used to reject requests whose path has a too long segment, by
+/// [`limits`].
+#[derive(Debug)]
+pub struct SegmentTooLong {
+    max: usize,
+}
+
+impl SegmentTooLong {
+    /// The maximum length of a segment, in bytes.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl fmt::Display for SegmentTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Request path has a segment longer than {} bytes",
+            self.max
+        )
+    }
+}
+
+impl std::error::Error for SegmentTooLong {}
+
+fn filter_segment<F, U>(
+    segment: Segment,
+    func: F,
+) -> impl Filter<Extract = U, Error = Rejection> + Copy
 where
     F: Fn(&str) -> Result<U, Rejection> + Copy,
     U: Tuple + Send + 'static,
 {
-    filter_fn(move |route| future::ready(with_segment(route, func)))
+    filter_fn(move |route| future::ready(with_segment(route, segment, func)))
 }
 
-fn with_segment<F, U>(route: &mut Route, func: F) -> Result<U, Rejection>
+fn with_segment<F, U>(route: &mut Route, matched: Segment, func: F) -> Result<U, Rejection>
 where
     F: Fn(&str) -> Result<U, Rejection>,
 {
@@ -448,11 +1289,20 @@ where
     let ret = func(seg);
     if ret.is_ok() {
         let idx = seg.len();
+        let start = route.matched_path_index();
         route.set_unmatched_path(idx);
+        route.push_template(start, matched);
     }
     ret
 }
 
+// The last part of a type's path, such as `u32` or `String`.
+fn type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
 fn segment(route: &Route) -> &str {
     route
         .path()
@@ -580,6 +1430,104 @@ macro_rules! __internal_path {
     });
 }
 
+/// Convenient way to build a route from a method, a path, and a handler.
+///
+/// The path is written like with [`path!`](crate::path!), optionally
+/// starting with a `/`, and the method is one of `GET`, `POST`, `PUT`,
+/// `DELETE`, `HEAD`, `OPTIONS`, or `PATCH`. The handler is called with the
+/// parameters of the path, with [`map`](crate::Filter::map), or with
+/// [`and_then`](crate::Filter::and_then) if it's preceded by `async`, so a
+/// handler taking the wrong number or types of parameters fails to compile.
+///
+/// Without a handler, the filter matching the method and path is returned,
+/// to combine with other filters.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use warp::Filter;
+///
+/// fn list_posts(user_id: u64) -> String {
+///     format!("posts of user {}", user_id)
+/// }
+///
+/// async fn delete_post(user_id: u64, post_id: u64) -> Result<String, Infallible> {
+///     Ok(format!("deleted post {} of user {}", post_id, user_id))
+/// }
+///
+/// let index = warp::route!(GET "/" => || "index");
+/// let list = warp::route!(GET "/users" / u64 / "posts" => list_posts);
+/// let delete = warp::route!(DELETE "/users" / u64 / "posts" / u64 => async delete_post);
+///
+/// // The same as `warp::post().and(warp::path!("users"))`.
+/// let create = warp::route!(POST "/users")
+///     .and(warp::body::json())
+///     .map(|user: std::collections::HashMap<String, String>| warp::reply::json(&user));
+///
+/// let routes = index.or(list).or(delete).or(create);
+/// ```
+#[macro_export]
+macro_rules! route {
+    ($method:ident $($pieces:tt)/+ => async $handler:expr) => (
+        $crate::Filter::and_then($crate::route!($method $($pieces)/+), $handler)
+    );
+    ($method:ident $($pieces:tt)/+ => $handler:expr) => (
+        $crate::Filter::map($crate::route!($method $($pieces)/+), $handler)
+    );
+    ($method:ident $($pieces:tt)/+) => (
+        $crate::Filter::and(
+            $crate::__internal_route!(@method $method),
+            $crate::__internal_route!(@path $($pieces)/+)
+        )
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+// not public API
+macro_rules! __internal_route {
+    (@method GET) => ($crate::get());
+    (@method POST) => ($crate::post());
+    (@method PUT) => ($crate::put());
+    (@method DELETE) => ($crate::delete());
+    (@method HEAD) => ($crate::head());
+    (@method OPTIONS) => ($crate::options());
+    (@method PATCH) => ($crate::patch());
+    (@method $other:ident) => (
+        compile_error!(concat!("unsupported method in route!: ", stringify!($other)))
+    );
+
+    (@path "/") => (
+        $crate::path::end()
+    );
+    (@path $first:literal) => (
+        $crate::Filter::and($crate::__internal_route!(@first $first), $crate::path::end())
+    );
+    (@path $first:literal / ..) => (
+        $crate::__internal_route!(@first $first)
+    );
+    (@path $first:literal $(/ $tail:tt)+) => (
+        $crate::Filter::and($crate::__internal_route!(@first $first), $crate::path!($($tail)/+))
+    );
+    (@path $($pieces:tt)/+) => (
+        $crate::path!($($pieces)/+)
+    );
+
+    // Like `path!`'s literal segments, without the leading slash.
+    (@first $s:literal) => ({
+        #[derive(Clone, Copy)]
+        struct __StaticPath;
+        impl ::std::convert::AsRef<str> for __StaticPath {
+            fn as_ref(&self) -> &str {
+                static S: &str = $s;
+                S.strip_prefix('/').unwrap_or(S)
+            }
+        }
+        $crate::path(__StaticPath)
+    });
+}
+
 // path! compile fail tests
 
 /// ```compile_fail
@@ -603,7 +1551,33 @@ macro_rules! __internal_path {
 /// ```
 fn _path_macro_compile_fail() {}
 
+// route! compile fail tests
+
+/// ```compile_fail
+/// // The handler must take the path's parameters.
+/// warp::route!(GET "/users" / u64 => || "users");
+/// ```
+///
+/// ```compile_fail
+/// warp::route!(FETCH "/users");
+/// ```
+fn _route_macro_compile_fail() {}
+
 mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use http::{header, StatusCode};
+    use pin_project::pin_project;
+
+    use super::{Limits, NormalizeSlashes, SlashPolicy};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{IsReject, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
     // Used to prevent users from naming this type.
     //
     // For instance, `Exact<Opaque<String>>` means a user cannot depend
@@ -618,6 +1592,194 @@ mod internal {
             self.0.as_ref()
         }
     }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithIgnoreCase<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithIgnoreCase<F>
+    where
+        F: Filter + Clone,
+    {
+        type Extract = F::Extract;
+        type Error = F::Error;
+        type Future = WithIgnoreCaseFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let previous = route::with(|route| {
+                let previous = route.ignore_case();
+                route.set_ignore_case(true);
+                previous
+            });
+            WithIgnoreCaseFuture {
+                future: self.filter.filter(Internal),
+                previous,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithIgnoreCaseFuture<F> {
+        #[pin]
+        future: F,
+        previous: bool,
+    }
+
+    impl<F: TryFuture> Future for WithIgnoreCaseFuture<F> {
+        type Output = Result<F::Ok, F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.future.try_poll(cx));
+            // Filters tried after these ones match case again.
+            let previous = *pin.previous;
+            route::with(|route| route.set_ignore_case(previous));
+            Poll::Ready(result)
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithLimits<F> {
+        pub(super) filter: F,
+        pub(super) limits: Limits,
+    }
+
+    impl<F> FilterBase for WithLimits<F>
+    where
+        F: Filter + Clone,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = MaybeRejected<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            match route::with(|route| self.limits.check(route.path())) {
+                Ok(()) => MaybeRejected::Filtering(self.filter.filter(Internal)),
+                Err(rejection) => {
+                    tracing::debug!("path over limits: {:?}", rejection);
+                    MaybeRejected::Rejected(Some(rejection))
+                }
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project(project = MaybeRejectedProj)]
+    pub enum MaybeRejected<F> {
+        Filtering(#[pin] F),
+        Rejected(Option<Rejection>),
+    }
+
+    impl<F> Future for MaybeRejected<F>
+    where
+        F: TryFuture,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<F::Ok, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.project() {
+                MaybeRejectedProj::Filtering(future) => future.try_poll(cx).map_err(Into::into),
+                MaybeRejectedProj::Rejected(rejection) => {
+                    Poll::Ready(Err(rejection.take().expect("polled after complete")))
+                }
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct Normalized(Response);
+
+    impl Reply for Normalized {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithNormalizeSlashes<F> {
+        pub(super) filter: F,
+        pub(super) normalize: NormalizeSlashes,
+    }
+
+    impl<F> FilterBase for WithNormalizeSlashes<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Normalized,);
+        type Error = F::Error;
+        type Future = WithNormalizeSlashesFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let redirect = route::with(|route| {
+                let idx = route.matched_path_index();
+                let normal = self.normalize.normalize(route.path())?;
+                let path = format!("{}{}", &route.full_path()[..idx], normal);
+                tracing::trace!("normalized path {:?} to {:?}", route.full_path(), path);
+
+                let status = match self.normalize.policy {
+                    SlashPolicy::Rewrite => {
+                        route.set_path(&path);
+                        return None;
+                    }
+                    SlashPolicy::Redirect => StatusCode::MOVED_PERMANENTLY,
+                    SlashPolicy::PermanentRedirect => StatusCode::PERMANENT_REDIRECT,
+                };
+                let location = match route.uri().query() {
+                    Some(query) => format!("{}?{}", path, query),
+                    None => path,
+                };
+                let mut res = Response::default();
+                *res.status_mut() = status;
+                if let Ok(location) = header::HeaderValue::from_str(&location) {
+                    res.headers_mut().insert(header::LOCATION, location);
+                }
+                Some(res)
+            });
+
+            match redirect {
+                Some(res) => WithNormalizeSlashesFuture::Redirect(Some(res)),
+                None => WithNormalizeSlashesFuture::Filtering(self.filter.filter(Internal)),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project(project = WithNormalizeSlashesProj)]
+    pub enum WithNormalizeSlashesFuture<F> {
+        Filtering(#[pin] F),
+        Redirect(Option<Response>),
+    }
+
+    impl<F> Future for WithNormalizeSlashesFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+    {
+        type Output = Result<(Normalized,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.project() {
+                WithNormalizeSlashesProj::Filtering(future) => {
+                    let reply = ready!(future.try_poll(cx))?;
+                    Poll::Ready(Ok((Normalized(reply.into_response()),)))
+                }
+                WithNormalizeSlashesProj::Redirect(res) => {
+                    let res = res.take().expect("polled after complete");
+                    Poll::Ready(Ok((Normalized(res),)))
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,152 @@
+//! Precondition Filters
+//!
+//! These filters guard updates against lost writes: a client sends the
+//! `ETag` or `Last-Modified` of the version it read in `If-Match` or
+//! `If-Unmodified-Since`, and the update is refused with a
+//! `412 Precondition Failed` if the resource changed since.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::SystemTime;
+
+use headers::{ETag, IfMatch, IfUnmodifiedSince};
+
+use crate::filter::{Filter, One};
+use crate::header;
+use crate::reject::{self, Rejection};
+
+/// Create a `Filter` that extracts the request's `If-Match` header, if any.
+///
+/// Rejects if the header can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use warp::headers::IfMatch;
+///
+/// let if_match = warp::precondition::if_match();
+/// ```
+pub fn if_match() -> impl Filter<Extract = One<Option<IfMatch>>, Error = Rejection> + Copy {
+    header::typed_optional()
+}
+
+/// Create a `Filter` that extracts the request's `If-Unmodified-Since`
+/// header, if any.
+///
+/// Rejects if the header can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use warp::headers::IfUnmodifiedSince;
+///
+/// let if_unmodified_since = warp::precondition::if_unmodified_since();
+/// ```
+pub fn if_unmodified_since(
+) -> impl Filter<Extract = One<Option<IfUnmodifiedSince>>, Error = Rejection> + Copy {
+    header::typed_optional()
+}
+
+/// Create a `Filter` that extracts the [`Precondition`] of the request,
+/// to check against the current version of the resource being updated.
+///
+/// Rejects if either header can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use std::time::SystemTime;
+/// use warp::Filter;
+/// use warp::headers::ETag;
+/// use warp::precondition::Precondition;
+///
+/// let update = warp::put()
+///     .and(warp::path!("docs" / u32))
+///     .and(warp::precondition())
+///     .and_then(|id: u32, precondition: Precondition| async move {
+///         // Load the current version of the document...
+///         let etag: ETag = "\"v2\"".parse().unwrap();
+///         let modified = SystemTime::UNIX_EPOCH;
+///
+///         precondition.check(Some(&etag), Some(modified))?;
+///         // ...and only now write the new one.
+///         Ok::<_, warp::Rejection>(format!("updated {}", id))
+///     });
+/// ```
+pub fn precondition() -> impl Filter<Extract = One<Precondition>, Error = Rejection> + Copy {
+    if_match()
+        .and(if_unmodified_since())
+        .map(|if_match, if_unmodified_since| Precondition {
+            if_match,
+            if_unmodified_since,
+        })
+}
+
+/// The `If-Match` and `If-Unmodified-Since` validators of a request,
+/// extracted with [`precondition()`].
+#[derive(Clone, Debug)]
+pub struct Precondition {
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<IfUnmodifiedSince>,
+}
+
+impl Precondition {
+    /// The `If-Match` header of the request, if any.
+    pub fn if_match(&self) -> Option<&IfMatch> {
+        self.if_match.as_ref()
+    }
+
+    /// The `If-Unmodified-Since` header of the request, if any.
+    pub fn if_unmodified_since(&self) -> Option<&IfUnmodifiedSince> {
+        self.if_unmodified_since.as_ref()
+    }
+
+    /// Whether the request may go on, given the current `ETag` and
+    /// modification time of the resource, or `None` if it doesn't exist or
+    /// has none.
+    ///
+    /// As in [RFC 7232](https://tools.ietf.org/html/rfc7232#section-6),
+    /// `If-Match` is checked with the strong comparison, and fails if the
+    /// resource doesn't exist. `If-Unmodified-Since` is only checked when
+    /// there's no `If-Match`, and passes if the time is unknown.
+    pub fn passes(&self, etag: Option<&ETag>, last_modified: Option<SystemTime>) -> bool {
+        if let Some(ref if_match) = self.if_match {
+            return etag.is_some_and(|etag| if_match.precondition_passes(etag));
+        }
+        match (&self.if_unmodified_since, last_modified) {
+            (Some(since), Some(modified)) => since.precondition_passes(modified),
+            _ => true,
+        }
+    }
+
+    /// Like [`passes`](Precondition::passes), but rejects with a
+    /// [`PreconditionFailed`] if the request may not go on, so handlers can
+    /// return early with `?`.
+    pub fn check(
+        &self,
+        etag: Option<&ETag>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), Rejection> {
+        if self.passes(etag, last_modified) {
+            Ok(())
+        } else {
+            tracing::debug!("precondition failed");
+            Err(reject::known(PreconditionFailed { _p: () }))
+        }
+    }
+}
+
+/// An error used to reject requests whose [`Precondition`] failed, which
+/// replies with a `412 Precondition Failed`.
+#[derive(Debug)]
+pub struct PreconditionFailed {
+    _p: (),
+}
+
+impl fmt::Display for PreconditionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Precondition failed")
+    }
+}
+
+impl StdError for PreconditionFailed {}
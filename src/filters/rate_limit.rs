@@ -0,0 +1,561 @@
+//! Rate limiting Filters
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use tokio::time::Instant;
+
+use crate::clock;
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::Reply;
+use crate::route::Route;
+
+use self::internal::WithRateLimit;
+
+/// Create a wrapping filter that limits requests with a token bucket.
+///
+/// Each client may make up to `rate` requests `per` the given period, with
+/// bursts of up to `rate` requests, unless changed with
+/// [`RateLimit::burst`]. By default, clients are told apart by their remote
+/// IP address.
+///
+/// Requests over the limit are rejected with a [`RateLimited`] rejection,
+/// which replies with a `429 Too Many Requests` and a `Retry-After` header.
+/// Replies to allowed requests get `X-RateLimit-Limit`,
+/// `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let limit = warp::rate_limit(10, Duration::from_secs(1))
+///     .burst(20)
+///     .key_by_header("x-api-key");
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(limit);
+/// ```
+pub fn rate_limit(rate: u32, per: Duration) -> RateLimit<MemoryStore> {
+    assert!(rate > 0, "rate_limit rate must be greater than 0");
+    RateLimit {
+        quota: Quota {
+            rate,
+            per,
+            burst: rate,
+        },
+        key: Key::Ip,
+        store: Arc::new(MemoryStore::default()),
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to limit the rate of requests.
+pub struct RateLimit<S> {
+    quota: Quota,
+    key: Key,
+    store: Arc<S>,
+}
+
+#[derive(Clone)]
+enum Key {
+    Ip,
+    Header(HeaderName),
+    Custom(Arc<dyn Fn(Info<'_>) -> Option<String> + Send + Sync>),
+}
+
+/// The allowance of a [`rate_limit`] filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quota {
+    rate: u32,
+    per: Duration,
+    burst: u32,
+}
+
+/// The outcome of taking a token from a [`RateLimitStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decision {
+    allowed: bool,
+    limit: u32,
+    remaining: u32,
+    reset: Duration,
+    retry_after: Duration,
+}
+
+/// Storage for rate limiting state.
+///
+/// The default [`MemoryStore`] keeps buckets in the memory of this process.
+/// Implement this to share the state between instances, such as in Redis.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Try to take a token from the bucket for `key`.
+    fn acquire(
+        &self,
+        key: String,
+        quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Decision> + Send + 'static>>;
+}
+
+/// A [`RateLimitStore`] keeping buckets in memory.
+///
+/// Buckets that have refilled are forgotten, since they're the same as new
+/// ones, so memory is bounded by the clients seen within a refill period.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    // How many buckets there may be before the next sweep, which grows with
+    // the buckets left after one, so sweeps take amortized constant time.
+    sweep_at: usize,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    // When the bucket will have refilled, and can be forgotten.
+    full: Instant,
+}
+
+/// Information about the request, used to pick the key of a rate limit.
+#[allow(missing_debug_implementations)]
+pub struct Info<'a> {
+    route: &'a Route,
+}
+
+impl<S> RateLimit<S> {
+    /// Sets how many requests may be made at once, before being limited to
+    /// the steady rate.
+    pub fn burst(mut self, burst: u32) -> Self {
+        assert!(burst > 0, "rate_limit burst must be greater than 0");
+        self.quota.burst = burst;
+        self
+    }
+
+    /// Limit clients by their remote IP address.
+    ///
+    /// This is the default. Requests without a remote address aren't limited.
+    pub fn key_by_ip(mut self) -> Self {
+        self.key = Key::Ip;
+        self
+    }
+
+    /// Limit clients by the value of a request header, such as an API key.
+    ///
+    /// Requests without the header aren't limited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn key_by_header(mut self, name: &'static str) -> Self {
+        self.key = Key::Header(HeaderName::from_static(name));
+        self
+    }
+
+    /// Limit clients by a custom key, such as an authenticated principal.
+    ///
+    /// Requests for which the function returns `None` aren't limited.
+    pub fn key_by<F>(mut self, func: F) -> Self
+    where
+        F: Fn(Info<'_>) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = Key::Custom(Arc::new(func));
+        self
+    }
+
+    /// Use a different store for the buckets.
+    pub fn store<S2: RateLimitStore>(self, store: S2) -> RateLimit<S2> {
+        RateLimit {
+            quota: self.quota,
+            key: self.key,
+            store: Arc::new(store),
+        }
+    }
+
+    fn key(&self, route: &Route) -> Option<String> {
+        match self.key {
+            Key::Ip => route.remote_addr().map(|addr| addr.ip().to_string()),
+            Key::Header(ref name) => route
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned),
+            Key::Custom(ref func) => func(Info { route }),
+        }
+    }
+}
+
+impl<S> Clone for RateLimit<S> {
+    fn clone(&self) -> Self {
+        RateLimit {
+            quota: self.quota,
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for RateLimit<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RateLimit")
+            .field("quota", &self.quota)
+            .finish()
+    }
+}
+
+impl<F, S> Wrap<F> for RateLimit<S>
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+    S: RateLimitStore,
+{
+    type Wrapped = WithRateLimit<F, S>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithRateLimit {
+            filter,
+            limit: self.clone(),
+        }
+    }
+}
+
+impl Quota {
+    /// How many requests are allowed `per` period.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// The period over which `rate` requests are allowed.
+    pub fn per(&self) -> Duration {
+        self.per
+    }
+
+    /// The size of the bucket.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    // The time it takes for one token to be added back.
+    fn interval(&self) -> Duration {
+        self.per / self.rate
+    }
+}
+
+impl Decision {
+    /// A request that may go ahead, for use by a [`RateLimitStore`].
+    ///
+    /// `reset` is how long until the bucket is full again.
+    pub fn allowed(limit: u32, remaining: u32, reset: Duration) -> Decision {
+        Decision {
+            allowed: true,
+            limit,
+            remaining,
+            reset,
+            retry_after: Duration::from_secs(0),
+        }
+    }
+
+    /// A request over the limit, for use by a [`RateLimitStore`].
+    ///
+    /// `reset` is how long until the bucket is full again, and `retry_after`
+    /// how long until the next token is available.
+    pub fn denied(limit: u32, reset: Duration, retry_after: Duration) -> Decision {
+        Decision {
+            allowed: false,
+            limit,
+            remaining: 0,
+            reset,
+            retry_after,
+        }
+    }
+
+    /// Whether the request may go ahead.
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    /// The size of the bucket.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// The tokens left in the bucket.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// How long until the bucket is full again.
+    pub fn reset(&self) -> Duration {
+        self.reset
+    }
+
+    /// How long until another request would be allowed.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+
+    fn headers(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert(
+            "x-ratelimit-reset",
+            HeaderValue::from(ceil_secs(self.reset)),
+        );
+    }
+}
+
+// Sweeping fewer buckets than this isn't worth it.
+const MIN_SWEEP: usize = 1024;
+
+impl MemoryStore {
+    /// The number of buckets currently kept, including refilled ones that
+    /// haven't been swept yet.
+    pub fn len(&self) -> usize {
+        self.buckets.lock().unwrap().map.len()
+    }
+
+    /// Whether no buckets are kept.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn take(&self, key: String, quota: Quota) -> Decision {
+        let now = clock::now();
+        let burst = f64::from(quota.burst);
+        let interval = quota.interval().as_secs_f64();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let buckets = &mut *buckets;
+        if buckets.map.len() >= buckets.sweep_at && !buckets.map.contains_key(&key) {
+            buckets.map.retain(|_, bucket| bucket.full > now);
+            buckets.sweep_at = (buckets.map.len() * 2).max(MIN_SWEEP);
+        }
+        let bucket = buckets.map.entry(key).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+            full: now,
+        });
+        let refilled = (now - bucket.updated).as_secs_f64() / interval;
+        bucket.tokens = (bucket.tokens + refilled).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            let reset = Duration::from_secs_f64((burst - bucket.tokens) * interval);
+            bucket.full = now + reset;
+            Decision::allowed(quota.burst, bucket.tokens as u32, reset)
+        } else {
+            let reset = Duration::from_secs_f64((burst - bucket.tokens) * interval);
+            let retry_after = Duration::from_secs_f64((1.0 - bucket.tokens) * interval);
+            Decision::denied(quota.burst, reset, retry_after)
+        }
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn acquire(
+        &self,
+        key: String,
+        quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Decision> + Send + 'static>> {
+        Box::pin(futures::future::ready(self.take(key, quota)))
+    }
+}
+
+impl<S: RateLimitStore> RateLimitStore for Arc<S> {
+    fn acquire(
+        &self,
+        key: String,
+        quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Decision> + Send + 'static>> {
+        (**self).acquire(key, quota)
+    }
+}
+
+impl<'a> Info<'a> {
+    /// View the remote `SocketAddr` of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.route.remote_addr()
+    }
+
+    /// View the `http::Method` of the request.
+    pub fn method(&self) -> &http::Method {
+        self.route.method()
+    }
+
+    /// View the URI path of the request.
+    pub fn path(&self) -> &str {
+        self.route.full_path()
+    }
+
+    /// Access the full headers of the request.
+    pub fn request_headers(&self) -> &http::HeaderMap {
+        self.route.headers()
+    }
+
+    /// Access the extensions of the request.
+    pub fn extensions(&self) -> &http::Extensions {
+        self.route.extensions()
+    }
+}
+
+/// An error used to reject requests over a [`rate_limit`].
+#[derive(Debug)]
+pub struct RateLimited {
+    decision: Decision,
+}
+
+impl RateLimited {
+    /// How long until another request would be allowed.
+    pub fn retry_after(&self) -> Duration {
+        self.decision.retry_after
+    }
+
+    pub(crate) fn headers(&self, headers: &mut HeaderMap) {
+        self.decision.headers(headers);
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from(ceil_secs(self.retry_after())),
+        );
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Too many requests")
+    }
+}
+
+impl StdError for RateLimited {}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    let secs = duration.as_secs();
+    if duration.subsec_nanos() > 0 {
+        secs + 1
+    } else {
+        secs
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{Decision, RateLimit, RateLimitStore, RateLimited};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Limited(Response);
+
+    impl Reply for Limited {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithRateLimit<F, S> {
+        pub(super) filter: F,
+        pub(super) limit: RateLimit<S>,
+    }
+
+    impl<F, S> FilterBase for WithRateLimit<F, S>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+        S: RateLimitStore,
+    {
+        type Extract = (Limited,);
+        type Error = Rejection;
+        type Future = WithRateLimitFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let state = match route::with(|route| self.limit.key(route)) {
+                Some(key) => State::Acquire(
+                    self.limit.store.acquire(key, self.limit.quota),
+                    self.filter.clone(),
+                ),
+                None => State::Filter(self.filter.filter(Internal), None),
+            };
+            WithRateLimitFuture { state }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithRateLimitFuture<F: Filter> {
+        #[pin]
+        state: State<F>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Acquire(Pin<Box<dyn Future<Output = Decision> + Send>>, F),
+        Filter(#[pin] F::Future, Option<Decision>),
+        Done,
+    }
+
+    impl<F> Future for WithRateLimitFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Limited,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut state = self.project().state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Acquire(acquire, filter) => {
+                        let decision = ready!(acquire.as_mut().poll(cx));
+                        if !decision.is_allowed() {
+                            state.set(State::Done);
+                            return Poll::Ready(Err(reject::known(RateLimited { decision })));
+                        }
+                        let fut = filter.filter(Internal);
+                        state.set(State::Filter(fut, Some(decision)));
+                    }
+                    StateProj::Filter(fut, decision) => {
+                        let result = match ready!(fut.try_poll(cx)) {
+                            Ok(reply) => {
+                                let mut res = reply.into_response();
+                                if let Some(decision) = decision {
+                                    decision.headers(res.headers_mut());
+                                }
+                                Ok((Limited(res),))
+                            }
+                            Err(err) => Err(err.into()),
+                        };
+                        state.set(State::Done);
+                        return Poll::Ready(result);
+                    }
+                    StateProj::Done => panic!("polled after complete"),
+                }
+            }
+        }
+    }
+}
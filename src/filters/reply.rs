@@ -25,8 +25,8 @@ use std::sync::Arc;
 use http::header::{HeaderMap, HeaderName, HeaderValue};
 
 use self::sealed::{WithDefaultHeader_, WithHeader_, WithHeaders_};
-use crate::filter::{Filter, Map, WrapSealed};
-use crate::reply::Reply;
+use crate::filter::{Filter, Map, MapResponse, Wrap};
+use crate::reply::{Reply, Response};
 
 /// Wrap a [`Filter`](crate::Filter) that adds a header to the reply.
 ///
@@ -118,6 +118,37 @@ where
     WithDefaultHeader { name, value }
 }
 
+/// Wrap a [`Filter`](crate::Filter) that passes the reply, converted into a
+/// `Response`, through a function.
+///
+/// This is [`Filter::map_response`](crate::Filter::map_response), as a
+/// wrapper. Like the other wrappers here, it's skipped if the underlying
+/// filter was rejected.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::header::{HeaderValue, CACHE_CONTROL};
+/// use warp::Filter;
+///
+/// // Don't cache any replies of the API.
+/// let no_store = warp::reply::with::map_response(|mut res: warp::reply::Response| {
+///     res.headers_mut()
+///         .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+///     res
+/// });
+///
+/// let route = warp::path("api")
+///     .map(warp::reply)
+///     .with(no_store);
+/// ```
+pub fn map_response<F>(fun: F) -> WithMapResponse<F>
+where
+    F: Fn(Response) -> Response + Clone + Send,
+{
+    WithMapResponse { fun }
+}
+
 /// Wrap a `Filter` to always set a header.
 #[derive(Clone, Debug)]
 pub struct WithHeader {
@@ -125,7 +156,7 @@ pub struct WithHeader {
     value: HeaderValue,
 }
 
-impl<F, R> WrapSealed<F> for WithHeader
+impl<F, R> Wrap<F> for WithHeader
 where
     F: Filter<Extract = (R,)>,
     R: Reply,
@@ -144,7 +175,7 @@ pub struct WithHeaders {
     headers: Arc<HeaderMap>,
 }
 
-impl<F, R> WrapSealed<F> for WithHeaders
+impl<F, R> Wrap<F> for WithHeaders
 where
     F: Filter<Extract = (R,)>,
     R: Reply,
@@ -164,7 +195,7 @@ pub struct WithDefaultHeader {
     value: HeaderValue,
 }
 
-impl<F, R> WrapSealed<F> for WithDefaultHeader
+impl<F, R> Wrap<F> for WithDefaultHeader
 where
     F: Filter<Extract = (R,)>,
     R: Reply,
@@ -177,6 +208,25 @@ where
     }
 }
 
+/// Wrap a `Filter` to pass its reply through a function.
+#[derive(Clone, Copy, Debug)]
+pub struct WithMapResponse<F> {
+    fun: F,
+}
+
+impl<T, F> Wrap<T> for WithMapResponse<F>
+where
+    T: Filter,
+    T::Extract: Reply,
+    F: Fn(Response) -> Response + Clone + Send,
+{
+    type Wrapped = MapResponse<T, F>;
+
+    fn wrap(&self, filter: T) -> Self::Wrapped {
+        filter.map_response(self.fun.clone())
+    }
+}
+
 fn assert_name_and_value<K, V>(name: K, value: V) -> (HeaderName, HeaderValue)
 where
     HeaderName: TryFrom<K>,
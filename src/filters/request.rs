@@ -0,0 +1,42 @@
+//! Whole request Filters
+//!
+//! Most handlers only need parts of a request, extracted by the other
+//! filters. [`full`] is an escape hatch for the ones that delegate to
+//! lower-level libraries needing the whole `http::Request`.
+
+use std::convert::Infallible;
+
+use futures::future;
+use hyper::Body;
+
+use crate::filter::{filter_fn_one, Filter};
+use crate::reject::Rejection;
+
+/// Create a `Filter` that extracts the whole request, with its method, URI,
+/// headers, extensions, and body.
+///
+/// The extensions and body are moved out of the request, so any filters
+/// after this one can't get the extensions, nor read the body. Like other
+/// filters using the body, it rejects if the body was already taken by a
+/// previous filter.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::Request;
+/// use warp::hyper::Body;
+/// use warp::Filter;
+///
+/// let route = warp::path("raw")
+///     .and(warp::request::full())
+///     .map(|req: Request<Body>| {
+///         format!("{} {} {:?}", req.method(), req.uri(), req.version())
+///     });
+/// ```
+pub fn full() -> impl Filter<Extract = (http::Request<Body>,), Error = Rejection> + Copy {
+    crate::body::body()
+        .and(filter_fn_one(|route| {
+            future::ok::<_, Infallible>(route.take_request())
+        }))
+        .map(|body, req: http::Request<Body>| req.map(|_| body))
+}
@@ -0,0 +1,250 @@
+//! Request ID Filters
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Arc;
+
+use http::header::{HeaderName, HeaderValue};
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::Reply;
+
+use self::internal::WithRequestId;
+
+/// Create a wrapping filter that gives every request an ID.
+///
+/// The ID is taken from the `X-Request-Id` header of the request if it has
+/// one, or else a random UUID is generated. It is stored in the request
+/// extensions, where [`id()`] extracts it, and is sent back in the same
+/// header of the reply.
+///
+/// `warp::log` and `warp::trace` include the ID in their output. For
+/// `warp::trace`, the `request_id` wrapper needs to be on the outside, so
+/// the ID is known when the span is created.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .and(warp::request_id::id())
+///     .map(|id: warp::request_id::RequestId| format!("handling {}", id))
+///     .with(warp::log("example::api"))
+///     .with(warp::request_id());
+/// ```
+pub fn request_id() -> SetRequestId {
+    SetRequestId {
+        header: HeaderName::from_static("x-request-id"),
+        generate: Arc::new(uuid_v4),
+    }
+}
+
+/// Creates a `Filter` that extracts the ID given to the request by an
+/// enclosing [`request_id()`] wrapper.
+///
+/// If there is none, this rejects with a `MissingExtension`.
+pub fn id() -> impl Filter<Extract = (RequestId,), Error = Rejection> + Copy {
+    crate::ext::get::<RequestId>()
+}
+
+/// The ID of a request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    /// View the ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to give requests an ID.
+#[derive(Clone)]
+pub struct SetRequestId {
+    header: HeaderName,
+    generate: Arc<dyn Fn() -> String + Send + Sync>,
+}
+
+impl SetRequestId {
+    /// Sets the header the ID is read from and written to.
+    ///
+    /// Defaults to `X-Request-Id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    pub fn header<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        self.header = HeaderName::try_from(name)
+            .unwrap_or_else(|_| panic!("request_id: invalid header name"));
+        self
+    }
+
+    /// Sets the function generating IDs for requests that don't have one.
+    ///
+    /// Defaults to random (version 4) UUIDs.
+    pub fn generate<F>(mut self, func: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.generate = Arc::new(func);
+        self
+    }
+
+    fn id_for(&self, headers: &http::HeaderMap) -> RequestId {
+        let incoming = headers
+            .get(&self.header)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| is_valid(id));
+        match incoming {
+            Some(id) => RequestId(id.into()),
+            None => RequestId((self.generate)().into()),
+        }
+    }
+}
+
+impl fmt::Debug for SetRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SetRequestId")
+            .field("header", &self.header)
+            .finish()
+    }
+}
+
+impl<F> Wrap<F> for SetRequestId
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithRequestId<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithRequestId {
+            filter,
+            set: self.clone(),
+        }
+    }
+}
+
+// IDs from clients end up in logs, so only accept reasonable ones.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("request_id needs a random number source");
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let mut id = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if let 4 | 6 | 8 | 10 = i {
+            id.push('-');
+        }
+        id.push_str(&format!("{:02x}", byte));
+    }
+    id
+}
+
+fn header_value(id: &RequestId) -> Option<HeaderValue> {
+    HeaderValue::from_str(id.as_str()).ok()
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{header_value, RequestId, SetRequestId};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct WithId(Response);
+
+    impl Reply for WithId {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithRequestId<F> {
+        pub(super) filter: F,
+        pub(super) set: SetRequestId,
+    }
+
+    impl<F> FilterBase for WithRequestId<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (WithId,);
+        type Error = Rejection;
+        type Future = WithRequestIdFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let id = route::with(|route| {
+                let id = match route.extensions().get::<RequestId>() {
+                    Some(id) => id.clone(),
+                    None => self.set.id_for(route.headers()),
+                };
+                route.extensions_mut().insert(id.clone());
+                id
+            });
+            WithRequestIdFuture {
+                future: self.filter.filter(Internal),
+                set: self.set.clone(),
+                id,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithRequestIdFuture<F> {
+        #[pin]
+        future: F,
+        set: SetRequestId,
+        id: RequestId,
+    }
+
+    impl<F> Future for WithRequestIdFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(WithId,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let reply = ready!(pin.future.try_poll(cx)).map_err(Into::into)?;
+            let mut res = reply.into_response();
+            if let Some(value) = header_value(pin.id) {
+                res.headers_mut().insert(pin.set.header.clone(), value);
+            }
+            Poll::Ready(Ok((WithId(res),)))
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Request sampling for [`log`](crate::log()) and [`trace`](crate::trace()).
+//!
+//! A [`Sample`] picks which requests are logged or traced, so that busy
+//! services don't pay for observability on every request.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::StatusCode;
+
+/// Which requests to log or trace.
+///
+/// A request is sampled if it's picked by the rate of the sample, or if it
+/// matches one of the conditions added with [`Sample::or_errors`] and
+/// [`Sample::or_slower_than`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::sample::Sample;
+/// use warp::Filter;
+///
+/// // Log 1% of requests, plus every failing or slow one.
+/// let log = warp::log("example::api").sample(
+///     Sample::ratio(0.01)
+///         .or_errors()
+///         .or_slower_than(Duration::from_secs(1)),
+/// );
+///
+/// let route = warp::any().map(warp::reply).with(log);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sample {
+    rate: Rate,
+    errors: bool,
+    slower_than: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+enum Rate {
+    All,
+    None,
+    Every(u64, Arc<AtomicU64>),
+    Ratio(f64),
+}
+
+impl Sample {
+    /// Samples every request.
+    ///
+    /// This is the default.
+    pub fn all() -> Sample {
+        Sample::with_rate(Rate::All)
+    }
+
+    /// Samples no requests, except for those matching the conditions added
+    /// to it.
+    pub fn none() -> Sample {
+        Sample::with_rate(Rate::None)
+    }
+
+    /// Samples every `n`th request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is `0`.
+    pub fn every(n: u64) -> Sample {
+        assert!(n > 0, "sample every must be at least 1");
+        Sample::with_rate(Rate::Every(n, Arc::default()))
+    }
+
+    /// Samples each request with a probability of `ratio`, such as `0.01`
+    /// for about 1 in 100.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` isn't between `0.0` and `1.0`.
+    pub fn ratio(ratio: f64) -> Sample {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "sample ratio must be between 0 and 1"
+        );
+        Sample::with_rate(Rate::Ratio(ratio))
+    }
+
+    /// Samples no requests, except those replied to with a `5xx` status.
+    pub fn errors() -> Sample {
+        Sample::none().or_errors()
+    }
+
+    /// Samples no requests, except those taking longer than `threshold`.
+    pub fn slower_than(threshold: Duration) -> Sample {
+        Sample::none().or_slower_than(threshold)
+    }
+
+    /// Also samples requests replied to with a `5xx` status.
+    pub fn or_errors(mut self) -> Sample {
+        self.errors = true;
+        self
+    }
+
+    /// Also samples requests taking longer than `threshold`.
+    pub fn or_slower_than(mut self, threshold: Duration) -> Sample {
+        self.slower_than = Some(threshold);
+        self
+    }
+
+    fn with_rate(rate: Rate) -> Sample {
+        Sample {
+            rate,
+            errors: false,
+            slower_than: None,
+        }
+    }
+
+    /// Whether a request is picked by the rate, before it's handled.
+    pub(crate) fn head(&self) -> bool {
+        match self.rate {
+            Rate::All => true,
+            Rate::None => false,
+            Rate::Every(n, ref seen) => seen.fetch_add(1, Ordering::Relaxed) % n == 0,
+            Rate::Ratio(ratio) => random() < ratio,
+        }
+    }
+
+    /// Whether any request could be sampled once it has been handled.
+    pub(crate) fn has_tail(&self) -> bool {
+        self.errors || self.slower_than.is_some()
+    }
+
+    /// Whether a request matches the conditions, once it has been handled.
+    pub(crate) fn tail(&self, status: StatusCode, elapsed: Duration) -> bool {
+        (self.errors && status.is_server_error())
+            || self
+                .slower_than
+                .is_some_and(|threshold| elapsed > threshold)
+    }
+}
+
+impl Default for Sample {
+    fn default() -> Sample {
+        Sample::all()
+    }
+}
+
+// A random number in `[0, 1)`, from a per-thread xorshift generator, since
+// reading the OS's random source for every request would cost more than
+// the sampling saves.
+pub(crate) fn random() -> f64 {
+    thread_local!(static STATE: Cell<u64> = Cell::new(seed()));
+
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+fn seed() -> u64 {
+    let mut bytes = [0; 8];
+    getrandom::getrandom(&mut bytes).expect("sampling needs a random number source");
+    // xorshift gets stuck at zero.
+    u64::from_ne_bytes(bytes) | 1
+}
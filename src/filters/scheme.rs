@@ -0,0 +1,125 @@
+//! Scheme filters.
+
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use headers::HeaderMapExt;
+use http::header::HeaderMap;
+pub use http::uri::Scheme;
+
+use crate::addr::{ForwardedHeader, IpNet};
+use crate::conn::Info;
+use crate::filter::{filter_fn, Filter, One};
+use crate::header::Forwarded;
+use crate::route::Route;
+
+/// Creates a `Filter` to get the scheme the client used, `http` or `https`.
+///
+/// The scheme is the one of the request target when it's absolute, and
+/// otherwise `https` if the connection uses TLS. When the connection comes
+/// from a trusted proxy, such as a load balancer terminating TLS, it's
+/// taken from the header the proxies set instead: the `proto` parameters of
+/// `Forwarded`, or `X-Forwarded-Proto` for the other kinds of
+/// [`ForwardedHeader`]. Other headers are ignored, since proxies pass on the
+/// ones they don't set as the client sent them.
+///
+/// When the peer isn't trusted, the headers are ignored, since an untrusted
+/// client could claim to use any scheme.
+///
+/// # Example
+///
+/// ```
+/// use warp::addr::ForwardedHeader;
+/// use warp::http::uri::Scheme;
+/// use warp::Filter;
+///
+/// let trusted = vec!["10.0.0.0/8".parse().unwrap()];
+///
+/// let route = warp::scheme(trusted, ForwardedHeader::XForwardedFor)
+///     .map(|scheme: Scheme| {
+///         if scheme == Scheme::HTTPS {
+///             "secure"
+///         } else {
+///             "plain"
+///         }
+///     });
+/// ```
+pub fn scheme<I>(
+    trusted: I,
+    header: ForwardedHeader,
+) -> impl Filter<Extract = One<Scheme>, Error = Infallible> + Clone
+where
+    I: IntoIterator<Item = IpNet>,
+{
+    let trusted: Arc<[IpNet]> = trusted.into_iter().collect();
+    filter_fn(move |route| futures::future::ok((resolve(&trusted, header, route),)))
+}
+
+fn resolve(trusted: &[IpNet], header: ForwardedHeader, route: &Route) -> Scheme {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+
+    let peer_trusted = route
+        .remote_addr()
+        .is_some_and(|addr| is_trusted(&addr.ip()));
+    if peer_trusted {
+        let forwarded = match header {
+            ForwardedHeader::Forwarded => match route.headers().typed_try_get::<Forwarded>() {
+                Ok(Some(forwarded)) => forwarded_proto(&forwarded, &is_trusted).map(str::parse),
+                Ok(None) => None,
+                // Nothing in a header the nearest proxy couldn't parse is
+                // trusted.
+                Err(_) => None,
+            },
+            ForwardedHeader::XForwardedFor | ForwardedHeader::XRealIp => {
+                x_forwarded_proto(route.headers()).map(str::parse)
+            }
+        };
+        if let Some(Ok(scheme)) = forwarded {
+            return scheme;
+        }
+    }
+
+    if let Some(scheme) = route.uri().scheme() {
+        return scheme.clone();
+    }
+    let tls = route.extensions().get::<Info>().is_some_and(Info::is_tls);
+    if tls {
+        Scheme::HTTPS
+    } else {
+        Scheme::HTTP
+    }
+}
+
+// The `proto` of the outermost `Forwarded` element vouched for by trusted
+// proxies: each element was appended by the proxy after it, so the elements
+// are walked from the nearest one outwards, while they come from trusted
+// addresses.
+fn forwarded_proto<'a>(
+    forwarded: &'a Forwarded,
+    is_trusted: &dyn Fn(&IpAddr) -> bool,
+) -> Option<&'a str> {
+    let mut proto = None;
+    for element in forwarded.elements().iter().rev() {
+        proto = element.proto().or(proto);
+        let from = element.for_node().and_then(|node| node.ip());
+        if !from.is_some_and(|ip| is_trusted(&ip)) {
+            break;
+        }
+    }
+    proto
+}
+
+// The last `X-Forwarded-Proto`, set by the nearest proxy: the ones before
+// it may have come from the client.
+fn x_forwarded_proto(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all("x-forwarded-proto")
+        .iter()
+        .next_back()?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()
+        .map(str::trim)
+}
@@ -0,0 +1,303 @@
+//! Security header Filters
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::{
+    HeaderMap, HeaderName, HeaderValue, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+    X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+
+use self::sealed::WithSecurityHeaders_;
+use crate::filter::{Filter, Map, Wrap};
+use crate::reply::Reply;
+
+const PERMISSIONS_POLICY: &str = "permissions-policy";
+const CROSS_ORIGIN_OPENER_POLICY: &str = "cross-origin-opener-policy";
+const CROSS_ORIGIN_EMBEDDER_POLICY: &str = "cross-origin-embedder-policy";
+
+/// Create a wrapping filter that adds common security headers to replies.
+///
+/// By default, these headers are set:
+///
+/// - `Strict-Transport-Security: max-age=31536000; includeSubDomains`
+/// - `X-Content-Type-Options: nosniff`
+/// - `X-Frame-Options: DENY`
+/// - `Referrer-Policy: no-referrer`
+/// - `Cross-Origin-Opener-Policy: same-origin`
+///
+/// Each can be changed with the builder methods, or left out with
+/// [`SecurityHeaders::without`]. Headers already set by the wrapped filter
+/// are kept as they are.
+///
+/// # Note
+///
+/// This **only** adds headers if the underlying filter is successful, and
+/// returns a [`Reply`](Reply). If the underlying filter was rejected, the
+/// headers are not added.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let headers = warp::security_headers()
+///     .hsts(Duration::from_secs(63_072_000))
+///     .hsts_preload(true)
+///     .frame_options(warp::security_headers::FrameOptions::SameOrigin)
+///     .permissions_policy("geolocation=(), camera=()");
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(headers);
+/// ```
+pub fn security_headers() -> SecurityHeaders {
+    let mut headers = HeaderMap::new();
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(X_FRAME_OPTIONS, FrameOptions::Deny.value());
+    headers.insert(REFERRER_POLICY, HeaderValue::from_static("no-referrer"));
+    headers.insert(
+        CROSS_ORIGIN_OPENER_POLICY,
+        HeaderValue::from_static("same-origin"),
+    );
+    SecurityHeaders {
+        headers,
+        hsts: Some(DEFAULT_HSTS),
+    }
+}
+
+/// Wrap a `Filter` to add security headers to the reply.
+#[derive(Clone, Debug)]
+pub struct SecurityHeaders {
+    headers: HeaderMap,
+    hsts: Option<Hsts>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+const DEFAULT_HSTS: Hsts = Hsts {
+    max_age: Duration::from_secs(31_536_000),
+    include_subdomains: true,
+    preload: false,
+};
+
+/// The values of the `X-Frame-Options` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameOptions {
+    /// The page may not be shown in a frame at all.
+    Deny,
+    /// The page may only be shown in a frame on the same origin.
+    SameOrigin,
+}
+
+impl FrameOptions {
+    fn value(self) -> HeaderValue {
+        match self {
+            FrameOptions::Deny => HeaderValue::from_static("DENY"),
+            FrameOptions::SameOrigin => HeaderValue::from_static("SAMEORIGIN"),
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// Sets the `max-age` of `Strict-Transport-Security`.
+    pub fn hsts(mut self, max_age: Duration) -> Self {
+        self.hsts_mut().max_age = max_age;
+        self
+    }
+
+    /// Sets whether `Strict-Transport-Security` applies to subdomains.
+    ///
+    /// Defaults to `true`.
+    pub fn hsts_include_subdomains(mut self, include: bool) -> Self {
+        self.hsts_mut().include_subdomains = include;
+        self
+    }
+
+    /// Sets whether `Strict-Transport-Security` asks to be preloaded.
+    ///
+    /// Defaults to `false`.
+    pub fn hsts_preload(mut self, preload: bool) -> Self {
+        self.hsts_mut().preload = preload;
+        self
+    }
+
+    /// Sets `X-Content-Type-Options: nosniff`, or leaves it out.
+    ///
+    /// Defaults to `true`.
+    pub fn content_type_options(mut self, nosniff: bool) -> Self {
+        if nosniff {
+            self.headers
+                .insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+        } else {
+            self.headers.remove(X_CONTENT_TYPE_OPTIONS);
+        }
+        self
+    }
+
+    /// Sets the `X-Frame-Options` header.
+    ///
+    /// Defaults to [`FrameOptions::Deny`].
+    pub fn frame_options(mut self, options: FrameOptions) -> Self {
+        self.headers.insert(X_FRAME_OPTIONS, options.value());
+        self
+    }
+
+    /// Sets the `Referrer-Policy` header.
+    ///
+    /// Defaults to `no-referrer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn referrer_policy<V>(self, policy: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        self.set(REFERRER_POLICY, policy)
+    }
+
+    /// Sets the `Permissions-Policy` header.
+    ///
+    /// Not set by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn permissions_policy<V>(self, policy: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        self.set(HeaderName::from_static(PERMISSIONS_POLICY), policy)
+    }
+
+    /// Sets the `Cross-Origin-Opener-Policy` header.
+    ///
+    /// Defaults to `same-origin`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn cross_origin_opener_policy<V>(self, policy: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        self.set(HeaderName::from_static(CROSS_ORIGIN_OPENER_POLICY), policy)
+    }
+
+    /// Sets the `Cross-Origin-Embedder-Policy` header.
+    ///
+    /// Not set by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn cross_origin_embedder_policy<V>(self, policy: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        self.set(
+            HeaderName::from_static(CROSS_ORIGIN_EMBEDDER_POLICY),
+            policy,
+        )
+    }
+
+    /// Leaves out a header that would otherwise be set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    pub fn without<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        let name = HeaderName::try_from(name)
+            .unwrap_or_else(|_| panic!("security_headers: invalid header name"));
+        if name == STRICT_TRANSPORT_SECURITY {
+            self.hsts = None;
+        }
+        self.headers.remove(name);
+        self
+    }
+
+    fn set<V>(mut self, name: HeaderName, value: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        let value = HeaderValue::try_from(value)
+            .unwrap_or_else(|_| panic!("security_headers: invalid {} value", name));
+        self.headers.insert(name, value);
+        self
+    }
+
+    fn hsts_mut(&mut self) -> &mut Hsts {
+        self.hsts.get_or_insert(DEFAULT_HSTS)
+    }
+
+    fn build(&self) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        if let Some(hsts) = self.hsts {
+            let mut value = format!("max-age={}", hsts.max_age.as_secs());
+            if hsts.include_subdomains {
+                value.push_str("; includeSubDomains");
+            }
+            if hsts.preload {
+                value.push_str("; preload");
+            }
+            let value = HeaderValue::from_str(&value).expect("hsts is a valid header value");
+            headers.insert(STRICT_TRANSPORT_SECURITY, value);
+        }
+        headers
+    }
+}
+
+impl<F, R> Wrap<F> for SecurityHeaders
+where
+    F: Filter<Extract = (R,)>,
+    R: Reply,
+{
+    type Wrapped = Map<F, WithSecurityHeaders_>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        let with = WithSecurityHeaders_ {
+            headers: Arc::new(self.build()),
+        };
+        filter.map(with)
+    }
+}
+
+mod sealed {
+    use std::sync::Arc;
+
+    use http::header::HeaderMap;
+
+    use crate::generic::{Func, One};
+    use crate::reply::{Reply, Reply_};
+
+    #[derive(Clone)]
+    #[allow(missing_debug_implementations)]
+    pub struct WithSecurityHeaders_ {
+        pub(super) headers: Arc<HeaderMap>,
+    }
+
+    impl<R: Reply> Func<One<R>> for WithSecurityHeaders_ {
+        type Output = Reply_;
+
+        fn call(&self, args: One<R>) -> Self::Output {
+            let mut resp = args.0.into_response();
+            for (name, value) in &*self.headers {
+                resp.headers_mut()
+                    .entry(name)
+                    .or_insert_with(|| value.clone());
+            }
+            Reply_(resp)
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Server-Timing Filters
+
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use http::header::{HeaderName, HeaderValue};
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::{Reply, Response};
+
+use self::internal::WithServerTiming;
+
+/// Create a wrapping filter that sends the timings recorded while handling a
+/// request in a `Server-Timing` header, for browser developer tools to show.
+///
+/// Timings are recorded with the [`Timings`] handle extracted by
+/// [`timings()`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+/// use warp::server_timing::Timings;
+///
+/// let route = warp::path("users")
+///     .and(warp::server_timing::timings())
+///     .map(|timings: Timings| {
+///         let db = timings.start("db").describe("Load users");
+///         // query the database...
+///         db.stop();
+///
+///         timings.record("cache", Duration::from_millis(2));
+///         "users"
+///     })
+///     .with(warp::server_timing().total("total"));
+/// ```
+pub fn server_timing() -> ServerTiming {
+    ServerTiming { total: None }
+}
+
+/// Creates a `Filter` that extracts the [`Timings`] of the request, from an
+/// enclosing [`server_timing()`] wrapper.
+///
+/// If there is none, this rejects with a `MissingExtension`.
+pub fn timings() -> impl Filter<Extract = (Timings,), Error = Rejection> + Copy {
+    crate::ext::get::<Timings>()
+}
+
+/// Decorates a [`Filter`](crate::Filter) to send a `Server-Timing` header.
+#[derive(Clone, Debug)]
+pub struct ServerTiming {
+    total: Option<&'static str>,
+}
+
+/// The timings recorded for a request.
+///
+/// Clones record to the same request.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    metrics: Arc<Mutex<Vec<Metric>>>,
+}
+
+/// A running timer, started with [`Timings::start`].
+///
+/// The time is recorded when it is stopped or dropped.
+#[derive(Debug)]
+pub struct Timer {
+    timings: Timings,
+    name: String,
+    description: Option<String>,
+    started: Instant,
+}
+
+#[derive(Clone, Debug)]
+struct Metric {
+    name: String,
+    description: Option<String>,
+    duration: Duration,
+}
+
+impl ServerTiming {
+    /// Also sends the total time taken to reply, as the metric `name`.
+    pub fn total(mut self, name: &'static str) -> Self {
+        self.total = Some(name);
+        self
+    }
+}
+
+impl<F> Wrap<F> for ServerTiming
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithServerTiming<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithServerTiming {
+            filter,
+            timing: self.clone(),
+        }
+    }
+}
+
+impl Timings {
+    /// Records that `name` took `duration`.
+    pub fn record(&self, name: impl Into<String>, duration: Duration) {
+        self.push(Metric {
+            name: name.into(),
+            description: None,
+            duration,
+        });
+    }
+
+    /// Starts timing `name`.
+    pub fn start(&self, name: impl Into<String>) -> Timer {
+        Timer {
+            timings: self.clone(),
+            name: name.into(),
+            description: None,
+            started: Instant::now(),
+        }
+    }
+
+    fn push(&self, metric: Metric) {
+        self.metrics.lock().unwrap().push(metric);
+    }
+
+    fn header_value(&self) -> Option<HeaderValue> {
+        let metrics = self.metrics.lock().unwrap();
+        if metrics.is_empty() {
+            return None;
+        }
+        let mut value = String::new();
+        for metric in metrics.iter() {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            let _ = write!(value, "{}", metric);
+        }
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+impl Timer {
+    /// Sets the description shown with the metric.
+    pub fn describe(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Stops the timer, and records the time elapsed.
+    pub fn stop(self) {
+        // Recorded on drop.
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.timings.push(Metric {
+            name: std::mem::take(&mut self.name),
+            description: self.description.take(),
+            duration: self.started.elapsed(),
+        });
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(ref description) = self.description {
+            f.write_str(";desc=\"")?;
+            for c in description.chars() {
+                if c == '"' || c == '\\' {
+                    f.write_char('\\')?;
+                }
+                f.write_char(c)?;
+            }
+            f.write_char('"')?;
+        }
+        write!(f, ";dur={:.1}", self.duration.as_secs_f64() * 1000.0)
+    }
+}
+
+fn append_header(res: &mut Response, timings: &Timings) {
+    if let Some(value) = timings.header_value() {
+        res.headers_mut()
+            .append(HeaderName::from_static("server-timing"), value);
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{append_header, ServerTiming, Timings};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Timed(Response);
+
+    impl Reply for Timed {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithServerTiming<F> {
+        pub(super) filter: F,
+        pub(super) timing: ServerTiming,
+    }
+
+    impl<F> FilterBase for WithServerTiming<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Timed,);
+        type Error = Rejection;
+        type Future = WithServerTimingFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let timings = Timings::default();
+            route::with(|route| route.extensions_mut().insert(timings.clone()));
+            WithServerTimingFuture {
+                future: self.filter.filter(Internal),
+                timings,
+                total: self.timing.total,
+                started: Instant::now(),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithServerTimingFuture<F> {
+        #[pin]
+        future: F,
+        timings: Timings,
+        total: Option<&'static str>,
+        started: Instant,
+    }
+
+    impl<F> Future for WithServerTimingFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Timed,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let reply = ready!(pin.future.try_poll(cx)).map_err(Into::into)?;
+            let mut res = reply.into_response();
+            if let Some(name) = *pin.total {
+                pin.timings.record(name, pin.started.elapsed());
+            }
+            append_header(&mut res, pin.timings);
+            Poll::Ready(Ok((Timed(res),)))
+        }
+    }
+}
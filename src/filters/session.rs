@@ -0,0 +1,625 @@
+//! Session Filters
+//!
+//! Sessions keep data about a client across requests, such as who is
+//! logged in. The data is kept in a [`SessionStore`], and the client only
+//! gets a random session id, in a cookie.
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use headers::HeaderMapExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::time::Instant;
+
+use crate::clock;
+use crate::filter::{Filter, Wrap};
+use crate::filters::cookie::{Cookie, SameSite};
+use crate::reject::Rejection;
+use crate::reply::Reply;
+use crate::route::Route;
+
+use self::internal::WithSession;
+
+/// Create a wrapping filter that manages sessions, keeping their data in
+/// `store`.
+///
+/// The session of each request is loaded from the store before the wrapped
+/// filter runs, and can be extracted with [`current`]. If the session was
+/// changed, it's saved once the filter replies, and the session cookie is
+/// set. Sessions expire after the [`ttl`](Sessions::ttl) since they were
+/// last saved.
+///
+/// Requests are rejected with a [`SessionError`] if the store fails.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::session::{MemoryStore, Session};
+///
+/// let login = warp::path("login")
+///     .and(warp::session::current())
+///     .map(|session: Session| {
+///         session.renew();
+///         session.insert("user", "sean").unwrap();
+///         "welcome"
+///     });
+///
+/// let whoami = warp::path("whoami")
+///     .and(warp::session::current())
+///     .map(|session: Session| {
+///         let user = session.get::<String>("user");
+///         format!("{}", user.as_deref().unwrap_or("nobody"))
+///     });
+///
+/// let routes = login
+///     .or(whoami)
+///     .with(warp::session(MemoryStore::default()));
+/// ```
+pub fn session<S: SessionStore>(store: S) -> Sessions<S> {
+    Sessions {
+        store: Arc::new(store),
+        config: Arc::new(Config {
+            cookie_name: "session",
+            ttl: Duration::from_secs(24 * 60 * 60),
+            secure: true,
+        }),
+    }
+}
+
+/// Creates a `Filter` that extracts the [`Session`] of the request.
+///
+/// Rejects with a `500 Internal Server Error` if the filter isn't wrapped
+/// with a [`session`] filter.
+pub fn current() -> impl Filter<Extract = (Session,), Error = Rejection> + Copy {
+    crate::ext::get::<Session>()
+}
+
+/// Decorates a [`Filter`](crate::Filter) to manage sessions.
+pub struct Sessions<S> {
+    store: Arc<S>,
+    config: Arc<Config>,
+}
+
+#[derive(Clone, Debug)]
+struct Config {
+    cookie_name: &'static str,
+    ttl: Duration,
+    secure: bool,
+}
+
+/// The data of a session, as saved in a [`SessionStore`].
+pub type SessionData = HashMap<String, Value>;
+
+/// The future returned by the methods of a [`SessionStore`].
+pub type StoreFuture<T> = Pin<Box<dyn Future<Output = Result<T, SessionError>> + Send + 'static>>;
+
+/// Storage for the data of sessions.
+///
+/// [`MemoryStore`] and [`FileStore`] are provided. Implement this to keep
+/// sessions elsewhere, such as in Redis, so they're shared between
+/// instances.
+pub trait SessionStore: Send + Sync + 'static {
+    /// Loads the data of the session `id`, if it exists and hasn't expired.
+    fn load(&self, id: &str) -> StoreFuture<Option<SessionData>>;
+
+    /// Saves the data of the session `id`, which expires after `ttl`.
+    fn save(&self, id: &str, data: SessionData, ttl: Duration) -> StoreFuture<()>;
+
+    /// Deletes the session `id`.
+    fn delete(&self, id: &str) -> StoreFuture<()>;
+}
+
+/// The session of a request, extracted with [`current`].
+///
+/// Its data is a map of keys to values serialized as JSON. Clones share the
+/// same session.
+#[derive(Clone)]
+pub struct Session {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    // The id the session was loaded with, if any.
+    id: Option<String>,
+    data: SessionData,
+    changed: bool,
+    renew: bool,
+    destroy: bool,
+}
+
+/// A [`SessionStore`] keeping sessions in the memory of this process.
+///
+/// Sessions are lost when the process exits, so it's meant for development
+/// and single instances.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    sessions: Mutex<HashMap<String, (SessionData, Instant)>>,
+}
+
+/// A [`SessionStore`] keeping each session in a JSON file of a directory.
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+/// An error from a [`SessionStore`], which rejects the request with a
+/// `500 Internal Server Error`.
+#[derive(Debug)]
+pub struct SessionError {
+    cause: Box<dyn StdError + Send + Sync>,
+}
+
+impl<S> Sessions<S> {
+    /// Sets the name of the session cookie.
+    ///
+    /// The default is `session`.
+    pub fn cookie_name(mut self, name: &'static str) -> Self {
+        Arc::make_mut(&mut self.config).cookie_name = name;
+        self
+    }
+
+    /// Sets how long sessions last after they were last saved.
+    ///
+    /// The default is 24 hours.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.config).ttl = ttl;
+        self
+    }
+
+    /// Sets whether the session cookie is only sent over HTTPS.
+    ///
+    /// The default is `true`, which browsers may ignore for `localhost`
+    /// during development.
+    pub fn secure(mut self, secure: bool) -> Self {
+        Arc::make_mut(&mut self.config).secure = secure;
+        self
+    }
+
+    // Ids are checked before reaching the store, since they come from the
+    // client, and could be paths for a `FileStore`.
+    fn id(&self, route: &Route) -> Option<String> {
+        let cookies = route.headers().typed_get::<headers::Cookie>()?;
+        let id = cookies.get(self.config.cookie_name)?;
+        let valid = id.len() == 43
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if valid {
+            Some(id.to_owned())
+        } else {
+            None
+        }
+    }
+
+    fn cookie(&self, id: String) -> Cookie {
+        Cookie::new(self.config.cookie_name, id)
+            .path("/")
+            .max_age(self.config.ttl)
+            .secure(self.config.secure)
+            .http_only(true)
+            .same_site(SameSite::Lax)
+    }
+}
+
+impl<S> Clone for Sessions<S> {
+    fn clone(&self) -> Self {
+        Sessions {
+            store: self.store.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Sessions<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sessions")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<F, S> Wrap<F> for Sessions<S>
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+    S: SessionStore,
+{
+    type Wrapped = WithSession<F, S>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithSession {
+            filter,
+            sessions: self.clone(),
+        }
+    }
+}
+
+// ===== impl Session =====
+
+impl Session {
+    fn new(id: Option<String>, data: SessionData) -> Session {
+        Session {
+            inner: Arc::new(Mutex::new(Inner {
+                id,
+                data,
+                changed: false,
+                renew: false,
+                destroy: false,
+            })),
+        }
+    }
+
+    /// Gets the value of `key`, if it's set and can be deserialized as `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let inner = self.inner.lock().unwrap();
+        let value = inner.data.get(key)?;
+        T::deserialize(value).ok()
+    }
+
+    /// Sets the value of `key`.
+    ///
+    /// Fails if `value` can't be serialized as JSON.
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.data.insert(key.to_owned(), value);
+        inner.changed = true;
+        Ok(())
+    }
+
+    /// Removes `key`, returning its value, if it was set and can be
+    /// deserialized as `T`.
+    pub fn remove<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap();
+        let value = inner.data.remove(key)?;
+        inner.changed = true;
+        T::deserialize(value).ok()
+    }
+
+    /// Removes all the data of the session.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.changed |= !inner.data.is_empty();
+        inner.data.clear();
+    }
+
+    /// Moves the session to a new id, keeping its data.
+    ///
+    /// Renewing the session when a user logs in prevents session fixation,
+    /// where an attacker makes the user log in with a session id they know.
+    pub fn renew(&self) {
+        self.inner.lock().unwrap().renew = true;
+    }
+
+    /// Deletes the session from the store, and removes the session cookie
+    /// from the client, such as when a user logs out.
+    pub fn destroy(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.data.clear();
+        inner.destroy = true;
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("Session")
+            .field("keys", &inner.data.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+fn generate_id() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("session ids need a random number source");
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+// ===== impl MemoryStore =====
+
+impl SessionStore for MemoryStore {
+    fn load(&self, id: &str) -> StoreFuture<Option<SessionData>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let data = match sessions.get(id) {
+            Some((data, expires)) if *expires > clock::now() => Some(data.clone()),
+            Some(_) => {
+                sessions.remove(id);
+                None
+            }
+            None => None,
+        };
+        Box::pin(futures::future::ok(data))
+    }
+
+    fn save(&self, id: &str, data: SessionData, ttl: Duration) -> StoreFuture<()> {
+        let now = clock::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, (_, expires)| *expires > now);
+        sessions.insert(id.to_owned(), (data, now + ttl));
+        Box::pin(futures::future::ok(()))
+    }
+
+    fn delete(&self, id: &str) -> StoreFuture<()> {
+        self.sessions.lock().unwrap().remove(id);
+        Box::pin(futures::future::ok(()))
+    }
+}
+
+// ===== impl FileStore =====
+
+impl FileStore {
+    /// Creates a store keeping sessions in `dir`, which is created if
+    /// needed.
+    ///
+    /// Expired sessions are deleted when they're loaded, but others are
+    /// left in the directory.
+    pub fn new(dir: impl Into<PathBuf>) -> FileStore {
+        FileStore { dir: dir.into() }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+impl SessionStore for FileStore {
+    fn load(&self, id: &str) -> StoreFuture<Option<SessionData>> {
+        let path = self.path(id);
+        Box::pin(async move {
+            let file = match tokio::fs::read(&path).await {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(SessionError::new(err)),
+            };
+            let file: Value = serde_json::from_slice(&file).map_err(SessionError::new)?;
+            let expires = file["expires"].as_u64().unwrap_or(0);
+            if UNIX_EPOCH + Duration::from_secs(expires) <= clock::system_now() {
+                let _ = tokio::fs::remove_file(&path).await;
+                return Ok(None);
+            }
+            let data = serde_json::from_value(file["data"].clone()).map_err(SessionError::new)?;
+            Ok(Some(data))
+        })
+    }
+
+    fn save(&self, id: &str, data: SessionData, ttl: Duration) -> StoreFuture<()> {
+        let dir = self.dir.clone();
+        let path = self.path(id);
+        Box::pin(async move {
+            let expires = (clock::system_now() + ttl)
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let file = json!({ "expires": expires, "data": data });
+            // Written aside first, so a crash can't leave half a session.
+            let tmp = path.with_extension("json.tmp");
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .map_err(SessionError::new)?;
+            tokio::fs::write(&tmp, file.to_string())
+                .await
+                .map_err(SessionError::new)?;
+            tokio::fs::rename(&tmp, &path)
+                .await
+                .map_err(SessionError::new)
+        })
+    }
+
+    fn delete(&self, id: &str) -> StoreFuture<()> {
+        let path = self.path(id);
+        Box::pin(async move {
+            match tokio::fs::remove_file(&path).await {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(SessionError::new(err)),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+// ===== impl SessionError =====
+
+impl SessionError {
+    /// Creates an error from its cause, for use by a [`SessionStore`].
+    pub fn new<E: Into<Box<dyn StdError + Send + Sync>>>(cause: E) -> Self {
+        SessionError {
+            cause: cause.into(),
+        }
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "session error: {}", self.cause)
+    }
+}
+
+impl StdError for SessionError {}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use http::header::SET_COOKIE;
+    use pin_project::pin_project;
+
+    use super::{generate_id, Session, SessionData, SessionStore, Sessions, StoreFuture};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Sessioned(Response);
+
+    impl Reply for Sessioned {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct WithSession<F, S> {
+        pub(super) filter: F,
+        pub(super) sessions: Sessions<S>,
+    }
+
+    impl<F: Clone, S> Clone for WithSession<F, S> {
+        fn clone(&self) -> Self {
+            WithSession {
+                filter: self.filter.clone(),
+                sessions: self.sessions.clone(),
+            }
+        }
+    }
+
+    impl<F, S> FilterBase for WithSession<F, S>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+        S: SessionStore,
+    {
+        type Extract = (Sessioned,);
+        type Error = Rejection;
+        type Future = WithSessionFuture<F, S>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let id = route::with(|route| self.sessions.id(route));
+            let load = match id {
+                Some(ref id) => self.sessions.store.load(id),
+                None => Box::pin(futures::future::ok(None)),
+            };
+            WithSessionFuture {
+                state: State::Load(load, id, self.filter.clone()),
+                sessions: self.sessions.clone(),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithSessionFuture<F: Filter, S> {
+        #[pin]
+        state: State<F>,
+        sessions: Sessions<S>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Load(StoreFuture<Option<SessionData>>, Option<String>, F),
+        Filter(#[pin] F::Future, Session),
+        Save(StoreFuture<()>, Option<Response>),
+        Done,
+    }
+
+    impl<F, S> Future for WithSessionFuture<F, S>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+        S: SessionStore,
+    {
+        type Output = Result<(Sessioned,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            let sessions = pin.sessions;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Load(load, id, filter) => {
+                        let session = match ready!(load.as_mut().poll(cx)) {
+                            Ok(Some(data)) => Session::new(id.take(), data),
+                            // An unknown or expired id is replaced when saved.
+                            Ok(None) => Session::new(None, SessionData::new()),
+                            Err(err) => {
+                                state.set(State::Done);
+                                return Poll::Ready(Err(reject::known(err)));
+                            }
+                        };
+                        route::with(|route| route.extensions_mut().insert(session.clone()));
+                        let fut = filter.filter(Internal);
+                        state.set(State::Filter(fut, session));
+                    }
+                    StateProj::Filter(fut, session) => {
+                        let mut res = match ready!(fut.try_poll(cx)) {
+                            Ok(reply) => reply.into_response(),
+                            Err(err) => {
+                                state.set(State::Done);
+                                return Poll::Ready(Err(err.into()));
+                            }
+                        };
+                        let save = finish(sessions, session, &mut res);
+                        state.set(State::Save(save, Some(res)));
+                    }
+                    StateProj::Save(save, res) => {
+                        let result = match ready!(save.as_mut().poll(cx)) {
+                            Ok(()) => Ok((Sessioned(res.take().expect("polled after complete")),)),
+                            Err(err) => Err(reject::known(err)),
+                        };
+                        state.set(State::Done);
+                        return Poll::Ready(result);
+                    }
+                    StateProj::Done => panic!("polled after complete"),
+                }
+            }
+        }
+    }
+
+    // Saves or deletes the session, if needed, setting the cookie on the
+    // response.
+    fn finish<S: SessionStore>(
+        sessions: &Sessions<S>,
+        session: &Session,
+        res: &mut Response,
+    ) -> StoreFuture<()> {
+        let mut inner = session.inner.lock().unwrap();
+        let store = sessions.store.clone();
+
+        if inner.destroy {
+            let cookie = sessions
+                .cookie(String::new())
+                .max_age(std::time::Duration::from_secs(0));
+            if let Some(cookie) = cookie.header_value() {
+                res.headers_mut().append(SET_COOKIE, cookie);
+            }
+            return match inner.id.take() {
+                Some(id) => store.delete(&id),
+                None => Box::pin(futures::future::ok(())),
+            };
+        }
+        // A session is only saved when changed, or moved to a new id.
+        let renew = inner.renew && inner.id.is_some();
+        if !inner.changed && !renew {
+            return Box::pin(futures::future::ok(()));
+        }
+
+        let old = if inner.renew { inner.id.take() } else { None };
+        let id = inner.id.clone().unwrap_or_else(generate_id);
+        if let Some(cookie) = sessions.cookie(id.clone()).header_value() {
+            res.headers_mut().append(SET_COOKIE, cookie);
+        }
+        let save = store.save(&id, inner.data.clone(), sessions.config.ttl);
+        Box::pin(async move {
+            if let Some(old) = old {
+                store.delete(&old).await?;
+            }
+            save.await
+        })
+    }
+}
@@ -0,0 +1,372 @@
+//! Request coalescing Filters
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, AUTHORIZATION, COOKIE};
+use http::{StatusCode, Version};
+use hyper::Body;
+use tokio::sync::watch;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+use crate::reply::{Reply, Response};
+use crate::route::Route;
+
+use self::internal::WithSingleflight;
+
+/// Create a wrapping filter that coalesces identical concurrent `GET`
+/// requests.
+///
+/// While a request is running the wrapped filter, other `GET` requests with
+/// the same host, path and query (and the same values of any [`vary`] headers)
+/// wait for it instead of running the filter themselves, and then all reply
+/// with a copy of its reply. This keeps a burst of requests for the same
+/// expensive resource, such as right after a cache entry expires, from all
+/// reaching the backend at once.
+///
+/// Requests with an `Authorization` or `Cookie` header are never coalesced,
+/// since their replies are likely meant for that client only, unless the
+/// header is one of the [`vary`] headers.
+///
+/// Only replies with bodies already in memory can be shared. If the first
+/// request is rejected, or its reply is streaming, the waiting requests run
+/// the wrapped filter on their own.
+///
+/// [`vary`]: Singleflight::vary
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path("leaderboard")
+///     .map(|| "expensive to compute")
+///     .with(warp::cache())
+///     .with(warp::singleflight());
+/// ```
+pub fn singleflight() -> Singleflight {
+    Singleflight {
+        flights: Arc::new(Mutex::new(Flights {
+            running: HashMap::new(),
+            next_id: 0,
+        })),
+        vary: Arc::new(Vec::new()),
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to coalesce identical requests.
+///
+/// Filters wrapped with the same `Singleflight`, or a clone of it, share
+/// their requests in flight.
+#[derive(Clone, Debug)]
+pub struct Singleflight {
+    flights: Arc<Mutex<Flights>>,
+    vary: Arc<Vec<HeaderName>>,
+}
+
+#[derive(Debug)]
+struct Flights {
+    running: HashMap<String, (u64, watch::Receiver<Flight>)>,
+    next_id: u64,
+}
+
+#[derive(Clone, Debug)]
+enum Flight {
+    Pending,
+    Done(Arc<Shared>),
+}
+
+#[derive(Debug)]
+struct Shared {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+enum Join {
+    Lead(Leader),
+    Wait(watch::Receiver<Flight>),
+}
+
+// Removes the flight once the request running it is done, successful or
+// not. Waiting requests notice the sender is gone.
+#[derive(Debug)]
+struct Leader {
+    flights: Arc<Mutex<Flights>>,
+    key: String,
+    id: u64,
+    tx: watch::Sender<Flight>,
+}
+
+impl Singleflight {
+    /// Only coalesces requests with the same value of a request header,
+    /// such as `Authorization`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    pub fn vary<K>(mut self, name: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        let name = HeaderName::try_from(name)
+            .unwrap_or_else(|_| panic!("singleflight: invalid header name"));
+        Arc::make_mut(&mut self.vary).push(name);
+        self
+    }
+
+    /// The number of distinct requests currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.flights.lock().unwrap().running.len()
+    }
+
+    // Whether the request carries credentials that aren't part of the key,
+    // so its reply mustn't be shared with other clients.
+    fn is_private(&self, route: &Route) -> bool {
+        [AUTHORIZATION, COOKIE]
+            .iter()
+            .any(|name| route.headers().contains_key(name) && !self.vary.contains(name))
+    }
+
+    fn key(&self, route: &Route) -> String {
+        // Virtual hosts may serve different replies for the same path.
+        let mut key = route.authority().unwrap_or_default();
+        key.push_str(route.full_path());
+        if let Some(query) = route.query() {
+            key.push('?');
+            key.push_str(query);
+        }
+        for name in self.vary.iter() {
+            key.push('\n');
+            for value in route.headers().get_all(name) {
+                key.push_str(&String::from_utf8_lossy(value.as_bytes()));
+                key.push('\n');
+            }
+        }
+        key
+    }
+
+    fn join(&self, key: String) -> Join {
+        let mut flights = self.flights.lock().unwrap();
+        if let Some((_, rx)) = flights.running.get(&key) {
+            return Join::Wait(rx.clone());
+        }
+        flights.next_id += 1;
+        let id = flights.next_id;
+        let (tx, rx) = watch::channel(Flight::Pending);
+        flights.running.insert(key.clone(), (id, rx));
+        Join::Lead(Leader {
+            flights: self.flights.clone(),
+            key,
+            id,
+            tx,
+        })
+    }
+}
+
+impl<F> Wrap<F> for Singleflight
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithSingleflight<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithSingleflight {
+            filter,
+            singleflight: self.clone(),
+        }
+    }
+}
+
+impl Leader {
+    fn finish(self, shared: Shared) {
+        let _ = self.tx.send(Flight::Done(Arc::new(shared)));
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        let mut flights = self.flights.lock().unwrap();
+        if matches!(flights.running.get(&self.key), Some((id, _)) if *id == self.id) {
+            flights.running.remove(&self.key);
+        }
+    }
+}
+
+impl Shared {
+    fn response(&self) -> Response {
+        let mut res = Response::new(Body::from(self.body.clone()));
+        *res.status_mut() = self.status;
+        *res.version_mut() = self.version;
+        *res.headers_mut() = self.headers.clone();
+        res
+    }
+}
+
+// Resolves to the shared reply, or `None` if the flight ended without one.
+async fn wait(mut rx: watch::Receiver<Flight>) -> Option<Arc<Shared>> {
+    loop {
+        if let Flight::Done(ref shared) = *rx.borrow() {
+            return Some(shared.clone());
+        }
+        if rx.changed().await.is_err() {
+            return None;
+        }
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use bytes::Bytes;
+    use futures::{ready, TryFuture};
+    use http::Method;
+    use hyper::body::HttpBody;
+    use hyper::Body;
+    use pin_project::pin_project;
+
+    use super::{wait, Join, Leader, Shared, Singleflight};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::Rejection;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Coalesced(Response);
+
+    impl Reply for Coalesced {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithSingleflight<F> {
+        pub(super) filter: F,
+        pub(super) singleflight: Singleflight,
+    }
+
+    impl<F> FilterBase for WithSingleflight<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Coalesced,);
+        type Error = Rejection;
+        type Future = WithSingleflightFuture<F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let join = route::with(|route| {
+                if route.method() != Method::GET || self.singleflight.is_private(route) {
+                    return None;
+                }
+                Some(self.singleflight.join(self.singleflight.key(route)))
+            });
+            let state = match join {
+                Some(Join::Wait(rx)) => State::Wait(Box::pin(wait(rx))),
+                Some(Join::Lead(leader)) => {
+                    State::Filter(self.filter.filter(Internal), Some(leader))
+                }
+                None => State::Filter(self.filter.filter(Internal), None),
+            };
+            WithSingleflightFuture {
+                state,
+                filter: self.filter.clone(),
+            }
+        }
+    }
+
+    type Buffer = Pin<Box<dyn Future<Output = Result<Bytes, hyper::Error>> + Send>>;
+    type Wait = Pin<Box<dyn Future<Output = Option<Arc<Shared>>> + Send>>;
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithSingleflightFuture<F: Filter> {
+        #[pin]
+        state: State<F>,
+        filter: F,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<F: Filter> {
+        Filter(#[pin] F::Future, Option<Leader>),
+        Buffer(Buffer, Option<(http::response::Parts, Leader)>),
+        Wait(Wait),
+    }
+
+    impl<F> Future for WithSingleflightFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Coalesced,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut state = pin.state;
+            loop {
+                match state.as_mut().project() {
+                    StateProj::Filter(fut, leader) => {
+                        let res = ready!(fut.try_poll(cx))
+                            .map_err(Into::into)?
+                            .into_response();
+                        // Only bodies already in memory, since buffering a
+                        // stream could hold up every waiting request.
+                        let in_memory = res.body().size_hint().exact().is_some();
+                        match leader.take() {
+                            Some(leader) if in_memory => {
+                                let (parts, body) = res.into_parts();
+                                state.set(State::Buffer(
+                                    Box::pin(hyper::body::to_bytes(body)),
+                                    Some((parts, leader)),
+                                ));
+                            }
+                            _ => return Poll::Ready(Ok((Coalesced(res),))),
+                        }
+                    }
+                    StateProj::Buffer(buffer, parts) => {
+                        let result = ready!(buffer.as_mut().poll(cx));
+                        let (parts, leader) = parts.take().expect("polled after complete");
+                        let body = match result {
+                            Ok(body) => body,
+                            Err(err) => {
+                                tracing::debug!("singleflight: error buffering body: {}", err);
+                                let res = Response::from_parts(parts, Body::empty());
+                                return Poll::Ready(Ok((Coalesced(res),)));
+                            }
+                        };
+                        leader.finish(Shared {
+                            status: parts.status,
+                            version: parts.version,
+                            headers: parts.headers.clone(),
+                            body: body.clone(),
+                        });
+                        let res = Response::from_parts(parts, Body::from(body));
+                        return Poll::Ready(Ok((Coalesced(res),)));
+                    }
+                    StateProj::Wait(wait) => match ready!(wait.as_mut().poll(cx)) {
+                        Some(shared) => return Poll::Ready(Ok((Coalesced(shared.response()),))),
+                        None => {
+                            tracing::debug!("singleflight: no reply to share, running filter");
+                            let fut = pin.filter.filter(Internal);
+                            state.set(State::Filter(fut, None));
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Live request tap Filters
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::Stream;
+use http::{Method, StatusCode};
+use tokio::sync::broadcast;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::IsReject;
+use crate::reply::Reply;
+
+use self::internal::WithTap;
+
+/// Create a wrapping filter that sends a [`Summary`] of requests to the
+/// subscribers of a [`TapHandle`].
+///
+/// This lets an operator watch the live traffic of wrapped routes, such as
+/// from an SSE endpoint. While nobody is subscribed, nothing is recorded.
+/// Use [`Tap::sample`] to only send a fraction of requests, to bound the
+/// overhead on busy routes.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use futures::StreamExt;
+/// use warp::Filter;
+/// use warp::tap::TapHandle;
+///
+/// let handle = TapHandle::new();
+///
+/// let api = warp::path("api")
+///     .map(|| "hello")
+///     .with(warp::tap(&handle).sample(0.1));
+///
+/// let watch = warp::path("tap").map(move || {
+///     let summaries = handle.subscribe().map(|summary| {
+///         Ok::<_, Infallible>(warp::sse::Event::default().data(summary.to_string()))
+///     });
+///     warp::sse::reply(summaries)
+/// });
+///
+/// let routes = api.or(watch);
+/// ```
+pub fn tap(handle: &TapHandle) -> Tap {
+    Tap {
+        handle: handle.clone(),
+        sample: 1.0,
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to send request summaries to a
+/// [`TapHandle`].
+#[derive(Clone, Debug)]
+pub struct Tap {
+    handle: TapHandle,
+    sample: f64,
+}
+
+/// A live stream of request summaries from [`tap`] filters.
+///
+/// Clones share the same subscribers.
+#[derive(Clone, Debug)]
+pub struct TapHandle {
+    sender: broadcast::Sender<Summary>,
+    seen: Arc<AtomicU64>,
+}
+
+/// A summary of a request and its reply.
+#[derive(Clone, Debug)]
+pub struct Summary {
+    method: Method,
+    path: String,
+    route: Option<String>,
+    remote_addr: Option<SocketAddr>,
+    status: StatusCode,
+    elapsed: Duration,
+}
+
+impl Tap {
+    /// Only sends about `ratio` of requests, such as `0.01` for 1 in 100.
+    ///
+    /// Requests are picked evenly, not randomly. Defaults to `1.0`, sending
+    /// every request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` isn't between `0.0` and `1.0`.
+    pub fn sample(mut self, ratio: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "tap sample ratio must be between 0 and 1"
+        );
+        self.sample = ratio;
+        self
+    }
+}
+
+impl<F> Wrap<F> for Tap
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithTap<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithTap {
+            filter,
+            tap: self.clone(),
+        }
+    }
+}
+
+impl TapHandle {
+    /// Creates a handle with no subscribers.
+    ///
+    /// Subscribers that fall more than 1024 summaries behind miss the
+    /// oldest ones.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        TapHandle {
+            sender,
+            seen: Arc::default(),
+        }
+    }
+
+    /// Subscribes to the summaries of requests from now on.
+    pub fn subscribe(&self) -> impl Stream<Item = Summary> + Send + 'static {
+        let receiver = self.sender.subscribe();
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(summary) => return Some((summary, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::debug!("tap subscriber missed {} summaries", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// The number of current subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    fn should_sample(&self, ratio: f64) -> bool {
+        if self.sender.receiver_count() == 0 || ratio <= 0.0 {
+            return false;
+        }
+        if ratio >= 1.0 {
+            return true;
+        }
+        // Sends whenever the running total of `ratio` passes a whole number.
+        let seen = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((seen + 1.0) * ratio).floor() > (seen * ratio).floor()
+    }
+}
+
+impl Default for TapHandle {
+    fn default() -> Self {
+        TapHandle::new()
+    }
+}
+
+impl Summary {
+    /// The request's method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The request's path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The [template](crate::path::template) of the path filters that
+    /// matched, if any.
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// The remote address of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// The status the request was replied to with, including rejections.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The time taken to reply.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {:?}",
+            self.method,
+            self.path,
+            self.status.as_u16(),
+            self.elapsed
+        )?;
+        if let Some(ref route) = self.route {
+            write!(f, " route={}", route)?;
+        }
+        if let Some(addr) = self.remote_addr {
+            write!(f, " remote={}", addr)?;
+        }
+        Ok(())
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{Summary, Tap, TapHandle};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::IsReject;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Tapped(Response);
+
+    impl Reply for Tapped {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithTap<F> {
+        pub(super) filter: F,
+        pub(super) tap: Tap,
+    }
+
+    impl<F> FilterBase for WithTap<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Tapped,);
+        type Error = F::Error;
+        type Future = WithTapFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let handle = if self.tap.handle.should_sample(self.tap.sample) {
+                Some(self.tap.handle.clone())
+            } else {
+                None
+            };
+            WithTapFuture {
+                future: self.filter.filter(Internal),
+                handle,
+                started: Instant::now(),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithTapFuture<F> {
+        #[pin]
+        future: F,
+        handle: Option<TapHandle>,
+        started: Instant,
+    }
+
+    impl<F> Future for WithTapFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Tapped,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let (result, status) = match ready!(pin.future.try_poll(cx)) {
+                Ok(reply) => {
+                    let res = reply.into_response();
+                    let status = res.status();
+                    (Ok((Tapped(res),)), status)
+                }
+                Err(reject) => {
+                    let status = reject.status();
+                    (Err(reject), status)
+                }
+            };
+
+            if let Some(handle) = pin.handle.take() {
+                let started = *pin.started;
+                let summary = route::with(|route| Summary {
+                    method: route.method().clone(),
+                    path: route.full_path().to_owned(),
+                    route: route.template(),
+                    remote_addr: route.remote_addr(),
+                    status,
+                    elapsed: started.elapsed(),
+                });
+                // Subscribers may have left since the request was sampled.
+                let _ = handle.sender.send(summary);
+            }
+
+            Poll::Ready(result)
+        }
+    }
+}
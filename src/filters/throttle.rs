@@ -0,0 +1,195 @@
+//! Bandwidth throttling Filters
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream;
+use http::header::{HeaderValue, CONTENT_LENGTH};
+use hyper::body::HttpBody;
+use hyper::Body;
+use tokio::time::Instant;
+
+use self::sealed::WithThrottle_;
+use crate::filter::{Filter, Map, Wrap};
+use crate::reply::{Reply, Response};
+
+/// Create a wrapping filter that limits how fast reply bodies are sent.
+///
+/// Each reply body is sent at no more than `bytes_per_sec`, by pacing its
+/// chunks. Large chunks, such as those read by [`warp::fs`](crate::fs), are
+/// split so the pace stays smooth. Adding a [`global`](Throttle::global)
+/// limit also caps all replies of the filter together.
+///
+/// Only the body is paced; headers are sent right away.
+///
+/// # Panics
+///
+/// Panics if `bytes_per_sec` is 0.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Each download gets up to 1 MiB/s, and all of them 10 MiB/s together.
+/// let downloads = warp::path("downloads")
+///     .and(warp::fs::dir("./downloads"))
+///     .with(warp::throttle(1024 * 1024).global(10 * 1024 * 1024));
+/// ```
+pub fn throttle(bytes_per_sec: u64) -> Throttle {
+    assert!(bytes_per_sec > 0, "throttle rate must be greater than 0");
+    Throttle {
+        per_reply: bytes_per_sec,
+        global: None,
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to limit the byte rate of replies.
+#[derive(Clone, Debug)]
+pub struct Throttle {
+    per_reply: u64,
+    global: Option<Arc<Mutex<Bucket>>>,
+}
+
+// A token bucket that may go into debt: sending `n` bytes takes `n`
+// tokens, and waits for the tokens missing to be refilled.
+#[derive(Debug)]
+struct Bucket {
+    rate: u64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Throttle {
+    /// Also limits all reply bodies of this filter, and of filters wrapped
+    /// with clones of it, to `bytes_per_sec` together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_sec` is 0.
+    pub fn global(mut self, bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "throttle rate must be greater than 0");
+        self.global = Some(Arc::new(Mutex::new(Bucket::new(bytes_per_sec))));
+        self
+    }
+
+    fn pace(&self, res: Response) -> Response {
+        let (mut parts, body) = res.into_parts();
+        if let Some(len) = body.size_hint().exact() {
+            // The paced body is a stream, so keep the length known.
+            parts
+                .headers
+                .entry(CONTENT_LENGTH)
+                .or_insert_with(|| HeaderValue::from(len));
+        }
+
+        let state = Paced {
+            body,
+            pending: Bytes::new(),
+            own: Bucket::new(self.per_reply),
+            global: self.global.clone(),
+        };
+        let stream = stream::try_unfold(state, |mut state| async move {
+            if state.pending.is_empty() {
+                state.pending = match state.body.data().await {
+                    Some(chunk) => chunk?,
+                    None => return Ok(None),
+                };
+            }
+            let len = state.pending.len().min(state.own.chunk_size());
+            let chunk = state.pending.split_to(len);
+            let delay = state.reserve(len);
+            if delay > Duration::from_secs(0) {
+                tokio::time::sleep(delay).await;
+            }
+            Ok::<_, hyper::Error>(Some((chunk, state)))
+        });
+        Response::from_parts(parts, Body::wrap_stream(stream))
+    }
+}
+
+impl Bucket {
+    fn new(rate: u64) -> Bucket {
+        let mut bucket = Bucket {
+            rate,
+            capacity: 0.0,
+            tokens: 0.0,
+            last: Instant::now(),
+        };
+        bucket.capacity = bucket.chunk_size() as f64;
+        bucket.tokens = bucket.capacity;
+        bucket
+    }
+
+    // About a tenth of a second's worth, within reason.
+    fn chunk_size(&self) -> usize {
+        (self.rate / 10).clamp(1024, 64 * 1024) as usize
+    }
+
+    fn reserve(&mut self, len: usize) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.capacity);
+        self.tokens -= len as f64;
+        if self.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate as f64)
+        }
+    }
+}
+
+struct Paced {
+    body: Body,
+    pending: Bytes,
+    own: Bucket,
+    global: Option<Arc<Mutex<Bucket>>>,
+}
+
+impl Paced {
+    fn reserve(&mut self, len: usize) -> Duration {
+        let own = self.own.reserve(len);
+        match self.global {
+            Some(ref global) => own.max(global.lock().unwrap().reserve(len)),
+            None => own,
+        }
+    }
+}
+
+impl<F, R> Wrap<F> for Throttle
+where
+    F: Filter<Extract = (R,)>,
+    R: Reply,
+{
+    type Wrapped = Map<F, WithThrottle_>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        let with = WithThrottle_ {
+            throttle: self.clone(),
+        };
+        filter.map(with)
+    }
+}
+
+mod sealed {
+    use super::Throttle;
+    use crate::generic::{Func, One};
+    use crate::reply::{Reply, Reply_};
+
+    #[derive(Clone)]
+    #[allow(missing_debug_implementations)]
+    pub struct WithThrottle_ {
+        pub(super) throttle: Throttle,
+    }
+
+    impl<R: Reply> Func<One<R>> for WithThrottle_ {
+        type Output = Reply_;
+
+        fn call(&self, args: One<R>) -> Self::Output {
+            Reply_(self.throttle.pace(args.0.into_response()))
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Timeout Filters
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::Duration;
+
+use http::StatusCode;
+
+use crate::filter::{Filter, Wrap};
+use crate::reject::Rejection;
+
+use self::internal::WithTimeout;
+
+/// Create a wrapping filter that rejects requests taking longer than
+/// `duration`.
+///
+/// When the deadline passes, the wrapped filter's future is dropped, and the
+/// request is rejected with a [`TimedOut`] rejection. Unless recovered, it
+/// replies with a `504 Gateway Timeout`; use [`Timeout::status`] to pick a
+/// different status, such as `408 Request Timeout`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::timeout(Duration::from_secs(5)));
+/// ```
+pub fn timeout(duration: Duration) -> Timeout {
+    Timeout {
+        duration,
+        status: StatusCode::GATEWAY_TIMEOUT,
+    }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to reject requests exceeding a
+/// deadline.
+#[derive(Clone, Copy, Debug)]
+pub struct Timeout {
+    duration: Duration,
+    status: StatusCode,
+}
+
+impl Timeout {
+    /// Sets the status code of the reply when the deadline passes.
+    ///
+    /// Defaults to `504 Gateway Timeout`.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl<F> Wrap<F> for Timeout
+where
+    F: Filter + Clone + Send,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithTimeout<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithTimeout {
+            filter,
+            timeout: *self,
+        }
+    }
+}
+
+/// An error used to reject requests that exceeded a [`timeout`].
+#[derive(Debug)]
+pub struct TimedOut {
+    duration: Duration,
+    status: StatusCode,
+}
+
+impl TimedOut {
+    /// The deadline that was exceeded.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub(crate) fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Request timed out after {:?}", self.duration)
+    }
+}
+
+impl StdError for TimedOut {}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use pin_project::pin_project;
+
+    use super::{TimedOut, Timeout};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithTimeout<F> {
+        pub(super) filter: F,
+        pub(super) timeout: Timeout,
+    }
+
+    impl<F> FilterBase for WithTimeout<F>
+    where
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = Rejection;
+        type Future = WithTimeoutFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            WithTimeoutFuture {
+                future: tokio::time::timeout(self.timeout.duration, self.filter.filter(Internal)),
+                timeout: self.timeout,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithTimeoutFuture<F> {
+        #[pin]
+        future: tokio::time::Timeout<F>,
+        timeout: Timeout,
+    }
+
+    impl<F, T, E> Future for WithTimeoutFuture<F>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Into<Rejection>,
+    {
+        type Output = Result<T, Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            match pin.future.poll(cx) {
+                Poll::Ready(Ok(result)) => Poll::Ready(result.map_err(Into::into)),
+                Poll::Ready(Err(_elapsed)) => {
+                    tracing::debug!("filter timed out after {:?}", pin.timeout.duration);
+                    Poll::Ready(Err(reject::known(TimedOut {
+                        duration: pin.timeout.duration,
+                        status: pin.timeout.status,
+                    })))
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+}
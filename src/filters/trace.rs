@@ -14,18 +14,28 @@ use std::net::SocketAddr;
 
 use http::{self, header};
 
-use crate::filter::{Filter, WrapSealed};
-use crate::reject::IsReject;
+use crate::filter::{Filter, Func, Wrap};
+use crate::reject::{IsReject, Rejection};
 use crate::reply::Reply;
+use crate::request_id::RequestId;
 use crate::route::Route;
+use crate::sample::Sample;
 
-use self::internal::WithTrace;
+use self::internal::{WithExtracted, WithTrace};
+#[cfg(feature = "opentelemetry")]
+pub use self::otel::{opentelemetry, OpenTelemetry};
+
+#[cfg(feature = "opentelemetry")]
+mod otel;
 
 /// Create a wrapping filter that instruments every request with a `tracing`
 /// [`Span`] at the [`INFO`] level, containing a summary of the request.
 /// Additionally, if the [`DEBUG`] level is enabled, the span will contain an
 /// event recording the request's headers.
 ///
+/// Once the wrapped filter is finished, the span's `route` field is recorded
+/// with the [template](crate::path::template) of the matched path.
+///
 /// # Example
 ///
 /// ```
@@ -49,15 +59,21 @@ pub fn request() -> Trace<impl Fn(Info) -> Span + Clone> {
             path = %info.path(),
             version = ?info.route.version(),
             referer = Empty,
+            request.id = Empty,
+            route = Empty,
         );
 
         // Record optional fields.
         if let Some(remote_addr) = info.remote_addr() {
-            span.record("remote.addr", &display(remote_addr));
+            span.record("remote.addr", display(remote_addr));
         }
 
         if let Some(referer) = info.referer() {
-            span.record("referer", &display(referer));
+            span.record("referer", display(referer));
+        }
+
+        if let Some(id) = info.request_id() {
+            span.record("request.id", display(id));
         }
 
         tracing::debug!(parent: &span, "received request");
@@ -92,7 +108,10 @@ pub fn trace<F>(func: F) -> Trace<F>
 where
     F: Fn(Info) -> Span + Clone,
 {
-    Trace { func }
+    Trace {
+        func,
+        sample: Sample::all(),
+    }
 }
 
 /// Create a wrapping filter that instruments every request with a `tracing`
@@ -117,20 +136,70 @@ where
 /// let routes = hello.or(goodbye);
 /// ```
 ///
+/// To include fields computed from the request, such as a path parameter,
+/// use [`extracted`] instead.
+///
 /// [`Span`]: https://docs.rs/tracing/latest/tracing/#spans
 /// [`DEBUG`]: https://docs.rs/tracing/0.1.16/tracing/struct.Level.html#associatedconstant.DEBUG
 pub fn named(name: &'static str) -> Trace<impl Fn(Info<'_>) -> Span + Copy> {
     trace(move |_| tracing::debug_span!("context", "{}", name,))
 }
 
+/// Create a wrapping filter that instruments every request with a `tracing`
+/// [`Span`] created from the extractions of another filter.
+///
+/// The `extract` filter runs first, and its extracted values are passed to
+/// `func` to create the span, in which the wrapped filter then runs. Any path
+/// segments matched by `extract` are matched again by the wrapped filter, so
+/// the wrapped filter can still use the same path filters. If `extract`
+/// rejects, the request is rejected without running the wrapped filter.
+///
+/// `extract` shouldn't take the request body, since the wrapped filter
+/// couldn't then read it.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let user = warp::path!("users" / u32)
+///     .map(|id| format!("user #{}", id))
+///     .with(warp::trace::extracted(
+///         warp::path!("users" / u32),
+///         |id| tracing::debug_span!("user", user.id = id),
+///     ));
+/// ```
+///
+/// [`Span`]: https://docs.rs/tracing/latest/tracing/#spans
+pub fn extracted<E, FN>(extract: E, func: FN) -> Extracted<E, FN>
+where
+    E: Filter + Clone + Send,
+    E::Error: Into<Rejection>,
+    FN: Func<E::Extract, Output = Span> + Clone + Send,
+{
+    Extracted { extract, func }
+}
+
 /// Decorates a [`Filter`](crate::Filter) to create a [`tracing`] [span] for
 /// requests and responses.
 ///
 /// [`tracing`]: https://crates.io/tracing
 /// [span]: https://docs.rs/tracing/latest/tracing/#spans
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Trace<F> {
     func: F,
+    sample: Sample,
+}
+
+/// Decorates a [`Filter`](crate::Filter) to create a [`tracing`] [span] from
+/// the extractions of another filter.
+///
+/// [`tracing`]: https://crates.io/tracing
+/// [span]: https://docs.rs/tracing/latest/tracing/#spans
+#[derive(Clone, Copy, Debug)]
+pub struct Extracted<E, FN> {
+    extract: E,
+    func: FN,
 }
 
 /// Information about the request/response that can be used to prepare log lines.
@@ -139,7 +208,7 @@ pub struct Info<'a> {
     route: &'a Route,
 }
 
-impl<FN, F> WrapSealed<F> for Trace<FN>
+impl<FN, F> Wrap<F> for Trace<FN>
 where
     FN: Fn(Info) -> Span + Clone + Send,
     F: Filter + Clone + Send,
@@ -156,6 +225,39 @@ where
     }
 }
 
+impl<E, FN, F> Wrap<F> for Extracted<E, FN>
+where
+    E: Filter + Clone + Send,
+    E::Error: Into<Rejection>,
+    FN: Func<E::Extract, Output = Span> + Clone + Send,
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+{
+    type Wrapped = WithExtracted<E, FN, F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithExtracted {
+            filter,
+            extracted: self.clone(),
+        }
+    }
+}
+
+impl<F> Trace<F> {
+    /// Only traces the requests picked by `sample`.
+    ///
+    /// Defaults to tracing every request. The wrapped filter of a request
+    /// that isn't picked by the rate of the sample runs outside of any span.
+    /// If it then matches a condition of the sample, such as failing, the
+    /// span is created once it's finished, so the "finished" event is still
+    /// recorded in it.
+    pub fn sample(mut self, sample: Sample) -> Self {
+        self.sample = sample;
+        self
+    }
+}
+
 impl<'a> Info<'a> {
     /// View the remote `SocketAddr` of the request.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
@@ -205,14 +307,37 @@ impl<'a> Info<'a> {
     pub fn request_headers(&self) -> &http::HeaderMap {
         self.route.headers()
     }
+
+    /// View the ID given to the request by [`warp::request_id`](crate::request_id()).
+    pub fn request_id(&self) -> Option<&str> {
+        self.route
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+    }
+
+    /// View the template of the path matched before the span was created.
+    ///
+    /// Spans are created before the wrapped filter runs, so this only has the
+    /// path filters outside of it. A `route` field declared on the span is
+    /// recorded with the full template once the wrapped filter is finished.
+    pub fn route_template(&self) -> Option<String> {
+        self.route.template()
+    }
 }
 
 mod internal {
-    use futures::{future::Inspect, future::MapOk, FutureExt, TryFutureExt};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
 
-    use super::{Info, Trace};
-    use crate::filter::{Filter, FilterBase, Internal};
-    use crate::reject::IsReject;
+    use super::{Extracted, Info, Trace};
+    use crate::filter::{Filter, FilterBase, Func, Internal};
+    use crate::reject::{IsReject, Rejection};
     use crate::reply::Reply;
     use crate::reply::Response;
     use crate::route;
@@ -228,7 +353,7 @@ mod internal {
     }
 
     #[allow(missing_debug_implementations)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone)]
     pub struct WithTrace<FN, F> {
         pub(super) filter: F,
         pub(super) trace: Trace<FN>,
@@ -238,6 +363,9 @@ mod internal {
     use tracing_futures::{Instrument, Instrumented};
 
     fn finished_logger<E: IsReject>(reply: &Result<(Traced,), E>) {
+        if let Some(template) = route::with(|route| route.template()) {
+            Span::current().record("route", tracing::field::display(template));
+        }
         match reply {
             Ok((Traced(resp),)) => {
                 tracing::info!(target: "warp::filters::trace", status = resp.status().as_u16(), "finished processing with success");
@@ -283,23 +411,175 @@ mod internal {
     {
         type Extract = (Traced,);
         type Error = F::Error;
-        type Future = Instrumented<
-            Inspect<
-                MapOk<F::Future, fn(F::Extract) -> Self::Extract>,
-                fn(&Result<Self::Extract, F::Error>),
-            >,
-        >;
+        type Future = Instrumented<WithTraceFuture<FN, F::Future>>;
 
         fn filter(&self, _: Internal) -> Self::Future {
-            let span = route::with(|route| (self.trace.func)(Info { route }));
+            let sampled = self.trace.sample.head();
+            let span = if sampled {
+                route::with(|route| (self.trace.func)(Info { route }))
+            } else {
+                Span::none()
+            };
             let _entered = span.enter();
 
-            tracing::info!(target: "warp::filters::trace", "processing request");
-            self.filter
-                .filter(Internal)
-                .map_ok(convert_reply as fn(F::Extract) -> Self::Extract)
-                .inspect(finished_logger as fn(&Result<Self::Extract, F::Error>))
-                .instrument(span.clone())
+            if sampled {
+                tracing::info!(target: "warp::filters::trace", "processing request");
+            }
+            let tail = if !sampled && self.trace.sample.has_tail() {
+                Some(self.trace.clone())
+            } else {
+                None
+            };
+            WithTraceFuture {
+                future: self.filter.filter(Internal),
+                sampled,
+                tail,
+                started: Instant::now(),
+            }
+            .instrument(span.clone())
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithTraceFuture<FN, F> {
+        #[pin]
+        future: F,
+        sampled: bool,
+        tail: Option<Trace<FN>>,
+        started: Instant,
+    }
+
+    impl<FN, F> Future for WithTraceFuture<FN, F>
+    where
+        FN: Fn(Info) -> Span,
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Traced,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.future.try_poll(cx)).map(convert_reply);
+
+            if *pin.sampled {
+                finished_logger(&result);
+            } else if let Some(ref trace) = pin.tail {
+                let status = match result {
+                    Ok((Traced(ref res),)) => res.status(),
+                    Err(ref e) => e.status(),
+                };
+                if trace.sample.tail(status, pin.started.elapsed()) {
+                    let span = route::with(|route| (trace.func)(Info { route }));
+                    let _entered = span.enter();
+                    finished_logger(&result);
+                }
+            }
+
+            Poll::Ready(result)
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithExtracted<E, FN, F> {
+        pub(super) filter: F,
+        pub(super) extracted: Extracted<E, FN>,
+    }
+
+    impl<E, FN, F> FilterBase for WithExtracted<E, FN, F>
+    where
+        E: Filter + Clone + Send,
+        E::Error: Into<Rejection>,
+        FN: Func<E::Extract, Output = Span> + Clone + Send,
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Extract = (Traced,);
+        type Error = Rejection;
+        type Future = WithExtractedFuture<E, FN, F>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let (index, path_matched) =
+                route::with(|route| (route.matched_path_index(), route.path_matched()));
+            WithExtractedFuture {
+                state: State::Extracting {
+                    future: self.extracted.extract.filter(Internal),
+                    func: self.extracted.func.clone(),
+                    filter: self.filter.clone(),
+                    index,
+                    path_matched,
+                },
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithExtractedFuture<E: Filter, FN, F: Filter> {
+        #[pin]
+        state: State<E, FN, F>,
+    }
+
+    #[pin_project(project = StateProj)]
+    enum State<E: Filter, FN, F: Filter> {
+        Extracting {
+            #[pin]
+            future: E::Future,
+            func: FN,
+            filter: F,
+            index: usize,
+            path_matched: bool,
+        },
+        Filtering(#[pin] Instrumented<F::Future>),
+    }
+
+    impl<E, FN, F> Future for WithExtractedFuture<E, FN, F>
+    where
+        E: Filter,
+        E::Error: Into<Rejection>,
+        FN: Func<E::Extract, Output = Span>,
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<(Traced,), Rejection>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            loop {
+                let next = match self.as_mut().project().state.project() {
+                    StateProj::Extracting {
+                        future,
+                        func,
+                        filter,
+                        index,
+                        path_matched,
+                    } => {
+                        let args = ready!(future.try_poll(cx)).map_err(Into::into)?;
+                        // The wrapped filter matches the path again.
+                        route::with(|route| {
+                            route.reset_matched_path_index(*index);
+                            route.set_path_matched(*path_matched);
+                        });
+                        let span = func.call(args);
+                        let _entered = span.enter();
+
+                        tracing::info!(target: "warp::filters::trace", "processing request");
+                        filter.filter(Internal).instrument(span.clone())
+                    }
+                    StateProj::Filtering(mut future) => {
+                        let result = ready!(future.as_mut().try_poll(cx))
+                            .map(convert_reply)
+                            .map_err(Into::into);
+                        let _entered = future.span().enter();
+                        finished_logger(&result);
+                        return Poll::Ready(result);
+                    }
+                };
+                self.as_mut().project().state.set(State::Filtering(next));
+            }
         }
     }
 }
@@ -0,0 +1,192 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::ready;
+use http::header::{HeaderMap, USER_AGENT};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer, WithContext};
+use opentelemetry::{global, KeyValue};
+use pin_project::pin_project;
+
+use super::internal::Traced;
+use crate::filter::{Filter, FilterBase, Internal, Wrap};
+use crate::reject::IsReject;
+use crate::reply::Reply;
+use crate::route::{self, Route};
+
+/// Create a wrapping filter that instruments every request with an
+/// [OpenTelemetry] server span.
+///
+/// The span is a child of the W3C `traceparent` and `tracestate` headers of
+/// the request, if any, and is created with the tracer named `warp` from the
+/// global tracer provider. It has the `http.method`, `http.target`,
+/// `http.route`, and `http.status_code` attributes, and an error status for
+/// `5xx` replies.
+///
+/// The span's context is the current one while the wrapped filter runs, so
+/// handlers can add events to it, or propagate it to other services.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path!("hello" / String)
+///     .map(|name| format!("Hello, {}!", name))
+///     .with(warp::trace::opentelemetry());
+/// ```
+///
+/// [OpenTelemetry]: https://opentelemetry.io
+pub fn opentelemetry() -> OpenTelemetry {
+    OpenTelemetry { _p: () }
+}
+
+/// Decorates a [`Filter`](crate::Filter) to create an OpenTelemetry span for
+/// requests.
+#[derive(Clone, Copy, Debug)]
+pub struct OpenTelemetry {
+    _p: (),
+}
+
+impl<F> Wrap<F> for OpenTelemetry
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithOpenTelemetry<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithOpenTelemetry { filter }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[derive(Clone, Copy)]
+pub struct WithOpenTelemetry<F> {
+    filter: F,
+}
+
+impl<F> FilterBase for WithOpenTelemetry<F>
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Extract = (Traced,);
+    type Error = F::Error;
+    type Future = WithOpenTelemetryFuture<F::Future>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let cx = route::with(start_span);
+        let future = {
+            let _attached = cx.clone().attach();
+            self.filter.filter(Internal)
+        };
+        WithOpenTelemetryFuture {
+            future: future.with_context(cx.clone()),
+            cx,
+        }
+    }
+}
+
+fn start_span(route: &mut Route) -> opentelemetry::Context {
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(route.headers()));
+
+    let uri = route.uri();
+    let target = uri.path_and_query().map_or(uri.path(), |pq| pq.as_str());
+    let mut attributes = vec![
+        KeyValue::new("http.method", route.method().as_str().to_owned()),
+        KeyValue::new("http.target", target.to_owned()),
+        KeyValue::new("http.flavor", flavor(route.version())),
+    ];
+    if let Some(user_agent) = route
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+    {
+        attributes.push(KeyValue::new("http.user_agent", user_agent.to_owned()));
+    }
+    if let Some(addr) = route.remote_addr() {
+        attributes.push(KeyValue::new("net.sock.peer.addr", addr.ip().to_string()));
+        attributes.push(KeyValue::new("net.sock.peer.port", i64::from(addr.port())));
+    }
+
+    // The route isn't known until the filter has matched, so the span is
+    // renamed once it has.
+    let tracer = global::tracer("warp");
+    let span = tracer
+        .span_builder(format!("HTTP {}", route.method()))
+        .with_kind(SpanKind::Server)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &parent);
+    parent.with_span(span)
+}
+
+fn flavor(version: http::Version) -> &'static str {
+    match version {
+        http::Version::HTTP_09 => "0.9",
+        http::Version::HTTP_10 => "1.0",
+        http::Version::HTTP_2 => "2.0",
+        http::Version::HTTP_3 => "3.0",
+        _ => "1.1",
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct WithOpenTelemetryFuture<F> {
+    #[pin]
+    future: WithContext<F>,
+    cx: opentelemetry::Context,
+}
+
+impl<F, T, E> Future for WithOpenTelemetryFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    T: Reply,
+    E: IsReject,
+{
+    type Output = Result<(Traced,), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pin = self.project();
+        let result = ready!(pin.future.poll(cx));
+        let result = result.map(|reply| Traced(reply.into_response()));
+        let status = match result {
+            Ok(Traced(ref res)) => res.status(),
+            Err(ref reject) => reject.status(),
+        };
+
+        let span = pin.cx.span();
+        let (method, template) = route::with(|route| (route.method().clone(), route.template()));
+        if let Some(template) = template {
+            span.update_name(format!("{} {}", method, template));
+            span.set_attribute(KeyValue::new("http.route", template));
+        }
+        span.set_attribute(KeyValue::new(
+            "http.status_code",
+            i64::from(status.as_u16()),
+        ));
+        if status.is_server_error() {
+            span.set_status(Status::error(status.to_string()));
+        }
+        span.end();
+
+        Poll::Ready(result.map(|traced| (traced,)))
+    }
+}
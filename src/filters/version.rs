@@ -0,0 +1,190 @@
+//! API versioning Filters
+//!
+//! Several versions of an API can be served from the same tree of filters,
+//! selected by a path segment with [`path`], or by a header with
+//! [`header`]. [`across`] declares a route once for many versions, replacing
+//! its handler for only some of them.
+
+use std::fmt;
+use std::sync::Arc;
+
+use futures::future;
+
+use crate::filter::{filter_fn, BoxedFilter, Filter, FilterBase, Internal};
+use crate::reject::{self, Rejection};
+use crate::reply::{Reply, Response};
+
+/// Create a `Filter` that matches the path segment `version`, like `v2`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /v2/users
+/// let users = warp::version::path("v2")
+///     .and(warp::path!("users"))
+///     .map(|| "v2 users");
+/// ```
+pub fn path(version: &'static str) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    crate::path(version)
+}
+
+/// Create a `Filter` that requires the header `name` to be `version`, like
+/// `Accept-Version: 2`.
+///
+/// Rejects with a `404 Not Found` if the header is missing or names another
+/// version, so that filters for other versions can be tried.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let v2 = warp::version::header("accept-version", "2").map(|| "v2");
+/// let v1 = warp::version::header("accept-version", "1").map(|| "v1");
+///
+/// let routes = v2.or(v1);
+/// ```
+pub fn header(
+    name: &'static str,
+    version: &'static str,
+) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        tracing::trace!("version::header({:?}, {:?})", name, version);
+        let matches = route
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim() == version);
+        if matches {
+            future::ok(())
+        } else {
+            future::err(reject::not_found())
+        }
+    })
+}
+
+/// Declare a route for every one of `versions`, each selected by the filter
+/// returned by `select`, such as [`path`] or a [`header`].
+///
+/// All versions reply with `filter`, until another filter is given for some
+/// of them with [`Versioned::version`].
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let users = warp::path!("users").map(|| "users");
+/// let old_users = warp::path!("users").map(|| "users, the old way");
+///
+/// // GET /v1/users, /v2/users, and /v3/users
+/// let users = warp::version::across(["v1", "v2", "v3"], warp::version::path, users)
+///     .version("v1", old_users);
+///
+/// // or picked with a header, like `Accept-Version: 3`
+/// let posts = warp::version::across(
+///     ["1", "2", "3"],
+///     |version| warp::version::header("accept-version", version),
+///     warp::path!("posts").map(|| "posts"),
+/// );
+///
+/// let routes = users.or(posts);
+/// ```
+pub fn across<V, S, SF, F, R>(versions: V, select: S, filter: F) -> Versioned
+where
+    V: IntoIterator<Item = &'static str>,
+    S: Fn(&'static str) -> SF + Send + Sync + 'static,
+    SF: Filter<Extract = (), Error = Rejection> + Send + Sync + 'static,
+    F: Filter<Extract = (R,)> + Send + Sync + 'static,
+    F::Error: Into<Rejection>,
+    R: Reply + 'static,
+{
+    let filter = into_response(filter);
+    let mut versioned = Versioned {
+        select: Arc::new(move |version| select(version).boxed()),
+        versions: Vec::new(),
+        combined: reject_all(),
+    };
+    for version in versions {
+        versioned.versions.push((version, filter.clone()));
+    }
+    versioned.combine();
+    versioned
+}
+
+/// A route declared for several versions, with [`across`].
+#[derive(Clone)]
+pub struct Versioned {
+    select: Arc<dyn Fn(&'static str) -> BoxedFilter<()> + Send + Sync>,
+    versions: Vec<(&'static str, BoxedFilter<(Response,)>)>,
+    // The versions, combined with `or`.
+    combined: BoxedFilter<(Response,)>,
+}
+
+impl Versioned {
+    /// Replies to requests for `version` with `filter`, instead of the
+    /// filter shared by all versions.
+    ///
+    /// If `version` isn't one of the versions yet, it's added.
+    pub fn version<F, R>(mut self, version: &'static str, filter: F) -> Versioned
+    where
+        F: Filter<Extract = (R,)> + Send + Sync + 'static,
+        F::Error: Into<Rejection>,
+        R: Reply + 'static,
+    {
+        let filter = into_response(filter);
+        match self.versions.iter_mut().find(|(v, _)| *v == version) {
+            Some(entry) => entry.1 = filter,
+            None => self.versions.push((version, filter)),
+        }
+        self.combine();
+        self
+    }
+
+    /// The versions the route is declared for.
+    pub fn versions(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.versions.iter().map(|&(version, _)| version)
+    }
+
+    fn combine(&mut self) {
+        let mut combined = reject_all();
+        for (version, filter) in &self.versions {
+            let route = (self.select)(version).and(filter.clone());
+            combined = combined.or(route).unify().boxed();
+        }
+        self.combined = combined;
+    }
+}
+
+impl FilterBase for Versioned {
+    type Extract = (Response,);
+    type Error = Rejection;
+    type Future = <BoxedFilter<(Response,)> as FilterBase>::Future;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        self.combined.filter(Internal)
+    }
+}
+
+impl fmt::Debug for Versioned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Versioned")
+            .field("versions", &self.versions().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+fn into_response<F, R>(filter: F) -> BoxedFilter<(Response,)>
+where
+    F: Filter<Extract = (R,)> + Send + Sync + 'static,
+    F::Error: Into<Rejection>,
+    R: Reply + 'static,
+{
+    filter.map(Reply::into_response).boxed()
+}
+
+fn reject_all() -> BoxedFilter<(Response,)> {
+    filter_fn(|_| future::err::<(Response,), _>(reject::not_found())).boxed()
+}
@@ -0,0 +1,351 @@
+//! Tower middleware interop
+//!
+//! Middleware from the [tower] ecosystem, such as timeouts, retries, or
+//! tracing, is written as a [`Layer`] around a [`Service`]. [`from_layer`]
+//! applies such a layer to a `Filter`, with [`Filter::with`], and
+//! [`into_layer`] goes the other way, applying a warp [`Wrap`] to a
+//! `Service`.
+//!
+//! [tower]: https://docs.rs/tower
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::filter::service::FilteredService;
+use crate::filter::{Filter, FilterBase, Wrap};
+use crate::reject::{IsReject, Rejection};
+use crate::reply::Reply;
+use crate::Request;
+
+use self::internal::FilterService;
+pub(crate) use self::internal::ServiceFilter;
+
+pub(crate) type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Create a wrapping filter that applies a [`Layer`] to the filter it wraps.
+///
+/// The wrapped filter is run as the innermost [`Service`], and is handed
+/// the request by the layer's middleware, including any changes it makes.
+/// Its rejections pass through the middleware back to warp, as they would
+/// without the layer. Other errors of the middleware, such as a timeout,
+/// reject the request with a [`ServiceError`].
+///
+/// The extensions and body of the request are moved into the service, so
+/// filters tried after a rejection, with `or`, don't see them.
+///
+/// # Example
+///
+/// ```
+/// use std::task::{Context, Poll};
+/// use tower_layer::Layer;
+/// use tower_service::Service;
+/// use warp::Filter;
+///
+/// // A minimal tower middleware, that logs the requests it sees.
+/// #[derive(Clone)]
+/// struct Log<S>(S);
+///
+/// impl<S, B> Service<warp::http::Request<B>> for Log<S>
+/// where
+///     S: Service<warp::http::Request<B>>,
+/// {
+///     type Response = S::Response;
+///     type Error = S::Error;
+///     type Future = S::Future;
+///
+///     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+///         self.0.poll_ready(cx)
+///     }
+///
+///     fn call(&mut self, req: warp::http::Request<B>) -> S::Future {
+///         println!("{} {}", req.method(), req.uri());
+///         self.0.call(req)
+///     }
+/// }
+///
+/// struct LogLayer;
+///
+/// impl<S> Layer<S> for LogLayer {
+///     type Service = Log<S>;
+///
+///     fn layer(&self, inner: S) -> Log<S> {
+///         Log(inner)
+///     }
+/// }
+///
+/// let route = warp::path("hello")
+///     .map(|| "hello")
+///     .with(warp::wrap::from_layer(LogLayer));
+/// ```
+pub fn from_layer<L>(layer: L) -> FromLayer<L> {
+    FromLayer { layer }
+}
+
+/// Turn a [`Wrap`] into a [`Layer`], applying it to a [`Service`].
+///
+/// The service is run as a filter, extracting its response, and the
+/// wrapped filter is served like with [`warp::service`](crate::service()).
+/// Errors of the service reject the request with a [`ServiceError`].
+pub fn into_layer<W>(wrap: W) -> IntoLayer<W> {
+    IntoLayer { wrap }
+}
+
+/// Applies a [`Layer`] to a filter, with [`from_layer`].
+#[derive(Clone, Copy, Debug)]
+pub struct FromLayer<L> {
+    layer: L,
+}
+
+impl<F, L> Wrap<F> for FromLayer<L>
+where
+    F: Filter + Send + Sync + 'static,
+    F::Extract: Reply,
+    F::Error: Into<Rejection>,
+    L: Layer<FilterService<F>>,
+    L::Service: Service<Request> + Clone + Send,
+    <L::Service as Service<Request>>::Response: Reply,
+    <L::Service as Service<Request>>::Error: Into<BoxError>,
+    <L::Service as Service<Request>>::Future: Send,
+{
+    type Wrapped = ServiceFilter<L::Service>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        let service = self.layer.layer(FilterService::new(Arc::new(filter)));
+        ServiceFilter::new(service)
+    }
+}
+
+/// A [`Wrap`] as a [`Layer`], with [`into_layer`].
+#[derive(Clone, Copy, Debug)]
+pub struct IntoLayer<W> {
+    wrap: W,
+}
+
+impl<W, S> Layer<S> for IntoLayer<W>
+where
+    W: Wrap<ServiceFilter<S>>,
+    S: Service<Request> + Clone + Send,
+    S::Response: Reply,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+    <W::Wrapped as FilterBase>::Extract: Reply,
+    <W::Wrapped as FilterBase>::Error: IsReject,
+{
+    type Service = FilteredService<W::Wrapped>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        crate::service(self.wrap.wrap(ServiceFilter::new(service)))
+    }
+}
+
+/// An error used to reject requests when a [`Service`] fails.
+pub struct ServiceError {
+    error: BoxError,
+}
+
+impl ServiceError {
+    pub(crate) fn new(error: BoxError) -> ServiceError {
+        ServiceError { error }
+    }
+
+    /// The error the service failed with.
+    pub fn error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        &*self.error
+    }
+}
+
+impl fmt::Debug for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ServiceError").field(&self.error).finish()
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Service failed: {}", self.error)
+    }
+}
+
+impl StdError for ServiceError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+mod internal {
+    use std::cell::RefCell;
+    use std::error::Error as StdError;
+    use std::fmt;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::{ready, TryFuture};
+    use pin_project::pin_project;
+    use tower_service::Service;
+
+    use super::{BoxError, ServiceError};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, Rejection};
+    use crate::reply::{Reply, Response};
+    use crate::route::{self, Route};
+    use crate::Request;
+
+    // A rejection, passed through tower middleware as an error.
+    #[derive(Debug)]
+    pub struct Rejected(pub(super) Rejection);
+
+    impl fmt::Display for Rejected {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "rejected: {:?}", self.0)
+        }
+    }
+
+    impl StdError for Rejected {}
+
+    pub(super) fn into_rejection(error: BoxError) -> Rejection {
+        match error.downcast::<Rejected>() {
+            Ok(rejected) => rejected.0,
+            Err(error) => reject::known(ServiceError::new(error)),
+        }
+    }
+
+    /// A filter, as the innermost [`Service`] of a [`Layer`](tower_layer::Layer).
+    #[allow(missing_debug_implementations)]
+    pub struct FilterService<F> {
+        filter: Arc<F>,
+    }
+
+    impl<F> FilterService<F> {
+        pub(super) fn new(filter: Arc<F>) -> FilterService<F> {
+            FilterService { filter }
+        }
+    }
+
+    impl<F> Clone for FilterService<F> {
+        fn clone(&self) -> Self {
+            FilterService {
+                filter: self.filter.clone(),
+            }
+        }
+    }
+
+    impl<F> Service<Request> for FilterService<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Response = Response;
+        type Error = Rejected;
+        type Future = FilterServiceFuture<F>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request) -> Self::Future {
+            let route = Route::resume(req);
+            let future = route::set(&route, || self.filter.filter(Internal));
+            FilterServiceFuture { future, route }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct FilterServiceFuture<F: Filter> {
+        #[pin]
+        future: F::Future,
+        route: RefCell<Route>,
+    }
+
+    impl<F> Future for FilterServiceFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        type Output = Result<Response, Rejected>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let future = pin.future;
+            match ready!(route::set(pin.route, || future.try_poll(cx))) {
+                Ok(ex) => Poll::Ready(Ok(ex.into_response())),
+                Err(err) => Poll::Ready(Err(Rejected(err.into()))),
+            }
+        }
+    }
+
+    /// A [`Service`], as a filter extracting its response.
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct ServiceFilter<S> {
+        service: S,
+    }
+
+    impl<S> ServiceFilter<S> {
+        pub(crate) fn new(service: S) -> ServiceFilter<S> {
+            ServiceFilter { service }
+        }
+    }
+
+    impl<S> FilterBase for ServiceFilter<S>
+    where
+        S: Service<Request> + Clone + Send,
+        S::Error: Into<BoxError>,
+        S::Future: Send,
+    {
+        type Extract = (S::Response,);
+        type Error = Rejection;
+        type Future = ServiceFilterFuture<S>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let req = route::with(|route| route.take_resumable_request());
+            ServiceFilterFuture {
+                service: self.service.clone(),
+                req: Some(req),
+                future: None,
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct ServiceFilterFuture<S: Service<Request>> {
+        service: S,
+        // Taken once the service is ready, to call it.
+        req: Option<Request>,
+        #[pin]
+        future: Option<S::Future>,
+    }
+
+    impl<S> Future for ServiceFilterFuture<S>
+    where
+        S: Service<Request>,
+        S::Error: Into<BoxError>,
+    {
+        type Output = Result<(S::Response,), Rejection>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut pin = self.project();
+            if pin.future.is_none() {
+                if let Err(err) = ready!(pin.service.poll_ready(cx)) {
+                    return Poll::Ready(Err(into_rejection(err.into())));
+                }
+                let req = pin.req.take().expect("polled after complete");
+                pin.future.set(Some(pin.service.call(req)));
+            }
+            let future = pin.future.as_pin_mut().expect("future was just set");
+            match ready!(future.poll(cx)) {
+                Ok(res) => Poll::Ready(Ok((res,))),
+                Err(err) => Poll::Ready(Err(into_rejection(err.into()))),
+            }
+        }
+    }
+}
@@ -48,6 +48,14 @@ pub trait Combine<T: HList> {
     fn combine(self, other: T) -> Self::Output;
 }
 
+// Flattens a tuple of tuples into one tuple, like `((a, b), (c,))` into
+// `(a, b, c)`.
+pub trait Flatten {
+    type Output: Tuple;
+
+    fn flatten_tuples(self) -> Self::Output;
+}
+
 pub trait Func<Args> {
     type Output;
 
@@ -77,6 +85,67 @@ where
     }
 }
 
+// ===== impl Flatten =====
+
+impl<T1: Tuple> Flatten for (T1,) {
+    type Output = T1;
+
+    #[inline]
+    fn flatten_tuples(self) -> Self::Output {
+        self.0
+    }
+}
+
+impl<T1: Tuple, T2: Tuple> Flatten for (T1, T2)
+where
+    T1::HList: Combine<T2::HList>,
+{
+    type Output = CombinedTuples<T1, T2>;
+
+    #[inline]
+    fn flatten_tuples(self) -> Self::Output {
+        self.0.combine(self.1)
+    }
+}
+
+impl<T1: Tuple, T2: Tuple, T3: Tuple> Flatten for (T1, T2, T3)
+where
+    T1::HList: Combine<T2::HList>,
+    Combined<T1, T2>: Combine<T3::HList>,
+{
+    type Output = <<Combined<T1, T2> as Combine<T3::HList>>::Output as HList>::Tuple;
+
+    #[inline]
+    fn flatten_tuples(self) -> Self::Output {
+        let (t1, t2, t3) = self;
+        t1.hlist().combine(t2.hlist()).combine(t3.hlist()).flatten()
+    }
+}
+
+impl<T1: Tuple, T2: Tuple, T3: Tuple, T4: Tuple> Flatten for (T1, T2, T3, T4)
+where
+    T1::HList: Combine<T2::HList>,
+    Combined<T1, T2>: Combine<T3::HList>,
+    <Combined<T1, T2> as Combine<T3::HList>>::Output: Combine<T4::HList>,
+{
+    type Output = <<<Combined<T1, T2> as Combine<T3::HList>>::Output as Combine<
+        T4::HList,
+    >>::Output as HList>::Tuple;
+
+    #[inline]
+    fn flatten_tuples(self) -> Self::Output {
+        let (t1, t2, t3, t4) = self;
+        t1.hlist()
+            .combine(t2.hlist())
+            .combine(t3.hlist())
+            .combine(t4.hlist())
+            .flatten()
+    }
+}
+
+// The HList of two tuples combined.
+type Combined<T1, T2> = <<T1 as Tuple>::HList as Combine<<T2 as Tuple>::HList>>::Output;
+
 impl HList for () {
     type Tuple = ();
     #[inline]
@@ -246,5 +315,13 @@ generics! {
     T13,
     T14,
     T15,
-    T16
+    T16,
+    T17,
+    T18,
+    T19,
+    T20,
+    T21,
+    T22,
+    T23,
+    T24
 }
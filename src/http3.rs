@@ -0,0 +1,235 @@
+use std::convert::{Infallible, TryFrom};
+use std::error::Error as StdError;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::{Buf, Bytes, BytesMut};
+use h3::server::{RequestResolver, RequestStream};
+use hyper::body::HttpBody;
+use hyper::service::Service;
+use quinn::crypto::rustls::QuicServerConfig;
+
+use crate::filters::conn::TlsInfo;
+use crate::reply::Response;
+use crate::transport::{ConnInfo, ConnLimit};
+use crate::Request;
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+type H3Conn = h3_quinn::Connection;
+type H3Stream = RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>;
+
+pub(crate) const DEFAULT_MAX_BODY_SIZE: u64 = 2 * 1024 * 1024;
+
+// `new_service` makes the service of each connection, applying the hooks
+// and timeouts of the `Server`, as it does for TCP connections.
+pub(crate) fn bind<M, S>(
+    new_service: M,
+    addr: SocketAddr,
+    mut tls: quinn::rustls::ServerConfig,
+    limit: ConnLimit,
+    header_read_timeout: Option<Duration>,
+    max_body_size: u64,
+) -> Result<(SocketAddr, impl Future<Output = ()> + 'static), BoxError>
+where
+    M: Fn(ConnInfo) -> S + Send + Sync + 'static,
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    if tls.alpn_protocols.is_empty() {
+        tls.alpn_protocols = vec![b"h3".to_vec()];
+    }
+    let crypto = QuicServerConfig::try_from(tls)?;
+    let config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+    let endpoint = quinn::Endpoint::server(config, addr)?;
+    let addr = endpoint.local_addr()?;
+    let new_service = Arc::new(new_service);
+
+    let fut = async move {
+        loop {
+            // Like TCP connections over the limit are left in the backlog,
+            // QUIC ones wait in the endpoint's queue.
+            limit.ready().await;
+            let incoming = match endpoint.accept().await {
+                Some(incoming) => incoming,
+                None => break,
+            };
+            let counted = limit.track_connection();
+            let new_service = new_service.clone();
+            tokio::spawn(async move {
+                let result = serve_connection(
+                    &*new_service,
+                    incoming,
+                    addr,
+                    header_read_timeout,
+                    max_body_size,
+                )
+                .await;
+                if let Err(err) = result {
+                    tracing::debug!("h3 connection error: {}", err);
+                }
+                drop(counted);
+            });
+        }
+    };
+
+    Ok((addr, fut))
+}
+
+async fn serve_connection<M, S>(
+    new_service: &M,
+    incoming: quinn::Incoming,
+    local_addr: SocketAddr,
+    header_read_timeout: Option<Duration>,
+    max_body_size: u64,
+) -> Result<(), BoxError>
+where
+    M: Fn(ConnInfo) -> S,
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    let conn = incoming.await?;
+    let alpn_protocol = conn
+        .handshake_data()
+        .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok())
+        .and_then(|data| data.protocol);
+    let info = ConnInfo::new(
+        Some(local_addr),
+        Some(conn.remote_address()),
+        // QUIC always runs over TLS 1.3.
+        Some(TlsInfo {
+            alpn_protocol,
+            version: Some("TLSv1_3".to_owned()),
+            cipher_suite: None,
+        }),
+    );
+    // The requests of a connection are served concurrently, so they share
+    // its service, which is dropped with the last of them.
+    let service = Arc::new(Mutex::new(new_service(info)));
+    let mut conn = h3::server::Connection::new(h3_quinn::Connection::new(conn)).await?;
+
+    while let Some(resolver) = conn.accept().await? {
+        let service = service.clone();
+        tokio::spawn(async move {
+            let result = serve_request(service, resolver, header_read_timeout, max_body_size);
+            if let Err(err) = result.await {
+                tracing::debug!("h3 request error: {}", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn serve_request<S>(
+    service: Arc<Mutex<S>>,
+    resolver: RequestResolver<H3Conn, Bytes>,
+    header_read_timeout: Option<Duration>,
+    max_body_size: u64,
+) -> Result<(), BoxError>
+where
+    S: Service<Request, Response = Response, Error = Infallible>,
+{
+    let (head, mut stream) = match header_read_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, resolver.resolve_request())
+            .await
+            .map_err(|_| "timed out reading request headers")??,
+        None => resolver.resolve_request().await?,
+    };
+
+    // The filter tree expects a complete `hyper::Body`, so the request body
+    // is buffered before routing, as long as it isn't too large.
+    let body = match read_body(&head, &mut stream, max_body_size).await? {
+        Some(body) => body,
+        None => {
+            tracing::debug!("h3 request body is over {} bytes", max_body_size);
+            // The rest of the body isn't needed to reply.
+            stream.stop_sending(h3::error::Code::H3_NO_ERROR);
+            let res = http1::Response::builder()
+                .status(http1::StatusCode::PAYLOAD_TOO_LARGE)
+                .body(())?;
+            stream.send_response(res).await?;
+            stream.finish().await?;
+            return Ok(());
+        }
+    };
+
+    let req = into_request(head, body)?;
+    // The connection's service is always ready.
+    let fut = service.lock().unwrap().call(req);
+    let res = match fut.await {
+        Ok(res) => res,
+        Err(never) => match never {},
+    };
+
+    let (parts, mut body) = res.into_parts();
+    stream.send_response(from_response(parts)?).await?;
+    while let Some(chunk) = body.data().await {
+        stream.send_data(chunk?).await?;
+    }
+    stream.finish().await?;
+
+    Ok(())
+}
+
+// Reads the whole body, or returns `None` as soon as it's known to be over
+// `max` bytes.
+async fn read_body(
+    head: &http1::Request<()>,
+    stream: &mut H3Stream,
+    max: u64,
+) -> Result<Option<Bytes>, BoxError> {
+    let content_length = head
+        .headers()
+        .get(http1::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+    if content_length.is_some_and(|len| len > max) {
+        return Ok(None);
+    }
+
+    let mut body = BytesMut::new();
+    while let Some(mut chunk) = stream.recv_data().await? {
+        if (body.len() + chunk.remaining()) as u64 > max {
+            return Ok(None);
+        }
+        while chunk.has_remaining() {
+            let bytes = chunk.chunk();
+            let len = bytes.len();
+            body.extend_from_slice(bytes);
+            chunk.advance(len);
+        }
+    }
+    Ok(Some(body.freeze()))
+}
+
+// h3 is built on `http` 1.x, while warp still uses 0.2, so the request head
+// has to be converted piece by piece.
+fn into_request(head: http1::Request<()>, body: Bytes) -> Result<Request, BoxError> {
+    let (parts, ()) = head.into_parts();
+    let mut req = http::Request::builder()
+        .method(parts.method.as_str())
+        .uri(parts.uri.to_string())
+        .version(http::Version::HTTP_3);
+    for (name, value) in &parts.headers {
+        req = req.header(name.as_str(), value.as_bytes());
+    }
+    Ok(req.body(body.into())?)
+}
+
+fn from_response(parts: http::response::Parts) -> Result<http1::Response<()>, BoxError> {
+    let mut res = http1::Response::builder().status(parts.status.as_u16());
+    for (name, value) in &parts.headers {
+        if is_connection_specific(name) {
+            continue;
+        }
+        res = res.header(name.as_str(), value.as_bytes());
+    }
+    Ok(res.body(())?)
+}
+
+fn is_connection_specific(name: &http::header::HeaderName) -> bool {
+    use http::header::{CONNECTION, TRANSFER_ENCODING, UPGRADE};
+
+    name == CONNECTION || name == TRANSFER_ENCODING || name == UPGRADE || name == "keep-alive"
+}
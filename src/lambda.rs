@@ -0,0 +1,355 @@
+//! AWS Lambda
+//!
+//! Run a filter as an AWS Lambda function, behind API Gateway or an
+//! Application Load Balancer, without a TCP listener.
+//!
+//! The events of API Gateway REST APIs (payload format 1.0), HTTP APIs
+//! (payload format 2.0) and function URLs, and of Application Load
+//! Balancers, are turned into requests, and the replies of the filter into
+//! the responses these services expect.
+//!
+//! *This module requires the `"lambda"` feature.*
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::TryFuture;
+use http::header::{HeaderName, HeaderValue, CONTENT_ENCODING, COOKIE, SET_COOKIE};
+use http::{Method, StatusCode};
+use lambda_runtime::LambdaEvent;
+use serde_json::{json, Map, Value};
+use tower_service::Service;
+
+use crate::filter::service::FilteredService;
+use crate::filter::Filter;
+use crate::reject::IsReject;
+use crate::reply::{Reply, Response};
+use crate::Request;
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Run the filter as the handler of a Lambda function, forever.
+///
+/// Returns an error if the Lambda runtime can't be reached, such as when
+/// this isn't running in a Lambda environment.
+///
+/// # Example
+///
+/// ```no_run
+/// use warp::Filter;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///     let routes = warp::path("hello").map(|| "Hello, World!");
+///     warp::lambda::run(routes).await
+/// }
+/// ```
+pub async fn run<F>(filter: F) -> Result<(), BoxError>
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Future: Send,
+    <F::Future as TryFuture>::Ok: Reply,
+    <F::Future as TryFuture>::Error: IsReject,
+{
+    lambda_runtime::run(handler(filter)).await
+}
+
+/// Create a Lambda handler out of the filter, as a [`Service`] of
+/// `lambda_runtime` events.
+///
+/// This is what [`run`] uses, and allows adding `lambda_runtime` layers,
+/// or calling the handler with events in tests.
+///
+/// [`Service`]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+pub fn handler<F>(filter: F) -> Handler<F>
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    <F::Future as TryFuture>::Ok: Reply,
+    <F::Future as TryFuture>::Error: IsReject,
+{
+    Handler {
+        service: crate::service(filter),
+    }
+}
+
+/// A filter as a Lambda handler, returned by [`handler`].
+#[derive(Clone, Copy, Debug)]
+pub struct Handler<F> {
+    service: FilteredService<F>,
+}
+
+/// The future of a [`Handler`], replying to one event.
+pub struct HandlerFuture {
+    inner: Pin<Box<dyn Future<Output = Result<Value, BoxError>> + Send>>,
+}
+
+/// An error for events that don't come from API Gateway or an Application
+/// Load Balancer.
+#[derive(Debug)]
+pub struct UnsupportedEvent {
+    _p: (),
+}
+
+// The events differ in how they carry the request, and so in how they
+// expect the response.
+#[derive(Clone, Copy, Debug)]
+struct Format {
+    // API Gateway HTTP APIs and function URLs use the payload format 2.0.
+    v2: bool,
+    // REST APIs always send multi-value headers, load balancers only if
+    // they're enabled, and then expect them back.
+    multi_value: bool,
+    alb: bool,
+}
+
+impl<F> Service<LambdaEvent<Value>> for Handler<F>
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Future: Send,
+    <F::Future as TryFuture>::Ok: Reply,
+    <F::Future as TryFuture>::Error: IsReject,
+{
+    type Response = Value;
+    type Error = BoxError;
+    type Future = HandlerFuture;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, event: LambdaEvent<Value>) -> Self::Future {
+        let service = self.service.clone();
+        HandlerFuture {
+            inner: Box::pin(async move {
+                let (format, req, remote_addr) = into_request(&event.payload)?;
+                let res = match service.call_with_addr(req, remote_addr).await {
+                    Ok(res) => res,
+                    Err(never) => match never {},
+                };
+                from_response(format, res).await
+            }),
+        }
+    }
+}
+
+impl Future for HandlerFuture {
+    type Output = Result<Value, BoxError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl fmt::Debug for HandlerFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerFuture").finish()
+    }
+}
+
+fn into_request(event: &Value) -> Result<(Format, Request, Option<SocketAddr>), BoxError> {
+    let context = &event["requestContext"];
+    let format = Format {
+        v2: event["version"] == "2.0",
+        multi_value: event["multiValueHeaders"].is_object(),
+        alb: context["elb"].is_object(),
+    };
+    let (method, path, source_ip) = if format.v2 {
+        let http = &context["http"];
+        (&http["method"], &event["rawPath"], &http["sourceIp"])
+    } else if event["httpMethod"].is_string() {
+        (
+            &event["httpMethod"],
+            &event["path"],
+            &context["identity"]["sourceIp"],
+        )
+    } else {
+        return Err(UnsupportedEvent { _p: () }.into());
+    };
+
+    let method = method.as_str().unwrap_or_default().parse::<Method>()?;
+    let mut uri = path.as_str().unwrap_or("/").to_owned();
+    let query = query(format, event);
+    if !query.is_empty() {
+        uri.push('?');
+        uri.push_str(&query);
+    }
+
+    let mut req = http::Request::builder().method(method).uri(uri);
+    for (name, value) in headers(format, event) {
+        let name = HeaderName::from_bytes(name.as_bytes())?;
+        req = req.header(name, HeaderValue::from_str(value)?);
+    }
+    // HTTP APIs move cookies out of the headers.
+    let cookies = event["cookies"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>();
+    if !cookies.is_empty() {
+        req = req.header(COOKIE, HeaderValue::from_str(&cookies.join("; "))?);
+    }
+
+    let body = match event["body"].as_str() {
+        Some(body) if event["isBase64Encoded"] == true => base64::decode(body)?,
+        Some(body) => body.as_bytes().to_vec(),
+        None => Vec::new(),
+    };
+    let req = req.body(body.into())?;
+
+    // The port of the client isn't known.
+    let remote_addr = source_ip
+        .as_str()
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 0));
+
+    Ok((format, req, remote_addr))
+}
+
+fn headers(format: Format, event: &Value) -> Vec<(&str, &str)> {
+    let mut headers = Vec::new();
+    if format.multi_value {
+        for (name, values) in event["multiValueHeaders"].as_object().into_iter().flatten() {
+            for value in values.as_array().into_iter().flatten() {
+                if let Some(value) = value.as_str() {
+                    headers.push((name.as_str(), value));
+                }
+            }
+        }
+    } else {
+        for (name, value) in event["headers"].as_object().into_iter().flatten() {
+            if let Some(value) = value.as_str() {
+                headers.push((name.as_str(), value));
+            }
+        }
+    }
+    headers
+}
+
+fn query(format: Format, event: &Value) -> String {
+    // HTTP APIs pass the query string as it was sent.
+    if format.v2 {
+        return event["rawQueryString"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned();
+    }
+
+    let mut pairs = Vec::new();
+    match event["multiValueQueryStringParameters"].as_object() {
+        Some(params) => {
+            for (name, values) in params {
+                for value in values.as_array().into_iter().flatten() {
+                    if let Some(value) = value.as_str() {
+                        pairs.push((name.as_str(), value));
+                    }
+                }
+            }
+        }
+        None => {
+            for (name, value) in event["queryStringParameters"]
+                .as_object()
+                .into_iter()
+                .flatten()
+            {
+                if let Some(value) = value.as_str() {
+                    pairs.push((name.as_str(), value));
+                }
+            }
+        }
+    }
+
+    // Load balancers pass parameters as they were sent, while REST APIs
+    // decode them.
+    if format.alb {
+        pairs
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&")
+    } else {
+        serde_urlencoded::to_string(pairs).unwrap_or_default()
+    }
+}
+
+async fn from_response(format: Format, res: Response) -> Result<Value, BoxError> {
+    let (parts, body) = res.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+
+    // Bodies that aren't text, or are compressed, are sent in base64.
+    let (body, is_base64_encoded) = match std::str::from_utf8(&body) {
+        Ok(text) if !parts.headers.contains_key(CONTENT_ENCODING) => (text.to_owned(), false),
+        _ => (base64::encode(&body), true),
+    };
+
+    let mut res = Map::new();
+    res.insert("statusCode".into(), parts.status.as_u16().into());
+    if format.alb {
+        res.insert(
+            "statusDescription".into(),
+            status_description(parts.status).into(),
+        );
+    }
+
+    let mut cookies = Vec::new();
+    let mut single = Map::new();
+    let mut multi = Map::<String, Value>::new();
+    for (name, value) in &parts.headers {
+        let value = value.to_str()?;
+        if format.v2 && name == SET_COOKIE {
+            cookies.push(Value::from(value));
+            continue;
+        }
+        multi
+            .entry(name.as_str())
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .expect("header values are an array")
+            .push(value.into());
+        // Repeated headers are joined, except for `Set-Cookie`, which can't
+        // be, so only its last value is kept.
+        match single.get_mut(name.as_str()) {
+            Some(Value::String(joined)) if name != SET_COOKIE => {
+                joined.push_str(", ");
+                joined.push_str(value);
+            }
+            _ => {
+                single.insert(name.as_str().into(), value.into());
+            }
+        }
+    }
+
+    if format.v2 {
+        res.insert("cookies".into(), cookies.into());
+    }
+    if format.multi_value {
+        res.insert("multiValueHeaders".into(), multi.into());
+    } else {
+        res.insert("headers".into(), single.into());
+    }
+    res.insert("body".into(), body.into());
+    res.insert("isBase64Encoded".into(), is_base64_encoded.into());
+    Ok(res.into())
+}
+
+fn status_description(status: StatusCode) -> String {
+    format!(
+        "{} {}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    )
+    .trim_end()
+    .to_owned()
+}
+
+impl fmt::Display for UnsupportedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("event isn't from API Gateway or an Application Load Balancer")
+    }
+}
+
+impl StdError for UnsupportedEvent {}
@@ -85,21 +85,33 @@
 //! [Filter]: trait.Filter.html
 //! [reject]: reject/index.html
 
+#[cfg(feature = "admin")]
+pub mod admin;
+mod clock;
 #[macro_use]
 mod error;
 mod filter;
 pub mod filters;
 mod generic;
+#[cfg(feature = "http3")]
+mod http3;
+#[cfg(feature = "lambda")]
+pub mod lambda;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod redirect;
 pub mod reject;
 pub mod reply;
 mod route;
+pub mod router;
 mod server;
-mod service;
+pub mod service;
 pub mod test;
 #[cfg(feature = "tls")]
 mod tls;
 mod transport;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
 
 pub use self::error::Error;
 pub use self::filter::Filter;
@@ -121,35 +133,113 @@ pub use self::filters::{
     // any() function
     any::any,
     body,
+    cache,
+    // cache() function
+    cache::cache,
+    capture,
+    // capture() function
+    capture::capture,
+    catch_panic,
+    // catch_panic() function
+    catch_panic::catch_panic,
+    circuit_breaker,
+    // circuit_breaker() function
+    circuit_breaker::circuit_breaker,
+    concurrency_limit,
+    // concurrency_limit() function
+    concurrency_limit::concurrency_limit,
+    conn,
     cookie,
     // cookie() function
     cookie::cookie,
     cors,
     // cors() function
     cors::cors,
+    csp,
+    // csp() function
+    csp::csp,
+    etag,
+    // etag() function
+    etag::etag,
     ext,
+    fault,
+    flash,
+    // flash() function
+    flash::flash,
     fs,
     header,
     // header() function
     header::header,
+    health,
     host,
+    inject,
+    // inject() function
+    inject::inject,
+    load_shed,
+    // load_shed() function
+    load_shed::load_shed,
     log,
     // log() function
     log::log,
+    maintenance,
+    // maintenance() function
+    maintenance::maintenance,
+    method,
+    // method() function and shortcuts
     method::{delete, get, head, method, options, patch, post, put},
+    metrics,
+    // metrics() function
+    metrics::metrics,
     path,
-    // path() function and macro
-    path::path,
+    // path() function and macro, and mount()
+    path::{mount, path},
+    precondition,
+    // precondition() function
+    precondition::precondition,
     query,
     // query() function
     query::query,
+    rate_limit,
+    // rate_limit() function
+    rate_limit::rate_limit,
+    request,
+    request_id,
+    // request_id() function
+    request_id::request_id,
+    sample,
+    scheme,
+    // scheme() function
+    scheme::scheme,
+    security_headers,
+    // security_headers() function
+    security_headers::security_headers,
+    server_timing,
+    // server_timing() function
+    server_timing::server_timing,
+    session,
+    // session() function
+    session::session,
+    singleflight,
+    // singleflight() function
+    singleflight::singleflight,
     sse,
+    tap,
+    // tap() function
+    tap::tap,
+    throttle,
+    // throttle() function
+    throttle::throttle,
+    timeout,
+    // timeout() function
+    timeout::timeout,
     trace,
     // trace() function
     trace::trace,
+    version,
+    wrap,
 };
 // ws() function
-pub use self::filter::wrap_fn;
+pub use self::filter::{wrap_fn, Wrap, WrapFn};
 #[cfg(feature = "websocket")]
 #[doc(hidden)]
 pub use self::filters::ws::ws;
@@ -160,10 +250,12 @@ pub use self::redirect::redirect;
 pub use self::reject::{reject, Rejection};
 #[doc(hidden)]
 pub use self::reply::{reply, Reply};
+pub use self::router::{uri_for, Router};
 #[cfg(feature = "tls")]
 pub use self::server::TlsServer;
-pub use self::server::{serve, Server};
+pub use self::server::{serve, Drained, RequestEvent, ResponseEvent, Server, UnhandledError};
 pub use self::service::service;
+pub use headers;
 #[doc(hidden)]
 pub use http;
 #[doc(hidden)]
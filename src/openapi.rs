@@ -0,0 +1,338 @@
+//! OpenAPI documents
+//!
+//! The routes of a [`Router`] can be described with the methods below on
+//! their [`Endpoint`], such as [`Endpoint::json_body`] and
+//! [`Endpoint::response`]. An [`OpenApi`] then generates an [OpenAPI 3]
+//! document from them, with the schemas of the types derived by [schemars],
+//! and serves it with a Swagger UI page.
+//!
+//! *This module requires the `"openapi"` feature.*
+//!
+//! [OpenAPI 3]: https://spec.openapis.org/oas/v3.0.3
+//! [schemars]: https://docs.rs/schemars
+
+use std::fmt;
+
+use http::StatusCode;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use crate::filter::Filter;
+use crate::reject::Rejection;
+use crate::reply::{self, Reply};
+use crate::router::{self, Endpoint, Part, Router};
+
+/// An OpenAPI document, describing the routes of some [`Router`]s.
+///
+/// # Example
+///
+/// ```
+/// use schemars::JsonSchema;
+/// use serde_derive::{Deserialize, Serialize};
+/// use warp::http::{Method, StatusCode};
+/// use warp::openapi::OpenApi;
+/// use warp::router::{Endpoint, Router};
+/// use warp::Filter;
+///
+/// #[derive(Deserialize, Serialize, JsonSchema)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// let router = Router::new().route(
+///     Endpoint::new(Method::POST, "/users")
+///         .named("createUser")
+///         .summary("Create a user")
+///         .json_body::<User>()
+///         .response::<User>(StatusCode::CREATED, "The created user"),
+///     warp::body::json().map(|user: User| warp::reply::json(&user)),
+/// );
+///
+/// let docs = OpenApi::new("Users", "1.0.0").router(&router);
+///
+/// // `GET /openapi.json` and `GET /docs`
+/// let routes = docs.filter().or(router);
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    endpoints: Vec<Endpoint>,
+}
+
+/// The parts of an [`Endpoint`] only used to describe it.
+#[derive(Clone, Default)]
+pub(crate) struct Docs {
+    summary: Option<&'static str>,
+    description: Option<&'static str>,
+    params: Vec<(&'static str, SchemaFn)>,
+    query: Option<SchemaFn>,
+    body: Option<SchemaFn>,
+    responses: Vec<(StatusCode, &'static str, Option<SchemaFn>)>,
+}
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn subschema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+/// Describing routes, for [`OpenApi`] documents.
+///
+/// *These methods require the `"openapi"` feature.*
+impl Endpoint {
+    /// Sets a short summary of what the route does.
+    pub fn summary(mut self, summary: &'static str) -> Endpoint {
+        self.docs.summary = Some(summary);
+        self
+    }
+
+    /// Sets a longer description of the route.
+    pub fn description(mut self, description: &'static str) -> Endpoint {
+        self.docs.description = Some(description);
+        self
+    }
+
+    /// Sets the type of the path parameter `name`.
+    ///
+    /// Path parameters that aren't given a type are described as strings.
+    pub fn param<T: JsonSchema>(mut self, name: &'static str) -> Endpoint {
+        self.docs.params.push((name, subschema::<T>));
+        self
+    }
+
+    /// Describes the query parameters as the fields of `T`, as deserialized
+    /// by [`warp::query`](crate::query()).
+    pub fn query<T: JsonSchema>(mut self) -> Endpoint {
+        self.docs.query = Some(T::json_schema);
+        self
+    }
+
+    /// Describes the request body as JSON of type `T`, as deserialized by
+    /// [`warp::body::json`](crate::body::json).
+    pub fn json_body<T: JsonSchema>(mut self) -> Endpoint {
+        self.docs.body = Some(subschema::<T>);
+        self
+    }
+
+    /// Describes a response with `status`, and a JSON body of type `T`.
+    pub fn response<T: JsonSchema>(
+        mut self,
+        status: StatusCode,
+        description: &'static str,
+    ) -> Endpoint {
+        self.docs
+            .responses
+            .push((status, description, Some(subschema::<T>)));
+        self
+    }
+
+    /// Describes a response with `status`, and no body.
+    pub fn empty_response(mut self, status: StatusCode, description: &'static str) -> Endpoint {
+        self.docs.responses.push((status, description, None));
+        self
+    }
+}
+
+impl OpenApi {
+    /// Creates a document for the API `title`, at `version`.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> OpenApi {
+        OpenApi {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// Sets the description of the API.
+    pub fn description(mut self, description: impl Into<String>) -> OpenApi {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Describes the routes of `router`.
+    ///
+    /// A router mounted under a path prefix should be described with that
+    /// prefix in its templates, for the document to be accurate.
+    pub fn router(mut self, router: &Router) -> OpenApi {
+        self.endpoints.extend(router.routes().cloned());
+        self
+    }
+
+    /// Generates the OpenAPI document, as JSON.
+    pub fn document(&self) -> Value {
+        let mut gen = SchemaSettings::openapi3().into_generator();
+        let mut paths = Map::new();
+        for endpoint in &self.endpoints {
+            let (path, operation) = operation(endpoint, &mut gen);
+            let item = paths.entry(path).or_insert_with(|| json!({}));
+            item[endpoint.method().as_str().to_lowercase()] = operation;
+        }
+
+        let mut info = json!({
+            "title": self.title,
+            "version": self.version,
+        });
+        if let Some(ref description) = self.description {
+            info["description"] = json!(description);
+        }
+
+        json!({
+            "openapi": "3.0.3",
+            "info": info,
+            "paths": paths,
+            "components": {
+                "schemas": gen.take_definitions(),
+            },
+        })
+    }
+
+    /// Create a filter serving the document at `GET /openapi.json`, and a
+    /// Swagger UI page showing it at `GET /docs`.
+    ///
+    /// The Swagger UI scripts are loaded from the unpkg CDN.
+    pub fn filter(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        let document = self.document();
+        let json = crate::path("openapi.json")
+            .and(crate::path::end())
+            .and(crate::get())
+            .map(move || reply::json(&document));
+
+        let docs = crate::path("docs")
+            .and(crate::path::end())
+            .and(crate::get())
+            .map(|| reply::html(SWAGGER_UI));
+
+        json.map(Reply::into_response)
+            .or(docs.map(Reply::into_response))
+            .unify()
+    }
+}
+
+fn operation(endpoint: &Endpoint, gen: &mut SchemaGenerator) -> (String, Value) {
+    let docs = &endpoint.docs;
+    let mut path = String::new();
+    let mut parameters = Vec::new();
+
+    for part in router::parse(endpoint.template()) {
+        path.push('/');
+        let name = match part {
+            Part::Literal(literal) => {
+                path.push_str(literal);
+                continue;
+            }
+            Part::Param(name) => name,
+            Part::Tail => "tail",
+        };
+        path.push('{');
+        path.push_str(name);
+        path.push('}');
+
+        let schema = match docs.params.iter().find(|&&(param, _)| param == name) {
+            Some(&(_, schema)) => to_json(schema(gen)),
+            None => json!({ "type": "string" }),
+        };
+        parameters.push(json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": schema,
+        }));
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+
+    if let Some(query) = docs.query {
+        let object = match query(gen) {
+            Schema::Object(SchemaObject {
+                object: Some(object),
+                ..
+            }) => object,
+            _ => panic!("openapi query type must be a struct: {}", endpoint),
+        };
+        for (name, schema) in object.properties {
+            parameters.push(json!({
+                "name": name,
+                "in": "query",
+                "required": object.required.contains(&name),
+                "schema": to_json(schema),
+            }));
+        }
+    }
+
+    let mut operation = json!({ "parameters": parameters });
+    if let Some(name) = endpoint.name() {
+        operation["operationId"] = json!(name);
+    }
+    if !endpoint.tags().is_empty() {
+        operation["tags"] = json!(endpoint.tags());
+    }
+    if let Some(summary) = docs.summary {
+        operation["summary"] = json!(summary);
+    }
+    if let Some(description) = docs.description {
+        operation["description"] = json!(description);
+    }
+    if let Some(body) = docs.body {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": {
+                "application/json": { "schema": to_json(body(gen)) },
+            },
+        });
+    }
+
+    let mut responses = Map::new();
+    for &(status, description, schema) in &docs.responses {
+        let mut response = json!({ "description": description });
+        if let Some(schema) = schema {
+            response["content"] = json!({
+                "application/json": { "schema": to_json(schema(gen)) },
+            });
+        }
+        responses.insert(status.as_str().to_owned(), response);
+    }
+    if responses.is_empty() {
+        responses.insert("200".to_owned(), json!({ "description": "OK" }));
+    }
+    operation["responses"] = Value::Object(responses);
+
+    (path, operation)
+}
+
+fn to_json(schema: Schema) -> Value {
+    serde_json::to_value(schema).expect("schemas serialize to JSON")
+}
+
+impl fmt::Debug for Docs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Docs")
+            .field("summary", &self.summary)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>API docs</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = function () {
+      SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
@@ -2,10 +2,27 @@
 //!
 //! The types in this module are helpers that implement [`Reply`](Reply), and easy
 //! to use in order to setup redirects.
+//!
+//! Which function to use depends on whether the redirect is permanent, and
+//! whether clients should repeat the request with the same method and body:
+//!
+//! | | Same method | May change to `GET` |
+//! |-|-|-|
+//! | Permanent | [`permanent`] (`308`) | [`redirect`] (`301`) |
+//! | Temporary | [`temporary`] (`307`) | [`found`] (`302`) |
+//!
+//! [`see_other`] (`303`) always has clients follow with a `GET`, such as to
+//! show the result of a form submitted with `POST`.
+
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::fmt;
 
 use http::{header, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 
 use self::sealed::AsLocation;
+use crate::filter::{filter_fn, Filter};
 use crate::reply::{self, Reply};
 
 /// A simple `301` redirect to a different location.
@@ -21,11 +38,7 @@ use crate::reply::{self, Reply};
 ///     });
 /// ```
 pub fn redirect(uri: impl AsLocation) -> impl Reply {
-    reply::with_header(
-        StatusCode::MOVED_PERMANENTLY,
-        header::LOCATION,
-        uri.header_value(),
-    )
+    with_status(StatusCode::MOVED_PERMANENTLY, uri)
 }
 
 /// A simple `307` temporary redirect to a different location.
@@ -41,17 +54,251 @@ pub fn redirect(uri: impl AsLocation) -> impl Reply {
 ///     });
 /// ```
 pub fn temporary(uri: impl AsLocation) -> impl Reply {
-    reply::with_header(
-        StatusCode::TEMPORARY_REDIRECT,
-        header::LOCATION,
-        uri.header_value(),
-    )
+    with_status(StatusCode::TEMPORARY_REDIRECT, uri)
+}
+
+/// A `308` permanent redirect to a different location, which clients
+/// follow with the same method and body.
+///
+/// # Example
+///
+/// ```
+/// use warp::{http::Uri, Filter};
+///
+/// let route = warp::path("v1")
+///     .map(|| {
+///         warp::redirect::permanent(Uri::from_static("/v2"))
+///     });
+/// ```
+pub fn permanent(uri: impl AsLocation) -> impl Reply {
+    with_status(StatusCode::PERMANENT_REDIRECT, uri)
+}
+
+/// A `302` temporary redirect to a different location.
+///
+/// Clients may follow it with a `GET`, whatever the method of the request
+/// was. Use [`temporary`] to keep the method, or [`see_other`] to always
+/// change it to `GET`.
+pub fn found(uri: impl AsLocation) -> impl Reply {
+    with_status(StatusCode::FOUND, uri)
+}
+
+/// A `303` redirect to a different location, which clients follow with a
+/// `GET`.
+///
+/// # Example
+///
+/// ```
+/// use warp::{http::Uri, Filter};
+///
+/// // After creating a post, show it.
+/// let route = warp::post()
+///     .and(warp::path("posts"))
+///     .map(|| {
+///         warp::redirect::see_other(Uri::from_static("/posts/1"))
+///     });
+/// ```
+pub fn see_other(uri: impl AsLocation) -> impl Reply {
+    with_status(StatusCode::SEE_OTHER, uri)
+}
+
+/// Create a `Filter` that replies with a redirect to `uri`, with `status`,
+/// appending the query string of the request.
+///
+/// # Panics
+///
+/// Panics if `status` isn't a `3xx` redirection.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::{StatusCode, Uri};
+/// use warp::Filter;
+///
+/// // GET /v1/search?q=warp -> /v2/search?q=warp
+/// let route = warp::path!("v1" / "search").and(warp::redirect::keep_query(
+///     StatusCode::PERMANENT_REDIRECT,
+///     Uri::from_static("/v2/search"),
+/// ));
+/// ```
+pub fn keep_query(
+    status: StatusCode,
+    uri: impl AsLocation,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    assert!(
+        status.is_redirection(),
+        "keep_query status must be a redirection: {}",
+        status
+    );
+    let location = uri
+        .header_value()
+        .to_str()
+        .expect("locations are visible ASCII")
+        .to_owned();
+    filter_fn(move |route| {
+        let location = match route.query() {
+            Some(query) if !query.is_empty() => {
+                let separator = if location.contains('?') { '&' } else { '?' };
+                format!("{}{}{}", location, separator, query)
+            }
+            _ => location.clone(),
+        };
+        futures::future::ok((with_status(status, Location(location)),))
+    })
+}
+
+fn with_status(status: StatusCode, uri: impl AsLocation) -> impl Reply {
+    reply::with_header(status, header::LOCATION, uri.header_value())
 }
 
+// Percent-encoded in the segments of a `Location`, besides controls.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+// Percent-encoded in the query of a `Location`, besides controls.
+const QUERY: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'`');
+
+/// A location to redirect to, built from path segments and query
+/// parameters that are percent-encoded as needed.
+///
+/// Segments that would change which path is redirected to, such as `..`,
+/// are refused, so segments from clients can't redirect elsewhere.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::StatusCode;
+/// use warp::redirect::Location;
+/// use warp::{Filter, Reply};
+///
+/// let route = warp::path!("old" / "users" / String).map(|name: String| {
+///     // `a b` becomes `/users/a%20b?tab=posts`
+///     match Location::new().segment("users").try_segment(&name) {
+///         Ok(location) => {
+///             let location = location.query("tab", "posts");
+///             warp::redirect::permanent(location).into_response()
+///         }
+///         Err(_) => StatusCode::NOT_FOUND.into_response(),
+///     }
+/// });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location(String);
+
+impl Location {
+    /// Starts a location at the root, `/`.
+    pub fn new() -> Location {
+        Location(String::new())
+    }
+
+    /// Appends a path segment, percent-encoding it, including any `/` or
+    /// `\`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment is empty, `.` or `..`, which can't be encoded.
+    /// Use [`try_segment`](Location::try_segment) for segments from clients.
+    pub fn segment(self, segment: impl fmt::Display) -> Location {
+        self.try_segment(segment)
+            .unwrap_or_else(|err| panic!("Location::segment: {}", err))
+    }
+
+    /// Appends a path segment, percent-encoding it, including any `/` or
+    /// `\`.
+    ///
+    /// Fails if the segment is empty, `.` or `..`: they can't be encoded,
+    /// and would point somewhere else, such as `//host` for an empty one.
+    pub fn try_segment(mut self, segment: impl fmt::Display) -> Result<Location, InvalidSegment> {
+        debug_assert!(!self.0.contains('?'), "segments go before the query");
+        let segment = segment.to_string();
+        if segment.is_empty() || segment == "." || segment == ".." {
+            return Err(InvalidSegment { _p: () });
+        }
+        self.0.push('/');
+        self.0.extend(utf8_percent_encode(&segment, SEGMENT));
+        Ok(self)
+    }
+
+    /// Appends a query parameter, percent-encoding its name and value.
+    pub fn query(mut self, name: &str, value: impl fmt::Display) -> Location {
+        if self.0.contains('?') {
+            self.0.push('&');
+        } else {
+            if self.0.is_empty() {
+                self.0.push('/');
+            }
+            self.0.push('?');
+        }
+        self.0.extend(utf8_percent_encode(name, QUERY));
+        self.0.push('=');
+        let value = value.to_string();
+        self.0.extend(utf8_percent_encode(&value, QUERY));
+        self
+    }
+
+    /// The location, as it's sent in the `Location` header.
+    pub fn as_str(&self) -> &str {
+        if self.0.is_empty() {
+            "/"
+        } else {
+            &self.0
+        }
+    }
+}
+
+impl Default for Location {
+    fn default() -> Location {
+        Location::new()
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error appending a segment to a [`Location`].
+#[derive(Debug)]
+pub struct InvalidSegment {
+    _p: (),
+}
+
+impl fmt::Display for InvalidSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("segment is empty, `.` or `..`")
+    }
+}
+
+impl StdError for InvalidSegment {}
+
 mod sealed {
     use bytes::Bytes;
     use http::{header::HeaderValue, Uri};
 
+    use super::Location;
+
     // These sealed traits are to allow adding possibly new impls so other
     // arguments could be accepted, like maybe just `warp::redirect("/v2")`.
     pub trait AsLocation: Sealed {}
@@ -67,4 +314,13 @@ mod sealed {
             HeaderValue::from_maybe_shared(bytes).expect("Uri is a valid HeaderValue")
         }
     }
+
+    impl AsLocation for Location {}
+
+    impl Sealed for Location {
+        fn header_value(self) -> HeaderValue {
+            let bytes = Bytes::from(self.as_str().to_owned());
+            HeaderValue::from_maybe_shared(bytes).expect("Location is a valid HeaderValue")
+        }
+    }
 }
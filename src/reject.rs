@@ -79,10 +79,20 @@ pub(crate) fn missing_cookie(name: &'static str) -> Rejection {
     known(MissingCookie { name })
 }
 
+// 400 Bad Request
+#[cfg(feature = "cookie-signed")]
+#[inline]
+pub(crate) fn invalid_cookie(name: &'static str) -> Rejection {
+    known(InvalidCookie { name })
+}
+
 // 405 Method Not Allowed
 #[inline]
-pub(crate) fn method_not_allowed() -> Rejection {
-    known(MethodNotAllowed { _p: () })
+pub(crate) fn method_not_allowed(allowed: http::Method, path_matched: bool) -> Rejection {
+    known(MethodNotAllowed {
+        allowed,
+        path_matched,
+    })
 }
 
 // 411 Length Required
@@ -242,11 +252,24 @@ macro_rules! enum_known {
     );
 }
 
+impl Known {
+    // Some rejections tell the client more than the status code.
+    fn headers(&self, headers: &mut http::HeaderMap) {
+        match *self {
+            Known::RateLimited(ref e) => e.headers(headers),
+            Known::Overloaded(ref e) => e.headers(headers),
+            Known::CircuitOpen(ref e) => e.headers(headers),
+            _ => (),
+        }
+    }
+}
+
 enum_known! {
     MethodNotAllowed(MethodNotAllowed),
     InvalidHeader(InvalidHeader),
     MissingHeader(MissingHeader),
     MissingCookie(MissingCookie),
+    InvalidCookie(InvalidCookie),
     InvalidQuery(InvalidQuery),
     LengthRequired(LengthRequired),
     PayloadTooLarge(PayloadTooLarge),
@@ -256,10 +279,22 @@ enum_known! {
     BodyReadError(crate::body::BodyReadError),
     BodyDeserializeError(crate::body::BodyDeserializeError),
     CorsForbidden(crate::cors::CorsForbidden),
+    AddressForbidden(crate::addr::AddressForbidden),
     #[cfg(feature = "websocket")]
     MissingConnectionUpgrade(crate::ws::MissingConnectionUpgrade),
     MissingExtension(crate::ext::MissingExtension),
     BodyConsumedMultipleTimes(crate::body::BodyConsumedMultipleTimes),
+    TimedOut(crate::timeout::TimedOut),
+    ConcurrencyLimited(crate::concurrency_limit::ConcurrencyLimited),
+    Overloaded(crate::load_shed::Overloaded),
+    CircuitOpen(crate::circuit_breaker::CircuitOpen),
+    Panicked(crate::catch_panic::Panicked),
+    ServiceError(crate::wrap::ServiceError),
+    RateLimited(crate::rate_limit::RateLimited),
+    PreconditionFailed(crate::precondition::PreconditionFailed),
+    SessionError(crate::session::SessionError),
+    TooManySegments(crate::path::TooManySegments),
+    SegmentTooLong(crate::path::SegmentTooLong),
 }
 
 impl Rejection {
@@ -301,6 +336,54 @@ impl Rejection {
         None
     }
 
+    /// Returns true if a filter matched the whole path of the request, but
+    /// not its method, so the request should be answered with a `405 Method
+    /// Not Allowed` rather than a `404 Not Found`.
+    ///
+    /// This is only known of method filters checked after the path filters
+    /// of their route, such as `warp::path!("users").and(warp::get())`, and
+    /// of [`Router`](crate::Router) routes. See
+    /// [`MethodNotAllowed::path_matched`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::http::StatusCode;
+    /// use warp::{Filter, Rejection, Reply};
+    ///
+    /// async fn recover(err: Rejection) -> Result<impl Reply, Rejection> {
+    ///     if err.is_method_not_allowed() {
+    ///         let allowed = format!("try {:?}", err.allowed_methods());
+    ///         Ok(warp::reply::with_status(allowed, StatusCode::METHOD_NOT_ALLOWED))
+    ///     } else {
+    ///         Err(err)
+    ///     }
+    /// }
+    ///
+    /// let users = warp::path!("users").and(warp::get()).map(|| "users");
+    /// let posts = warp::path!("posts").and(warp::post()).map(|| "posted");
+    /// let routes = users.or(posts).recover(recover);
+    /// ```
+    pub fn is_method_not_allowed(&self) -> bool {
+        match self.reason {
+            Reason::Other(ref rejections) => rejections.path_matched(),
+            Reason::NotFound => false,
+        }
+    }
+
+    /// The methods that the method filters that rejected the request would
+    /// have allowed.
+    ///
+    /// If some of them [matched the path](Rejection::is_method_not_allowed),
+    /// only their methods are returned.
+    pub fn allowed_methods(&self) -> Vec<http::Method> {
+        let mut methods = Vec::new();
+        if let Reason::Other(ref rejections) = self.reason {
+            rejections.allowed_methods(&mut methods, rejections.path_matched());
+        }
+        methods
+    }
+
     /// Returns true if this Rejection was made via `warp::reject::not_found`.
     ///
     /// # Example
@@ -358,9 +441,27 @@ impl IsReject for Rejection {
                 *res.status_mut() = StatusCode::NOT_FOUND;
                 res
             }
-            Reason::Other(ref other) => other.into_response(),
+            Reason::Other(ref other) => {
+                let mut res = other.into_response();
+                if res.status() == StatusCode::METHOD_NOT_ALLOWED {
+                    let allowed = self
+                        .allowed_methods()
+                        .iter()
+                        .map(http::Method::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if let Ok(allowed) = HeaderValue::from_str(&allowed) {
+                        res.headers_mut().insert(http::header::ALLOW, allowed);
+                    }
+                }
+                res
+            }
         }
     }
+
+    fn as_rejection(&self) -> Option<&Rejection> {
+        Some(self)
+    }
 }
 
 impl fmt::Debug for Rejection {
@@ -397,18 +498,32 @@ impl Rejections {
                 Known::InvalidHeader(_)
                 | Known::MissingHeader(_)
                 | Known::MissingCookie(_)
+                | Known::InvalidCookie(_)
                 | Known::InvalidQuery(_)
                 | Known::BodyReadError(_)
-                | Known::BodyDeserializeError(_) => StatusCode::BAD_REQUEST,
+                | Known::BodyDeserializeError(_)
+                | Known::SegmentTooLong(_) => StatusCode::BAD_REQUEST,
                 #[cfg(feature = "websocket")]
                 Known::MissingConnectionUpgrade(_) => StatusCode::BAD_REQUEST,
                 Known::LengthRequired(_) => StatusCode::LENGTH_REQUIRED,
+                Known::TooManySegments(_) => StatusCode::URI_TOO_LONG,
                 Known::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
                 Known::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                Known::FilePermissionError(_) | Known::CorsForbidden(_) => StatusCode::FORBIDDEN,
+                Known::FilePermissionError(_)
+                | Known::CorsForbidden(_)
+                | Known::AddressForbidden(_) => StatusCode::FORBIDDEN,
                 Known::FileOpenError(_)
                 | Known::MissingExtension(_)
-                | Known::BodyConsumedMultipleTimes(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                | Known::BodyConsumedMultipleTimes(_)
+                | Known::Panicked(_)
+                | Known::ServiceError(_)
+                | Known::SessionError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                Known::TimedOut(ref t) => t.status(),
+                Known::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+                Known::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+                Known::ConcurrencyLimited(_) | Known::Overloaded(_) | Known::CircuitOpen(_) => {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
             },
             Rejections::Custom(..) => StatusCode::INTERNAL_SERVER_ERROR,
             Rejections::Combined(ref a, ref b) => preferred(a, b).status(),
@@ -424,6 +539,7 @@ impl Rejections {
                     CONTENT_TYPE,
                     HeaderValue::from_static("text/plain; charset=utf-8"),
                 );
+                e.headers(res.headers_mut());
                 res
             }
             Rejections::Custom(ref e) => {
@@ -452,6 +568,30 @@ impl Rejections {
         }
     }
 
+    fn allowed_methods(&self, methods: &mut Vec<http::Method>, path_matched: bool) {
+        match *self {
+            Rejections::Known(Known::MethodNotAllowed(ref e)) => {
+                if (e.path_matched || !path_matched) && !methods.contains(e.allowed()) {
+                    methods.push(e.allowed().clone());
+                }
+            }
+            Rejections::Known(_) | Rejections::Custom(_) => (),
+            Rejections::Combined(ref a, ref b) => {
+                a.allowed_methods(methods, path_matched);
+                b.allowed_methods(methods, path_matched);
+            }
+        }
+    }
+
+    // Whether any method filter rejected a request whose path it matched.
+    fn path_matched(&self) -> bool {
+        match *self {
+            Rejections::Known(Known::MethodNotAllowed(ref e)) => e.path_matched,
+            Rejections::Known(_) | Rejections::Custom(_) => false,
+            Rejections::Combined(ref a, ref b) => a.path_matched() || b.path_matched(),
+        }
+    }
+
     fn debug_list(&self, f: &mut fmt::DebugList<'_, '_>) {
         match *self {
             Rejections::Known(ref e) => {
@@ -471,12 +611,20 @@ impl Rejections {
 fn preferred<'a>(a: &'a Rejections, b: &'a Rejections) -> &'a Rejections {
     // Compare status codes, with this priority:
     // - NOT_FOUND is lowest
-    // - METHOD_NOT_ALLOWED is second
+    // - METHOD_NOT_ALLOWED is second, preferring the ones that matched the
+    //   path, whose `Allow` methods are accurate
     // - if one status code is greater than the other
     // - otherwise, prefer A...
     match (a.status(), b.status()) {
         (_, StatusCode::NOT_FOUND) => a,
         (StatusCode::NOT_FOUND, _) => b,
+        (StatusCode::METHOD_NOT_ALLOWED, StatusCode::METHOD_NOT_ALLOWED) => {
+            if b.path_matched() && !a.path_matched() {
+                b
+            } else {
+                a
+            }
+        }
         (_, StatusCode::METHOD_NOT_ALLOWED) => a,
         (StatusCode::METHOD_NOT_ALLOWED, _) => b,
         (sa, sb) if sa < sb => b,
@@ -489,11 +637,39 @@ unit_error! {
     pub InvalidQuery: "Invalid query string"
 }
 
-unit_error! {
-    /// HTTP method not allowed
-    pub MethodNotAllowed: "HTTP method not allowed"
+/// HTTP method not allowed
+#[derive(Debug)]
+pub struct MethodNotAllowed {
+    allowed: http::Method,
+    path_matched: bool,
+}
+
+impl MethodNotAllowed {
+    /// The method the rejecting filter required.
+    pub fn allowed(&self) -> &http::Method {
+        &self.allowed
+    }
+
+    /// Returns true if the whole path of the request was matched when the
+    /// method was checked, meaning the path exists under the allowed
+    /// method.
+    ///
+    /// A method filter checked before the path filters of its route, like
+    /// `warp::get().and(warp::path("users"))`, can't know whether the path
+    /// would have matched, and returns `false`.
+    pub fn path_matched(&self) -> bool {
+        self.path_matched
+    }
+}
+
+impl ::std::fmt::Display for MethodNotAllowed {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str("HTTP method not allowed")
+    }
 }
 
+impl StdError for MethodNotAllowed {}
+
 unit_error! {
     /// A content-length header is required
     pub LengthRequired: "A content-length header is required"
@@ -572,6 +748,27 @@ impl ::std::fmt::Display for MissingCookie {
 
 impl StdError for MissingCookie {}
 
+/// Invalid cookie, such as a signed cookie with a bad signature
+#[derive(Debug)]
+pub struct InvalidCookie {
+    name: &'static str,
+}
+
+impl InvalidCookie {
+    /// Retrieve the name of the cookie that was invalid
+    pub fn name(&self) -> &str {
+        self.name
+    }
+}
+
+impl ::std::fmt::Display for InvalidCookie {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "Invalid request cookie {:?}", self.name)
+    }
+}
+
+impl StdError for InvalidCookie {}
+
 mod sealed {
     use super::{Reason, Rejection, Rejections};
     use http::StatusCode;
@@ -583,6 +780,10 @@ mod sealed {
     pub trait IsReject: fmt::Debug + Send + Sync {
         fn status(&self) -> StatusCode;
         fn into_response(&self) -> crate::reply::Response;
+
+        fn as_rejection(&self) -> Option<&Rejection> {
+            None
+        }
     }
 
     fn _assert_object_safe() {
@@ -688,7 +889,7 @@ mod tests {
     fn rejection_status() {
         assert_eq!(not_found().status(), StatusCode::NOT_FOUND);
         assert_eq!(
-            method_not_allowed().status(),
+            method_not_allowed(http::Method::GET, true).status(),
             StatusCode::METHOD_NOT_ALLOWED
         );
         assert_eq!(length_required().status(), StatusCode::LENGTH_REQUIRED);
@@ -777,7 +978,7 @@ mod tests {
 
         assert_eq!(rej.find::<Left>(), Some(&Left));
 
-        let rej = rej.combine(method_not_allowed());
+        let rej = rej.combine(method_not_allowed(http::Method::GET, false));
 
         assert_eq!(rej.find::<Left>(), Some(&Left));
         assert!(rej.find::<MethodNotAllowed>().is_some(), "MethodNotAllowed");
@@ -37,9 +37,12 @@ use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::error::Error as StdError;
 use std::fmt;
+use std::time::{Duration, UNIX_EPOCH};
 
+use crate::filters::cookie::Cookie;
+use crate::filters::header::LanguageTag;
 use crate::generic::{Either, One};
-use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use http::header::{HeaderName, HeaderValue, CONTENT_LANGUAGE, CONTENT_TYPE, SET_COOKIE, VARY};
 use http::StatusCode;
 use hyper::Body;
 use serde::Serialize;
@@ -392,6 +395,205 @@ impl<T: Reply> Reply for WithHeader<T> {
     }
 }
 
+/// Wrap an `impl Reply` to set a cookie.
+///
+/// Other cookies set by the reply are kept. If the name or value of the
+/// cookie aren't valid, it's not set, and an error is logged.
+///
+/// # Example
+///
+/// ```
+/// use warp::cookie::{Cookie, SameSite};
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .map(|reply| {
+///         let cookie = Cookie::new("theme", "dark")
+///             .path("/")
+///             .same_site(SameSite::Lax);
+///         warp::reply::with_cookie(reply, cookie)
+///     });
+/// ```
+pub fn with_cookie<T: Reply>(reply: T, cookie: Cookie) -> WithCookie<T> {
+    WithCookie {
+        cookie: cookie.header_value(),
+        reply,
+    }
+}
+
+/// Wrap an `impl Reply` to remove the cookie named `name`, set for `path`,
+/// from the client.
+///
+/// The cookie is replaced with an empty one that has already expired. The
+/// path must be the one it was set with, or the client keeps it.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let logout = warp::path("logout")
+///     .map(warp::reply)
+///     .map(|reply| warp::reply::remove_cookie(reply, "session", "/"));
+/// ```
+pub fn remove_cookie<T: Reply>(reply: T, name: &str, path: &str) -> WithCookie<T> {
+    let cookie = Cookie::new(name, "")
+        .path(path)
+        .max_age(Duration::from_secs(0))
+        .expires(UNIX_EPOCH);
+    with_cookie(reply, cookie)
+}
+
+/// Wrap an `impl Reply` to set a cookie signed with `key`, which the
+/// [`cookie::signed`](crate::cookie::signed) filter verifies.
+///
+/// The value is readable by the client, but it can't be changed without
+/// the filter rejecting it. The cookie is set with `Path=/` and `HttpOnly`.
+///
+/// *This function requires the `"cookie-signed"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let key = warp::cookie::Key::generate();
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .map(move |reply| {
+///         warp::reply::with_signed_cookie(reply, "theme", "dark", &key)
+///     });
+/// ```
+#[cfg(feature = "cookie-signed")]
+pub fn with_signed_cookie<T: Reply>(
+    reply: T,
+    name: &str,
+    value: &str,
+    key: &crate::cookie::Key,
+) -> WithCookie<T> {
+    let cookie = Cookie::new(name, key.sign(name, value))
+        .path("/")
+        .http_only(true);
+    with_cookie(reply, cookie)
+}
+
+/// Wrap an `impl Reply` to set a cookie holding `value`, serialized and
+/// encrypted with `key`, which the [`cookie::private`](crate::cookie::private)
+/// filter decrypts.
+///
+/// The value can't be read or changed by the client. The cookie is set with
+/// `Path=/` and `HttpOnly`.
+///
+/// *This function requires the `"cookie-signed"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let key = warp::cookie::Key::generate();
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .map(move |reply| {
+///         warp::reply::with_private_cookie(reply, "visits", &vec![1, 2, 3], &key)
+///     });
+/// ```
+#[cfg(feature = "cookie-signed")]
+pub fn with_private_cookie<T: Reply, V: Serialize>(
+    reply: T,
+    name: &str,
+    value: &V,
+    key: &crate::cookie::Key,
+) -> WithCookie<T> {
+    match serde_json::to_vec(value) {
+        Ok(value) => {
+            let cookie = Cookie::new(name, key.encrypt(name, &value))
+                .path("/")
+                .http_only(true);
+            with_cookie(reply, cookie)
+        }
+        Err(err) => {
+            tracing::error!("with_private_cookie serialize error: {}", err);
+            WithCookie {
+                cookie: None,
+                reply,
+            }
+        }
+    }
+}
+
+/// Wraps an `impl Reply` and adds a `Set-Cookie` header when rendering,
+/// keeping any others.
+///
+/// Returned by `warp::reply::with_cookie` and the other cookie helpers.
+#[derive(Debug)]
+pub struct WithCookie<T> {
+    cookie: Option<HeaderValue>,
+    reply: T,
+}
+
+impl<T: Reply> Reply for WithCookie<T> {
+    fn into_response(self) -> Response {
+        let mut res = self.reply.into_response();
+        if let Some(cookie) = self.cookie {
+            res.headers_mut().append(SET_COOKIE, cookie);
+        }
+        res
+    }
+}
+
+/// Wrap an `impl Reply` to set its `Content-Language` to `lang`.
+///
+/// `Vary: accept-language` is added too, so caches keep a reply per
+/// language, when it was negotiated with
+/// [`header::accept_language`](crate::header::accept_language).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::header::LanguageTag;
+///
+/// let route = warp::any()
+///     .map(|| {
+///         let lang = LanguageTag::from_static("de");
+///         warp::reply::with_content_language("Hallo", &lang)
+///     });
+/// ```
+pub fn with_content_language<T: Reply>(reply: T, lang: &LanguageTag) -> WithContentLanguage<T> {
+    let lang = HeaderValue::from_str(lang.as_str()).expect("language tags are valid header values");
+    WithContentLanguage { lang, reply }
+}
+
+/// Wraps an `impl Reply` and sets its `Content-Language` when rendering.
+///
+/// Returned by `warp::reply::with_content_language`.
+#[derive(Debug)]
+pub struct WithContentLanguage<T> {
+    lang: HeaderValue,
+    reply: T,
+}
+
+impl<T: Reply> Reply for WithContentLanguage<T> {
+    fn into_response(self) -> Response {
+        let mut res = self.reply.into_response();
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_LANGUAGE, self.lang);
+        let varies = headers.get_all(VARY).iter().any(|value| {
+            value.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .any(|name| name.trim().eq_ignore_ascii_case("accept-language"))
+            })
+        });
+        if !varies {
+            headers.append(VARY, HeaderValue::from_static("accept-language"));
+        }
+        res
+    }
+}
+
 impl<T: Send> Reply for ::http::Response<T>
 where
     Body: From<T>,
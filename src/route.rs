@@ -34,6 +34,33 @@ pub(crate) struct Route {
     remote_addr: Option<SocketAddr>,
     req: Request,
     segments_index: usize,
+    path_matched: bool,
+    template: Vec<(usize, Segment)>,
+    ignore_case: bool,
+}
+
+/// A path segment matched by a path filter, to build the route template.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Segment {
+    /// A literal, found in the request path at this range.
+    Literal(usize, usize),
+    /// A parameter, with the name of its type.
+    Param(&'static str),
+    /// The rest of the path.
+    Tail,
+}
+
+// Where matching a request stopped, when it was moved out of its route with
+// `take_resumable_request`, so a route made again from it can carry on from there.
+#[derive(Clone, Debug)]
+struct Resume {
+    path: String,
+    remote_addr: Option<SocketAddr>,
+    segments_index: usize,
+    path_matched: bool,
+    template: Vec<(usize, Segment)>,
+    ignore_case: bool,
+    body_taken: bool,
 }
 
 #[derive(Debug)]
@@ -56,13 +83,101 @@ impl Route {
             remote_addr,
             req,
             segments_index,
+            path_matched: false,
+            template: Vec::new(),
+            ignore_case: false,
+        })
+    }
+
+    /// Copy the head of the request into a new route, at the same point of
+    /// matching, so filters can run again outside of the original request.
+    ///
+    /// The body and extensions are not copied.
+    pub(crate) fn fork(&self) -> RefCell<Route> {
+        let mut req = Request::new(Body::empty());
+        *req.method_mut() = self.req.method().clone();
+        *req.uri_mut() = self.req.uri().clone();
+        *req.version_mut() = self.req.version();
+        *req.headers_mut() = self.req.headers().clone();
+
+        RefCell::new(Route {
+            body: BodyState::Ready,
+            remote_addr: self.remote_addr,
+            req,
+            segments_index: self.segments_index,
+            path_matched: self.path_matched,
+            template: self.template.clone(),
+            ignore_case: self.ignore_case,
+        })
+    }
+
+    /// Make a route for a request moved out of another one with
+    /// `take_resumable_request`, at the same point of matching.
+    ///
+    /// If the path was changed in between, matching starts over.
+    pub(crate) fn resume(mut req: Request) -> RefCell<Route> {
+        let resume = match req.extensions_mut().remove::<Resume>() {
+            Some(resume) if resume.path == req.uri().path() => resume,
+            Some(resume) => return Route::new(req, resume.remote_addr),
+            None => return Route::new(req, None),
+        };
+
+        RefCell::new(Route {
+            body: if resume.body_taken {
+                BodyState::Taken
+            } else {
+                BodyState::Ready
+            },
+            remote_addr: resume.remote_addr,
+            req,
+            segments_index: resume.segments_index,
+            path_matched: resume.path_matched,
+            template: resume.template,
+            ignore_case: resume.ignore_case,
         })
     }
 
+    /// Move the request out of the route, to hand it to something else.
+    ///
+    /// The head of the request is copied, but its extensions and body are
+    /// taken, so they're gone for any filters that run afterwards. If the
+    /// body was already taken, it's empty.
+    pub(crate) fn take_request(&mut self) -> Request {
+        let mut req = Request::new(self.take_body().unwrap_or_else(Body::empty));
+        *req.method_mut() = self.req.method().clone();
+        *req.uri_mut() = self.req.uri().clone();
+        *req.version_mut() = self.req.version();
+        *req.headers_mut() = self.req.headers().clone();
+        *req.extensions_mut() = mem::take(self.req.extensions_mut());
+        req
+    }
+
+    /// Like `take_request`, but noting how far matching got, for a route
+    /// made from the request with `resume` to carry on from there, like
+    /// when it's passed through a `tower::Service`.
+    pub(crate) fn take_resumable_request(&mut self) -> Request {
+        let body_taken = matches!(self.body, BodyState::Taken);
+        let mut req = self.take_request();
+        req.extensions_mut().insert(Resume {
+            path: self.full_path().to_owned(),
+            remote_addr: self.remote_addr,
+            segments_index: self.segments_index,
+            path_matched: self.path_matched,
+            template: self.template.clone(),
+            ignore_case: self.ignore_case,
+            body_taken,
+        });
+        req
+    }
+
     pub(crate) fn method(&self) -> &http::Method {
         self.req.method()
     }
 
+    pub(crate) fn set_method(&mut self, method: http::Method) {
+        *self.req.method_mut() = method;
+    }
+
     pub(crate) fn headers(&self) -> &http::HeaderMap {
         self.req.headers()
     }
@@ -75,7 +190,6 @@ impl Route {
         self.req.extensions()
     }
 
-    #[cfg(feature = "websocket")]
     pub(crate) fn extensions_mut(&mut self) -> &mut http::Extensions {
         self.req.extensions_mut()
     }
@@ -84,10 +198,47 @@ impl Route {
         self.req.uri()
     }
 
+    /// The authority the request is for, from the target URI, or else the
+    /// `Host` header, lowercased.
+    pub(crate) fn authority(&self) -> Option<String> {
+        let authority = match self.req.uri().authority() {
+            Some(authority) => authority.as_str(),
+            None => self.req.headers().get(http::header::HOST)?.to_str().ok()?,
+        };
+        Some(authority.to_ascii_lowercase())
+    }
+
     pub(crate) fn path(&self) -> &str {
         &self.req.uri().path()[self.segments_index..]
     }
 
+    /// Replaces the path of the request, keeping its query. The matched
+    /// part of the path must not change.
+    pub(crate) fn set_path(&mut self, path: &str) {
+        debug_assert_eq!(
+            &path[..self.segments_index.min(path.len())],
+            &self.full_path()[..self.segments_index],
+            "set_path must keep the matched path"
+        );
+        let path_and_query = match self.req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path.to_owned(),
+        };
+        let mut parts = self.req.uri().clone().into_parts();
+        match path_and_query.parse() {
+            Ok(path_and_query) => {
+                parts.path_and_query = Some(path_and_query);
+                if let Ok(uri) = http::Uri::from_parts(parts) {
+                    *self.req.uri_mut() = uri;
+                }
+            }
+            Err(err) => tracing::debug!("invalid rewritten path {:?}: {}", path, err),
+        }
+        if !self.path().is_empty() {
+            self.path_matched = false;
+        }
+    }
+
     pub(crate) fn full_path(&self) -> &str {
         self.req.uri().path()
     }
@@ -104,6 +255,28 @@ impl Route {
             debug_assert_eq!(path.as_bytes()[index], b'/');
             self.segments_index = index + 1;
         }
+        if self.path().is_empty() {
+            self.path_matched = true;
+        }
+    }
+
+    /// Whether path filters have matched the whole path, so that a method
+    /// filter after them knows the path exists.
+    pub(crate) fn path_matched(&self) -> bool {
+        self.path_matched
+    }
+
+    pub(crate) fn set_path_matched(&mut self, matched: bool) {
+        self.path_matched = matched;
+    }
+
+    /// Whether path literals are matched ignoring ASCII case.
+    pub(crate) fn ignore_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    pub(crate) fn set_ignore_case(&mut self, ignore_case: bool) {
+        self.ignore_case = ignore_case;
     }
 
     pub(crate) fn query(&self) -> Option<&str> {
@@ -121,7 +294,38 @@ impl Route {
             self.segments_index,
             index,
         );
+        if index < self.segments_index {
+            self.path_matched = false;
+        }
         self.segments_index = index;
+        self.template.retain(|&(start, _)| start < index);
+    }
+
+    /// Records that the segment starting at `start` was matched.
+    pub(crate) fn push_template(&mut self, start: usize, segment: Segment) {
+        self.template.push((start, segment));
+    }
+
+    /// The template of the path matched so far, such as `/users/{u32}`.
+    pub(crate) fn template(&self) -> Option<String> {
+        if self.template.is_empty() {
+            return None;
+        }
+        let path = self.full_path();
+        let mut template = String::new();
+        for &(_, segment) in &self.template {
+            template.push('/');
+            match segment {
+                Segment::Literal(start, end) => template.push_str(&path[start..end]),
+                Segment::Param(name) => {
+                    template.push('{');
+                    template.push_str(name);
+                    template.push('}');
+                }
+                Segment::Tail => template.push('*'),
+            }
+        }
+        Some(template)
     }
 
     pub(crate) fn remote_addr(&self) -> Option<SocketAddr> {
@@ -138,4 +342,9 @@ impl Route {
             BodyState::Taken => None,
         }
     }
+
+    pub(crate) fn restore_body(&mut self, body: Body) {
+        *self.req.body_mut() = body;
+        self.body = BodyState::Ready;
+    }
 }
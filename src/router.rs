@@ -0,0 +1,799 @@
+//! Routers that know their routes.
+//!
+//! Filters combined with [`or`](crate::Filter::or) can't be inspected: the
+//! methods and paths they match are only known by running them. A
+//! [`Router`] instead matches the method and path template of each route
+//! itself, so it can list its routes at runtime, for example to show them
+//! on an admin page, or to find the methods allowed on a path.
+//!
+//! Named routes and [patterns](crate::path::Pattern::name) can also be
+//! turned back into paths, with [`uri_for`] or [`Router::uri_for`], so links
+//! and `Location` headers follow the templates they point to.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use futures::future::{self, BoxFuture};
+use http::Method;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::filter::{filter_fn_one, Filter, FilterBase, Internal, One};
+use crate::reject::{self, CombineRejection, Rejection};
+use crate::reply::{Reply, Response};
+use crate::route::{self, Route, Segment};
+
+/// A set of routes, each matching a method and a path template.
+///
+/// A `Router` is a [`Filter`] itself, replying with the first of its routes
+/// that matches the request and doesn't reject it. Unlike filters combined
+/// with [`or`](crate::Filter::or), the order routes are added in doesn't
+/// matter: the most specific templates are tried first, so `/users/me` is
+/// tried before `/users/{id}`, whichever was added first. Going through the
+/// segments of two templates, a literal is more specific than a parameter,
+/// which is more specific than a `*`. Routes that are as specific as each
+/// other are tried in the order they were added, unless their
+/// [priority](Endpoint::prioritized) differs.
+///
+/// Path templates are made of segments separated by `/`. A segment is
+/// either a literal, a `{name}` parameter matching any one segment, or, as
+/// the last segment, `*` matching the rest of the path. The values of the
+/// parameters are extracted with [`param`] and [`params`], and the rest of
+/// the path with [`path::tail`](crate::path::tail).
+///
+/// If the path of a request matches a route, but its method doesn't match
+/// any such route, the request is rejected with a `405 Method Not Allowed`,
+/// listing the allowed methods.
+///
+/// The templates are kept in a tree of their segments, so the routes a path
+/// matches are found by walking down the tree with the segments of the
+/// path, instead of trying every route in turn. Routers with hundreds of
+/// routes are as quick to dispatch as small ones, and compile much faster
+/// than as many filters combined with `or`.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::Method;
+/// use warp::router::{Endpoint, Router};
+/// use warp::Filter;
+///
+/// let router = Router::new()
+///     .get("/users", warp::any().map(|| "all users"))
+///     .route(
+///         Endpoint::new(Method::GET, "/users/{id}").named("user").tagged("users"),
+///         warp::router::param::<u32>("id").map(|id| format!("user #{}", id)),
+///     )
+///     .delete(
+///         "/users/{id}",
+///         warp::router::param::<u32>("id").map(|id| format!("deleted user #{}", id)),
+///     );
+///
+/// for endpoint in router.routes() {
+///     println!("{} {}", endpoint.method(), endpoint.template());
+/// }
+///
+/// assert_eq!(router.allowed_methods("/users/7"), [Method::GET, Method::DELETE]);
+///
+/// let routes = warp::path("api").and(router);
+/// ```
+#[derive(Clone, Default)]
+pub struct Router {
+    routes: Arc<Vec<Entry>>,
+    // Built from `routes` when first needed, after routes were added.
+    tree: Arc<OnceLock<Node>>,
+}
+
+/// The method, path template, and annotations of a route in a [`Router`].
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    method: Method,
+    template: &'static str,
+    name: Option<&'static str>,
+    tags: Vec<&'static str>,
+    priority: i32,
+    #[cfg(feature = "openapi")]
+    pub(crate) docs: crate::openapi::Docs,
+}
+
+/// The parameters of the path templates that matched a request, extracted
+/// with [`params`].
+#[derive(Clone, Debug, Default)]
+pub struct Params {
+    params: Vec<Param>,
+}
+
+#[derive(Clone, Debug)]
+struct Param {
+    // Where the segment starts in the path, to forget the parameters of
+    // branches that were matched and then rejected.
+    start: usize,
+    name: &'static str,
+    value: String,
+}
+
+type Handler = Arc<dyn Fn() -> BoxFuture<'static, Result<Response, Rejection>> + Send + Sync>;
+
+#[derive(Clone)]
+struct Entry {
+    endpoint: Endpoint,
+    parts: Vec<Part>,
+    handler: Handler,
+}
+
+// A segment of the templates of a router, with the segments that can follow
+// it. The routes are referred to by their index in `Router::routes`.
+#[derive(Default)]
+struct Node {
+    literals: HashMap<&'static str, Node>,
+    param: Option<Box<Node>>,
+    // The routes whose templates end after this segment.
+    ends: Vec<usize>,
+    // The routes whose templates end with a `*` after this segment.
+    tails: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Part {
+    Literal(&'static str),
+    Param(&'static str),
+    Tail,
+}
+
+// A template matched against a path: the length of each matched segment,
+// and the values of the parameters.
+struct Matched {
+    segments: Vec<(usize, Part)>,
+    params: Vec<(&'static str, String)>,
+}
+
+// The characters encoded in the parameters of built paths, besides controls.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/');
+
+const TAIL: &AsciiSet = &SEGMENT.remove(b'/');
+
+/// Builds the path of the route named `name`, with the parameters of its
+/// template replaced by `params`.
+///
+/// Routes are found by the name given with [`Endpoint::named`] or
+/// [`Pattern::name`](crate::path::Pattern::name), among every route and
+/// pattern named so far, in any router. A final `*` of the template is
+/// replaced by the parameter `tail`.
+///
+/// The values are percent-encoded, except for the slashes of `tail`.
+/// Returns `None` if no route has that name, or a parameter is missing.
+///
+/// The path doesn't include any prefix matched before the router, such as
+/// the `/api` of `warp::path("api").and(router)`.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::Method;
+/// use warp::router::{Endpoint, Router};
+/// use warp::Filter;
+///
+/// let router = Router::new().route(
+///     Endpoint::new(Method::GET, "/users/{id}").named("user_detail"),
+///     warp::router::param::<u32>("id").map(|id| format!("user #{}", id)),
+/// );
+///
+/// assert_eq!(
+///     warp::uri_for("user_detail", &[("id", 42)]).as_deref(),
+///     Some("/users/42"),
+/// );
+/// ```
+pub fn uri_for<V: fmt::Display>(name: &str, params: &[(&str, V)]) -> Option<String> {
+    let template = *names()
+        .read()
+        .expect("route names lock poisoned")
+        .get(name)?;
+    build_path(template, params)
+}
+
+// The templates of the named routes of every router.
+fn names() -> &'static RwLock<HashMap<&'static str, &'static str>> {
+    static NAMES: OnceLock<RwLock<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    NAMES.get_or_init(Default::default)
+}
+
+// Names are global, so one can't be given to another template, which
+// `uri_for` would then silently build paths to.
+pub(crate) fn name_template(name: &'static str, template: &'static str) {
+    let previous = *names()
+        .write()
+        .expect("route names lock poisoned")
+        .entry(name)
+        .or_insert(template);
+    assert!(
+        previous == template,
+        "route name {:?} is already used for {:?}, not {:?}",
+        name,
+        previous,
+        template
+    );
+}
+
+fn build_path<V: fmt::Display>(template: &'static str, params: &[(&str, V)]) -> Option<String> {
+    let value = |name: &str| {
+        params
+            .iter()
+            .find(|&&(param, _)| param == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    let mut path = String::new();
+    for part in parse(template) {
+        path.push('/');
+        match part {
+            Part::Literal(literal) => path.push_str(literal),
+            Part::Param(name) => path.extend(utf8_percent_encode(&value(name)?, SEGMENT)),
+            Part::Tail => {
+                let tail = value("tail")?;
+                path.extend(utf8_percent_encode(tail.trim_start_matches('/'), TAIL));
+            }
+        }
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+    Some(path)
+}
+
+/// Create a `Filter` that extracts the parameter `name` of the path template
+/// that matched, parsed as a `T`.
+///
+/// Rejects with a `404 Not Found` if no [`Router`] route or
+/// [`path::pattern`](crate::path::pattern) with such a parameter matched,
+/// or the value couldn't be parsed.
+pub fn param<T>(name: &'static str) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy
+where
+    T: FromStr + Send + 'static,
+{
+    filter_fn_one(move |route| {
+        let value = route
+            .extensions()
+            .get::<Params>()
+            .and_then(|params| params.parse(name))
+            .ok_or_else(reject::not_found);
+        future::ready(value)
+    })
+}
+
+/// Create a `Filter` that extracts all the [`Params`] of the path template
+/// that matched.
+///
+/// If no [`Router`] route or [`path::pattern`](crate::path::pattern)
+/// matched, the `Params` are empty.
+pub fn params() -> impl Filter<Extract = One<Params>, Error = std::convert::Infallible> + Copy {
+    filter_fn_one(|route| {
+        future::ok(
+            route
+                .extensions()
+                .get::<Params>()
+                .cloned()
+                .unwrap_or_default(),
+        )
+    })
+}
+
+impl Router {
+    /// Creates a router with no routes.
+    pub fn new() -> Router {
+        Router::default()
+    }
+
+    /// Adds a route, replying with `filter` to requests matching `endpoint`.
+    ///
+    /// The path segments of the template are matched before `filter` runs,
+    /// so it shouldn't match them again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the template doesn't start with `/`, has an unclosed `{`,
+    /// or has a `*` segment that isn't the last, or if the endpoint's name
+    /// was already given to a different template.
+    pub fn route<F>(mut self, endpoint: Endpoint, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        let parts = parse(endpoint.template);
+        if let Some(name) = endpoint.name {
+            name_template(name, endpoint.template);
+        }
+        let rank = (endpoint.priority, specificity(&parts));
+        let handler: Handler = Arc::new(move || {
+            let future = filter.filter(Internal);
+            Box::pin(async move {
+                match future.await {
+                    Ok(reply) => Ok(reply.into_response()),
+                    Err(err) => Err(err.into()),
+                }
+            })
+        });
+        let routes = Arc::make_mut(&mut self.routes);
+        // After the routes that are at least as specific, to keep the order
+        // of equal ones.
+        let index = routes
+            .iter()
+            .position(|entry| (entry.endpoint.priority, specificity(&entry.parts)) < rank)
+            .unwrap_or(routes.len());
+        routes.insert(
+            index,
+            Entry {
+                endpoint,
+                parts,
+                handler,
+            },
+        );
+        self.tree = Arc::default();
+        self
+    }
+
+    /// Adds a `GET` route.
+    pub fn get<F>(self, template: &'static str, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        self.route(Endpoint::new(Method::GET, template), filter)
+    }
+
+    /// Adds a `POST` route.
+    pub fn post<F>(self, template: &'static str, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        self.route(Endpoint::new(Method::POST, template), filter)
+    }
+
+    /// Adds a `PUT` route.
+    pub fn put<F>(self, template: &'static str, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        self.route(Endpoint::new(Method::PUT, template), filter)
+    }
+
+    /// Adds a `PATCH` route.
+    pub fn patch<F>(self, template: &'static str, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        self.route(Endpoint::new(Method::PATCH, template), filter)
+    }
+
+    /// Adds a `DELETE` route.
+    pub fn delete<F>(self, template: &'static str, filter: F) -> Router
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Error: Into<Rejection>,
+    {
+        self.route(Endpoint::new(Method::DELETE, template), filter)
+    }
+
+    /// The routes of this router, in the order they're tried.
+    pub fn routes(&self) -> impl Iterator<Item = &Endpoint> {
+        self.routes.iter().map(|entry| &entry.endpoint)
+    }
+
+    /// Builds the path of this router's route named `name`, like
+    /// [`uri_for`] does among every router.
+    pub fn uri_for<V: fmt::Display>(&self, name: &str, params: &[(&str, V)]) -> Option<String> {
+        let entry = self
+            .routes
+            .iter()
+            .find(|entry| entry.endpoint.name == Some(name))?;
+        build_path(entry.endpoint.template, params)
+    }
+
+    /// The methods of the routes whose template matches `path`.
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let mut methods = Vec::new();
+        for index in self.tree().find(path, false) {
+            let method = &self.routes[index].endpoint.method;
+            if !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        }
+        methods
+    }
+
+    fn tree(&self) -> &Node {
+        self.tree.get_or_init(|| {
+            let mut tree = Node::default();
+            for (index, entry) in self.routes.iter().enumerate() {
+                tree.insert(&entry.parts, index);
+            }
+            tree
+        })
+    }
+}
+
+impl FilterBase for Router {
+    type Extract = (Response,);
+    type Error = Rejection;
+    type Future = Pin<Box<dyn Future<Output = Result<(Response,), Rejection>> + Send>>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let candidates = route::with(|route| self.tree().find(route.path(), route.ignore_case()));
+        let routes = self.routes.clone();
+        Box::pin(async move { dispatch(&routes, candidates).await.map(|res| (res,)) })
+    }
+}
+
+// Tries the routes at `candidates`, whose templates match the path.
+async fn dispatch(routes: &[Entry], candidates: Vec<usize>) -> Result<Response, Rejection> {
+    let (start, path_matched, method) = route::with(|route| {
+        (
+            route.matched_path_index(),
+            route.path_matched(),
+            route.method().clone(),
+        )
+    });
+    let mut rejection: Option<Rejection> = None;
+    let mut allowed = Vec::new();
+
+    for entry in candidates.into_iter().map(|index| &routes[index]) {
+        let matched = match route::with(|route| matches(&entry.parts, route)) {
+            Some(matched) => matched,
+            None => continue,
+        };
+        if entry.endpoint.method != method {
+            allowed.push(entry.endpoint.method.clone());
+            continue;
+        }
+
+        route::with(|route| apply(route, matched));
+        match (entry.handler)().await {
+            Ok(res) => return Ok(res),
+            Err(err) => {
+                tracing::trace!("route {} rejected: {:?}", entry.endpoint, err);
+                route::with(|route| {
+                    route.reset_matched_path_index(start);
+                    route.set_path_matched(path_matched);
+                    forget_params(route, start);
+                });
+                rejection = Some(match rejection {
+                    Some(prev) => prev.combine(err),
+                    None => err,
+                });
+            }
+        }
+    }
+
+    for method in allowed {
+        let err = reject::method_not_allowed(method, true);
+        rejection = Some(match rejection {
+            Some(prev) => prev.combine(err),
+            None => err,
+        });
+    }
+    Err(rejection.unwrap_or_else(reject::not_found))
+}
+
+impl Node {
+    fn insert(&mut self, parts: &[Part], index: usize) {
+        let mut node = self;
+        for &part in parts {
+            node = match part {
+                Part::Literal(literal) => node.literals.entry(literal).or_default(),
+                Part::Param(_) => node.param.get_or_insert_with(Default::default),
+                Part::Tail => {
+                    node.tails.push(index);
+                    return;
+                }
+            };
+        }
+        node.ends.push(index);
+    }
+
+    // The routes whose templates match the unmatched part of a path, in
+    // the order they're tried.
+    fn find(&self, path: &str, ignore_case: bool) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.collect(path, ignore_case, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    // Segments are split like `match_path` does, so both agree on what
+    // matches.
+    fn collect(&self, rest: &str, ignore_case: bool, found: &mut Vec<usize>) {
+        found.extend(&self.tails);
+        if rest.is_empty() {
+            found.extend(&self.ends);
+        }
+
+        let (segment, after) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        if ignore_case {
+            for (literal, node) in &self.literals {
+                if segment.eq_ignore_ascii_case(literal) {
+                    node.collect(after, ignore_case, found);
+                }
+            }
+        } else if let Some(node) = self.literals.get(segment) {
+            node.collect(after, ignore_case, found);
+        }
+        if let Some(ref node) = self.param {
+            if !segment.is_empty() {
+                node.collect(after, ignore_case, found);
+            }
+        }
+    }
+}
+
+pub(crate) fn parse(template: &'static str) -> Vec<Part> {
+    let path = template
+        .strip_prefix('/')
+        .unwrap_or_else(|| panic!("route template must start with '/': {:?}", template));
+    if path.is_empty() {
+        return Vec::new();
+    }
+
+    let mut parts = Vec::new();
+    let mut segments = path.split('/').peekable();
+    while let Some(segment) = segments.next() {
+        let part = if segment == "*" {
+            assert!(
+                segments.peek().is_none(),
+                "route template can only end with '*': {:?}",
+                template
+            );
+            Part::Tail
+        } else if let Some(name) = segment.strip_prefix('{') {
+            let name = name
+                .strip_suffix('}')
+                .unwrap_or_else(|| panic!("route template has an unclosed '{{': {:?}", template));
+            Part::Param(name)
+        } else {
+            Part::Literal(segment)
+        };
+        parts.push(part);
+    }
+    parts
+}
+
+// Ordered from the least to the most specific template: comparing the
+// segments in turn, a literal beats a parameter, which beats a tail, and a
+// template ending where another goes on beats it.
+fn specificity(parts: &[Part]) -> std::cmp::Reverse<Vec<u8>> {
+    let ranks = parts
+        .iter()
+        .map(|part| match part {
+            Part::Literal(_) => 0,
+            Part::Param(_) => 1,
+            Part::Tail => 2,
+        })
+        .collect();
+    std::cmp::Reverse(ranks)
+}
+
+fn matches(parts: &[Part], route: &Route) -> Option<Matched> {
+    match_path(parts, route.path(), route.ignore_case())
+}
+
+// Matches the unmatched part of a path, without its leading slash.
+fn match_path(parts: &[Part], path: &str, ignore_case: bool) -> Option<Matched> {
+    let mut rest = path;
+    let mut matched = Matched {
+        segments: Vec::with_capacity(parts.len()),
+        params: Vec::new(),
+    };
+
+    for &part in parts {
+        if part == Part::Tail {
+            matched.segments.push((rest.len(), part));
+            rest = "";
+            break;
+        }
+
+        let (segment, after) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        match part {
+            Part::Literal(literal)
+                if segment == literal || (ignore_case && segment.eq_ignore_ascii_case(literal)) => {
+            }
+            Part::Param(name) if !segment.is_empty() => {
+                matched.params.push((name, segment.to_owned()));
+            }
+            _ => return None,
+        }
+        matched.segments.push((segment.len(), part));
+        rest = after;
+    }
+
+    if rest.is_empty() {
+        Some(matched)
+    } else {
+        None
+    }
+}
+
+/// Matches the unmatched part of the path against a parsed template,
+/// recording its parameters in the route's [`Params`].
+pub(crate) fn match_template(route: &mut Route, parts: &[Part]) -> bool {
+    match matches(parts, route) {
+        Some(matched) => {
+            apply(route, matched);
+            true
+        }
+        None => false,
+    }
+}
+
+fn apply(route: &mut Route, matched: Matched) {
+    forget_params(route, route.matched_path_index());
+    let mut params = route
+        .extensions_mut()
+        .remove::<Params>()
+        .unwrap_or_default();
+    let mut values = matched.params.into_iter();
+
+    for (len, part) in matched.segments {
+        let start = route.matched_path_index();
+        let segment = match part {
+            Part::Literal(_) => Segment::Literal(start, start + len),
+            Part::Param(name) => {
+                let (_, value) = values.next().expect("a value for each param");
+                params.params.push(Param { start, name, value });
+                Segment::Param(name)
+            }
+            // Left unmatched, for `path::tail()`.
+            Part::Tail => {
+                route.push_template(start, Segment::Tail);
+                break;
+            }
+        };
+        route.set_unmatched_path(len);
+        route.push_template(start, segment);
+    }
+    route.extensions_mut().insert(params);
+}
+
+fn forget_params(route: &mut Route, from: usize) {
+    if let Some(params) = route.extensions_mut().get_mut::<Params>() {
+        params.params.retain(|param| param.start < from);
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.routes()).finish()
+    }
+}
+
+impl Endpoint {
+    /// Creates an endpoint for requests with `method` and a path matching
+    /// `template`.
+    pub fn new(method: Method, template: &'static str) -> Endpoint {
+        Endpoint {
+            method,
+            template,
+            name: None,
+            tags: Vec::new(),
+            priority: 0,
+            #[cfg(feature = "openapi")]
+            docs: Default::default(),
+        }
+    }
+
+    /// Names the route, to build paths to it with [`uri_for`].
+    pub fn named(mut self, name: &'static str) -> Endpoint {
+        self.name = Some(name);
+        self
+    }
+
+    /// Tags the route, for example with the part of the API it belongs to.
+    pub fn tagged(mut self, tag: &'static str) -> Endpoint {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Sets the priority of the route, `0` by default.
+    ///
+    /// Routes with a higher priority are tried before others, whatever
+    /// their templates. For example, a `/*` route with a priority of `1` is
+    /// tried before a `/{page}` one, instead of after it.
+    pub fn prioritized(mut self, priority: i32) -> Endpoint {
+        self.priority = priority;
+        self
+    }
+
+    /// The method of the route.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path template of the route, such as `/users/{id}`.
+    pub fn template(&self) -> &'static str {
+        self.template
+    }
+
+    /// The name of the route, if it has one.
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// The tags of the route.
+    pub fn tags(&self) -> &[&'static str] {
+        &self.tags
+    }
+
+    /// The priority of the route.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.template)
+    }
+}
+
+impl Params {
+    /// The value of the parameter `name`, as it appeared in the path.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .rev()
+            .find(|param| param.name == name)
+            .map(|param| param.value.as_str())
+    }
+
+    /// The value of the parameter `name`, parsed as a `T`.
+    ///
+    /// Returns `None` if there is no such parameter, or it can't be parsed.
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name)?.parse().ok()
+    }
+
+    /// The number of parameters.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Whether there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Iterates over the names and values of the parameters, in the order
+    /// they appear in the path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params
+            .iter()
+            .map(|param| (param.name, param.value.as_str()))
+    }
+}
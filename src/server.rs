@@ -2,22 +2,32 @@
 use crate::tls::TlsConfigBuilder;
 use std::convert::Infallible;
 use std::error::Error as StdError;
+use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
 #[cfg(feature = "tls")]
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use futures::{future, FutureExt, TryFuture, TryStream, TryStreamExt};
-use hyper::server::conn::AddrIncoming;
+use futures::{future, FutureExt, TryFuture, TryFutureExt, TryStream, TryStreamExt};
+use http::header::{HeaderValue, ALT_SVC, CONNECTION};
+use http::StatusCode;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::Server as HyperServer;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tracing_futures::Instrument;
 
+use crate::conn::Info as ConnInfo;
 use crate::filter::Filter;
-use crate::reject::IsReject;
+use crate::filters::catch_panic::Panic;
+use crate::reject::{IsReject, Rejection};
 use crate::reply::Reply;
-use crate::transport::Transport;
+use crate::request_id::RequestId;
+use crate::route::Route;
+use crate::transport::{
+    ConnConfig, ConnLimit, HeaderTimeoutIncoming, LimitedIncoming, TcpConfig, Transport,
+};
 
 /// Create a `Server` with the provided `Filter`.
 pub fn serve<F>(filter: F) -> Server<F>
@@ -28,17 +38,85 @@ where
 {
     Server {
         pipeline: false,
+        alt_svc: None,
+        connections: ConnConfig::default(),
+        timeouts: Timeouts::default(),
+        tcp: TcpConfig::default(),
+        hooks: Hooks::default(),
+        #[cfg(feature = "http3")]
+        h3_max_body_size: crate::http3::DEFAULT_MAX_BODY_SIZE,
         filter,
     }
 }
 
 /// A Warp Server ready to filter requests.
-#[derive(Debug)]
 pub struct Server<F> {
     pipeline: bool,
+    alt_svc: Option<HeaderValue>,
+    connections: ConnConfig,
+    timeouts: Timeouts,
+    tcp: TcpConfig,
+    hooks: Hooks,
+    #[cfg(feature = "http3")]
+    h3_max_body_size: u64,
     filter: F,
 }
 
+pub(crate) type ErrorHook = Arc<dyn Fn(&UnhandledError<'_>) + Send + Sync>;
+
+type Hook<T> = Option<Arc<dyn Fn(&T) + Send + Sync>>;
+
+type RequestHook = Arc<dyn Fn(&RequestEvent<'_>) + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct Hooks {
+    on_error: Option<ErrorHook>,
+    on_request: Option<RequestHook>,
+    on_response: Hook<ResponseEvent>,
+    on_connection_open: Hook<ConnInfo>,
+    on_connection_close: Hook<ConnInfo>,
+}
+
+// Calls the `on_connection_close` hook when the connection's service is
+// dropped.
+struct ConnectionClosed {
+    hook: Hook<ConnInfo>,
+    info: ConnInfo,
+}
+
+/// A request received by the server, passed to the [`Server::on_request`]
+/// hook.
+pub struct RequestEvent<'a> {
+    req: &'a crate::Request,
+    remote_addr: Option<SocketAddr>,
+}
+
+/// A reply sent by the server, passed to the [`Server::on_response`] hook.
+#[derive(Debug)]
+pub struct ResponseEvent {
+    method: http::Method,
+    uri: http::Uri,
+    remote_addr: Option<SocketAddr>,
+    status: StatusCode,
+    elapsed: Duration,
+}
+
+/// An error that reached the server, passed to the [`Server::on_error`] hook.
+///
+/// This is either a rejection that no filter recovered from, or a panic.
+pub struct UnhandledError<'a> {
+    route: &'a Route,
+    status: StatusCode,
+    rejection: Option<&'a Rejection>,
+    panic: Option<&'a Panic>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Timeouts {
+    header_read: Option<Duration>,
+    request: Option<Duration>,
+}
+
 /// A Warp Server ready to filter requests over TLS.
 ///
 /// *This type requires the `"tls"` feature.*
@@ -50,23 +128,138 @@ pub struct TlsServer<F> {
 
 // Getting all various generic bounds to make this a re-usable method is
 // very complicated, so instead this is just a macro.
-macro_rules! into_service {
-    ($into:expr) => {{
+//
+// `conn_service!` builds a function making the service of each connection,
+// which `into_service!` turns into hyper's `MakeService`.
+macro_rules! conn_service {
+    ($into:expr, $alt_svc:expr, $timeouts:expr, $limit:expr, $hooks:expr) => {{
         let inner = crate::service($into);
-        make_service_fn(move |transport| {
+        let alt_svc = $alt_svc;
+        let hooks: Hooks = $hooks;
+        let request_timeout = $timeouts.request;
+        let limit: ConnLimit = $limit;
+        move |conn: crate::transport::ConnInfo| {
             let inner = inner.clone();
-            let remote_addr = Transport::remote_addr(transport);
-            future::ok::<_, Infallible>(service_fn(move |req| {
-                inner.call_with_addr(req, remote_addr)
-            }))
+            let alt_svc = alt_svc.clone();
+            let limit = limit.clone();
+            let hooks = hooks.clone();
+            if let Some(ref on_open) = hooks.on_connection_open {
+                on_open(&conn.info());
+            }
+            let closed = ConnectionClosed {
+                hook: hooks.on_connection_close.clone(),
+                info: conn.info(),
+            };
+            // Connections accepted over the limit only get to say goodbye.
+            let shed = limit.is_shedding();
+            service_fn(move |req| {
+                // The service owns the guard, so it's dropped with the
+                // connection.
+                let _ = &closed;
+                if shed {
+                    return future::Either::Left(future::ok(shed_response()));
+                }
+                let alt_svc = alt_svc.clone();
+                let in_flight = limit.track_request();
+                // The headers of the request have been read, so the header
+                // timeout is off until it's replied to.
+                let serving = conn.requests.clone().map(|requests| {
+                    let connect = req.method() == http::Method::CONNECT;
+                    (requests.track(), requests, connect)
+                });
+                let mut req = req;
+                req.extensions_mut().insert(conn.info());
+                if let Some(ref on_request) = hooks.on_request {
+                    on_request(&RequestEvent {
+                        req: &req,
+                        remote_addr: conn.remote_addr,
+                    });
+                }
+                let responded = hooks.on_response.clone().map(|hook| {
+                    let started = tokio::time::Instant::now();
+                    (hook, req.method().clone(), req.uri().clone(), started)
+                });
+                let remote_addr = conn.remote_addr;
+                let fut = inner
+                    .call_reporting(req, conn.remote_addr, hooks.on_error.clone())
+                    .map_ok(move |mut res| {
+                        drop(in_flight);
+                        if let Some(alt_svc) = alt_svc {
+                            res.headers_mut().entry(ALT_SVC).or_insert(alt_svc);
+                        }
+                        res
+                    });
+                let fut = match request_timeout {
+                    Some(timeout) => future::Either::Left(
+                        tokio::time::timeout(timeout, fut)
+                            .map(|result| result.unwrap_or_else(|_| Ok(timeout_response()))),
+                    ),
+                    None => future::Either::Right(fut),
+                };
+                future::Either::Right(fut.map_ok(move |res| {
+                    if let Some((serving, requests, connect)) = serving {
+                        let status = res.status();
+                        // Upgraded connections carry another protocol.
+                        if status == StatusCode::SWITCHING_PROTOCOLS
+                            || (connect && status.is_success())
+                        {
+                            requests.upgraded();
+                        }
+                        drop(serving);
+                    }
+                    if let Some((hook, method, uri, started)) = responded {
+                        hook(&ResponseEvent {
+                            method,
+                            uri,
+                            remote_addr,
+                            status: res.status(),
+                            elapsed: started.elapsed(),
+                        });
+                    }
+                    res
+                }))
+            })
+        }
+    }};
+}
+
+macro_rules! into_service {
+    ($into:expr, $alt_svc:expr, $timeouts:expr, $limit:expr, $hooks:expr) => {{
+        let conn_service = conn_service!($into, $alt_svc, $timeouts, $limit, $hooks);
+        make_service_fn(move |transport| {
+            let conn = crate::transport::conn_info(transport);
+            future::ok::<_, Infallible>(conn_service(conn))
         })
     }};
 }
 
+fn timeout_response() -> crate::reply::Response {
+    tracing::debug!("request timed out");
+    let mut res = crate::reply::Response::default();
+    *res.status_mut() = StatusCode::REQUEST_TIMEOUT;
+    res.headers_mut()
+        .insert(CONNECTION, HeaderValue::from_static("close"));
+    res
+}
+
+macro_rules! hyper_builder {
+    ($incoming:expr, $pipeline:expr, $timeouts:expr) => {{
+        let incoming = HeaderTimeoutIncoming::new($incoming, $timeouts.header_read);
+        HyperServer::builder(incoming).http1_pipeline_flush($pipeline)
+    }};
+}
+
+fn shed_response() -> crate::reply::Response {
+    let mut res = crate::reply::Response::default();
+    *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    res.headers_mut()
+        .insert(CONNECTION, HeaderValue::from_static("close"));
+    res
+}
+
 macro_rules! addr_incoming {
-    ($addr:expr) => {{
-        let mut incoming = AddrIncoming::bind($addr)?;
-        incoming.set_nodelay(true);
+    ($addr:expr, $tcp:expr) => {{
+        let incoming = crate::transport::bind_incoming($addr, &$tcp)?;
         let addr = incoming.local_addr();
         (addr, incoming)
     }};
@@ -74,21 +267,41 @@ macro_rules! addr_incoming {
 
 macro_rules! bind_inner {
     ($this:ident, $addr:expr) => {{
-        let service = into_service!($this.filter);
-        let (addr, incoming) = addr_incoming!($addr);
-        let srv = HyperServer::builder(incoming)
-            .http1_pipeline_flush($this.pipeline)
-            .serve(service);
-        Ok::<_, hyper::Error>((addr, srv))
+        let limit = ConnLimit::new($this.connections);
+        let service = into_service!(
+            $this.filter,
+            $this.alt_svc,
+            $this.timeouts,
+            limit.clone(),
+            $this.hooks
+        );
+        let (addr, incoming) = addr_incoming!($addr, $this.tcp);
+        let srv = hyper_builder!(
+            LimitedIncoming::new(incoming, limit),
+            $this.pipeline,
+            $this.timeouts
+        )
+        .serve(service);
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>((addr, srv))
     }};
 
     (tls: $this:ident, $addr:expr) => {{
-        let service = into_service!($this.server.filter);
-        let (addr, incoming) = addr_incoming!($addr);
+        let limit = ConnLimit::new($this.server.connections);
+        let service = into_service!(
+            $this.server.filter,
+            $this.server.alt_svc,
+            $this.server.timeouts,
+            limit.clone(),
+            $this.server.hooks
+        );
+        let (addr, incoming) = addr_incoming!($addr, $this.server.tcp);
         let tls = $this.tls.build()?;
-        let srv = HyperServer::builder(crate::tls::TlsAcceptor::new(tls, incoming))
-            .http1_pipeline_flush($this.server.pipeline)
-            .serve(service);
+        let srv = hyper_builder!(
+            LimitedIncoming::new(crate::tls::TlsAcceptor::new(tls, incoming), limit),
+            $this.server.pipeline,
+            $this.server.timeouts
+        )
+        .serve(service);
         Ok::<_, Box<dyn std::error::Error + Send + Sync>>((addr, srv))
     }};
 }
@@ -119,6 +332,251 @@ macro_rules! try_bind {
     }};
 }
 
+/// Statistics from draining a server started with [`Server::bind_with_drain`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Drained {
+    forced_requests: usize,
+    forced_connections: usize,
+}
+
+impl Drained {
+    /// The number of requests that were aborted before a response was sent.
+    pub fn forced_requests(&self) -> usize {
+        self.forced_requests
+    }
+
+    /// The number of connections that were aborted at the deadline.
+    pub fn forced_connections(&self) -> usize {
+        self.forced_connections
+    }
+
+    /// Whether every connection closed on its own before the deadline.
+    pub fn is_clean(&self) -> bool {
+        self.forced_connections == 0
+    }
+}
+
+impl<F: fmt::Debug> fmt::Debug for Server<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Server");
+        debug
+            .field("pipeline", &self.pipeline)
+            .field("alt_svc", &self.alt_svc)
+            .field("connections", &self.connections)
+            .field("timeouts", &self.timeouts)
+            .field("tcp", &self.tcp)
+            .field("hooks", &self.hooks);
+        #[cfg(feature = "http3")]
+        debug.field("h3_max_body_size", &self.h3_max_body_size);
+        debug.field("filter", &self.filter).finish()
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_error", &self.on_error.is_some())
+            .field("on_request", &self.on_request.is_some())
+            .field("on_response", &self.on_response.is_some())
+            .field("on_connection_open", &self.on_connection_open.is_some())
+            .field("on_connection_close", &self.on_connection_close.is_some())
+            .finish()
+    }
+}
+
+impl Drop for ConnectionClosed {
+    fn drop(&mut self) {
+        if let Some(ref hook) = self.hook {
+            hook(&self.info);
+        }
+    }
+}
+
+// ===== impl RequestEvent =====
+
+impl RequestEvent<'_> {
+    /// View the request's method.
+    pub fn method(&self) -> &http::Method {
+        self.req.method()
+    }
+
+    /// View the request's URI.
+    pub fn uri(&self) -> &http::Uri {
+        self.req.uri()
+    }
+
+    /// View the request's HTTP version.
+    pub fn version(&self) -> http::Version {
+        self.req.version()
+    }
+
+    /// View the request's headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        self.req.headers()
+    }
+
+    /// View the remote `SocketAddr` of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+}
+
+impl fmt::Debug for RequestEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestEvent")
+            .field("method", self.method())
+            .field("uri", self.uri())
+            .field("remote_addr", &self.remote_addr)
+            .finish()
+    }
+}
+
+// ===== impl ResponseEvent =====
+
+impl ResponseEvent {
+    /// View the method of the request replied to.
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    /// View the URI of the request replied to.
+    pub fn uri(&self) -> &http::Uri {
+        &self.uri
+    }
+
+    /// View the remote `SocketAddr` of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// View the status of the reply.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// View the time taken from receiving the request to the reply being
+    /// ready.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+// ===== impl UnhandledError =====
+
+impl<'a> UnhandledError<'a> {
+    pub(crate) fn new(
+        route: &'a Route,
+        status: StatusCode,
+        rejection: Option<&'a Rejection>,
+        panic: Option<&'a Panic>,
+    ) -> Self {
+        UnhandledError {
+            route,
+            status,
+            rejection,
+            panic,
+        }
+    }
+
+    /// View the request's method.
+    pub fn method(&self) -> &http::Method {
+        self.route.method()
+    }
+
+    /// View the request's URI.
+    pub fn uri(&self) -> &http::Uri {
+        self.route.uri()
+    }
+
+    /// View the remote `SocketAddr` of the request.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.route.remote_addr()
+    }
+
+    /// View the request's headers.
+    pub fn request_headers(&self) -> &http::HeaderMap {
+        self.route.headers()
+    }
+
+    /// View the request's ID, if it was given one with
+    /// [`request_id()`](crate::request_id()).
+    pub fn request_id(&self) -> Option<&str> {
+        self.route
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+    }
+
+    /// View the status code the request was replied to with.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// View the rejection, if the request was rejected.
+    pub fn rejection(&self) -> Option<&Rejection> {
+        self.rejection
+    }
+
+    /// View the panic, if a filter or handler panicked.
+    pub fn panic(&self) -> Option<&Panic> {
+        self.panic
+    }
+}
+
+impl fmt::Debug for UnhandledError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnhandledError")
+            .field("method", self.method())
+            .field("uri", self.uri())
+            .field("status", &self.status)
+            .field("rejection", &self.rejection)
+            .field("panic", &self.panic)
+            .finish()
+    }
+}
+
+// Spawns connection tasks so that they can all be aborted at once.
+#[derive(Clone)]
+struct DrainExec {
+    abort: tokio::sync::watch::Receiver<bool>,
+}
+
+impl DrainExec {
+    fn new() -> (tokio::sync::watch::Sender<bool>, DrainExec) {
+        let (tx, abort) = tokio::sync::watch::channel(false);
+        (tx, DrainExec { abort })
+    }
+}
+
+impl<Fut> hyper::rt::Executor<Fut> for DrainExec
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    fn execute(&self, fut: Fut) {
+        let mut abort = self.abort.clone();
+        tokio::spawn(async move {
+            let aborted = async move { abort.changed().await };
+            futures::pin_mut!(fut, aborted);
+            future::select(fut, aborted).await;
+        });
+    }
+}
+
+// Spawns connection tasks onto the current `LocalSet`.
+#[derive(Clone, Copy, Debug)]
+struct LocalExec;
+
+impl<Fut> hyper::rt::Executor<Fut> for LocalExec
+where
+    Fut: Future + 'static,
+    Fut::Output: 'static,
+{
+    fn execute(&self, fut: Fut) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
 // ===== impl Server =====
 
 impl<F> Server<F>
@@ -136,6 +594,124 @@ where
         fut.instrument(span).await;
     }
 
+    /// Run this `Server` forever on the current thread, driving connections
+    /// with the current [`LocalSet`](tokio::task::LocalSet).
+    ///
+    /// This allows using [`Filter::and_then_local`] handlers, whose futures
+    /// don't need to be `Send`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not awaited within a `LocalSet`, or if we are unable to bind
+    /// to the provided address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::rc::Rc;
+    /// use warp::Filter;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let routes = warp::any().and_then_local(|| async {
+    ///         let greeting = Rc::new("Hello, World!");
+    ///         tokio::task::yield_now().await;
+    ///         Ok::<_, warp::Rejection>(*greeting)
+    ///     });
+    ///
+    ///     let local = tokio::task::LocalSet::new();
+    ///     local
+    ///         .run_until(warp::serve(routes).run_local(([127, 0, 0, 1], 3030)))
+    ///         .await;
+    /// }
+    /// ```
+    pub async fn run_local(self, addr: impl Into<SocketAddr>) {
+        let (addr, fut) = self.bind_local(addr);
+        let span = tracing::info_span!("Server::run_local", ?addr);
+        tracing::info!(parent: &span, "listening on http://{}", addr);
+
+        fut.instrument(span).await;
+    }
+
+    /// Run this `Server` forever on the current thread, using io_uring for
+    /// accepting connections, socket IO, and `warp::fs` file reads.
+    ///
+    /// This must be awaited within a [`tokio_uring`] runtime. Connections are
+    /// spawned onto it, so [`Filter::and_then_local`] handlers work as well.
+    ///
+    /// *This function requires the `"io-uring"` feature, and is only
+    /// available on Linux.*
+    ///
+    /// # Panics
+    ///
+    /// Panics if not awaited within a `tokio_uring` runtime, or if we are
+    /// unable to bind to the provided address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::fs::dir("static");
+    ///
+    /// tokio_uring::start(warp::serve(routes).run_uring(([127, 0, 0, 1], 3030)));
+    /// ```
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub async fn run_uring(self, addr: impl Into<SocketAddr>) {
+        let addr = addr.into();
+        let limit = ConnLimit::new(self.connections);
+        let service = into_service!(
+            self.filter,
+            self.alt_svc,
+            self.timeouts,
+            limit.clone(),
+            self.hooks
+        );
+        let (addr, incoming) = crate::uring::bind(&addr).unwrap_or_else(|e| {
+            panic!("error binding to {}: {}", addr, e);
+        });
+        let span = tracing::info_span!("Server::run_uring", ?addr);
+        tracing::info!(parent: &span, "listening on http://{}", addr);
+
+        use hyper::server::accept::Accept;
+        use hyper::service::Service;
+
+        // hyper's `Server` requires `Send` connections, so connections are
+        // accepted and spawned here instead.
+        let mut http = hyper::server::conn::Http::new().with_executor(LocalExec);
+        http.pipeline_flush(self.pipeline);
+        let mut make_service = service;
+        let incoming = HeaderTimeoutIncoming::new(
+            LimitedIncoming::new(incoming, limit),
+            self.timeouts.header_read,
+        );
+        futures::pin_mut!(incoming);
+
+        async move {
+            while let Some(conn) = future::poll_fn(|cx| incoming.as_mut().poll_accept(cx)).await {
+                let conn = match conn {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        tracing::debug!("accept error: {}", err);
+                        continue;
+                    }
+                };
+                let service = match make_service.call(&conn).await {
+                    Ok(service) => service,
+                    Err(never) => match never {},
+                };
+                let conn = http.serve_connection(conn, service);
+                tokio::task::spawn_local(async move {
+                    if let Err(err) = conn.await {
+                        tracing::debug!("connection error: {}", err);
+                    }
+                });
+            }
+        }
+        .instrument(span)
+        .await;
+    }
+
     /// Run this `Server` forever on the current thread with a specific stream
     /// of incoming connections.
     ///
@@ -220,6 +796,48 @@ where
         (addr, srv)
     }
 
+    /// Bind to a possibly ephemeral socket address, spawning connections on
+    /// the current [`LocalSet`](tokio::task::LocalSet).
+    ///
+    /// Returns the bound address and a `Future` that must be executed within
+    /// a `LocalSet`. See [`Server::run_local`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided address.
+    pub fn bind_local(
+        self,
+        addr: impl Into<SocketAddr>,
+    ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
+        let addr = addr.into();
+        let limit = ConnLimit::new(self.connections);
+        let service = into_service!(
+            self.filter,
+            self.alt_svc,
+            self.timeouts,
+            limit.clone(),
+            self.hooks
+        );
+        let incoming = crate::transport::bind_incoming(&addr, &self.tcp).unwrap_or_else(|e| {
+            panic!("error binding to {}: {}", addr, e);
+        });
+        let addr = incoming.local_addr();
+        let srv = hyper_builder!(
+            LimitedIncoming::new(incoming, limit),
+            self.pipeline,
+            self.timeouts
+        )
+        .executor(LocalExec)
+        .serve(service)
+        .map(|result| {
+            if let Err(err) = result {
+                tracing::error!("server error: {}", err)
+            }
+        });
+
+        (addr, srv)
+    }
+
     /// Tried to bind a possibly ephemeral socket address.
     ///
     /// Returns a `Result` which fails in case we are unable to bind with the
@@ -309,6 +927,112 @@ where
         Ok((addr, srv))
     }
 
+    /// Create a server with a graceful shutdown signal and a drain deadline.
+    ///
+    /// When the signal completes, the server stops accepting connections and
+    /// asks open ones to close, as [`Server::bind_with_graceful_shutdown`]
+    /// does. Connections still busy once `deadline` has passed are aborted.
+    ///
+    /// Returns the bound address and a `Future` resolving to statistics about
+    /// what had to be forcibly closed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use tokio::sync::oneshot;
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let (tx, rx) = oneshot::channel::<()>();
+    ///
+    /// let (_addr, server) = warp::serve(routes).bind_with_drain(
+    ///     ([127, 0, 0, 1], 3030),
+    ///     async {
+    ///         rx.await.ok();
+    ///     },
+    ///     Duration::from_secs(30),
+    /// );
+    ///
+    /// // Later, start the shutdown...
+    /// # drop(tx);
+    /// let drained = server.await;
+    /// if !drained.is_clean() {
+    ///     eprintln!("aborted {} requests", drained.forced_requests());
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided address.
+    pub fn bind_with_drain(
+        self,
+        addr: impl Into<SocketAddr> + 'static,
+        signal: impl Future<Output = ()> + Send + 'static,
+        deadline: Duration,
+    ) -> (SocketAddr, impl Future<Output = Drained> + 'static) {
+        let addr = addr.into();
+        let limit = ConnLimit::new(self.connections);
+        let service = into_service!(
+            self.filter,
+            self.alt_svc,
+            self.timeouts,
+            limit.clone(),
+            self.hooks
+        );
+        let incoming = crate::transport::bind_incoming(&addr, &self.tcp).unwrap_or_else(|e| {
+            panic!("error binding to {}: {}", addr, e);
+        });
+        let addr = incoming.local_addr();
+        let (abort, exec) = DrainExec::new();
+        let srv = hyper_builder!(
+            LimitedIncoming::new(incoming, limit.clone()),
+            self.pipeline,
+            self.timeouts
+        )
+        .executor(exec)
+        .serve(service);
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let signal = signal.map(move |()| {
+            let _ = started_tx.send(());
+        });
+
+        let fut = async move {
+            let graceful = srv.with_graceful_shutdown(signal);
+            futures::pin_mut!(graceful);
+            let result = match future::select(graceful, started_rx).await {
+                future::Either::Left((result, _)) => result,
+                future::Either::Right((_, graceful)) => {
+                    match tokio::time::timeout(deadline, graceful).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            let drained = Drained {
+                                forced_requests: limit.in_flight(),
+                                forced_connections: limit.active(),
+                            };
+                            tracing::warn!(
+                                "drain deadline passed, aborting {} connections",
+                                drained.forced_connections
+                            );
+                            let _ = abort.send(true);
+                            return drained;
+                        }
+                    }
+                }
+            };
+            if let Err(err) = result {
+                tracing::error!("server error: {}", err)
+            }
+            Drained::default()
+        };
+
+        (addr, fut)
+    }
+
     /// Setup this `Server` with a specific stream of incoming connections.
     ///
     /// This can be used for Unix Domain Sockets, or TLS, etc.
@@ -345,16 +1069,23 @@ where
         I::Error: Into<Box<dyn StdError + Send + Sync>>,
     {
         let incoming = incoming.map_ok(crate::transport::LiftIo);
-        let service = into_service!(self.filter);
+        let limit = ConnLimit::new(self.connections);
+        let service = into_service!(
+            self.filter,
+            self.alt_svc,
+            self.timeouts,
+            limit.clone(),
+            self.hooks
+        );
         let pipeline = self.pipeline;
+        let timeouts = self.timeouts;
 
         async move {
-            let srv =
-                HyperServer::builder(hyper::server::accept::from_stream(incoming.into_stream()))
-                    .http1_pipeline_flush(pipeline)
-                    .serve(service)
-                    .with_graceful_shutdown(signal)
-                    .await;
+            let incoming = hyper::server::accept::from_stream(incoming.into_stream());
+            let srv = hyper_builder!(LimitedIncoming::new(incoming, limit), pipeline, timeouts)
+                .serve(service)
+                .with_graceful_shutdown(signal)
+                .await;
 
             if let Err(err) = srv {
                 tracing::error!("server error: {}", err);
@@ -371,18 +1102,267 @@ where
         I::Ok: Transport + Send + 'static + Unpin,
         I::Error: Into<Box<dyn StdError + Send + Sync>>,
     {
-        let service = into_service!(self.filter);
+        let limit = ConnLimit::new(self.connections);
+        let service = into_service!(
+            self.filter,
+            self.alt_svc,
+            self.timeouts,
+            limit.clone(),
+            self.hooks
+        );
 
-        let srv = HyperServer::builder(hyper::server::accept::from_stream(incoming.into_stream()))
-            .http1_pipeline_flush(self.pipeline)
-            .serve(service)
-            .await;
+        let incoming = hyper::server::accept::from_stream(incoming.into_stream());
+        let srv = hyper_builder!(
+            LimitedIncoming::new(incoming, limit),
+            self.pipeline,
+            self.timeouts
+        )
+        .serve(service)
+        .await;
 
         if let Err(err) = srv {
             tracing::error!("server error: {}", err);
         }
     }
 
+    /// Limit the number of connections that may be open at the same time.
+    ///
+    /// Once `max` connections are open, the server stops accepting new ones
+    /// until some close, leaving them queued in the listen backlog. Use
+    /// [`Server::shed_excess_connections`] to instead answer them right away
+    /// with a `503 Service Unavailable`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let server = warp::serve(routes)
+    ///     .max_connections(1024)
+    ///     .connection_gauge(|open| {
+    ///         println!("{} connections open", open);
+    ///     });
+    /// ```
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.connections.max = Some(max);
+        self
+    }
+
+    /// Accept connections over the [`Server::max_connections`] limit, but
+    /// reply to their requests with `503 Service Unavailable` and close them.
+    ///
+    /// This keeps clients from waiting in the backlog when the server is
+    /// saturated.
+    pub fn shed_excess_connections(mut self) -> Self {
+        self.connections.shed = true;
+        self
+    }
+
+    /// Register a function that is called with the number of open connections
+    /// every time a connection is opened or closed.
+    ///
+    /// This can be used to export a gauge of connection saturation.
+    pub fn connection_gauge<G>(mut self, gauge: G) -> Self
+    where
+        G: Fn(usize) + Send + Sync + 'static,
+    {
+        self.connections.gauge = Some(Arc::new(gauge));
+        self
+    }
+
+    /// Register a hook that is called with every error that reaches the
+    /// server, for example to send it to an error reporting service.
+    ///
+    /// These are rejections that no filter recovered from, and panics in
+    /// filters and handlers. With a hook registered, a panic no longer tears
+    /// down the connection, but replies with a `500 Internal Server Error`.
+    ///
+    /// The hook is called once the reply to the request is known, with the
+    /// request's metadata and the reply's status.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::path("hello").map(|| "Hello, World!");
+    ///
+    /// let server = warp::serve(routes).on_error(|err| {
+    ///     if err.status().is_server_error() || err.panic().is_some() {
+    ///         eprintln!("{} {} failed with {}", err.method(), err.uri(), err.status());
+    ///     }
+    /// });
+    /// ```
+    pub fn on_error<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&UnhandledError<'_>) + Send + Sync + 'static,
+    {
+        crate::filters::catch_panic::install_hook();
+        self.hooks.on_error = Some(Arc::new(hook));
+        self
+    }
+
+    /// Register a hook that is called with every request, before it's
+    /// filtered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let server = warp::serve(routes)
+    ///     .on_request(|req| println!("--> {} {}", req.method(), req.uri()))
+    ///     .on_response(|res| {
+    ///         println!("<-- {} {} {} in {:?}", res.method(), res.uri(), res.status(), res.elapsed())
+    ///     });
+    /// ```
+    pub fn on_request<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&RequestEvent<'_>) + Send + Sync + 'static,
+    {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Register a hook that is called with every reply, once it's ready to be
+    /// sent.
+    ///
+    /// This includes replies to rejected requests, and to requests that
+    /// timed out.
+    pub fn on_response<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&ResponseEvent) + Send + Sync + 'static,
+    {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Register a hook that is called every time a connection is accepted.
+    ///
+    /// Details of a TLS session aren't known yet when it's called.
+    pub fn on_connection_open<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&ConnInfo) + Send + Sync + 'static,
+    {
+        self.hooks.on_connection_open = Some(Arc::new(hook));
+        self
+    }
+
+    /// Register a hook that is called every time a connection is closed.
+    pub fn on_connection_close<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&ConnInfo) + Send + Sync + 'static,
+    {
+        self.hooks.on_connection_close = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set whether `TCP_NODELAY` is set on accepted connections.
+    ///
+    /// Default is `true`.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp.nodelay = enabled;
+        self
+    }
+
+    /// Set how long a connection must be idle before TCP keepalive probes are
+    /// sent, or `None` to disable keepalive.
+    ///
+    /// Default is `None`.
+    pub fn tcp_keepalive(mut self, time: Option<Duration>) -> Self {
+        self.tcp.keepalive = time;
+        self
+    }
+
+    /// Set the interval between TCP keepalive probes.
+    ///
+    /// This only has an effect if [`Server::tcp_keepalive`] is enabled.
+    pub fn tcp_keepalive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.tcp.keepalive_interval = interval;
+        self
+    }
+
+    /// Set `SO_REUSEPORT` on the listening socket.
+    ///
+    /// This allows several processes to bind the same address, with the
+    /// kernel balancing incoming connections between them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let server = warp::serve(routes)
+    ///     .reuse_port(true)
+    ///     .listen_backlog(4096)
+    ///     .tcp_nodelay(false);
+    /// ```
+    #[cfg(unix)]
+    pub fn reuse_port(mut self, enabled: bool) -> Self {
+        self.tcp.reuse_port = enabled;
+        self
+    }
+
+    /// Set the maximum length of the queue of pending connections.
+    pub fn listen_backlog(mut self, backlog: u32) -> Self {
+        self.tcp.backlog = Some(backlog);
+        self
+    }
+
+    /// Set the size of the send buffer (`SO_SNDBUF`) of accepted connections.
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.tcp.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the size of the receive buffer (`SO_RCVBUF`) of accepted
+    /// connections.
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.tcp.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Set how long to wait for a client to send the full headers of a
+    /// request.
+    ///
+    /// This protects against slow clients holding connections open by
+    /// trickling in header bytes. If the headers aren't received in time,
+    /// the connection is closed. This applies to HTTP/1 connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let server = warp::serve(routes)
+    ///     .header_read_timeout(Duration::from_secs(5))
+    ///     .request_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.header_read = Some(timeout);
+        self
+    }
+
+    /// Set how long a request may take to produce a response.
+    ///
+    /// If the filter hasn't replied when the timeout elapses, it is canceled
+    /// and the client receives a `408 Request Timeout`, after which the
+    /// connection is closed. The timeout covers reading the request body and
+    /// running the filters, not streaming the response body.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.request = Some(timeout);
+        self
+    }
+
     // Generally shouldn't be used, as it can slow down non-pipelined responses.
     //
     // It's only real use is to make silly pipeline benchmarks look better.
@@ -392,6 +1372,78 @@ where
         self
     }
 
+    /// Advertise an HTTP/3 endpoint on the given UDP port.
+    ///
+    /// Responses served over TCP will include an `Alt-Svc` header pointing
+    /// clients at the QUIC listener started with [`Server::run_h3`], unless
+    /// the reply already set one.
+    ///
+    /// *This function requires the `"http3"` feature.*
+    #[cfg(feature = "http3")]
+    pub fn alt_svc_h3(mut self, port: u16) -> Self {
+        let value = format!("h3=\":{}\"; ma=86400", port);
+        self.alt_svc = Some(HeaderValue::from_str(&value).expect("valid alt-svc value"));
+        self
+    }
+
+    /// Set the largest request body, in bytes, accepted over HTTP/3.
+    ///
+    /// Request bodies are read in full before they're filtered, so larger
+    /// ones are refused with a `413 Payload Too Large`. Default is 2 MiB.
+    ///
+    /// *This function requires the `"http3"` feature.*
+    #[cfg(feature = "http3")]
+    pub fn h3_max_body_size(mut self, bytes: u64) -> Self {
+        self.h3_max_body_size = bytes;
+        self
+    }
+
+    /// Run this `Server` forever over HTTP/3, using QUIC on the provided
+    /// UDP socket address.
+    ///
+    /// The `tls_config` must contain the certificate and key to use. If no
+    /// ALPN protocols have been configured, `h3` will be set.
+    ///
+    /// Pair this with [`Server::alt_svc_h3`] on a TCP server so that clients
+    /// discover the HTTP/3 endpoint.
+    ///
+    /// The hooks, timeouts and connection limits of the `Server` apply to
+    /// QUIC connections as they do to TCP ones, except that a request whose
+    /// headers take longer than [`Server::header_read_timeout`] has its
+    /// stream dropped. TCP options, such as [`Server::tcp_nodelay`], are
+    /// ignored.
+    ///
+    /// *This function requires the `"http3"` feature, and is experimental.*
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided address, or if the
+    /// `tls_config` cannot be used for QUIC.
+    #[cfg(feature = "http3")]
+    pub async fn run_h3(
+        self,
+        addr: impl Into<SocketAddr>,
+        tls_config: quinn::rustls::ServerConfig,
+    ) {
+        let addr = addr.into();
+        let limit = ConnLimit::new(self.connections);
+        let conn_service =
+            conn_service!(self.filter, None, self.timeouts, limit.clone(), self.hooks);
+        let (addr, fut) = crate::http3::bind(
+            conn_service,
+            addr,
+            tls_config,
+            limit,
+            self.timeouts.header_read,
+            self.h3_max_body_size,
+        )
+        .unwrap_or_else(|e| panic!("error binding to {}: {}", addr, e));
+        let span = tracing::info_span!("Server::run_h3", ?addr);
+        tracing::info!(parent: &span, "listening on https://{} (h3)", addr);
+
+        fut.instrument(span).await;
+    }
+
     /// Configure a server to use TLS.
     ///
     /// *This function requires the `"tls"` feature.*
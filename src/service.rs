@@ -1,3 +1,3 @@
-//! Convert `Filter`s into `Service`s
+//! Convert `Filter`s into `Service`s, and back
 
-pub use crate::filter::service::service;
+pub use crate::filter::service::{from_service, service, FilteredService};
@@ -80,7 +80,8 @@
 //!     assert_eq!(res.body(), "Sum is 3");
 //! }
 //! ```
-use std::convert::TryFrom;
+use std::cell::RefCell;
+use std::convert::{Infallible, TryFrom};
 use std::error::Error as StdError;
 use std::fmt;
 use std::future::Future;
@@ -91,13 +92,12 @@ use std::pin::Pin;
 use std::task::{self, Poll};
 
 use bytes::Bytes;
-#[cfg(feature = "websocket")]
-use futures::StreamExt;
-use futures::{future, FutureExt, TryFutureExt};
+use futures::{future, FutureExt, Stream, StreamExt, TryFutureExt};
 use http::{
     header::{HeaderName, HeaderValue},
     Response,
 };
+use hyper::Body;
 use serde::Serialize;
 use serde_json;
 #[cfg(feature = "websocket")]
@@ -111,13 +111,41 @@ use crate::reply::Reply;
 use crate::route::{self, Route};
 use crate::Request;
 
+pub use self::assert::{AssertReply, ExpectRejection, ExpectedRejection};
+pub use self::bench::{bench, Bench};
+pub use self::chunked::ChunkedBody;
+pub use self::client::Client;
+use self::client::CookieJar;
 use self::inner::OneOrTuple;
+pub use self::multipart::MultipartForm;
+pub use self::replay::{Replay, ReplayError, ReplayReport};
+#[cfg(feature = "tls")]
+pub use self::serve::serve_tls;
+pub use self::serve::{serve, TestServer};
+pub use self::snapshot::{AssertSnapshot, Snapshot};
+pub use self::sse::{sse, SseBuilder, SseClient, SseError, SseEvent};
+pub use self::time::{advance, pause_time, resume_time};
+
+pub mod arbitrary;
+mod assert;
+mod bench;
+mod chunked;
+mod client;
+#[cfg(feature = "compression")]
+mod compression;
+mod multipart;
+mod replay;
+mod serve;
+mod snapshot;
+mod sse;
+mod time;
 
 /// Starts a new test `RequestBuilder`.
 pub fn request() -> RequestBuilder {
     RequestBuilder {
         remote_addr: None,
         req: Request::default(),
+        jar: None,
     }
 }
 
@@ -135,6 +163,7 @@ pub fn ws() -> WsBuilder {
 pub struct RequestBuilder {
     remote_addr: Option<SocketAddr>,
     req: Request,
+    jar: Option<CookieJar>,
 }
 
 /// A Websocket builder for testing filters.
@@ -152,12 +181,15 @@ pub struct WsBuilder {
 pub struct WsClient {
     tx: mpsc::UnboundedSender<crate::ws::Message>,
     rx: mpsc::UnboundedReceiver<Result<crate::ws::Message, crate::error::Error>>,
+    response: Response<()>,
 }
 
 /// An error from Websocket filter tests.
 #[derive(Debug)]
 pub struct WsError {
     cause: Box<dyn StdError + Send + Sync>,
+    #[cfg(feature = "websocket")]
+    response: Option<Box<Response<()>>>,
 }
 
 impl RequestBuilder {
@@ -230,6 +262,46 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the `authorization` header of this request to the `Basic`
+    /// credentials of `username` and `password`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::request()
+    ///     .basic_auth("aladdin", "open sesame");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if `username` contains a `:`, which can't be told apart
+    /// from the separator of the password.
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        assert!(
+            !username.contains(':'),
+            "basic auth username can't contain ':'"
+        );
+        let credentials = base64::encode(format!("{}:{}", username, password));
+        self.header("authorization", format!("Basic {}", credentials))
+    }
+
+    /// Set the `authorization` header of this request to the `Bearer`
+    /// `token`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::request()
+    ///     .bearer_auth("mF_9.B5f-4.1JqM");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if `token` isn't a valid `HeaderValue`.
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header("authorization", format!("Bearer {}", token))
+    }
+
     /// Set the remote address of this request
     ///
     /// Default is no remote address.
@@ -272,6 +344,32 @@ impl RequestBuilder {
         self.header("content-length", len.to_string())
     }
 
+    /// Set the body of this request to a stream of chunks, sent with
+    /// `transfer-encoding: chunked` instead of a `content-length`.
+    ///
+    /// A [`ChunkedBody`] controls when the chunks arrive, and can make
+    /// bodies that never end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures::stream;
+    /// use warp::hyper::body::Bytes;
+    ///
+    /// let req = warp::test::request().method("POST").body_stream(stream::iter(vec![
+    ///     Bytes::from_static(b"foo=bar"),
+    ///     Bytes::from_static(b"&baz=quux"),
+    /// ]));
+    /// ```
+    pub fn body_stream<S>(mut self, stream: S) -> Self
+    where
+        S: Stream<Item = Bytes> + Send + 'static,
+    {
+        *self.req.body_mut() = Body::wrap_stream(stream.map(Ok::<_, Infallible>));
+        self.req.headers_mut().remove("content-length");
+        self.header("transfer-encoding", "chunked")
+    }
+
     /// Set the bytes of this request body by serializing a value into JSON.
     ///
     /// # Example
@@ -288,6 +386,26 @@ impl RequestBuilder {
             .header("content-type", "application/json")
     }
 
+    /// Set the body of this request to a `multipart/form-data` form, and
+    /// its `content-type` to match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::test::MultipartForm;
+    ///
+    /// let req = warp::test::request().method("POST").multipart(
+    ///     MultipartForm::new()
+    ///         .text_field("name", "warp")
+    ///         .file_field("readme", "README.md", "text/markdown", "# warp"),
+    /// );
+    /// ```
+    pub fn multipart(self, form: MultipartForm) -> Self {
+        let content_type = form.content_type();
+        self.body(form.into_body())
+            .header("content-type", content_type)
+    }
+
     /// Tries to apply the `Filter` on this request.
     ///
     /// # Example
@@ -369,7 +487,9 @@ impl RequestBuilder {
         // TODO: de-duplicate this and apply_filter()
         assert!(!route::is_set(), "nested test filter calls");
 
-        let route = Route::new(self.req, self.remote_addr);
+        let jar = self.jar.clone();
+        let uri = self.req.uri().clone();
+        let route = self.into_route();
         let mut fut = Box::pin(
             route::set(&route, move || f.filter(crate::filter::Internal)).then(|result| {
                 let res = match result {
@@ -387,7 +507,11 @@ impl RequestBuilder {
 
         let fut = future::poll_fn(move |cx| route::set(&route, || fut.as_mut().poll(cx)));
 
-        fut.await.expect("reply shouldn't fail")
+        let res = fut.await.expect("reply shouldn't fail");
+        if let Some(jar) = jar {
+            jar.store_cookies(&uri, res.headers());
+        }
+        res
     }
 
     fn apply_filter<F>(self, f: &F) -> impl Future<Output = Result<F::Extract, F::Error>>
@@ -399,12 +523,25 @@ impl RequestBuilder {
     {
         assert!(!route::is_set(), "nested test filter calls");
 
-        let route = Route::new(self.req, self.remote_addr);
+        let route = self.into_route();
         let mut fut = Box::pin(route::set(&route, move || {
             f.filter(crate::filter::Internal)
         }));
         future::poll_fn(move |cx| route::set(&route, || fut.as_mut().poll(cx)))
     }
+
+    fn into_route(self) -> RefCell<Route> {
+        let remote_addr = self.remote_addr;
+        Route::new(self.into_request(), remote_addr)
+    }
+
+    fn into_request(mut self) -> Request {
+        if let Some(ref jar) = self.jar {
+            let uri = self.req.uri().clone();
+            jar.add_cookies(&uri, self.req.headers_mut());
+        }
+        self.req
+    }
 }
 
 #[cfg(feature = "websocket")]
@@ -453,9 +590,56 @@ impl WsBuilder {
         }
     }
 
+    /// Offer the subprotocol `protocol` in the `sec-websocket-protocol`
+    /// header of the handshake.
+    ///
+    /// Calling it again offers several subprotocols, in order of
+    /// preference.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::ws()
+    ///     .protocol("graphql-transport-ws")
+    ///     .protocol("graphql-ws");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if `protocol` is not a valid `HeaderValue`.
+    pub fn protocol(self, protocol: &str) -> Self {
+        let offered = match self.req.req.headers().get("sec-websocket-protocol") {
+            Some(offered) => format!(
+                "{}, {}",
+                offered.to_str().expect("offered protocols are strings"),
+                protocol
+            ),
+            None => protocol.to_owned(),
+        };
+        self.header("sec-websocket-protocol", offered)
+    }
+
+    /// Set the `origin` header of the handshake, as browsers do.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::ws()
+    ///     .origin("https://example.com");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if `origin` is not a valid `HeaderValue`.
+    pub fn origin(self, origin: &str) -> Self {
+        self.header("origin", origin)
+    }
+
     /// Execute this Websocket request against the provided filter.
     ///
-    /// If the handshake succeeds, returns a `WsClient`.
+    /// If the handshake succeeds, returns a `WsClient`. Otherwise, the
+    /// response of the server, if there was one, is available from
+    /// [`WsError::response`].
     ///
     /// # Example
     ///
@@ -515,18 +699,35 @@ impl WsBuilder {
             // let mut rt = current_thread::Runtime::new().unwrap();
             tokio::spawn(srv);
 
-            let upgrade = ::hyper::Client::builder()
+            let res = ::hyper::Client::builder()
                 .build(AddrConnect(addr))
                 .request(req)
-                .and_then(|res| hyper::upgrade::on(res));
+                .await;
+            let res = match res {
+                Ok(res) => res,
+                Err(err) => {
+                    let _ = upgraded_tx.send(Err(WsError::new(err)));
+                    return;
+                }
+            };
 
-            let upgraded = match upgrade.await {
+            let mut head = Response::new(());
+            *head.status_mut() = res.status();
+            *head.version_mut() = res.version();
+            *head.headers_mut() = res.headers().clone();
+            if res.status() != http::StatusCode::SWITCHING_PROTOCOLS {
+                let err = WsError::new(format!("handshake replied with {}", res.status()));
+                let _ = upgraded_tx.send(Err(err.with_response(head)));
+                return;
+            }
+
+            let upgraded = match hyper::upgrade::on(res).await {
                 Ok(up) => {
-                    let _ = upgraded_tx.send(Ok(()));
+                    let _ = upgraded_tx.send(Ok(head));
                     up
                 }
                 Err(err) => {
-                    let _ = upgraded_tx.send(Err(err));
+                    let _ = upgraded_tx.send(Err(WsError::new(err).with_response(head)));
                     return;
                 }
             };
@@ -554,11 +755,12 @@ impl WsBuilder {
         });
 
         match upgraded_rx.await {
-            Ok(Ok(())) => Ok(WsClient {
+            Ok(Ok(response)) => Ok(WsClient {
                 tx: wr_tx,
                 rx: rd_rx,
+                response,
             }),
-            Ok(Err(err)) => Err(WsError::new(err)),
+            Ok(Err(err)) => Err(err),
             Err(_canceled) => panic!("websocket handshake thread panicked"),
         }
     }
@@ -588,6 +790,20 @@ impl WsClient {
             })
     }
 
+    /// The response of the server to the handshake, without its body.
+    pub fn handshake_response(&self) -> &Response<()> {
+        &self.response
+    }
+
+    /// The subprotocol the server selected, from the
+    /// `sec-websocket-protocol` header of its handshake response.
+    pub fn protocol(&self) -> Option<&str> {
+        self.response
+            .headers()
+            .get("sec-websocket-protocol")
+            .and_then(|protocol| protocol.to_str().ok())
+    }
+
     /// Assert the server has closed the connection.
     pub async fn recv_closed(&mut self) -> Result<(), WsError> {
         self.rx
@@ -618,8 +834,20 @@ impl WsError {
     fn new<E: Into<Box<dyn StdError + Send + Sync>>>(cause: E) -> Self {
         WsError {
             cause: cause.into(),
+            response: None,
         }
     }
+
+    fn with_response(mut self, response: Response<()>) -> Self {
+        self.response = Some(Box::new(response));
+        self
+    }
+
+    /// The response of the server to a handshake that failed, without its
+    /// body, if it replied.
+    pub fn response(&self) -> Option<&Response<()>> {
+        self.response.as_deref()
+    }
 }
 
 impl fmt::Display for WsError {
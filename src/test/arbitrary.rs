@@ -0,0 +1,451 @@
+//! Randomized requests, to check that filters cope with whatever clients
+//! send.
+//!
+//! [`request`] makes a request from a seed, picking from valid requests and
+//! their edge cases: unusual methods, odd or broken percent-encodings, very
+//! long paths, duplicated headers with oddly cased values, and chunked or
+//! malformed bodies. The same seed always makes the same request, so a
+//! failure can be reproduced, and seeds can come from a property testing
+//! library such as `proptest`.
+//!
+//! [`check`] applies a filter to a request, failing if it panics or doesn't
+//! finish, and [`check_all`] does so for many requests.
+//!
+//! # Example
+//!
+//! ```
+//! # async fn run() {
+//! use warp::Filter;
+//!
+//! let routes = warp::path!("users" / u32)
+//!     .and(warp::query::raw())
+//!     .map(|id, query| format!("{} {}", id, query));
+//!
+//! warp::test::arbitrary::check_all(&routes, 0x5eed, 500).await;
+//! # }
+//! ```
+//!
+//! With `proptest`, the seed can be generated instead:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn never_panics(seed: u64) {
+//!         let rt = tokio::runtime::Runtime::new().unwrap();
+//!         rt.block_on(warp::test::arbitrary::check(&routes(), request(seed))).unwrap();
+//!     }
+//! }
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{future, stream};
+use http::header::{HeaderName, HeaderValue};
+use http::Response;
+use hyper::Body;
+
+use super::RequestBuilder;
+use crate::filter::Filter;
+use crate::filters::catch_panic::{catching, install_hook, Panic};
+use crate::reject::IsReject;
+use crate::reply::Reply;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const METHODS: &[&str] = &[
+    "GET", "GET", "POST", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+    "PROPFIND", "get", "X-CUSTOM",
+];
+
+const SEGMENTS: &[&str] = &[
+    "a",
+    "users",
+    "42",
+    "0",
+    "-1",
+    "18446744073709551616",
+    "1.5",
+    "%20",
+    "%2F",
+    "%25",
+    "%",
+    "%2",
+    "%zz",
+    "%E9",
+    "%F0%9F%92%96",
+    "%00",
+    "caf%C3%A9",
+    "..",
+    ".",
+    "",
+    "~user",
+    "a+b",
+    "UPPER",
+    "file.json",
+    ";param=1",
+    "!$&'()*,=",
+    ":@",
+];
+
+const QUERY: &[&str] = &[
+    "a", "1", "", "%20", "+", "%", "%zz", "a%3Db", "%26", "[]", "a[b]", "true", "null", "-0",
+];
+
+const HEADERS: &[(&str, &[&str])] = &[
+    (
+        "content-type",
+        &[
+            "application/json",
+            "application/json; charset=utf-8",
+            "text/plain",
+            "application/x-www-form-urlencoded",
+            "multipart/form-data; boundary=x",
+            "multipart/form-data",
+            "application/json;;",
+            "*/*",
+            "",
+        ],
+    ),
+    (
+        "accept",
+        &[
+            "*/*",
+            "text/html, application/json;q=0.9",
+            "application/json;q=abc",
+            "text/event-stream",
+            "",
+        ],
+    ),
+    (
+        "authorization",
+        &[
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+            "basic !!!",
+            "Bearer ",
+            "bearer token",
+            "Digest x",
+        ],
+    ),
+    (
+        "cookie",
+        &["a=b", "a=b; c=d", "=", ";;", "a=\"b\"", "a=%zz"],
+    ),
+    (
+        "host",
+        &["example.com", "example.com:8080", "[::1]:80", "", "a..b"],
+    ),
+    (
+        "accept-encoding",
+        &["gzip", "gzip, br;q=0", "identity;q=0", "*", "x;q=2"],
+    ),
+    ("content-encoding", &["gzip", "br", "identity", "unknown"]),
+    ("origin", &["https://example.com", "null", "file://"]),
+    (
+        "range",
+        &["bytes=0-1", "bytes=-5", "bytes=5-1", "items=0-1", "bytes="],
+    ),
+    ("if-none-match", &["\"abc\"", "W/\"abc\"", "*", "abc"]),
+    (
+        "if-modified-since",
+        &["Sun, 06 Nov 1994 08:49:37 GMT", "yesterday"],
+    ),
+    ("x-forwarded-for", &["1.2.3.4", "1.2.3.4, ::1", "garbage"]),
+    ("connection", &["upgrade", "keep-alive, upgrade", "close"]),
+    ("upgrade", &["websocket", "h2c"]),
+    (
+        "user-agent",
+        &["curl/8.0", "", "Mozilla/5.0 (X11; Linux x86_64)"],
+    ),
+    ("x-request-id", &["1", "", "not a uuid"]),
+];
+
+const BODIES: &[&str] = &[
+    "{}",
+    "[]",
+    "{\"a\":1}",
+    "{\"a\":",
+    "null",
+    "1e999",
+    "\"\\u0000\"",
+    "a=1&b=2",
+    "a=%zz&=",
+    "--x\r\ncontent-disposition: form-data; name=\"a\"\r\n\r\nb\r\n--x--\r\n",
+    "--x\r\n",
+];
+
+/// Makes the request for `seed`.
+///
+/// The same seed always makes the same request.
+pub fn request(seed: u64) -> RequestBuilder {
+    let mut rng = Rng::new(seed);
+
+    let mut path = String::new();
+    let segments = if rng.chance(20) {
+        100 + rng.below(200)
+    } else {
+        rng.below(6)
+    };
+    for _ in 0..segments {
+        path.push('/');
+        for _ in 0..1 + rng.below(2) {
+            path.push_str(rng.pick(SEGMENTS));
+        }
+    }
+    if rng.chance(20) {
+        path.push('/');
+        path.push_str(&"a".repeat(2000 + rng.below(4000)));
+    }
+    if path.is_empty() || rng.chance(8) {
+        path.push('/');
+    }
+    if rng.chance(2) {
+        path.push('?');
+        for i in 0..rng.below(4) {
+            if i > 0 {
+                path.push('&');
+            }
+            path.push_str(rng.pick(QUERY));
+            if !rng.chance(4) {
+                path.push('=');
+                path.push_str(rng.pick(QUERY));
+            }
+        }
+    }
+    if path.parse::<http::Uri>().is_err() {
+        path = String::from("/");
+    }
+
+    let mut req = super::request().method(rng.pick(METHODS)).path(&path);
+
+    for _ in 0..rng.below(6) {
+        let (name, values) = rng.pick(HEADERS);
+        let value = rng.pick(values);
+        let value = odd_case(&mut rng, value);
+        let value = if rng.chance(30) {
+            // Not UTF-8, but a valid header value.
+            HeaderValue::from_bytes(b"\x80caf\xe9").expect("obs-text is valid")
+        } else {
+            HeaderValue::from_str(&value).expect("header values are valid")
+        };
+        // Appended, so headers may be repeated.
+        req.req
+            .headers_mut()
+            .append(HeaderName::from_static(name), value);
+    }
+
+    let body = match rng.below(6) {
+        0 | 1 => return req,
+        2 => rng.pick(BODIES).as_bytes().to_vec(),
+        3 => (0..rng.below(64)).map(|_| rng.next() as u8).collect(),
+        4 if rng.chance(10) => vec![b'a'; 64 * 1024],
+        _ => rng.pick(BODIES).as_bytes().to_vec(),
+    };
+    if rng.chance(3) {
+        let mut chunks = Vec::new();
+        let mut rest = &body[..];
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at(1 + rng.below(rest.len()));
+            chunks.push(Ok::<_, io::Error>(Bytes::copy_from_slice(chunk)));
+            rest = tail;
+        }
+        *req.req.body_mut() = Body::wrap_stream(stream::iter(chunks));
+        req.req
+            .headers_mut()
+            .insert("transfer-encoding", HeaderValue::from_static("chunked"));
+        req
+    } else {
+        req.body(body)
+    }
+}
+
+/// Makes `cases` requests, starting from `seed`.
+pub fn requests(seed: u64, cases: u32) -> impl Iterator<Item = RequestBuilder> {
+    (0..cases).map(move |case| request(case_seed(seed, case)))
+}
+
+/// Applies `filter` to `req`, checking that it finishes without panicking,
+/// and returns its reply, or the reply of its rejection.
+///
+/// Filters get 5 seconds to finish.
+pub async fn check<F>(filter: &F, req: RequestBuilder) -> Result<Response<Bytes>, Failure>
+where
+    F: Filter + 'static,
+    F::Extract: Reply + Send,
+    F::Error: IsReject + Send,
+{
+    install_hook();
+
+    let request = format!(
+        "{} {} {:?}",
+        req.req.method(),
+        req.req.uri(),
+        req.req.headers()
+    );
+    let fut = req.reply(filter);
+    futures::pin_mut!(fut);
+    let caught = future::poll_fn(|cx| match catching(|| fut.as_mut().poll(cx)) {
+        Ok(poll) => poll.map(Ok),
+        Err(payload) => std::task::Poll::Ready(Err(payload)),
+    });
+
+    match tokio::time::timeout(TIMEOUT, caught).await {
+        Ok(Ok(res)) => Ok(res),
+        Ok(Err(payload)) => Err(Failure {
+            request,
+            panic: Some(Panic::new(payload)),
+            seed: None,
+        }),
+        Err(_elapsed) => Err(Failure {
+            request,
+            panic: None,
+            seed: None,
+        }),
+    }
+}
+
+/// Checks `filter` with `cases` requests made from `seed`, like
+/// [`check`].
+///
+/// # Panics
+///
+/// Panics on the first failure, with the seed making the request that
+/// caused it, to reproduce it with [`request`].
+pub async fn check_all<F>(filter: &F, seed: u64, cases: u32)
+where
+    F: Filter + 'static,
+    F::Extract: Reply + Send,
+    F::Error: IsReject + Send,
+{
+    for case in 0..cases {
+        let seed = case_seed(seed, case);
+        if let Err(mut failure) = check(filter, request(seed)).await {
+            failure.seed = Some(seed);
+            panic!("{}", failure);
+        }
+    }
+}
+
+/// A request that made a filter panic, or not finish, found by [`check`].
+pub struct Failure {
+    request: String,
+    panic: Option<Panic>,
+    seed: Option<u64>,
+}
+
+impl Failure {
+    /// A description of the request.
+    pub fn request(&self) -> &str {
+        &self.request
+    }
+
+    /// The panic, if the filter panicked.
+    pub fn panic(&self) -> Option<&Panic> {
+        self.panic.as_ref()
+    }
+
+    /// Whether the filter didn't finish in time.
+    pub fn is_timeout(&self) -> bool {
+        self.panic.is_none()
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.panic {
+            Some(ref panic) => write!(
+                f,
+                "filter panicked: {}",
+                panic.message().unwrap_or("Box<dyn Any>")
+            )?,
+            None => write!(f, "filter didn't finish within {:?}", TIMEOUT)?,
+        }
+        write!(f, "\nrequest: {}", self.request)?;
+        if let Some(seed) = self.seed {
+            write!(
+                f,
+                "\nreproduce with warp::test::arbitrary::request({:#x})",
+                seed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Failure")
+            .field("request", &self.request)
+            .field("panic", &self.panic)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl StdError for Failure {}
+
+fn case_seed(seed: u64, case: u32) -> u64 {
+    splitmix(seed.wrapping_add(case as u64))
+}
+
+fn splitmix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Changes the case of letters, since values like media types and auth
+// schemes are case-insensitive, but handlers often forget it.
+fn odd_case(rng: &mut Rng, value: &str) -> String {
+    match rng.below(4) {
+        0 => value.to_ascii_uppercase(),
+        1 => value
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i % 2 == 0 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect(),
+        _ => value.to_owned(),
+    }
+}
+
+// A xorshift generator, so requests only depend on their seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // xorshift gets stuck at zero.
+        Rng(splitmix(seed) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
@@ -0,0 +1,222 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use bytes::Bytes;
+use http::header::HeaderName;
+use http::{Response, StatusCode};
+
+use crate::reject::{IsReject, Rejection};
+
+/// Assertions on the results of [`filter`](super::RequestBuilder::filter).
+pub trait ExpectRejection {
+    /// Asserts that the filter rejected the request with a cause of type
+    /// `E`, returning the rejection, which derefs to the cause.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter didn't reject, or rejected with other causes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() {
+    /// use warp::Filter;
+    ///
+    /// #[derive(Debug)]
+    /// struct Challenge {
+    ///     realm: &'static str,
+    /// }
+    ///
+    /// impl warp::reject::Reject for Challenge {}
+    ///
+    /// let route = warp::any().and_then(|| async {
+    ///     Err::<String, _>(warp::reject::custom(Challenge { realm: "admin" }))
+    /// });
+    ///
+    /// use warp::test::ExpectRejection;
+    ///
+    /// let challenge = warp::test::request()
+    ///     .filter(&route)
+    ///     .await
+    ///     .expect_rejection::<Challenge>();
+    /// assert_eq!(challenge.realm, "admin");
+    /// # }
+    /// ```
+    fn expect_rejection<E: 'static>(self) -> ExpectedRejection<E>;
+
+    /// Asserts that no filter matched the request, which would reply with
+    /// `404 Not Found`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter didn't reject, or rejected with a cause.
+    fn expect_not_found(self);
+}
+
+impl<T> ExpectRejection for Result<T, Rejection> {
+    fn expect_rejection<E: 'static>(self) -> ExpectedRejection<E> {
+        let rejection = match self {
+            Err(rejection) => rejection,
+            Ok(_) => panic!(
+                "expected a rejection with {}, but the filter extracted {}",
+                std::any::type_name::<E>(),
+                std::any::type_name::<T>(),
+            ),
+        };
+        if rejection.find::<E>().is_none() {
+            panic!(
+                "expected a rejection with {}, but got {:?}",
+                std::any::type_name::<E>(),
+                rejection,
+            );
+        }
+        ExpectedRejection {
+            rejection,
+            _cause: PhantomData,
+        }
+    }
+
+    fn expect_not_found(self) {
+        match self {
+            Err(rejection) if rejection.is_not_found() => {}
+            Err(rejection) => panic!("expected a not found rejection, but got {:?}", rejection),
+            Ok(_) => panic!(
+                "expected a not found rejection, but the filter extracted {}",
+                std::any::type_name::<T>(),
+            ),
+        }
+    }
+}
+
+/// A [`Rejection`] known to have a cause of type `E`, returned by
+/// [`expect_rejection`](ExpectRejection::expect_rejection).
+///
+/// It derefs to the cause.
+pub struct ExpectedRejection<E> {
+    rejection: Rejection,
+    _cause: PhantomData<fn() -> E>,
+}
+
+impl<E: 'static> ExpectedRejection<E> {
+    /// The status of the reply the rejection would make, if it's not
+    /// recovered.
+    pub fn status(&self) -> StatusCode {
+        self.rejection.status()
+    }
+
+    /// Returns the whole rejection.
+    pub fn into_rejection(self) -> Rejection {
+        self.rejection
+    }
+}
+
+impl<E: 'static> Deref for ExpectedRejection<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.rejection.find().expect("checked by expect_rejection")
+    }
+}
+
+impl<E> fmt::Debug for ExpectedRejection<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.rejection.fmt(f)
+    }
+}
+
+/// Assertions on the responses of [`reply`](super::RequestBuilder::reply),
+/// such as the replies made by recovering from rejections.
+///
+/// The assertions return the response, so they can be chained.
+///
+/// # Example
+///
+/// ```
+/// # async fn run() {
+/// use warp::Filter;
+/// use warp::test::AssertReply;
+///
+/// let route = warp::path("old").map(|| {
+///     warp::redirect::permanent(warp::http::Uri::from_static("/new"))
+/// });
+///
+/// warp::test::request()
+///     .path("/old")
+///     .reply(&route)
+///     .await
+///     .assert_status(301)
+///     .assert_header("location", "/new");
+/// # }
+/// ```
+pub trait AssertReply {
+    /// Asserts that the response has the status `status`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` isn't a valid status code, or if the response has
+    /// another status.
+    fn assert_status<S>(&self, status: S) -> &Self
+    where
+        StatusCode: TryFrom<S>;
+
+    /// Asserts that the response has the header `name`, with the value
+    /// `value`.
+    ///
+    /// If the header has several values, one of them must match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name, or if the response
+    /// doesn't have the header value.
+    fn assert_header(&self, name: &str, value: &str) -> &Self;
+
+    /// Asserts that the response doesn't have the header `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name, or if the response has
+    /// the header.
+    fn assert_no_header(&self, name: &str) -> &Self;
+}
+
+impl AssertReply for Response<Bytes> {
+    fn assert_status<S>(&self, status: S) -> &Self
+    where
+        StatusCode: TryFrom<S>,
+    {
+        let status = StatusCode::try_from(status)
+            .map_err(|_| ())
+            .expect("invalid status code");
+        assert!(
+            self.status() == status,
+            "expected status {}, but got {} with body {:?}",
+            status,
+            self.status(),
+            String::from_utf8_lossy(self.body()),
+        );
+        self
+    }
+
+    fn assert_header(&self, name: &str, value: &str) -> &Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        let values = self.headers().get_all(&name);
+        assert!(
+            values.iter().any(|v| v == value),
+            "expected header {}: {:?}, but got {:?}",
+            name,
+            value,
+            values.iter().collect::<Vec<_>>(),
+        );
+        self
+    }
+
+    fn assert_no_header(&self, name: &str) -> &Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        if let Some(value) = self.headers().get(&name) {
+            panic!("expected no header {}, but got {:?}", name, value);
+        }
+        self
+    }
+}
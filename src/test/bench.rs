@@ -0,0 +1,123 @@
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::future;
+use http::{HeaderMap, Method, Uri, Version};
+use hyper::Body;
+use tokio::runtime::{Builder, Runtime};
+
+use super::RequestBuilder;
+use crate::filter::{Filter, Internal};
+use crate::route::{self, Route};
+use crate::Request;
+
+/// Prepares `req` to be handled by `filter` repeatedly, for benchmarks.
+///
+/// The request is parsed, and a runtime to drive the filter is built, once,
+/// so that each [`Bench::run`] only measures the filter. Unlike
+/// [`RequestBuilder::filter`](super::RequestBuilder::filter), nothing is
+/// logged or boxed along the way.
+///
+/// Extensions of the request aren't kept, since they can't be cloned for
+/// each run.
+///
+/// # Panics
+///
+/// Panics if called from within a Tokio runtime, since it has its own.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let routes = warp::path!("users" / u32).map(|id| format!("user {}", id));
+/// let bench = warp::test::bench(routes, warp::test::request().path("/users/7"));
+///
+/// // With criterion, this would be `b.iter(|| bench.run())`.
+/// for _ in 0..1_000 {
+///     assert!(bench.run().is_ok());
+/// }
+/// println!("{:?} per request", bench.time(1_000));
+/// ```
+pub fn bench<F: Filter>(filter: F, req: RequestBuilder) -> Bench<F> {
+    let rt = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("bench runtime");
+    let remote_addr = req.remote_addr;
+    let (head, body) = req.into_request().into_parts();
+    let body = rt
+        .block_on(hyper::body::to_bytes(body))
+        .expect("test request body");
+    Bench {
+        filter,
+        method: head.method,
+        uri: head.uri,
+        version: head.version,
+        headers: head.headers,
+        body,
+        remote_addr,
+        rt,
+    }
+}
+
+/// A filter and request prepared for benchmarks, made by [`bench()`].
+pub struct Bench<F> {
+    filter: F,
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+    remote_addr: Option<SocketAddr>,
+    rt: Runtime,
+}
+
+impl<F: Filter> Bench<F> {
+    /// Applies the filter to a copy of the request, driving it to
+    /// completion.
+    pub fn run(&self) -> Result<F::Extract, F::Error> {
+        let mut req = Request::new(Body::from(self.body.clone()));
+        *req.method_mut() = self.method.clone();
+        *req.uri_mut() = self.uri.clone();
+        *req.version_mut() = self.version;
+        *req.headers_mut() = self.headers.clone();
+
+        let route = Route::new(req, self.remote_addr);
+        let fut = route::set(&route, || self.filter.filter(Internal));
+        futures::pin_mut!(fut);
+        self.rt.block_on(future::poll_fn(|cx| {
+            route::set(&route, || fut.as_mut().poll(cx))
+        }))
+    }
+
+    /// Runs the filter `iterations` times, returning the mean time of a
+    /// run.
+    ///
+    /// This is a rough measure, for when a benchmarking harness isn't at
+    /// hand.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iterations` is `0`.
+    pub fn time(&self, iterations: u32) -> Duration {
+        assert!(iterations > 0, "bench needs at least 1 iteration");
+        let start = Instant::now();
+        for _ in 0..iterations {
+            let _ = self.run();
+        }
+        start.elapsed() / iterations
+    }
+}
+
+impl<F> fmt::Debug for Bench<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Bench")
+            .field("method", &self.method)
+            .field("uri", &self.uri)
+            .finish()
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::Stream;
+use tokio::time::Sleep;
+
+/// A request body sent in chunks, with control over when each chunk
+/// arrives, for [`RequestBuilder::body_stream`](super::RequestBuilder::body_stream).
+///
+/// Delays are measured by Tokio's timer, so tests using
+/// `tokio::time::pause` don't have to wait for them.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::test::ChunkedBody;
+///
+/// // A slow upload, which never finishes.
+/// let body = ChunkedBody::new()
+///     .chunk("hello")
+///     .delay(Duration::from_secs(1))
+///     .chunk(" world")
+///     .stall();
+///
+/// let req = warp::test::request().method("POST").body_stream(body);
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct ChunkedBody {
+    steps: VecDeque<Step>,
+    end: End,
+    repeat: Vec<Step>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+#[derive(Clone, Debug)]
+enum Step {
+    Chunk(Bytes),
+    Delay(Duration),
+}
+
+#[derive(Debug, PartialEq)]
+enum End {
+    Finish,
+    Stall,
+    Repeat,
+}
+
+impl ChunkedBody {
+    /// Creates an empty body.
+    pub fn new() -> Self {
+        ChunkedBody {
+            steps: VecDeque::new(),
+            end: End::Finish,
+            repeat: Vec::new(),
+            sleep: None,
+        }
+    }
+
+    /// Sends the chunk `bytes`.
+    pub fn chunk(mut self, bytes: impl Into<Bytes>) -> Self {
+        self.steps.push_back(Step::Chunk(bytes.into()));
+        self
+    }
+
+    /// Waits for `duration` before sending the following chunks.
+    pub fn delay(mut self, duration: Duration) -> Self {
+        self.steps.push_back(Step::Delay(duration));
+        self
+    }
+
+    /// Never ends the body once the chunks are sent, like a client that
+    /// stopped sending without closing the connection.
+    pub fn stall(mut self) -> Self {
+        self.end = End::Stall;
+        self
+    }
+
+    /// Sends the chunks and delays again, forever, making a body without
+    /// an end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no chunk was added.
+    pub fn repeat(mut self) -> Self {
+        assert!(
+            self.steps.iter().any(|step| matches!(step, Step::Chunk(_))),
+            "a repeated body needs a chunk"
+        );
+        self.repeat = self.steps.iter().cloned().collect();
+        self.end = End::Repeat;
+        self
+    }
+}
+
+impl Default for ChunkedBody {
+    fn default() -> Self {
+        ChunkedBody::new()
+    }
+}
+
+impl Stream for ChunkedBody {
+    type Item = Bytes;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        loop {
+            if let Some(sleep) = self.sleep.as_mut() {
+                futures::ready!(sleep.as_mut().poll(cx));
+                self.sleep = None;
+            }
+            match self.steps.pop_front() {
+                Some(Step::Chunk(bytes)) => return Poll::Ready(Some(bytes)),
+                Some(Step::Delay(duration)) => {
+                    self.sleep = Some(Box::pin(tokio::time::sleep(duration)));
+                }
+                None => match self.end {
+                    End::Finish => return Poll::Ready(None),
+                    End::Stall => return Poll::Pending,
+                    End::Repeat => {
+                        let steps = self.repeat.clone();
+                        self.steps.extend(steps);
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ChunkedBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChunkedBody")
+            .field("steps", &self.steps)
+            .field("end", &self.end)
+            .finish()
+    }
+}
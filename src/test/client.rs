@@ -0,0 +1,219 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use headers::{Expires, Header};
+use http::header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE};
+use http::Uri;
+
+use super::RequestBuilder;
+
+/// A test client that keeps cookies between requests, like a browser.
+///
+/// Cookies set by `Set-Cookie` headers in replies are stored, and sent
+/// along with later requests made from this client whose path matches the
+/// cookie's `Path`. Expired cookies are removed. `Secure` cookies are only
+/// sent with secure requests, see [`Client::secure`].
+///
+/// Cookies are only stored from [`reply`](RequestBuilder::reply), since
+/// the other ways of applying a filter don't make a response.
+///
+/// # Example
+///
+/// ```
+/// # async fn run() {
+/// use warp::Filter;
+///
+/// let login = warp::path("login").map(|| {
+///     warp::reply::with_header("ok", "set-cookie", "session=abc; Path=/")
+/// });
+/// let me = warp::path("me").and(warp::cookie::<String>("session"));
+///
+/// let client = warp::test::Client::new();
+/// client.request().path("/login").reply(&login).await;
+///
+/// let session = client.request().path("/me").filter(&me).await.unwrap();
+/// assert_eq!(session, "abc");
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Client {
+    jar: CookieJar,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct CookieJar {
+    cookies: Arc<Mutex<Vec<StoredCookie>>>,
+    secure: bool,
+}
+
+#[derive(Debug)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    path: String,
+    secure: bool,
+    expires: Option<SystemTime>,
+}
+
+impl Client {
+    /// Creates a client with no cookies, making secure requests.
+    pub fn new() -> Client {
+        Client {
+            jar: CookieJar {
+                cookies: Arc::default(),
+                secure: true,
+            },
+        }
+    }
+
+    /// Sets whether requests from this client are secure, as if they were
+    /// made over HTTPS, so `Secure` cookies are sent with them.
+    ///
+    /// The default is `true`. Requests with an absolute URI as their path
+    /// are secure if its scheme is `https`, regardless of this setting.
+    pub fn secure(mut self, secure: bool) -> Client {
+        self.jar.secure = secure;
+        self
+    }
+
+    /// Starts a new `RequestBuilder` that sends and stores the cookies of
+    /// this client.
+    pub fn request(&self) -> RequestBuilder {
+        let mut req = super::request();
+        req.jar = Some(self.jar.clone());
+        req
+    }
+
+    /// Returns the value of the stored cookie `name`, if there is one that
+    /// hasn't expired.
+    ///
+    /// If cookies with this name were set for several paths, the one with
+    /// the longest path is returned.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.jar
+            .lock()
+            .iter()
+            .filter(|cookie| cookie.name == name)
+            .max_by_key(|cookie| cookie.path.len())
+            .map(|cookie| cookie.value.clone())
+    }
+
+    /// Removes all stored cookies.
+    pub fn clear_cookies(&self) {
+        self.jar.lock().clear();
+    }
+}
+
+impl Default for Client {
+    fn default() -> Client {
+        Client::new()
+    }
+}
+
+impl CookieJar {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StoredCookie>> {
+        let mut cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        let now = SystemTime::now();
+        cookies.retain(|cookie| cookie.expires.map_or(true, |at| at > now));
+        cookies
+    }
+
+    /// Adds the cookies matching `uri` to the `cookie` header of a request.
+    pub(super) fn add_cookies(&self, uri: &Uri, headers: &mut HeaderMap) {
+        let secure = match uri.scheme_str() {
+            Some(scheme) => scheme == "https",
+            None => self.secure,
+        };
+        let path = uri.path();
+
+        let mut cookies = self.lock();
+        // Cookies with longer paths are listed first.
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let pairs = cookies
+            .iter()
+            .filter(|cookie| secure || !cookie.secure)
+            .filter(|cookie| path_matches(path, &cookie.path))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>();
+        if pairs.is_empty() {
+            return;
+        }
+
+        let mut header = pairs.join("; ");
+        if let Some(existing) = headers.get(COOKIE).and_then(|v| v.to_str().ok()) {
+            header = format!("{}; {}", existing, header);
+        }
+        let value = HeaderValue::from_str(&header).expect("stored cookies are valid headers");
+        headers.insert(COOKIE, value);
+    }
+
+    /// Stores the cookies set in the headers of a response to `uri`.
+    pub(super) fn store_cookies(&self, uri: &Uri, headers: &HeaderMap) {
+        let mut cookies = self.lock();
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            let cookie = match set_cookie.to_str().ok().and_then(|v| parse(uri, v)) {
+                Some(cookie) => cookie,
+                None => continue,
+            };
+            cookies.retain(|c| c.name != cookie.name || c.path != cookie.path);
+            cookies.push(cookie);
+        }
+    }
+}
+
+fn parse(uri: &Uri, set_cookie: &str) -> Option<StoredCookie> {
+    let mut attrs = set_cookie.split(';');
+    let (name, value) = attrs.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = StoredCookie {
+        name: name.to_owned(),
+        value: value.trim().to_owned(),
+        path: default_path(uri.path()).to_owned(),
+        secure: false,
+        expires: None,
+    };
+    let mut max_age = None;
+    for attr in attrs {
+        let (key, value) = attr.split_once('=').unwrap_or((attr, ""));
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "path" if value.starts_with('/') => cookie.path = value.to_owned(),
+            "secure" => cookie.secure = true,
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            "expires" => {
+                cookie.expires = HeaderValue::from_str(value)
+                    .ok()
+                    .and_then(|v| Expires::decode(&mut std::iter::once(&v)).ok())
+                    .map(SystemTime::from);
+            }
+            _ => {}
+        }
+    }
+    // Max-Age takes precedence over Expires.
+    if let Some(max_age) = max_age {
+        cookie.expires = Some(if max_age <= 0 {
+            SystemTime::UNIX_EPOCH
+        } else {
+            SystemTime::now() + Duration::from_secs(max_age as u64)
+        });
+    }
+    Some(cookie)
+}
+
+// The directory of the request path, as defined in RFC 6265, section 5.1.4.
+fn default_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(i) => &path[..i],
+    }
+}
+
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
@@ -0,0 +1,149 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_compression::tokio::bufread::{
+    BrotliDecoder, BrotliEncoder, DeflateDecoder, DeflateEncoder, GzipDecoder, GzipEncoder,
+};
+use bytes::Bytes;
+use futures::task::noop_waker_ref;
+use http::header::{HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH};
+use http::Response;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::RequestBuilder;
+use crate::filter::Filter;
+use crate::reject::IsReject;
+use crate::reply::Reply;
+
+impl RequestBuilder {
+    /// Set the body of this request to `body` compressed with gzip, and add
+    /// `content-encoding: gzip`.
+    ///
+    /// *This method requires the `"compression"` feature.*
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::request()
+    ///     .method("POST")
+    ///     .gzip_body(r#"{"name":"warp"}"#);
+    /// ```
+    pub fn gzip_body(self, body: impl AsRef<[u8]>) -> Self {
+        let body = read_now(GzipEncoder::new(body.as_ref())).expect("gzip encoding");
+        self.compressed_body("gzip", body)
+    }
+
+    /// Set the body of this request to `body` compressed with deflate, and
+    /// add `content-encoding: deflate`.
+    ///
+    /// *This method requires the `"compression"` feature.*
+    pub fn deflate_body(self, body: impl AsRef<[u8]>) -> Self {
+        let body = read_now(DeflateEncoder::new(body.as_ref())).expect("deflate encoding");
+        self.compressed_body("deflate", body)
+    }
+
+    /// Set the body of this request to `body` compressed with brotli, and
+    /// add `content-encoding: br`.
+    ///
+    /// *This method requires the `"compression"` feature.*
+    pub fn brotli_body(self, body: impl AsRef<[u8]>) -> Self {
+        let body = read_now(BrotliEncoder::new(body.as_ref())).expect("brotli encoding");
+        self.compressed_body("br", body)
+    }
+
+    fn compressed_body(self, encoding: &'static str, body: Vec<u8>) -> Self {
+        self.body(body).header("content-encoding", encoding)
+    }
+
+    /// Returns a `Response` provided by the `Filter`, like
+    /// [`reply`](RequestBuilder::reply), with its body decompressed.
+    ///
+    /// The body is decoded following the `content-encoding` header, which is
+    /// then removed, so assertions can be made on the payload of routes
+    /// wrapped in [`warp::compression`](crate::compression). To check that
+    /// the response was compressed, use `reply` instead.
+    ///
+    /// *This method requires the `"compression"` feature.*
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body can't be decoded, or if it uses an unsupported
+    /// encoding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() {
+    /// use warp::Filter;
+    ///
+    /// let route = warp::any()
+    ///     .map(|| "hello")
+    ///     .with(warp::compression::gzip());
+    ///
+    /// let res = warp::test::request()
+    ///     .reply_decompressed(&route)
+    ///     .await;
+    /// assert_eq!(res.body(), "hello");
+    /// # }
+    /// ```
+    pub async fn reply_decompressed<F>(self, f: &F) -> Response<Bytes>
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+        F::Error: IsReject + Send,
+    {
+        decompress(self.reply(f).await)
+    }
+}
+
+fn decompress(res: Response<Bytes>) -> Response<Bytes> {
+    let (mut parts, mut body) = res.into_parts();
+    let encodings = parts
+        .headers
+        .get_all(CONTENT_ENCODING)
+        .iter()
+        .map(|value| value.to_str().expect("content-encoding is not a string"))
+        .flat_map(|value| value.split(','))
+        .map(|encoding| encoding.trim().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+
+    // Encodings are listed in the order they were applied.
+    for encoding in encodings.iter().rev() {
+        let decoded = match encoding.as_str() {
+            "gzip" | "x-gzip" => read_now(GzipDecoder::new(&body[..])),
+            "deflate" => read_now(DeflateDecoder::new(&body[..])),
+            "br" => read_now(BrotliDecoder::new(&body[..])),
+            "identity" | "" => continue,
+            other => panic!("unsupported content-encoding: {:?}", other),
+        };
+        let decoded = decoded.unwrap_or_else(|e| panic!("{} decoding: {}", encoding, e));
+        body = Bytes::from(decoded);
+    }
+
+    if !encodings.is_empty() {
+        parts.headers.remove(CONTENT_ENCODING);
+        parts
+            .headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+    }
+    Response::from_parts(parts, body)
+}
+
+// The codecs are only given bytes that are already in memory, so they never
+// have to wait, and can be read without a runtime.
+fn read_now<R: AsyncRead>(reader: R) -> io::Result<Vec<u8>> {
+    let mut reader = Box::pin(reader);
+    let mut cx = Context::from_waker(noop_waker_ref());
+    let mut out = Vec::new();
+    let mut buf = [0; 8 * 1024];
+    loop {
+        let mut read = ReadBuf::new(&mut buf);
+        match Pin::as_mut(&mut reader).poll_read(&mut cx, &mut read) {
+            Poll::Ready(Ok(())) if read.filled().is_empty() => return Ok(out),
+            Poll::Ready(Ok(())) => out.extend_from_slice(read.filled()),
+            Poll::Ready(Err(err)) => return Err(err),
+            Poll::Pending => unreachable!("in-memory codecs never wait"),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt::Write;
+
+/// A `multipart/form-data` body for test requests, sent with
+/// [`RequestBuilder::multipart`](super::RequestBuilder::multipart).
+///
+/// The fields are delimited by a random boundary, which is also set in the
+/// `content-type` header of the request.
+///
+/// # Example
+///
+/// ```
+/// use warp::test::MultipartForm;
+///
+/// let req = warp::test::request().method("POST").multipart(
+///     MultipartForm::new()
+///         .text_field("title", "Holiday")
+///         .file_field("photo", "beach.png", "image/png", &b"\x89PNG..."[..]),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultipartForm {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl MultipartForm {
+    /// Creates a form with no fields.
+    pub fn new() -> MultipartForm {
+        let mut bytes = [0u8; 12];
+        getrandom::getrandom(&mut bytes).expect("multipart boundary needs a random number source");
+        let mut boundary = String::from("warp-test-");
+        for byte in bytes {
+            let _ = write!(boundary, "{:02x}", byte);
+        }
+        MultipartForm {
+            boundary,
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a text field.
+    pub fn text_field(self, name: &str, value: impl AsRef<str>) -> MultipartForm {
+        let disposition = format!("form-data; name=\"{}\"", escape(name));
+        self.part(&disposition, None, value.as_ref().as_bytes())
+    }
+
+    /// Adds a file field, with the name and content type of the file.
+    pub fn file_field(
+        self,
+        name: &str,
+        filename: &str,
+        content_type: &str,
+        contents: impl AsRef<[u8]>,
+    ) -> MultipartForm {
+        let disposition = format!(
+            "form-data; name=\"{}\"; filename=\"{}\"",
+            escape(name),
+            escape(filename)
+        );
+        self.part(&disposition, Some(content_type), contents.as_ref())
+    }
+
+    /// The boundary delimiting the fields.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    fn part(mut self, disposition: &str, content_type: Option<&str>, contents: &[u8]) -> Self {
+        let mut head = format!(
+            "--{}\r\ncontent-disposition: {}\r\n",
+            self.boundary, disposition
+        );
+        if let Some(content_type) = content_type {
+            let _ = write!(head, "content-type: {}\r\n", content_type);
+        }
+        head.push_str("\r\n");
+        self.body.extend_from_slice(head.as_bytes());
+        self.body.extend_from_slice(contents);
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    pub(super) fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    pub(super) fn into_body(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
+
+impl Default for MultipartForm {
+    fn default() -> MultipartForm {
+        MultipartForm::new()
+    }
+}
+
+// Escapes names like browsers do, so they can't end the quoted string.
+fn escape(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
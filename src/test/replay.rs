@@ -0,0 +1,338 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::path::Path;
+
+use bytes::Bytes;
+use http::header::{HeaderName, CONTENT_TYPE};
+use http::{Method, Response, StatusCode, Uri};
+use serde_json::Value;
+
+use super::RequestBuilder;
+use crate::filter::Filter;
+use crate::reject::IsReject;
+use crate::reply::Reply;
+
+/// Recorded HTTP exchanges, replayed against a filter to check that it
+/// still replies the same way.
+///
+/// Exchanges are loaded from [HAR](https://w3c.github.io/web-performance/specs/HAR/Overview.html)
+/// files, which browsers and most HTTP proxies can export, so regression
+/// suites can be built from captured traffic.
+///
+/// The status, body, and some headers of each reply are compared to the
+/// recorded response: `content-type` by default, plus any added with
+/// [`Replay::header`]. JSON bodies are compared as JSON values, so that
+/// formatting doesn't matter.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() {
+/// use warp::Filter;
+///
+/// let routes = warp::path("hello").map(|| "hello");
+///
+/// warp::test::Replay::load_har("tests/fixtures/hello.har")
+///     .expect("fixture")
+///     .run(&routes)
+///     .await
+///     .assert_ok();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Replay {
+    exchanges: Vec<Exchange>,
+    headers: Vec<HeaderName>,
+}
+
+#[derive(Debug)]
+struct Exchange {
+    method: Method,
+    uri: Uri,
+    headers: Vec<(HeaderName, String)>,
+    body: Bytes,
+    status: StatusCode,
+    response_headers: Vec<(HeaderName, String)>,
+    response_body: Bytes,
+}
+
+/// The differences found by [`Replay::run`].
+#[derive(Debug)]
+pub struct ReplayReport {
+    total: usize,
+    mismatches: Vec<String>,
+}
+
+/// An error loading recorded exchanges.
+#[derive(Debug)]
+pub struct ReplayError {
+    cause: Box<dyn StdError + Send + Sync>,
+}
+
+impl Replay {
+    /// Reads the exchanges of the HAR file at `path`.
+    pub fn load_har(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        let path = path.as_ref();
+        let har = std::fs::read_to_string(path)
+            .map_err(|e| ReplayError::new(format!("reading {}: {}", path.display(), e)))?;
+        Replay::from_har(&har)
+    }
+
+    /// Parses the exchanges of a HAR document.
+    pub fn from_har(har: &str) -> Result<Replay, ReplayError> {
+        let har: Value = serde_json::from_str(har).map_err(ReplayError::new)?;
+        let entries = har["log"]["entries"]
+            .as_array()
+            .ok_or_else(|| ReplayError::new("missing log.entries"))?;
+        let exchanges = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                parse_entry(entry).map_err(|e| ReplayError::new(format!("entry {}: {}", i, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Replay {
+            exchanges,
+            headers: vec![CONTENT_TYPE],
+        })
+    }
+
+    /// Also compare the header `name` of replies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn header(mut self, name: &str) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        if !self.headers.contains(&name) {
+            self.headers.push(name);
+        }
+        self
+    }
+
+    /// The number of recorded exchanges.
+    pub fn len(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    /// Whether there are no recorded exchanges.
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty()
+    }
+
+    /// Replays each recorded request against `f`, in order, comparing the
+    /// replies to the recorded responses.
+    pub async fn run<F>(&self, f: &F) -> ReplayReport
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+        F::Error: IsReject + Send,
+    {
+        let mut mismatches = Vec::new();
+        for ex in &self.exchanges {
+            let res = ex.request().reply(f).await;
+            let diffs = self.compare(ex, &res);
+            if !diffs.is_empty() {
+                mismatches.push(format!("{} {}:\n{}", ex.method, ex.uri, diffs.join("\n")));
+            }
+        }
+        ReplayReport {
+            total: self.exchanges.len(),
+            mismatches,
+        }
+    }
+
+    fn compare(&self, ex: &Exchange, res: &Response<Bytes>) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if res.status() != ex.status {
+            diffs.push(format!(
+                "  status: expected {}, got {}",
+                ex.status,
+                res.status()
+            ));
+        }
+        for name in &self.headers {
+            let expected = ex
+                .response_headers
+                .iter()
+                .filter(|(n, _)| n == name)
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>();
+            let actual = res
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(|v| String::from_utf8_lossy(v.as_bytes()))
+                .collect::<Vec<_>>();
+            if expected != actual {
+                diffs.push(format!(
+                    "  {}: expected {:?}, got {:?}",
+                    name, expected, actual
+                ));
+            }
+        }
+        if !same_body(&ex.response_body, res.body()) {
+            diffs.push(format!(
+                "  body: expected {:?}, got {:?}",
+                String::from_utf8_lossy(&ex.response_body),
+                String::from_utf8_lossy(res.body()),
+            ));
+        }
+        diffs
+    }
+}
+
+impl Exchange {
+    fn request(&self) -> RequestBuilder {
+        let path = self
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+        let mut req = super::request().method(self.method.as_str()).path(path);
+        for (name, value) in &self.headers {
+            req.req
+                .headers_mut()
+                .append(name, value.parse().expect("checked when parsed"));
+        }
+        if self.body.is_empty() {
+            req
+        } else {
+            req.body(&self.body)
+        }
+    }
+}
+
+fn same_body(expected: &[u8], actual: &[u8]) -> bool {
+    if expected == actual {
+        return true;
+    }
+    match (
+        serde_json::from_slice::<Value>(expected),
+        serde_json::from_slice::<Value>(actual),
+    ) {
+        (Ok(expected), Ok(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+fn parse_entry(entry: &Value) -> Result<Exchange, String> {
+    let req = &entry["request"];
+    let res = &entry["response"];
+
+    let method = str_field(req, "method")?
+        .parse()
+        .map_err(|_| "invalid request method")?;
+    let uri = str_field(req, "url")?
+        .parse()
+        .map_err(|_| "invalid request url")?;
+    let body = req["postData"]["text"]
+        .as_str()
+        .map(|text| Bytes::copy_from_slice(text.as_bytes()))
+        .unwrap_or_default();
+
+    let status = res["status"]
+        .as_u64()
+        .and_then(|status| StatusCode::from_u16(status as u16).ok())
+        .ok_or("invalid response status")?;
+    let content = &res["content"];
+    let response_body = match content["text"].as_str() {
+        Some(text) if content["encoding"] == "base64" => base64::decode(text)
+            .map_err(|e| format!("invalid base64 response body: {}", e))?
+            .into(),
+        Some(text) => Bytes::copy_from_slice(text.as_bytes()),
+        None => Bytes::new(),
+    };
+
+    Ok(Exchange {
+        method,
+        uri,
+        headers: headers(req)?,
+        body,
+        status,
+        response_headers: headers(res)?,
+        response_body,
+    })
+}
+
+fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str, String> {
+    value[name]
+        .as_str()
+        .ok_or_else(|| format!("missing {}", name))
+}
+
+// HTTP/2 pseudo-headers, like `:authority`, aren't headers of the request,
+// and `content-length` is set from the body when it's replayed.
+fn headers(message: &Value) -> Result<Vec<(HeaderName, String)>, String> {
+    let mut headers = Vec::new();
+    for header in message["headers"].as_array().into_iter().flatten() {
+        let name = str_field(header, "name")?;
+        if name.starts_with(':') || name.eq_ignore_ascii_case("content-length") {
+            continue;
+        }
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("invalid header name {:?}", name))?;
+        let value = str_field(header, "value")?;
+        http::HeaderValue::from_str(value)
+            .map_err(|_| format!("invalid value of header {}", name))?;
+        headers.push((name, value.to_owned()));
+    }
+    Ok(headers)
+}
+
+// ===== impl ReplayReport =====
+
+impl ReplayReport {
+    /// Whether every reply matched its recorded response.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// The description of each reply that didn't match its recorded
+    /// response.
+    pub fn mismatches(&self) -> &[String] {
+        &self.mismatches
+    }
+
+    /// Asserts that every reply matched its recorded response.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the differences if some replies didn't match.
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "{}", self);
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} replayed exchanges differ",
+            self.mismatches.len(),
+            self.total
+        )?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+// ===== impl ReplayError =====
+
+impl ReplayError {
+    fn new<E: Into<Box<dyn StdError + Send + Sync>>>(cause: E) -> Self {
+        ReplayError {
+            cause: cause.into(),
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "replay error: {}", self.cause)
+    }
+}
+
+impl StdError for ReplayError {}
@@ -0,0 +1,127 @@
+use std::future::Future;
+use std::net::SocketAddr;
+
+use futures::future::TryFuture;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::filter::Filter;
+use crate::reject::IsReject;
+use crate::reply::Reply;
+
+/// Serves `filter` on an ephemeral port of `127.0.0.1`, to test it with real
+/// HTTP clients.
+///
+/// Unlike [`request`](super::request), requests go through a real
+/// connection, so keep-alive, streaming bodies, and the clients themselves
+/// are tested too. The server runs until the returned [`TestServer`] is shut
+/// down or dropped.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime.
+///
+/// # Example
+///
+/// ```
+/// # async fn run() {
+/// use warp::Filter;
+///
+/// let server = warp::test::serve(warp::path("hello").map(|| "hello"));
+///
+/// let res = warp::hyper::Client::new()
+///     .get(server.url("/hello").parse().unwrap())
+///     .await
+///     .unwrap();
+/// assert_eq!(res.status(), 200);
+///
+/// server.shutdown().await;
+/// # }
+/// ```
+pub fn serve<F>(filter: F) -> TestServer
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    <F::Future as TryFuture>::Ok: Reply,
+    <F::Future as TryFuture>::Error: IsReject,
+{
+    let (tx, rx) = oneshot::channel();
+    let (addr, server) =
+        crate::serve(filter).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async move {
+            let _ = rx.await;
+        });
+    TestServer::spawn(addr, "http", tx, server)
+}
+
+/// Serves a TLS server on an ephemeral port of `127.0.0.1`, like [`serve`]
+/// does, with the certificate and key it was configured with.
+///
+/// *This function requires the `"tls"` feature.*
+#[cfg(feature = "tls")]
+pub fn serve_tls<F>(server: crate::TlsServer<F>) -> TestServer
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    <F::Future as TryFuture>::Ok: Reply,
+    <F::Future as TryFuture>::Error: IsReject,
+{
+    let (tx, rx) = oneshot::channel();
+    let (addr, server) = server.bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async move {
+        let _ = rx.await;
+    });
+    TestServer::spawn(addr, "https", tx, server)
+}
+
+/// A server running filters for tests, started with [`serve`].
+///
+/// Dropping it stops the server right away, closing any open connections.
+/// Use [`TestServer::shutdown`] to let requests in flight finish first.
+#[derive(Debug)]
+pub struct TestServer {
+    addr: SocketAddr,
+    scheme: &'static str,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    fn spawn(
+        addr: SocketAddr,
+        scheme: &'static str,
+        shutdown: oneshot::Sender<()>,
+        server: impl Future<Output = ()> + Send + 'static,
+    ) -> TestServer {
+        TestServer {
+            addr,
+            scheme,
+            shutdown: Some(shutdown),
+            task: Some(tokio::spawn(server)),
+        }
+    }
+
+    /// The address the server is bound to.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL of `path` on the server, like `http://127.0.0.1:49152/hello`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}://{}{}", self.scheme, self.addr, path)
+    }
+
+    /// Stops the server gracefully, waiting for the connections to close.
+    pub async fn shutdown(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
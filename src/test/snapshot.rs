@@ -0,0 +1,219 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http::header::{HeaderName, CONTENT_TYPE};
+use http::Response;
+
+/// Settings for response snapshots, compared by
+/// [`assert_snapshot`](AssertSnapshot::assert_snapshot).
+///
+/// A snapshot has the status of a response, some of its headers, and its
+/// body, in a stable text format:
+///
+/// ```text
+/// status: 200 OK
+/// content-type: application/json
+///
+/// {"id":"[id]"}
+/// ```
+///
+/// Snapshots are stored in `tests/snapshots` of the crate being tested. A
+/// missing snapshot is written on the first run; after that, responses must
+/// match it. Set `WARP_UPDATE_SNAPSHOTS=1` to rewrite snapshots instead of
+/// comparing them.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() {
+/// use warp::test::Snapshot;
+/// use warp::Filter;
+///
+/// let route = warp::any().map(|| warp::reply::json(&"created"));
+///
+/// let res = warp::test::request().reply(&route).await;
+/// Snapshot::new()
+///     .redact_header("date")
+///     .redact(|text| text.replace("created", "[state]"))
+///     .assert("created", &res);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Snapshot {
+    dir: PathBuf,
+    headers: Vec<(HeaderName, bool)>,
+    redactions: Vec<Redaction>,
+}
+
+type Redaction = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+const REDACTED: &str = "[redacted]";
+
+/// Compare responses to stored snapshots.
+///
+/// See [`Snapshot`] for how snapshots are made.
+pub trait AssertSnapshot {
+    /// Asserts that this response matches the snapshot called `name`, made
+    /// with the default [`Snapshot`] settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response doesn't match, or if the snapshot can't be
+    /// read or written.
+    fn assert_snapshot(&self, name: &str);
+}
+
+impl AssertSnapshot for Response<Bytes> {
+    fn assert_snapshot(&self, name: &str) {
+        Snapshot::new().assert(name, self);
+    }
+}
+
+impl Snapshot {
+    /// Creates snapshot settings with the `content-type` header, stored in
+    /// `tests/snapshots`.
+    pub fn new() -> Snapshot {
+        let root = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+        Snapshot {
+            dir: PathBuf::from(root).join("tests").join("snapshots"),
+            headers: vec![(CONTENT_TYPE, false)],
+            redactions: Vec::new(),
+        }
+    }
+
+    /// Store snapshots in `dir` instead.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Include the header `name` in snapshots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn header(self, name: &str) -> Self {
+        self.with_header(name, false)
+    }
+
+    /// Include the header `name` in snapshots, with its value replaced by
+    /// `[redacted]`, for headers like `date` that change with every
+    /// response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn redact_header(self, name: &str) -> Self {
+        self.with_header(name, true)
+    }
+
+    fn with_header(mut self, name: &str, redact: bool) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        self.headers.retain(|(header, _)| *header != name);
+        self.headers.push((name, redact));
+        self
+    }
+
+    /// Rewrite the snapshot text with `redaction` before it's compared, for
+    /// values like ids or timestamps that change between runs.
+    ///
+    /// Redactions are applied in the order they were added.
+    pub fn redact<F>(mut self, redaction: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.redactions.push(Arc::new(redaction));
+        self
+    }
+
+    /// Makes the snapshot text of `res`.
+    pub fn serialize(&self, res: &Response<Bytes>) -> String {
+        let mut text = format!("status: {}\n", res.status());
+        for (name, redact) in &self.headers {
+            for value in res.headers().get_all(name) {
+                let value = if *redact {
+                    REDACTED.into()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes())
+                };
+                let _ = writeln!(text, "{}: {}", name, value);
+            }
+        }
+        text.push('\n');
+        match std::str::from_utf8(res.body()) {
+            Ok(body) => text.push_str(body),
+            Err(_) => {
+                let _ = write!(text, "<{} bytes>", res.body().len());
+                for (i, byte) in res.body().iter().enumerate() {
+                    let sep = if i % 32 == 0 { '\n' } else { ' ' };
+                    let _ = write!(text, "{}{:02x}", sep, byte);
+                }
+            }
+        }
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.redactions
+            .iter()
+            .fold(text, |text, redaction| redaction(&text))
+    }
+
+    /// Asserts that `res` matches the snapshot called `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response doesn't match, or if the snapshot can't be
+    /// read or written.
+    pub fn assert(&self, name: &str, res: &Response<Bytes>) {
+        let actual = self.serialize(res);
+        let path = self.dir.join(format!("{}.snap", name));
+        let update = std::env::var_os("WARP_UPDATE_SNAPSHOTS").is_some_and(|v| v != "0");
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) if !update => expected,
+            Ok(_) => return write_snapshot(&path, &actual),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return write_snapshot(&path, &actual)
+            }
+            Err(err) => panic!("reading snapshot {}: {}", path.display(), err),
+        };
+        if expected != actual {
+            panic!(
+                "response doesn't match snapshot {}\n\
+                 (set WARP_UPDATE_SNAPSHOTS=1 to update it)\n\
+                 --- expected\n{}--- actual\n{}",
+                path.display(),
+                expected,
+                actual,
+            );
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, text: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .unwrap_or_else(|e| panic!("creating {}: {}", parent.display(), e));
+    }
+    fs::write(path, text).unwrap_or_else(|e| panic!("writing snapshot {}: {}", path.display(), e));
+}
+
+impl Default for Snapshot {
+    fn default() -> Snapshot {
+        Snapshot::new()
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("dir", &self.dir)
+            .field("headers", &self.headers)
+            .field("redactions", &self.redactions.len())
+            .finish()
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{future, FutureExt, Stream, StreamExt};
+use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use hyper::body::HttpBody;
+use hyper::Body;
+use tokio::time::{Instant, Sleep};
+
+use super::RequestBuilder;
+use crate::filter::Filter;
+use crate::reject::IsReject;
+use crate::reply::Reply;
+use crate::route;
+
+/// Starts a new test `SseBuilder`.
+pub fn sse() -> SseBuilder {
+    SseBuilder {
+        req: super::request().header("accept", "text/event-stream"),
+        timeout: Duration::from_secs(5),
+    }
+}
+
+/// A builder for testing filters replying with
+/// [server-sent events](crate::sse).
+///
+/// See [module documentation](crate::test) for an overview.
+#[must_use = "SseBuilder does nothing on its own"]
+#[derive(Debug)]
+pub struct SseBuilder {
+    req: RequestBuilder,
+    timeout: Duration,
+}
+
+/// A test client receiving the events of a [`sse::reply`](crate::sse::reply).
+///
+/// It's a `Stream` of the events, which yields an error each time no event
+/// is received within the timeout of the [`SseBuilder`], and ends when the
+/// reply does.
+pub struct SseClient {
+    body: Body,
+    buf: Vec<u8>,
+    block: SseEvent,
+    events: VecDeque<SseEvent>,
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
+    done: bool,
+}
+
+/// An event received by a [`SseClient`].
+///
+/// Keep-alive comments are received as events with only a
+/// [`comment`](SseEvent::comment).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    event: Option<String>,
+    id: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+/// An error from server-sent events filter tests.
+#[derive(Debug)]
+pub struct SseError {
+    cause: Box<dyn StdError + Send + Sync>,
+}
+
+#[derive(Debug)]
+struct Elapsed(Duration);
+
+impl SseBuilder {
+    /// Sets the request path of this builder.
+    ///
+    /// The default is not set is `/`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::sse()
+    ///     .path("/events");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if the passed string is not able to be parsed as a valid
+    /// `Uri`.
+    pub fn path(self, p: &str) -> Self {
+        SseBuilder {
+            req: self.req.path(p),
+            ..self
+        }
+    }
+
+    /// Set a header for this request, such as `last-event-id`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let req = warp::test::sse()
+    ///     .header("last-event-id", "41");
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// This panics if the passed strings are not able to be parsed as a valid
+    /// `HeaderName` and `HeaderValue`.
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        HeaderValue: TryFrom<V>,
+    {
+        SseBuilder {
+            req: self.req.header(key, value),
+            ..self
+        }
+    }
+
+    /// Sets how long the client waits for each event.
+    ///
+    /// The default is 5 seconds.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        SseBuilder { timeout, ..self }
+    }
+
+    /// Execute this request against the provided filter.
+    ///
+    /// If the filter replies with a successful `text/event-stream`
+    /// response, returns a `SseClient` receiving its events.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::stream;
+    /// use warp::sse::Event;
+    /// use warp::Filter;
+    /// # async fn run() {
+    ///
+    /// let route = warp::path("events").map(|| {
+    ///     let events = stream::iter(vec![
+    ///         Ok::<_, std::convert::Infallible>(Event::default().event("hello").data("world")),
+    ///     ]);
+    ///     warp::sse::reply(events)
+    /// });
+    ///
+    /// let mut client = warp::test::sse()
+    ///     .path("/events")
+    ///     .connect(&route)
+    ///     .await
+    ///     .expect("connect");
+    ///
+    /// let event = client.recv().await.unwrap();
+    /// assert_eq!(event.event(), Some("hello"));
+    /// assert_eq!(event.data(), Some("world"));
+    /// client.recv_closed().await.unwrap();
+    /// # }
+    /// ```
+    pub async fn connect<F>(self, f: &F) -> Result<SseClient, SseError>
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+        F::Error: IsReject + Send,
+    {
+        assert!(!route::is_set(), "nested test filter calls");
+
+        let route = self.req.into_route();
+        let mut fut = Box::pin(
+            route::set(&route, move || f.filter(crate::filter::Internal)).map(
+                |result| match result {
+                    Ok(rep) => rep.into_response(),
+                    Err(rej) => {
+                        tracing::debug!("rejected: {:?}", rej);
+                        rej.into_response()
+                    }
+                },
+            ),
+        );
+        let res = future::poll_fn(move |cx| route::set(&route, || fut.as_mut().poll(cx))).await;
+
+        if !res.status().is_success() {
+            return Err(SseError::new(format!("replied with {}", res.status())));
+        }
+        let content_type = res.headers().get(CONTENT_TYPE);
+        if content_type.map_or(true, |v| !v.as_bytes().starts_with(b"text/event-stream")) {
+            return Err(SseError::new(format!(
+                "replied with content-type {:?}",
+                content_type
+            )));
+        }
+
+        Ok(SseClient {
+            body: res.into_body(),
+            buf: Vec::new(),
+            block: SseEvent::default(),
+            events: VecDeque::new(),
+            timeout: self.timeout,
+            deadline: Box::pin(tokio::time::sleep(self.timeout)),
+            done: false,
+        })
+    }
+}
+
+impl SseClient {
+    /// Receive the next event from the server.
+    ///
+    /// Fails if no event is received within the timeout, or if the reply
+    /// has ended.
+    pub async fn recv(&mut self) -> Result<SseEvent, SseError> {
+        self.next()
+            .await
+            .unwrap_or_else(|| Err(SseError::new("closed")))
+    }
+
+    /// Assert the server has ended the reply.
+    pub async fn recv_closed(&mut self) -> Result<(), SseError> {
+        match self.next().await {
+            Some(Ok(event)) => Err(SseError::new(format!("received event: {:?}", event))),
+            Some(Err(err)) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    // Splits the buffered bytes into lines, adding complete events to the
+    // queue. A trailing `\r` is kept until the next chunk, since it could
+    // be the start of a `\r\n`.
+    fn parse(&mut self) {
+        let mut start = 0;
+        while let Some(end) = self.buf[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+            .map(|i| start + i)
+        {
+            let next = match (self.buf[end], self.buf.get(end + 1)) {
+                (b'\r', Some(b'\n')) => end + 2,
+                (b'\r', None) if !self.done => break,
+                _ => end + 1,
+            };
+            let line = String::from_utf8_lossy(&self.buf[start..end]).into_owned();
+            self.line(&line);
+            start = next;
+        }
+        self.buf.drain(..start);
+    }
+
+    fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            let event = std::mem::take(&mut self.block);
+            if event != SseEvent::default() {
+                self.events.push_back(event);
+            }
+            return;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        let block = &mut self.block;
+        match field {
+            "" => append(&mut block.comment, value),
+            "event" => block.event = Some(value.to_owned()),
+            "data" => append(&mut block.data, value),
+            "id" => block.id = Some(value.to_owned()),
+            "retry" => {
+                if let Ok(ms) = value.parse() {
+                    block.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn append(field: &mut Option<String>, value: &str) {
+    match field {
+        Some(lines) => {
+            lines.push('\n');
+            lines.push_str(value);
+        }
+        None => *field = Some(value.to_owned()),
+    }
+}
+
+impl Stream for SseClient {
+    type Item = Result<SseEvent, SseError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                let deadline = Instant::now() + self.timeout;
+                self.deadline.as_mut().reset(deadline);
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            match Pin::new(&mut self.body).poll_data(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.buf.extend_from_slice(&chunk);
+                    self.parse();
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(SseError::new(err))));
+                }
+                Poll::Ready(None) => {
+                    // An event without its final blank line is dropped.
+                    self.done = true;
+                    self.parse();
+                }
+                Poll::Pending => {
+                    futures::ready!(self.deadline.as_mut().poll(cx));
+                    let deadline = Instant::now() + self.timeout;
+                    self.deadline.as_mut().reset(deadline);
+                    let timeout = self.timeout;
+                    return Poll::Ready(Some(Err(SseError::new(Elapsed(timeout)))));
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SseClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SseClient")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+// ===== impl SseEvent =====
+
+impl SseEvent {
+    /// The name of the event, from its `event` field.
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// The id of the event.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// The data of the event, with its lines joined by `\n`.
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+
+    /// The reconnection time of the event.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// The comment of the event, with its lines joined by `\n`.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+}
+
+// ===== impl SseError =====
+
+impl SseError {
+    fn new<E: Into<Box<dyn StdError + Send + Sync>>>(cause: E) -> Self {
+        SseError {
+            cause: cause.into(),
+        }
+    }
+
+    /// Whether no event was received within the timeout.
+    pub fn is_timeout(&self) -> bool {
+        self.cause.is::<Elapsed>()
+    }
+}
+
+impl fmt::Display for SseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sse error: {}", self.cause)
+    }
+}
+
+impl StdError for SseError {}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no event received within {:?}", self.0)
+    }
+}
+
+impl StdError for Elapsed {}
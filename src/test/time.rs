@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::clock;
+
+/// Pauses the clock used by time-dependent filters, on the current thread.
+///
+/// While paused, the time only moves with [`advance`], so filters such as
+/// [`rate_limit`](crate::rate_limit()), [`cache`](crate::cache()),
+/// [`circuit_breaker`](crate::circuit_breaker()) and
+/// [`session`](crate::session()), and values kept with
+/// [`Filter::cached`](crate::Filter::cached), can be tested without
+/// sleeping. Pausing again keeps the current time.
+///
+/// Only the time these filters read is paused: timers, such as those of
+/// [`timeout`](crate::timeout()), still run in real time.
+///
+/// # Example
+///
+/// ```
+/// # async fn run() {
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .with(warp::rate_limit(1, Duration::from_secs(60)).key_by_header("x-api-key"));
+/// let req = || warp::test::request().header("x-api-key", "abc");
+///
+/// warp::test::pause_time();
+/// assert_eq!(req().reply(&route).await.status(), 200);
+/// assert_eq!(req().reply(&route).await.status(), 429);
+///
+/// warp::test::advance(Duration::from_secs(60));
+/// assert_eq!(req().reply(&route).await.status(), 200);
+/// # }
+/// ```
+pub fn pause_time() {
+    clock::pause();
+}
+
+/// Moves the paused clock forward by `duration`.
+///
+/// # Panics
+///
+/// Panics if the clock isn't paused, by [`pause_time`].
+pub fn advance(duration: Duration) {
+    clock::advance(duration);
+}
+
+/// Lets the clock paused by [`pause_time`] follow the real time again.
+pub fn resume_time() {
+    clock::resume();
+}
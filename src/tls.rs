@@ -12,10 +12,11 @@ use futures::ready;
 use hyper::server::accept::Accept;
 use hyper::server::conn::{AddrIncoming, AddrStream};
 
-use crate::transport::Transport;
+use crate::filters::conn::TlsInfo;
+use crate::transport::{TlsSlot, Transport};
 use tokio_rustls::rustls::{
     AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, NoClientAuth,
-    RootCertStore, ServerConfig, TLSError,
+    RootCertStore, ServerConfig, Session, TLSError,
 };
 
 /// Represents errors that can occur building the TlsConfig
@@ -265,6 +266,14 @@ impl Transport for TlsStream {
     fn remote_addr(&self) -> Option<SocketAddr> {
         Some(self.remote_addr)
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Some(self.local_addr)
+    }
+
+    fn tls_info(&self) -> Option<TlsSlot> {
+        Some(self.info.clone())
+    }
 }
 
 enum State {
@@ -278,17 +287,34 @@ enum State {
 pub(crate) struct TlsStream {
     state: State,
     remote_addr: SocketAddr,
+    local_addr: SocketAddr,
+    info: TlsSlot,
 }
 
 impl TlsStream {
     fn new(stream: AddrStream, config: Arc<ServerConfig>) -> TlsStream {
         let remote_addr = stream.remote_addr();
+        let local_addr = stream.local_addr();
         let accept = tokio_rustls::TlsAcceptor::from(config).accept(stream);
         TlsStream {
             state: State::Handshaking(accept),
             remote_addr,
+            local_addr,
+            info: TlsSlot::default(),
         }
     }
+
+    fn handshaken(&mut self, stream: tokio_rustls::server::TlsStream<AddrStream>) {
+        let (_, session) = stream.get_ref();
+        *self.info.lock().unwrap() = Some(TlsInfo {
+            alpn_protocol: session.get_alpn_protocol().map(<[u8]>::to_vec),
+            version: session.get_protocol_version().map(|v| format!("{:?}", v)),
+            cipher_suite: session
+                .get_negotiated_ciphersuite()
+                .map(|cs| format!("{:?}", cs.suite)),
+        });
+        self.state = State::Streaming(stream);
+    }
 }
 
 impl AsyncRead for TlsStream {
@@ -302,7 +328,7 @@ impl AsyncRead for TlsStream {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
                     let result = Pin::new(&mut stream).poll_read(cx, buf);
-                    pin.state = State::Streaming(stream);
+                    pin.handshaken(stream);
                     result
                 }
                 Err(err) => Poll::Ready(Err(err)),
@@ -323,7 +349,7 @@ impl AsyncWrite for TlsStream {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
                     let result = Pin::new(&mut stream).poll_write(cx, buf);
-                    pin.state = State::Streaming(stream);
+                    pin.handshaken(stream);
                     result
                 }
                 Err(err) => Poll::Ready(Err(err)),
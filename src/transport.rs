@@ -1,23 +1,185 @@
+use std::fmt;
+use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Duration;
 
-use hyper::server::conn::AddrStream;
+use futures::task::AtomicWaker;
+use hyper::server::accept::Accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use pin_project::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
+use crate::filters::conn::{Info, TlsInfo};
+
 pub trait Transport: AsyncRead + AsyncWrite {
     fn remote_addr(&self) -> Option<SocketAddr>;
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    // Handshakes happen lazily, so TLS details are only known once the
+    // first request is read, and get filled into this slot.
+    fn tls_info(&self) -> Option<TlsSlot> {
+        None
+    }
+
+    // The requests being served on the connection, if reading the headers
+    // of the next one is timed out.
+    fn requests(&self) -> Option<Requests> {
+        None
+    }
+}
+
+pub(crate) type TlsSlot = Arc<Mutex<Option<TlsInfo>>>;
+
+/// Gather what the transport knows about a connection.
+pub(crate) fn conn_info<T: Transport + ?Sized>(transport: &T) -> ConnInfo {
+    ConnInfo {
+        local_addr: transport.local_addr(),
+        remote_addr: transport.remote_addr(),
+        tls: transport.tls_info(),
+        requests: transport.requests(),
+    }
+}
+
+/// Connection details captured when a connection is accepted.
+#[derive(Clone)]
+pub(crate) struct ConnInfo {
+    local_addr: Option<SocketAddr>,
+    pub(crate) remote_addr: Option<SocketAddr>,
+    tls: Option<TlsSlot>,
+    pub(crate) requests: Option<Requests>,
+}
+
+impl ConnInfo {
+    /// Connection details of a transport that isn't a `Transport`, with its
+    /// TLS session already established.
+    #[cfg(feature = "http3")]
+    pub(crate) fn new(
+        local_addr: Option<SocketAddr>,
+        remote_addr: Option<SocketAddr>,
+        tls: Option<TlsInfo>,
+    ) -> ConnInfo {
+        ConnInfo {
+            local_addr,
+            remote_addr,
+            tls: tls.map(|tls| Arc::new(Mutex::new(Some(tls)))),
+            requests: None,
+        }
+    }
+
+    pub(crate) fn info(&self) -> Info {
+        Info {
+            local_addr: self.local_addr,
+            remote_addr: self.remote_addr,
+            tls: self
+                .tls
+                .as_ref()
+                .map(|slot| slot.lock().unwrap().clone().unwrap_or_default()),
+        }
+    }
 }
 
 impl Transport for AddrStream {
     fn remote_addr(&self) -> Option<SocketAddr> {
         Some(self.remote_addr())
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Some(AddrStream::local_addr(self))
+    }
 }
 
 pub(crate) struct LiftIo<T>(pub(crate) T);
 
+// ===== TCP options =====
+
+/// Socket options used when binding a TCP listener.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TcpConfig {
+    pub(crate) nodelay: bool,
+    pub(crate) keepalive: Option<Duration>,
+    pub(crate) keepalive_interval: Option<Duration>,
+    pub(crate) reuse_port: bool,
+    pub(crate) backlog: Option<u32>,
+    pub(crate) send_buffer_size: Option<usize>,
+    pub(crate) recv_buffer_size: Option<usize>,
+}
+
+impl Default for TcpConfig {
+    fn default() -> TcpConfig {
+        TcpConfig {
+            nodelay: true,
+            keepalive: None,
+            keepalive_interval: None,
+            reuse_port: false,
+            backlog: None,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+        }
+    }
+}
+
+impl TcpConfig {
+    // Whether the listener needs more than `std`'s default setup.
+    fn has_socket_options(&self) -> bool {
+        self.reuse_port
+            || self.backlog.is_some()
+            || self.send_buffer_size.is_some()
+            || self.recv_buffer_size.is_some()
+    }
+}
+
+pub(crate) fn bind_incoming(
+    addr: &SocketAddr,
+    config: &TcpConfig,
+) -> Result<AddrIncoming, Box<dyn std::error::Error + Send + Sync>> {
+    let mut incoming = if config.has_socket_options() {
+        let listener = bind_listener(addr, config)?;
+        AddrIncoming::from_listener(listener)?
+    } else {
+        AddrIncoming::bind(addr)?
+    };
+
+    incoming.set_nodelay(config.nodelay);
+    incoming.set_keepalive(config.keepalive);
+    incoming.set_keepalive_interval(config.keepalive_interval);
+    Ok(incoming)
+}
+
+fn bind_listener(addr: &SocketAddr, config: &TcpConfig) -> io::Result<tokio::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(
+        Domain::for_address(*addr),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    // Match what `std` does when binding a listener.
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(config.reuse_port)?;
+    // Buffer sizes set on the listener are inherited by accepted sockets.
+    if let Some(size) = config.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+    if let Some(size) = config.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&(*addr).into())?;
+    socket.listen(config.backlog.unwrap_or(1024) as i32)?;
+
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
 impl<T: AsyncRead + Unpin> AsyncRead for LiftIo<T> {
     fn poll_read(
         self: Pin<&mut Self>,
@@ -51,3 +213,441 @@ impl<T: AsyncRead + AsyncWrite + Unpin> Transport for LiftIo<T> {
         None
     }
 }
+
+// ===== Connection limits =====
+
+/// Configuration of how many connections a server keeps open.
+#[derive(Clone, Default)]
+pub(crate) struct ConnConfig {
+    pub(crate) max: Option<usize>,
+    pub(crate) shed: bool,
+    pub(crate) gauge: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+}
+
+impl fmt::Debug for ConnConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConnConfig")
+            .field("max", &self.max)
+            .field("shed", &self.shed)
+            .field("gauge", &self.gauge.is_some())
+            .finish()
+    }
+}
+
+/// Shared counter of open connections for a single bound server.
+#[derive(Clone)]
+pub(crate) struct ConnLimit {
+    inner: Arc<ConnLimitInner>,
+}
+
+struct ConnLimitInner {
+    config: ConnConfig,
+    active: AtomicUsize,
+    in_flight: AtomicUsize,
+    waker: AtomicWaker,
+}
+
+impl ConnLimit {
+    pub(crate) fn new(config: ConnConfig) -> ConnLimit {
+        ConnLimit {
+            inner: Arc::new(ConnLimitInner {
+                config,
+                active: AtomicUsize::new(0),
+                in_flight: AtomicUsize::new(0),
+                waker: AtomicWaker::new(),
+            }),
+        }
+    }
+
+    /// The number of currently open connections.
+    pub(crate) fn active(&self) -> usize {
+        self.inner.active.load(Ordering::Acquire)
+    }
+
+    /// The number of requests that haven't produced a response yet.
+    pub(crate) fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Count a request as in flight until the returned guard is dropped.
+    pub(crate) fn track_request(&self) -> InFlight {
+        self.inner.in_flight.fetch_add(1, Ordering::AcqRel);
+        InFlight {
+            limit: self.clone(),
+        }
+    }
+
+    /// Whether connections over the limit should be accepted and answered
+    /// with `503 Service Unavailable`, and this count is over the limit.
+    pub(crate) fn is_shedding(&self) -> bool {
+        let inner = &self.inner;
+        match inner.config.max {
+            Some(max) if inner.config.shed => inner.active.load(Ordering::Acquire) > max,
+            _ => false,
+        }
+    }
+
+    // Without shedding, the accept loop stops polling the listener once the
+    // limit is reached, leaving new connections in the kernel backlog.
+    fn is_saturated(&self) -> bool {
+        let inner = &self.inner;
+        match inner.config.max {
+            Some(max) if !inner.config.shed => inner.active.load(Ordering::Acquire) >= max,
+            _ => false,
+        }
+    }
+
+    /// Wait until another connection may be accepted.
+    #[cfg(feature = "http3")]
+    pub(crate) async fn ready(&self) {
+        futures::future::poll_fn(|cx| self.poll_ready(cx)).await
+    }
+
+    /// Count a connection accepted without a `LimitedIncoming` as open
+    /// until the returned guard is dropped.
+    #[cfg(feature = "http3")]
+    pub(crate) fn track_connection(&self) -> Counted<()> {
+        self.acquire();
+        Counted {
+            conn: (),
+            limit: self.clone(),
+        }
+    }
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_saturated() {
+            self.inner.waker.register(cx.waker());
+            // Check again, in case a connection closed before registering.
+            if self.is_saturated() {
+                return Poll::Pending;
+            }
+        }
+        Poll::Ready(())
+    }
+
+    fn acquire(&self) {
+        let active = self.inner.active.fetch_add(1, Ordering::AcqRel) + 1;
+        self.report(active);
+    }
+
+    fn release(&self) {
+        let active = self.inner.active.fetch_sub(1, Ordering::AcqRel) - 1;
+        self.report(active);
+        self.inner.waker.wake();
+    }
+
+    fn report(&self, active: usize) {
+        if let Some(ref gauge) = self.inner.config.gauge {
+            gauge(active);
+        }
+    }
+}
+
+/// A guard counting a request as in flight.
+pub(crate) struct InFlight {
+    limit: ConnLimit,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.limit.inner.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// An `Accept` that counts, and possibly limits, open connections.
+#[pin_project]
+pub(crate) struct LimitedIncoming<A> {
+    #[pin]
+    incoming: A,
+    limit: ConnLimit,
+}
+
+impl<A> LimitedIncoming<A> {
+    pub(crate) fn new(incoming: A, limit: ConnLimit) -> LimitedIncoming<A> {
+        LimitedIncoming { incoming, limit }
+    }
+}
+
+impl<A> Accept for LimitedIncoming<A>
+where
+    A: Accept,
+{
+    type Conn = Counted<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let pin = self.project();
+        futures::ready!(pin.limit.poll_ready(cx));
+
+        match pin.incoming.poll_accept(cx) {
+            Poll::Ready(Some(Ok(conn))) => {
+                pin.limit.acquire();
+                Poll::Ready(Some(Ok(Counted {
+                    conn,
+                    limit: pin.limit.clone(),
+                })))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A connection that is counted as open until dropped.
+pub(crate) struct Counted<T> {
+    conn: T,
+    limit: ConnLimit,
+}
+
+impl<T> Drop for Counted<T> {
+    fn drop(&mut self) {
+        self.limit.release();
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Counted<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().conn).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Counted<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().conn).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.get_mut().conn).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.get_mut().conn).poll_shutdown(cx)
+    }
+}
+
+impl<T: Transport + Unpin> Transport for Counted<T> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.conn.local_addr()
+    }
+
+    fn tls_info(&self) -> Option<TlsSlot> {
+        self.conn.tls_info()
+    }
+}
+
+// ===== Header read timeout =====
+
+/// An `Accept` that times out reading the headers of requests on the
+/// connections it accepts.
+#[pin_project]
+pub(crate) struct HeaderTimeoutIncoming<A> {
+    #[pin]
+    incoming: A,
+    timeout: Option<Duration>,
+}
+
+impl<A> HeaderTimeoutIncoming<A> {
+    pub(crate) fn new(incoming: A, timeout: Option<Duration>) -> HeaderTimeoutIncoming<A> {
+        HeaderTimeoutIncoming { incoming, timeout }
+    }
+}
+
+impl<A> Accept for HeaderTimeoutIncoming<A>
+where
+    A: Accept,
+{
+    type Conn = HeaderTimeout<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let pin = self.project();
+        let timeout = *pin.timeout;
+        pin.incoming
+            .poll_accept(cx)
+            .map_ok(|conn| HeaderTimeout::new(conn, timeout))
+    }
+}
+
+/// A count of the requests being served on a connection, shared by its
+/// transport and its service.
+#[derive(Clone, Default)]
+pub(crate) struct Requests(Arc<AtomicUsize>);
+
+impl Requests {
+    /// Count a request as being served until the returned guard is dropped.
+    pub(crate) fn track(&self) -> ServingRequest {
+        self.0.fetch_add(1, Ordering::AcqRel);
+        ServingRequest(self.clone())
+    }
+
+    /// Stop timing out the connection, once it's upgraded to another
+    /// protocol, since it carries no more requests.
+    pub(crate) fn upgraded(&self) {
+        self.0.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.0.load(Ordering::Acquire) == 0
+    }
+}
+
+/// A guard counting a request as being served.
+pub(crate) struct ServingRequest(Requests);
+
+impl Drop for ServingRequest {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+// hyper closes connections whose headers time out without replying, so the
+// timeout is kept here instead, where the `408` can be written first.
+const REQUEST_TIMEOUT: &[u8] =
+    b"HTTP/1.1 408 Request Timeout\r\nconnection: close\r\ncontent-length: 0\r\n\r\n";
+
+/// A connection that replies `408 Request Timeout` and fails reads once
+/// the headers of a request take too long to arrive.
+///
+/// The timer starts with the first bytes read while no request is being
+/// served, so that idle keep-alive connections aren't timed out, and stops
+/// once the service is called with the request.
+pub(crate) struct HeaderTimeout<T> {
+    conn: T,
+    timeout: Option<Duration>,
+    requests: Requests,
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+    // What's left to write of the `408`, once timed out.
+    timed_out: Option<&'static [u8]>,
+    started: bool,
+}
+
+impl<T> HeaderTimeout<T> {
+    fn new(conn: T, timeout: Option<Duration>) -> HeaderTimeout<T> {
+        HeaderTimeout {
+            conn,
+            timeout,
+            requests: Requests::default(),
+            deadline: None,
+            timed_out: None,
+            started: false,
+        }
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> HeaderTimeout<T> {
+    fn poll_reply_timeout(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(rest) = self.timed_out.filter(|rest| !rest.is_empty()) {
+            let n = futures::ready!(Pin::new(&mut self.conn).poll_write(cx, rest))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.timed_out = Some(&rest[n..]);
+        }
+        futures::ready!(Pin::new(&mut self.conn).poll_flush(cx))?;
+        Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "timed out reading request headers",
+        )))
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncRead for HeaderTimeout<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.timed_out.is_some() {
+            return this.poll_reply_timeout(cx);
+        }
+
+        let timeout = match this.timeout {
+            Some(timeout) => timeout,
+            None => return Pin::new(&mut this.conn).poll_read(cx, buf),
+        };
+        if !this.requests.is_idle() {
+            this.deadline = None;
+        } else if let Some(ref mut deadline) = this.deadline {
+            if deadline.as_mut().poll(cx).is_ready() {
+                tracing::debug!("timed out reading request headers");
+                this.timed_out = Some(REQUEST_TIMEOUT);
+                return this.poll_reply_timeout(cx);
+            }
+        }
+
+        let filled = buf.filled().len();
+        futures::ready!(Pin::new(&mut this.conn).poll_read(cx, buf))?;
+        let read = &buf.filled()[filled..];
+        if !this.started && !read.is_empty() {
+            this.started = true;
+            // The header timeout only applies to HTTP/1.
+            if read.starts_with(b"PRI ") {
+                this.timeout = None;
+                return Poll::Ready(Ok(()));
+            }
+        }
+        if this.deadline.is_none() && !read.is_empty() && this.requests.is_idle() {
+            let mut deadline = Box::pin(tokio::time::sleep(timeout));
+            // Registers the wakeup.
+            let _ = deadline.as_mut().poll(cx);
+            this.deadline = Some(deadline);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for HeaderTimeout<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().conn).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.get_mut().conn).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.get_mut().conn).poll_shutdown(cx)
+    }
+}
+
+impl<T: Transport + Unpin> Transport for HeaderTimeout<T> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.conn.local_addr()
+    }
+
+    fn tls_info(&self) -> Option<TlsSlot> {
+        self.conn.tls_info()
+    }
+
+    fn requests(&self) -> Option<Requests> {
+        self.timeout.map(|_| self.requests.clone())
+    }
+}
@@ -0,0 +1,184 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use hyper::server::accept::Accept;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_uring::net::{TcpListener, TcpStream};
+
+use crate::transport::Transport;
+
+const READ_BUF_SIZE: usize = 8_192;
+
+thread_local! {
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the current thread is serving with the io_uring backend, so
+/// `warp::fs` should read files through it as well.
+pub(crate) fn is_active() -> bool {
+    ACTIVE.with(Cell::get)
+}
+
+/// Bind a listener whose connections are driven by io_uring.
+///
+/// Must be called from within a `tokio_uring` runtime.
+pub(crate) fn bind(
+    addr: &SocketAddr,
+) -> io::Result<(
+    SocketAddr,
+    impl Accept<Conn = UringStream, Error = io::Error>,
+)> {
+    let listener = TcpListener::bind(*addr)?;
+    let local_addr = listener.local_addr()?;
+    ACTIVE.with(|active| active.set(true));
+
+    let incoming = stream::unfold(listener, move |listener| async move {
+        let conn = listener
+            .accept()
+            .await
+            .map(|(stream, remote_addr)| UringStream::new(stream, local_addr, remote_addr));
+        Some((conn, listener))
+    });
+    Ok((local_addr, hyper::server::accept::from_stream(incoming)))
+}
+
+type BufFuture = Pin<Box<dyn Future<Output = tokio_uring::BufResult<usize, Vec<u8>>>>>;
+
+/// Adapts the owned-buffer API of `tokio_uring` to `AsyncRead`/`AsyncWrite`.
+pub(crate) struct UringStream {
+    stream: Rc<TcpStream>,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    read: Option<BufFuture>,
+    // Bytes read by the kernel that didn't fit into the caller's buffer.
+    leftover: Bytes,
+    write: Option<BufFuture>,
+}
+
+impl UringStream {
+    fn new(stream: TcpStream, local_addr: SocketAddr, remote_addr: SocketAddr) -> UringStream {
+        UringStream {
+            stream: Rc::new(stream),
+            local_addr,
+            remote_addr,
+            read: None,
+            leftover: Bytes::new(),
+            write: None,
+        }
+    }
+}
+
+impl AsyncRead for UringStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.leftover.is_empty() {
+            if this.read.is_none() {
+                let stream = this.stream.clone();
+                this.read = Some(Box::pin(async move {
+                    stream.read(Vec::with_capacity(READ_BUF_SIZE)).await
+                }));
+            }
+            let read = this.read.as_mut().expect("read in flight");
+            let (result, data) = futures::ready!(read.as_mut().poll(cx));
+            this.read = None;
+            result?;
+            this.leftover = Bytes::from(data);
+        }
+
+        let n = std::cmp::min(buf.remaining(), this.leftover.len());
+        buf.put_slice(&this.leftover.split_to(n));
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for UringStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        // A pending write already owns a copy of `buf`, since callers must
+        // retry with the same bytes after `Poll::Pending`.
+        if this.write.is_none() {
+            let stream = this.stream.clone();
+            let data = buf.to_vec();
+            this.write = Some(Box::pin(async move { stream.write(data).await }));
+        }
+        let write = this.write.as_mut().expect("write in flight");
+        let (result, _) = futures::ready!(write.as_mut().poll(cx));
+        this.write = None;
+        Poll::Ready(result)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.stream.shutdown(std::net::Shutdown::Write))
+    }
+}
+
+impl Transport for UringStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        Some(self.remote_addr)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Some(self.local_addr)
+    }
+}
+
+/// Stream `start..end` of a file, reading it with io_uring.
+///
+/// The reads happen on a task local to the io_uring runtime, and the chunks
+/// are sent over a channel so the body itself stays `Send`.
+pub(crate) fn file_stream(
+    file: std::fs::File,
+    buf_size: usize,
+    (start, end): (u64, u64),
+) -> impl Stream<Item = Result<Bytes, io::Error>> + Send {
+    let (tx, rx) = tokio::sync::mpsc::channel(2);
+    tokio_uring::spawn(async move {
+        let file = tokio_uring::fs::File::from_std(file);
+        let mut pos = start;
+        while pos < end {
+            let len = std::cmp::min(buf_size as u64, end - pos) as usize;
+            let (result, mut buf) = file.read_at(Vec::with_capacity(len), pos).await;
+            let chunk = match result {
+                Ok(0) => {
+                    tracing::debug!("file read found EOF before expected length");
+                    break;
+                }
+                Ok(n) => {
+                    pos += n as u64;
+                    buf.truncate(n);
+                    Ok(Bytes::from(buf))
+                }
+                Err(err) => {
+                    tracing::debug!("file read error: {}", err);
+                    Err(err)
+                }
+            };
+            let failed = chunk.is_err();
+            if tx.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+        let _ = file.close().await;
+    });
+    ReceiverStream::new(rx).fuse()
+}
@@ -2,6 +2,9 @@
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
+use warp::addr::ForwardedHeader;
+use warp::Filter;
+
 #[tokio::test]
 async fn remote_addr_missing() {
     let extract_remote_addr = warp::addr::remote();
@@ -22,3 +25,187 @@ async fn remote_addr_present() {
         Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 5678))
     )
 }
+
+fn client_ip(
+    header: ForwardedHeader,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = std::convert::Infallible> + Clone {
+    warp::addr::client_ip(vec!["10.0.0.0/8".parse().unwrap()], header)
+}
+
+#[tokio::test]
+async fn client_ip_untrusted_peer_ignores_headers() {
+    let req = warp::test::request()
+        .remote_addr("1.2.3.4:5678".parse().unwrap())
+        .header("x-forwarded-for", "5.6.7.8");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::XForwardedFor))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("1.2.3.4".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn client_ip_x_forwarded_for() {
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("x-forwarded-for", "9.9.9.9, 5.6.7.8, 10.0.0.2");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::XForwardedFor))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("5.6.7.8".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn client_ip_forwarded() {
+    let req = || {
+        warp::test::request()
+            .remote_addr("10.0.0.1:5678".parse().unwrap())
+            .header(
+                "forwarded",
+                "for=\"[2001:db8::1]:4711\";proto=https, for=10.0.0.3",
+            )
+            .header("x-forwarded-for", "5.6.7.8")
+    };
+    let ip = req()
+        .filter(&client_ip(ForwardedHeader::Forwarded))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("2001:db8::1".parse().unwrap()));
+
+    // Only the header the proxies set is read, since the others come from
+    // the client.
+    let ip = req()
+        .filter(&client_ip(ForwardedHeader::XForwardedFor))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("5.6.7.8".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn client_ip_ignores_other_headers() {
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("forwarded", "for=1.2.3.4")
+        .header("x-forwarded-for", "5.6.7.8");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::XRealIp))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("10.0.0.1".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn client_ip_x_real_ip() {
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("x-real-ip", "5.6.7.8");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::XRealIp))
+        .await
+        .unwrap();
+    assert_eq!(ip, Some("5.6.7.8".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn client_ip_obfuscated_hop() {
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("forwarded", "for=5.6.7.8, for=_hidden");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::Forwarded))
+        .await
+        .unwrap();
+    assert_eq!(ip, None);
+}
+
+#[tokio::test]
+async fn allow_list() {
+    let list = warp::addr::IpList::new(vec!["192.168.0.0/16".parse().unwrap()]);
+    let filter = warp::addr::allow(list.clone());
+
+    let req = warp::test::request().remote_addr("192.168.1.2:5678".parse().unwrap());
+    assert!(req.filter(&filter).await.is_ok());
+
+    let req = warp::test::request().remote_addr("1.2.3.4:5678".parse().unwrap());
+    let err = req.filter(&filter).await.unwrap_err();
+    let forbidden = err.find::<warp::addr::AddressForbidden>().unwrap();
+    assert_eq!(forbidden.ip(), Some("1.2.3.4".parse().unwrap()));
+
+    let res = warp::test::request()
+        .reply(&filter.clone().map(warp::reply))
+        .await;
+    assert_eq!(res.status(), 403);
+
+    // Reloading applies to filters already built.
+    list.set(vec![
+        "1.2.3.0/24".parse().unwrap(),
+        "::1/128".parse().unwrap(),
+    ]);
+    let req = warp::test::request().remote_addr("1.2.3.4:5678".parse().unwrap());
+    assert!(req.filter(&filter).await.is_ok());
+    let req = warp::test::request().remote_addr("[::1]:5678".parse().unwrap());
+    assert!(req.filter(&filter).await.is_ok());
+}
+
+#[tokio::test]
+async fn deny_list_behind_proxy() {
+    let list = warp::addr::IpList::new(vec!["5.6.7.0/24".parse().unwrap()]).trust_proxies(
+        vec!["10.0.0.0/8".parse().unwrap()],
+        ForwardedHeader::XForwardedFor,
+    );
+    let filter = warp::addr::deny(list);
+
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("x-forwarded-for", "5.6.7.8");
+    assert!(req.filter(&filter).await.is_err());
+
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("x-forwarded-for", "1.2.3.4");
+    assert!(req.filter(&filter).await.is_ok());
+
+    // Untrusted peers can't hide behind the header.
+    let req = warp::test::request()
+        .remote_addr("5.6.7.8:5678".parse().unwrap())
+        .header("x-forwarded-for", "1.2.3.4");
+    assert!(req.filter(&filter).await.is_err());
+}
+
+#[tokio::test]
+async fn client_ip_invalid_forwarded() {
+    // Unquoted IPv6 addresses aren't tokens, so nothing is trusted, and the
+    // proxy isn't mistaken for the client.
+    let req = warp::test::request()
+        .remote_addr("10.0.0.1:5678".parse().unwrap())
+        .header("forwarded", "for=[2001:db8::1], for=10.0.0.3")
+        .header("x-forwarded-for", "5.6.7.8");
+    let ip = req
+        .filter(&client_ip(ForwardedHeader::Forwarded))
+        .await
+        .unwrap();
+    assert_eq!(ip, None);
+}
+
+#[tokio::test]
+async fn allow_and_deny_invalid_forwarded() {
+    let proxies = vec!["10.0.0.0/8".parse().unwrap()];
+    let allow = warp::addr::IpList::new(vec!["10.0.0.0/8".parse().unwrap()])
+        .trust_proxies(proxies.clone(), ForwardedHeader::Forwarded);
+    let deny = warp::addr::IpList::new(vec!["5.6.7.0/24".parse().unwrap()])
+        .trust_proxies(proxies, ForwardedHeader::Forwarded);
+    let req = || {
+        warp::test::request()
+            .remote_addr("10.0.0.1:5678".parse().unwrap())
+            .header("forwarded", "for=\"bad, for=5.6.7.8")
+    };
+
+    let res = req()
+        .reply(&warp::addr::allow(allow).map(warp::reply))
+        .await;
+    assert_eq!(res.status(), 403);
+
+    let res = req().reply(&warp::addr::deny(deny).map(warp::reply)).await;
+    assert_eq!(res.status(), 403);
+}
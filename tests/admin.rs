@@ -0,0 +1,73 @@
+#![deny(warnings)]
+
+use warp::admin::Admin;
+use warp::http::Method;
+
+#[tokio::test]
+async fn routes_and_config() {
+    let admin = Admin::new()
+        .route(Method::GET, "/users/{u32}")
+        .config("max_connections", 1024)
+        .config("tls", false);
+    let filter = admin.filter();
+
+    let res = warp::test::request().path("/routes").reply(&filter).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), r#"[{"method":"GET","path":"/users/{u32}"}]"#);
+
+    let res = warp::test::request().path("/config").reply(&filter).await;
+    assert_eq!(res.body(), r#"{"max_connections":1024,"tls":false}"#);
+
+    let gauge = admin.connection_gauge();
+    gauge(3);
+    gauge(1);
+    let res = warp::test::request()
+        .path("/connections")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.body(), r#"{"open":1,"peak":3}"#);
+}
+
+#[tokio::test]
+async fn change_log_level() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let tx = std::sync::Mutex::new(tx);
+    let admin = Admin::new().on_log_level(move |level| tx.lock().unwrap().send(level).unwrap());
+    let filter = admin.filter();
+
+    let res = warp::test::request()
+        .method("PUT")
+        .path("/log-level")
+        .body("debug")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), r#"{"level":"debug"}"#);
+    assert_eq!(rx.try_recv().unwrap(), log::LevelFilter::Debug);
+
+    let res = warp::test::request()
+        .path("/log-level")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.body(), r#"{"level":"debug"}"#);
+
+    let res = warp::test::request()
+        .method("PUT")
+        .path("/log-level")
+        .body("loud")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.status(), 400);
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn separate_bind() {
+    let admin = Admin::new();
+    let (addr, server) = admin.bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let uri = format!("http://{}/connections", addr).parse().unwrap();
+    let res = hyper::Client::new().get(uri).await.unwrap();
+    assert_eq!(res.status(), 200);
+}
@@ -0,0 +1,67 @@
+#![deny(warnings)]
+
+use warp::test::arbitrary;
+use warp::Filter;
+
+#[tokio::test]
+async fn arbitrary_requests_are_deterministic() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::method()
+        .and(warp::path::full())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .map(|method, path: warp::path::FullPath, headers, body| {
+            format!("{} {} {:?} {:?}", method, path.as_str(), headers, body)
+        });
+
+    for seed in 0..50 {
+        let a = arbitrary::check(&route, arbitrary::request(seed))
+            .await
+            .unwrap();
+        let b = arbitrary::check(&route, arbitrary::request(seed))
+            .await
+            .unwrap();
+        assert_eq!(a.body(), b.body());
+    }
+}
+
+#[tokio::test]
+async fn arbitrary_check_all_passes() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path!("users" / u32)
+        .and(warp::query::raw())
+        .map(|id, query| format!("{} {}", id, query))
+        .or(warp::body::json().map(|json: serde_json::Value| warp::reply::json(&json)))
+        .or(warp::cookie::optional::<String>("a").map(|a| format!("{:?}", a)));
+
+    arbitrary::check_all(&routes, 7, 300).await;
+}
+
+#[tokio::test]
+async fn arbitrary_check_finds_panics() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::header::optional::<String>("authorization").map(|auth: Option<String>| {
+        if auth.is_some_and(|auth| auth.starts_with("BEARER")) {
+            panic!("unexpected scheme casing");
+        }
+        "ok"
+    });
+
+    let mut found = None;
+    for seed in 0..2_000 {
+        if let Err(failure) = arbitrary::check(&route, arbitrary::request(seed)).await {
+            found = Some(failure);
+            break;
+        }
+    }
+    let failure = found.expect("a request with an uppercase bearer scheme");
+    assert!(!failure.is_timeout());
+    assert_eq!(
+        failure.panic().unwrap().message(),
+        Some("unexpected scheme casing")
+    );
+    assert!(failure.request().contains("authorization"));
+}
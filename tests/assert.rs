@@ -0,0 +1,92 @@
+#![deny(warnings)]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use warp::http::StatusCode;
+use warp::test::{AssertReply, ExpectRejection};
+use warp::Filter;
+
+#[derive(Debug)]
+struct Challenge {
+    realm: &'static str,
+}
+
+impl warp::reject::Reject for Challenge {}
+
+#[tokio::test]
+async fn expect_rejection() {
+    let _ = pretty_env_logger::try_init();
+
+    let admin = warp::path("admin").and_then(|| async {
+        Err::<String, _>(warp::reject::custom(Challenge { realm: "admin" }))
+    });
+
+    let challenge = warp::test::request()
+        .path("/admin")
+        .filter(&admin)
+        .await
+        .expect_rejection::<Challenge>();
+    assert_eq!(challenge.realm, "admin");
+    assert_eq!(challenge.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    warp::test::request()
+        .path("/other")
+        .filter(&admin)
+        .await
+        .expect_not_found();
+
+    let header = warp::test::request()
+        .filter(&warp::header::<String>("x-token"))
+        .await
+        .expect_rejection::<warp::reject::MissingHeader>();
+    assert_eq!(header.name(), "x-token");
+    assert_eq!(header.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn expect_rejection_mismatch() {
+    let _ = pretty_env_logger::try_init();
+
+    let extracted = warp::test::request().filter(&warp::any().map(|| 1)).await;
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        Ok::<_, warp::Rejection>(extracted.unwrap()).expect_rejection::<Challenge>();
+    }));
+    assert!(res.is_err());
+
+    let not_found = warp::test::request().filter(&warp::path("a")).await;
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        not_found.expect_rejection::<Challenge>();
+    }));
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn assert_reply() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("admin")
+        .and_then(|| async { Err::<String, _>(warp::reject::custom(Challenge { realm: "admin" })) })
+        .recover(|rejection: warp::Rejection| async move {
+            let challenge = rejection.find::<Challenge>().unwrap();
+            Ok::<_, warp::Rejection>(warp::reply::with_header(
+                warp::reply::with_status("", StatusCode::UNAUTHORIZED),
+                "www-authenticate",
+                format!("Basic realm=\"{}\"", challenge.realm),
+            ))
+        });
+
+    let res = warp::test::request().path("/admin").reply(&route).await;
+    res.assert_status(401)
+        .assert_status(StatusCode::UNAUTHORIZED)
+        .assert_header("www-authenticate", "Basic realm=\"admin\"")
+        .assert_no_header("location");
+
+    let wrong = panic::catch_unwind(AssertUnwindSafe(|| {
+        res.assert_status(200);
+    }));
+    assert!(wrong.is_err());
+    let wrong = panic::catch_unwind(AssertUnwindSafe(|| {
+        res.assert_header("www-authenticate", "Bearer");
+    }));
+    assert!(wrong.is_err());
+}
@@ -0,0 +1,29 @@
+#![deny(warnings)]
+
+use warp::Filter;
+
+#[test]
+fn bench_runs_filter() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::post()
+        .and(warp::path!("users" / u32))
+        .and(warp::header::<String>("x-name"))
+        .and(warp::body::bytes())
+        .map(|id, name: String, body: bytes::Bytes| format!("{} {} {}", id, name, body.len()));
+    let req = warp::test::request()
+        .method("POST")
+        .path("/users/7")
+        .header("x-name", "sean")
+        .body("hello");
+
+    let bench = warp::test::bench(routes, req);
+    for _ in 0..3 {
+        // every run gets its own copy of the request, body included
+        assert_eq!(bench.run().unwrap().0, "7 sean 5");
+    }
+    assert!(bench.time(10) > std::time::Duration::ZERO);
+
+    let bench = warp::test::bench(warp::path("other"), warp::test::request());
+    assert!(bench.run().unwrap_err().is_not_found());
+}
@@ -2,6 +2,7 @@
 
 use bytes::Buf;
 use futures::TryStreamExt;
+use std::time::Duration;
 use warp::Filter;
 
 #[tokio::test]
@@ -200,3 +201,55 @@ async fn stream() {
     assert_eq!(bufs.len(), 1);
     assert_eq!(bufs[0].chunk(), b"foo=bar");
 }
+
+#[tokio::test]
+async fn stream_chunked() {
+    let _ = pretty_env_logger::try_init();
+
+    let stream = warp::body::stream();
+    let body = warp::test::ChunkedBody::new()
+        .chunk("foo")
+        .delay(Duration::from_millis(50))
+        .chunk("=bar");
+
+    let body = warp::test::request()
+        .body_stream(body)
+        .filter(&stream)
+        .await
+        .expect("filter() stream");
+
+    let start = std::time::Instant::now();
+    let bufs: Vec<_> = body.try_collect().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    let bufs: Vec<_> = bufs.iter().map(|buf| buf.chunk()).collect();
+    assert_eq!(bufs, [&b"foo"[..], &b"=bar"[..]]);
+}
+
+#[tokio::test]
+async fn chunked_without_length() {
+    let _ = pretty_env_logger::try_init();
+
+    let limited = warp::body::content_length_limit(16)
+        .and(warp::body::bytes())
+        .map(|_| "ok");
+    let body = warp::test::ChunkedBody::new().chunk("a").repeat();
+
+    let res = warp::test::request()
+        .body_stream(body)
+        .reply(&limited)
+        .await;
+    assert_eq!(res.status(), 411);
+}
+
+#[tokio::test]
+async fn chunked_stall_times_out() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::bytes()
+        .map(|_| "done")
+        .with(warp::timeout(Duration::from_millis(50)));
+    let body = warp::test::ChunkedBody::new().chunk("partial").stall();
+
+    let res = warp::test::request().body_stream(body).reply(&route).await;
+    assert_eq!(res.status(), 504);
+}
@@ -0,0 +1,246 @@
+#![deny(warnings)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use warp::cache::Cache;
+use warp::Filter;
+
+// Replies with how many times the handler ran.
+fn counted(
+    cache: Cache,
+) -> (
+    Arc<AtomicUsize>,
+    impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone,
+) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let route = warp::any()
+        .map(move || (c.fetch_add(1, Ordering::SeqCst) + 1).to_string())
+        .with(cache);
+    (count, route)
+}
+
+#[tokio::test]
+async fn caches_get() {
+    let (count, route) = counted(warp::cache());
+
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    assert_eq!(res.headers()["age"], "0");
+
+    // Different query, different entry.
+    let res = warp::test::request().path("/a?b=c").reply(&route).await;
+    assert_eq!(res.body(), "2");
+
+    // Other methods skip the cache.
+    let res = warp::test::request()
+        .method("POST")
+        .path("/a")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "3");
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn respects_vary_and_no_store() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let route = warp::path::param()
+        .map(move |kind: String| {
+            let n = (c.fetch_add(1, Ordering::SeqCst) + 1).to_string();
+            let header = if kind == "vary" {
+                ("vary", "accept-language")
+            } else {
+                ("cache-control", "no-store")
+            };
+            warp::reply::with_header(n, header.0, header.1)
+        })
+        .with(warp::cache());
+
+    let en = warp::test::request()
+        .path("/vary")
+        .header("accept-language", "en");
+    assert_eq!(en.reply(&route).await.body(), "1");
+    let de = warp::test::request()
+        .path("/vary")
+        .header("accept-language", "de");
+    assert_eq!(de.reply(&route).await.body(), "2");
+    let en = warp::test::request()
+        .path("/vary")
+        .header("accept-language", "en");
+    assert_eq!(en.reply(&route).await.body(), "1");
+
+    let res = warp::test::request().path("/nostore").reply(&route).await;
+    assert_eq!(res.body(), "3");
+    let res = warp::test::request().path("/nostore").reply(&route).await;
+    assert_eq!(res.body(), "4");
+}
+
+#[tokio::test]
+async fn separates_hosts() {
+    let cache = warp::cache();
+    let handle = cache.handle();
+    let (_, route) = counted(cache);
+    let req = |host: &str| warp::test::request().path("/a").header("host", host);
+
+    assert_eq!(req("a.example.com").reply(&route).await.body(), "1");
+    assert_eq!(req("b.example.com").reply(&route).await.body(), "2");
+    assert_eq!(req("A.example.com").reply(&route).await.body(), "1");
+    let res = warp::test::request()
+        .path("http://b.example.com/a")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "2");
+
+    // Invalidating a path covers every host.
+    assert_eq!(handle.len(), 2);
+    assert!(handle.invalidate("/a"));
+    assert!(handle.is_empty());
+}
+
+#[tokio::test]
+async fn invalidation() {
+    let cache = warp::cache();
+    let handle = cache.handle();
+    let (_, route) = counted(cache);
+
+    for path in &["/a/1", "/a/2", "/b"] {
+        warp::test::request().path(path).reply(&route).await;
+    }
+    assert_eq!(handle.len(), 3);
+
+    assert!(handle.invalidate("/b"));
+    assert!(!handle.invalidate("/b"));
+    assert_eq!(handle.invalidate_prefix("/a/"), 2);
+    assert!(handle.is_empty());
+
+    let res = warp::test::request().path("/b").reply(&route).await;
+    assert_eq!(res.body(), "4");
+}
+
+#[tokio::test]
+async fn evicts_least_recently_used() {
+    let cache = warp::cache().max_entries(2);
+    let handle = cache.handle();
+    let (_, route) = counted(cache);
+
+    warp::test::request().path("/1").reply(&route).await;
+    warp::test::request().path("/2").reply(&route).await;
+    warp::test::request().path("/1").reply(&route).await;
+    warp::test::request().path("/3").reply(&route).await;
+    assert_eq!(handle.len(), 2);
+
+    // `/2` was the least recently used, so it's gone.
+    let res = warp::test::request().path("/1").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    let res = warp::test::request().path("/2").reply(&route).await;
+    assert_eq!(res.body(), "4");
+}
+
+#[tokio::test]
+async fn stale_while_revalidate() {
+    let cache = warp::cache()
+        .ttl(Duration::from_millis(10))
+        .stale_while_revalidate(Duration::from_secs(60));
+    let (count, route) = counted(cache);
+
+    let res = warp::test::request().path("/p").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // The stale reply is served, while it's refreshed in the background.
+    let res = warp::test::request().path("/p").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    while count.load(Ordering::SeqCst) < 2 {
+        tokio::task::yield_now().await;
+    }
+    tokio::task::yield_now().await;
+
+    let res = warp::test::request().path("/p").reply(&route).await;
+    assert_eq!(res.body(), "2");
+}
+
+#[tokio::test]
+async fn expires_after_ttl() {
+    warp::test::pause_time();
+
+    let (count, route) = counted(warp::cache().ttl(Duration::from_secs(30)));
+
+    let res = warp::test::request().path("/t").reply(&route).await;
+    assert_eq!(res.body(), "1");
+
+    warp::test::advance(Duration::from_secs(29));
+    let res = warp::test::request().path("/t").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    assert_eq!(res.headers()["age"], "29");
+
+    warp::test::advance(Duration::from_secs(1));
+    let res = warp::test::request().path("/t").reply(&route).await;
+    assert_eq!(res.body(), "2");
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn skips_set_cookie() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let route = warp::any()
+        .map(move || {
+            let n = c.fetch_add(1, Ordering::SeqCst) + 1;
+            warp::reply::with_header(n.to_string(), "set-cookie", "session=abc")
+        })
+        .with(warp::cache());
+
+    let res = warp::test::request().path("/login").reply(&route).await;
+    assert_eq!(res.body(), "1");
+    let res = warp::test::request().path("/login").reply(&route).await;
+    assert_eq!(res.body(), "2");
+    assert!(res.headers().get("age").is_none());
+}
+
+#[tokio::test]
+async fn skips_credentials() {
+    let (count, route) = counted(warp::cache());
+
+    // Neither stored nor served from the cache.
+    for header in &["authorization", "cookie"] {
+        let res = warp::test::request()
+            .path("/me")
+            .header(*header, "alice")
+            .reply(&route)
+            .await;
+        assert!(res.headers().get("age").is_none());
+    }
+    let res = warp::test::request().path("/me").reply(&route).await;
+    assert_eq!(res.body(), "3");
+    let res = warp::test::request()
+        .path("/me")
+        .header("cookie", "alice")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "4");
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+
+    // Unless the cache varies on them.
+    let (count, route) = counted(warp::cache().vary("authorization"));
+    for _ in 0..2 {
+        let res = warp::test::request()
+            .path("/me")
+            .header("authorization", "alice")
+            .reply(&route)
+            .await;
+        assert_eq!(res.body(), "1");
+    }
+    let res = warp::test::request()
+        .path("/me")
+        .header("authorization", "bob")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "2");
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,110 @@
+#![deny(warnings)]
+
+use std::sync::{Arc, Mutex};
+
+use warp::capture::Record;
+use warp::Filter;
+
+type Records = Arc<Mutex<Vec<Record>>>;
+
+fn recorder() -> (Records, impl Fn(Record) + Clone + Send + Sync + 'static) {
+    let records = Records::default();
+    let push = {
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record)
+    };
+    (records, push)
+}
+
+#[tokio::test]
+async fn captures_and_redacts() {
+    let (records, push) = recorder();
+    let route = warp::post()
+        .and(warp::body::json())
+        .map(|body: serde_json::Value| {
+            warp::reply::with_header(warp::reply::json(&body), "set-cookie", "session=abc")
+        })
+        .with(
+            warp::capture(push)
+                .redact_header("x-api-key")
+                .redact_json("/password"),
+        );
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer secret")
+        .header("x-api-key", "secret")
+        .header("accept", "*/*")
+        .body(r#"{"user":"sean","password":"hunter2"}"#)
+        .reply(&route)
+        .await;
+    // The handler still sees the real values.
+    assert_eq!(res.body(), r#"{"password":"hunter2","user":"sean"}"#);
+
+    let records = records.lock().unwrap();
+    let record = &records[0];
+    assert_eq!(record.method(), "POST");
+    assert_eq!(record.status(), 200);
+    assert_eq!(record.request_headers()["authorization"], "[REDACTED]");
+    assert_eq!(record.request_headers()["x-api-key"], "[REDACTED]");
+    assert_eq!(record.request_headers()["accept"], "*/*");
+    assert_eq!(
+        record.request_body().unwrap(),
+        r#"{"password":"[REDACTED]","user":"sean"}"#
+    );
+    assert_eq!(record.response_headers()["set-cookie"], "[REDACTED]");
+    assert_eq!(
+        record.response_body().unwrap(),
+        r#"{"password":"[REDACTED]","user":"sean"}"#
+    );
+}
+
+#[tokio::test]
+async fn truncates_bodies() {
+    let (records, push) = recorder();
+    let route = warp::body::bytes()
+        .map(|body: bytes::Bytes| body.to_vec())
+        .with(warp::capture(push).max_body(4));
+
+    let res = warp::test::request()
+        .body("hello world")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "hello world");
+    assert_eq!(res.headers()["content-length"], "11");
+
+    let records = records.lock().unwrap();
+    let record = &records[0];
+    assert_eq!(record.request_body().unwrap(), "hell");
+    assert!(record.request_truncated());
+    assert_eq!(record.response_body().unwrap(), "hell");
+    assert!(record.response_truncated());
+}
+
+#[tokio::test]
+async fn truncated_json_is_left_out() {
+    let (records, push) = recorder();
+    let route = warp::any()
+        .map(|| warp::reply::json(&serde_json::json!({ "token": "0123456789" })))
+        .with(warp::capture(push).max_body(8).redact_json("/token"));
+
+    warp::test::request().reply(&route).await;
+
+    let records = records.lock().unwrap();
+    assert!(records[0].response_truncated());
+    assert!(records[0].response_body().is_none());
+}
+
+#[tokio::test]
+async fn rejections() {
+    let (records, push) = recorder();
+    let route = warp::path("y").map(warp::reply).with(warp::capture(push));
+
+    warp::test::request().path("/x").reply(&route).await;
+
+    let records = records.lock().unwrap();
+    assert_eq!(records[0].status(), 404);
+    assert!(records[0].response_headers().is_empty());
+    assert!(records[0].response_body().is_none());
+}
@@ -0,0 +1,53 @@
+#![deny(warnings)]
+
+use std::sync::{Arc, Mutex};
+
+use warp::http::StatusCode;
+use warp::Filter;
+
+#[tokio::test]
+async fn panic_becomes_500() {
+    let _ = pretty_env_logger::try_init();
+
+    let reported = Arc::new(Mutex::new(None));
+    let r = reported.clone();
+    let route = warp::path::param()
+        .map(|n: u32| -> String {
+            if n == 0 {
+                panic!("zero is not allowed");
+            }
+            n.to_string()
+        })
+        .with(warp::catch_panic().on_panic(move |panic| {
+            *r.lock().unwrap() = panic.message().map(ToOwned::to_owned);
+        }));
+
+    let res = warp::test::request().path("/0").reply(&route).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    // The message is reported, but not shown to the client.
+    assert_eq!(res.body(), "Request handler panicked");
+    assert_eq!(
+        reported.lock().unwrap().as_deref(),
+        Some("zero is not allowed")
+    );
+
+    let res = warp::test::request().path("/7").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "7");
+}
+
+#[tokio::test]
+async fn panicked_rejection() {
+    let route = warp::any()
+        .and_then(|| async {
+            let numbers: Vec<u8> = Vec::new();
+            Ok::<_, warp::Rejection>(numbers.first().expect("missing number").to_string())
+        })
+        .with(warp::catch_panic());
+
+    let rejection = warp::test::request().filter(&route).await.unwrap_err();
+    let panicked = rejection
+        .find::<warp::catch_panic::Panicked>()
+        .expect("Panicked rejection");
+    assert_eq!(panicked.message(), Some("missing number"));
+}
@@ -0,0 +1,115 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use warp::circuit_breaker::{CircuitBreaker, CircuitState};
+use warp::http::StatusCode;
+use warp::Filter;
+
+// A route failing with a 500 while `failing` is set.
+fn flaky(
+    failing: Arc<AtomicBool>,
+    breaker: CircuitBreaker,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let failing = failing.load(Ordering::SeqCst);
+            async move {
+                let status = if failing {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    StatusCode::OK
+                };
+                Ok::<_, Infallible>(warp::reply::with_status("flaky", status))
+            }
+        })
+        .with(breaker)
+}
+
+#[tokio::test]
+async fn opens_and_recovers() {
+    let _ = pretty_env_logger::try_init();
+
+    let failing = Arc::new(AtomicBool::new(true));
+    let breaker = warp::circuit_breaker()
+        .window(4)
+        .min_requests(4)
+        .failure_rate(0.5)
+        .open_for(Duration::from_millis(20));
+    let route = flaky(failing.clone(), breaker.clone());
+
+    for _ in 0..4 {
+        let res = warp::test::request().reply(&route).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()["retry-after"], "1");
+
+    // A failed probe opens the circuit again...
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // ...while a successful one closes it.
+    failing.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn ignores_client_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let breaker = warp::circuit_breaker().window(2).min_requests(2);
+    let route = warp::path("exists").map(warp::reply).with(breaker.clone());
+
+    for _ in 0..4 {
+        let rejection = warp::test::request()
+            .path("/missing")
+            .filter(&route)
+            .await
+            .err()
+            .expect("rejection");
+        assert!(rejection.is_not_found());
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn open_rejection() {
+    let breaker = warp::circuit_breaker()
+        .window(1)
+        .min_requests(1)
+        .failure_rate(1.0);
+    let route = warp::any()
+        .and_then(|| async { Err::<&str, _>(warp::reject::custom(Boom)) })
+        .with(breaker.clone());
+
+    let _ = warp::test::request().filter(&route).await;
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let rejection = warp::test::request()
+        .filter(&route)
+        .await
+        .err()
+        .expect("rejection");
+    let open = rejection
+        .find::<warp::circuit_breaker::CircuitOpen>()
+        .expect("CircuitOpen rejection");
+    assert!(open.retry_after() <= Duration::from_secs(30));
+}
+
+#[derive(Debug)]
+struct Boom;
+
+impl warp::reject::Reject for Boom {}
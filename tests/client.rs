@@ -0,0 +1,114 @@
+#![deny(warnings)]
+
+use warp::Filter;
+
+fn set_cookie(
+    value: &'static str,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || warp::reply::with_header("ok", "set-cookie", value))
+}
+
+fn cookies() -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("cookie")
+        .map(|cookie: Option<String>| cookie.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn client_sends_stored_cookies() {
+    let _ = pretty_env_logger::try_init();
+
+    let client = warp::test::Client::new();
+    client
+        .request()
+        .path("/login")
+        .reply(&set_cookie("session=abc; Path=/; HttpOnly"))
+        .await;
+    assert_eq!(client.cookie("session").as_deref(), Some("abc"));
+
+    let sent = client
+        .request()
+        .path("/me")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "session=abc");
+
+    // requests not made from the client don't get them
+    let sent = warp::test::request()
+        .path("/me")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "");
+
+    // cookies are replaced, and explicit ones are kept
+    client.request().reply(&set_cookie("session=def")).await;
+    let sent = client
+        .request()
+        .header("cookie", "theme=dark")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "theme=dark; session=def");
+}
+
+#[tokio::test]
+async fn client_cookie_attributes() {
+    let _ = pretty_env_logger::try_init();
+
+    let client = warp::test::Client::new().secure(false);
+    client
+        .request()
+        .path("/admin/login")
+        .reply(&set_cookie("admin=1"))
+        .await;
+    client
+        .request()
+        .reply(&set_cookie("csrf=x; Path=/; Secure"))
+        .await;
+    client.request().reply(&set_cookie("lang=en; Path=/")).await;
+
+    // the default path is the directory of the request
+    let sent = client
+        .request()
+        .path("/admin/users")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "admin=1; lang=en");
+    let sent = client
+        .request()
+        .path("/administrator")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "lang=en");
+
+    // secure cookies only go to secure requests
+    let sent = client
+        .request()
+        .path("https://example.com/")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "csrf=x; lang=en");
+
+    // expired cookies are removed
+    client
+        .request()
+        .reply(&set_cookie("lang=; Path=/; Max-Age=0"))
+        .await;
+    client
+        .request()
+        .path("/admin/logout")
+        .reply(&set_cookie("admin=; Expires=Thu, 01 Jan 1970 00:00:00 GMT"))
+        .await;
+    assert_eq!(client.cookie("lang"), None);
+    let sent = client
+        .request()
+        .path("/admin/users")
+        .filter(&cookies())
+        .await
+        .unwrap();
+    assert_eq!(sent, "");
+}
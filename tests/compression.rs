@@ -0,0 +1,58 @@
+#![deny(warnings)]
+
+use warp::Filter;
+
+#[tokio::test]
+async fn compressed_request_bodies() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::header::<String>("content-encoding")
+        .and(warp::body::bytes())
+        .map(|encoding: String, body: warp::hyper::body::Bytes| {
+            format!("{} {}", encoding, body.len())
+        });
+
+    let payload = "hello ".repeat(100);
+    for encoding in &["gzip", "deflate", "br"] {
+        let req = match *encoding {
+            "gzip" => warp::test::request().gzip_body(&payload),
+            "deflate" => warp::test::request().deflate_body(&payload),
+            _ => warp::test::request().brotli_body(&payload),
+        };
+        let res = req.reply(&route).await;
+        let (name, len) = std::str::from_utf8(res.body())
+            .unwrap()
+            .split_once(' ')
+            .unwrap();
+        assert_eq!(name, *encoding);
+        assert!(len.parse::<usize>().unwrap() < payload.len());
+    }
+}
+
+#[tokio::test]
+async fn decompressed_replies() {
+    let _ = pretty_env_logger::try_init();
+
+    let payload = "hello ".repeat(100);
+    let hello = warp::any().map(move || payload.clone());
+
+    let res = warp::test::request()
+        .reply(&hello.clone().with(warp::compression::gzip()))
+        .await;
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+
+    for route in vec![
+        hello.clone().with(warp::compression::gzip()).boxed(),
+        hello.clone().with(warp::compression::deflate()).boxed(),
+        hello.clone().with(warp::compression::brotli()).boxed(),
+    ] {
+        let res = warp::test::request().reply_decompressed(&route).await;
+        assert!(!res.headers().contains_key("content-encoding"));
+        assert_eq!(res.headers()["content-length"], "600");
+        assert_eq!(res.body(), &"hello ".repeat(100));
+    }
+
+    // uncompressed replies are left as they are
+    let res = warp::test::request().reply_decompressed(&hello).await;
+    assert_eq!(res.body(), &"hello ".repeat(100));
+}
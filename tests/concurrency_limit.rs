@@ -0,0 +1,79 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use warp::concurrency_limit::ConcurrencyLimit;
+use warp::http::StatusCode;
+use warp::Filter;
+
+// A route that waits until the test opens the gate.
+fn gated(
+    gate: Arc<Semaphore>,
+    limit: ConcurrencyLimit,
+) -> impl Filter<Extract = (&'static str,), Error = warp::Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let gate = gate.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                Ok::<_, Infallible>("done")
+            }
+        })
+        .with(limit)
+}
+
+async fn wait_for(f: impl Fn() -> bool) {
+    while !f() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn sheds_when_full() {
+    let _ = pretty_env_logger::try_init();
+
+    let gate = Arc::new(Semaphore::new(0));
+    let limit = warp::concurrency_limit(1);
+    let route = gated(gate.clone(), limit.clone());
+
+    let r = route.clone();
+    let first = tokio::spawn(async move { warp::test::request().reply(&r).await });
+    wait_for(|| limit.in_flight() == 1).await;
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    gate.add_permits(1);
+    let res = first.await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(limit.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn queues_up_to_depth() {
+    let _ = pretty_env_logger::try_init();
+
+    let gate = Arc::new(Semaphore::new(0));
+    let limit = warp::concurrency_limit(1).queue(1);
+    let route = gated(gate.clone(), limit.clone());
+
+    let r = route.clone();
+    let first = tokio::spawn(async move { warp::test::request().reply(&r).await });
+    wait_for(|| limit.in_flight() == 1).await;
+    let r = route.clone();
+    let second = tokio::spawn(async move { warp::test::request().reply(&r).await });
+    wait_for(|| limit.queued() == 1).await;
+
+    let rejection = warp::test::request().filter(&route).await.unwrap_err();
+    let limited = rejection
+        .find::<warp::concurrency_limit::ConcurrencyLimited>()
+        .expect("ConcurrencyLimited rejection");
+    assert_eq!(limited.max(), 1);
+
+    gate.add_permits(2);
+    assert_eq!(first.await.unwrap().status(), 200);
+    assert_eq!(second.await.unwrap().status(), 200);
+    assert_eq!(limit.queued(), 0);
+}
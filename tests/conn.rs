@@ -0,0 +1,38 @@
+#![deny(warnings)]
+
+use std::net::SocketAddr;
+
+use warp::Filter;
+
+#[tokio::test]
+async fn info_from_test_request() {
+    let remote: SocketAddr = "1.2.3.4:5678".parse().unwrap();
+    let req = warp::test::request().remote_addr(remote);
+    let info = req.filter(&warp::conn::info()).await.unwrap();
+
+    assert_eq!(info.remote_addr(), Some(remote));
+    assert_eq!(info.local_addr(), None);
+    assert!(!info.is_tls());
+}
+
+#[tokio::test]
+async fn info_from_server() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::conn::info().map(|info: warp::conn::Info| {
+        format!(
+            "{} {} {}",
+            info.local_addr().unwrap(),
+            info.remote_addr().is_some(),
+            info.is_tls()
+        )
+    });
+    let (addr, srv) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let uri = format!("http://{}/", addr).parse().unwrap();
+    let res = hyper::Client::new().get(uri).await.expect("request");
+    let body = hyper::body::to_bytes(res.into_body()).await.expect("body");
+
+    assert_eq!(body, format!("{} true false", addr));
+}
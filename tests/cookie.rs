@@ -1,5 +1,7 @@
 #![deny(warnings)]
 
+use warp::Filter;
+
 #[tokio::test]
 async fn cookie() {
     let foo = warp::cookie::<String>("foo");
@@ -48,3 +50,50 @@ async fn missing() {
     assert_eq!(res.status(), 400);
     assert_eq!(res.body(), "Missing request cookie \"foo\"");
 }
+
+#[tokio::test]
+async fn with_cookie() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use warp::cookie::{Cookie, SameSite};
+
+    let route = warp::any().map(|| {
+        let theme = Cookie::new("theme", "dark")
+            .expires(UNIX_EPOCH + Duration::from_secs(784_111_777))
+            .domain("example.com")
+            .path("/app")
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Strict);
+        let reply = warp::reply::with_cookie(warp::reply(), theme);
+        let reply = warp::reply::with_cookie(reply, Cookie::new("lang", "en"));
+        // Not set, since the value would add an attribute.
+        warp::reply::with_cookie(reply, Cookie::new("bad", "x; Domain=evil.com"))
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    let cookies = res
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|v| v.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cookies,
+        [
+            "theme=dark; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Domain=example.com; \
+             Path=/app; Secure; HttpOnly; SameSite=Strict",
+            "lang=en",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn remove_cookie() {
+    let route = warp::any().map(|| warp::reply::remove_cookie(warp::reply(), "session", "/"));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(
+        res.headers()["set-cookie"],
+        "session=; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0; Path=/"
+    );
+}
@@ -0,0 +1,166 @@
+#![deny(warnings)]
+
+use warp::Filter;
+
+// The `name=value` pair of the cookie set by a reply.
+fn set_cookie(res: &warp::http::Response<warp::hyper::body::Bytes>) -> String {
+    let header = res.headers()["set-cookie"].to_str().unwrap();
+    header.split(';').next().unwrap().to_owned()
+}
+
+#[tokio::test]
+async fn signed() {
+    let key = warp::cookie::Key::from_secret(&[7; 32]);
+    let set = warp::any().map({
+        let key = key.clone();
+        move || warp::reply::with_signed_cookie(warp::reply(), "user", "sean; admin", &key)
+    });
+    let user = warp::cookie::signed::<String>("user", key);
+
+    let res = warp::test::request().reply(&set).await;
+    assert!(res.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .ends_with("; Path=/; HttpOnly"));
+    let cookie = set_cookie(&res);
+
+    let req = warp::test::request().header("cookie", &cookie);
+    assert_eq!(req.filter(&user).await.unwrap(), "sean; admin");
+
+    // Changing the value breaks the signature.
+    let (value, tag) = cookie.rsplit_once('.').unwrap();
+    let tampered = format!("{}A.{}", value, tag);
+    let rejection = warp::test::request()
+        .header("cookie", tampered)
+        .filter(&user)
+        .await
+        .unwrap_err();
+    let invalid = rejection.find::<warp::reject::InvalidCookie>().unwrap();
+    assert_eq!(invalid.name(), "user");
+    let res = warp::test::request()
+        .header("cookie", "user=sean")
+        .reply(&user.clone().map(|u: String| u))
+        .await;
+    assert_eq!(res.status(), 400);
+
+    // So does moving it to another cookie.
+    let other = warp::cookie::signed::<String>("other", warp::cookie::Key::from_secret(&[7; 32]));
+    let moved = cookie.replacen("user=", "other=", 1);
+    let req = warp::test::request().header("cookie", moved);
+    assert!(!req.matches(&other).await);
+
+    let req = warp::test::request().header("cookie", "abc=def");
+    let rejection = req.filter(&user).await.unwrap_err();
+    assert!(rejection.find::<warp::reject::MissingCookie>().is_some());
+}
+
+#[tokio::test]
+async fn signed_key_rotation() {
+    let old = warp::cookie::Key::from_secret(b"an old secret, at least 32 bytes long");
+    let new = warp::cookie::Key::from_secret(b"a new secret, also 32 bytes long or more");
+
+    let res = warp::test::request()
+        .reply(
+            &warp::any()
+                .map(move || warp::reply::with_signed_cookie(warp::reply(), "theme", "dark", &old)),
+        )
+        .await;
+    let cookie = set_cookie(&res);
+
+    let req = warp::test::request().header("cookie", &cookie);
+    assert!(
+        !req.matches(&warp::cookie::signed::<String>("theme", new.clone()))
+            .await
+    );
+
+    let rotated = new.clone().with_previous(&warp::cookie::Key::from_secret(
+        b"an old secret, at least 32 bytes long",
+    ));
+    let req = warp::test::request().header("cookie", &cookie);
+    let theme = warp::cookie::signed::<String>("theme", rotated.clone());
+    assert_eq!(req.filter(&theme).await.unwrap(), "dark");
+
+    // New cookies are signed with the new key.
+    let res = warp::test::request()
+        .reply(&warp::any().map(move || {
+            warp::reply::with_signed_cookie(warp::reply(), "theme", "light", &rotated)
+        }))
+        .await;
+    let req = warp::test::request().header("cookie", set_cookie(&res));
+    let theme = warp::cookie::signed::<String>("theme", new);
+    assert_eq!(req.filter(&theme).await.unwrap(), "light");
+}
+
+#[derive(Debug, PartialEq, serde_derive::Deserialize, serde_derive::Serialize)]
+struct Session {
+    user: String,
+    admin: bool,
+}
+
+#[tokio::test]
+async fn private() {
+    let key = warp::cookie::Key::from_secret(&[9; 32]);
+    let session = Session {
+        user: "sean".into(),
+        admin: false,
+    };
+    let set = warp::any().map({
+        let key = key.clone();
+        move || {
+            let session = Session {
+                user: "sean".into(),
+                admin: false,
+            };
+            warp::reply::with_private_cookie(warp::reply(), "session", &session, &key)
+        }
+    });
+    let read = warp::cookie::private::<Session>("session", key.clone());
+
+    let res = warp::test::request().reply(&set).await;
+    let cookie = set_cookie(&res);
+    assert!(!cookie.contains("sean"));
+
+    let req = warp::test::request().header("cookie", &cookie);
+    assert_eq!(req.filter(&read).await.unwrap(), session);
+
+    // Values are encrypted with a fresh nonce each time.
+    let res = warp::test::request().reply(&set).await;
+    assert_ne!(set_cookie(&res), cookie);
+
+    // Tampered, moved, or foreign values are rejected.
+    let (name, value) = cookie.split_once('=').unwrap();
+    let mut bytes = value.as_bytes().to_vec();
+    bytes[20] = if bytes[20] == b'A' { b'B' } else { b'A' };
+    let tampered = format!("{}={}", name, String::from_utf8(bytes).unwrap());
+    for bad in [tampered, "session=abc".to_owned()] {
+        let rejection = warp::test::request()
+            .header("cookie", bad)
+            .filter(&read)
+            .await
+            .unwrap_err();
+        assert!(rejection.find::<warp::reject::InvalidCookie>().is_some());
+    }
+    let other = warp::cookie::private::<Session>("other", key);
+    let req = warp::test::request().header("cookie", format!("other={}", value));
+    assert!(!req.matches(&other).await);
+
+    let stranger = warp::cookie::private::<Session>("session", warp::cookie::Key::generate());
+    let req = warp::test::request().header("cookie", &cookie);
+    assert!(!req.matches(&stranger).await);
+}
+
+#[tokio::test]
+async fn private_key_rotation() {
+    let old = warp::cookie::Key::from_secret(&[1; 32]);
+    let new = warp::cookie::Key::from_secret(&[2; 32]).with_previous(&old);
+
+    let res = warp::test::request()
+        .reply(
+            &warp::any()
+                .map(move || warp::reply::with_private_cookie(warp::reply(), "n", &7u32, &old)),
+        )
+        .await;
+    let req = warp::test::request().header("cookie", set_cookie(&res));
+    let n = warp::cookie::private::<u32>("n", new);
+    assert_eq!(req.filter(&n).await.unwrap(), 7);
+}
@@ -0,0 +1,62 @@
+#![deny(warnings)]
+
+use warp::csp::Nonce;
+use warp::Filter;
+
+#[tokio::test]
+async fn policy() {
+    let policy = warp::csp()
+        .default_src(vec!["'self'"])
+        .img_src(vec!["'self'", "data:"])
+        .default_src(vec!["https://cdn.example.com"])
+        .upgrade_insecure_requests()
+        .report_uri("/csp");
+    let route = warp::any().map(warp::reply).with(policy);
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(
+        res.headers()["content-security-policy"],
+        "default-src 'self' https://cdn.example.com; img-src 'self' data:; \
+         upgrade-insecure-requests; report-uri /csp"
+    );
+}
+
+#[tokio::test]
+async fn report_only() {
+    let policy = warp::csp().default_src(vec!["'none'"]).report_only(true);
+    let route = warp::any().map(warp::reply).with(policy);
+
+    let res = warp::test::request().reply(&route).await;
+    assert!(!res.headers().contains_key("content-security-policy"));
+    assert_eq!(
+        res.headers()["content-security-policy-report-only"],
+        "default-src 'none'"
+    );
+}
+
+#[tokio::test]
+async fn nonce_matches_header() {
+    let policy = warp::csp()
+        .script_src(vec!["'self'"])
+        .script_nonce()
+        .style_nonce();
+    let route = warp::csp::nonce()
+        .map(|nonce: Nonce| nonce.to_string())
+        .with(policy);
+
+    let res = warp::test::request().reply(&route).await;
+    let nonce = std::str::from_utf8(res.body()).unwrap();
+    assert_eq!(nonce.len(), 24);
+    assert_eq!(
+        res.headers()["content-security-policy"],
+        format!(
+            "script-src 'self' 'nonce-{0}'; style-src 'nonce-{0}'",
+            nonce
+        )
+        .as_str()
+    );
+
+    // Every request gets its own nonce.
+    let other = warp::test::request().reply(&route).await;
+    assert_ne!(other.body(), res.body());
+}
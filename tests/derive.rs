@@ -0,0 +1,84 @@
+#![deny(warnings)]
+
+use warp::path::UriPath;
+use warp::Filter;
+
+#[derive(Debug, PartialEq, UriPath)]
+#[uri_path("/users/:user_id/posts/:post_id")]
+struct UserPost {
+    user_id: u64,
+    post_id: u64,
+}
+
+#[derive(Debug, PartialEq, UriPath)]
+#[uri_path("/files/{name}")]
+struct File {
+    name: String,
+}
+
+#[derive(Debug, PartialEq, UriPath)]
+#[uri_path("/")]
+struct Index {}
+
+#[tokio::test]
+async fn extracts_fields() {
+    let _ = pretty_env_logger::try_init();
+
+    let post = warp::path::typed::<UserPost>();
+    let extracted = warp::test::request()
+        .path("/users/7/posts/42")
+        .filter(&post)
+        .await
+        .unwrap();
+    assert_eq!(
+        extracted,
+        UserPost {
+            user_id: 7,
+            post_id: 42
+        }
+    );
+
+    let file = warp::path::typed::<File>();
+    let extracted = warp::test::request()
+        .path("/files/a.txt")
+        .filter(&file)
+        .await
+        .unwrap();
+    assert_eq!(extracted.name, "a.txt");
+
+    let index = warp::path::typed::<Index>();
+    assert!(warp::test::request().path("/").matches(&index).await);
+}
+
+#[tokio::test]
+async fn rejects_mismatches() {
+    let _ = pretty_env_logger::try_init();
+
+    let post = warp::path::typed::<UserPost>();
+    for path in &[
+        "/users/7/posts",
+        "/users/seven/posts/42",
+        "/users/7/comments/42",
+        "/users/7/posts/42/extra",
+    ] {
+        assert!(
+            !warp::test::request().path(path).matches(&post).await,
+            "{}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn records_template() {
+    let route = warp::path("api")
+        .and(warp::path::typed::<UserPost>())
+        .and(warp::path::template())
+        .map(|_, template: warp::path::Template| template.to_string());
+
+    let res = warp::test::request()
+        .path("/api/users/7/posts/42")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "/api/users/{u64}/posts/{u64}");
+}
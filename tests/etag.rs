@@ -0,0 +1,89 @@
+#![deny(warnings)]
+
+use warp::http::header::{CACHE_CONTROL, ETAG};
+use warp::Filter;
+
+#[tokio::test]
+async fn sets_etag_from_body() {
+    let route = warp::any().map(|| "stable").with(warp::etag());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "stable");
+    let etag = res.headers()[ETAG].clone();
+    assert!(etag.to_str().unwrap().starts_with('"'));
+
+    let again = warp::test::request().reply(&route).await;
+    assert_eq!(again.headers()[ETAG], etag);
+
+    let other = warp::any().map(|| "changed").with(warp::etag());
+    let res = warp::test::request().reply(&other).await;
+    assert_ne!(res.headers()[ETAG], etag);
+}
+
+#[tokio::test]
+async fn if_none_match_gets_304() {
+    let route = warp::any()
+        .map(|| warp::reply::with_header("stable", CACHE_CONTROL, "max-age=60"))
+        .with(warp::etag());
+
+    let res = warp::test::request().reply(&route).await;
+    let etag = res.headers()[ETAG].to_str().unwrap().to_owned();
+
+    let res = warp::test::request()
+        .header("if-none-match", format!("\"other\", W/{}", etag))
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+    assert!(res.body().is_empty());
+    assert_eq!(res.headers()[ETAG], etag.as_str());
+    assert_eq!(res.headers()[CACHE_CONTROL], "max-age=60");
+
+    let res = warp::test::request()
+        .header("if-none-match", "*")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"other\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "stable");
+}
+
+#[tokio::test]
+async fn keeps_existing_etag() {
+    let route = warp::any()
+        .map(|| warp::reply::with_header("body", ETAG, "\"v1\""))
+        .with(warp::etag());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()[ETAG], "\"v1\"");
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"v1\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+}
+
+#[tokio::test]
+async fn skips_unsafe_methods_and_errors() {
+    let route = warp::any().map(|| "stable").with(warp::etag());
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("if-none-match", "*")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert!(!res.headers().contains_key(ETAG));
+
+    let route = warp::any()
+        .map(|| warp::reply::with_status("nope", warp::http::StatusCode::NOT_FOUND))
+        .with(warp::etag());
+    let res = warp::test::request().reply(&route).await;
+    assert!(!res.headers().contains_key(ETAG));
+}
@@ -26,3 +26,32 @@ async fn get_missing() {
     assert_eq!(res.status(), 500);
     assert_eq!(res.body(), "Missing request extension");
 }
+
+#[tokio::test]
+async fn set_then_get() {
+    let route = warp::ext::set(Ext1(7))
+        .and(warp::ext::get::<Ext1>())
+        .map(|e: Ext1| e.0.to_string());
+
+    let res = warp::test::request().extension(Ext1(1)).reply(&route).await;
+    assert_eq!(res.body(), "7");
+}
+
+#[tokio::test]
+async fn store_extracted() {
+    let auth = warp::header::<i32>("x-id").map(Ext1);
+    let route = warp::ext::store(auth)
+        .and(warp::path("me"))
+        .and(warp::ext::get::<Ext1>())
+        .map(|e: Ext1| e.0.to_string());
+
+    let res = warp::test::request()
+        .path("/me")
+        .header("x-id", "42")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "42");
+
+    let res = warp::test::request().path("/me").reply(&route).await;
+    assert_eq!(res.status(), 400);
+}
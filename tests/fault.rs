@@ -0,0 +1,65 @@
+#![deny(warnings)]
+
+use std::time::{Duration, Instant};
+
+use warp::fault::Config;
+use warp::http::StatusCode;
+use warp::Filter;
+
+fn hello() -> impl Filter<Extract = (String,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(|| "hello ".repeat(100))
+}
+
+#[tokio::test]
+async fn no_faults() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = hello().with(warp::fault::inject(Config::new()));
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body().len(), 600);
+}
+
+#[tokio::test]
+async fn injected_latency_and_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = hello().with(warp::fault::inject(
+        Config::new().latency(1.0, Duration::from_millis(50)),
+    ));
+    let start = Instant::now();
+    let res = warp::test::request().reply(&route).await;
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(res.status(), 200);
+
+    let route = hello().with(warp::fault::inject(
+        Config::new().error(1.0, StatusCode::SERVICE_UNAVAILABLE),
+    ));
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.body(), "");
+}
+
+#[tokio::test]
+async fn injected_broken_connections() {
+    let _ = pretty_env_logger::try_init();
+
+    let client = warp::hyper::Client::new();
+
+    let server = warp::test::serve(hello().with(warp::fault::inject(Config::new().abort(1.0))));
+    let res = client.get(server.url("/").parse().unwrap()).await;
+    assert!(res.unwrap_err().is_incomplete_message());
+    server.shutdown().await;
+
+    let server = warp::test::serve(hello().with(warp::fault::inject(Config::new().truncate(1.0))));
+    let res = client.get(server.url("/").parse().unwrap()).await.unwrap();
+    assert_eq!(res.headers()["content-length"], "600");
+    assert!(warp::hyper::body::to_bytes(res.into_body()).await.is_err());
+    server.shutdown().await;
+}
+
+#[test]
+#[should_panic(expected = "fault probability must be between 0 and 1")]
+fn invalid_probability() {
+    let _ = Config::new().abort(1.5);
+}
@@ -1,5 +1,8 @@
 #![deny(warnings)]
 use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use warp::Filter;
 
 #[tokio::test]
@@ -79,6 +82,45 @@ async fn map() {
     assert_eq!(resp.status(), 200);
 }
 
+#[tokio::test]
+async fn then() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path::param().then(|id: u32| async move {
+        tokio::task::yield_now().await;
+        format!("#{}", id)
+    });
+
+    let ext = warp::test::request()
+        .path("/7")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(ext, "#7");
+
+    // The error is still the one of the filter before.
+    let res = warp::test::request().path("/nope").reply(&route).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn and_then_local() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path::param().and_then_local(|id: u32| async move {
+        let id = std::rc::Rc::new(id);
+        tokio::task::yield_now().await;
+        Ok::<_, warp::Rejection>(format!("#{}", id))
+    });
+
+    let local = tokio::task::LocalSet::new();
+    let ext = local
+        .run_until(warp::test::request().path("/7").filter(&route))
+        .await
+        .unwrap();
+    assert_eq!(ext, "#7");
+}
+
 #[tokio::test]
 async fn or() {
     let _ = pretty_env_logger::try_init();
@@ -133,6 +175,51 @@ async fn recover() {
     let _: Result<_, Infallible> = warp::test::request().filter(&f).await;
 }
 
+#[tokio::test]
+async fn map_err() {
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Debug)]
+    struct MissingKey;
+    impl warp::reject::Reject for MissingKey {}
+
+    let key = warp::header::<String>("x-key").map_err(|_| MissingKey);
+
+    let err = warp::test::request().filter(&key).await.unwrap_err();
+    assert!(err.find::<MissingKey>().is_some());
+
+    let key = warp::test::request()
+        .header("x-key", "k")
+        .filter(&key)
+        .await
+        .unwrap();
+    assert_eq!(key, "k");
+}
+
+#[tokio::test]
+async fn recover_with() {
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Debug)]
+    struct NotAdmin;
+    impl warp::reject::Reject for NotAdmin {}
+
+    let admin = warp::path("admin")
+        .and_then(|| async { Err::<&str, _>(warp::reject::custom(NotAdmin)) })
+        .recover_with(|_: &NotAdmin| {
+            warp::reply::with_status("admins only", warp::http::StatusCode::FORBIDDEN)
+        });
+
+    let res = warp::test::request().path("/admin").reply(&admin).await;
+    assert_eq!(res.status(), 403);
+    assert_eq!(res.body(), "admins only");
+
+    // Other rejections are left for other filters.
+    let routes = admin.or(warp::path("other").map(|| "other"));
+    let res = warp::test::request().path("/other").reply(&routes).await;
+    assert_eq!(res.body(), "other");
+}
+
 #[tokio::test]
 async fn unify() {
     let _ = pretty_env_logger::try_init();
@@ -146,6 +233,102 @@ async fn unify() {
     assert_eq!(ex, 1);
 }
 
+#[tokio::test]
+async fn map_response() {
+    let _ = pretty_env_logger::try_init();
+
+    let f = warp::path::param::<u32>()
+        .map(|id: u32| format!("user {}", id))
+        .map_response(|mut res: warp::reply::Response| {
+            res.headers_mut()
+                .insert("x-mapped", warp::http::HeaderValue::from_static("1"));
+            res
+        });
+
+    let res = warp::test::request().path("/7").reply(&f).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-mapped"], "1");
+    assert_eq!(res.body(), "user 7");
+}
+
+#[tokio::test]
+async fn shared() {
+    let _ = pretty_env_logger::try_init();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let runs2 = runs.clone();
+    let f = warp::any()
+        .and_then(move || {
+            let runs = runs2.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, Infallible>(runs.fetch_add(1, Ordering::SeqCst))
+            }
+        })
+        .shared();
+
+    // concurrent requests wait for the first run
+    let (a, b) = tokio::join!(
+        warp::test::request().filter(&f),
+        warp::test::request().filter(&f),
+    );
+    assert_eq!(a.unwrap(), 0);
+    assert_eq!(b.unwrap(), 0);
+
+    let c = warp::test::request().filter(&f.clone()).await.unwrap();
+    assert_eq!(c, 0);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn cached() {
+    let _ = pretty_env_logger::try_init();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let runs2 = runs.clone();
+    let f = warp::any()
+        .map(move || runs2.fetch_add(1, Ordering::SeqCst))
+        .cached(Duration::from_millis(50));
+
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 0);
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 0);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 1);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn cached_with_paused_time() {
+    warp::test::pause_time();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let runs2 = runs.clone();
+    let f = warp::any()
+        .map(move || runs2.fetch_add(1, Ordering::SeqCst))
+        .cached(Duration::from_secs(60));
+
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 0);
+    warp::test::advance(Duration::from_secs(59));
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 0);
+    warp::test::advance(Duration::from_secs(1));
+    assert_eq!(warp::test::request().filter(&f).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn untuple() {
+    let _ = pretty_env_logger::try_init();
+
+    let f = warp::path::param::<u32>()
+        .map(|id: u32| ((id,), ("name", true), ()))
+        .untuple_one()
+        .untuple();
+
+    let ex = warp::test::request().path("/7").filter(&f).await.unwrap();
+
+    assert_eq!(ex, (7, "name", true));
+}
+
 #[should_panic]
 #[tokio::test]
 async fn nested() {
@@ -156,3 +339,38 @@ async fn nested() {
 
     let _ = warp::test::request().filter(&f).await;
 }
+
+#[tokio::test]
+async fn custom_wrap() {
+    use warp::filters::BoxedFilter;
+    use warp::{Rejection, Reply, Wrap};
+
+    struct Tag(&'static str);
+
+    impl<F, R> Wrap<F> for Tag
+    where
+        F: Filter<Extract = (R,)> + Clone + Send + Sync + 'static,
+        F::Error: Into<Rejection>,
+        R: Reply,
+    {
+        type Wrapped = BoxedFilter<(warp::reply::Response,)>;
+
+        fn wrap(&self, filter: F) -> Self::Wrapped {
+            let tag = self.0;
+            filter
+                .map(move |reply: R| warp::reply::with_header(reply, "x-tag", tag).into_response())
+                .boxed()
+        }
+    }
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("a").map(warp::reply).with(Tag("wrapped"));
+
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-tag"], "wrapped");
+
+    let res = warp::test::request().path("/b").reply(&route).await;
+    assert_eq!(res.status(), 404);
+}
@@ -0,0 +1,66 @@
+#![deny(warnings)]
+
+use warp::flash::Flash;
+use warp::http::Uri;
+use warp::Filter;
+
+fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let save = warp::post()
+        .and(warp::path("settings"))
+        .and(warp::flash())
+        .map(|flash: Flash| {
+            flash.set("Saved!");
+            warp::redirect::see_other(Uri::from_static("/settings"))
+        });
+    let show = warp::get()
+        .and(warp::path("settings"))
+        .and(warp::flash())
+        .map(|flash: Flash| flash.message().unwrap_or("none").to_owned());
+    save.or(show)
+        .with(warp::session(warp::session::MemoryStore::default()))
+}
+
+#[tokio::test]
+async fn shown_once() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = routes();
+    let client = warp::test::Client::new();
+
+    let res = client.request().path("/settings").reply(&routes).await;
+    assert_eq!(res.body(), "none");
+
+    let res = client
+        .request()
+        .method("POST")
+        .path("/settings")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 303);
+
+    let res = client.request().path("/settings").reply(&routes).await;
+    assert_eq!(res.body(), "Saved!");
+
+    let res = client.request().path("/settings").reply(&routes).await;
+    assert_eq!(res.body(), "none");
+}
+
+#[tokio::test]
+async fn per_client() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = routes();
+    let (alice, bob) = (warp::test::Client::new(), warp::test::Client::new());
+
+    alice
+        .request()
+        .method("POST")
+        .path("/settings")
+        .reply(&routes)
+        .await;
+
+    let res = bob.request().path("/settings").reply(&routes).await;
+    assert_eq!(res.body(), "none");
+    let res = alice.request().path("/settings").reply(&routes).await;
+    assert_eq!(res.body(), "Saved!");
+}
@@ -69,3 +69,170 @@ async fn optional() {
         "invalid optional header still rejects",
     );
 }
+
+#[tokio::test]
+async fn test_request_auth() {
+    let _ = pretty_env_logger::try_init();
+
+    let auth = warp::header::<String>("authorization");
+
+    let basic = warp::test::request()
+        .basic_auth("Aladdin", "open sesame")
+        .filter(&auth)
+        .await
+        .unwrap();
+    assert_eq!(basic, "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+
+    let bearer = warp::test::request()
+        .bearer_auth("mF_9.B5f-4.1JqM")
+        .filter(&auth)
+        .await
+        .unwrap();
+    assert_eq!(bearer, "Bearer mF_9.B5f-4.1JqM");
+}
+
+#[tokio::test]
+async fn typed() {
+    use warp::headers::{CacheControl, ContentType, IfNoneMatch};
+
+    let cache_control = warp::header::typed::<CacheControl>();
+
+    let req = warp::test::request().header("cache-control", "no-cache, max-age=60");
+    let extracted = req.filter(&cache_control).await.unwrap();
+    assert!(extracted.no_cache());
+    assert_eq!(
+        extracted.max_age(),
+        Some(std::time::Duration::from_secs(60))
+    );
+
+    let rejection = warp::test::request()
+        .filter(&cache_control)
+        .await
+        .unwrap_err();
+    let missing = rejection.find::<warp::reject::MissingHeader>().unwrap();
+    assert_eq!(missing.name(), "cache-control");
+
+    let content_type = warp::header::typed::<ContentType>();
+    let req = warp::test::request().header("content-type", "not a mime type");
+    let rejection = req.filter(&content_type).await.unwrap_err();
+    let invalid = rejection.find::<warp::reject::InvalidHeader>().unwrap();
+    assert_eq!(invalid.name(), "content-type");
+
+    let if_none_match = warp::header::typed_optional::<IfNoneMatch>();
+    let req = warp::test::request();
+    assert_eq!(req.filter(&if_none_match).await.unwrap(), None);
+    let req = warp::test::request().header("if-none-match", "\"abc\"");
+    let etag = req.filter(&if_none_match).await.unwrap().unwrap();
+    assert!(!etag.precondition_passes(&"\"abc\"".parse().unwrap()));
+    let req = warp::test::request().header("content-type", "nope");
+    let optional = warp::header::typed_optional::<ContentType>();
+    assert!(!req.matches(&optional).await);
+}
+
+#[tokio::test]
+async fn accept_language() {
+    use warp::header::LanguageTag;
+
+    let supported = [
+        LanguageTag::from_static("en-US"),
+        LanguageTag::from_static("fr"),
+        LanguageTag::from_static("pt-BR"),
+    ];
+    let lang = warp::header::accept_language(&supported).map(|lang: LanguageTag| lang.to_string());
+
+    let negotiate = |header: Option<&'static str>| {
+        let mut req = warp::test::request();
+        if let Some(header) = header {
+            req = req.header("accept-language", header);
+        }
+        let lang = lang.clone();
+        async move { req.filter(&lang).await.unwrap() }
+    };
+
+    // Missing, unsupported and garbage headers fall back to the first.
+    assert_eq!(negotiate(None).await, "en-US");
+    assert_eq!(negotiate(Some("de, ja;q=0.5")).await, "en-US");
+    assert_eq!(negotiate(Some(";;,q=x")).await, "en-US");
+
+    // Highest q-value wins, ties keep the client's order.
+    assert_eq!(negotiate(Some("en;q=0.5, FR;q=0.9")).await, "fr");
+    assert_eq!(negotiate(Some("de, pt, fr")).await, "pt-BR");
+    // Ranges are shortened when nothing starts with them.
+    assert_eq!(negotiate(Some("fr-CA, en;q=0.8")).await, "fr");
+    // Wildcards pick the first supported that wasn't refused.
+    assert_eq!(negotiate(Some("de, *;q=0.1, en;q=0")).await, "fr");
+
+    let route = warp::header::accept_language(&supported)
+        .map(|lang: LanguageTag| warp::reply::with_content_language("hi", &lang));
+    let res = warp::test::request()
+        .header("accept-language", "pt-BR")
+        .reply(&route)
+        .await;
+    assert_eq!(res.headers()["content-language"], "pt-BR");
+    assert_eq!(res.headers()["vary"], "accept-language");
+
+    assert!("en-GB".parse::<LanguageTag>().is_ok());
+    assert!("en_GB".parse::<LanguageTag>().is_err());
+    assert_eq!(LanguageTag::from_static("EN-gb"), "en-GB".parse().unwrap());
+}
+
+#[tokio::test]
+async fn forwarded() {
+    use warp::header::Forwarded;
+
+    let forwarded = warp::header::typed::<Forwarded>();
+
+    let req = warp::test::request().header(
+        "forwarded",
+        "for=\"[2001:db8::1]:4711\";proto=https;host=example.com, ,\
+         for=_hidden;by=unknown, For=\"192.0.2.60:_port\";BY=203.0.113.43;ext=\"a, b\"",
+    );
+    let extracted = req.filter(&forwarded).await.unwrap();
+    let elements = extracted.elements();
+    assert_eq!(elements.len(), 3);
+
+    let client = elements[0].for_node().unwrap();
+    assert_eq!(client.ip(), Some("2001:db8::1".parse().unwrap()));
+    assert_eq!(client.port(), Some(4711));
+    assert_eq!(elements[0].proto(), Some("https"));
+    assert_eq!(elements[0].host(), Some("example.com"));
+    assert!(elements[0].by_node().is_none());
+
+    assert!(elements[1].for_node().unwrap().is_obfuscated());
+    assert!(elements[1].by_node().unwrap().is_unknown());
+    assert_eq!(elements[1].for_node().unwrap().ip(), None);
+
+    let proxy = elements[2].for_node().unwrap();
+    assert_eq!(proxy.ip(), Some("192.0.2.60".parse().unwrap()));
+    assert_eq!(proxy.port(), None);
+    assert_eq!(proxy.as_str(), "192.0.2.60:_port");
+    assert_eq!(
+        elements[2].by_node().unwrap().ip(),
+        Some("203.0.113.43".parse().unwrap())
+    );
+
+    assert_eq!(
+        extracted.to_string(),
+        "for=\"[2001:db8::1]:4711\";host=example.com;proto=https, \
+         by=unknown;for=_hidden, by=203.0.113.43;for=\"192.0.2.60:_port\""
+    );
+
+    for invalid in &[
+        "for=[::1]",
+        "for=1.2.3.4;for=5.6.7.8",
+        "for=example.com",
+        "for=\"1.2.3.4:99999\"",
+        "proto=\"not a scheme\"",
+        "for=1.2.3.4 by=5.6.7.8",
+        "for=\"1.2.3.4",
+        ",",
+    ] {
+        let req = warp::test::request().header("forwarded", *invalid);
+        let rejection = req.filter(&forwarded).await.unwrap_err();
+        assert!(
+            rejection.find::<warp::reject::InvalidHeader>().is_some(),
+            "{:?} should be invalid",
+            invalid
+        );
+    }
+}
@@ -0,0 +1,80 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+fn json(res: &http::Response<bytes::Bytes>) -> Value {
+    serde_json::from_slice(res.body()).unwrap()
+}
+
+#[tokio::test]
+async fn passing_checks() {
+    let _ = pretty_env_logger::try_init();
+
+    let health = warp::health::builder()
+        .readiness("db", Duration::from_secs(1), || async {
+            Ok::<_, String>(())
+        })
+        .build();
+    let routes = health.routes();
+
+    for path in &["/healthz", "/readyz", "/livez"] {
+        let res = warp::test::request().path(path).reply(&routes).await;
+        assert_eq!(res.status(), 200, "{}", path);
+        assert_eq!(json(&res)["status"], "pass");
+    }
+
+    let res = warp::test::request().path("/readyz").reply(&routes).await;
+    assert_eq!(json(&res)["checks"]["db"]["status"], "pass");
+
+    // Liveness doesn't run readiness checks.
+    let res = warp::test::request().path("/livez").reply(&routes).await;
+    assert_eq!(json(&res)["checks"], serde_json::json!({}));
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/healthz")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn failing_checks() {
+    let _ = pretty_env_logger::try_init();
+
+    let health = warp::health::builder()
+        .readiness("db", Duration::from_secs(1), || async {
+            Err::<(), _>("connection refused")
+        })
+        .readiness("queue", Duration::from_millis(10), || async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok::<_, String>(())
+        })
+        .liveness("loop", Duration::from_secs(1), || async {
+            Ok::<_, String>(())
+        })
+        .build();
+
+    let res = warp::test::request()
+        .path("/healthz")
+        .reply(&health.healthz())
+        .await;
+    assert_eq!(res.status(), 503);
+    let body = json(&res);
+    assert_eq!(body["status"], "fail");
+    assert_eq!(body["checks"]["db"]["error"], "connection refused");
+    assert_eq!(body["checks"]["queue"]["status"], "fail");
+    assert!(body["checks"]["queue"]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("timed out"));
+    assert_eq!(body["checks"]["loop"]["status"], "pass");
+
+    let res = warp::test::request()
+        .path("/livez")
+        .reply(&health.livez())
+        .await;
+    assert_eq!(res.status(), 200);
+}
@@ -1,5 +1,6 @@
 #![deny(warnings)]
 use warp::host::Authority;
+use warp::Filter;
 
 #[tokio::test]
 async fn exact() {
@@ -85,3 +86,71 @@ async fn optional() {
     let req = warp::test::request();
     assert_eq!(req.filter(&filter).await.unwrap(), None);
 }
+
+#[tokio::test]
+async fn matches() {
+    let _ = pretty_env_logger::try_init();
+
+    let exact = warp::host::matches("Api.Example.com");
+    let wildcard = warp::host::matches("*.example.com");
+    let with_port = warp::host::matches("example.com:8080");
+
+    let req = || warp::test::request().header("host", "api.example.com:3030");
+    assert!(req().matches(&exact).await);
+    assert!(req().matches(&wildcard).await);
+    assert!(!req().matches(&with_port).await);
+
+    let req = || warp::test::request().path("http://example.com:8080/");
+    assert!(!req().matches(&exact).await);
+    assert!(!req().matches(&wildcard).await);
+    assert!(req().matches(&with_port).await);
+
+    let subdomain = warp::host::subdomain("example.com");
+    let tenant = warp::test::request()
+        .header("host", "Acme.eu.Example.com")
+        .filter(&subdomain)
+        .await
+        .unwrap();
+    assert_eq!(tenant, "acme.eu");
+}
+
+#[tokio::test]
+async fn vhost() {
+    let _ = pretty_env_logger::try_init();
+
+    let api = warp::path("users").map(|| "users");
+    let tenants = warp::host::subdomain("example.com").map(|tenant| tenant);
+    let www = warp::any().map(|| "home");
+
+    let routes = warp::vhost! {
+        "api.example.com" => api,
+        "*.example.com" => tenants,
+        _ => www,
+    };
+
+    let res = warp::test::request()
+        .header("host", "api.example.com")
+        .path("/users")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "users");
+
+    let res = warp::test::request()
+        .path("http://acme.example.com/")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "acme");
+
+    let res = warp::test::request()
+        .header("host", "other.org")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "home");
+
+    let only_api = warp::vhost! { "api.example.com" => warp::any().map(warp::reply) };
+    let res = warp::test::request()
+        .header("host", "other.org")
+        .reply(&only_api)
+        .await;
+    assert_eq!(res.status(), 404);
+}
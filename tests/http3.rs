@@ -0,0 +1,185 @@
+#![deny(warnings)]
+
+use std::convert::TryFrom;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bytes::{Buf, Bytes, BytesMut};
+use quinn::rustls;
+use quinn::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified};
+use quinn::rustls::pki_types::pem::PemObject;
+use quinn::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use quinn::rustls::{DigitallySignedStruct, SignatureScheme};
+use warp::Filter;
+
+#[tokio::test]
+async fn round_trip() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::post()
+        .and(warp::path("echo"))
+        .and(warp::body::bytes())
+        .map(|body: Bytes| body.to_vec());
+    let addr = free_addr();
+    tokio::spawn(warp::serve(route).run_h3(addr, tls_config()));
+
+    let (status, body) = request(addr, "/echo", Bytes::from_static(b"ping")).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "ping");
+
+    let (status, _) = request(addr, "/nope", Bytes::new()).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn refuses_large_bodies() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::bytes().map(|body: Bytes| body.to_vec());
+    let addr = free_addr();
+    let server = warp::serve(route).h3_max_body_size(16);
+    tokio::spawn(server.run_h3(addr, tls_config()));
+
+    let (status, body) = request(addr, "/", Bytes::from_static(&[b'a'; 16])).await;
+    assert_eq!(status, 200);
+    assert_eq!(body.len(), 16);
+
+    let (status, _) = request(addr, "/", Bytes::from_static(&[b'a'; 17])).await;
+    assert_eq!(status, 413);
+}
+
+#[tokio::test]
+async fn applies_server_hooks() {
+    let _ = pretty_env_logger::try_init();
+
+    let opened = Arc::new(AtomicUsize::new(0));
+    let requests = Arc::new(AtomicUsize::new(0));
+    let responses = Arc::new(AtomicUsize::new(0));
+
+    let route = warp::path("hello").map(|| "Hello, World!");
+    let server = warp::serve(route)
+        .on_connection_open({
+            let opened = opened.clone();
+            move |_| {
+                opened.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .on_request({
+            let requests = requests.clone();
+            move |req| {
+                assert_eq!(req.uri().path(), "/hello");
+                requests.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .on_response({
+            let responses = responses.clone();
+            move |res| {
+                assert_eq!(res.status(), 200);
+                responses.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+    let addr = free_addr();
+    tokio::spawn(server.run_h3(addr, tls_config()));
+
+    let (status, body) = request(addr, "/hello", Bytes::new()).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "Hello, World!");
+    assert_eq!(opened.load(Ordering::SeqCst), 1);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert_eq!(responses.load(Ordering::SeqCst), 1);
+}
+
+// `run_h3` doesn't report the address it's bound to, so a free port is
+// looked up first.
+fn free_addr() -> SocketAddr {
+    UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+fn tls_config() -> rustls::ServerConfig {
+    let cert = CertificateDer::from_pem_slice(include_bytes!("../examples/tls/cert.pem")).unwrap();
+    let key = PrivateKeyDer::from_pem_slice(include_bytes!("../examples/tls/key.rsa")).unwrap();
+    rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap()
+}
+
+async fn request(addr: SocketAddr, path: &str, body: Bytes) -> (u16, Bytes) {
+    let mut tls = rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoVerifier))
+        .with_no_client_auth();
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+    let crypto = quinn::crypto::rustls::QuicClientConfig::try_from(tls).unwrap();
+    let mut endpoint = quinn::Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+    endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(crypto)));
+
+    let conn = endpoint.connect(addr, "localhost").unwrap().await.unwrap();
+    let (mut driver, mut send_request) = h3::client::new(h3_quinn::Connection::new(conn))
+        .await
+        .unwrap();
+    tokio::spawn(async move { futures::future::poll_fn(|cx| driver.poll_close(cx)).await });
+
+    let req = http1::Request::post(format!("https://localhost{}", path))
+        .body(())
+        .unwrap();
+    let mut stream = send_request.send_request(req).await.unwrap();
+    // The server may stop reading a body it refuses, so sending it can fail.
+    if !body.is_empty() {
+        let _ = stream.send_data(body).await;
+    }
+    let _ = stream.finish().await;
+
+    let res = stream.recv_response().await.unwrap();
+    let mut body = BytesMut::new();
+    while let Some(mut chunk) = stream.recv_data().await.unwrap() {
+        body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+    }
+    (res.status().as_u16(), body.freeze())
+}
+
+// The example certificate is signed by a CA that isn't around, so the
+// client takes any certificate.
+#[derive(Debug)]
+struct NoVerifier;
+
+impl rustls::client::danger::ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        rustls::crypto::ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
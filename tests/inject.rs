@@ -0,0 +1,36 @@
+#![deny(warnings)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use warp::Filter;
+
+#[tokio::test]
+async fn inject() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let route = warp::inject(hits.clone())
+        .map(|hits: Arc<AtomicUsize>| hits.fetch_add(1, Ordering::SeqCst).to_string());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.body(), "0");
+    let res = warp::test::request().reply(&route.clone()).await;
+    assert_eq!(res.body(), "1");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tenant(&'static str);
+
+#[tokio::test]
+async fn overridable() {
+    let tenant = warp::inject::overridable(Tenant("default"));
+
+    let extracted = warp::test::request().filter(&tenant).await.unwrap();
+    assert_eq!(extracted, Tenant("default"));
+
+    let extracted = warp::test::request()
+        .extension(Tenant("other"))
+        .filter(&tenant)
+        .await
+        .unwrap();
+    assert_eq!(extracted, Tenant("other"));
+}
@@ -0,0 +1,178 @@
+#![deny(warnings)]
+
+use lambda_runtime::{Context, LambdaEvent};
+use serde_json::{json, Value};
+use tower_service::Service;
+use warp::Filter;
+
+async fn call<F>(handler: &mut warp::lambda::Handler<F>, event: Value) -> Value
+where
+    warp::lambda::Handler<F>: Service<
+        LambdaEvent<Value>,
+        Response = Value,
+        Error = Box<dyn std::error::Error + Send + Sync>,
+    >,
+{
+    handler
+        .call(LambdaEvent::new(event, Context::default()))
+        .await
+        .expect("handled")
+}
+
+fn routes() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let hello = warp::get()
+        .and(warp::path!("hello" / String))
+        .and(warp::query::raw())
+        .and(warp::header::<String>("x-greeting"))
+        .and(warp::cookie::<String>("session"))
+        .map(|name, query, greeting, session| {
+            warp::reply::with_header(
+                format!("{} {}, {} ({})", greeting, name, query, session),
+                "set-cookie",
+                "seen=1",
+            )
+        });
+    let echo = warp::post()
+        .and(warp::path("echo"))
+        .and(warp::body::bytes())
+        .map(|body: bytes::Bytes| body.to_vec());
+    let addr = warp::path("addr")
+        .and(warp::addr::remote())
+        .map(|addr: Option<std::net::SocketAddr>| addr.unwrap().ip().to_string());
+    hello.or(echo).or(addr)
+}
+
+#[tokio::test]
+async fn http_api() {
+    let _ = pretty_env_logger::try_init();
+    let mut handler = warp::lambda::handler(routes());
+
+    let res = call(
+        &mut handler,
+        json!({
+            "version": "2.0",
+            "rawPath": "/hello/sean",
+            "rawQueryString": "a=1&b=%20",
+            "headers": { "x-greeting": "Hi" },
+            "cookies": ["session=abc", "other=1"],
+            "requestContext": {
+                "http": { "method": "GET", "sourceIp": "1.2.3.4" }
+            },
+            "isBase64Encoded": false
+        }),
+    )
+    .await;
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["body"], "Hi sean, a=1&b=%20 (abc)");
+    assert_eq!(res["isBase64Encoded"], false);
+    assert_eq!(res["headers"]["content-type"], "text/plain; charset=utf-8");
+    assert_eq!(res["cookies"], json!(["seen=1"]));
+    assert!(res["headers"].get("set-cookie").is_none());
+
+    let res = call(
+        &mut handler,
+        json!({
+            "version": "2.0",
+            "rawPath": "/addr",
+            "rawQueryString": "",
+            "requestContext": {
+                "http": { "method": "GET", "sourceIp": "1.2.3.4" }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(res["body"], "1.2.3.4");
+}
+
+#[tokio::test]
+async fn rest_api() {
+    let _ = pretty_env_logger::try_init();
+    let mut handler = warp::lambda::handler(routes());
+
+    let res = call(
+        &mut handler,
+        json!({
+            "httpMethod": "GET",
+            "path": "/hello/sean",
+            "multiValueQueryStringParameters": { "q": ["a b", "c"] },
+            "headers": { "x-greeting": "Hello", "cookie": "session=xyz" },
+            "multiValueHeaders": { "x-greeting": ["Hello"], "cookie": ["session=xyz"] },
+            "requestContext": { "identity": { "sourceIp": "1.2.3.4" } },
+            "body": null,
+            "isBase64Encoded": false
+        }),
+    )
+    .await;
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["body"], "Hello sean, q=a+b&q=c (xyz)");
+    assert_eq!(res["multiValueHeaders"]["set-cookie"], json!(["seen=1"]));
+    assert!(res.get("statusDescription").is_none());
+
+    // Binary bodies are base64 both ways.
+    let res = call(
+        &mut handler,
+        json!({
+            "httpMethod": "POST",
+            "path": "/echo",
+            "multiValueHeaders": {},
+            "requestContext": {},
+            "body": base64::encode([0xff, 0x00]),
+            "isBase64Encoded": true
+        }),
+    )
+    .await;
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["body"], base64::encode([0xff, 0x00]));
+    assert_eq!(res["isBase64Encoded"], true);
+
+    // Rejections are replies too.
+    let res = call(
+        &mut handler,
+        json!({
+            "httpMethod": "GET",
+            "path": "/nope",
+            "multiValueHeaders": {},
+            "requestContext": {}
+        }),
+    )
+    .await;
+    assert_eq!(res["statusCode"], 405);
+}
+
+#[tokio::test]
+async fn load_balancer() {
+    let _ = pretty_env_logger::try_init();
+    let mut handler = warp::lambda::handler(routes());
+
+    let res = call(
+        &mut handler,
+        json!({
+            "httpMethod": "GET",
+            "path": "/hello/sean",
+            "queryStringParameters": { "q": "a%20b" },
+            "headers": { "x-greeting": "Hey", "cookie": "session=1" },
+            "requestContext": { "elb": { "targetGroupArn": "arn" } },
+            "body": "",
+            "isBase64Encoded": false
+        }),
+    )
+    .await;
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["statusDescription"], "200 OK");
+    assert_eq!(res["body"], "Hey sean, q=a%20b (1)");
+    assert_eq!(res["headers"]["set-cookie"], "seen=1");
+    assert!(res.get("multiValueHeaders").is_none());
+}
+
+#[tokio::test]
+async fn unsupported_event() {
+    let mut handler = warp::lambda::handler(routes());
+    let err = handler
+        .call(LambdaEvent::new(
+            json!({ "Records": [] }),
+            Context::default(),
+        ))
+        .await
+        .unwrap_err();
+    assert!(err.is::<warp::lambda::UnsupportedEvent>());
+}
@@ -0,0 +1,123 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+use warp::http::StatusCode;
+use warp::Filter;
+
+#[tokio::test]
+async fn sheds_over_max_pending() {
+    let _ = pretty_env_logger::try_init();
+
+    let gate = Arc::new(Semaphore::new(0));
+    let shed = warp::load_shed().max_pending(1);
+    let g = gate.clone();
+    let route = warp::any()
+        .and_then(move || {
+            let gate = g.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                Ok::<_, Infallible>("done")
+            }
+        })
+        .with(shed.clone());
+
+    let r = route.clone();
+    let first = tokio::spawn(async move { warp::test::request().reply(&r).await });
+    while shed.load().pending() == 0 {
+        tokio::task::yield_now().await;
+    }
+    assert!(shed.load().is_shedding());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()["retry-after"], "1");
+
+    gate.add_permits(1);
+    assert_eq!(first.await.unwrap().status(), 200);
+    assert!(!shed.load().is_shedding());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn max_pending_holds_under_contention() {
+    let _ = pretty_env_logger::try_init();
+
+    let gate = Arc::new(Semaphore::new(0));
+    let admitted = Arc::new(AtomicUsize::new(0));
+    let (g, a) = (gate.clone(), admitted.clone());
+    let route = warp::any()
+        .and_then(move || {
+            let (gate, admitted) = (g.clone(), a.clone());
+            async move {
+                admitted.fetch_add(1, Ordering::SeqCst);
+                gate.acquire().await.unwrap().forget();
+                Ok::<_, Infallible>("done")
+            }
+        })
+        .with(warp::load_shed().max_pending(4));
+
+    let requests = (0..64)
+        .map(|_| {
+            let route = route.clone();
+            tokio::spawn(async move { warp::test::request().reply(&route).await.status() })
+        })
+        .collect::<Vec<_>>();
+    // Wait until every request was either shed or admitted.
+    while requests.iter().filter(|req| req.is_finished()).count() + admitted.load(Ordering::SeqCst)
+        < 64
+    {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(admitted.load(Ordering::SeqCst), 4);
+
+    gate.add_permits(64);
+    let mut ok = 0;
+    for req in requests {
+        if req.await.unwrap() == StatusCode::OK {
+            ok += 1;
+        }
+    }
+    assert_eq!(ok, 4);
+}
+
+#[tokio::test]
+async fn sheds_over_max_latency() {
+    let _ = pretty_env_logger::try_init();
+
+    let updates = Arc::new(AtomicUsize::new(0));
+    let u = updates.clone();
+    let shed = warp::load_shed()
+        .max_latency(Duration::from_millis(5))
+        .retry_after(Duration::from_secs(30))
+        .on_update(move |_| {
+            u.fetch_add(1, Ordering::SeqCst);
+        });
+    let route = warp::any()
+        .and_then(|| async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok::<_, Infallible>("slow")
+        })
+        .with(shed.clone());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert!(shed.load().latency().unwrap() >= Duration::from_millis(20));
+    assert!(shed.load().is_shedding());
+
+    // The first request after crossing the threshold probes the latency...
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+
+    // ...and the rest are shed until `retry_after` has passed.
+    let rejection = warp::test::request().filter(&route).await.unwrap_err();
+    let overloaded = rejection
+        .find::<warp::load_shed::Overloaded>()
+        .expect("Overloaded rejection");
+    assert_eq!(overloaded.retry_after(), Duration::from_secs(30));
+
+    assert_eq!(updates.load(Ordering::SeqCst), 3);
+}
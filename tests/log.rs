@@ -0,0 +1,290 @@
+#![deny(warnings)]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use warp::Filter;
+
+// A writer the test can read back.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+
+    fn lines(&self) -> Vec<serde_json::Value> {
+        self.text()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn json_writer() {
+    let buffer = Buffer::default();
+    let route = warp::path("hello")
+        .map(|| "Hello, World!")
+        .with(warp::log::json_writer(buffer.clone()));
+
+    warp::test::request()
+        .path("/hello?name=warp")
+        .header("user-agent", "tests")
+        .remote_addr("1.2.3.4:5678".parse().unwrap())
+        .reply(&route)
+        .await;
+    warp::test::request().path("/bye").reply(&route).await;
+
+    let lines = buffer.lines();
+    assert_eq!(lines.len(), 2);
+
+    let hello = &lines[0];
+    assert_eq!(hello["method"], "GET");
+    assert_eq!(hello["path"], "/hello");
+    assert_eq!(hello["status"], 200);
+    assert_eq!(hello["bytes"], 13);
+    assert_eq!(hello["remote_addr"], "1.2.3.4:5678");
+    assert_eq!(hello["user_agent"], "tests");
+    assert!(hello["request_id"].is_null());
+    assert!(hello["latency_ms"].is_f64());
+    let timestamp = hello["timestamp"].as_str().unwrap();
+    assert_eq!(timestamp.len(), "2021-02-03T04:05:06.789Z".len());
+    assert!(timestamp.ends_with('Z'));
+
+    let bye = &lines[1];
+    assert_eq!(bye["status"], 404);
+    assert!(bye["bytes"].is_null());
+    assert!(bye["remote_addr"].is_null());
+}
+
+#[tokio::test]
+async fn json_includes_request_id() {
+    let buffer = Buffer::default();
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::log::json_writer(buffer.clone()))
+        .with(warp::request_id());
+
+    warp::test::request()
+        .header("x-request-id", "abc-123")
+        .reply(&route)
+        .await;
+
+    assert_eq!(buffer.lines()[0]["request_id"], "abc-123");
+}
+
+#[tokio::test]
+async fn clf_and_combined() {
+    let common = Buffer::default();
+    let combined = Buffer::default();
+    let route = warp::path("hello")
+        .map(|| "Hello, World!")
+        .with(warp::log::clf_writer(common.clone()))
+        .with(warp::log::combined_writer(combined.clone()));
+
+    warp::test::request()
+        .path("/hello?name=warp")
+        .header("user-agent", "tests")
+        .remote_addr("1.2.3.4:5678".parse().unwrap())
+        .reply(&route)
+        .await;
+
+    let line = common.text();
+    assert!(line.starts_with("1.2.3.4 - - ["), "{}", line);
+    assert!(
+        line.ends_with(" +0000] \"GET /hello?name=warp HTTP/1.1\" 200 13\n"),
+        "{}",
+        line
+    );
+
+    let line = combined.text();
+    assert!(
+        line.ends_with("\"GET /hello?name=warp HTTP/1.1\" 200 13 \"-\" \"tests\"\n"),
+        "{}",
+        line
+    );
+}
+
+#[tokio::test]
+async fn rotating_file() {
+    let dir = std::env::temp_dir().join(format!("warp-log-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("access.log");
+
+    let file = warp::log::RotatingFile::new(&path)
+        .max_size(100)
+        .keep(2)
+        .open()
+        .unwrap();
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::log::clf_writer(file));
+
+    for _ in 0..5 {
+        warp::test::request().reply(&route).await;
+    }
+    drop(route);
+
+    // Lines are written by a background thread.
+    let rotated = dir.join("access.log.2");
+    for _ in 0..100 {
+        if rotated.exists() && std::fs::metadata(&path).unwrap().len() > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(dir.join("access.log.1").exists());
+    assert!(rotated.exists());
+    assert!(!dir.join("access.log.3").exists());
+    for file in &["access.log", "access.log.1", "access.log.2"] {
+        let text = std::fs::read_to_string(dir.join(file)).unwrap();
+        assert_eq!(text.lines().count(), 1, "{}: {:?}", file, text);
+        assert!(text.len() <= 100);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn custom_route_template_extensions_and_bytes() {
+    #[derive(Clone)]
+    struct User(&'static str);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = {
+        let seen = seen.clone();
+        warp::log::custom(move |info| {
+            seen.lock().unwrap().push((
+                info.route_template(),
+                info.extension::<User>().map(|user| user.0),
+                info.bytes_sent(),
+            ));
+        })
+        .extension::<User>()
+    };
+    let users = warp::path!("users" / u32 / "posts")
+        .and(warp::ext::optional::<User>())
+        .map(|_, _| "posts");
+    let files = warp::path("files")
+        .and(warp::path::tail())
+        .map(|_| warp::reply());
+    let route = users.or(files).with(log);
+
+    warp::test::request()
+        .path("/users/7/posts")
+        .extension(User("sean"))
+        .reply(&route)
+        .await;
+    warp::test::request()
+        .path("/files/a/b.txt")
+        .reply(&route)
+        .await;
+    warp::test::request().path("/nope").reply(&route).await;
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        vec![
+            (Some("/users/{u32}/posts".to_owned()), Some("sean"), Some(5)),
+            (Some("/files/*".to_owned()), None, Some(0)),
+            (None, None, None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn custom_async_after_body_sent() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let log = warp::log::custom_async(move |info: warp::log::OwnedInfo| {
+        let tx = tx.clone();
+        async move {
+            tx.send(info).unwrap();
+        }
+    })
+    .extension::<warp::request_id::RequestId>();
+    let route = warp::path!("hello" / String)
+        .map(|name| format!("Hello, {}!", name))
+        .with(log)
+        .with(warp::request_id());
+
+    let res = warp::test::request()
+        .path("/hello/warp")
+        .header("x-request-id", "abc")
+        .reply(&route)
+        .await;
+    assert_eq!(res.headers()["content-length"], "12");
+    assert_eq!(res.body(), "Hello, warp!");
+
+    let info = rx.recv().await.unwrap();
+    assert_eq!(info.status(), 200);
+    assert_eq!(info.path(), "/hello/warp");
+    assert_eq!(info.bytes_sent(), Some(12));
+    assert_eq!(info.content_length(), Some(12));
+    assert_eq!(info.route_template(), Some("/hello/{String}"));
+    assert_eq!(info.request_id(), Some("abc"));
+    assert_eq!(
+        info.extension::<warp::request_id::RequestId>()
+            .map(|id| id.as_str()),
+        Some("abc")
+    );
+
+    warp::test::request().path("/bye").reply(&route).await;
+    let info = rx.recv().await.unwrap();
+    assert_eq!(info.status(), 404);
+    assert_eq!(info.bytes_sent(), None);
+}
+
+#[tokio::test]
+async fn sampled() {
+    use warp::http::StatusCode;
+    use warp::sample::Sample;
+
+    let logged = Arc::new(Mutex::new(Vec::new()));
+
+    let records = logged.clone();
+    let route = warp::path::full()
+        .map(|path: warp::path::FullPath| {
+            let status = if path.as_str() == "/fail" {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::OK
+            };
+            warp::reply::with_status(warp::reply(), status)
+        })
+        .with(
+            warp::log::custom(move |info| {
+                records.lock().unwrap().push(info.path().to_owned());
+            })
+            .sample(Sample::every(3).or_errors()),
+        );
+
+    for path in &["/1", "/2", "/3", "/fail", "/5", "/6", "/7"] {
+        warp::test::request().path(path).reply(&route).await;
+    }
+    assert_eq!(*logged.lock().unwrap(), vec!["/1", "/fail", "/7"]);
+
+    logged.lock().unwrap().clear();
+    let records = logged.clone();
+    let route = warp::any().map(warp::reply).with(
+        warp::log::custom(move |info| {
+            records.lock().unwrap().push(info.path().to_owned());
+        })
+        .sample(Sample::none()),
+    );
+    warp::test::request().reply(&route).await;
+    assert!(logged.lock().unwrap().is_empty());
+}
@@ -0,0 +1,58 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use warp::maintenance::MaintenanceHandle;
+use warp::Filter;
+
+#[tokio::test]
+async fn toggles_at_runtime() {
+    let handle = MaintenanceHandle::new();
+    let route = warp::any().map(|| "hello").with(warp::maintenance(&handle));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "hello");
+
+    handle.enable();
+    assert!(handle.is_enabled());
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.headers()["retry-after"], "60");
+    assert_eq!(res.body(), "Service Unavailable");
+
+    handle.clone().disable();
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn custom_reply() {
+    let handle = MaintenanceHandle::new();
+    handle.set(true);
+    let route = warp::any().map(|| "hello").with(
+        warp::maintenance(&handle)
+            .retry_after(Duration::from_secs(300))
+            .body(r#"{"error":"maintenance"}"#)
+            .content_type("application/json"),
+    );
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.headers()["retry-after"], "300");
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.body(), r#"{"error":"maintenance"}"#);
+}
+
+#[tokio::test]
+async fn skips_wrapped_filter() {
+    let handle = MaintenanceHandle::new();
+    handle.enable();
+    let route = warp::path("api")
+        .map(|| "hello")
+        .with(warp::maintenance(&handle));
+
+    // Even requests the filter would reject get the maintenance reply.
+    let res = warp::test::request().path("/other").reply(&route).await;
+    assert_eq!(res.status(), 503);
+}
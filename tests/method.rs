@@ -50,3 +50,213 @@ async fn bad_request_trumps_method_not_allowed() {
     // assume POST was the appropriate method.
     assert_eq!(resp.status(), 400);
 }
+
+#[tokio::test]
+async fn method_not_allowed_on_matched_path() {
+    let _ = pretty_env_logger::try_init();
+    // `DELETE` is checked before the path, so it can't tell whether
+    // `/users` exists; `GET` and `POST` are checked after it.
+    let delete = warp::delete().and(warp::path!("posts")).map(warp::reply);
+    let get = warp::path!("users").and(warp::get()).map(warp::reply);
+    let post = warp::path!("users").and(warp::post()).map(warp::reply);
+
+    let routes = delete.or(get).or(post);
+
+    let req = warp::test::request().method("PUT").path("/users");
+    let err = req.filter(&routes).await.err().unwrap();
+    assert!(err.is_method_not_allowed());
+    let allowed = err.allowed_methods();
+    assert_eq!(allowed.len(), 2);
+    assert!(allowed.contains(&warp::http::Method::GET));
+    assert!(allowed.contains(&warp::http::Method::POST));
+
+    let resp = warp::test::request()
+        .method("PUT")
+        .path("/users")
+        .reply(&routes)
+        .await;
+    assert_eq!(resp.status(), 405);
+    let allow = resp.headers()["allow"].to_str().unwrap();
+    assert!(!allow.contains("DELETE"), "allow: {}", allow);
+
+    let req = warp::test::request().method("PUT").path("/posts");
+    let err = req.filter(&routes).await.err().unwrap();
+    assert!(!err.is_method_not_allowed());
+    assert_eq!(err.allowed_methods(), [warp::http::Method::DELETE]);
+}
+
+#[tokio::test]
+async fn method_not_allowed_on_root() {
+    let _ = pretty_env_logger::try_init();
+    // `/` has an empty path before any path filter runs, so it only
+    // counts as matched after `path::end()`.
+    let a = warp::get().and(warp::path("a")).map(warp::reply);
+
+    let req = warp::test::request().method("POST").path("/");
+    let err = req.filter(&a).await.err().unwrap();
+    assert!(!err.is_method_not_allowed());
+
+    let index = warp::path::end().and(warp::get()).map(warp::reply);
+    let routes = index.or(warp::put().and(warp::path("a")).map(warp::reply));
+
+    let req = warp::test::request().method("POST").path("/");
+    let err = req.filter(&routes).await.err().unwrap();
+    assert!(err.is_method_not_allowed());
+    assert_eq!(err.allowed_methods(), [warp::http::Method::GET]);
+}
+
+#[tokio::test]
+async fn override_header() {
+    let _ = pretty_env_logger::try_init();
+    let delete = warp::delete().map(|| "deleted");
+    let post = warp::post().map(|| "posted");
+    let routes = delete.or(post).with(warp::method::override_header());
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("x-http-method-override", "delete")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "deleted");
+
+    // Only POST requests are overridden, and only to allowed methods.
+    let res = warp::test::request()
+        .method("POST")
+        .header("x-http-method-override", "GET")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "posted");
+
+    let res = warp::test::request()
+        .method("GET")
+        .header("x-http-method-override", "DELETE")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn override_form_field() {
+    let _ = pretty_env_logger::try_init();
+    let put = warp::put()
+        .and(warp::body::form())
+        .map(|form: std::collections::HashMap<String, String>| form["title"].clone());
+    let routes = put.with(
+        warp::method::override_header()
+            .form_field("_method")
+            .allow(vec![warp::http::Method::PUT]),
+    );
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body("_method=PUT&title=hello")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "hello");
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body("_method=DELETE&title=hello")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn auto_head() {
+    let _ = pretty_env_logger::try_init();
+    let hello = warp::get()
+        .and(warp::path("hello"))
+        .map(|| warp::reply::with_header("Hello, World!", "x-greeting", "yes"));
+    let explicit = warp::head().and(warp::path("explicit")).map(|| "head");
+    let routes = hello.or(explicit).with(warp::method::auto_head());
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/hello")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "13");
+    assert_eq!(res.headers()["x-greeting"], "yes");
+    assert!(res.body().is_empty());
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/explicit")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "4");
+
+    let res = warp::test::request().path("/hello").reply(&routes).await;
+    assert_eq!(res.body(), "Hello, World!");
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/missing")
+        .reply(&routes)
+        .await;
+    // As without `auto_head`, the method rejections win over not found.
+    assert_eq!(res.status(), 405);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/hello")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn auto_options() {
+    let _ = pretty_env_logger::try_init();
+    let get = warp::path("posts").and(warp::get()).map(|| "all posts");
+    let post = warp::path("posts").and(warp::post()).map(|| "created");
+    let about = warp::path("about").and(warp::get()).map(|| "about");
+    let explicit = warp::path("explicit").and(warp::options()).map(|| "custom");
+    let routes = get
+        .or(post)
+        .or(about)
+        .or(explicit)
+        .with(warp::method::auto_options());
+
+    let res = warp::test::request()
+        .method("OPTIONS")
+        .path("/posts")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 204);
+    assert_eq!(res.headers()["allow"], "GET, HEAD, POST, OPTIONS");
+
+    let res = warp::test::request()
+        .method("OPTIONS")
+        .path("/about")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.headers()["allow"], "GET, HEAD, OPTIONS");
+
+    let res = warp::test::request()
+        .method("OPTIONS")
+        .path("/explicit")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "custom");
+
+    let res = warp::test::request()
+        .method("OPTIONS")
+        .path("/missing")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 404);
+
+    let res = warp::test::request()
+        .method("DELETE")
+        .path("/posts")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
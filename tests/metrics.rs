@@ -0,0 +1,100 @@
+#![deny(warnings)]
+
+use warp::metrics::Registry;
+use warp::Filter;
+
+#[tokio::test]
+async fn records_by_route() {
+    let registry = Registry::new();
+    let route = warp::path!("users" / u32)
+        .map(|id| format!("user #{}", id))
+        .with(warp::metrics().registry(&registry));
+
+    warp::test::request().path("/users/1").reply(&route).await;
+    warp::test::request().path("/users/2").reply(&route).await;
+    warp::test::request().path("/nope").reply(&route).await;
+
+    let text = registry.render();
+    assert!(
+        text.contains("# TYPE http_requests_total counter\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "http_requests_total{method=\"GET\",route=\"/users/{u32}\",status=\"200\"} 2\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("http_request_duration_seconds_count{method=\"GET\",route=\"/users/{u32}\",status=\"200\"} 2\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("http_response_size_bytes_bucket{method=\"GET\",route=\"/users/{u32}\",status=\"200\",le=\"100\"} 2\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("http_response_size_bytes_sum{method=\"GET\",route=\"/users/{u32}\",status=\"200\"} 14\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("http_requests_in_flight 0\n"), "{}", text);
+}
+
+#[tokio::test]
+async fn endpoint() {
+    let registry = Registry::new();
+    let api = warp::path("api")
+        .map(warp::reply)
+        .with(warp::metrics().registry(&registry));
+    let route = api.or(warp::path("metrics").and(registry.endpoint()));
+
+    warp::test::request().path("/api").reply(&route).await;
+    let res = warp::test::request().path("/metrics").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["content-type"],
+        "text/plain; version=0.0.4; charset=utf-8"
+    );
+    let body = std::str::from_utf8(res.body()).unwrap();
+    assert!(body.contains("route=\"/api\""), "{}", body);
+}
+
+#[tokio::test]
+async fn labels_unknown_methods_as_other() {
+    let registry = Registry::new();
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::metrics().registry(&registry));
+
+    for method in &["PURGE", "X-MADE-UP-1", "X-MADE-UP-2"] {
+        warp::test::request().method(method).reply(&route).await;
+    }
+    warp::test::request().method("PATCH").reply(&route).await;
+
+    let text = registry.render();
+    assert!(
+        text.contains(
+            "http_requests_total{method=\"other\",route=\"unmatched\",status=\"200\"} 3\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "http_requests_total{method=\"PATCH\",route=\"unmatched\",status=\"200\"} 1\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(!text.contains("PURGE"), "{}", text);
+}
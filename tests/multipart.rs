@@ -52,3 +52,54 @@ async fn form_fields() {
     assert_eq!(&vec[0].0, "foo");
     assert_eq!(&vec[0].1, b"bar");
 }
+
+// (name, filename, content type, contents)
+type Field = (String, Option<String>, Option<String>, Vec<u8>);
+
+#[tokio::test]
+async fn builder_form() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = multipart::form().and_then(|form: multipart::FormData| async {
+        let parts: Result<Vec<Field>, warp::Rejection> = form
+            .and_then(|part| {
+                let name = part.name().to_string();
+                let filename = part.filename().map(str::to_owned);
+                let content_type = part.content_type().map(str::to_owned);
+                part.stream()
+                    .try_fold(Vec::new(), |mut vec, data| {
+                        vec.put(data);
+                        async move { Ok(vec) }
+                    })
+                    .map_ok(move |vec| (name, filename, content_type, vec))
+            })
+            .try_collect()
+            .await
+            .map_err(|e| {
+                panic!("multipart error: {:?}", e);
+            });
+        parts
+    });
+
+    let form = warp::test::MultipartForm::new()
+        .text_field("title", "Holiday")
+        .file_field("photo", "beach \"1\".png", "image/png", b"\x89PNG\r\n--");
+    let parts = warp::test::request()
+        .method("POST")
+        .multipart(form)
+        .filter(&route)
+        .await
+        .unwrap();
+
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0], ("title".into(), None, None, b"Holiday".to_vec()));
+    assert_eq!(
+        parts[1],
+        (
+            "photo".into(),
+            Some("beach %221%22.png".into()),
+            Some("image/png".into()),
+            b"\x89PNG\r\n--".to_vec()
+        )
+    );
+}
@@ -0,0 +1,105 @@
+#![deny(warnings)]
+
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use warp::http::{Method, StatusCode};
+use warp::openapi::OpenApi;
+use warp::router::{Endpoint, Router};
+use warp::Filter;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct User {
+    name: String,
+    age: Option<u32>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, JsonSchema)]
+struct Page {
+    limit: u32,
+    after: Option<String>,
+}
+
+fn router() -> Router {
+    Router::new()
+        .route(
+            Endpoint::new(Method::GET, "/users")
+                .named("listUsers")
+                .tagged("users")
+                .query::<Page>()
+                .response::<Vec<User>>(StatusCode::OK, "The users"),
+            warp::any().map(|| warp::reply::json(&Vec::<String>::new())),
+        )
+        .route(
+            Endpoint::new(Method::POST, "/users")
+                .summary("Create a user")
+                .json_body::<User>()
+                .response::<User>(StatusCode::CREATED, "The created user"),
+            warp::body::json().map(|user: User| warp::reply::json(&user)),
+        )
+        .route(
+            Endpoint::new(Method::DELETE, "/users/{id}").param::<u64>("id"),
+            warp::any().map(warp::reply),
+        )
+}
+
+#[test]
+fn document() {
+    let doc = OpenApi::new("Users", "1.0.0")
+        .description("Manages users")
+        .router(&router())
+        .document();
+
+    assert_eq!(doc["openapi"], "3.0.3");
+    assert_eq!(doc["info"]["title"], "Users");
+    assert_eq!(doc["info"]["description"], "Manages users");
+
+    let list = &doc["paths"]["/users"]["get"];
+    assert_eq!(list["operationId"], "listUsers");
+    assert_eq!(list["tags"][0], "users");
+    let params = list["parameters"].as_array().unwrap();
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0]["name"], "after");
+    assert_eq!(params[0]["in"], "query");
+    assert_eq!(params[0]["required"], false);
+    assert_eq!(params[1]["name"], "limit");
+    assert_eq!(params[1]["required"], true);
+    assert_eq!(list["responses"]["200"]["description"], "The users");
+
+    let create = &doc["paths"]["/users"]["post"];
+    assert_eq!(create["summary"], "Create a user");
+    assert_eq!(
+        create["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/User"
+    );
+    assert!(create["responses"]["201"].is_object());
+    assert!(doc["components"]["schemas"]["User"]["properties"]["name"].is_object());
+
+    let delete = &doc["paths"]["/users/{id}"]["delete"];
+    assert_eq!(delete["parameters"][0]["in"], "path");
+    assert_eq!(delete["parameters"][0]["schema"]["type"], "integer");
+    assert_eq!(delete["responses"]["200"]["description"], "OK");
+}
+
+#[tokio::test]
+async fn serves_document_and_ui() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = OpenApi::new("Users", "1.0.0").router(&router()).filter();
+
+    let res = warp::test::request()
+        .path("/openapi.json")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    let doc: Value = serde_json::from_slice(res.body()).unwrap();
+    assert!(doc["paths"]["/users"].is_object());
+
+    let res = warp::test::request().path("/docs").reply(&routes).await;
+    assert_eq!(res.status(), 200);
+    assert!(std::str::from_utf8(res.body())
+        .unwrap()
+        .contains("SwaggerUIBundle"));
+}
@@ -0,0 +1,77 @@
+#![deny(warnings)]
+#![cfg(feature = "opentelemetry")]
+
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use opentelemetry::sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry::sdk::trace::TracerProvider;
+use opentelemetry::trace::{SpanKind, TraceContextExt};
+use opentelemetry::{global, Context, Key, Value};
+use warp::Filter;
+
+#[derive(Clone, Debug, Default)]
+struct Exported(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanExporter for Exported {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.0.lock().unwrap().extend(batch);
+        Box::pin(futures::future::ready(Ok(())))
+    }
+}
+
+fn attribute(span: &SpanData, key: &'static str) -> Option<Value> {
+    span.attributes.get(&Key::from_static_str(key)).cloned()
+}
+
+#[tokio::test]
+async fn server_span() {
+    let exported = Exported::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exported.clone())
+        .build();
+    global::set_tracer_provider(provider.clone());
+
+    let route = warp::path!("users" / u32)
+        .map(|_| {
+            // The span is current in handlers.
+            let cx = Context::current();
+            cx.span().span_context().trace_id().to_string()
+        })
+        .with(warp::trace::opentelemetry());
+
+    let res = warp::test::request()
+        .path("/users/7")
+        .header(
+            "traceparent",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        )
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "0af7651916cd43dd8448eb211c80319c");
+
+    warp::test::request().path("/nope").reply(&route).await;
+
+    for result in provider.force_flush() {
+        result.unwrap();
+    }
+    let spans = exported.0.lock().unwrap();
+    assert_eq!(spans.len(), 2);
+
+    let span = &spans[0];
+    assert_eq!(span.name, "GET /users/{u32}");
+    assert_eq!(span.span_kind, SpanKind::Server);
+    assert_eq!(
+        span.span_context.trace_id().to_string(),
+        "0af7651916cd43dd8448eb211c80319c"
+    );
+    assert_eq!(span.parent_span_id.to_string(), "b7ad6b7169203331");
+    assert_eq!(attribute(span, "http.method"), Some("GET".into()));
+    assert_eq!(attribute(span, "http.route"), Some("/users/{u32}".into()));
+    assert_eq!(attribute(span, "http.status_code"), Some(200i64.into()));
+
+    let span = &spans[1];
+    assert_eq!(span.name, "HTTP GET");
+    assert_eq!(attribute(span, "http.route"), None);
+    assert_eq!(attribute(span, "http.status_code"), Some(404i64.into()));
+}
@@ -399,3 +399,515 @@ async fn peek_segments() {
     let segs = ex.segments().collect::<Vec<_>>();
     assert_eq!(segs, Vec::<&str>::new());
 }
+
+#[tokio::test]
+async fn template() {
+    let template = warp::path::template();
+
+    let ex = warp::test::request()
+        .path("/users/42")
+        .filter(&template)
+        .await
+        .unwrap();
+    assert_eq!(ex.as_str(), "/");
+
+    let users = warp::path!("users" / u32 / "posts").and(template);
+    let ex = warp::test::request()
+        .path("/users/42/posts")
+        .filter(&users)
+        .await
+        .unwrap();
+    assert_eq!(ex.1.as_str(), "/users/{u32}/posts");
+
+    // segments of branches that didn't match aren't included
+    let avatar = warp::path!("users" / u32 / "avatar").map(|_| "avatar");
+    let files = warp::path("users").and(warp::path::tail()).map(|_| "files");
+    let route = avatar.or(files).unify().and(template);
+    let ex = warp::test::request()
+        .path("/users/42/files/a.txt")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(ex.0, "files");
+    assert_eq!(ex.1.as_str(), "/users/*");
+}
+
+#[tokio::test]
+async fn route_macro() {
+    let _ = pretty_env_logger::try_init();
+
+    fn posts(user_id: u64) -> String {
+        format!("posts of {}", user_id)
+    }
+    async fn delete(user_id: u64, post_id: u64) -> Result<String, warp::Rejection> {
+        Ok(format!("deleted {} of {}", post_id, user_id))
+    }
+
+    let index = warp::route!(GET "/" => || "index");
+    let list = warp::route!(GET "/users" / u64 / "posts" => posts);
+    let remove = warp::route!(DELETE "users" / u64 / "posts" / u64 => async delete);
+    let prefix = warp::route!(POST "/api" / ..).map(|| "api");
+
+    let res = warp::test::request().path("/").reply(&index).await;
+    assert_eq!(res.body(), "index");
+    assert!(!warp::test::request().path("/a").matches(&index).await);
+
+    let res = warp::test::request()
+        .path("/users/7/posts")
+        .reply(&list)
+        .await;
+    assert_eq!(res.body(), "posts of 7");
+    assert!(
+        !warp::test::request()
+            .method("POST")
+            .path("/users/7/posts")
+            .matches(&list)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/users/7/posts/1")
+            .matches(&list)
+            .await
+    );
+
+    let res = warp::test::request()
+        .method("DELETE")
+        .path("/users/7/posts/1")
+        .reply(&remove)
+        .await;
+    assert_eq!(res.body(), "deleted 1 of 7");
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/api/anything")
+        .reply(&prefix)
+        .await;
+    assert_eq!(res.body(), "api");
+}
+
+#[tokio::test]
+async fn param_with() {
+    let _ = pretty_env_logger::try_init();
+
+    let page = warp::path("page")
+        .and(warp::path::param_with(|page: &u32| *page > 0))
+        .map(|page: u32| format!("page {}", page));
+    let fallback = warp::path("page").map(|| String::from("first page"));
+    let route = page.or(fallback).unify();
+
+    let res = warp::test::request().path("/page/3").reply(&route).await;
+    assert_eq!(res.body(), "page 3");
+    let res = warp::test::request().path("/page/0").reply(&route).await;
+    assert_eq!(res.body(), "first page");
+    let res = warp::test::request().path("/page/x").reply(&route).await;
+    assert_eq!(res.body(), "first page");
+}
+
+#[cfg(feature = "regex")]
+#[tokio::test]
+async fn regex() {
+    let _ = pretty_env_logger::try_init();
+
+    let blob = warp::path("blobs")
+        .and(warp::path::regex("^[a-f0-9]{8}$"))
+        .and(warp::path::end())
+        .map(|id: String| id);
+    let by_name = warp::path!("blobs" / String).map(|name: String| format!("named {}", name));
+    let route = blob.or(by_name).unify();
+
+    let res = warp::test::request()
+        .path("/blobs/0123abcd")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "0123abcd");
+    let res = warp::test::request()
+        .path("/blobs/0123ABCD")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "named 0123ABCD");
+}
+
+#[tokio::test]
+async fn glob() {
+    let _ = pretty_env_logger::try_init();
+
+    let scripts = warp::path("static")
+        .and(warp::path::glob("assets/**/*.js"))
+        .map(|tail: warp::path::Tail| tail.as_str().to_owned());
+
+    for path in &["assets/app.js", "assets/vendor/react/index.js"] {
+        let res = warp::test::request()
+            .path(&format!("/static/{}", path))
+            .reply(&scripts)
+            .await;
+        assert_eq!(res.status(), 200, "{}", path);
+        assert_eq!(res.body(), path);
+    }
+
+    for path in &["/static/assets/app.css", "/static/app.js", "/static/assets"] {
+        assert!(
+            !warp::test::request().path(path).matches(&scripts).await,
+            "{}",
+            path
+        );
+    }
+
+    let single = warp::path::glob("/v?/*");
+    assert!(
+        warp::test::request()
+            .path("/v1/users")
+            .matches(&single)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/v10/users")
+            .matches(&single)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/v1/users/7")
+            .matches(&single)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn param_opt() {
+    let _ = pretty_env_logger::try_init();
+
+    let report = warp::path!("reports" / u32 / ..)
+        .and(warp::path::param_opt::<String>())
+        .and(warp::path::end())
+        .map(|id: u32, view: Option<String>| format!("{} {:?}", id, view));
+
+    let res = warp::test::request()
+        .path("/reports/7")
+        .reply(&report)
+        .await;
+    assert_eq!(res.body(), "7 None");
+
+    let res = warp::test::request()
+        .path("/reports/7/summary")
+        .reply(&report)
+        .await;
+    assert_eq!(res.body(), "7 Some(\"summary\")");
+
+    assert!(
+        !warp::test::request()
+            .path("/reports/7/summary/extra")
+            .matches(&report)
+            .await
+    );
+
+    let numbered = warp::path("pages").and(warp::path::param_opt::<u32>());
+    assert!(
+        !warp::test::request()
+            .path("/pages/x")
+            .matches(&numbered)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn pattern_params() {
+    let _ = pretty_env_logger::try_init();
+
+    let post = warp::path("api")
+        .and(warp::path::pattern("/users/{id}/posts/{post}"))
+        .and(warp::path::params())
+        .and(warp::path::template())
+        .map(
+            |params: warp::path::Params, template: warp::path::Template| {
+                let id: u64 = params.parse("id").unwrap();
+                format!("{} {} {}", id, params.get("post").unwrap(), template)
+            },
+        );
+
+    let res = warp::test::request()
+        .path("/api/users/7/posts/hello")
+        .reply(&post)
+        .await;
+    assert_eq!(res.body(), "7 hello /api/users/{id}/posts/{post}");
+
+    assert!(
+        !warp::test::request()
+            .path("/api/users/7/posts")
+            .matches(&post)
+            .await
+    );
+
+    // The parameters of a rejected branch are forgotten.
+    let names = |params: warp::path::Params| {
+        params
+            .iter()
+            .map(|(name, _)| name.to_owned())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let first = warp::path::pattern("/{a}/{b}")
+        .and(warp::header::exact("x-first", "1"))
+        .and(warp::path::params())
+        .map(names);
+    let second = warp::path::pattern("/x/{c}")
+        .and(warp::path::params())
+        .map(names);
+    let route = first.or(second).unify();
+    let res = warp::test::request().path("/x/y").reply(&route).await;
+    assert_eq!(res.body(), "c");
+}
+
+#[tokio::test]
+async fn normalize_slashes() {
+    use warp::path::SlashPolicy;
+
+    let _ = pretty_env_logger::try_init();
+
+    let users = warp::path!("users" / u32).map(|id| format!("user {}", id));
+
+    let rewrite = users.with(warp::path::normalize_slashes(SlashPolicy::Rewrite));
+    for path in &["/users/7", "/users/7/", "//users//7"] {
+        let res = warp::test::request().path(path).reply(&rewrite).await;
+        assert_eq!(res.status(), 200, "{}", path);
+        assert_eq!(res.body(), "user 7");
+    }
+
+    let redirect = users.with(warp::path::normalize_slashes(SlashPolicy::Redirect));
+    let res = warp::test::request()
+        .path("/users/7/?a=b")
+        .reply(&redirect)
+        .await;
+    assert_eq!(res.status(), 301);
+    assert_eq!(res.headers()["location"], "/users/7?a=b");
+    let res = warp::test::request()
+        .path("/users/7")
+        .reply(&redirect)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    // Only the unmatched path is normalized.
+    let append = warp::path("api").and(warp::path!("users").map(|| "users").with(
+        warp::path::normalize_slashes(SlashPolicy::PermanentRedirect).append_trailing_slash(),
+    ));
+    let res = warp::test::request()
+        .path("/api/users")
+        .reply(&append)
+        .await;
+    assert_eq!(res.status(), 308);
+    assert_eq!(res.headers()["location"], "/api/users/");
+    let res = warp::test::request().path("/").reply(&rewrite).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn ignore_case() {
+    let _ = pretty_env_logger::try_init();
+
+    let users = warp::path!("api" / "users" / String)
+        .map(|name| name)
+        .with(warp::path::ignore_case());
+    let res = warp::test::request()
+        .path("/API/Users/Alice")
+        .reply(&users)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "Alice");
+
+    // Only the wrapped filters ignore case.
+    let strict = warp::path!("api" / "teams").map(|| "teams".to_owned());
+    let routes = users.or(strict).unify();
+    assert!(
+        !warp::test::request()
+            .path("/API/Teams")
+            .matches(&routes)
+            .await
+    );
+
+    let router = warp::Router::new()
+        .get("/api/{id}", warp::any().map(|| "router"))
+        .with(warp::path::ignore_case());
+    let res = warp::test::request().path("/Api/7").reply(&router).await;
+    assert_eq!(res.body(), "router");
+}
+
+#[tokio::test]
+async fn percent_decoding() {
+    use warp::path::Decode;
+
+    let _ = pretty_env_logger::try_init();
+
+    let strict = warp::path("files")
+        .and(warp::path::param_decoded::<String>(Decode::Strict))
+        .and(warp::path::end());
+    let name = warp::test::request()
+        .path("/files/a%20b%2Fc.txt")
+        .filter(&strict)
+        .await
+        .unwrap();
+    assert_eq!(name, "a b/c.txt");
+    assert!(
+        !warp::test::request()
+            .path("/files/%FF")
+            .matches(&strict)
+            .await
+    );
+
+    let lossy = warp::path("files").and(warp::path::param_decoded::<String>(Decode::Lossy));
+    let name = warp::test::request()
+        .path("/files/%FFa")
+        .filter(&lossy)
+        .await
+        .unwrap();
+    assert_eq!(name, "\u{FFFD}a");
+
+    let raw = warp::path("files").and(warp::path::param_decoded::<String>(Decode::Raw));
+    let name = warp::test::request()
+        .path("/files/a%20b")
+        .filter(&raw)
+        .await
+        .unwrap();
+    assert_eq!(name, "a%20b");
+
+    let tail = warp::path("files")
+        .and(warp::path::tail())
+        .map(|tail: warp::path::Tail| tail.decoded(Decode::Strict).unwrap().into_owned());
+    let res = warp::test::request()
+        .path("/files/dir%20one/a.txt")
+        .reply(&tail)
+        .await;
+    assert_eq!(res.body(), "dir one/a.txt");
+
+    let full = warp::test::request()
+        .path("/caf%C3%A9")
+        .filter(&warp::path::full())
+        .await
+        .unwrap();
+    assert_eq!(full.decoded(Decode::Strict).unwrap(), "/café");
+    assert_eq!(full.decoded(Decode::Raw).unwrap(), "/caf%C3%A9");
+}
+
+#[tokio::test]
+async fn mount() {
+    let _ = pretty_env_logger::try_init();
+
+    let users = warp::path!("users" / u32)
+        .and(warp::path::full())
+        .and(warp::path::template())
+        .map(
+            |id, full: warp::path::FullPath, template: warp::path::Template| {
+                format!("{} {} {}", id, full.as_str(), template.as_str())
+            },
+        );
+    let routes =
+        warp::mount("/api/v2", users).or(warp::path!("api" / "v2" / "other").map(|| "other"));
+
+    let res = warp::test::request()
+        .path("/api/v2/users/7")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "7 /api/v2/users/7 /api/v2/users/{u32}");
+
+    let res = warp::test::request()
+        .path("/api/v2/other")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "other");
+
+    assert!(
+        !warp::test::request()
+            .path("/users/7")
+            .matches(&routes)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/api/v1/users/7")
+            .matches(&routes)
+            .await
+    );
+
+    // A trailing slash in the prefix is ignored, and `/` mounts at the root.
+    let root = warp::mount("/", warp::path("health").map(warp::reply));
+    assert!(warp::test::request().path("/health").matches(&root).await);
+    let trailing = warp::mount("/api/", warp::path("health").map(warp::reply));
+    assert!(
+        warp::test::request()
+            .path("/api/health")
+            .matches(&trailing)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn limits() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path::tail()
+        .map(|tail: warp::path::Tail| tail.as_str().to_owned())
+        .with(warp::path::limits(3, 5));
+
+    let res = warp::test::request().path("/a/bb/ccc").reply(&routes).await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request().path("/a/b/c/d").reply(&routes).await;
+    assert_eq!(res.status(), 414);
+
+    let res = warp::test::request().path("/a/bbbbbb").reply(&routes).await;
+    assert_eq!(res.status(), 400);
+
+    let err = warp::test::request()
+        .path("/a/b/c/d")
+        .filter(&routes)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.find::<warp::path::TooManySegments>().map(|e| e.max()),
+        Some(3)
+    );
+
+    // Only the unmatched path is limited.
+    let prefixed = warp::path("api").and(routes);
+    let res = warp::test::request()
+        .path("/api/a/b/c")
+        .reply(&prefixed)
+        .await;
+    assert_eq!(res.body(), "a/b/c");
+}
+
+#[tokio::test]
+async fn glob_many_stars() {
+    // Backtracking into every star would take exponential time on these.
+    let segments = warp::path::glob("**/a/**/a/**/a/**/a/**/a/**/b");
+    let path = format!("/{}c", "a/".repeat(200));
+    assert!(!warp::test::request().path(&path).matches(&segments).await);
+
+    let chars = warp::path::glob("*a*a*a*a*a*a*a*b");
+    let path = format!("/{}", "a".repeat(2000));
+    assert!(!warp::test::request().path(&path).matches(&chars).await);
+    let path = format!("/{}b", "a".repeat(2000));
+    assert!(warp::test::request().path(&path).matches(&chars).await);
+
+    let nested = warp::path::glob("**/x*y/**/*.txt");
+    assert!(
+        warp::test::request()
+            .path("/a/b/xay/c/d/e.txt")
+            .matches(&nested)
+            .await
+    );
+    assert!(
+        warp::test::request()
+            .path("/xy/e.txt")
+            .matches(&nested)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/a/xay/e.txt/c")
+            .matches(&nested)
+            .await
+    );
+}
@@ -0,0 +1,103 @@
+#![deny(warnings)]
+use std::time::{Duration, SystemTime};
+
+use warp::headers::ETag;
+use warp::http::StatusCode;
+use warp::precondition::{Precondition, PreconditionFailed};
+use warp::Filter;
+
+fn update(
+    etag: &'static str,
+    modified: SystemTime,
+) -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    warp::precondition().and_then(move |precondition: Precondition| async move {
+        let etag = etag.parse::<ETag>().unwrap();
+        precondition.check(Some(&etag), Some(modified))?;
+        Ok::<_, warp::Rejection>("updated".to_string())
+    })
+}
+
+#[tokio::test]
+async fn if_match() {
+    let route = update("\"v2\"", SystemTime::UNIX_EPOCH);
+
+    let res = warp::test::request()
+        .method("PUT")
+        .header("if-match", "\"v1\", \"v2\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .method("PUT")
+        .header("if-match", "\"v1\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+    // Weak tags never match strongly.
+    let rejection = warp::test::request()
+        .header("if-match", "W/\"v2\"")
+        .filter(&route)
+        .await
+        .unwrap_err();
+    assert!(rejection.find::<PreconditionFailed>().is_some());
+
+    // No conditions, no checks.
+    let res = warp::test::request().method("PUT").reply(&route).await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn if_unmodified_since() {
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    let route = update("\"v2\"", modified);
+
+    let res = warp::test::request()
+        .header("if-unmodified-since", "Sun, 09 Sep 2001 01:46:40 GMT")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .header("if-unmodified-since", "Sun, 09 Sep 2001 01:46:39 GMT")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+    // If-Match takes precedence.
+    let res = warp::test::request()
+        .header("if-match", "\"v2\"")
+        .header("if-unmodified-since", "Sun, 09 Sep 2001 01:46:39 GMT")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn missing_resource() {
+    let precondition = warp::test::request()
+        .header("if-match", "*")
+        .filter(&warp::precondition())
+        .await
+        .unwrap();
+    assert!(precondition.if_match().is_some());
+    assert!(!precondition.passes(None, None));
+    assert!(precondition.passes(Some(&"\"any\"".parse().unwrap()), None));
+
+    let precondition = warp::test::request()
+        .header("if-unmodified-since", "Sun, 09 Sep 2001 01:46:39 GMT")
+        .filter(&warp::precondition())
+        .await
+        .unwrap();
+    assert!(precondition.passes(None, None));
+}
+
+#[tokio::test]
+async fn invalid() {
+    let res = warp::test::request()
+        .header("if-unmodified-since", "yesterday")
+        .reply(&warp::precondition().map(|_| "ok"))
+        .await;
+    assert_eq!(res.status(), 400);
+}
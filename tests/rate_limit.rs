@@ -0,0 +1,125 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use warp::http::StatusCode;
+use warp::Filter;
+
+#[tokio::test]
+async fn limits_by_ip() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::rate_limit(2, Duration::from_secs(60)));
+
+    let ip = |addr: &str| warp::test::request().remote_addr(addr.parse().unwrap());
+
+    let res = ip("1.2.3.4:1").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-ratelimit-limit"], "2");
+    assert_eq!(res.headers()["x-ratelimit-remaining"], "1");
+    assert_eq!(res.headers()["x-ratelimit-reset"], "30");
+
+    let res = ip("1.2.3.4:2").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-ratelimit-remaining"], "0");
+
+    let res = ip("1.2.3.4:3").reply(&route).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers()["retry-after"], "30");
+    assert_eq!(res.headers()["x-ratelimit-remaining"], "0");
+
+    // Other clients have their own bucket.
+    let res = ip("5.6.7.8:1").reply(&route).await;
+    assert_eq!(res.status(), 200);
+
+    // Requests without a key aren't limited.
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert!(!res.headers().contains_key("x-ratelimit-limit"));
+}
+
+#[tokio::test]
+async fn limits_by_custom_key() {
+    let _ = pretty_env_logger::try_init();
+
+    let limit = warp::rate_limit(1, Duration::from_secs(60))
+        .burst(1)
+        .key_by(|info| Some(info.path().to_owned()));
+    let route = warp::any().map(warp::reply).with(limit);
+
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.status(), 200);
+
+    let rejection = warp::test::request()
+        .path("/a")
+        .filter(&route)
+        .await
+        .err()
+        .expect("rate limited");
+    let limited = rejection
+        .find::<warp::rate_limit::RateLimited>()
+        .expect("RateLimited rejection");
+    assert!(limited.retry_after() <= Duration::from_secs(60));
+    assert!(limited.retry_after() > Duration::from_secs(59));
+
+    let res = warp::test::request().path("/b").reply(&route).await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn refills_over_time() {
+    let _ = pretty_env_logger::try_init();
+    warp::test::pause_time();
+
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::rate_limit(2, Duration::from_secs(60)).key_by_header("x-api-key"));
+    let req = || warp::test::request().header("x-api-key", "abc");
+
+    assert_eq!(req().reply(&route).await.status(), 200);
+    assert_eq!(req().reply(&route).await.status(), 200);
+    let res = req().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers()["retry-after"], "30");
+
+    warp::test::advance(Duration::from_secs(29));
+    let res = req().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers()["retry-after"], "1");
+
+    warp::test::advance(Duration::from_secs(1));
+    let res = req().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-ratelimit-remaining"], "0");
+}
+
+#[tokio::test]
+async fn forgets_refilled_buckets() {
+    warp::test::pause_time();
+
+    let store = std::sync::Arc::new(warp::rate_limit::MemoryStore::default());
+    let route = warp::any().map(warp::reply).with(
+        warp::rate_limit(1, Duration::from_secs(60))
+            .key_by_header("x-api-key")
+            .store(store.clone()),
+    );
+    let req = |key: usize| warp::test::request().header("x-api-key", key.to_string());
+
+    for key in 0..1500 {
+        assert_eq!(req(key).reply(&route).await.status(), 200);
+    }
+    assert_eq!(store.len(), 1500);
+
+    // Once refilled, the first buckets are swept as new ones come in.
+    warp::test::advance(Duration::from_secs(60));
+    for key in 1500..2100 {
+        assert_eq!(req(key).reply(&route).await.status(), 200);
+    }
+    assert_eq!(store.len(), 600);
+
+    // The ones still refilling are kept.
+    let res = req(2099).reply(&route).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+}
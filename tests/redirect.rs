@@ -1,4 +1,5 @@
 #![deny(warnings)]
+use warp::redirect::Location;
 use warp::{http::Uri, Filter};
 
 #[tokio::test]
@@ -11,3 +12,80 @@ async fn redirect_uri() {
     assert_eq!(resp.status(), 301);
     assert_eq!(resp.headers()["location"], "/over-there");
 }
+
+#[tokio::test]
+async fn redirect_statuses() {
+    let uri = || Uri::from_static("/v2");
+    let resp = warp::test::request()
+        .reply(&warp::any().map(move || warp::redirect::see_other(uri())))
+        .await;
+    assert_eq!(resp.status(), 303);
+    let resp = warp::test::request()
+        .reply(&warp::any().map(move || warp::redirect::found(uri())))
+        .await;
+    assert_eq!(resp.status(), 302);
+    let resp = warp::test::request()
+        .reply(&warp::any().map(move || warp::redirect::permanent(uri())))
+        .await;
+    assert_eq!(resp.status(), 308);
+    assert_eq!(resp.headers()["location"], "/v2");
+}
+
+#[tokio::test]
+async fn redirect_keep_query() {
+    let route = warp::path("v1").and(warp::redirect::keep_query(
+        warp::http::StatusCode::TEMPORARY_REDIRECT,
+        Uri::from_static("/v2?from=v1"),
+    ));
+
+    let resp = warp::test::request()
+        .path("/v1?q=warp&page=2")
+        .reply(&route)
+        .await;
+    assert_eq!(resp.status(), 307);
+    assert_eq!(resp.headers()["location"], "/v2?from=v1&q=warp&page=2");
+
+    let resp = warp::test::request().path("/v1").reply(&route).await;
+    assert_eq!(resp.headers()["location"], "/v2?from=v1");
+}
+
+#[tokio::test]
+async fn redirect_location() {
+    let route = warp::path::param().map(|name: String| {
+        let location = Location::new()
+            .segment("users")
+            .segment(name)
+            .query("next", "/a b&c");
+        warp::redirect::see_other(location)
+    });
+
+    let resp = warp::test::request().path("/a%2Fb").reply(&route).await;
+    assert_eq!(resp.headers()["location"], "/users/a%252Fb?next=/a%20b%26c");
+
+    assert_eq!(Location::new().as_str(), "/");
+    assert_eq!(Location::new().query("a", 1).as_str(), "/?a=1");
+}
+
+#[test]
+fn location_segments_stay_on_path() {
+    // Browsers read `/\host` and `//host` as other hosts.
+    let location = Location::new().segment("\\evil.com");
+    assert_eq!(location.as_str(), "/%5Cevil.com");
+
+    for segment in &["", ".", ".."] {
+        assert!(Location::new().try_segment(segment).is_err());
+        let err = Location::new()
+            .segment("a")
+            .try_segment(segment)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "segment is empty, `.` or `..`");
+    }
+    let location = Location::new().try_segment("...").unwrap();
+    assert_eq!(location.as_str(), "/...");
+}
+
+#[test]
+#[should_panic(expected = "segment is empty")]
+fn location_empty_segment_panics() {
+    let _ = Location::new().segment("").segment("evil.com");
+}
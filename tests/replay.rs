@@ -0,0 +1,92 @@
+#![deny(warnings)]
+
+use warp::Filter;
+
+const HAR: &str = r#"{
+  "log": {
+    "version": "1.2",
+    "entries": [
+      {
+        "request": {
+          "method": "GET",
+          "url": "https://api.example.com/users/1?fields=name",
+          "httpVersion": "HTTP/2",
+          "headers": [
+            { "name": ":authority", "value": "api.example.com" },
+            { "name": "accept", "value": "application/json" }
+          ]
+        },
+        "response": {
+          "status": 200,
+          "headers": [{ "name": "content-type", "value": "application/json" }],
+          "content": { "mimeType": "application/json", "text": "{ \"id\": 1, \"name\": \"sean\" }" }
+        }
+      },
+      {
+        "request": {
+          "method": "POST",
+          "url": "https://api.example.com/echo",
+          "headers": [{ "name": "content-length", "value": "5" }],
+          "postData": { "mimeType": "text/plain", "text": "hello" }
+        },
+        "response": {
+          "status": 200,
+          "headers": [
+            { "name": "content-type", "value": "application/octet-stream" },
+            { "name": "x-echo", "value": "yes" }
+          ],
+          "content": { "encoding": "base64", "text": "aGVsbG8=" }
+        }
+      }
+    ]
+  }
+}"#;
+
+fn api() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let user = warp::path!("users" / u32)
+        .and(warp::header::exact("accept", "application/json"))
+        .map(|id| warp::reply::json(&serde_json::json!({"name": "sean", "id": id})));
+    let echo = warp::path("echo")
+        .and(warp::body::bytes())
+        .map(|body: bytes::Bytes| warp::reply::with_header(body.to_vec(), "x-echo", "yes"));
+    user.or(echo)
+}
+
+#[tokio::test]
+async fn replay_matches() {
+    let _ = pretty_env_logger::try_init();
+
+    let replay = warp::test::Replay::from_har(HAR).unwrap().header("x-echo");
+    assert_eq!(replay.len(), 2);
+
+    let report = replay.run(&api()).await;
+    assert!(report.is_ok(), "{}", report);
+    report.assert_ok();
+}
+
+#[tokio::test]
+async fn replay_mismatches() {
+    let _ = pretty_env_logger::try_init();
+
+    let replay = warp::test::Replay::from_har(HAR).unwrap();
+    let route = warp::path!("users" / u32).map(|_| "changed");
+
+    let report = replay.run(&route).await;
+    assert_eq!(report.mismatches().len(), 2);
+    assert!(report.mismatches()[0].starts_with("GET https://api.example.com/users/1?fields=name"));
+    assert!(report.mismatches()[0].contains("body"));
+    assert!(report.mismatches()[1].contains("status: expected 200 OK, got 404 Not Found"));
+    assert!(report
+        .to_string()
+        .starts_with("2 of 2 replayed exchanges differ"));
+}
+
+#[test]
+fn replay_invalid_har() {
+    let err = warp::test::Replay::from_har(r#"{"log": {}}"#).unwrap_err();
+    assert_eq!(err.to_string(), "replay error: missing log.entries");
+
+    let err =
+        warp::test::Replay::from_har(r#"{"log": {"entries": [{"request": {}}]}}"#).unwrap_err();
+    assert_eq!(err.to_string(), "replay error: entry 0: missing method");
+}
@@ -62,3 +62,20 @@ async fn default_header() {
 
     assert_eq!(resp.headers()["foo"], "sean", "doesn't replace header");
 }
+
+#[tokio::test]
+async fn map_response() {
+    let with_status = warp::reply::with::map_response(|mut res: warp::reply::Response| {
+        *res.status_mut() = warp::http::StatusCode::ACCEPTED;
+        res
+    });
+
+    let route = warp::path("accepted").map(warp::reply).with(with_status);
+
+    let resp = warp::test::request().path("/accepted").reply(&route).await;
+    assert_eq!(resp.status(), 202);
+
+    // rejections aren't mapped
+    let resp = warp::test::request().path("/other").reply(&route).await;
+    assert_eq!(resp.status(), 404);
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+
+use warp::http::{Request, StatusCode};
+use warp::hyper::Body;
+use warp::Filter;
+
+#[tokio::test]
+async fn full() {
+    let _ = pretty_env_logger::try_init();
+
+    let route =
+        warp::path("raw")
+            .and(warp::request::full())
+            .and_then(|req: Request<Body>| async move {
+                let (parts, body) = req.into_parts();
+                let body = warp::hyper::body::to_bytes(body).await.unwrap();
+                let ext = parts.extensions.get::<u32>().copied();
+                Ok::<_, warp::Rejection>(format!(
+                    "{} {} {} {:?} {:?}",
+                    parts.method,
+                    parts.uri,
+                    parts.headers["x-id"].to_str().unwrap(),
+                    ext,
+                    body,
+                ))
+            });
+
+    let res = warp::test::request()
+        .method("PUT")
+        .path("/raw?q=1")
+        .header("x-id", "7")
+        .extension(5u32)
+        .body("hello")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), r#"PUT /raw?q=1 7 Some(5) b"hello""#);
+}
+
+#[tokio::test]
+async fn full_after_body() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::bytes()
+        .and(warp::request::full())
+        .map(|_, _| "unreachable");
+
+    let res = warp::test::request().body("hello").reply(&route).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,82 @@
+#![deny(warnings)]
+
+use warp::request_id::RequestId;
+use warp::Filter;
+
+fn echo_id() -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    warp::request_id::id().map(|id: RequestId| id.to_string())
+}
+
+#[tokio::test]
+async fn generates_id() {
+    let route = echo_id().with(warp::request_id());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    let id = res.headers()["x-request-id"].to_str().unwrap();
+    assert_eq!(res.body(), id);
+    assert_eq!(id.len(), 36);
+    assert_eq!(&id[14..15], "4");
+
+    let other = warp::test::request().reply(&route).await;
+    assert_ne!(other.headers()["x-request-id"], id);
+}
+
+#[tokio::test]
+async fn propagates_incoming_id() {
+    let route = echo_id().with(warp::request_id());
+
+    let res = warp::test::request()
+        .header("x-request-id", "abc-123")
+        .reply(&route)
+        .await;
+    assert_eq!(res.headers()["x-request-id"], "abc-123");
+    assert_eq!(res.body(), "abc-123");
+
+    // Unreasonable IDs are replaced.
+    let res = warp::test::request()
+        .header("x-request-id", "has spaces")
+        .reply(&route)
+        .await;
+    assert_ne!(res.headers()["x-request-id"], "has spaces");
+}
+
+#[tokio::test]
+async fn custom_header_and_generator() {
+    let route = echo_id().with(
+        warp::request_id()
+            .header("x-correlation-id")
+            .generate(|| "fixed".to_owned()),
+    );
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()["x-correlation-id"], "fixed");
+    assert!(!res.headers().contains_key("x-request-id"));
+}
+
+#[tokio::test]
+async fn logged() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let log = warp::log::custom(move |info| {
+        tx.send(info.request_id().map(ToOwned::to_owned)).unwrap();
+    });
+    let route = warp::any()
+        .map(warp::reply)
+        .with(log)
+        .with(warp::request_id());
+
+    warp::test::request()
+        .header("x-request-id", "logged-1")
+        .reply(&route)
+        .await;
+    assert_eq!(rx.recv().unwrap().as_deref(), Some("logged-1"));
+}
+
+#[tokio::test]
+async fn missing_wrapper_rejects() {
+    let rejection = warp::test::request()
+        .filter(&warp::request_id::id())
+        .await
+        .unwrap_err();
+    assert!(rejection.find::<warp::ext::MissingExtension>().is_some());
+}
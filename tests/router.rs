@@ -0,0 +1,255 @@
+#![deny(warnings)]
+
+use warp::http::Method;
+use warp::router::{Endpoint, Router};
+use warp::Filter;
+
+fn users() -> Router {
+    Router::new()
+        .get("/users", warp::any().map(|| "all users"))
+        .route(
+            Endpoint::new(Method::GET, "/users/{id}")
+                .named("user")
+                .tagged("users"),
+            warp::router::param::<u32>("id").map(|id| format!("user #{}", id)),
+        )
+        .delete(
+            "/users/{id}",
+            warp::router::param::<u32>("id").map(|id| format!("deleted #{}", id)),
+        )
+        .get(
+            "/files/*",
+            warp::path::tail().map(|tail: warp::path::Tail| tail.as_str().to_owned()),
+        )
+}
+
+#[test]
+fn lists_routes() {
+    let router = users();
+    let routes = router
+        .routes()
+        .map(|endpoint| endpoint.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        routes,
+        [
+            "GET /users",
+            "GET /users/{id}",
+            "DELETE /users/{id}",
+            "GET /files/*",
+        ]
+    );
+
+    let user = router.routes().nth(1).unwrap();
+    assert_eq!(user.name(), Some("user"));
+    assert_eq!(user.tags(), ["users"]);
+
+    assert_eq!(
+        router.allowed_methods("/users/7"),
+        [Method::GET, Method::DELETE]
+    );
+    assert!(router.allowed_methods("/nope").is_empty());
+}
+
+#[tokio::test]
+async fn dispatches_with_params() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = users();
+
+    let res = warp::test::request().path("/users").reply(&router).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "all users");
+
+    let res = warp::test::request().path("/users/7").reply(&router).await;
+    assert_eq!(res.body(), "user #7");
+
+    let res = warp::test::request()
+        .method("DELETE")
+        .path("/users/7")
+        .reply(&router)
+        .await;
+    assert_eq!(res.body(), "deleted #7");
+
+    let res = warp::test::request()
+        .path("/files/a/b.txt")
+        .reply(&router)
+        .await;
+    assert_eq!(res.body(), "a/b.txt");
+
+    let params = warp::path("api").and(Router::new().get(
+        "/{a}/{b}",
+        warp::router::params().map(|params: warp::router::Params| {
+            params
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(",")
+        }),
+    ));
+    let res = warp::test::request().path("/api/x/y").reply(&params).await;
+    assert_eq!(res.body(), "a=x,b=y");
+}
+
+#[tokio::test]
+async fn method_not_allowed() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = users();
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/users/7")
+        .reply(&router)
+        .await;
+    assert_eq!(res.status(), 405);
+    assert_eq!(res.headers()["allow"], "GET, DELETE");
+
+    let res = warp::test::request().path("/nope").reply(&router).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn falls_through_rejections() {
+    let _ = pretty_env_logger::try_init();
+
+    // `abc` isn't a `u32`, so the first route rejects and the next is tried.
+    let router = users().get(
+        "/users/{name}",
+        warp::router::param::<String>("name").map(|name| format!("user {}", name)),
+    );
+
+    let res = warp::test::request()
+        .path("/users/abc")
+        .reply(&router)
+        .await;
+    assert_eq!(res.body(), "user abc");
+
+    let routes = Router::new()
+        .get(
+            "/a",
+            warp::any().and_then(|| async { Err::<String, _>(warp::reject::not_found()) }),
+        )
+        .or(warp::path("a").map(|| "fallback"));
+    let res = warp::test::request().path("/a").reply(&routes).await;
+    assert_eq!(res.body(), "fallback");
+}
+
+#[tokio::test]
+async fn tries_specific_routes_first() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = Router::new()
+        .get("/*", warp::any().map(|| "fallback"))
+        .get(
+            "/users/{id}",
+            warp::router::param::<String>("id").map(|id| format!("user {}", id)),
+        )
+        .get("/users/me", warp::any().map(|| "me"))
+        .get("/users", warp::any().map(|| "users"));
+
+    let routes = router
+        .routes()
+        .map(|endpoint| endpoint.template())
+        .collect::<Vec<_>>();
+    assert_eq!(routes, ["/users", "/users/me", "/users/{id}", "/*"]);
+
+    let res = warp::test::request().path("/users/me").reply(&router).await;
+    assert_eq!(res.body(), "me");
+    let res = warp::test::request().path("/users/7").reply(&router).await;
+    assert_eq!(res.body(), "user 7");
+    let res = warp::test::request().path("/other").reply(&router).await;
+    assert_eq!(res.body(), "fallback");
+
+    // A priority goes before specificity.
+    let router = router.route(
+        Endpoint::new(Method::GET, "/{page}").prioritized(1),
+        warp::any().map(|| "page"),
+    );
+    assert_eq!(router.routes().next().unwrap().priority(), 1);
+    let res = warp::test::request().path("/users").reply(&router).await;
+    assert_eq!(res.body(), "page");
+}
+
+#[tokio::test]
+async fn dispatches_among_many_routes() {
+    let _ = pretty_env_logger::try_init();
+
+    let mut router = Router::new();
+    for i in 0..300 {
+        let template: &'static str = Box::leak(format!("/items{}/{{id}}", i).into_boxed_str());
+        router = router.get(
+            template,
+            warp::router::param::<u32>("id").map(move |id| format!("items{} #{}", i, id)),
+        );
+    }
+    let router = router.get("/items7/new", warp::any().map(|| "new item"));
+
+    let res = warp::test::request()
+        .path("/items250/3")
+        .reply(&router)
+        .await;
+    assert_eq!(res.body(), "items250 #3");
+    let res = warp::test::request()
+        .path("/items7/new")
+        .reply(&router)
+        .await;
+    assert_eq!(res.body(), "new item");
+    let res = warp::test::request().path("/items7/").reply(&router).await;
+    assert_eq!(res.status(), 404);
+    let res = warp::test::request()
+        .method("POST")
+        .path("/items3/1")
+        .reply(&router)
+        .await;
+    assert_eq!(res.status(), 405);
+
+    // routes added after dispatching are found too
+    let router = router.get("/late", warp::any().map(|| "late"));
+    let res = warp::test::request().path("/late").reply(&router).await;
+    assert_eq!(res.body(), "late");
+}
+
+#[test]
+fn builds_paths_of_named_routes() {
+    let router = users().route(
+        Endpoint::new(Method::GET, "/users/{id}/files/*").named("user_file"),
+        warp::any().map(warp::reply),
+    );
+
+    assert_eq!(
+        warp::uri_for("user", &[("id", 42)]).as_deref(),
+        Some("/users/42")
+    );
+    assert_eq!(
+        router
+            .uri_for("user_file", &[("id", "a b"), ("tail", "docs/c?.txt")])
+            .as_deref(),
+        Some("/users/a%20b/files/docs/c%3F.txt")
+    );
+    assert_eq!(router.uri_for("user", &[("name", 1)]), None);
+    assert_eq!(warp::uri_for("nope", &[("id", 1)]), None);
+}
+
+#[test]
+fn builds_paths_of_named_patterns() {
+    let comment = warp::path::pattern("/posts/{post}/comments/{comment}").name("comment");
+    assert_eq!(
+        warp::uri_for("comment", &[("post", 1), ("comment", 2)]).as_deref(),
+        Some("/posts/1/comments/2")
+    );
+
+    // building the same filter again is fine
+    let again = warp::path::pattern(comment.template()).name("comment");
+    assert_eq!(again.template(), "/posts/{post}/comments/{comment}");
+}
+
+#[test]
+#[should_panic(expected = "route name \"conflict\" is already used for \"/a\"")]
+fn name_of_another_template() {
+    let _ = warp::path::pattern("/a").name("conflict");
+    let _ = Router::new().route(
+        Endpoint::new(Method::GET, "/b").named("conflict"),
+        warp::any().map(warp::reply),
+    );
+}
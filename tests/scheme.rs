@@ -0,0 +1,95 @@
+#![deny(warnings)]
+
+use std::net::SocketAddr;
+
+use warp::addr::ForwardedHeader;
+use warp::http::uri::Scheme;
+
+fn trusted() -> Vec<warp::addr::IpNet> {
+    vec!["10.0.0.0/8".parse().unwrap()]
+}
+
+fn proxied() -> warp::test::RequestBuilder {
+    let proxy: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+    warp::test::request().remote_addr(proxy)
+}
+
+#[tokio::test]
+async fn direct() {
+    let scheme = warp::scheme(trusted(), ForwardedHeader::XForwardedFor);
+
+    let s = warp::test::request().filter(&scheme).await.unwrap();
+    assert_eq!(s, Scheme::HTTP);
+
+    let s = warp::test::request()
+        .path("https://example.com/")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTPS);
+}
+
+#[tokio::test]
+async fn forwarded_by_trusted_proxy() {
+    let scheme = warp::scheme(trusted(), ForwardedHeader::XForwardedFor);
+
+    let s = proxied()
+        .header("x-forwarded-proto", "https")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTPS);
+
+    // The proxy appends to what the client sent, and only sets
+    // `X-Forwarded-Proto`.
+    let s = proxied()
+        .header("forwarded", "for=203.0.113.7;proto=https")
+        .header("x-forwarded-proto", "https, http")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTP);
+
+    let scheme = warp::scheme(trusted(), ForwardedHeader::Forwarded);
+
+    let s = proxied()
+        .header("forwarded", "for=203.0.113.7;proto=https")
+        .header("x-forwarded-proto", "http")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTPS);
+
+    let s = proxied()
+        .header("x-forwarded-proto", "https")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTP);
+
+    // The outer proxy is trusted, so its view of the scheme wins.
+    let s = proxied()
+        .header(
+            "forwarded",
+            "for=203.0.113.7;proto=https, for=10.0.0.2;proto=http",
+        )
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTPS);
+}
+
+#[tokio::test]
+async fn ignores_untrusted_headers() {
+    let scheme = warp::scheme(trusted(), ForwardedHeader::Forwarded);
+    let client: SocketAddr = "203.0.113.7:5000".parse().unwrap();
+
+    let s = warp::test::request()
+        .remote_addr(client)
+        .header("x-forwarded-proto", "https")
+        .header("forwarded", "proto=https")
+        .filter(&scheme)
+        .await
+        .unwrap();
+    assert_eq!(s, Scheme::HTTP);
+}
@@ -0,0 +1,60 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use warp::security_headers::FrameOptions;
+use warp::Filter;
+
+#[tokio::test]
+async fn defaults() {
+    let route = warp::any().map(warp::reply).with(warp::security_headers());
+
+    let res = warp::test::request().reply(&route).await;
+    let headers = res.headers();
+    assert_eq!(
+        headers["strict-transport-security"],
+        "max-age=31536000; includeSubDomains"
+    );
+    assert_eq!(headers["x-content-type-options"], "nosniff");
+    assert_eq!(headers["x-frame-options"], "DENY");
+    assert_eq!(headers["referrer-policy"], "no-referrer");
+    assert_eq!(headers["cross-origin-opener-policy"], "same-origin");
+    assert!(!headers.contains_key("permissions-policy"));
+    assert!(!headers.contains_key("cross-origin-embedder-policy"));
+}
+
+#[tokio::test]
+async fn customized() {
+    let headers = warp::security_headers()
+        .hsts(Duration::from_secs(600))
+        .hsts_include_subdomains(false)
+        .hsts_preload(true)
+        .content_type_options(false)
+        .frame_options(FrameOptions::SameOrigin)
+        .referrer_policy("strict-origin")
+        .permissions_policy("camera=()")
+        .cross_origin_embedder_policy("require-corp")
+        .without("cross-origin-opener-policy");
+    let route = warp::any().map(warp::reply).with(headers);
+
+    let res = warp::test::request().reply(&route).await;
+    let headers = res.headers();
+    assert_eq!(headers["strict-transport-security"], "max-age=600; preload");
+    assert!(!headers.contains_key("x-content-type-options"));
+    assert_eq!(headers["x-frame-options"], "SAMEORIGIN");
+    assert_eq!(headers["referrer-policy"], "strict-origin");
+    assert_eq!(headers["permissions-policy"], "camera=()");
+    assert_eq!(headers["cross-origin-embedder-policy"], "require-corp");
+    assert!(!headers.contains_key("cross-origin-opener-policy"));
+}
+
+#[tokio::test]
+async fn keeps_reply_headers() {
+    let route = warp::any()
+        .map(|| warp::reply::with_header(warp::reply(), "x-frame-options", "SAMEORIGIN"))
+        .with(warp::security_headers().without("strict-transport-security"));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()["x-frame-options"], "SAMEORIGIN");
+    assert!(!res.headers().contains_key("strict-transport-security"));
+}
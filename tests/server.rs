@@ -0,0 +1,312 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use warp::Filter;
+
+async fn get(addr: SocketAddr) -> (hyper::Client<hyper::client::HttpConnector>, u16) {
+    let client = hyper::Client::new();
+    let uri = format!("http://{}/", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    let status = res.status().as_u16();
+    hyper::body::to_bytes(res.into_body()).await.expect("body");
+    // Returning the client keeps its pooled connection open.
+    (client, status)
+}
+
+#[tokio::test]
+async fn max_connections_sheds_excess() {
+    let _ = pretty_env_logger::try_init();
+
+    let peak = Arc::new(AtomicUsize::new(0));
+    let gauge = peak.clone();
+
+    let route = warp::any().map(warp::reply);
+    let (addr, srv) = warp::serve(route)
+        .max_connections(1)
+        .shed_excess_connections()
+        .connection_gauge(move |open| {
+            gauge.fetch_max(open, Ordering::SeqCst);
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let (_first, status) = get(addr).await;
+    assert_eq!(status, 200);
+
+    let (_second, status) = get(addr).await;
+    assert_eq!(status, 503);
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn request_timeout_replies_408() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().and_then(|| async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok::<_, Infallible>(warp::reply())
+    });
+    let (addr, srv) = warp::serve(route)
+        .request_timeout(Duration::from_millis(50))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let (_client, status) = get(addr).await;
+    assert_eq!(status, 408);
+}
+
+#[tokio::test]
+async fn header_read_timeout_replies_408() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(warp::reply);
+    let (addr, srv) = warp::serve(route)
+        .header_read_timeout(Duration::from_millis(100))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    // Idle connections aren't timed out...
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+    assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+    // ...but slow headers are.
+    stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res).await.unwrap();
+    assert!(
+        res.starts_with(b"HTTP/1.1 408 Request Timeout\r\n"),
+        "{:?}",
+        String::from_utf8_lossy(&res)
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn socket_options_reuse_port() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(warp::reply);
+    let (addr, srv) = warp::serve(route)
+        .reuse_port(true)
+        .listen_backlog(64)
+        .send_buffer_size(64 * 1024)
+        .recv_buffer_size(64 * 1024)
+        .tcp_keepalive(Some(Duration::from_secs(60)))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    // A second listener can share the port.
+    let route = warp::any().map(warp::reply);
+    let (addr2, srv2) = warp::serve(route).reuse_port(true).bind_ephemeral(addr);
+    tokio::spawn(srv2);
+    assert_eq!(addr, addr2);
+
+    let (_client, status) = get(addr).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn drain_deadline_aborts_stragglers() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().and_then(|| async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok::<_, Infallible>(warp::reply())
+    });
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let (addr, srv) = warp::serve(route).bind_with_drain(
+        ([127, 0, 0, 1], 0),
+        async {
+            rx.await.ok();
+        },
+        Duration::from_millis(50),
+    );
+    let srv = tokio::spawn(srv);
+
+    let client = tokio::spawn(get(addr));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let _ = tx.send(());
+
+    let drained = srv.await.expect("server task");
+    assert_eq!(drained.forced_requests(), 1);
+    assert_eq!(drained.forced_connections(), 1);
+    assert!(!drained.is_clean());
+
+    // The aborted connection never gets a response.
+    assert!(client.await.is_err());
+}
+
+#[tokio::test]
+async fn bind_local_serves_non_send_handlers() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().and_then_local(|| async {
+        let greeting = std::rc::Rc::new("local");
+        tokio::task::yield_now().await;
+        Ok::<_, warp::Rejection>(*greeting)
+    });
+
+    let local = tokio::task::LocalSet::new();
+    let (_client, status) = local
+        .run_until(async {
+            let (addr, srv) = warp::serve(route).bind_local(([127, 0, 0, 1], 0));
+            tokio::task::spawn_local(srv);
+            get(addr).await
+        })
+        .await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn on_error_reports_rejections_and_panics() {
+    let _ = pretty_env_logger::try_init();
+
+    let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let hook = reported.clone();
+    let route = warp::post().map(warp::reply);
+    let (addr, srv) = warp::serve(route)
+        .on_error(move |err| {
+            assert!(err.rejection().is_some());
+            assert!(err.panic().is_none());
+            hook.lock().unwrap().push((
+                err.method().clone(),
+                err.uri().path().to_owned(),
+                err.status(),
+            ));
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let (_client, status) = get(addr).await;
+    assert_eq!(status, 405);
+
+    let hook = reported.clone();
+    let route = warp::any().map(|| -> &'static str { panic!("oh no") });
+    let (addr, srv) = warp::serve(route)
+        .on_error(move |err| {
+            assert!(err.rejection().is_none());
+            let message = err.panic().and_then(|panic| panic.message()).unwrap();
+            assert_eq!(message, "oh no");
+            hook.lock().unwrap().push((
+                err.method().clone(),
+                err.uri().path().to_owned(),
+                err.status(),
+            ));
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let (_client, status) = get(addr).await;
+    assert_eq!(status, 500);
+
+    let reported = reported.lock().unwrap();
+    assert_eq!(
+        *reported,
+        vec![
+            (
+                http::Method::GET,
+                "/".to_owned(),
+                http::StatusCode::METHOD_NOT_ALLOWED
+            ),
+            (
+                http::Method::GET,
+                "/".to_owned(),
+                http::StatusCode::INTERNAL_SERVER_ERROR
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lifecycle_hooks() {
+    let _ = pretty_env_logger::try_init();
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let (on_open, on_close, on_request, on_response) = (
+        events.clone(),
+        events.clone(),
+        events.clone(),
+        events.clone(),
+    );
+    let route = warp::any().map(warp::reply);
+    let (addr, srv) = warp::serve(route)
+        .on_connection_open(move |conn| {
+            assert!(conn.remote_addr().is_some());
+            on_open.lock().unwrap().push("open".to_owned());
+        })
+        .on_connection_close(move |_| on_close.lock().unwrap().push("close".to_owned()))
+        .on_request(move |req| {
+            on_request
+                .lock()
+                .unwrap()
+                .push(format!("request {} {}", req.method(), req.uri()));
+        })
+        .on_response(move |res| {
+            on_response.lock().unwrap().push(format!(
+                "response {} {}",
+                res.uri(),
+                res.status().as_u16()
+            ));
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(srv);
+
+    let (client, status) = get(addr).await;
+    assert_eq!(status, 200);
+    drop(client);
+
+    // Wait for the server to notice the connection closing.
+    for _ in 0..100 {
+        if events.lock().unwrap().len() == 4 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["open", "request GET /", "response / 200", "close"]
+    );
+}
+
+#[tokio::test]
+async fn test_serve() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("hello").map(|| "hello");
+    let server = warp::test::serve(route);
+    assert!(server.addr().ip().is_loopback());
+
+    let client = hyper::Client::new();
+    let res = client
+        .get(server.url("/hello").parse().unwrap())
+        .await
+        .expect("request");
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "hello");
+
+    let addr = server.addr();
+    drop(client);
+    server.shutdown().await;
+
+    // the port isn't served anymore
+    let uri = format!("http://{}/hello", addr).parse().unwrap();
+    assert!(hyper::Client::new().get(uri).await.is_err());
+}
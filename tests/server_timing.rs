@@ -0,0 +1,47 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use warp::server_timing::Timings;
+use warp::Filter;
+
+#[tokio::test]
+async fn sends_recorded_timings() {
+    let route = warp::any()
+        .and(warp::server_timing::timings())
+        .map(|timings: Timings| {
+            timings.record("db", Duration::from_micros(53_200));
+            timings.start("cache").describe("Cache \"read\"").stop();
+            warp::reply()
+        })
+        .with(warp::server_timing());
+
+    let res = warp::test::request().reply(&route).await;
+    let value = res.headers()["server-timing"].to_str().unwrap();
+    assert!(
+        value.starts_with("db;dur=53.2, cache;desc=\"Cache \\\"read\\\"\";dur="),
+        "{}",
+        value
+    );
+}
+
+#[tokio::test]
+async fn total_and_empty() {
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::server_timing().total("total"));
+    let res = warp::test::request().reply(&route).await;
+    let value = res.headers()["server-timing"].to_str().unwrap();
+    assert!(value.starts_with("total;dur="), "{}", value);
+
+    let route = warp::any().map(warp::reply).with(warp::server_timing());
+    let res = warp::test::request().reply(&route).await;
+    assert!(!res.headers().contains_key("server-timing"));
+}
+
+#[tokio::test]
+async fn timings_requires_wrapper() {
+    let route = warp::server_timing::timings();
+    let res = warp::test::request().filter(&route).await;
+    assert!(res.is_err());
+}
@@ -0,0 +1,83 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+
+use warp::http::StatusCode;
+use warp::hyper::service::service_fn;
+use warp::hyper::{Body, Request, Response};
+use warp::Filter;
+
+#[tokio::test]
+async fn from_service() {
+    let _ = pretty_env_logger::try_init();
+
+    let legacy = service_fn(|req: Request<Body>| async move {
+        let body = format!("{} {}", req.method(), req.uri().path());
+        Ok::<_, Infallible>(Response::new(Body::from(body)))
+    });
+    let route = warp::path("legacy")
+        .and(warp::service::from_service(legacy))
+        .or(warp::path("new").map(|| "new"));
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/legacy/users")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "POST /legacy/users");
+
+    let res = warp::test::request().path("/new").reply(&route).await;
+    assert_eq!(res.body(), "new");
+}
+
+#[tokio::test]
+async fn from_service_body() {
+    let _ = pretty_env_logger::try_init();
+
+    let echo = service_fn(|req: Request<Body>| async move {
+        Ok::<_, Infallible>(Response::new(req.into_body()))
+    });
+    let route = warp::service::from_service(echo);
+
+    let res = warp::test::request().body("echo").reply(&route).await;
+    assert_eq!(res.body(), "echo");
+}
+
+#[tokio::test]
+async fn from_service_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let failing = service_fn(|_: Request<Body>| async move {
+        Err::<Response<Body>, _>(std::io::Error::other("down"))
+    });
+    let route = warp::service::from_service(failing);
+
+    let rejection = warp::test::request().filter(&route).await.err().unwrap();
+    let err = rejection
+        .find::<warp::wrap::ServiceError>()
+        .expect("ServiceError");
+    assert_eq!(err.error().to_string(), "down");
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn call_once() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hello").map(|| "hello");
+    let service = warp::service(routes);
+
+    let req = Request::get("/hello").body(Body::empty()).unwrap();
+    let res = service.call_once(req).await;
+    assert_eq!(res.status(), 200);
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "hello");
+
+    // rejections are replies
+    let req = Request::get("/other").body(Body::empty()).unwrap();
+    let res = service.call_once(req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
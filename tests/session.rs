@@ -0,0 +1,159 @@
+#![deny(warnings)]
+
+use std::time::Duration;
+
+use warp::session::{FileStore, MemoryStore, Session, SessionData, SessionError, SessionStore};
+use warp::Filter;
+
+fn routes<S: SessionStore>(
+    sessions: warp::session::Sessions<S>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let login = warp::path!("login" / String)
+        .and(warp::session::current())
+        .map(|user: String, session: Session| {
+            session.renew();
+            session.insert("user", user).unwrap();
+            "welcome"
+        });
+    let logout = warp::path("logout")
+        .and(warp::session::current())
+        .map(|session: Session| {
+            session.destroy();
+            "bye"
+        });
+    let whoami = warp::path("whoami")
+        .and(warp::session::current())
+        .map(|session: Session| session.get::<String>("user").unwrap_or_default());
+    login.or(logout).or(whoami).with(sessions)
+}
+
+#[tokio::test]
+async fn memory_store() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = routes(warp::session(MemoryStore::default()));
+    let client = warp::test::Client::new();
+
+    // Nothing is saved, or set, for sessions that weren't changed.
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "");
+    assert!(!res.headers().contains_key("set-cookie"));
+
+    let res = client.request().path("/login/sean").reply(&routes).await;
+    let cookie = res.headers()["set-cookie"].to_str().unwrap();
+    assert!(cookie.ends_with("; Max-Age=86400; Path=/; Secure; HttpOnly; SameSite=Lax"));
+    let id = client.cookie("session").unwrap();
+
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "sean");
+    assert!(!res.headers().contains_key("set-cookie"));
+
+    // Logging in again moves the session to a new id.
+    client.request().path("/login/ada").reply(&routes).await;
+    assert_ne!(client.cookie("session").unwrap(), id);
+    let res = warp::test::request()
+        .path("/whoami")
+        .header("cookie", format!("session={}", id))
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "");
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "ada");
+
+    let res = client.request().path("/logout").reply(&routes).await;
+    assert!(res.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .starts_with("session=; Max-Age=0;"));
+    assert_eq!(client.cookie("session"), None);
+}
+
+#[tokio::test]
+async fn expires() {
+    let _ = pretty_env_logger::try_init();
+    warp::test::pause_time();
+
+    let routes = routes(warp::session(MemoryStore::default()).ttl(Duration::from_secs(60)));
+    let client = warp::test::Client::new();
+
+    client.request().path("/login/sean").reply(&routes).await;
+    warp::test::advance(Duration::from_secs(59));
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "sean");
+
+    warp::test::advance(Duration::from_secs(1));
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "");
+}
+
+#[tokio::test]
+async fn file_store() {
+    let _ = pretty_env_logger::try_init();
+
+    let dir = std::env::temp_dir().join(format!("warp-sessions-{}", std::process::id()));
+    let routes = routes(
+        warp::session(FileStore::new(&dir))
+            .cookie_name("sid")
+            .secure(false),
+    );
+    let client = warp::test::Client::new().secure(false);
+
+    client.request().path("/login/sean").reply(&routes).await;
+    let id = client.cookie("sid").unwrap();
+    assert!(dir.join(format!("{}.json", id)).exists());
+
+    let res = client.request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.body(), "sean");
+
+    // Ids that aren't session ids never reach the store.
+    let res = warp::test::request()
+        .path("/whoami")
+        .header("cookie", "sid=../../etc/passwd")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "");
+
+    client.request().path("/logout").reply(&routes).await;
+    assert!(!dir.join(format!("{}.json", id)).exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+struct Broken;
+
+impl SessionStore for Broken {
+    fn load(&self, _: &str) -> warp::session::StoreFuture<Option<SessionData>> {
+        Box::pin(async { Err(SessionError::new("unavailable")) })
+    }
+
+    fn save(&self, _: &str, _: SessionData, _: Duration) -> warp::session::StoreFuture<()> {
+        Box::pin(async { Err(SessionError::new("unavailable")) })
+    }
+
+    fn delete(&self, _: &str) -> warp::session::StoreFuture<()> {
+        Box::pin(async { Err(SessionError::new("unavailable")) })
+    }
+}
+
+#[tokio::test]
+async fn store_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = routes(warp::session(Broken));
+
+    let res = warp::test::request()
+        .path("/login/sean")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 500);
+
+    // Without an id, there's nothing to load.
+    let res = warp::test::request().path("/whoami").reply(&routes).await;
+    assert_eq!(res.status(), 200);
+
+    // Without the wrap, there's no session.
+    let route = warp::session::current().map(|_| "");
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 500);
+}
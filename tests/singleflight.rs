@@ -0,0 +1,150 @@
+#![deny(warnings)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use warp::singleflight::Singleflight;
+use warp::Filter;
+
+// Replies with how many times the slow handler ran.
+fn counted(
+    singleflight: Singleflight,
+) -> (
+    Arc<AtomicUsize>,
+    impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone,
+) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let route = warp::any()
+        .and_then(move || {
+            let n = c.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok::<_, warp::Rejection>(n.to_string())
+            }
+        })
+        .with(singleflight);
+    (count, route)
+}
+
+#[tokio::test]
+async fn coalesces_concurrent_gets() {
+    let singleflight = warp::singleflight();
+    let (count, route) = counted(singleflight.clone());
+
+    let (a, b, c) = tokio::join!(
+        warp::test::request().path("/a").reply(&route),
+        warp::test::request().path("/a").reply(&route),
+        warp::test::request().path("/a").reply(&route),
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    for res in &[a, b, c] {
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.body(), "1");
+    }
+    assert_eq!(singleflight.in_flight(), 0);
+
+    // Not concurrent, so it runs again.
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.body(), "2");
+}
+
+#[tokio::test]
+async fn separates_keys_and_methods() {
+    let (count, route) = counted(warp::singleflight().vary("authorization"));
+
+    let (a, b, c, d, e) = tokio::join!(
+        warp::test::request().path("/a").reply(&route),
+        warp::test::request().path("/a?page=2").reply(&route),
+        warp::test::request()
+            .path("/a")
+            .header("host", "other.example.com")
+            .reply(&route),
+        warp::test::request()
+            .path("/a")
+            .header("authorization", "other")
+            .reply(&route),
+        warp::test::request()
+            .method("POST")
+            .path("/a")
+            .reply(&route),
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 5);
+    let mut bodies = vec![
+        a.into_body(),
+        b.into_body(),
+        c.into_body(),
+        d.into_body(),
+        e.into_body(),
+    ];
+    bodies.sort();
+    assert_eq!(bodies, vec!["1", "2", "3", "4", "5"]);
+}
+
+#[tokio::test]
+async fn waiters_run_filter_after_rejection() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let route = warp::any()
+        .and_then(move || {
+            let n = c.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                if n == 1 {
+                    Err(warp::reject::not_found())
+                } else {
+                    Ok(n.to_string())
+                }
+            }
+        })
+        .with(warp::singleflight());
+
+    let (a, b) = tokio::join!(
+        warp::test::request().path("/a").reply(&route),
+        warp::test::request().path("/a").reply(&route),
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    let mut statuses = vec![a.status().as_u16(), b.status().as_u16()];
+    statuses.sort_unstable();
+    assert_eq!(statuses, vec![200, 404]);
+}
+
+#[tokio::test]
+async fn skips_credentials() {
+    let (count, route) = counted(warp::singleflight());
+
+    let (a, b, c) = tokio::join!(
+        warp::test::request()
+            .path("/me")
+            .header("cookie", "session=alice")
+            .reply(&route),
+        warp::test::request()
+            .path("/me")
+            .header("cookie", "session=bob")
+            .reply(&route),
+        warp::test::request()
+            .path("/me")
+            .header("authorization", "Bearer carol")
+            .reply(&route),
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    let mut bodies = vec![a.into_body(), b.into_body(), c.into_body()];
+    bodies.sort();
+    assert_eq!(bodies, vec!["1", "2", "3"]);
+
+    // Unless they're part of the key.
+    let (count, route) = counted(warp::singleflight().vary("cookie"));
+    let (a, b) = tokio::join!(
+        warp::test::request()
+            .path("/me")
+            .header("cookie", "session=alice")
+            .reply(&route),
+        warp::test::request()
+            .path("/me")
+            .header("cookie", "session=alice")
+            .reply(&route),
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(a.body(), b.body());
+}
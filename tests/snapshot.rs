@@ -0,0 +1,76 @@
+#![deny(warnings)]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use warp::test::Snapshot;
+use warp::Filter;
+
+fn snapshot_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("warp-snapshots-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn snapshot_written_then_compared() {
+    let _ = pretty_env_logger::try_init();
+
+    let dir = snapshot_dir("compared");
+    let snapshot = Snapshot::new().dir(&dir);
+    let hello = warp::any().map(|| "hello");
+
+    let res = warp::test::request().reply(&hello).await;
+    snapshot.assert("hello", &res);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("hello.snap")).unwrap(),
+        "status: 200 OK\ncontent-type: text/plain; charset=utf-8\n\nhello\n",
+    );
+
+    // matches the stored snapshot
+    snapshot.assert("hello", &res);
+
+    let res = warp::test::request()
+        .reply(&warp::any().map(|| "goodbye"))
+        .await;
+    let mismatch = panic::catch_unwind(AssertUnwindSafe(|| snapshot.assert("hello", &res)));
+    assert!(mismatch.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn snapshot_redactions() {
+    let _ = pretty_env_logger::try_init();
+
+    let dir = snapshot_dir("redactions");
+    let route = warp::any()
+        .map(|| {
+            let id = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            warp::reply::json(&id.to_string())
+        })
+        .with(warp::reply::with::header("x-request-id", "abc"));
+
+    let snapshot = Snapshot::new()
+        .dir(&dir)
+        .redact_header("x-request-id")
+        .redact(|text| {
+            let start = text.find("\n\n").unwrap() + 2;
+            format!("{}\"[id]\"\n", &text[..start])
+        });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(
+        snapshot.serialize(&res),
+        "status: 200 OK\ncontent-type: application/json\nx-request-id: [redacted]\n\n\"[id]\"\n",
+    );
+    snapshot.assert("id", &res);
+
+    let res = warp::test::request().reply(&route).await;
+    snapshot.assert("id", &res);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,97 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use futures::{stream, StreamExt};
+use warp::sse::Event;
+use warp::Filter;
+
+#[tokio::test]
+async fn sse_events() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("events").map(|| {
+        warp::sse::reply(stream::iter(vec![
+            Ok::<_, Infallible>(Event::default().event("greet").id("1").data("hello\nworld")),
+            Ok(Event::default()
+                .retry(Duration::from_millis(1500))
+                .json_data([1, 2])
+                .unwrap()),
+        ]))
+    });
+
+    let mut client = warp::test::sse()
+        .path("/events")
+        .connect(&route)
+        .await
+        .expect("connect");
+
+    let event = client.recv().await.unwrap();
+    assert_eq!(event.event(), Some("greet"));
+    assert_eq!(event.id(), Some("1"));
+    assert_eq!(event.data(), Some("hello\nworld"));
+    assert_eq!(event.retry(), None);
+
+    let event = client.recv().await.unwrap();
+    assert_eq!(event.event(), None);
+    assert_eq!(event.data(), Some("[1,2]"));
+    assert_eq!(event.retry(), Some(Duration::from_millis(1500)));
+
+    client.recv_closed().await.unwrap();
+}
+
+#[tokio::test]
+async fn sse_keep_alive_and_timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let quiet = warp::any().map(|| {
+        let events = stream::pending::<Result<Event, Infallible>>();
+        warp::sse::reply(
+            warp::sse::keep_alive()
+                .interval(Duration::from_millis(20))
+                .text("ping")
+                .stream(events),
+        )
+    });
+    let events = warp::test::sse()
+        .connect(&quiet)
+        .await
+        .unwrap()
+        .take(2)
+        .collect::<Vec<_>>()
+        .await;
+    for event in events {
+        assert_eq!(event.unwrap().comment(), Some("ping"));
+    }
+
+    let silent =
+        warp::any().map(|| warp::sse::reply(stream::pending::<Result<Event, Infallible>>()));
+    let mut client = warp::test::sse()
+        .timeout(Duration::from_millis(20))
+        .connect(&silent)
+        .await
+        .unwrap();
+    assert!(client.recv().await.unwrap_err().is_timeout());
+}
+
+#[tokio::test]
+async fn sse_connect_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("events").map(|| "not events");
+
+    let err = warp::test::sse()
+        .path("/other")
+        .connect(&route)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("404"), "{}", err);
+
+    let err = warp::test::sse()
+        .path("/events")
+        .connect(&route)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("content-type"), "{}", err);
+}
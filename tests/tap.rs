@@ -0,0 +1,50 @@
+#![deny(warnings)]
+
+use futures::StreamExt;
+use warp::tap::TapHandle;
+use warp::Filter;
+
+#[tokio::test]
+async fn subscribers_see_summaries() {
+    let handle = TapHandle::new();
+    let route = warp::path!("users" / u32)
+        .map(|id| format!("user #{}", id))
+        .with(warp::tap(&handle));
+
+    // Nobody is watching yet.
+    warp::test::request().path("/users/1").reply(&route).await;
+
+    let summaries = handle.subscribe();
+    assert_eq!(handle.subscribers(), 1);
+    warp::test::request().path("/users/2").reply(&route).await;
+    warp::test::request().path("/nope").reply(&route).await;
+    drop(handle);
+    drop(route);
+
+    let summaries = summaries.collect::<Vec<_>>().await;
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].method(), "GET");
+    assert_eq!(summaries[0].path(), "/users/2");
+    assert_eq!(summaries[0].route(), Some("/users/{u32}"));
+    assert_eq!(summaries[0].status(), 200);
+    assert!(summaries[0].to_string().starts_with("GET /users/2 200 "));
+    assert_eq!(summaries[1].path(), "/nope");
+    assert_eq!(summaries[1].status(), 404);
+}
+
+#[tokio::test]
+async fn sampling() {
+    let handle = TapHandle::new();
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::tap(&handle).sample(0.25));
+
+    let summaries = handle.subscribe();
+    for _ in 0..8 {
+        warp::test::request().reply(&route).await;
+    }
+    drop(handle);
+    drop(route);
+
+    assert_eq!(summaries.count().await, 2);
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::time::{Duration, Instant};
+
+use warp::Filter;
+
+fn body(len: usize) -> impl Filter<Extract = (Vec<u8>,), Error = Infallible> + Clone {
+    warp::any().map(move || vec![b'a'; len])
+}
+
+#[tokio::test]
+async fn paces_body() {
+    // The first KiB goes out right away, the rest at 10 KB/s.
+    let route = body(3 * 1024).with(warp::throttle(10_000));
+
+    let start = Instant::now();
+    let res = warp::test::request().reply(&route).await;
+    let elapsed = start.elapsed();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "3072");
+    assert_eq!(res.body().len(), 3 * 1024);
+    assert!(elapsed >= Duration::from_millis(150), "{:?}", elapsed);
+}
+
+#[tokio::test]
+async fn small_bodies_are_not_delayed() {
+    let route = body(100).with(warp::throttle(10_000));
+
+    let start = Instant::now();
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.body().len(), 100);
+    assert!(start.elapsed() < Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn global_limit_is_shared() {
+    let route = body(2 * 1024).with(warp::throttle(1_000_000).global(10_000));
+
+    let start = Instant::now();
+    let (a, b) = tokio::join!(
+        warp::test::request().reply(&route),
+        warp::test::request().reply(&route),
+    );
+    let elapsed = start.elapsed();
+    assert_eq!(a.body().len(), 2 * 1024);
+    assert_eq!(b.body().len(), 2 * 1024);
+    // 3 KiB over the burst, shared at 10 KB/s.
+    assert!(elapsed >= Duration::from_millis(250), "{:?}", elapsed);
+}
@@ -0,0 +1,54 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use warp::http::StatusCode;
+use warp::Filter;
+
+fn slow() -> impl Filter<Extract = (&'static str,), Error = Infallible> + Clone {
+    warp::any().and_then(|| async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok::<_, Infallible>("slow")
+    })
+}
+
+#[tokio::test]
+async fn within_deadline() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| "fast")
+        .with(warp::timeout(Duration::from_secs(1)));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "fast");
+}
+
+#[tokio::test]
+async fn exceeded_deadline() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = slow().with(warp::timeout(Duration::from_millis(10)));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let rejection = warp::test::request().filter(&route).await.unwrap_err();
+    let timed_out = rejection
+        .find::<warp::timeout::TimedOut>()
+        .expect("TimedOut rejection");
+    assert_eq!(timed_out.duration(), Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn custom_status() {
+    let _ = pretty_env_logger::try_init();
+
+    let route =
+        slow().with(warp::timeout(Duration::from_millis(10)).status(StatusCode::REQUEST_TIMEOUT));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+}
@@ -1,3 +1,11 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{self, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
 use warp::Filter;
 
 #[tokio::test]
@@ -46,3 +54,137 @@ async fn uses_tracing() {
     let resp = req.reply(&ok);
     assert_eq!(resp.await.status(), 200);
 }
+
+// Records the spans that events are emitted in, innermost first, and the
+// spans created.
+#[derive(Clone, Default)]
+struct Scopes(Arc<Mutex<Vec<Vec<String>>>>, Arc<Mutex<Vec<String>>>);
+
+struct SpanLabel(String);
+
+#[derive(Default)]
+struct LabelVisitor(String);
+
+impl Visit for LabelVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S> Layer<S> for Scopes
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        let mut visitor = LabelVisitor::default();
+        attrs.record(&mut visitor);
+        let span = ctx.span(id).unwrap();
+        let label = format!("{}({})", span.name(), visitor.0);
+        self.1.lock().unwrap().push(label.clone());
+        span.extensions_mut().insert(SpanLabel(label));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        if event.metadata().target() != "tracing" {
+            return;
+        }
+        let scope = match ctx.lookup_current() {
+            Some(span) => span
+                .scope()
+                .map(|span| span.extensions().get::<SpanLabel>().unwrap().0.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        self.0.lock().unwrap().push(scope);
+    }
+}
+
+#[tokio::test]
+async fn named_spans_parent_handlers_across_or() {
+    let scopes = Scopes::default();
+    let _default =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(scopes.clone()));
+
+    let hello = warp::path("hello")
+        .map(|| {
+            tracing::info!("hello handler");
+            "hello"
+        })
+        .with(warp::trace::named("hello"));
+
+    let user = warp::path!("users" / u32)
+        .and_then(|id: u32| async move {
+            tokio::task::yield_now().await;
+            tracing::info!("user handler");
+            Ok::<_, warp::Rejection>(format!("user #{}", id))
+        })
+        .with(warp::trace::extracted(warp::path!("users" / u32), |id| {
+            tracing::debug_span!("user", id)
+        }));
+
+    let routes = hello.or(user).with(warp::trace::request());
+
+    let res = warp::test::request().path("/users/7").reply(&routes).await;
+    assert_eq!(res.body(), "user #7");
+
+    let res = warp::test::request().path("/hello").reply(&routes).await;
+    assert_eq!(res.body(), "hello");
+
+    let res = warp::test::request().path("/nope").reply(&routes).await;
+    assert_eq!(res.status(), 404);
+
+    let scopes = scopes.0.lock().unwrap();
+    assert_eq!(
+        scopes[0],
+        vec![
+            "user(id=7)".to_owned(),
+            "request(method=GET path=/users/7 version=HTTP/1.1)".to_owned()
+        ]
+    );
+    assert_eq!(
+        scopes[1],
+        vec![
+            "context(hello)".to_owned(),
+            "request(method=GET path=/hello version=HTTP/1.1)".to_owned()
+        ]
+    );
+    assert_eq!(scopes.len(), 2);
+}
+
+#[tokio::test]
+async fn sampled_spans() {
+    use warp::sample::Sample;
+
+    let scopes = Scopes::default();
+    let _default =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(scopes.clone()));
+
+    let route = warp::path::param()
+        .and_then(|code: u16| async move {
+            tracing::info!("handler");
+            Ok::<_, warp::Rejection>(warp::reply::with_status(
+                warp::reply(),
+                warp::http::StatusCode::from_u16(code).unwrap(),
+            ))
+        })
+        .with(warp::trace::named("sampled").sample(Sample::errors()));
+
+    let res = warp::test::request().path("/200").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    let res = warp::test::request().path("/503").reply(&route).await;
+    assert_eq!(res.status(), 503);
+
+    // Handlers run outside of the span, but it's created for failures.
+    assert_eq!(
+        *scopes.0.lock().unwrap(),
+        vec![Vec::<String>::new(), Vec::new()]
+    );
+    assert_eq!(*scopes.1.lock().unwrap(), vec!["context(sampled)"]);
+}
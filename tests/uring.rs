@@ -0,0 +1,35 @@
+#![deny(warnings)]
+#![cfg(target_os = "linux")]
+
+use warp::Filter;
+
+#[test]
+fn serves_files() {
+    let _ = pretty_env_logger::try_init();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        tokio_uring::start(async move {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            drop(listener);
+            tx.send(addr).unwrap();
+
+            let routes = warp::path("readme").and(warp::fs::file("README.md"));
+            warp::serve(routes).run_uring(addr).await;
+        })
+    });
+    let addr = rx.recv().unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let body = rt.block_on(async move {
+        // Give the listener a moment to come up.
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let uri = format!("http://{}/readme", addr).parse().unwrap();
+        let res = hyper::Client::new().get(uri).await.expect("request");
+        assert_eq!(res.status(), 200);
+        hyper::body::to_bytes(res.into_body()).await.expect("body")
+    });
+
+    assert_eq!(body, std::fs::read("README.md").unwrap());
+}
@@ -0,0 +1,91 @@
+#![deny(warnings)]
+use warp::header::{DeviceClass, UserAgent};
+
+fn parse(header: &str) -> (Option<String>, Option<String>, Option<String>, DeviceClass) {
+    let agent = header.parse::<UserAgent>().unwrap();
+    assert_eq!(agent.as_str(), header);
+    (
+        agent.product().map(String::from),
+        agent.version().map(String::from),
+        agent.os().map(String::from),
+        agent.device_class(),
+    )
+}
+
+fn some(s: &str) -> Option<String> {
+    Some(s.to_string())
+}
+
+#[test]
+fn browsers() {
+    assert_eq!(
+        parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36"),
+        (some("Chrome"), some("91.0.4472.124"), some("Windows"), DeviceClass::Desktop),
+    );
+    assert_eq!(
+        parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36 Edg/91.0.864.59"),
+        (some("Edge"), some("91.0.864.59"), some("Windows"), DeviceClass::Desktop),
+    );
+    assert_eq!(
+        parse("Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:89.0) Gecko/20100101 Firefox/89.0"),
+        (
+            some("Firefox"),
+            some("89.0"),
+            some("macOS"),
+            DeviceClass::Desktop
+        ),
+    );
+    assert_eq!(
+        parse("Mozilla/5.0 (iPhone; CPU iPhone OS 14_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/14.1.1 Mobile/15E148 Safari/604.1"),
+        (some("Safari"), some("14.1.1"), some("iOS"), DeviceClass::Mobile),
+    );
+    assert_eq!(
+        parse("Mozilla/5.0 (Linux; Android 11; SM-T870) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.120 Safari/537.36"),
+        (some("Chrome"), some("91.0.4472.120"), some("Android"), DeviceClass::Tablet),
+    );
+    assert_eq!(
+        parse("Mozilla/5.0 (Windows NT 6.1; Trident/7.0; rv:11.0) like Gecko"),
+        (
+            some("Internet Explorer"),
+            some("11.0"),
+            some("Windows"),
+            DeviceClass::Desktop
+        ),
+    );
+}
+
+#[test]
+fn clients() {
+    assert_eq!(
+        parse("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)"),
+        (some("Googlebot"), some("2.1"), None, DeviceClass::Bot),
+    );
+    assert_eq!(
+        parse("curl/7.68.0"),
+        (some("curl"), some("7.68.0"), None, DeviceClass::Unknown),
+    );
+    assert_eq!(parse("???"), (None, None, None, DeviceClass::Unknown));
+}
+
+#[tokio::test]
+async fn filter() {
+    let agent = warp::test::request()
+        .header(
+            "user-agent",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:89.0) Gecko/20100101 Firefox/89.0",
+        )
+        .filter(&warp::header::user_agent())
+        .await
+        .unwrap();
+    assert_eq!(agent.product(), Some("Firefox"));
+    assert_eq!(agent.os(), Some("Linux"));
+
+    assert!(
+        !warp::test::request()
+            .matches(&warp::header::user_agent())
+            .await
+    );
+
+    let optional = warp::header::optional::<UserAgent>("user-agent");
+    assert_eq!(warp::test::request().filter(&optional).await.unwrap(), None);
+}
@@ -0,0 +1,70 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn path_and_header() {
+    let _ = pretty_env_logger::try_init();
+
+    let by_path = warp::version::path("v2")
+        .and(warp::path!("users"))
+        .map(|| "v2");
+    assert!(
+        warp::test::request()
+            .path("/v2/users")
+            .matches(&by_path)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .path("/v1/users")
+            .matches(&by_path)
+            .await
+    );
+
+    let by_header = warp::version::header("accept-version", "2").map(|| "v2");
+    assert!(
+        warp::test::request()
+            .header("accept-version", " 2")
+            .matches(&by_header)
+            .await
+    );
+    assert!(
+        !warp::test::request()
+            .header("accept-version", "1")
+            .matches(&by_header)
+            .await
+    );
+    assert!(!warp::test::request().matches(&by_header).await);
+}
+
+#[tokio::test]
+async fn across_versions() {
+    let _ = pretty_env_logger::try_init();
+
+    let users = warp::path!("users").map(|| "users");
+    let routes = warp::version::across(["v1", "v2"], warp::version::path, users)
+        .version("v1", warp::path!("users").map(|| "old users"))
+        .version("v3", warp::path!("users").map(|| "new users"));
+    assert_eq!(routes.versions().collect::<Vec<_>>(), ["v1", "v2", "v3"]);
+
+    let res = warp::test::request().path("/v1/users").reply(&routes).await;
+    assert_eq!(res.body(), "old users");
+    let res = warp::test::request().path("/v2/users").reply(&routes).await;
+    assert_eq!(res.body(), "users");
+    let res = warp::test::request().path("/v3/users").reply(&routes).await;
+    assert_eq!(res.body(), "new users");
+    let res = warp::test::request().path("/v4/users").reply(&routes).await;
+    assert_eq!(res.status(), 404);
+
+    let by_header = warp::version::across(
+        ["1", "2"],
+        |version| warp::version::header("accept-version", version),
+        warp::any().map(|| "posts"),
+    )
+    .version("1", warp::any().map(|| "old posts"));
+    let res = warp::test::request()
+        .header("accept-version", "1")
+        .reply(&by_header)
+        .await;
+    assert_eq!(res.body(), "old posts");
+}
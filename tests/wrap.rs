@@ -0,0 +1,137 @@
+#![deny(warnings)]
+
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::task::{Context, Poll};
+
+use tower_layer::Layer;
+use tower_service::Service;
+use warp::http::{Request, StatusCode};
+use warp::hyper::Body;
+use warp::Filter;
+
+// Adds an `x-layer` header to requests.
+#[derive(Clone)]
+struct SetHeader<S>(S);
+
+impl<S: Service<Request<Body>>> Service<Request<Body>> for SetHeader<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> S::Future {
+        req.headers_mut().insert("x-layer", "1".parse().unwrap());
+        self.0.call(req)
+    }
+}
+
+struct SetHeaderLayer;
+
+impl<S> Layer<S> for SetHeaderLayer {
+    type Service = SetHeader<S>;
+
+    fn layer(&self, inner: S) -> SetHeader<S> {
+        SetHeader(inner)
+    }
+}
+
+// Fails every request, without calling the inner service.
+#[derive(Clone)]
+struct Fail;
+
+impl Service<Request<Body>> for Fail {
+    type Response = warp::reply::Response;
+    type Error = std::io::Error;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Request<Body>) -> Self::Future {
+        ready(Err(std::io::Error::other("nope")))
+    }
+}
+
+struct FailLayer;
+
+impl<S> Layer<S> for FailLayer {
+    type Service = Fail;
+
+    fn layer(&self, _: S) -> Fail {
+        Fail
+    }
+}
+
+#[tokio::test]
+async fn from_layer() {
+    let _ = pretty_env_logger::try_init();
+
+    let hello = warp::path("hello")
+        .and(warp::path::end())
+        .and(warp::header::<String>("x-layer"))
+        .map(|layer: String| format!("hello, layer {}", layer));
+    let route = warp::path("api").and(hello.with(warp::wrap::from_layer(SetHeaderLayer)));
+
+    // matching the path carries on after `api`
+    let res = warp::test::request().path("/api/hello").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "hello, layer 1");
+}
+
+#[tokio::test]
+async fn from_layer_rejections() {
+    let _ = pretty_env_logger::try_init();
+
+    let hello = warp::path("hello")
+        .map(|| "hello")
+        .with(warp::wrap::from_layer(SetHeaderLayer));
+    let route = hello.or(warp::path("bye").map(|| "bye"));
+
+    let res = warp::test::request().path("/bye").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "bye");
+
+    let res = warp::test::request().path("/other").reply(&route).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn from_layer_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| "unreachable")
+        .with(warp::wrap::from_layer(FailLayer));
+
+    let rejection = warp::test::request().filter(&route).await.err().unwrap();
+    let err = rejection
+        .find::<warp::wrap::ServiceError>()
+        .expect("ServiceError");
+    assert_eq!(err.error().to_string(), "nope");
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn into_layer() {
+    let _ = pretty_env_logger::try_init();
+
+    let service = warp::hyper::service::service_fn(|req: Request<Body>| async move {
+        Ok::<_, Infallible>(warp::http::Response::new(Body::from(req.uri().to_string())))
+    });
+    let layer = warp::wrap::into_layer(warp::reply::with::header("x-wrapped", "1"));
+    let mut service = layer.layer(service);
+
+    let req = Request::get("/svc").body(Body::empty()).unwrap();
+    let res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-wrapped"], "1");
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "/svc");
+}
@@ -259,6 +259,40 @@ async fn ws_with_query() {
         .expect("handshake");
 }
 
+#[tokio::test]
+async fn handshake_negotiation() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::header::exact("origin", "https://example.com")
+        .and(warp::header::<String>("sec-websocket-protocol"))
+        .and(warp::ws())
+        .map(|offered: String, ws: warp::ws::Ws| {
+            let selected = offered.split(", ").last().unwrap().to_owned();
+            let reply = ws.on_upgrade(|_| async {});
+            warp::reply::with_header(reply, "sec-websocket-protocol", selected)
+        });
+
+    let client = warp::test::ws()
+        .origin("https://example.com")
+        .protocol("chat.v2")
+        .protocol("chat.v1")
+        .handshake(route)
+        .await
+        .expect("handshake");
+    assert_eq!(client.protocol(), Some("chat.v1"));
+    let res = client.handshake_response();
+    assert_eq!(res.status(), 101);
+    assert_eq!(res.headers()["upgrade"], "websocket");
+
+    let err = warp::test::ws()
+        .origin("https://evil.example.com")
+        .protocol("chat.v1")
+        .handshake(route)
+        .await
+        .expect_err("handshake from another origin should fail");
+    assert_eq!(err.response().unwrap().status(), 400);
+}
+
 // Websocket filter that echoes all messages back.
 fn ws_echo() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Copy {
     warp::ws().map(|ws: warp::ws::Ws| {
@@ -0,0 +1,143 @@
+//! Derive macros for [warp](https://docs.rs/warp).
+//!
+//! Use them through warp's `"derive"` feature, instead of depending on this
+//! crate directly.
+
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr};
+
+/// Derives `warp::path::UriPath`, matching the path in the `uri_path`
+/// attribute and extracting its parameters into the fields of the struct.
+///
+/// See `warp::path::typed` for an example.
+#[proc_macro_derive(UriPath, attributes(uri_path))]
+pub fn derive_uri_path(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum Segment {
+    Literal(String),
+    Param(Ident),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "UriPath can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "UriPath can only be derived for structs",
+            ))
+        }
+    };
+
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("uri_path"))
+        .ok_or_else(|| {
+            Error::new(
+                Span::call_site(),
+                "UriPath needs a path, such as #[uri_path(\"/users/:id\")]",
+            )
+        })?;
+    let template: LitStr = attr.parse_args()?;
+    let segments = parse_template(&template)?;
+
+    let mut filters = Vec::new();
+    let mut params = Vec::new();
+    for segment in &segments {
+        match *segment {
+            Segment::Literal(ref literal) => {
+                filters.push(quote!(::warp::path(#literal)));
+            }
+            Segment::Param(ref name) => {
+                let field = fields
+                    .iter()
+                    .find(|field| field.ident.as_ref() == Some(name))
+                    .ok_or_else(|| {
+                        Error::new_spanned(
+                            &template,
+                            format!("path parameter `{}` isn't a field of the struct", name),
+                        )
+                    })?;
+                let ty = &field.ty;
+                filters.push(quote!(::warp::path::param::<#ty>()));
+                params.push(name);
+            }
+        }
+    }
+    for field in fields {
+        let name = field.ident.as_ref().expect("named fields have names");
+        if !params.contains(&name) {
+            return Err(Error::new_spanned(
+                field,
+                format!("field `{}` isn't a parameter of the path", name),
+            ));
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::warp::path::UriPath for #ident #ty_generics #where_clause {
+            fn filter() -> ::warp::filters::BoxedFilter<(Self,)> {
+                use ::warp::Filter as _;
+                ::warp::any()
+                    #(.and(#filters))*
+                    .and(::warp::path::end())
+                    .map(|#(#params),*| #ident { #(#params),* })
+                    .boxed()
+            }
+        }
+    })
+}
+
+// Splits a path like `/users/:user_id` or `/users/{user_id}` into segments.
+fn parse_template(template: &LitStr) -> syn::Result<Vec<Segment>> {
+    let value = template.value();
+    let path = value
+        .strip_prefix('/')
+        .ok_or_else(|| Error::new_spanned(template, "path must start with '/'"))?;
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    path.split('/')
+        .map(|segment| {
+            let name = segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('{')?.strip_suffix('}'));
+            match name {
+                Some(name) => syn::parse_str::<Ident>(name)
+                    .map(Segment::Param)
+                    .map_err(|_| {
+                        Error::new_spanned(
+                            template,
+                            format!("`{}` isn't a valid parameter name", name),
+                        )
+                    }),
+                None if segment.is_empty() => {
+                    Err(Error::new_spanned(template, "path has an empty segment"))
+                }
+                None => Ok(Segment::Literal(segment.to_owned())),
+            }
+        })
+        .collect()
+}